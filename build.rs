@@ -1,5 +1,167 @@
 // build.rs
+//
+// Membangkitkan konstanta protokol (token kamus, tipe stub, kode status) dari
+// proto/constants.json ke $OUT_DIR/protocol_constants.rs. Data divalidasi di
+// sini agar kesalahan edit langsung menggagalkan build.
+//
+// Tipe protobuf pesan dibangkitkan dari proto/wa_message.proto dengan prost ke
+// $OUT_DIR/waproto.rs. File .proto di-parse oleh protox, jadi build tidak
+// membutuhkan `protoc`.
+
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const CONSTANTS_FILE: &str = "proto/constants.json";
+const MESSAGE_PROTO: &str = "proto/wa_message.proto";
+
+/// Token 0..3 dicadangkan untuk LIST_EMPTY/STREAM_END dan selalu kosong
+const RESERVED_TOKENS: usize = 3;
+/// Byte 236 ke atas dipakai untuk tag kamus ganda dan tipe list/binary
+const MAX_SINGLE_BYTE_TOKENS: usize = 236;
+/// Tag DICTIONARY_0..=DICTIONARY_3, masing-masing diikuti satu byte indeks
+const DOUBLE_BYTE_DICTIONARIES: usize = 4;
+const MAX_DOUBLE_BYTE_TOKENS: usize = 256;
+
+fn fail(message: String) -> ! {
+    panic!("{}: {}", CONSTANTS_FILE, message);
+}
+
+fn single_byte_tokens(data: &serde_json::Value) -> Vec<String> {
+    let tokens: Vec<String> = data["single_byte_tokens"]
+        .as_array()
+        .unwrap_or_else(|| fail("single_byte_tokens must be an array".to_string()))
+        .iter()
+        .map(|token| token.as_str().map(str::to_string).unwrap_or_else(|| fail(format!("token {} is not a string", token))))
+        .collect();
+
+    if tokens.len() > MAX_SINGLE_BYTE_TOKENS {
+        fail(format!("{} single byte tokens, at most {} allowed", tokens.len(), MAX_SINGLE_BYTE_TOKENS));
+    }
+    if tokens.iter().take(RESERVED_TOKENS).any(|token| !token.is_empty()) {
+        fail(format!("the first {} tokens are reserved and must be empty", RESERVED_TOKENS));
+    }
+    let mut seen = HashSet::new();
+    for token in tokens.iter().skip(RESERVED_TOKENS) {
+        if token.is_empty() || !seen.insert(token) {
+            fail(format!("token {:?} is empty or duplicated", token));
+        }
+    }
+    tokens
+}
+
+/// Kamus boleh kosong atau tidak ditulis; token harus unik di semua tabel,
+/// karena encoder memilih tabel pertama yang memuat token tersebut
+fn double_byte_tokens(data: &serde_json::Value, single: &[String]) -> Vec<Vec<String>> {
+    let dictionaries = match data.get("double_byte_tokens") {
+        None => Vec::new(),
+        Some(value) => value
+            .as_array()
+            .unwrap_or_else(|| fail("double_byte_tokens must be an array".to_string()))
+            .clone(),
+    };
+    if dictionaries.len() > DOUBLE_BYTE_DICTIONARIES {
+        fail(format!("{} double byte dictionaries, at most {} allowed", dictionaries.len(), DOUBLE_BYTE_DICTIONARIES));
+    }
+
+    let mut seen: HashSet<&str> = single.iter().map(String::as_str).collect();
+    let mut tables = vec![Vec::new(); DOUBLE_BYTE_DICTIONARIES];
+    for (index, dictionary) in dictionaries.iter().enumerate() {
+        let tokens = dictionary
+            .as_array()
+            .unwrap_or_else(|| fail(format!("double byte dictionary {} must be an array", index)));
+        if tokens.len() > MAX_DOUBLE_BYTE_TOKENS {
+            fail(format!("dictionary {} has {} tokens, at most {} allowed", index, tokens.len(), MAX_DOUBLE_BYTE_TOKENS));
+        }
+        for token in tokens {
+            let token = token.as_str().unwrap_or_else(|| fail(format!("token {} is not a string", token)));
+            if token.is_empty() || !seen.insert(token) {
+                fail(format!("token {:?} in dictionary {} is empty or duplicated", token, index));
+            }
+            tables[index].push(token.to_string());
+        }
+    }
+    tables
+}
+
+fn numbered_table(data: &serde_json::Value, key: &str, numbers_are_keys: bool) -> Vec<(u32, String)> {
+    let object = data[key].as_object().unwrap_or_else(|| fail(format!("{} must be an object", key)));
+    let mut entries: Vec<(u32, String)> = object
+        .iter()
+        .map(|(name, value)| {
+            let (number, label) = if numbers_are_keys {
+                (name.parse().ok(), value.as_str().map(str::to_string))
+            } else {
+                (value.as_u64().and_then(|n| u32::try_from(n).ok()), Some(name.clone()))
+            };
+            match (number, label) {
+                (Some(number), Some(label)) => (number, label),
+                _ => fail(format!("invalid {} entry {:?}", key, name)),
+            }
+        })
+        .collect();
+    entries.sort();
+
+    let mut seen = HashSet::new();
+    for (number, _) in &entries {
+        if !seen.insert(*number) {
+            fail(format!("duplicate number {} in {}", number, key));
+        }
+    }
+    entries
+}
+
+fn compile_protos() {
+    let descriptors = protox::compile([MESSAGE_PROTO], ["proto"]).unwrap_or_else(|e| panic!("{}: {}", MESSAGE_PROTO, e));
+    prost_build::Config::new()
+        .compile_fds(descriptors)
+        .unwrap_or_else(|e| panic!("{}: {}", MESSAGE_PROTO, e));
+}
+
 fn main() {
-    // Tidak ada konfigurasi build khusus saat ini
     println!("cargo:rerun-if-changed=build.rs");
-}
\ No newline at end of file
+    println!("cargo:rerun-if-changed={}", CONSTANTS_FILE);
+    println!("cargo:rerun-if-changed={}", MESSAGE_PROTO);
+    compile_protos();
+
+    let raw = fs::read_to_string(CONSTANTS_FILE).unwrap_or_else(|e| fail(e.to_string()));
+    let data: serde_json::Value = serde_json::from_str(&raw).unwrap_or_else(|e| fail(e.to_string()));
+
+    let mut out = String::new();
+    let single = single_byte_tokens(&data);
+    writeln!(out, "pub const SINGLE_BYTE_TOKENS: &[&str] = &[").unwrap();
+    for token in &single {
+        writeln!(out, "    {:?},", token).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    writeln!(out, "pub const DOUBLE_BYTE_TOKENS: [&[&str]; {}] = [", DOUBLE_BYTE_DICTIONARIES).unwrap();
+    for dictionary in double_byte_tokens(&data, &single) {
+        writeln!(out, "    &[").unwrap();
+        for token in dictionary {
+            writeln!(out, "        {:?},", token).unwrap();
+        }
+        writeln!(out, "    ],").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    writeln!(out, "pub const STUB_TYPES: &[(u32, &str)] = &[").unwrap();
+    for (number, name) in numbered_table(&data, "stub_types", false) {
+        writeln!(out, "    ({}, {:?}),", number, name).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    writeln!(out, "pub const STATUS_CODES: &[(u16, &str)] = &[").unwrap();
+    for (code, description) in numbered_table(&data, "status_codes", true) {
+        if code > u16::MAX as u32 {
+            fail(format!("status code {} out of range", code));
+        }
+        writeln!(out, "    ({}, {:?}),", code, description).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("protocol_constants.rs");
+    fs::write(dest, out).unwrap();
+}
@@ -0,0 +1,359 @@
+//! Pengaturan akun dalam satu facade
+//!
+//! `WhatsAppClient::account()` mengumpulkan operasi akun (push name, teks
+//! status, foto profil, privasi, pesan sementara default, cek PIN dua langkah)
+//! sehingga mudah ditemukan. Semua getter/setter memblokir sampai server
+//! membalas; jangan dipanggil dari node handler.
+
+use std::time::Duration;
+
+use crate::errors::*;
+use crate::iq;
+use crate::node_protocol::Node;
+use crate::{ephemeral, picture, two_step, utils, Jid, WhatsAppClient};
+
+/// Kategori privasi akun
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivacySetting {
+    LastSeen,
+    Online,
+    ProfilePicture,
+    /// Teks about (kategori `status`); penonton pembaruan status diatur
+    /// lewat `WhatsAppClient::set_status_privacy`
+    Status,
+    ReadReceipts,
+    GroupAdd,
+}
+
+/// Siapa yang boleh melihat/melakukan sesuatu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivacyValue {
+    All,
+    Contacts,
+    /// Kontak kecuali yang dikecualikan
+    ContactBlacklist,
+    None,
+    /// Sama dengan pengaturan terakhir dilihat (khusus `Online`)
+    MatchLastSeen,
+}
+
+impl PrivacySetting {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PrivacySetting::LastSeen => "last",
+            PrivacySetting::Online => "online",
+            PrivacySetting::ProfilePicture => "profile",
+            PrivacySetting::Status => "status",
+            PrivacySetting::ReadReceipts => "readreceipts",
+            PrivacySetting::GroupAdd => "groupadd",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            PrivacySetting::LastSeen,
+            PrivacySetting::Online,
+            PrivacySetting::ProfilePicture,
+            PrivacySetting::Status,
+            PrivacySetting::ReadReceipts,
+            PrivacySetting::GroupAdd,
+        ]
+        .into_iter()
+        .find(|setting| setting.name() == name)
+    }
+
+    /// Apakah `value` berlaku untuk kategori ini
+    pub fn accepts(&self, value: PrivacyValue) -> bool {
+        match self {
+            PrivacySetting::Online => matches!(value, PrivacyValue::All | PrivacyValue::MatchLastSeen),
+            PrivacySetting::ReadReceipts => matches!(value, PrivacyValue::All | PrivacyValue::None),
+            PrivacySetting::GroupAdd => matches!(value, PrivacyValue::All | PrivacyValue::Contacts | PrivacyValue::ContactBlacklist),
+            _ => value != PrivacyValue::MatchLastSeen,
+        }
+    }
+}
+
+/// Semua pengaturan privasi; `None` berarti tidak dikirim server (saat
+/// membaca) atau tidak diubah (saat menulis)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrivacySettings {
+    pub last_seen: Option<PrivacyValue>,
+    pub online: Option<PrivacyValue>,
+    pub profile_picture: Option<PrivacyValue>,
+    pub about: Option<PrivacyValue>,
+    pub read_receipts: Option<PrivacyValue>,
+    pub group_add: Option<PrivacyValue>,
+}
+
+impl PrivacySettings {
+    fn slot(&mut self, setting: PrivacySetting) -> &mut Option<PrivacyValue> {
+        match setting {
+            PrivacySetting::LastSeen => &mut self.last_seen,
+            PrivacySetting::Online => &mut self.online,
+            PrivacySetting::ProfilePicture => &mut self.profile_picture,
+            PrivacySetting::Status => &mut self.about,
+            PrivacySetting::ReadReceipts => &mut self.read_receipts,
+            PrivacySetting::GroupAdd => &mut self.group_add,
+        }
+    }
+
+    pub fn from_entries(entries: Vec<(PrivacySetting, PrivacyValue)>) -> Self {
+        let mut settings = PrivacySettings::default();
+        for (setting, value) in entries {
+            *settings.slot(setting) = Some(value);
+        }
+        settings
+    }
+
+    /// Pasangan kategori dan nilai yang terisi
+    pub fn entries(&self) -> Vec<(PrivacySetting, PrivacyValue)> {
+        [
+            (PrivacySetting::LastSeen, self.last_seen),
+            (PrivacySetting::Online, self.online),
+            (PrivacySetting::ProfilePicture, self.profile_picture),
+            (PrivacySetting::Status, self.about),
+            (PrivacySetting::ReadReceipts, self.read_receipts),
+            (PrivacySetting::GroupAdd, self.group_add),
+        ]
+        .into_iter()
+        .filter_map(|(setting, value)| value.map(|value| (setting, value)))
+        .collect()
+    }
+}
+
+impl PrivacyValue {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PrivacyValue::All => "all",
+            PrivacyValue::Contacts => "contacts",
+            PrivacyValue::ContactBlacklist => "contact_blacklist",
+            PrivacyValue::None => "none",
+            PrivacyValue::MatchLastSeen => "match_last_seen",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            PrivacyValue::All,
+            PrivacyValue::Contacts,
+            PrivacyValue::ContactBlacklist,
+            PrivacyValue::None,
+            PrivacyValue::MatchLastSeen,
+        ]
+        .into_iter()
+        .find(|value| value.name() == name)
+    }
+}
+
+fn iq_node(xmlns: &str, iq_type: &str, to: &str, children: Vec<Node>) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", xmlns)
+        .attr("type", iq_type)
+        .attr("to", to)
+        .children(children)
+}
+
+/// Membaca `<privacy><category name value/></privacy>`; kategori tak dikenal dilewati
+pub fn parse_privacy(response: &Node) -> Vec<(PrivacySetting, PrivacyValue)> {
+    response
+        .get_child("privacy")
+        .map(|privacy| {
+            privacy
+                .get_children()
+                .iter()
+                .filter(|child| child.tag == "category")
+                .filter_map(|category| {
+                    let setting = PrivacySetting::from_name(category.get_attr("name")?)?;
+                    let value = PrivacyValue::from_name(category.get_attr("value")?)?;
+                    Some((setting, value))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Facade pengaturan akun, didapat dari `WhatsAppClient::account`
+pub struct Account<'a> {
+    client: &'a WhatsAppClient,
+}
+
+impl Account<'_> {
+    fn query(&self, node: Node) -> Result<Node> {
+        self.client.query(&node, iq::DEFAULT_QUERY_TIMEOUT)
+    }
+
+    fn own_jid(&self) -> Result<Jid> {
+        self.client.get_own_jid().ok_or_else(|| "Not logged in".into())
+    }
+
+    /// Nama yang terlihat oleh kontak lain
+    pub fn push_name(&self) -> Option<String> {
+        let session = self.client.session.lock().unwrap();
+        session.as_ref().map(|session| session.push_name.clone()).filter(|name| !name.is_empty())
+    }
+
+    pub fn set_push_name(&self, name: &str) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err("Push name must not be empty".into());
+        }
+        self.client.send_node(&Node::new("presence").attr("name", name))?;
+        if let Some(session) = self.client.session.lock().unwrap().as_mut() {
+            session.push_name = name.to_string();
+        }
+        Ok(())
+    }
+
+    /// Teks status ("about") akun ini
+    pub fn status_text(&self) -> Result<Option<String>> {
+        let jid = self.own_jid()?.to_string();
+        let response = self.query(iq_node(
+            "status",
+            "get",
+            "s.whatsapp.net",
+            vec![Node::new("status").children(vec![Node::new("user").attr("jid", &jid)])],
+        ))?;
+        let text = response
+            .get_child("status")
+            .and_then(|status| status.get_children().iter().find(|user| user.get_attr("jid") == Some(jid.as_str())))
+            .and_then(|user| user.get_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+        Ok(text)
+    }
+
+    pub fn set_status_text(&self, text: &str) -> Result<()> {
+        self.query(iq_node("status", "set", "s.whatsapp.net", vec![Node::new("status").bytes(text.as_bytes().to_vec())]))
+            .map(|_| ())
+    }
+
+    /// URL foto profil akun ini; `None` jika belum diatur
+    pub fn profile_picture_url(&self) -> Result<Option<String>> {
+        let jid = self.own_jid()?.to_string();
+        let response = self.query(iq_node(
+            "w:profile:picture",
+            "get",
+            &jid,
+            vec![Node::new("picture").attr("type", "image").attr("query", "url")],
+        ))?;
+        Ok(response.get_child("picture").and_then(|picture| picture.get_attr("url")).map(|url| url.to_string()))
+    }
+
+    /// Mengganti foto profil (lihat `picture::prepare_picture`)
+    pub fn set_profile_picture(&self, image: &[u8]) -> Result<()> {
+        let jid = self.own_jid()?.to_string();
+        let jpeg = picture::prepare_picture(image)?;
+        self.query(iq_node("w:profile:picture", "set", &jid, vec![Node::new("picture").attr("type", "image").bytes(jpeg)]))
+            .map(|_| ())
+    }
+
+    pub fn remove_profile_picture(&self) -> Result<()> {
+        let jid = self.own_jid()?.to_string();
+        self.query(iq_node("w:profile:picture", "set", &jid, Vec::new())).map(|_| ())
+    }
+
+    /// Semua pengaturan privasi akun
+    pub fn privacy(&self) -> Result<Vec<(PrivacySetting, PrivacyValue)>> {
+        let response = self.query(iq_node("privacy", "get", "s.whatsapp.net", vec![Node::new("privacy")]))?;
+        Ok(parse_privacy(&response))
+    }
+
+    pub fn set_privacy(&self, setting: PrivacySetting, value: PrivacyValue) -> Result<()> {
+        self.set_privacy_entries(&[(setting, value)])
+    }
+
+    /// Semua pengaturan privasi dalam bentuk terstruktur
+    pub fn privacy_settings(&self) -> Result<PrivacySettings> {
+        self.privacy().map(PrivacySettings::from_entries)
+    }
+
+    /// Mengubah semua kategori yang terisi di `settings` dalam satu permintaan
+    pub fn set_privacy_settings(&self, settings: &PrivacySettings) -> Result<()> {
+        let entries = settings.entries();
+        if entries.is_empty() {
+            return Ok(());
+        }
+        self.set_privacy_entries(&entries)
+    }
+
+    fn set_privacy_entries(&self, entries: &[(PrivacySetting, PrivacyValue)]) -> Result<()> {
+        if let Some((setting, value)) = entries.iter().find(|(setting, value)| !setting.accepts(*value)) {
+            return Err(format!("{} is not a valid value for the {} setting", value.name(), setting.name()).into());
+        }
+        let categories = entries
+            .iter()
+            .map(|(setting, value)| Node::new("category").attr("name", setting.name()).attr("value", value.name()))
+            .collect();
+        self.query(iq_node("privacy", "set", "s.whatsapp.net", vec![Node::new("privacy").children(categories)]))
+            .map(|_| ())
+    }
+
+    /// Durasi pesan sementara default untuk chat baru
+    pub fn default_ephemeral(&self) -> Result<Option<Duration>> {
+        let response = self.query(iq_node("disappearing_mode", "get", "s.whatsapp.net", Vec::new()))?;
+        let secs = response
+            .get_child("disappearing_mode")
+            .and_then(|mode| mode.get_attr("duration"))
+            .and_then(|duration| duration.parse::<u64>().ok())
+            .unwrap_or(0);
+        Ok(if secs == 0 { None } else { Some(Duration::from_secs(secs)) })
+    }
+
+    /// Mengatur pesan sementara default; `None` menonaktifkan
+    pub fn set_default_ephemeral(&self, duration: Option<Duration>) -> Result<()> {
+        let expiration = ephemeral::expiration_secs(duration)?;
+        let mode = Node::new("disappearing_mode").attr("duration", &expiration.to_string());
+        self.query(iq_node("disappearing_mode", "set", "s.whatsapp.net", vec![mode])).map(|_| ())
+    }
+
+    /// Apakah verifikasi dua langkah (PIN) aktif
+    pub fn has_two_step_pin(&self) -> Result<bool> {
+        let response = self.query(iq_node("urn:xmpp:whatsapp:account", "get", "s.whatsapp.net", vec![Node::new("2fa")]))?;
+        let enabled = two_step::parse_two_fa(&response).unwrap_or(false);
+        if let Some(event) = self.client.two_step.lock().unwrap().set_enabled(enabled) {
+            self.client.event_tx.send(event).ok();
+        }
+        Ok(enabled)
+    }
+}
+
+impl WhatsAppClient {
+    /// Facade untuk pengaturan akun
+    pub fn account(&self) -> Account<'_> {
+        Account { client: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_privacy_skips_unknown() {
+        let response = Node::new("iq").children(vec![Node::new("privacy").children(vec![
+            Node::new("category").attr("name", "last").attr("value", "contacts"),
+            Node::new("category").attr("name", "calladd").attr("value", "all"),
+            Node::new("category").attr("name", "online").attr("value", "match_last_seen"),
+        ])]);
+
+        assert_eq!(
+            parse_privacy(&response),
+            vec![
+                (PrivacySetting::LastSeen, PrivacyValue::Contacts),
+                (PrivacySetting::Online, PrivacyValue::MatchLastSeen),
+            ]
+        );
+
+        let settings = PrivacySettings::from_entries(parse_privacy(&response));
+        assert_eq!(settings.last_seen, Some(PrivacyValue::Contacts));
+        assert_eq!(settings.read_receipts, None);
+        assert_eq!(settings.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_values_validated_per_setting() {
+        assert!(PrivacySetting::Online.accepts(PrivacyValue::MatchLastSeen));
+        assert!(!PrivacySetting::LastSeen.accepts(PrivacyValue::MatchLastSeen));
+        assert!(!PrivacySetting::ReadReceipts.accepts(PrivacyValue::Contacts));
+        assert!(!PrivacySetting::GroupAdd.accepts(PrivacyValue::None));
+    }
+}
@@ -0,0 +1,133 @@
+//! Impor kontak ke buku alamat ponsel
+//!
+//! Kontak ditulis sebagai aksi app state `contact` di koleksi
+//! `critical_unblock_low`, sehingga ponsel dan perangkat tertaut lain ikut
+//! menyimpannya. Impor besar dipecah per patch (`chats::MAX_MUTATIONS_PER_PATCH`).
+//! Kontak yang sudah ada dengan nama berbeda diperlakukan sesuai
+//! `ConflictPolicy`; kontak dengan nama sama tidak dikirim ulang.
+
+use std::collections::HashMap;
+
+use crate::app_state::{AppStateStore, Collection, Mutation};
+use crate::errors::*;
+use crate::initial_sync::CONTACT_ACTION;
+use crate::{Jid, WhatsAppClient};
+
+/// Kontak yang akan diimpor
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactEntry {
+    pub jid: Jid,
+    pub full_name: String,
+    pub first_name: Option<String>,
+}
+
+impl ContactEntry {
+    pub fn new(jid: Jid, full_name: &str) -> Self {
+        ContactEntry {
+            jid,
+            full_name: full_name.to_string(),
+            first_name: None,
+        }
+    }
+}
+
+/// Perlakuan untuk kontak yang sudah tersimpan dengan nama lain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictPolicy {
+    /// Nama di ponsel dipertahankan
+    KeepExisting,
+    /// Nama di ponsel diganti
+    Overwrite,
+}
+
+/// Ringkasan hasil impor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Kontak yang tidak diubah karena `ConflictPolicy::KeepExisting`
+    pub conflicts: usize,
+}
+
+fn contact_index(jid: &Jid) -> Vec<String> {
+    vec![CONTACT_ACTION.to_string(), jid.to_string()]
+}
+
+/// Menyusun mutasi untuk `entries`. Entry ganda untuk JID yang sama memakai
+/// yang terakhir.
+pub fn plan_import(app_state: &AppStateStore, entries: &[ContactEntry], policy: ConflictPolicy) -> Result<(Vec<Mutation>, ImportSummary)> {
+    if let Some(entry) = entries.iter().find(|entry| entry.jid.is_group() || entry.full_name.trim().is_empty()) {
+        return Err(format!("Invalid contact entry for {}: contacts need a user JID and a name", entry.jid.to_string()).into());
+    }
+
+    let mut latest: HashMap<String, &ContactEntry> = HashMap::new();
+    let mut order = Vec::new();
+    for entry in entries {
+        if latest.insert(entry.jid.to_string(), entry).is_none() {
+            order.push(entry.jid.to_string());
+        }
+    }
+
+    let mut summary = ImportSummary::default();
+    let mut mutations = Vec::new();
+    for jid in order {
+        let entry = latest[&jid];
+        let index = contact_index(&entry.jid);
+        let existing = app_state.get(Collection::CriticalUnblockLow, &index);
+        match existing.and_then(|mutation| mutation.value["fullName"].as_str()) {
+            Some(name) if name == entry.full_name => {
+                summary.unchanged += 1;
+                continue;
+            }
+            Some(_) if policy == ConflictPolicy::KeepExisting => {
+                summary.conflicts += 1;
+                continue;
+            }
+            Some(_) => summary.updated += 1,
+            None => summary.added += 1,
+        }
+        let first_name = entry.first_name.clone().unwrap_or_else(|| entry.full_name.split_whitespace().next().unwrap_or_default().to_string());
+        mutations.push(Mutation::set(index, serde_json::json!({ "fullName": entry.full_name, "firstName": first_name })));
+    }
+    Ok((mutations, summary))
+}
+
+impl WhatsAppClient {
+    /// Menyimpan banyak kontak ke buku alamat ponsel sekaligus
+    pub fn import_contacts(&self, entries: &[ContactEntry], policy: ConflictPolicy) -> Result<ImportSummary> {
+        let (mutations, summary) = plan_import(&self.app_state.lock().unwrap(), entries, policy)?;
+        self.push_batched(Collection::CriticalUnblockLow, mutations)?;
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(number: &str) -> Jid {
+        Jid::user(number)
+    }
+
+    #[test]
+    fn test_plan_import_resolves_conflicts() {
+        let mut app_state = AppStateStore::new();
+        app_state.apply(Collection::CriticalUnblockLow, &Mutation::set(contact_index(&user("1")), serde_json::json!({ "fullName": "Budi" })));
+        app_state.apply(Collection::CriticalUnblockLow, &Mutation::set(contact_index(&user("2")), serde_json::json!({ "fullName": "Sari" })));
+        let entries = vec![
+            ContactEntry::new(user("1"), "Budi"),
+            ContactEntry::new(user("2"), "Sari Dewi"),
+            ContactEntry::new(user("3"), "Andi"),
+            ContactEntry::new(user("3"), "Andi Wijaya"),
+        ];
+
+        let (mutations, summary) = plan_import(&app_state, &entries, ConflictPolicy::KeepExisting).unwrap();
+        assert_eq!(summary, ImportSummary { added: 1, updated: 0, unchanged: 1, conflicts: 1 });
+        assert_eq!(mutations[0].value["fullName"], "Andi Wijaya");
+        assert_eq!(mutations[0].value["firstName"], "Andi");
+
+        let (mutations, summary) = plan_import(&app_state, &entries, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!((mutations.len(), summary.updated), (2, 1));
+    }
+}
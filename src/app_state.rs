@@ -0,0 +1,570 @@
+//! Mutasi app state (syncd) yang disinkronkan ke semua perangkat akun
+//!
+//! Aksi seperti arsip chat, bisukan, bintang pesan, dan stiker favorit dikirim
+//! sebagai patch berisi mutasi `set`/`remove` ke sebuah koleksi. Setiap mutasi
+//! diidentifikasi oleh index (nama aksi diikuti argumennya, misalnya
+//! `["archive", "628xxx@s.whatsapp.net"]`). Salinan lokal mutasi terakhir per
+//! index disimpan agar aplikasi bisa membaca state tanpa sinkronisasi penuh.
+//!
+//! Saat terhubung, client menyinkronkan koleksi yang dipilih lewat
+//! `WhatsAppClientBuilder::with_app_state_collections` (default semua). Koleksi
+//! yang dilewati bisa disinkronkan kapan saja dengan
+//! `WhatsAppClient::sync_collection`.
+//!
+//! Snapshot dan patch dienkripsi dan diverifikasi MAC serta LT-hash-nya
+//! (lihat modul `syncd`). Patch yang gagal diverifikasi tidak diterapkan sama
+//! sekali dan koleksinya disinkronkan ulang dari snapshot. Koleksi yang
+//! membutuhkan kunci sinkronisasi yang belum dibagikan ponsel ditunda sampai
+//! kuncinya datang.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::*;
+use crate::messages::WebMessageInfo;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::syncd::{self, CollectionHash, MutationKeys, SyncdMutation, SyncdPatch, SyncdSnapshot};
+use crate::{utils, Event, WhatsAppClient};
+
+/// Koleksi app state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Collection {
+    Regular,
+    RegularLow,
+    RegularHigh,
+    CriticalBlock,
+    CriticalUnblockLow,
+}
+
+/// Semua koleksi, urutan sinkronisasi default
+pub const ALL_COLLECTIONS: [Collection; 5] = [
+    Collection::CriticalBlock,
+    Collection::CriticalUnblockLow,
+    Collection::RegularHigh,
+    Collection::Regular,
+    Collection::RegularLow,
+];
+
+impl Collection {
+    pub fn from_name(name: &str) -> Option<Collection> {
+        ALL_COLLECTIONS.iter().copied().find(|collection| collection.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Collection::Regular => "regular",
+            Collection::RegularLow => "regular_low",
+            Collection::RegularHigh => "regular_high",
+            Collection::CriticalBlock => "critical_block",
+            Collection::CriticalUnblockLow => "critical_unblock_low",
+        }
+    }
+}
+
+/// Jenis operasi mutasi
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MutationOperation {
+    Set,
+    Remove,
+}
+
+/// Satu mutasi app state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mutation {
+    pub operation: MutationOperation,
+    pub index: Vec<String>,
+    pub value: serde_json::Value,
+    pub timestamp: i64,
+}
+
+impl Mutation {
+    pub fn set(index: Vec<String>, value: serde_json::Value) -> Self {
+        Mutation {
+            operation: MutationOperation::Set,
+            index,
+            value,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    pub fn remove(index: Vec<String>) -> Self {
+        Mutation {
+            operation: MutationOperation::Remove,
+            index,
+            value: serde_json::Value::Null,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Nama aksi (elemen pertama index)
+    pub fn action(&self) -> &str {
+        self.index.first().map(|action| action.as_str()).unwrap_or_default()
+    }
+}
+
+/// Hasil menerapkan balasan sinkronisasi
+#[derive(Debug, Default)]
+pub struct SyncOutcome {
+    pub synced: Vec<Collection>,
+    /// Koleksi yang masih punya patch di server (`has_more_patches`)
+    pub has_more: Vec<Collection>,
+    /// Koleksi yang gagal diverifikasi dan perlu snapshot ulang
+    pub failed: Vec<(Collection, String)>,
+    /// Koleksi yang menunggu kunci sinkronisasi
+    pub blocked: Vec<Collection>,
+    /// Mutasi dari patch perangkat lain (tidak termasuk isi snapshot)
+    pub remote_mutations: Vec<(Collection, Mutation)>,
+}
+
+/// Patch keluar yang menunggu konfirmasi server (lihat `AppStateStore::commit_patch`)
+#[derive(Debug)]
+pub struct PendingPatch {
+    collection: Collection,
+    version: u64,
+    hash: CollectionHash,
+    mutations: Vec<Mutation>,
+}
+
+/// Versi koleksi, kunci sinkronisasi, LT-hash, dan salinan lokal mutasi
+/// terakhir per index
+#[derive(Default)]
+pub struct AppStateStore {
+    versions: HashMap<Collection, u64>,
+    entries: HashMap<(Collection, Vec<String>), Mutation>,
+    /// key id -> key data
+    keys: HashMap<Vec<u8>, Vec<u8>>,
+    /// Kunci terbaru, dipakai untuk patch keluar
+    latest_key: Option<Vec<u8>>,
+    hashes: HashMap<Collection, CollectionHash>,
+    blocked: HashSet<Collection>,
+    /// Id IQ sinkronisasi yang belum dibalas
+    pending_syncs: HashSet<String>,
+}
+
+impl AppStateStore {
+    pub fn new() -> Self {
+        AppStateStore::default()
+    }
+
+    pub fn version(&self, collection: Collection) -> u64 {
+        self.versions.get(&collection).copied().unwrap_or(0)
+    }
+
+    /// Menyimpan kunci sinkronisasi dari ponsel; mengembalikan koleksi yang
+    /// sebelumnya tertunda karena kunci belum ada
+    pub fn add_sync_key(&mut self, key_id: &[u8], key_data: &[u8]) -> Vec<Collection> {
+        self.keys.insert(key_id.to_vec(), key_data.to_vec());
+        self.latest_key = Some(key_id.to_vec());
+        self.blocked.drain().collect()
+    }
+
+    fn mutation_keys(&mut self, collection: Collection, key_id: &[u8]) -> Result<MutationKeys> {
+        match self.keys.get(key_id) {
+            Some(key_data) => MutationKeys::expand(key_data),
+            None => {
+                self.blocked.insert(collection);
+                Err(Error {
+                    kind: ErrorKind::CryptoError(format!("Missing app state sync key {}", base64::encode(key_id))),
+                })
+            }
+        }
+    }
+
+    /// Melupakan versi dan LT-hash koleksi agar sinkronisasi berikutnya meminta snapshot
+    pub fn reset(&mut self, collection: Collection) {
+        self.versions.remove(&collection);
+        self.hashes.remove(&collection);
+    }
+
+    /// Menerapkan mutasi ke salinan lokal; mutasi yang lebih lama dari entry yang ada diabaikan
+    pub fn apply(&mut self, collection: Collection, mutation: &Mutation) {
+        let key = (collection, mutation.index.clone());
+        if let Some(existing) = self.entries.get(&key) {
+            if existing.timestamp > mutation.timestamp {
+                return;
+            }
+        }
+        match mutation.operation {
+            MutationOperation::Set => {
+                self.entries.insert(key, mutation.clone());
+            }
+            MutationOperation::Remove => {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    /// Semua entry aktif untuk aksi `action` di koleksi
+    pub fn entries(&self, collection: Collection, action: &str) -> Vec<&Mutation> {
+        self.entries
+            .iter()
+            .filter(|((entry_collection, _), mutation)| *entry_collection == collection && mutation.action() == action)
+            .map(|(_, mutation)| mutation)
+            .collect()
+    }
+
+    /// Entry untuk index tertentu
+    pub fn get(&self, collection: Collection, index: &[String]) -> Option<&Mutation> {
+        self.entries.get(&(collection, index.to_vec()))
+    }
+
+    /// IQ sinkronisasi untuk koleksi; snapshot diminta jika belum pernah sinkron
+    pub fn sync_node(&mut self, collections: &[Collection]) -> Result<Node> {
+        if collections.is_empty() {
+            return Err("App state sync requires at least one collection".into());
+        }
+
+        let children = collections
+            .iter()
+            .map(|collection| {
+                let version = self.version(*collection);
+                Node::new("collection")
+                    .attr("name", collection.name())
+                    .attr("version", &version.to_string())
+                    .attr("return_snapshot", if version == 0 { "true" } else { "false" })
+            })
+            .collect();
+
+        let id = utils::generate_message_id();
+        self.pending_syncs.insert(id.clone());
+        Ok(Node::new("iq")
+            .attr("id", &id)
+            .attr("xmlns", "w:sync:app:state")
+            .attr("type", "set")
+            .attr("to", "s.whatsapp.net")
+            .children(vec![Node::new("sync").children(children)]))
+    }
+
+    /// `true` jika `id` adalah IQ sinkronisasi yang menunggu balasan; id
+    /// dilupakan setelah dicocokkan
+    pub fn take_pending_sync(&mut self, id: &str) -> bool {
+        self.pending_syncs.remove(id)
+    }
+
+    /// Menerapkan snapshot dan patch dari balasan sinkronisasi
+    pub fn apply_sync_response(&mut self, node: &Node) -> SyncOutcome {
+        let mut outcome = SyncOutcome::default();
+        let sync = match node.get_child("sync") {
+            Some(sync) => sync,
+            None => return outcome,
+        };
+
+        for child in sync.get_children().iter().filter(|child| child.tag == "collection") {
+            let collection = match child.get_attr("name").and_then(Collection::from_name) {
+                Some(collection) => collection,
+                None => continue,
+            };
+            match self.apply_collection(collection, child) {
+                Ok(remote) => {
+                    outcome.remote_mutations.extend(remote.into_iter().map(|mutation| (collection, mutation)));
+                    outcome.synced.push(collection);
+                    if child.get_attr("has_more_patches") == Some("true") {
+                        outcome.has_more.push(collection);
+                    }
+                }
+                Err(_) if self.blocked.contains(&collection) => outcome.blocked.push(collection),
+                Err(e) => outcome.failed.push((collection, e.to_string())),
+            }
+        }
+        outcome
+    }
+
+    /// Mendekripsi dan memverifikasi semua isi satu koleksi; state lokal
+    /// hanya diubah jika semuanya valid. Mengembalikan mutasi dari patch.
+    fn apply_collection(&mut self, collection: Collection, child: &Node) -> Result<Vec<Mutation>> {
+        let name = collection.name();
+        let mut version = self.version(collection);
+        let mut hash = self.hashes.get(&collection).cloned().unwrap_or_default();
+        let mut mutations = Vec::new();
+        let mut replace = false;
+
+        if let Some(bytes) = child.get_child("snapshot").and_then(|snapshot| snapshot.get_bytes()) {
+            let snapshot: SyncdSnapshot = parse_json(bytes, "snapshot")?;
+            let keys = self.mutation_keys(collection, &snapshot.key_id)?;
+            let records: Vec<SyncdMutation> = snapshot
+                .records
+                .into_iter()
+                .map(|record| SyncdMutation { operation: MutationOperation::Set, record })
+                .collect();
+            for record in &records {
+                mutations.push(syncd::decrypt_record(&keys, MutationOperation::Set, &record.record)?);
+            }
+            hash = CollectionHash::default().updated(&records)?;
+            syncd::verify_mac(&syncd::snapshot_mac(&keys, hash.lt_hash(), snapshot.version, name), &snapshot.mac, "snapshot")?;
+            version = snapshot.version;
+            replace = true;
+        }
+        let from_snapshot = mutations.len();
+
+        let patches = child.get_child("patches").map(|patches| patches.get_children()).unwrap_or_default();
+        for patch in patches.iter().filter(|patch| patch.tag == "patch") {
+            let patch: SyncdPatch = parse_json(patch.get_bytes().unwrap_or_default(), "patch")?;
+            if patch.version <= version {
+                continue;
+            }
+            let keys = self.mutation_keys(collection, &patch.key_id)?;
+            for mutation in &patch.mutations {
+                mutations.push(syncd::decrypt_record(&keys, mutation.operation, &mutation.record)?);
+            }
+            let next = hash.updated(&patch.mutations)?;
+            syncd::verify_mac(&syncd::snapshot_mac(&keys, next.lt_hash(), patch.version, name), &patch.snapshot_mac, "snapshot")?;
+            let value_macs: Vec<&[u8]> = patch.mutations.iter().map(|mutation| mutation.record.value_mac()).collect();
+            syncd::verify_mac(&syncd::patch_mac(&keys, &patch.snapshot_mac, &value_macs, patch.version, name), &patch.patch_mac, "patch")?;
+            hash = next;
+            version = patch.version;
+        }
+
+        if replace {
+            self.entries.retain(|(entry_collection, _), _| *entry_collection != collection);
+        }
+        for mutation in &mutations {
+            self.apply(collection, mutation);
+        }
+        self.hashes.insert(collection, hash);
+        self.versions.insert(collection, version);
+        Ok(mutations.split_off(from_snapshot))
+    }
+
+    /// Membangun IQ patch terenkripsi untuk mutasi; semua mutasi dikirim dalam
+    /// satu patch. State lokal belum diubah: panggil `commit_patch` setelah
+    /// server menerima patch.
+    pub fn patch_node(&mut self, collection: Collection, mutations: Vec<Mutation>) -> Result<(Node, PendingPatch)> {
+        if mutations.is_empty() {
+            return Err("App state patch requires at least one mutation".into());
+        }
+        let key_id = self.latest_key.clone().ok_or("No app state sync key received from the phone yet")?;
+        let keys = self.mutation_keys(collection, &key_id)?;
+
+        let version = self.version(collection) + 1;
+        let encrypted = mutations
+            .iter()
+            .map(|mutation| syncd::encrypt_mutation(&keys, &key_id, mutation))
+            .collect::<Result<Vec<_>>>()?;
+        let hash = self.hashes.get(&collection).cloned().unwrap_or_default().updated(&encrypted)?;
+        let snapshot_mac = syncd::snapshot_mac(&keys, hash.lt_hash(), version, collection.name());
+        let value_macs: Vec<&[u8]> = encrypted.iter().map(|mutation| mutation.record.value_mac()).collect();
+        let patch_mac = syncd::patch_mac(&keys, &snapshot_mac, &value_macs, version, collection.name());
+
+        let patch = SyncdPatch {
+            version,
+            mutations: encrypted,
+            snapshot_mac,
+            patch_mac,
+            key_id,
+        };
+        let bytes = serde_json::to_vec(&patch).map_err(|e| Error { kind: ErrorKind::InvalidFormat(e.to_string()) })?;
+
+        let node = Node::new("iq")
+            .attr("id", &utils::generate_message_id())
+            .attr("xmlns", "w:sync:app:state")
+            .attr("type", "set")
+            .attr("to", "s.whatsapp.net")
+            .children(vec![Node::new("sync").children(vec![Node::new("collection")
+                .attr("name", collection.name())
+                .attr("version", &(version - 1).to_string())
+                .attr("return_snapshot", "false")
+                .children(vec![Node::new("patch").bytes(bytes)])])]);
+        Ok((node, PendingPatch { collection, version, hash, mutations }))
+    }
+
+    /// Menerapkan patch yang sudah diterima server ke salinan lokal dan
+    /// menaikkan versi koleksi. Jika versi koleksi berubah sejak patch dibuat,
+    /// LT-hash lokal tidak lagi bisa dipercaya: koleksi direset dan `false`
+    /// dikembalikan agar pemanggil menyinkronkannya ulang.
+    pub fn commit_patch(&mut self, patch: PendingPatch) -> bool {
+        for mutation in &patch.mutations {
+            self.apply(patch.collection, mutation);
+        }
+        if self.version(patch.collection) + 1 != patch.version {
+            self.reset(patch.collection);
+            return false;
+        }
+        self.versions.insert(patch.collection, patch.version);
+        self.hashes.insert(patch.collection, patch.hash);
+        true
+    }
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(bytes: &[u8], what: &str) -> Result<T> {
+    serde_json::from_slice(bytes).map_err(|e| Error { kind: ErrorKind::InvalidPayload(format!("Invalid app state {}: {}", what, e)) })
+}
+
+/// Handler `iq type="result"` untuk balasan sinkronisasi app state. Koleksi
+/// yang masih punya patch diminta lagi; koleksi yang gagal diverifikasi
+/// diminta ulang dari snapshot, kecuali snapshot itu sendiri yang gagal.
+pub fn sync_response_handler(store: Arc<Mutex<AppStateStore>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let mut store = store.lock().unwrap();
+        // Balasan IQ lain juga bertipe `result`; hanya balasan untuk IQ sinkronisasi kita yang diproses
+        if !node.get_attr("id").map_or(false, |id| store.take_pending_sync(id)) {
+            return Ok(());
+        }
+        let outcome = store.apply_sync_response(node);
+        for (_, mutation) in &outcome.remote_mutations {
+            if let Some(event) = crate::chats::mutation_event(mutation) {
+                ctx.emit(event);
+            }
+        }
+        for collection in &outcome.synced {
+            ctx.emit(Event::AppStateSynced { collection: *collection });
+        }
+
+        let mut resync = outcome.has_more;
+        for (collection, error) in outcome.failed {
+            ctx.emit(Event::Error(format!("App state sync of {} failed: {}", collection.name(), error)));
+            if store.version(collection) > 0 {
+                store.reset(collection);
+                resync.push(collection);
+            }
+        }
+        if !resync.is_empty() {
+            ctx.send_node(&store.sync_node(&resync)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Handler `message` untuk kunci sinkronisasi yang dibagikan ponsel;
+/// koleksi yang tertunda langsung disinkronkan
+pub fn key_share_handler(store: Arc<Mutex<AppStateStore>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let web_message = match node.get_bytes().and_then(|bytes| WebMessageInfo::from_bytes(bytes).ok()) {
+            Some(web_message) if web_message.key.from_me => web_message,
+            _ => return Ok(()),
+        };
+        let share = match web_message
+            .message
+            .as_ref()
+            .and_then(|message| message.protocol_message.as_ref())
+            .and_then(|protocol| protocol.app_state_sync_key_share.as_ref())
+        {
+            Some(share) => share,
+            None => return Ok(()),
+        };
+
+        let mut store = store.lock().unwrap();
+        let mut pending = Vec::new();
+        for key in &share.keys {
+            if let (Some(id), Some(data)) = (key.key_id.as_ref(), key.key_data.as_ref()) {
+                pending.extend(store.add_sync_key(&id.key_id, &data.key_data));
+            }
+        }
+        if !pending.is_empty() {
+            ctx.send_node(&store.sync_node(&pending)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Menyinkronkan satu koleksi sekarang, mis. koleksi yang dilewati saat terhubung
+    pub fn sync_collection(&self, collection: Collection) -> Result<()> {
+        let node = self.app_state.lock().unwrap().sync_node(&[collection])?;
+        self.send_node(&node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_ID: &[u8] = b"key-1";
+
+    fn store_with_key() -> AppStateStore {
+        let mut store = AppStateStore::new();
+        store.add_sync_key(KEY_ID, &[9u8; 32]);
+        store
+    }
+
+    fn sync_response(collection: &str, patches: Vec<Node>) -> Node {
+        Node::new("iq").attr("type", "result").children(vec![Node::new("sync").children(vec![Node::new("collection")
+            .attr("name", collection)
+            .children(vec![Node::new("patches").children(patches)])])])
+    }
+
+    /// Patch dari perangkat lain: dibangun oleh store lain dengan kunci yang sama
+    fn remote_patch(remote: &mut AppStateStore, mutations: Vec<Mutation>) -> Node {
+        let (node, pending) = remote.patch_node(Collection::Regular, mutations).unwrap();
+        assert!(remote.commit_patch(pending));
+        let patch = node.get_child("sync").and_then(|sync| sync.get_child("collection")).and_then(|c| c.get_child("patch")).unwrap();
+        patch.clone()
+    }
+
+    #[test]
+    fn test_patch_applies_locally_and_bumps_version() {
+        let mut store = AppStateStore::new();
+        let index = vec!["archive".to_string(), "628111@s.whatsapp.net".to_string()];
+        let archive = || vec![Mutation::set(index.clone(), serde_json::json!({ "archived": true }))];
+        assert!(store.patch_node(Collection::RegularLow, archive()).is_err());
+
+        let mut store = store_with_key();
+        let (node, pending) = store.patch_node(Collection::RegularLow, archive()).unwrap();
+        assert_eq!(node.get_attr("xmlns"), Some("w:sync:app:state"));
+        // Belum diterapkan sebelum server menerima patch
+        assert_eq!(store.version(Collection::RegularLow), 0);
+        assert!(store.entries(Collection::RegularLow, "archive").is_empty());
+        assert!(store.commit_patch(pending));
+        assert_eq!(store.version(Collection::RegularLow), 1);
+        assert_eq!(store.entries(Collection::RegularLow, "archive").len(), 1);
+
+        // Patch yang ditolak server cukup dibuang
+        drop(store.patch_node(Collection::RegularLow, vec![Mutation::remove(index.clone())]).unwrap());
+        assert!(store.get(Collection::RegularLow, &index).is_some());
+
+        // Dua patch dari versi yang sama: yang kedua memaksa sinkronisasi ulang
+        let (_, first) = store.patch_node(Collection::RegularLow, vec![Mutation::remove(index.clone())]).unwrap();
+        let (_, second) = store.patch_node(Collection::RegularLow, archive()).unwrap();
+        assert!(store.commit_patch(first));
+        assert!(store.get(Collection::RegularLow, &index).is_none());
+        assert_eq!(store.version(Collection::RegularLow), 2);
+        assert!(!store.commit_patch(second));
+        assert_eq!(store.version(Collection::RegularLow), 0);
+    }
+
+    #[test]
+    fn test_sync_response_verifies_and_applies_patches() {
+        let mut store = store_with_key();
+        let sync = store.sync_node(&[Collection::Regular]).unwrap();
+        let requested = sync.get_child("sync").and_then(|sync| sync.get_child("collection")).unwrap();
+        assert_eq!(requested.get_attr("return_snapshot"), Some("true"));
+        // Id balasan dicocokkan sekali saja
+        assert!(!store.take_pending_sync("other-iq"));
+        assert!(store.take_pending_sync(sync.get_attr("id").unwrap()));
+        assert!(!store.take_pending_sync(sync.get_attr("id").unwrap()));
+
+        let mut remote = store_with_key();
+        let star = remote_patch(&mut remote, vec![Mutation::set(vec!["star".to_string(), "m1".to_string()], serde_json::json!(true))]);
+        let unstar = remote_patch(&mut remote, vec![Mutation::remove(vec!["star".to_string(), "m1".to_string()])]);
+
+        let outcome = store.apply_sync_response(&sync_response("regular", vec![star]));
+        assert_eq!(outcome.synced, vec![Collection::Regular]);
+        assert_eq!(store.version(Collection::Regular), 1);
+        assert_eq!(store.entries(Collection::Regular, "star").len(), 1);
+
+        // Patch yang diubah di tengah jalan ditolak seluruhnya
+        let mut tampered: SyncdPatch = serde_json::from_slice(unstar.get_bytes().unwrap()).unwrap();
+        tampered.version = 3;
+        let tampered = Node::new("patch").bytes(serde_json::to_vec(&tampered).unwrap());
+        let outcome = store.apply_sync_response(&sync_response("regular", vec![tampered]));
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(store.version(Collection::Regular), 1);
+
+        store.apply_sync_response(&sync_response("regular", vec![unstar]));
+        assert_eq!(store.version(Collection::Regular), 2);
+        assert!(store.entries(Collection::Regular, "star").is_empty());
+    }
+
+    #[test]
+    fn test_missing_key_blocks_collection_until_shared() {
+        let mut remote = store_with_key();
+        let star = remote_patch(&mut remote, vec![Mutation::set(vec!["star".to_string(), "m1".to_string()], serde_json::json!(true))]);
+
+        let mut store = AppStateStore::new();
+        let outcome = store.apply_sync_response(&sync_response("regular", vec![star]));
+        assert_eq!(outcome.blocked, vec![Collection::Regular]);
+        assert_eq!(store.add_sync_key(KEY_ID, &[9u8; 32]), vec![Collection::Regular]);
+    }
+}
@@ -0,0 +1,132 @@
+//! Kunci backup terenkripsi (end-to-end encrypted backup)
+//!
+//! Hanya tersedia dengan fitur `backup-keys`. Jika perangkat utama membagikan
+//! kunci backup lewat app state (aksi `backupKey`), alat arsip bisa memakai
+//! kunci tersebut untuk mendekripsi file backup `.crypt15` yang diberikan
+//! pengguna. Format kunci sama dengan kunci 64 digit hex yang ditampilkan
+//! aplikasi WhatsApp.
+//!
+//! Parsing header protobuf file backup dan dekompresi isinya (zlib) berada di
+//! luar cakupan modul ini; pemanggil menyerahkan IV dan payload terenkripsi.
+
+use openssl::symm::{decrypt_aead, Cipher};
+use ring::hkdf;
+
+use crate::app_state::Collection;
+use crate::errors::*;
+use crate::WhatsAppClient;
+
+/// Nama aksi app state yang membawa kunci backup
+pub const BACKUP_KEY_ACTION: &str = "backupKey";
+
+/// Panjang tag GCM di akhir payload
+pub const GCM_TAG_LENGTH: usize = 16;
+
+const BACKUP_ENCRYPTION_INFO: &[u8] = b"backup encryption";
+
+fn crypto_error(msg: &str) -> Error {
+    Error { kind: ErrorKind::CryptoError(msg.to_string()) }
+}
+
+/// Kunci root backup (32 byte)
+#[derive(Clone, PartialEq)]
+pub struct BackupKey {
+    root: [u8; 32],
+}
+
+impl std::fmt::Debug for BackupKey {
+    /// Kunci tidak pernah ditulis ke log
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("BackupKey(..)")
+    }
+}
+
+impl BackupKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 32 {
+            return Err(crypto_error("Backup key must be 32 bytes"));
+        }
+        let mut root = [0u8; 32];
+        root.copy_from_slice(bytes);
+        Ok(BackupKey { root })
+    }
+
+    /// Dari 64 digit hex (spasi diabaikan), seperti yang ditampilkan aplikasi
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let digits: Vec<u8> = hex
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| crypto_error("Backup key contains non-hex characters"))?;
+        if digits.len() != 64 {
+            return Err(crypto_error("Backup key must be 64 hex digits"));
+        }
+        let bytes: Vec<u8> = digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect();
+        BackupKey::from_bytes(&bytes)
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.root.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Kunci AES-256 untuk isi backup: HKDF-SHA256(salt nol, info "backup encryption")
+    pub fn encryption_key(&self) -> Result<[u8; 32]> {
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &[0u8; 32]).extract(&self.root);
+        let info = [BACKUP_ENCRYPTION_INFO];
+        let okm = prk.expand(&info, hkdf::HKDF_SHA256).map_err(|_| crypto_error("HKDF expand failed"))?;
+        let mut key = [0u8; 32];
+        okm.fill(&mut key).map_err(|_| crypto_error("HKDF fill failed"))?;
+        Ok(key)
+    }
+
+    /// Mendekripsi payload backup (ciphertext diikuti tag GCM 16 byte).
+    /// Hasilnya masih terkompresi zlib.
+    pub fn decrypt_payload(&self, iv: &[u8], payload: &[u8]) -> Result<Vec<u8>> {
+        if payload.len() < GCM_TAG_LENGTH {
+            return Err(crypto_error("Backup payload is too short"));
+        }
+        let (ciphertext, tag) = payload.split_at(payload.len() - GCM_TAG_LENGTH);
+        decrypt_aead(Cipher::aes_256_gcm(), &self.encryption_key()?, Some(iv), &[], ciphertext, tag)
+            .map_err(|_| crypto_error("Failed to decrypt backup (wrong key or corrupted file)"))
+    }
+}
+
+impl WhatsAppClient {
+    /// Kunci backup yang dibagikan perangkat utama lewat app state, jika ada
+    pub fn backup_key(&self) -> Option<BackupKey> {
+        let app_state = self.app_state.lock().unwrap();
+        let entry = app_state.entries(Collection::Regular, BACKUP_KEY_ACTION).into_iter().max_by_key(|entry| entry.timestamp)?;
+        let encoded = entry.value.get("rootKey")?.as_str()?;
+        let bytes = base64::decode(encoded).ok()?;
+        BackupKey::from_bytes(&bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::symm::encrypt_aead;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let hex = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+        let key = BackupKey::from_hex(hex).unwrap();
+        assert_eq!(key.to_hex(), hex);
+        assert!(BackupKey::from_hex("abc").is_err());
+        assert_eq!(format!("{:?}", key), "BackupKey(..)");
+    }
+
+    #[test]
+    fn test_decrypt_payload() {
+        let key = BackupKey::from_bytes(&[9u8; 32]).unwrap();
+        let iv = [3u8; 16];
+        let mut tag = [0u8; GCM_TAG_LENGTH];
+        let mut payload = encrypt_aead(Cipher::aes_256_gcm(), &key.encryption_key().unwrap(), Some(&iv), &[], b"sqlite", &mut tag).unwrap();
+        payload.extend_from_slice(&tag);
+
+        assert_eq!(key.decrypt_payload(&iv, &payload).unwrap(), b"sqlite");
+        let wrong = BackupKey::from_bytes(&[8u8; 32]).unwrap();
+        assert!(wrong.decrypt_payload(&iv, &payload).is_err());
+    }
+}
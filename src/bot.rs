@@ -0,0 +1,356 @@
+//! Kerangka bot berbasis perintah (`!ping`, `!kick`, ...) dengan model izin per chat
+//!
+//! Setiap pengirim memiliki `Role` di sebuah chat: owner bot (global), admin chat,
+//! pengguna biasa, atau ditolak. Daftar admin serta allow/deny list disimpan di
+//! `StateStore` sehingga tetap berlaku setelah bot di-restart.
+//!
+//! ```ignore
+//! let mut bot = Bot::new(Arc::new(FileStateStore::open("bot.json")?));
+//! bot.command("ping", |ctx: &CommandContext| ctx.reply("pong").map(|_| ()));
+//! bot.command("kick", requires_admin(|ctx: &CommandContext| { /* ... */ Ok(()) }));
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::*;
+use crate::messages::WebMessageInfo;
+use crate::store::{self, StateStore};
+use crate::{Jid, WhatsAppClient};
+
+const OWNER_KEY: &str = "bot:owner";
+const CHAT_KEY_PREFIX: &str = "bot:chat:";
+
+/// Tingkat izin pengirim di sebuah chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Ada di deny list (atau tidak ada di allow list yang aktif)
+    Denied,
+    User,
+    Admin,
+    Owner,
+}
+
+/// Izin yang disimpan untuk satu chat
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatPermissions {
+    pub admins: Vec<String>,
+    /// Jika tidak kosong, hanya anggota list ini (dan admin) yang boleh memakai bot
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl ChatPermissions {
+    fn role_of(&self, sender: &str) -> Role {
+        if self.deny.iter().any(|jid| jid == sender) {
+            Role::Denied
+        } else if self.admins.iter().any(|jid| jid == sender) {
+            Role::Admin
+        } else if !self.allow.is_empty() && !self.allow.iter().any(|jid| jid == sender) {
+            Role::Denied
+        } else {
+            Role::User
+        }
+    }
+}
+
+fn add_unique(list: &mut Vec<String>, jid: String) -> bool {
+    if list.contains(&jid) {
+        false
+    } else {
+        list.push(jid);
+        true
+    }
+}
+
+fn remove_entry(list: &mut Vec<String>, jid: &str) -> bool {
+    let before = list.len();
+    list.retain(|entry| entry != jid);
+    list.len() != before
+}
+
+/// Model izin bot yang dipersist di `StateStore`
+pub struct Permissions {
+    store: Arc<dyn StateStore>,
+}
+
+impl Permissions {
+    pub fn new(store: Arc<dyn StateStore>) -> Self {
+        Permissions { store }
+    }
+
+    fn chat_key(chat: &Jid) -> String {
+        format!("{}{}", CHAT_KEY_PREFIX, chat.to_string())
+    }
+
+    pub fn owner(&self) -> Result<Option<Jid>> {
+        let owner: Option<String> = store::load_json(self.store.as_ref(), OWNER_KEY)?;
+        owner.map(|jid| Jid::from_string(&jid)).transpose()
+    }
+
+    /// Owner berlaku di semua chat dan tidak bisa dikenai deny list
+    pub fn set_owner(&self, owner: &Jid) -> Result<()> {
+        store::save_json(self.store.as_ref(), OWNER_KEY, &owner.to_string())
+    }
+
+    pub fn chat(&self, chat: &Jid) -> Result<ChatPermissions> {
+        Ok(store::load_json(self.store.as_ref(), &Self::chat_key(chat))?.unwrap_or_default())
+    }
+
+    fn update_chat<F: FnOnce(&mut ChatPermissions) -> bool>(&self, chat: &Jid, update: F) -> Result<bool> {
+        let mut perms = self.chat(chat)?;
+        let changed = update(&mut perms);
+        if changed {
+            store::save_json(self.store.as_ref(), &Self::chat_key(chat), &perms)?;
+        }
+        Ok(changed)
+    }
+
+    pub fn add_admin(&self, chat: &Jid, user: &Jid) -> Result<bool> {
+        self.update_chat(chat, |perms| add_unique(&mut perms.admins, user.to_string()))
+    }
+
+    pub fn remove_admin(&self, chat: &Jid, user: &Jid) -> Result<bool> {
+        self.update_chat(chat, |perms| remove_entry(&mut perms.admins, &user.to_string()))
+    }
+
+    /// Menambahkan ke allow list (sekaligus menghapus dari deny list)
+    pub fn allow(&self, chat: &Jid, user: &Jid) -> Result<bool> {
+        let user = user.to_string();
+        self.update_chat(chat, |perms| {
+            let removed = remove_entry(&mut perms.deny, &user);
+            add_unique(&mut perms.allow, user) || removed
+        })
+    }
+
+    /// Menambahkan ke deny list (sekaligus menghapus dari allow list)
+    pub fn deny(&self, chat: &Jid, user: &Jid) -> Result<bool> {
+        let user = user.to_string();
+        self.update_chat(chat, |perms| {
+            let removed = remove_entry(&mut perms.allow, &user);
+            add_unique(&mut perms.deny, user) || removed
+        })
+    }
+
+    /// Menghapus pengguna dari allow dan deny list
+    pub fn reset(&self, chat: &Jid, user: &Jid) -> Result<bool> {
+        let user = user.to_string();
+        self.update_chat(chat, |perms| {
+            let allowed = remove_entry(&mut perms.allow, &user);
+            remove_entry(&mut perms.deny, &user) || allowed
+        })
+    }
+
+    /// Menentukan role pengirim di sebuah chat
+    pub fn role_of(&self, chat: &Jid, sender: &Jid) -> Result<Role> {
+        if self.owner()?.as_ref() == Some(sender) {
+            return Ok(Role::Owner);
+        }
+        Ok(self.chat(chat)?.role_of(&sender.to_string()))
+    }
+}
+
+/// Konteks eksekusi satu perintah
+pub struct CommandContext<'a> {
+    pub client: &'a WhatsAppClient,
+    pub message: &'a WebMessageInfo,
+    pub chat: Jid,
+    pub sender: Jid,
+    pub role: Role,
+    pub command: String,
+    pub args: Vec<String>,
+    denied_reply: Option<&'a str>,
+}
+
+impl CommandContext<'_> {
+    /// Membalas ke chat asal perintah
+    pub fn reply(&self, text: &str) -> Result<String> {
+        self.client.send_text_message(&self.chat, text)
+    }
+}
+
+/// Handler perintah bot
+pub trait CommandHandler: Send + Sync {
+    fn call(&self, ctx: &CommandContext) -> Result<()>;
+}
+
+impl<F> CommandHandler for F
+where
+    F: Fn(&CommandContext) -> Result<()> + Send + Sync,
+{
+    fn call(&self, ctx: &CommandContext) -> Result<()> {
+        self(ctx)
+    }
+}
+
+/// Membungkus handler agar hanya berjalan untuk role minimal `role`
+pub fn requires_role<H: CommandHandler>(role: Role, handler: H) -> impl Fn(&CommandContext) -> Result<()> + Send + Sync {
+    move |ctx: &CommandContext| {
+        if ctx.role >= role {
+            return handler.call(ctx);
+        }
+        if let Some(text) = ctx.denied_reply {
+            ctx.reply(text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Perintah hanya untuk admin chat (dan owner)
+pub fn requires_admin<H: CommandHandler>(handler: H) -> impl Fn(&CommandContext) -> Result<()> + Send + Sync {
+    requires_role(Role::Admin, handler)
+}
+
+/// Perintah hanya untuk owner bot
+pub fn requires_owner<H: CommandHandler>(handler: H) -> impl Fn(&CommandContext) -> Result<()> + Send + Sync {
+    requires_role(Role::Owner, handler)
+}
+
+/// Mengambil teks dari pesan biasa maupun extended text
+pub fn message_text(message: &WebMessageInfo) -> Option<&str> {
+    let content = message.message.as_ref()?;
+    content
+        .conversation
+        .as_deref()
+        .or_else(|| content.extended_text_message.as_ref().map(|ext| ext.text.as_str()))
+}
+
+/// Pengirim pesan: participant di grup, remote jid di chat pribadi
+pub fn message_sender(message: &WebMessageInfo) -> Result<Jid> {
+    let sender = message
+        .key
+        .participant
+        .as_deref()
+        .or(message.participant.as_deref())
+        .unwrap_or(&message.key.remote_jid);
+    Jid::from_string(sender)
+}
+
+/// Bot perintah dengan prefix (default `!`)
+pub struct Bot {
+    prefix: String,
+    commands: HashMap<String, Box<dyn CommandHandler>>,
+    permissions: Permissions,
+    denied_reply: Option<String>,
+}
+
+impl Bot {
+    pub fn new(store: Arc<dyn StateStore>) -> Self {
+        Bot {
+            prefix: "!".to_string(),
+            commands: HashMap::new(),
+            permissions: Permissions::new(store),
+            denied_reply: None,
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Balasan ketika perintah ditolak karena izin; default diam
+    pub fn with_denied_reply(mut self, text: &str) -> Self {
+        self.denied_reply = Some(text.to_string());
+        self
+    }
+
+    /// Mendaftarkan perintah (tanpa prefix, case-insensitive)
+    pub fn command<H: CommandHandler + 'static>(&mut self, name: &str, handler: H) -> &mut Self {
+        self.commands.insert(name.to_lowercase(), Box::new(handler));
+        self
+    }
+
+    pub fn permissions(&self) -> &Permissions {
+        &self.permissions
+    }
+
+    /// Memproses pesan masuk. Mengembalikan `true` jika pesan adalah perintah
+    /// terdaftar yang dijalankan (atau ditolak oleh pemeriksaan izin).
+    pub fn handle_message(&self, client: &WhatsAppClient, message: &WebMessageInfo) -> Result<bool> {
+        if message.key.from_me {
+            return Ok(false);
+        }
+
+        let text = match message_text(message).and_then(|text| text.trim().strip_prefix(self.prefix.as_str())) {
+            Some(text) => text,
+            None => return Ok(false),
+        };
+
+        let mut parts = text.split_whitespace();
+        let command = match parts.next() {
+            Some(command) => command.to_lowercase(),
+            None => return Ok(false),
+        };
+        let handler = match self.commands.get(&command) {
+            Some(handler) => handler,
+            None => return Ok(false),
+        };
+
+        let chat = Jid::from_string(&message.key.remote_jid)?;
+        let sender = message_sender(message)?;
+        let role = self.permissions.role_of(&chat, &sender)?;
+        if role == Role::Denied {
+            // Pengguna yang ditolak diabaikan sepenuhnya
+            return Ok(true);
+        }
+
+        let ctx = CommandContext {
+            client,
+            message,
+            chat,
+            sender,
+            role,
+            command,
+            args: parts.map(|arg| arg.to_string()).collect(),
+            denied_reply: self.denied_reply.as_deref(),
+        };
+        handler.call(&ctx)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStateStore;
+
+    fn jid(n: &str) -> Jid {
+        Jid::user(n)
+    }
+
+    #[test]
+    fn test_role_resolution() {
+        let perms = Permissions::new(Arc::new(MemoryStateStore::new()));
+        let group = Jid::group("123-456");
+
+        perms.set_owner(&jid("1")).unwrap();
+        perms.add_admin(&group, &jid("2")).unwrap();
+        perms.deny(&group, &jid("3")).unwrap();
+
+        assert_eq!(perms.role_of(&group, &jid("1")).unwrap(), Role::Owner);
+        assert_eq!(perms.role_of(&group, &jid("2")).unwrap(), Role::Admin);
+        assert_eq!(perms.role_of(&group, &jid("3")).unwrap(), Role::Denied);
+        assert_eq!(perms.role_of(&group, &jid("4")).unwrap(), Role::User);
+
+        // Allow list aktif: pengguna di luar list ditolak, admin tetap lolos
+        perms.allow(&group, &jid("5")).unwrap();
+        assert_eq!(perms.role_of(&group, &jid("4")).unwrap(), Role::Denied);
+        assert_eq!(perms.role_of(&group, &jid("5")).unwrap(), Role::User);
+        assert_eq!(perms.role_of(&group, &jid("2")).unwrap(), Role::Admin);
+    }
+
+    #[test]
+    fn test_allow_and_deny_are_exclusive() {
+        let perms = Permissions::new(Arc::new(MemoryStateStore::new()));
+        let chat = jid("10");
+
+        perms.deny(&chat, &jid("3")).unwrap();
+        perms.allow(&chat, &jid("3")).unwrap();
+        let state = perms.chat(&chat).unwrap();
+        assert!(state.deny.is_empty());
+        assert_eq!(state.allow, vec![jid("3").to_string()]);
+    }
+}
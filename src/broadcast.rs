@@ -0,0 +1,130 @@
+//! Daftar siaran (broadcast list)
+//!
+//! Pesan ke JID `<id>@broadcast` dikirim sekali dengan daftar penerima
+//! eksplisit; server meneruskannya ke chat pribadi masing-masing penerima.
+//! Status (`status@broadcast`) memakai stanza yang sama. Pesan masuk yang
+//! bertanda `broadcast` dikirim sebagai `Event::BroadcastMessageReceived`.
+
+use chrono::Utc;
+
+use crate::errors::*;
+use crate::messages::{self, WebMessageInfo};
+use crate::node_protocol::Node;
+use crate::status::STATUS_BROADCAST;
+use crate::{latency, signal, utils, Jid, WhatsAppClient};
+
+/// Suffix JID daftar siaran
+pub const BROADCAST_SUFFIX: &str = "@broadcast";
+
+/// Apakah `jid` adalah daftar siaran (termasuk `status@broadcast`)
+pub fn is_broadcast_jid(jid: &str) -> bool {
+    jid.ends_with(BROADCAST_SUFFIX)
+}
+
+/// Stanza relay: pesan beserta daftar perangkat penerima fanout
+pub fn relay_node(web_message: &WebMessageInfo, recipients: &[String]) -> Result<Node> {
+    let serialized = web_message.to_bytes();
+    Ok(Node::new("action")
+        .attr("type", "relay")
+        .attr("id", &web_message.key.id)
+        .attr("epoch", "1")
+        .attr("to", &web_message.key.remote_jid)
+        .children(vec![
+            Node::new("message").bytes(serialized),
+            Node::new("participants").children(recipients.iter().map(|jid| Node::new("to").attr("jid", jid)).collect()),
+        ]))
+}
+
+/// Pesan masuk yang dikirim lewat daftar siaran (bukan status); dikirim ke
+/// aplikasi sebagai `Event::BroadcastMessageReceived`
+pub fn is_list_message(web_message: &WebMessageInfo) -> bool {
+    web_message.broadcast == Some(true) && web_message.key.remote_jid != STATUS_BROADCAST
+}
+
+impl WhatsAppClient {
+    /// Mengirim pesan teks ke daftar siaran `list` (`<id>@broadcast`) untuk `recipients`
+    pub fn send_broadcast_text(&self, list: &str, recipients: &[Jid], text: &str) -> Result<String> {
+        if !is_broadcast_jid(list) || list == STATUS_BROADCAST {
+            return Err("Broadcast list JID must end with @broadcast (use send_status for statuses)".into());
+        }
+        let message = messages::Message {
+            conversation: Some(text.to_string()),
+            ..Default::default()
+        };
+        let admitted: Vec<String> = recipients.iter().map(|jid| jid.to_string()).collect();
+        self.admit_rate_limit(&admitted)?;
+        self.admit_warmup(&admitted)?;
+        self.send_fanout(list, recipients, message)
+    }
+
+    /// Mengirim `message` ke JID broadcast dengan daftar penerima eksplisit
+    pub(crate) fn send_fanout(&self, list: &str, recipients: &[Jid], message: messages::Message) -> Result<String> {
+        if recipients.is_empty() {
+            return Err("Broadcast requires at least one recipient".into());
+        }
+        if recipients.iter().any(|jid| jid.is_group()) {
+            return Err("Broadcast recipients must be contacts, not groups".into());
+        }
+
+        let message_id = utils::generate_message_id();
+        let timestamp = Utc::now().timestamp();
+        let web_message = WebMessageInfo {
+            key: messages::MessageKey {
+                remote_jid: list.to_string(),
+                from_me: true,
+                id: message_id.clone(),
+                participant: None,
+            },
+            message: Some(message),
+            message_timestamp: Some(timestamp as u64),
+            status: Some(1), // PENDING
+            broadcast: Some(true),
+            ..Default::default()
+        };
+        let recipients: Vec<String> = recipients.iter().map(|jid| jid.to_string()).collect();
+
+        self.latency.lock().unwrap().mark(&message_id, latency::Stage::Enqueued);
+        self.retries.lock().unwrap().record(&web_message);
+        let nodes = self.outbox.lock().unwrap().enqueue_broadcast(&recipients, web_message)?;
+        for node in nodes {
+            let node = if node.tag == "action" { signal::seal_relay(node, &self.signal, &self.session)? } else { node };
+            self.send_node(&node)?;
+        }
+        self.sent_log.lock().unwrap().record(&message_id, timestamp);
+
+        Ok(message_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(remote_jid: &str, broadcast: Option<bool>) -> WebMessageInfo {
+        WebMessageInfo {
+            key: messages::MessageKey {
+                remote_jid: remote_jid.to_string(),
+                from_me: false,
+                id: "b1".to_string(),
+                participant: None,
+            },
+            broadcast,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_relay_lists_recipients() {
+        let node = relay_node(&message("123@broadcast", Some(true)), &["1@s.whatsapp.net".to_string(), "2@s.whatsapp.net".to_string()]).unwrap();
+        assert_eq!(node.get_attr("to"), Some("123@broadcast"));
+        assert_eq!(node.get_child("participants").unwrap().get_children().len(), 2);
+        assert!(node.get_child("message").and_then(|m| m.get_bytes()).is_some());
+    }
+
+    #[test]
+    fn test_list_message_skips_direct_and_status() {
+        assert!(is_list_message(&message("628111@s.whatsapp.net", Some(true))));
+        assert!(!is_list_message(&message("628111@s.whatsapp.net", None)));
+        assert!(!is_list_message(&message(STATUS_BROADCAST, Some(true))));
+    }
+}
@@ -0,0 +1,303 @@
+//! Pesan list dan tombol dengan validasi batas WhatsApp
+//!
+//! Pesan yang melanggar batas (jumlah bagian, jumlah tombol, panjang teks)
+//! tetap diterima server tetapi tidak ditampilkan oleh aplikasi penerima.
+//! Builder di sini memeriksa batas tersebut sebelum pesan dikirim dan memilih
+//! `header_type` sesuai header yang dipasang.
+
+use std::collections::HashSet;
+
+use crate::errors::*;
+use crate::messages::{
+    Button, ButtonsMessage, DocumentMessage, ImageMessage, ListMessage, ListRow, ListSection, LocationMessage, Message, VideoMessage,
+};
+
+/// Jumlah bagian terbanyak pada pesan list
+pub const MAX_SECTIONS: usize = 10;
+/// Jumlah baris terbanyak pada pesan list (semua bagian)
+pub const MAX_ROWS: usize = 10;
+/// Jumlah tombol terbanyak pada pesan tombol
+pub const MAX_BUTTONS: usize = 3;
+/// Panjang teks isi pesan
+pub const MAX_BODY_LENGTH: usize = 1024;
+/// Panjang footer dan header teks
+pub const MAX_FOOTER_LENGTH: usize = 60;
+/// Panjang teks tombol (termasuk tombol pembuka list)
+pub const MAX_BUTTON_TEXT_LENGTH: usize = 20;
+/// Panjang judul bagian dan judul baris
+pub const MAX_TITLE_LENGTH: usize = 24;
+/// Panjang deskripsi baris
+pub const MAX_DESCRIPTION_LENGTH: usize = 72;
+/// Panjang id baris dan id tombol
+pub const MAX_ID_LENGTH: usize = 200;
+
+/// `ListMessage.list_type` untuk daftar pilihan tunggal
+const LIST_TYPE_SINGLE_SELECT: u32 = 1;
+/// `Button.type` untuk tombol balasan
+const BUTTON_TYPE_RESPONSE: u32 = 1;
+
+/// Nilai `ButtonsMessage.header_type`
+const HEADER_EMPTY: u32 = 1;
+const HEADER_TEXT: u32 = 2;
+const HEADER_DOCUMENT: u32 = 3;
+const HEADER_IMAGE: u32 = 4;
+const HEADER_VIDEO: u32 = 5;
+const HEADER_LOCATION: u32 = 6;
+
+fn check_length(field: &str, value: &str, max: usize) -> Result<()> {
+    let length = value.chars().count();
+    if length > max {
+        return Err(format!("{} is {} characters long, the limit is {}", field, length, max).into());
+    }
+    Ok(())
+}
+
+fn check_required(field: &str, value: &str, max: usize) -> Result<()> {
+    if value.trim().is_empty() {
+        return Err(format!("{} must not be empty", field).into());
+    }
+    check_length(field, value, max)
+}
+
+/// Builder pesan list
+#[derive(Debug, Clone, Default)]
+pub struct ListMessageBuilder {
+    title: String,
+    text: String,
+    button_text: String,
+    footer: Option<String>,
+    sections: Vec<ListSection>,
+}
+
+impl ListMessageBuilder {
+    /// `button_text` adalah tombol yang membuka daftar
+    pub fn new(text: &str, button_text: &str) -> Self {
+        ListMessageBuilder { text: text.to_string(), button_text: button_text.to_string(), ..Default::default() }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn footer(mut self, text: &str) -> Self {
+        self.footer = Some(text.to_string());
+        self
+    }
+
+    /// Bagian berisi baris `(id, judul, deskripsi)`
+    pub fn section(mut self, title: &str, rows: &[(&str, &str, &str)]) -> Self {
+        self.sections.push(ListSection {
+            title: title.to_string(),
+            rows: rows
+                .iter()
+                .map(|(id, title, description)| ListRow {
+                    row_id: id.to_string(),
+                    title: title.to_string(),
+                    description: description.to_string(),
+                })
+                .collect(),
+        });
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        check_required("List text", &self.text, MAX_BODY_LENGTH)?;
+        check_required("List button text", &self.button_text, MAX_BUTTON_TEXT_LENGTH)?;
+        check_length("List title", &self.title, MAX_FOOTER_LENGTH)?;
+        if let Some(ref footer) = self.footer {
+            check_length("List footer", footer, MAX_FOOTER_LENGTH)?;
+        }
+        if self.sections.is_empty() || self.sections.len() > MAX_SECTIONS {
+            return Err(format!("List message requires 1 to {} sections, got {}", MAX_SECTIONS, self.sections.len()).into());
+        }
+        let rows: usize = self.sections.iter().map(|section| section.rows.len()).sum();
+        if rows == 0 || rows > MAX_ROWS {
+            return Err(format!("List message requires 1 to {} rows in total, got {}", MAX_ROWS, rows).into());
+        }
+
+        let mut ids = HashSet::new();
+        for (index, section) in self.sections.iter().enumerate() {
+            // Judul wajib jika ada lebih dari satu bagian
+            if self.sections.len() > 1 {
+                check_required(&format!("Section {} title", index + 1), &section.title, MAX_TITLE_LENGTH)?;
+            } else {
+                check_length("Section title", &section.title, MAX_TITLE_LENGTH)?;
+            }
+            if section.rows.is_empty() {
+                return Err(format!("Section {} has no rows", index + 1).into());
+            }
+            for row in &section.rows {
+                check_required("Row id", &row.row_id, MAX_ID_LENGTH)?;
+                check_required(&format!("Row {} title", row.row_id), &row.title, MAX_TITLE_LENGTH)?;
+                check_length(&format!("Row {} description", row.row_id), &row.description, MAX_DESCRIPTION_LENGTH)?;
+                if !ids.insert(row.row_id.as_str()) {
+                    return Err(format!("Duplicate row id {}", row.row_id).into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pesan tanpa validasi; `build` memanggil `validate` terlebih dulu
+    pub(crate) fn list_message(&self, text: String) -> ListMessage {
+        ListMessage {
+            title: self.title.clone(),
+            description: text,
+            button_text: self.button_text.clone(),
+            list_type: LIST_TYPE_SINGLE_SELECT,
+            sections: self.sections.clone(),
+            footer_text: self.footer.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn build(&self) -> Result<Message> {
+        self.validate()?;
+        Ok(Message {
+            list_message: Some(self.list_message(self.text.clone())),
+            ..Default::default()
+        })
+    }
+}
+
+/// Header pesan tombol
+#[derive(Debug, Clone)]
+pub enum ButtonsHeader {
+    Text(String),
+    Image(ImageMessage),
+    Video(VideoMessage),
+    Document(DocumentMessage),
+    Location(LocationMessage),
+}
+
+/// Builder pesan tombol balasan
+#[derive(Debug, Clone, Default)]
+pub struct ButtonsMessageBuilder {
+    text: String,
+    footer: Option<String>,
+    header: Option<ButtonsHeader>,
+    buttons: Vec<Button>,
+}
+
+impl ButtonsMessageBuilder {
+    pub fn new(text: &str) -> Self {
+        ButtonsMessageBuilder { text: text.to_string(), ..Default::default() }
+    }
+
+    pub fn footer(mut self, text: &str) -> Self {
+        self.footer = Some(text.to_string());
+        self
+    }
+
+    /// Header teks atau media; `header_type` mengikuti jenisnya
+    pub fn header(mut self, header: ButtonsHeader) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Tombol balasan dengan `id` yang dikirim kembali saat ditekan
+    pub fn button(mut self, id: &str, text: &str) -> Self {
+        self.buttons.push(Button { button_id: id.to_string(), button_text: text.to_string(), r#type: BUTTON_TYPE_RESPONSE });
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        check_required("Buttons text", &self.text, MAX_BODY_LENGTH)?;
+        if let Some(ref footer) = self.footer {
+            check_length("Buttons footer", footer, MAX_FOOTER_LENGTH)?;
+        }
+        if let Some(ButtonsHeader::Text(ref header)) = self.header {
+            check_required("Buttons header", header, MAX_FOOTER_LENGTH)?;
+        }
+        if self.buttons.is_empty() || self.buttons.len() > MAX_BUTTONS {
+            return Err(format!("Buttons message requires 1 to {} buttons, got {}", MAX_BUTTONS, self.buttons.len()).into());
+        }
+        let mut ids = HashSet::new();
+        for button in &self.buttons {
+            check_required("Button id", &button.button_id, MAX_ID_LENGTH)?;
+            check_required(&format!("Button {} text", button.button_id), &button.button_text, MAX_BUTTON_TEXT_LENGTH)?;
+            if !ids.insert(button.button_id.as_str()) {
+                return Err(format!("Duplicate button id {}", button.button_id).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Pesan tanpa validasi; `build` memanggil `validate` terlebih dulu
+    pub(crate) fn buttons_message(&self, text: String) -> ButtonsMessage {
+        let mut message = ButtonsMessage {
+            content_text: text,
+            footer_text: self.footer.clone(),
+            buttons: self.buttons.clone(),
+            header_type: HEADER_EMPTY,
+            ..Default::default()
+        };
+        match self.header.clone() {
+            None => {}
+            Some(ButtonsHeader::Text(text)) => {
+                message.header_type = HEADER_TEXT;
+                message.text = Some(text);
+            }
+            Some(ButtonsHeader::Image(image)) => {
+                message.header_type = HEADER_IMAGE;
+                message.image_message = Some(image);
+            }
+            Some(ButtonsHeader::Video(video)) => {
+                message.header_type = HEADER_VIDEO;
+                message.video_message = Some(video);
+            }
+            Some(ButtonsHeader::Document(document)) => {
+                message.header_type = HEADER_DOCUMENT;
+                message.document_message = Some(document);
+            }
+            Some(ButtonsHeader::Location(location)) => {
+                message.header_type = HEADER_LOCATION;
+                message.location_message = Some(location);
+            }
+        }
+        message
+    }
+
+    pub fn build(&self) -> Result<Message> {
+        self.validate()?;
+        Ok(Message {
+            buttons_message: Some(self.buttons_message(self.text.clone())),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_and_header_type() {
+        let list = ListMessageBuilder::new("Menu hari ini", "Lihat menu").section("Minuman", &[("kopi", "Kopi", "Panas"), ("teh", "Teh", "")]);
+        assert!(list.build().unwrap().list_message.is_some());
+
+        let two_sections = list.clone().section("", &[("roti", "Roti", "")]);
+        assert!(two_sections.validate().unwrap_err().to_string().contains("Section 2 title"));
+        let duplicate = list.clone().section("Lagi", &[("kopi", "Kopi", "")]);
+        assert!(duplicate.validate().is_err());
+        assert!(ListMessageBuilder::new("x", "Buka menu yang sangat panjang").section("", &[("a", "A", "")]).validate().is_err());
+
+        let image = ImageMessage { url: "https://mmg.whatsapp.net/x".to_string(), ..Default::default() };
+        let buttons = ButtonsMessageBuilder::new("Konfirmasi?").header(ButtonsHeader::Image(image)).button("ya", "Ya").button("tidak", "Tidak");
+        let message = buttons.build().unwrap().buttons_message.unwrap();
+        assert_eq!(message.header_type, HEADER_IMAGE);
+        assert!(message.image_message.is_some());
+
+        let too_many = buttons.button("a", "A").button("b", "B");
+        assert!(too_many.validate().unwrap_err().to_string().contains("got 4"));
+    }
+}
@@ -0,0 +1,113 @@
+//! Sinyal panggilan suara/video
+//!
+//! Library tidak menangani media panggilan, hanya stanza `call`: tawaran
+//! (`offer`, atau `offer_notice` untuk panggilan tak terjawab saat offline)
+//! dan penghentian (`terminate`). Aplikasi bisa menolak panggilan dengan
+//! `reject_call`, mis. untuk bot yang tidak menerima telepon.
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{utils, Event, Jid, WhatsAppClient};
+
+/// Sinyal panggilan yang diteruskan ke aplikasi
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallSignal {
+    Offer { from: Jid, call_id: String, is_video: bool },
+    /// Panggilan tak terjawab yang terjadi saat client offline
+    Missed { from: Jid, call_id: String, is_video: bool },
+    Terminate { from: Jid, call_id: String, reason: Option<String> },
+}
+
+impl CallSignal {
+    pub fn into_event(self) -> Event {
+        match self {
+            CallSignal::Offer { from, call_id, is_video } => Event::IncomingCall { from, call_id, is_video },
+            CallSignal::Missed { from, call_id, is_video } => Event::MissedCall { from, call_id, is_video },
+            CallSignal::Terminate { from, call_id, reason } => Event::CallTerminated { from, call_id, reason },
+        }
+    }
+}
+
+/// Membaca `<call from><offer|offer_notice|terminate call-id ..>..</..></call>`
+pub fn parse_call(node: &Node) -> Option<CallSignal> {
+    let from = Jid::from_string(node.get_attr("from")?).ok()?;
+    let action = node.get_children().first()?;
+    let call_id = action.get_attr("call-id")?.to_string();
+    match action.tag.as_str() {
+        "offer" => Some(CallSignal::Offer { from, call_id, is_video: action.get_child("video").is_some() }),
+        "offer_notice" => Some(CallSignal::Missed { from, call_id, is_video: action.get_attr("media") == Some("video") }),
+        "terminate" => Some(CallSignal::Terminate { from, call_id, reason: action.get_attr("reason").map(|r| r.to_string()) }),
+        _ => None,
+    }
+}
+
+/// Ack stanza `call`; `type` diisi tag aksinya, bukan atribut stanza
+fn call_ack(node: &Node) -> Option<Node> {
+    let mut ack = Node::new("ack").attr("id", node.get_attr("id")?).attr("class", "call").attr("to", node.get_attr("from")?);
+    if let Some(action) = node.get_children().first() {
+        ack = ack.attr("type", &action.tag);
+    }
+    Some(ack)
+}
+
+/// Handler stanza `call`
+pub fn call_handler() -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if let Some(ack) = call_ack(node) {
+            ctx.send_node(&ack)?;
+        }
+        if let Some(signal) = parse_call(node) {
+            ctx.emit(signal.into_event());
+        }
+        Ok(())
+    }
+}
+
+/// Stanza penolakan panggilan `call_id` dari `from`
+pub fn reject_node(call_id: &str, from: &Jid) -> Node {
+    Node::new("call").attr("id", &utils::generate_message_id()).attr("to", &from.to_string()).children(vec![Node::new("reject")
+        .attr("call-id", call_id)
+        .attr("call-creator", &from.to_string())
+        .attr("count", "0")])
+}
+
+impl WhatsAppClient {
+    /// Menolak panggilan masuk
+    pub fn reject_call(&self, call_id: &str, from: &Jid) -> Result<()> {
+        self.send_node(&reject_node(call_id, from))
+    }
+
+    /// Menolak panggilan lalu mengirim `message` ke penelepon
+    pub fn reject_call_with_message(&self, call_id: &str, from: &Jid, message: &str) -> Result<String> {
+        self.reject_call(call_id, from)?;
+        self.send_text_message(&from.to_non_ad(), message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offer_and_terminate() {
+        let offer = Node::new("call").attr("from", "628111:3@s.whatsapp.net").attr("id", "S1").children(vec![Node::new("offer")
+            .attr("call-id", "C1")
+            .children(vec![Node::new("audio"), Node::new("video")])]);
+        let from = Jid::from_string("628111:3@s.whatsapp.net").unwrap();
+        assert_eq!(parse_call(&offer), Some(CallSignal::Offer { from: from.clone(), call_id: "C1".to_string(), is_video: true }));
+        let ack = call_ack(&offer).unwrap();
+        assert_eq!((ack.get_attr("class"), ack.get_attr("type")), (Some("call"), Some("offer")));
+
+        let terminate = Node::new("call")
+            .attr("from", "628111:3@s.whatsapp.net")
+            .children(vec![Node::new("terminate").attr("call-id", "C1").attr("reason", "timeout")]);
+        assert_eq!(
+            parse_call(&terminate),
+            Some(CallSignal::Terminate { from: from.clone(), call_id: "C1".to_string(), reason: Some("timeout".to_string()) })
+        );
+
+        let reject = reject_node("C1", &from);
+        assert_eq!(reject.get_child("reject").and_then(|r| r.get_attr("call-id")), Some("C1"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Handle percakapan per JID
+//!
+//! `WhatsAppClient::chat(&jid)` mengembalikan `ChatHandle` yang menyimpan JID
+//! tujuan, sehingga kode bot tidak perlu mengoper JID ke setiap panggilan.
+//! Pengaturan per chat (pesan sementara, jeda kirim) juga diatur dari sini.
+
+use std::time::Duration;
+
+use crate::errors::*;
+use crate::messages::WebMessageInfo;
+use crate::{ChatState, Jid, MediaType, MessageBuilder, WhatsAppClient};
+
+/// Percakapan dengan satu kontak atau grup
+pub struct ChatHandle<'a> {
+    client: &'a WhatsAppClient,
+    jid: Jid,
+}
+
+impl ChatHandle<'_> {
+    pub fn jid(&self) -> &Jid {
+        &self.jid
+    }
+
+    pub fn send_text(&self, text: &str) -> Result<String> {
+        self.client.send_text_message(&self.jid, text)
+    }
+
+    pub fn send(&self, builder: MessageBuilder) -> Result<String> {
+        self.client.send(&self.jid, builder)
+    }
+
+    pub fn send_media(&self, media_type: MediaType, url: &str, caption: Option<&str>) -> Result<String> {
+        self.client.send_media_message(&self.jid, media_type, url, caption)
+    }
+
+    /// Membalas pesan di chat ini
+    pub fn reply(&self, quoted: &WebMessageInfo, text: &str) -> Result<String> {
+        self.client.send_reply(&self.jid, text, quoted)
+    }
+
+    /// Mengirim status mengetik/merekam ke chat ini (lihat `WhatsAppClient::send_chat_state`)
+    pub fn send_chat_state(&self, state: ChatState) -> Result<()> {
+        self.client.send_chat_state(&self.jid, state)
+    }
+
+    /// Mengirim read receipt untuk pesan belum dibaca dan menandai chat dibaca
+    pub fn mark_read(&self) -> Result<()> {
+        self.client.mark_chat_read(&self.jid).map(|_| ())
+    }
+
+    pub fn unread_count(&self) -> usize {
+        self.client.unread_count(&self.jid)
+    }
+
+    /// Paling banyak `limit` pesan terakhir di chat ini selama sesi berjalan,
+    /// urut dari yang terlama
+    pub fn history(&self, limit: usize) -> Vec<WebMessageInfo> {
+        self.client.recent.lock().unwrap().recent(&self.jid.to_string(), limit)
+    }
+
+    /// Mengatur pesan sementara untuk chat ini; `None` menonaktifkan
+    pub fn set_ephemeral(&self, duration: Option<Duration>) -> Result<()> {
+        self.client.set_ephemeral(&self.jid, duration)
+    }
+
+    pub fn ephemeral(&self) -> Option<Duration> {
+        self.client.ephemeral_duration(&self.jid)
+    }
+
+    /// Jeda minimal antar pengiriman ke chat ini, menggantikan
+    /// `TrafficSettings::per_chat_interval`; `None` kembali ke pengaturan umum
+    pub fn set_rate_limit(&self, interval: Option<Duration>) {
+        self.client.traffic.lock().unwrap().set_chat_interval(&self.jid.to_string(), interval);
+    }
+}
+
+impl WhatsAppClient {
+    /// Handle untuk percakapan dengan `jid`
+    pub fn chat(&self, jid: &Jid) -> ChatHandle<'_> {
+        ChatHandle {
+            client: self,
+            jid: jid.clone(),
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! Operasi pada chat: arsip, sematkan, bisukan, bintangi pesan, tandai
+//! sudah/belum dibaca, kosongkan, dan hapus chat
+//!
+//! Semua mutasi untuk satu operasi dikirim dalam satu patch app state (dipecah
+//! per `MAX_MUTATIONS_PER_PATCH`), jauh lebih cepat daripada satu patch per chat.
+//! Patch dienkripsi dan diberi MAC (lihat modul `syncd`) sehingga perubahan
+//! terlihat di ponsel dan perangkat lain.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::app_state::{AppStateStore, Collection, Mutation, MutationOperation};
+use crate::errors::*;
+use crate::messages::{MessageKey, WebMessageInfo};
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{Event, Jid, WhatsAppClient};
+
+/// Batas mutasi dalam satu patch
+pub const MAX_MUTATIONS_PER_PATCH: usize = 500;
+
+/// Jumlah pesan terakhir yang disimpan per chat
+pub const RECENT_MESSAGES_PER_CHAT: usize = 50;
+
+/// Pesan masuk yang belum dibaca
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnreadMessage {
+    pub id: String,
+    /// Pengirim di grup
+    pub participant: Option<String>,
+}
+
+/// Pesan belum dibaca per chat, dicatat dari pesan masuk
+#[derive(Default)]
+pub struct UnreadChats {
+    messages: HashMap<String, Vec<UnreadMessage>>,
+}
+
+impl UnreadChats {
+    pub fn new() -> Self {
+        UnreadChats::default()
+    }
+
+    pub fn record(&mut self, chat: &str, id: &str, participant: Option<&str>) {
+        self.messages.entry(chat.to_string()).or_default().push(UnreadMessage {
+            id: id.to_string(),
+            participant: participant.map(|participant| participant.to_string()),
+        });
+    }
+
+    /// Menghapus dan mengembalikan pesan belum dibaca di `chat`
+    pub fn clear(&mut self, chat: &str) -> Vec<UnreadMessage> {
+        self.messages.remove(chat).unwrap_or_default()
+    }
+
+    pub fn count(&self, chat: &str) -> usize {
+        self.messages.get(chat).map(|messages| messages.len()).unwrap_or(0)
+    }
+
+    pub fn chats(&self) -> Vec<String> {
+        self.messages.keys().cloned().collect()
+    }
+}
+
+/// Pesan terakhir (masuk dan keluar) per chat, selama sesi berjalan
+#[derive(Default)]
+pub struct RecentMessages {
+    chats: HashMap<String, VecDeque<WebMessageInfo>>,
+}
+
+impl RecentMessages {
+    pub fn new() -> Self {
+        RecentMessages::default()
+    }
+
+    pub fn record(&mut self, message: &WebMessageInfo) {
+        let messages = self.chats.entry(message.key.remote_jid.clone()).or_default();
+        messages.push_back(message.clone());
+        if messages.len() > RECENT_MESSAGES_PER_CHAT {
+            messages.pop_front();
+        }
+    }
+
+    /// Paling banyak `limit` pesan terakhir, urut dari yang terlama
+    pub fn recent(&self, chat: &str, limit: usize) -> Vec<WebMessageInfo> {
+        let messages = match self.chats.get(chat) {
+            Some(messages) => messages,
+            None => return Vec::new(),
+        };
+        messages.iter().skip(messages.len().saturating_sub(limit)).cloned().collect()
+    }
+
+    pub fn find(&self, chat: &str, id: &str) -> Option<&WebMessageInfo> {
+        self.chats.get(chat)?.iter().find(|message| message.key.id == id)
+    }
+
+    pub fn clear(&mut self, chat: &str) {
+        self.chats.remove(chat);
+    }
+}
+
+/// Handler `message` yang mencatat pesan masuk ke `RecentMessages`
+pub fn recent_handler(recent: Arc<Mutex<RecentMessages>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if let Some(web_message) = node.get_bytes().and_then(|bytes| WebMessageInfo::from_bytes(bytes).ok()) {
+            recent.lock().unwrap().record(&web_message);
+        }
+        Ok(())
+    }
+}
+
+/// Handler `message` yang menghitung pesan belum dibaca
+pub fn unread_handler(unread: Arc<Mutex<UnreadChats>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if let (Some(chat), Some(id)) = (node.get_attr("from"), node.get_attr("id")) {
+            unread.lock().unwrap().record(chat, id, node.get_attr("participant"));
+        }
+        Ok(())
+    }
+}
+
+fn chat_mutation(action: &str, chat: &str, value: serde_json::Value) -> Mutation {
+    Mutation::set(vec![action.to_string(), chat.to_string()], value)
+}
+
+/// Rentang pesan yang dikenai aksi kosongkan/hapus/tandai: pesan terakhir yang diketahui client
+pub fn message_range(last: Option<&WebMessageInfo>) -> serde_json::Value {
+    match last {
+        Some(message) => serde_json::json!({
+            "lastMessageTimestamp": message.message_timestamp.unwrap_or_default(),
+            "messages": [{
+                "key": {
+                    "remoteJid": message.key.remote_jid,
+                    "fromMe": message.key.from_me,
+                    "id": message.key.id,
+                    "participant": message.key.participant,
+                },
+                "timestamp": message.message_timestamp.unwrap_or_default(),
+            }],
+        }),
+        None => serde_json::json!({ "lastMessageTimestamp": chrono::Utc::now().timestamp() }),
+    }
+}
+
+/// Index `clearChat`: chat, hapus pesan berbintang, hapus media
+pub fn clear_chat_index(chat: &str, delete_starred: bool) -> Vec<String> {
+    vec!["clearChat".to_string(), chat.to_string(), if delete_starred { "1" } else { "0" }.to_string(), "0".to_string()]
+}
+
+/// Index `deleteChat`: chat dan hapus media
+pub fn delete_chat_index(chat: &str) -> Vec<String> {
+    vec!["deleteChat".to_string(), chat.to_string(), "1".to_string()]
+}
+
+/// Index bintang: chat, id pesan, dari akun sendiri, dan pengirim di grup
+pub fn star_index(chat: &str, message_id: &str, from_me: bool, participant: Option<&str>) -> Vec<String> {
+    vec![
+        "star".to_string(),
+        chat.to_string(),
+        message_id.to_string(),
+        if from_me { "1" } else { "0" }.to_string(),
+        participant.unwrap_or("0").to_string(),
+    ]
+}
+
+/// Kunci pesan dari index bintang
+pub fn parse_star_index(index: &[String]) -> Option<MessageKey> {
+    match index {
+        [action, chat, id, from_me, participant] if action == "star" => Some(MessageKey {
+            remote_jid: chat.clone(),
+            from_me: from_me == "1",
+            id: id.clone(),
+            participant: if participant == "0" { None } else { Some(participant.clone()) },
+        }),
+        _ => None,
+    }
+}
+
+/// Event untuk mutasi yang dibuat perangkat lain
+pub fn mutation_event(mutation: &Mutation) -> Option<Event> {
+    let key = parse_star_index(&mutation.index)?;
+    let starred = mutation.operation == MutationOperation::Set && mutation.value["starred"].as_bool().unwrap_or(false);
+    Some(Event::MessageStarred { key, starred })
+}
+
+/// Pengaturan chat yang disinkronkan lewat app state
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChatSettings {
+    pub archived: bool,
+    pub pinned: bool,
+    /// Akhir bisukan (milidetik sejak epoch); -1 berarti selamanya
+    pub muted_until: Option<i64>,
+    /// Ditandai belum dibaca secara manual
+    pub marked_unread: bool,
+}
+
+/// Membaca arsip, sematan, dan bisukan `chat` dari salinan lokal app state
+pub fn chat_settings(app_state: &AppStateStore, chat: &str) -> ChatSettings {
+    let value = |collection: Collection, action: &str, field: &str| {
+        app_state
+            .get(collection, &[action.to_string(), chat.to_string()])
+            .map(|mutation| mutation.value[field].clone())
+            .unwrap_or_default()
+    };
+    let muted = value(Collection::RegularHigh, "mute", "muted").as_bool().unwrap_or(false);
+    ChatSettings {
+        archived: value(Collection::RegularLow, "archive", "archived").as_bool().unwrap_or(false),
+        pinned: value(Collection::RegularLow, "pin_v1", "pinned").as_bool().unwrap_or(false),
+        muted_until: if muted { value(Collection::RegularHigh, "mute", "muteEndTimestamp").as_i64().or(Some(-1)) } else { None },
+        marked_unread: value(Collection::RegularLow, "markChatAsRead", "read").as_bool() == Some(false),
+    }
+}
+
+impl WhatsAppClient {
+    /// Mengirim mutasi dalam patch sesedikit mungkin
+    pub(crate) fn push_batched(&self, collection: Collection, mutations: Vec<Mutation>) -> Result<()> {
+        if mutations.is_empty() {
+            return Ok(());
+        }
+        for chunk in mutations.chunks(MAX_MUTATIONS_PER_PATCH) {
+            self.push_app_state(collection, chunk.to_vec())?;
+        }
+        Ok(())
+    }
+
+    /// Mengarsipkan banyak chat sekaligus
+    pub fn archive_chats(&self, chats: &[Jid]) -> Result<()> {
+        self.set_chats_archived(chats, true)
+    }
+
+    /// Mengeluarkan banyak chat dari arsip sekaligus
+    pub fn unarchive_chats(&self, chats: &[Jid]) -> Result<()> {
+        self.set_chats_archived(chats, false)
+    }
+
+    fn set_chats_archived(&self, chats: &[Jid], archived: bool) -> Result<()> {
+        let mutations = chats
+            .iter()
+            .map(|chat| chat_mutation("archive", &chat.to_string(), serde_json::json!({ "archived": archived })))
+            .collect();
+        self.push_batched(Collection::RegularLow, mutations)
+    }
+
+    pub fn archive_chat(&self, chat: &Jid) -> Result<()> {
+        self.archive_chats(std::slice::from_ref(chat))
+    }
+
+    pub fn unarchive_chat(&self, chat: &Jid) -> Result<()> {
+        self.unarchive_chats(std::slice::from_ref(chat))
+    }
+
+    /// Menyematkan atau melepas sematan chat
+    pub fn pin_chat(&self, chat: &Jid, pinned: bool) -> Result<()> {
+        let mutation = chat_mutation("pin_v1", &chat.to_string(), serde_json::json!({ "pinned": pinned }));
+        self.push_app_state(Collection::RegularLow, vec![mutation])
+    }
+
+    /// Membisukan chat sampai `until`; `None` berarti selamanya
+    pub fn mute_chat(&self, chat: &Jid, until: Option<chrono::DateTime<chrono::Utc>>) -> Result<()> {
+        let mute_end = match until {
+            Some(until) if until <= chrono::Utc::now() => return Err("Mute end must be in the future".into()),
+            Some(until) => until.timestamp_millis(),
+            None => -1,
+        };
+        let mutation = chat_mutation("mute", &chat.to_string(), serde_json::json!({ "muted": true, "muteEndTimestamp": mute_end }));
+        self.push_app_state(Collection::RegularHigh, vec![mutation])
+    }
+
+    /// Memberi atau menghapus bintang pada pesan
+    pub fn star_message(&self, key: &MessageKey, starred: bool) -> Result<()> {
+        let index = star_index(&key.remote_jid, &key.id, key.from_me, key.participant.as_deref());
+        self.push_app_state(Collection::RegularHigh, vec![Mutation::set(index, serde_json::json!({ "starred": starred }))])
+    }
+
+    /// Apakah pesan berbintang menurut app state terakhir
+    pub fn is_starred(&self, key: &MessageKey) -> bool {
+        let index = star_index(&key.remote_jid, &key.id, key.from_me, key.participant.as_deref());
+        self.app_state
+            .lock()
+            .unwrap()
+            .get(Collection::RegularHigh, &index)
+            .and_then(|mutation| mutation.value["starred"].as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Pesan berbintang di `chat` menurut salinan lokal app state
+    pub fn get_starred(&self, chat: &Jid) -> Vec<MessageKey> {
+        let chat = chat.to_string();
+        self.app_state
+            .lock()
+            .unwrap()
+            .entries(Collection::RegularHigh, "star")
+            .into_iter()
+            .filter(|mutation| mutation.value["starred"].as_bool().unwrap_or(false))
+            .filter_map(|mutation| parse_star_index(&mutation.index))
+            .filter(|key| key.remote_jid == chat)
+            .collect()
+    }
+
+    /// Membisukan chat selama `duration`; `None` berarti selamanya
+    pub fn mute_chats(&self, chats: &[Jid], duration: Option<Duration>) -> Result<()> {
+        let mute_end = match duration {
+            Some(duration) => chrono::Utc::now().timestamp_millis() + duration.as_millis() as i64,
+            None => -1,
+        };
+        let mutations = chats
+            .iter()
+            .map(|chat| {
+                chat_mutation(
+                    "mute",
+                    &chat.to_string(),
+                    serde_json::json!({ "muted": true, "muteEndTimestamp": mute_end }),
+                )
+            })
+            .collect();
+        self.push_batched(Collection::RegularHigh, mutations)
+    }
+
+    /// Membatalkan bisukan untuk banyak chat
+    pub fn unmute_chats(&self, chats: &[Jid]) -> Result<()> {
+        let mutations = chats
+            .iter()
+            .map(|chat| chat_mutation("mute", &chat.to_string(), serde_json::json!({ "muted": false })))
+            .collect();
+        self.push_batched(Collection::RegularHigh, mutations)
+    }
+
+    /// Menandai chat sudah dibaca
+    pub fn mark_chats_read(&self, chats: &[Jid]) -> Result<()> {
+        let chats: Vec<String> = chats.iter().map(|chat| chat.to_string()).collect();
+        self.mark_read_by_jid(chats)
+    }
+
+    /// Menandai semua chat yang memiliki pesan belum dibaca sebagai sudah dibaca
+    pub fn mark_all_read(&self) -> Result<usize> {
+        let chats = self.unread.lock().unwrap().chats();
+        let count = chats.len();
+        self.mark_read_by_jid(chats)?;
+        Ok(count)
+    }
+
+    fn mark_read_by_jid(&self, chats: Vec<String>) -> Result<()> {
+        let mutations = chats
+            .iter()
+            .map(|chat| chat_mutation("markChatAsRead", chat, serde_json::json!({ "read": true })))
+            .collect();
+        self.push_batched(Collection::RegularLow, mutations)?;
+
+        let mut unread = self.unread.lock().unwrap();
+        for chat in &chats {
+            unread.clear(chat);
+        }
+        Ok(())
+    }
+
+    /// Menandai chat belum dibaca (titik hijau di daftar chat)
+    pub fn mark_chat_unread(&self, chat: &Jid) -> Result<()> {
+        let chat = chat.to_string();
+        let range = message_range(self.recent.lock().unwrap().recent(&chat, 1).last());
+        let mutation = chat_mutation("markChatAsRead", &chat, serde_json::json!({ "read": false, "messageRange": range }));
+        self.push_app_state(Collection::RegularLow, vec![mutation])
+    }
+
+    /// Mengosongkan isi chat di semua perangkat; pesan berbintang ikut
+    /// dihapus jika `delete_starred`
+    pub fn clear_chat(&self, chat: &Jid, delete_starred: bool) -> Result<()> {
+        let chat = chat.to_string();
+        let range = message_range(self.recent.lock().unwrap().recent(&chat, 1).last());
+        let mutation = Mutation::set(clear_chat_index(&chat, delete_starred), serde_json::json!({ "messageRange": range }));
+        self.push_app_state(Collection::RegularHigh, vec![mutation])?;
+        self.recent.lock().unwrap().clear(&chat);
+        self.unread.lock().unwrap().clear(&chat);
+        Ok(())
+    }
+
+    /// Menghapus chat dari daftar chat di semua perangkat
+    pub fn delete_chat(&self, chat: &Jid) -> Result<()> {
+        let chat = chat.to_string();
+        let range = message_range(self.recent.lock().unwrap().recent(&chat, 1).last());
+        let mutation = Mutation::set(delete_chat_index(&chat), serde_json::json!({ "messageRange": range }));
+        self.push_app_state(Collection::RegularHigh, vec![mutation])?;
+        self.recent.lock().unwrap().clear(&chat);
+        self.unread.lock().unwrap().clear(&chat);
+        Ok(())
+    }
+
+    /// Arsip, sematan, dan bisukan chat menurut app state terakhir
+    pub fn chat_settings(&self, chat: &Jid) -> ChatSettings {
+        chat_settings(&self.app_state.lock().unwrap(), &chat.to_string())
+    }
+
+    /// Jumlah pesan belum dibaca di chat sejak client berjalan
+    pub fn unread_count(&self, chat: &Jid) -> usize {
+        self.unread.lock().unwrap().count(&chat.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(app_state: &mut AppStateStore, collection: Collection, mutation: Mutation) {
+        let (_, pending) = app_state.patch_node(collection, vec![mutation]).unwrap();
+        assert!(app_state.commit_patch(pending));
+    }
+
+    #[test]
+    fn test_chat_settings_from_written_mutations() {
+        let mut app_state = AppStateStore::new();
+        app_state.add_sync_key(b"key-1", &[9u8; 32]);
+        let chat = "628111@s.whatsapp.net";
+        write(&mut app_state, Collection::RegularLow, chat_mutation("pin_v1", chat, serde_json::json!({ "pinned": true })));
+        write(&mut app_state, Collection::RegularHigh, chat_mutation("mute", chat, serde_json::json!({ "muted": true, "muteEndTimestamp": -1 })));
+
+        let settings = chat_settings(&app_state, chat);
+        assert!(settings.pinned);
+        assert!(!settings.archived);
+        assert_eq!(settings.muted_until, Some(-1));
+        assert!(!settings.marked_unread);
+        write(
+            &mut app_state,
+            Collection::RegularLow,
+            chat_mutation("markChatAsRead", chat, serde_json::json!({ "read": false, "messageRange": message_range(None) })),
+        );
+        assert!(chat_settings(&app_state, chat).marked_unread);
+        assert_eq!(clear_chat_index(chat, false), vec!["clearChat", chat, "0", "0"]);
+        assert_eq!(star_index(chat, "m1", true, None), vec!["star", chat, "m1", "1", "0"]);
+
+        let star = Mutation::set(star_index("123-456@g.us", "m2", false, Some(chat)), serde_json::json!({ "starred": true }));
+        match mutation_event(&star) {
+            Some(Event::MessageStarred { key, starred: true }) => {
+                assert_eq!((key.remote_jid.as_str(), key.id.as_str(), key.from_me), ("123-456@g.us", "m2", false));
+                assert_eq!(key.participant.as_deref(), Some(chat));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert!(mutation_event(&chat_mutation("archive", chat, serde_json::json!({ "archived": true }))).is_none());
+    }
+}
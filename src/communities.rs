@@ -0,0 +1,218 @@
+//! Komunitas WhatsApp: grup induk dan subgrup yang tertaut
+//!
+//! Komunitas adalah grup induk (`@g.us`) dengan subgrup tertaut. Salah satu
+//! subgrup adalah grup pengumuman default; pesan di grup itu dikirim ke
+//! aplikasi sebagai `Event::CommunityAnnouncement`. JID komunitas tidak bisa
+//! dibedakan dari bentuknya, jadi client mencatat grup induk dan grup
+//! pengumuman dari metadata yang diterima (`CommunityRegistry`).
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::errors::*;
+use crate::groups::{self, GroupMetadata};
+use crate::iq;
+use crate::messages::WebMessageInfo;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{utils, Event, Jid, WhatsAppClient};
+
+/// Subgrup yang tertaut ke komunitas
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkedGroup {
+    pub jid: Jid,
+    pub subject: String,
+    /// Grup pengumuman default komunitas
+    pub is_announcement: bool,
+}
+
+/// Grup induk komunitas dan grup pengumumannya yang diketahui client
+#[derive(Default)]
+pub struct CommunityRegistry {
+    parents: HashSet<String>,
+    /// Grup pengumuman -> grup induk
+    announcements: HashMap<String, String>,
+}
+
+impl CommunityRegistry {
+    pub fn new() -> Self {
+        CommunityRegistry::default()
+    }
+
+    /// Mencatat informasi komunitas dari metadata grup
+    pub fn record(&mut self, meta: &GroupMetadata) {
+        if meta.is_community {
+            self.parents.insert(meta.jid.to_string());
+        }
+        if let Some(ref parent) = meta.parent_group {
+            self.parents.insert(parent.to_string());
+            if meta.is_default_subgroup {
+                self.announcements.insert(meta.jid.to_string(), parent.to_string());
+            }
+        }
+    }
+
+    pub fn record_subgroups(&mut self, parent: &str, subgroups: &[LinkedGroup]) {
+        self.parents.insert(parent.to_string());
+        for group in subgroups.iter().filter(|group| group.is_announcement) {
+            self.announcements.insert(group.jid.to_string(), parent.to_string());
+        }
+    }
+
+    pub fn is_community(&self, jid: &str) -> bool {
+        self.parents.contains(jid)
+    }
+
+    /// Grup induk jika `group` adalah grup pengumuman komunitas
+    pub fn community_of_announcement(&self, group: &str) -> Option<&str> {
+        self.announcements.get(group).map(|parent| parent.as_str())
+    }
+}
+
+/// Event untuk pesan di grup pengumuman komunitas
+pub fn announcement_event(registry: &CommunityRegistry, web_message: &WebMessageInfo) -> Option<Event> {
+    let community = registry.community_of_announcement(&web_message.key.remote_jid)?;
+    Some(Event::CommunityAnnouncement {
+        community: Jid::from_string(community).ok()?,
+        message: web_message.clone(),
+    })
+}
+
+/// Handler `iq type="result"` yang mencatat komunitas dari metadata grup
+pub fn metadata_handler(registry: Arc<Mutex<CommunityRegistry>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if let Some(meta) = node.get_child("group").and_then(|group| groups::parse_group_metadata(group).ok()) {
+            registry.lock().unwrap().record(&meta);
+        }
+        Ok(())
+    }
+}
+
+fn community_iq(to: &str, iq_type: &str, child: Node) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "w:g2")
+        .attr("type", iq_type)
+        .attr("to", to)
+        .children(vec![child])
+}
+
+/// IQ untuk membuat komunitas
+pub fn create_community_node(name: &str, description: &str) -> Node {
+    community_iq(
+        "g.us",
+        "set",
+        Node::new("create").attr("subject", name).children(vec![
+            Node::new("description")
+                .attr("id", &utils::generate_message_id())
+                .children(vec![Node::new("body").bytes(description.as_bytes().to_vec())]),
+            Node::new("parent").attr("default_membership_approval_mode", "request_required"),
+            Node::new("allow_non_admin_sub_group_creation"),
+        ]),
+    )
+}
+
+/// IQ untuk mengambil subgrup komunitas
+pub fn subgroups_node(community: &Jid) -> Node {
+    community_iq(&community.to_string(), "get", Node::new("sub_groups"))
+}
+
+/// IQ untuk menautkan (`link = true`) atau melepas subgrup
+pub fn link_node(community: &Jid, groups: &[Jid], link: bool) -> Node {
+    let entries = groups.iter().map(|jid| Node::new("group").attr("jid", &jid.to_string())).collect();
+    let child = if link {
+        Node::new("links").children(vec![Node::new("link").attr("link_type", "sub_group").children(entries)])
+    } else {
+        Node::new("unlink").attr("unlink_type", "sub_group").children(entries)
+    };
+    community_iq(&community.to_string(), "set", child)
+}
+
+/// Membaca `<sub_groups><group id subject [default_sub_group]/>...</sub_groups>`
+pub fn parse_subgroups(response: &Node) -> Vec<LinkedGroup> {
+    let list = match response.get_child("sub_groups") {
+        Some(list) => list,
+        None => return Vec::new(),
+    };
+    list.get_children()
+        .iter()
+        .filter(|child| child.tag == "group")
+        .filter_map(|group| {
+            let id = group.get_attr("id")?;
+            let jid = if id.contains('@') { Jid::from_string(id).ok()? } else { Jid::group(id) };
+            Some(LinkedGroup {
+                jid,
+                subject: group.get_attr("subject").unwrap_or_default().to_string(),
+                is_announcement: group.get_child("default_sub_group").is_some(),
+            })
+        })
+        .collect()
+}
+
+impl WhatsAppClient {
+    /// Membuat komunitas baru; server juga membuat grup pengumuman default
+    pub fn create_community(&self, name: &str, description: &str) -> Result<GroupMetadata> {
+        if name.trim().is_empty() {
+            return Err("Community name must not be empty".into());
+        }
+        let response = self.query(&create_community_node(name, description), iq::DEFAULT_QUERY_TIMEOUT)?;
+        let group = response.get_child("group").ok_or("Create community response without group node")?;
+        let meta = groups::parse_group_metadata(group)?;
+        self.communities.lock().unwrap().record(&meta);
+        self.groups.lock().unwrap().set_subject(&meta.jid.to_string(), &meta.subject);
+        Ok(meta)
+    }
+
+    /// Subgrup yang tertaut ke komunitas, termasuk grup pengumuman
+    pub fn community_subgroups(&self, community: &Jid) -> Result<Vec<LinkedGroup>> {
+        let response = self.query(&subgroups_node(community), iq::DEFAULT_QUERY_TIMEOUT)?;
+        let subgroups = parse_subgroups(&response);
+        self.communities.lock().unwrap().record_subgroups(&community.to_string(), &subgroups);
+        Ok(subgroups)
+    }
+
+    /// Menautkan grup yang sudah ada ke komunitas
+    pub fn link_groups(&self, community: &Jid, groups: &[Jid]) -> Result<()> {
+        self.change_links(community, groups, true)
+    }
+
+    /// Melepas grup dari komunitas
+    pub fn unlink_groups(&self, community: &Jid, groups: &[Jid]) -> Result<()> {
+        self.change_links(community, groups, false)
+    }
+
+    fn change_links(&self, community: &Jid, groups: &[Jid], link: bool) -> Result<()> {
+        if groups.is_empty() || groups.iter().any(|jid| !jid.is_group()) {
+            return Err("Only groups can be linked to a community".into());
+        }
+        self.query(&link_node(community, groups, link), iq::DEFAULT_QUERY_TIMEOUT)?;
+        self.communities.lock().unwrap().parents.insert(community.to_string());
+        Ok(())
+    }
+
+    /// Apakah `jid` dikenal sebagai grup induk komunitas
+    pub fn is_community(&self, jid: &Jid) -> bool {
+        self.communities.lock().unwrap().is_community(&jid.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_announcement_group_routes_to_community() {
+        let response = Node::new("iq").children(vec![Node::new("sub_groups").children(vec![
+            Node::new("group").attr("id", "111").attr("subject", "Pengumuman").children(vec![Node::new("default_sub_group")]),
+            Node::new("group").attr("id", "222").attr("subject", "Obrolan"),
+        ])]);
+        let subgroups = parse_subgroups(&response);
+        assert_eq!(subgroups.len(), 2);
+
+        let mut registry = CommunityRegistry::new();
+        registry.record_subgroups("999@g.us", &subgroups);
+        assert!(registry.is_community("999@g.us"));
+        assert_eq!(registry.community_of_announcement("111@g.us"), Some("999@g.us"));
+        assert_eq!(registry.community_of_announcement("222@g.us"), None);
+    }
+}
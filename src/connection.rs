@@ -652,7 +652,7 @@ impl<H: RustdiHandler<H> + Send + Sync> RustdiConnection<H> {
     }
 
     pub fn get_group_metadata(&self, jid: &Jid, callback: Box<dyn Fn(Option<GroupMetadata>) + Send + Sync>) {
-        debug_assert!(jid.is_group);
+        debug_assert!(jid.is_group());
         self.send_json_message(json_protocol::build_group_metadata_request(jid), Box::new(move |response, _| {
             callback(json_protocol::parse_group_metadata_response(&response).ok());
         }));
@@ -0,0 +1,140 @@
+//! Deduplikasi stanza masuk
+//!
+//! Setelah reconnect atau saat melanjutkan antrean offline, server bisa
+//! mengirim ulang stanza yang sudah diterima. `StanzaDedup` mengingat
+//! identitas stanza (tag, `from`, `participant`, `type`, id, dan `count`
+//! retry receipt) selama `ttl` dan paling banyak `capacity` stanza, sehingga
+//! router membuang kiriman ulang sebelum handler mana pun menerimanya.
+//! Receipt `read` yang menyusul receipt `delivery` dengan id yang sama, atau
+//! retry receipt dengan `count` berikutnya, tetap diteruskan.
+
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::node_protocol::Node;
+
+/// Jumlah stanza default yang diingat
+pub const DEFAULT_DEDUP_CAPACITY: usize = 4096;
+/// Lama default sebuah stanza diingat
+pub const DEFAULT_DEDUP_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Identitas stanza untuk deduplikasi
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct StanzaKey {
+    tag: String,
+    from: Option<String>,
+    participant: Option<String>,
+    node_type: Option<String>,
+    id: String,
+    retry_count: Option<String>,
+}
+
+impl StanzaKey {
+    fn of(node: &Node) -> Option<Self> {
+        let attr = |name: &str| node.get_attr(name).map(String::from);
+        Some(StanzaKey {
+            tag: node.tag.clone(),
+            from: attr("from"),
+            participant: attr("participant"),
+            node_type: attr("type"),
+            id: attr("id")?,
+            retry_count: node.get_child("retry").and_then(|retry| retry.get_attr("count")).map(String::from),
+        })
+    }
+}
+
+/// Jendela deduplikasi stanza
+pub struct StanzaDedup {
+    capacity: usize,
+    ttl: Duration,
+    seen: HashSet<StanzaKey>,
+    order: VecDeque<(StanzaKey, Instant)>,
+}
+
+impl StanzaDedup {
+    /// `capacity` 0 mematikan deduplikasi
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        StanzaDedup { capacity, ttl, seen: HashSet::new(), order: VecDeque::new() }
+    }
+
+    /// `true` jika stanza baru pertama kali terlihat dalam jendela. Stanza
+    /// tanpa id selalu diteruskan.
+    pub fn first_seen(&mut self, node: &Node, now: Instant) -> bool {
+        while let Some((_, seen_at)) = self.order.front() {
+            if now.duration_since(*seen_at) < self.ttl {
+                break;
+            }
+            if let Some((key, _)) = self.order.pop_front() {
+                self.seen.remove(&key);
+            }
+        }
+
+        let key = match StanzaKey::of(node) {
+            Some(key) if self.capacity > 0 => key,
+            _ => return true,
+        };
+        if !self.seen.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back((key, now));
+        if self.order.len() > self.capacity {
+            if let Some((old, _)) = self.order.pop_front() {
+                self.seen.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+impl Default for StanzaDedup {
+    fn default() -> Self {
+        StanzaDedup::new(DEFAULT_DEDUP_CAPACITY, DEFAULT_DEDUP_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(node_type: Option<&str>, participant: &str) -> Node {
+        let receipt = Node::new("receipt").attr("id", "3EB0A1").attr("from", "120363@g.us").attr("participant", participant);
+        match node_type {
+            Some(node_type) => receipt.attr("type", node_type),
+            None => receipt,
+        }
+    }
+
+    #[test]
+    fn test_redelivery_within_window_and_expiry() {
+        let start = Instant::now();
+        let mut dedup = StanzaDedup::new(2, Duration::from_secs(60));
+        let message = |id: &str| Node::new("message").attr("id", id).attr("from", "628111@s.whatsapp.net");
+        assert!(dedup.first_seen(&message("A"), start));
+        assert!(!dedup.first_seen(&message("A"), start + Duration::from_secs(5)));
+        // Tag berbeda dengan id yang sama bukan kiriman ulang
+        assert!(dedup.first_seen(&Node::new("notification").attr("id", "A"), start));
+
+        // Kapasitas 2: "A" terlupa setelah stanza ketiga
+        assert!(dedup.first_seen(&message("B"), start));
+        assert!(dedup.first_seen(&message("A"), start));
+        // Setelah TTL semua terlupa
+        assert!(dedup.first_seen(&message("B"), start + Duration::from_secs(61)));
+        assert!(dedup.first_seen(&Node::new("iq"), start));
+    }
+
+    #[test]
+    fn test_follow_up_receipts_are_not_duplicates() {
+        let now = Instant::now();
+        let mut dedup = StanzaDedup::default();
+        assert!(dedup.first_seen(&receipt(None, "628111@s.whatsapp.net"), now));
+        assert!(dedup.first_seen(&receipt(Some("read"), "628111@s.whatsapp.net"), now));
+        assert!(dedup.first_seen(&receipt(Some("read"), "628222@s.whatsapp.net"), now));
+        assert!(!dedup.first_seen(&receipt(Some("read"), "628111@s.whatsapp.net"), now));
+
+        // Retry berikutnya membawa `count` baru
+        let retry = |count: &str| receipt(Some("retry"), "628111@s.whatsapp.net").children(vec![Node::new("retry").attr("count", count)]);
+        assert!(dedup.first_seen(&retry("1"), now));
+        assert!(dedup.first_seen(&retry("2"), now));
+        assert!(!dedup.first_seen(&retry("2"), now));
+    }
+}
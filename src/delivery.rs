@@ -0,0 +1,422 @@
+//! Laporan pengiriman untuk pengiriman massal (bulk) dan broadcast
+//!
+//! Setiap job menyimpan status per penerima dan diperbarui otomatis dari
+//! receipt yang diterima dari server. Ketika semua penerima mencapai status
+//! akhir (delivered, read, atau failed), `Event::DeliveryReportCompleted` dikirim.
+//!
+//! Selain job, setiap pesan keluar dilacak statusnya (`MessageStatus`) dari
+//! ack server dan receipt penerima; setiap kenaikan status dikirim sebagai
+//! `Event::MessageStatusChanged`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::errors::*;
+use crate::latency::MessageTimings;
+use crate::node_protocol::Node;
+use crate::routing::{self, NodeContext};
+use crate::{Event, Jid, WhatsAppClient};
+
+/// Status pengiriman untuk satu penerima
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecipientStatus {
+    Queued,
+    Sent,
+    Delivered,
+    Read,
+    Failed(String),
+}
+
+impl RecipientStatus {
+    /// Urutan status; receipt yang lebih lama tidak boleh menurunkan status
+    fn rank(&self) -> u8 {
+        match self {
+            RecipientStatus::Queued => 0,
+            RecipientStatus::Sent => 1,
+            RecipientStatus::Delivered => 2,
+            RecipientStatus::Read => 3,
+            RecipientStatus::Failed(_) => 4,
+        }
+    }
+
+    /// Status akhir: tidak akan berubah lagi untuk keperluan laporan
+    pub fn is_final(&self) -> bool {
+        matches!(self, RecipientStatus::Delivered | RecipientStatus::Read | RecipientStatus::Failed(_))
+    }
+}
+
+/// Status satu pesan keluar; hanya bisa naik
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MessageStatus {
+    /// Sudah ditulis ke socket, belum di-ack server
+    Pending,
+    ServerAck,
+    Delivered,
+    Read,
+    /// Voice note diputar atau media view once dibuka
+    Played,
+}
+
+/// Jumlah pesan keluar terakhir yang dilacak statusnya
+pub const TRACKED_MESSAGES: usize = 5000;
+
+/// Status satu penerima dalam job
+#[derive(Debug, Clone)]
+pub struct RecipientDelivery {
+    pub recipient: Jid,
+    pub message_id: Option<String>,
+    pub status: RecipientStatus,
+    /// Waktu tiap tahap pipeline, diisi oleh `WhatsAppClient::delivery_report`
+    pub timings: Option<MessageTimings>,
+    /// Nama tampilan penerima dari `NameResolver`, diisi oleh `WhatsAppClient::delivery_report`
+    pub display_name: Option<String>,
+}
+
+/// Ringkasan jumlah penerima per status
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeliverySummary {
+    pub job_id: String,
+    pub total: usize,
+    pub queued: usize,
+    pub sent: usize,
+    pub delivered: usize,
+    pub read: usize,
+    pub failed: usize,
+}
+
+/// Laporan pengiriman untuk satu job bulk/broadcast
+#[derive(Debug, Clone)]
+pub struct DeliveryReport {
+    pub job_id: String,
+    pub recipients: Vec<RecipientDelivery>,
+    completed_notified: bool,
+}
+
+impl DeliveryReport {
+    pub fn new(job_id: &str, recipients: &[Jid]) -> Self {
+        DeliveryReport {
+            job_id: job_id.to_string(),
+            recipients: recipients
+                .iter()
+                .map(|jid| RecipientDelivery {
+                    recipient: jid.clone(),
+                    message_id: None,
+                    status: RecipientStatus::Queued,
+                    timings: None,
+                    display_name: None,
+                })
+                .collect(),
+            completed_notified: false,
+        }
+    }
+
+    /// Menghitung ringkasan status
+    pub fn summary(&self) -> DeliverySummary {
+        let mut summary = DeliverySummary {
+            job_id: self.job_id.clone(),
+            total: self.recipients.len(),
+            ..Default::default()
+        };
+
+        for entry in &self.recipients {
+            match entry.status {
+                RecipientStatus::Queued => summary.queued += 1,
+                RecipientStatus::Sent => summary.sent += 1,
+                RecipientStatus::Delivered => summary.delivered += 1,
+                RecipientStatus::Read => summary.read += 1,
+                RecipientStatus::Failed(_) => summary.failed += 1,
+            }
+        }
+
+        summary
+    }
+
+    /// Semua penerima sudah mencapai status akhir
+    pub fn is_complete(&self) -> bool {
+        self.recipients.iter().all(|entry| entry.status.is_final())
+    }
+
+    /// Daftar penerima yang gagal beserta alasannya
+    pub fn failures(&self) -> Vec<(Jid, String)> {
+        self.recipients
+            .iter()
+            .filter_map(|entry| match entry.status {
+                RecipientStatus::Failed(ref reason) => Some((entry.recipient.clone(), reason.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Pelacak semua job pengiriman yang sedang berjalan
+#[derive(Default)]
+pub struct DeliveryTracker {
+    reports: HashMap<String, DeliveryReport>,
+    /// message id -> (job id, index penerima)
+    message_index: HashMap<String, (String, usize)>,
+    statuses: HashMap<String, MessageStatus>,
+    /// Urutan pesan untuk membuang yang terlama
+    tracked: VecDeque<String>,
+}
+
+impl DeliveryTracker {
+    pub fn new() -> Self {
+        DeliveryTracker::default()
+    }
+
+    /// Mendaftarkan job baru
+    pub fn start_job(&mut self, job_id: &str, recipients: &[Jid]) {
+        self.reports.insert(job_id.to_string(), DeliveryReport::new(job_id, recipients));
+    }
+
+    /// Mencatat hasil pengiriman ke socket untuk penerima ke-`index`
+    pub fn record_send(&mut self, job_id: &str, index: usize, result: std::result::Result<String, String>) {
+        if let Some(report) = self.reports.get_mut(job_id) {
+            if let Some(entry) = report.recipients.get_mut(index) {
+                match result {
+                    Ok(message_id) => {
+                        self.message_index.insert(message_id.clone(), (job_id.to_string(), index));
+                        entry.message_id = Some(message_id);
+                        entry.status = RecipientStatus::Sent;
+                    }
+                    Err(reason) => entry.status = RecipientStatus::Failed(reason),
+                }
+            }
+        }
+    }
+
+    /// Memperbarui status dari receipt. Mengembalikan ringkasan jika job baru saja selesai.
+    pub fn update_status(&mut self, message_id: &str, status: RecipientStatus) -> Option<DeliverySummary> {
+        let (job_id, index) = self.message_index.get(message_id)?.clone();
+        let report = self.reports.get_mut(&job_id)?;
+        let entry = report.recipients.get_mut(index)?;
+
+        if status.rank() > entry.status.rank() {
+            entry.status = status;
+        }
+
+        if report.is_complete() && !report.completed_notified {
+            report.completed_notified = true;
+            return Some(report.summary());
+        }
+        None
+    }
+
+    /// Mulai melacak status pesan keluar
+    pub fn track(&mut self, message_id: &str) {
+        if self.statuses.insert(message_id.to_string(), MessageStatus::Pending).is_none() {
+            self.tracked.push_back(message_id.to_string());
+        }
+        while self.tracked.len() > TRACKED_MESSAGES {
+            if let Some(oldest) = self.tracked.pop_front() {
+                self.statuses.remove(&oldest);
+            }
+        }
+    }
+
+    /// Menaikkan status pesan; mengembalikan status baru jika berubah
+    pub fn advance(&mut self, message_id: &str, status: MessageStatus) -> Option<MessageStatus> {
+        let current = self.statuses.get_mut(message_id)?;
+        if status <= *current {
+            return None;
+        }
+        *current = status;
+        Some(status)
+    }
+
+    pub fn message_status(&self, message_id: &str) -> Option<MessageStatus> {
+        self.statuses.get(message_id).copied()
+    }
+
+    /// Mengambil salinan laporan
+    pub fn report(&self, job_id: &str) -> Option<DeliveryReport> {
+        self.reports.get(job_id).cloned()
+    }
+
+    /// Menghapus job beserta index pesannya
+    pub fn remove_job(&mut self, job_id: &str) -> Option<DeliveryReport> {
+        self.message_index.retain(|_, (job, _)| job != job_id);
+        self.reports.remove(job_id)
+    }
+}
+
+/// Status penerima dari atribut `type` pada receipt
+fn receipt_status(node: &Node) -> Option<RecipientStatus> {
+    match node.get_attr("type") {
+        None | Some("delivery") => Some(RecipientStatus::Delivered),
+        Some("read") | Some("read-self") | Some("played") => Some(RecipientStatus::Read),
+        Some("error") => Some(RecipientStatus::Failed("Recipient reported an error".to_string())),
+        _ => None,
+    }
+}
+
+/// Status pesan dari atribut `type` pada receipt
+fn receipt_message_status(node: &Node) -> Option<MessageStatus> {
+    match node.get_attr("type") {
+        None | Some("delivery") => Some(MessageStatus::Delivered),
+        Some("read") | Some("read-self") => Some(MessageStatus::Read),
+        Some("played") => Some(MessageStatus::Played),
+        _ => None,
+    }
+}
+
+/// Id pesan dalam receipt; satu receipt bisa berisi beberapa id di dalam <list><item id=.../></list>
+pub(crate) fn receipt_message_ids(node: &Node) -> Vec<&str> {
+    let mut message_ids: Vec<&str> = node.get_attr("id").into_iter().collect();
+    if let Some(list) = node.get_child("list") {
+        message_ids.extend(list.get_children().iter().filter_map(|item| item.get_attr("id")));
+    }
+    message_ids
+}
+
+/// Handler node `receipt` yang memperbarui tracker
+pub fn receipt_handler(tracker: Arc<Mutex<DeliveryTracker>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if let Some(ack) = routing::ack_for(node) {
+            ctx.send_node(&ack)?;
+        }
+
+        let mut tracker = tracker.lock().unwrap();
+        if let Some(status) = receipt_message_status(node) {
+            for message_id in receipt_message_ids(node) {
+                if let Some(status) = tracker.advance(message_id, status) {
+                    ctx.emit(Event::MessageStatusChanged {
+                        message_id: message_id.to_string(),
+                        status,
+                    });
+                }
+            }
+        }
+
+        let status = match receipt_status(node) {
+            Some(status) => status,
+            None => return Ok(()),
+        };
+        for message_id in receipt_message_ids(node) {
+            if let Some(summary) = tracker.update_status(message_id, status.clone()) {
+                ctx.emit(Event::DeliveryReportCompleted(summary));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Handler `ack` yang menandai pesan sudah diterima server
+pub fn ack_handler(tracker: Arc<Mutex<DeliveryTracker>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if !matches!(node.get_attr("class"), None | Some("message")) || node.get_attr("error").is_some() {
+            return Ok(());
+        }
+        if let Some(message_id) = node.get_attr("id") {
+            if let Some(status) = tracker.lock().unwrap().advance(message_id, MessageStatus::ServerAck) {
+                ctx.emit(Event::MessageStatusChanged {
+                    message_id: message_id.to_string(),
+                    status,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Status terakhir pesan keluar `message_id`; `None` jika tidak dilacak
+    pub fn message_status(&self, message_id: &str) -> Option<MessageStatus> {
+        self.delivery.lock().unwrap().message_status(message_id)
+    }
+
+    /// Mengirim pesan teks yang sama ke banyak penerima.
+    /// Mengembalikan job id untuk `delivery_report`.
+    pub fn send_bulk_text(&self, recipients: &[Jid], text: &str) -> Result<String> {
+        if recipients.is_empty() {
+            return Err("Bulk send requires at least one recipient".into());
+        }
+
+        let job_id = format!("job_{}", crate::utils::generate_message_id());
+        self.delivery.lock().unwrap().start_job(&job_id, recipients);
+
+        for (index, recipient) in recipients.iter().enumerate() {
+            let result = self.send_text_message(recipient, text).map_err(|e| e.to_string());
+            self.delivery.lock().unwrap().record_send(&job_id, index, result);
+        }
+
+        // Semua gagal: job langsung selesai tanpa menunggu receipt
+        let report = self.delivery.lock().unwrap().report(&job_id);
+        if let Some(report) = report {
+            if report.is_complete() {
+                self.event_tx.send(Event::DeliveryReportCompleted(report.summary())).ok();
+            }
+        }
+
+        Ok(job_id)
+    }
+
+    /// Mengambil laporan pengiriman untuk job bulk/broadcast
+    pub fn delivery_report(&self, job_id: &str) -> Option<DeliveryReport> {
+        let mut report = self.delivery.lock().unwrap().report(job_id)?;
+        let latency = self.latency.lock().unwrap();
+        let names = Arc::clone(&self.names.lock().unwrap());
+        for entry in report.recipients.iter_mut() {
+            entry.timings = entry.message_id.as_deref().and_then(|id| latency.timings(id));
+            entry.display_name = Some(names.display(&entry.recipient));
+        }
+        Some(report)
+    }
+
+    /// Menghapus laporan yang sudah tidak diperlukan
+    pub fn clear_delivery_report(&self, job_id: &str) -> Option<DeliveryReport> {
+        self.delivery.lock().unwrap().remove_job(job_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jid(n: &str) -> Jid {
+        Jid::user(n)
+    }
+
+    #[test]
+    fn test_job_completes_when_all_final() {
+        let mut tracker = DeliveryTracker::new();
+        tracker.start_job("job", &[jid("1"), jid("2"), jid("3")]);
+        tracker.record_send("job", 0, Ok("m1".to_string()));
+        tracker.record_send("job", 1, Ok("m2".to_string()));
+        tracker.record_send("job", 2, Err("No active connection".to_string()));
+
+        assert!(tracker.update_status("m1", RecipientStatus::Delivered).is_none());
+        let summary = tracker.update_status("m2", RecipientStatus::Read).unwrap();
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.delivered, 1);
+        assert_eq!(summary.read, 1);
+        assert_eq!(summary.failed, 1);
+
+        // Notifikasi selesai hanya sekali
+        assert!(tracker.update_status("m1", RecipientStatus::Read).is_none());
+    }
+
+    #[test]
+    fn test_status_never_regresses() {
+        let mut tracker = DeliveryTracker::new();
+        tracker.start_job("job", &[jid("1")]);
+        tracker.record_send("job", 0, Ok("m1".to_string()));
+        tracker.update_status("m1", RecipientStatus::Read);
+        tracker.update_status("m1", RecipientStatus::Delivered);
+
+        let report = tracker.report("job").unwrap();
+        assert_eq!(report.recipients[0].status, RecipientStatus::Read);
+    }
+
+    #[test]
+    fn test_message_status_machine() {
+        let mut tracker = DeliveryTracker::new();
+        tracker.track("m1");
+        assert_eq!(tracker.advance("m1", MessageStatus::ServerAck), Some(MessageStatus::ServerAck));
+        assert_eq!(tracker.advance("m1", MessageStatus::Read), Some(MessageStatus::Read));
+        // Receipt delivered yang datang terlambat diabaikan
+        assert_eq!(tracker.advance("m1", MessageStatus::Delivered), None);
+        assert_eq!(tracker.message_status("m1"), Some(MessageStatus::Read));
+        assert_eq!(tracker.advance("unknown", MessageStatus::Read), None);
+    }
+}
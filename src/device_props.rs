@@ -0,0 +1,102 @@
+//! Identitas perangkat companion
+//!
+//! Platform, nama browser, dan OS dikirim saat registrasi dan ditampilkan di
+//! daftar Perangkat Tertaut ponsel sebagai `Browser (OS)`. Gateway yang
+//! menautkan banyak akun bisa memberi nama berbeda per tenant lewat
+//! `WhatsAppClientBuilder::with_device_props`.
+
+use crate::errors::*;
+
+/// Panjang nama browser dan OS
+pub const MAX_LABEL_LENGTH: usize = 50;
+
+/// Jenis platform companion (`DeviceProps.PlatformType`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Platform {
+    Chrome,
+    Firefox,
+    Opera,
+    Safari,
+    Edge,
+    Desktop,
+}
+
+impl Platform {
+    /// Id platform pada `companion_platform_id`
+    pub fn id(&self) -> u32 {
+        match self {
+            Platform::Chrome => 1,
+            Platform::Firefox => 2,
+            Platform::Opera => 4,
+            Platform::Safari => 5,
+            Platform::Edge => 6,
+            Platform::Desktop => 7,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::Chrome => "chrome",
+            Platform::Firefox => "firefox",
+            Platform::Opera => "opera",
+            Platform::Safari => "safari",
+            Platform::Edge => "edge",
+            Platform::Desktop => "desktop",
+        }
+    }
+}
+
+/// Identitas yang ditampilkan untuk sesi ini
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceProps {
+    pub platform: Platform,
+    pub browser: String,
+    pub os: String,
+}
+
+impl DeviceProps {
+    pub fn new(platform: Platform, browser: &str, os: &str) -> Self {
+        DeviceProps { platform, browser: browser.to_string(), os: os.to_string() }
+    }
+
+    /// Label di daftar Perangkat Tertaut, mis. `Chrome (Linux)`
+    pub fn display_name(&self) -> String {
+        format!("{} ({})", self.browser, self.os)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for (field, value) in [("Browser name", &self.browser), ("OS name", &self.os)] {
+            if value.trim().is_empty() {
+                return Err(format!("{} must not be empty", field).into());
+            }
+            if value.chars().count() > MAX_LABEL_LENGTH {
+                return Err(format!("{} is longer than {} characters", field, MAX_LABEL_LENGTH).into());
+            }
+            if value.chars().any(|c| c.is_control()) {
+                return Err(format!("{} contains control characters", field).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for DeviceProps {
+    fn default() -> Self {
+        DeviceProps::new(Platform::Chrome, "Chrome", "Linux")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_name_and_validation() {
+        assert_eq!(DeviceProps::default().display_name(), "Chrome (Linux)");
+        let tenant = DeviceProps::new(Platform::Desktop, "Toko Budi", "Gateway");
+        assert_eq!((tenant.platform.id(), tenant.display_name().as_str()), (7, "Toko Budi (Gateway)"));
+        assert!(tenant.validate().is_ok());
+        assert!(DeviceProps::new(Platform::Chrome, " ", "Linux").validate().is_err());
+        assert!(DeviceProps::new(Platform::Chrome, "Chrome", &"x".repeat(MAX_LABEL_LENGTH + 1)).validate().is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! Daftar perangkat kontak (multi-device)
+//!
+//! Pesan dienkripsi per perangkat penerima: ponsel (device 0) dan setiap
+//! perangkat tertaut. Daftar perangkat diambil lewat query usync `devices`,
+//! di-cache per user oleh `PendingOutbox`, dan dibuang saat server mengirim
+//! `notification type="devices"` (perangkat ditambah atau dihapus) sehingga
+//! pesan berikutnya mengambil daftar baru.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::errors::*;
+use crate::iq;
+use crate::node_protocol::Node;
+use crate::outbox::PendingOutbox;
+use crate::routing::{self, NodeContext};
+use crate::{utils, Jid, WhatsAppClient};
+
+/// Id perangkat utama (ponsel)
+pub const PRIMARY_DEVICE: u32 = 0;
+
+/// JID perangkat: `user:device@server`; perangkat utama memakai JID user
+pub fn device_jid(user: &str, device: u32) -> String {
+    if device == PRIMARY_DEVICE {
+        return user.to_string();
+    }
+    match user.split_once('@') {
+        Some((id, server)) => format!("{}:{}@{}", id, device, server),
+        None => user.to_string(),
+    }
+}
+
+/// Memecah JID perangkat menjadi (JID user, id perangkat)
+pub fn split_device_jid(jid: &str) -> (String, u32) {
+    match Jid::from_string(jid) {
+        Ok(parsed) => (parsed.to_non_ad().to_string(), parsed.device.map_or(PRIMARY_DEVICE, u32::from)),
+        Err(_) => (jid.to_string(), PRIMARY_DEVICE),
+    }
+}
+
+/// Daftar perangkat per user
+#[derive(Default)]
+pub struct DeviceCache {
+    lists: HashMap<String, Vec<u32>>,
+}
+
+impl DeviceCache {
+    pub fn new() -> Self {
+        DeviceCache::default()
+    }
+
+    pub fn get(&self, user: &str) -> Option<&[u32]> {
+        self.lists.get(user).map(|devices| devices.as_slice())
+    }
+
+    pub fn set(&mut self, user: &str, mut devices: Vec<u32>) {
+        devices.sort_unstable();
+        devices.dedup();
+        self.lists.insert(user.to_string(), devices);
+    }
+
+    /// Membuang satu perangkat (mis. tidak punya prekey)
+    pub fn remove_device(&mut self, device_jid: &str) {
+        let (user, device) = split_device_jid(device_jid);
+        if let Some(devices) = self.lists.get_mut(&user) {
+            devices.retain(|id| *id != device);
+        }
+    }
+
+    pub fn invalidate(&mut self, user: &str) -> bool {
+        self.lists.remove(user).is_some()
+    }
+
+    /// JID semua perangkat `user`; `None` jika belum diketahui
+    pub fn device_jids(&self, user: &str) -> Option<Vec<String>> {
+        self.get(user).map(|devices| devices.iter().map(|device| device_jid(user, *device)).collect())
+    }
+}
+
+/// IQ usync untuk daftar perangkat `users`
+pub fn device_query_node(id: &str, users: &[String]) -> Node {
+    Node::new("iq")
+        .attr("id", id)
+        .attr("xmlns", "usync")
+        .attr("type", "get")
+        .attr("to", "s.whatsapp.net")
+        .children(vec![Node::new("usync")
+            .attr("sid", &utils::generate_message_id())
+            .attr("mode", "query")
+            .attr("last", "true")
+            .attr("index", "0")
+            .attr("context", "message")
+            .children(vec![
+                Node::new("query").children(vec![Node::new("devices").attr("version", "2")]),
+                Node::new("list").children(users.iter().map(|user| Node::new("user").attr("jid", user)).collect()),
+            ])])
+}
+
+/// Membaca `<usync><list><user jid><devices><device-list><device id/>..`;
+/// JID user dinormalkan (tanpa bagian perangkat, `c.us` dibaca sebagai
+/// `s.whatsapp.net`)
+pub fn parse_device_response(response: &Node) -> Vec<(Jid, Vec<u32>)> {
+    response
+        .get_child("usync")
+        .and_then(|usync| usync.get_child("list"))
+        .map(|list| {
+            list.get_children()
+                .iter()
+                .filter(|user| user.tag == "user")
+                .filter_map(|user| {
+                    let devices = user
+                        .get_child("devices")?
+                        .get_child("device-list")?
+                        .get_children()
+                        .iter()
+                        .filter(|device| device.tag == "device")
+                        .filter_map(|device| device.get_attr("id")?.parse().ok())
+                        .collect();
+                    Some((user.attr_jid("jid").ok()?.to_non_ad(), devices))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Handler `notification type="devices"`: daftar perangkat pengirim dibuang
+pub fn device_notification_handler(outbox: Arc<Mutex<PendingOutbox>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if let Some(ack) = routing::ack_for(node) {
+            ctx.send_node(&ack)?;
+        }
+        if let Some(from) = node.get_attr("from") {
+            outbox.lock().unwrap().invalidate_devices(&split_device_jid(from).0);
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Id perangkat `jid` (0 = ponsel); diambil dari server jika belum di-cache
+    pub fn devices(&self, jid: &Jid) -> Result<Vec<u32>> {
+        if jid.is_group() {
+            return Err("Device lists are only available for contacts".into());
+        }
+        let user = jid.to_non_ad().to_string();
+        if let Some(devices) = self.outbox.lock().unwrap().devices().get(&user) {
+            return Ok(devices.to_vec());
+        }
+
+        let response = self.query(&device_query_node(&utils::generate_message_id(), &[user.clone()]), iq::DEFAULT_QUERY_TIMEOUT)?;
+        let devices = parse_device_response(&response)
+            .into_iter()
+            .find(|(found, _)| *found == jid.to_non_ad())
+            .map(|(_, devices)| devices)
+            .ok_or("Server returned no device list")?;
+        self.outbox.lock().unwrap().set_devices(&user, devices.clone());
+        Ok(devices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_jids() {
+        assert_eq!(device_jid("628111@s.whatsapp.net", 0), "628111@s.whatsapp.net");
+        assert_eq!(device_jid("628111@s.whatsapp.net", 3), "628111:3@s.whatsapp.net");
+        assert_eq!(split_device_jid("628111:3@s.whatsapp.net"), ("628111@s.whatsapp.net".to_string(), 3));
+        assert_eq!(split_device_jid("628111@s.whatsapp.net"), ("628111@s.whatsapp.net".to_string(), 0));
+
+        let response = Node::new("iq").children(vec![Node::new("usync").children(vec![Node::new("list").children(vec![Node::new("user")
+            .attr("jid", "628111@s.whatsapp.net")
+            .children(vec![Node::new("devices").children(vec![Node::new("device-list").children(vec![
+                Node::new("device").attr("id", "0"),
+                Node::new("device").attr("id", "3").attr("key-index", "1"),
+            ])])])])])]);
+        let mut cache = DeviceCache::new();
+        for (user, devices) in parse_device_response(&response) {
+            cache.set(&user.to_string(), devices);
+        }
+        assert_eq!(
+            cache.device_jids("628111@s.whatsapp.net").unwrap(),
+            vec!["628111@s.whatsapp.net".to_string(), "628111:3@s.whatsapp.net".to_string()]
+        );
+        cache.remove_device("628111:3@s.whatsapp.net");
+        assert_eq!(cache.get("628111@s.whatsapp.net"), Some(&[0][..]));
+        assert!(cache.invalidate("628111@s.whatsapp.net"));
+        assert_eq!(cache.device_jids("628111@s.whatsapp.net"), None);
+    }
+
+    #[test]
+    fn test_device_response_jids_are_normalized() {
+        let user = |jid: &str| {
+            Node::new("user").attr("jid", jid).children(vec![Node::new("devices")
+                .children(vec![Node::new("device-list").children(vec![Node::new("device").attr("id", "0"), Node::new("device").attr("id", "7")])])])
+        };
+        // Balasan server memakai `s.whatsapp.net`; setelah decoder menjadi `c.us`
+        let response = Node::new("iq").children(vec![Node::new("usync")
+            .children(vec![Node::new("list").children(vec![user("628111@s.whatsapp.net"), user("628222@c.us")])])]);
+        let lists = parse_device_response(&response);
+        assert_eq!(lists[0], (Jid::user("628111"), vec![0, 7]));
+        assert_eq!(lists[1].0, Jid::user("628222"));
+    }
+}
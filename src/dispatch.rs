@@ -0,0 +1,159 @@
+//! Menjalankan `EventHandler` aplikasi secara terisolasi
+//!
+//! Handler dijalankan di thread worker terpisah dengan batas waktu per event.
+//! Jika handler macet melewati batas waktu, worker ditinggalkan dan diganti
+//! worker baru, lalu `Event::HandlerTimeout` dikirim sebagai diagnostik. Panic di
+//! handler ditangkap dan dilaporkan sebagai `Event::Error`. Event loop protokol
+//! tidak pernah menunggu kode aplikasi.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::{Event, EventHandler};
+
+/// Batas waktu default satu panggilan `handle_event`
+pub const DEFAULT_HANDLER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Nama varian event untuk diagnostik (tanpa payload)
+pub fn event_kind(event: &Event) -> String {
+    let debug = format!("{:?}", event);
+    debug
+        .split(|c: char| c == '(' || c == ' ' || c == '{')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Hasil satu panggilan handler di worker
+enum Outcome {
+    Done,
+    Panicked(String),
+}
+
+struct Worker {
+    tx: mpsc::Sender<Event>,
+    done_rx: mpsc::Receiver<Outcome>,
+}
+
+fn spawn_worker(handler: Arc<dyn EventHandler>) -> Worker {
+    let (tx, rx) = mpsc::channel::<Event>();
+    let (done_tx, done_rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        for event in rx {
+            let outcome = match panic::catch_unwind(AssertUnwindSafe(|| handler.handle_event(event))) {
+                Ok(()) => Outcome::Done,
+                Err(payload) => {
+                    let message = payload
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "unknown panic".to_string());
+                    Outcome::Panicked(message)
+                }
+            };
+            if done_tx.send(outcome).is_err() {
+                // Supervisor sudah meninggalkan worker ini
+                break;
+            }
+        }
+    });
+
+    Worker { tx, done_rx }
+}
+
+/// Menjalankan handler dengan batas waktu dan isolasi panic
+pub struct HandlerSupervisor {
+    handler: Arc<dyn EventHandler>,
+    timeout: Duration,
+    worker: Worker,
+}
+
+impl HandlerSupervisor {
+    pub fn new(handler: Arc<dyn EventHandler>, timeout: Duration) -> Self {
+        HandlerSupervisor {
+            worker: spawn_worker(Arc::clone(&handler)),
+            handler,
+            timeout,
+        }
+    }
+
+    /// Menyerahkan event ke handler dan menunggu paling lama `timeout`.
+    /// Diagnostik (timeout/panic) juga diserahkan ke handler, tetapi kegagalan
+    /// saat menangani diagnostik tidak menghasilkan diagnostik baru.
+    pub fn deliver(&mut self, event: Event) {
+        if let Some(diagnostic) = self.run(event) {
+            self.run(diagnostic);
+        }
+    }
+
+    fn run(&mut self, event: Event) -> Option<Event> {
+        let kind = event_kind(&event);
+        if self.worker.tx.send(event).is_err() {
+            self.worker = spawn_worker(Arc::clone(&self.handler));
+            return Some(Event::Error(format!("Event handler worker stopped while handling {}", kind)));
+        }
+
+        match self.worker.done_rx.recv_timeout(self.timeout) {
+            Ok(Outcome::Done) => None,
+            Ok(Outcome::Panicked(message)) => Some(Event::Error(format!("Event handler panicked on {}: {}", kind, message))),
+            Err(RecvTimeoutError::Timeout) => {
+                // Thread yang macet tidak bisa dihentikan; tinggalkan dan pakai worker baru
+                self.worker = spawn_worker(Arc::clone(&self.handler));
+                Some(Event::HandlerTimeout {
+                    event: kind,
+                    timeout: self.timeout,
+                })
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                self.worker = spawn_worker(Arc::clone(&self.handler));
+                Some(Event::Error(format!("Event handler worker stopped while handling {}", kind)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DisconnectReason;
+    use std::sync::Mutex;
+
+    struct Recorder {
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl EventHandler for Recorder {
+        fn handle_event(&self, event: Event) {
+            let kind = event_kind(&event);
+            self.seen.lock().unwrap().push(kind.clone());
+            match kind.as_str() {
+                "Connected" => thread::sleep(Duration::from_secs(5)),
+                "Disconnected" => panic!("boom"),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_kind() {
+        assert_eq!(event_kind(&Event::QrTimeout), "QrTimeout");
+        assert_eq!(event_kind(&Event::Error("x".to_string())), "Error");
+    }
+
+    #[test]
+    fn test_stuck_and_panicking_handlers_are_isolated() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut supervisor = HandlerSupervisor::new(Arc::new(Recorder { seen: Arc::clone(&seen) }), Duration::from_millis(100));
+
+        supervisor.deliver(Event::Connected);
+        supervisor.deliver(Event::Disconnected(DisconnectReason::ConnectionClosed));
+        supervisor.deliver(Event::QrTimeout);
+
+        let seen = seen.lock().unwrap().clone();
+        assert_eq!(seen, vec!["Connected", "HandlerTimeout", "Disconnected", "Error", "QrTimeout"]);
+    }
+}
@@ -0,0 +1,163 @@
+//! Pesan sementara (disappearing messages) per chat
+//!
+//! Chat pribadi mengatur durasi lewat `ProtocolMessage` bertipe
+//! EPHEMERAL_SETTING, grup lewat stanza `w:g2`. Durasi yang aktif disimpan per
+//! chat agar pesan keluar otomatis diberi `ephemeral_duration`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::errors::*;
+use crate::messages::WebMessageInfo;
+use crate::node_protocol::Node;
+use crate::routing::{self, NodeContext};
+use crate::{utils, Event, Jid};
+
+/// Nilai `ProtocolMessage.type` untuk pengaturan pesan sementara
+pub const PROTOCOL_MESSAGE_EPHEMERAL_SETTING: u32 = 3;
+
+/// Durasi yang diterima server: 24 jam, 7 hari, 90 hari
+pub const ALLOWED_DURATIONS: [Duration; 3] = [
+    Duration::from_secs(24 * 60 * 60),
+    Duration::from_secs(7 * 24 * 60 * 60),
+    Duration::from_secs(90 * 24 * 60 * 60),
+];
+
+/// Memvalidasi durasi; mengembalikan detik (0 berarti nonaktif)
+pub fn expiration_secs(duration: Option<Duration>) -> Result<u32> {
+    match duration {
+        None => Ok(0),
+        Some(duration) if ALLOWED_DURATIONS.contains(&duration) => Ok(duration.as_secs() as u32),
+        Some(_) => Err("Unsupported disappearing message duration (use 24 hours, 7 days or 90 days)".into()),
+    }
+}
+
+fn duration_from_secs(secs: u32) -> Option<Duration> {
+    if secs == 0 {
+        None
+    } else {
+        Some(Duration::from_secs(secs as u64))
+    }
+}
+
+/// Durasi pesan sementara per chat (detik)
+#[derive(Default)]
+pub struct EphemeralSettings {
+    chats: HashMap<String, u32>,
+}
+
+impl EphemeralSettings {
+    pub fn new() -> Self {
+        EphemeralSettings::default()
+    }
+
+    pub fn set(&mut self, chat: &str, expiration: u32) {
+        if expiration == 0 {
+            self.chats.remove(chat);
+        } else {
+            self.chats.insert(chat.to_string(), expiration);
+        }
+    }
+
+    /// Durasi aktif untuk chat, `None` jika nonaktif
+    pub fn get(&self, chat: &str) -> Option<u32> {
+        self.chats.get(chat).copied()
+    }
+}
+
+/// Pengaturan dari pesan masuk: (chat, durasi detik)
+fn setting_from_message(web_message: &WebMessageInfo) -> Option<(Jid, u32)> {
+    let protocol = web_message.message.as_ref()?.protocol_message.as_ref()?;
+    if protocol.r#type != Some(PROTOCOL_MESSAGE_EPHEMERAL_SETTING) {
+        return None;
+    }
+    let chat = Jid::from_string(&web_message.key.remote_jid).ok()?;
+    Some((chat, protocol.ephemeral_expiration.unwrap_or(0)))
+}
+
+/// Mengubah pesan pengaturan pesan sementara menjadi `Event::EphemeralSettingChanged`
+pub fn setting_event(web_message: &WebMessageInfo) -> Option<Event> {
+    let (chat, expiration) = setting_from_message(web_message)?;
+    Some(Event::EphemeralSettingChanged {
+        chat,
+        expiration: duration_from_secs(expiration),
+    })
+}
+
+/// Handler `message` yang mencatat pengaturan dari chat pribadi
+pub fn message_handler(settings: Arc<Mutex<EphemeralSettings>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if let Some(bytes) = node.get_bytes() {
+            if let Ok(web_message) = WebMessageInfo::from_bytes(bytes) {
+                if let Some((chat, expiration)) = setting_from_message(&web_message) {
+                    settings.lock().unwrap().set(&chat.to_string(), expiration);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Handler `notification type="w:gp2"` untuk perubahan pengaturan grup
+pub fn group_notification_handler(settings: Arc<Mutex<EphemeralSettings>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let expiration = if let Some(ephemeral) = node.get_child("ephemeral") {
+            ephemeral.get_attr("expiration").and_then(|e| e.parse::<u32>().ok()).unwrap_or(0)
+        } else if node.get_child("not_ephemeral").is_some() {
+            0
+        } else {
+            return Ok(());
+        };
+
+        if let Some(ack) = routing::ack_for(node) {
+            ctx.send_node(&ack)?;
+        }
+        let chat = match node.get_attr("from").and_then(|from| Jid::from_string(from).ok()) {
+            Some(chat) => chat,
+            None => return Ok(()),
+        };
+        settings.lock().unwrap().set(&chat.to_string(), expiration);
+        ctx.emit(Event::EphemeralSettingChanged {
+            chat,
+            expiration: duration_from_secs(expiration),
+        });
+        Ok(())
+    }
+}
+
+/// IQ pengaturan pesan sementara untuk grup
+pub fn group_setting_node(group: &Jid, expiration: u32) -> Node {
+    let setting = if expiration == 0 {
+        Node::new("not_ephemeral")
+    } else {
+        Node::new("ephemeral").attr("expiration", &expiration.to_string())
+    };
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "w:g2")
+        .attr("type", "set")
+        .attr("to", &group.to_string())
+        .children(vec![setting])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiration_validation() {
+        assert_eq!(expiration_secs(None).unwrap(), 0);
+        assert_eq!(expiration_secs(Some(Duration::from_secs(86400))).unwrap(), 86400);
+        assert!(expiration_secs(Some(Duration::from_secs(3600))).is_err());
+    }
+
+    #[test]
+    fn test_settings_disable_removes_chat() {
+        let mut settings = EphemeralSettings::new();
+        settings.set("628111@s.whatsapp.net", 604800);
+        assert_eq!(settings.get("628111@s.whatsapp.net"), Some(604800));
+        settings.set("628111@s.whatsapp.net", 0);
+        assert_eq!(settings.get("628111@s.whatsapp.net"), None);
+    }
+}
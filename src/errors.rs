@@ -1,47 +1,128 @@
-use std::fmt;
+use std::time::Duration;
 
 /// Error type untuk library WhatsApp
-#[derive(Debug)]
+///
+/// Kode dan pesan dari server dipertahankan (`IqError`) sehingga pemanggil
+/// bisa bercabang berdasarkan jenis kegagalan alih-alih mencocokkan string.
+#[derive(Debug, thiserror::Error)]
 pub enum ErrorKind {
     /// Kesalahan dalam format data
+    #[error("Invalid format: {0}")]
     InvalidFormat(String),
     /// Kesalahan koneksi
+    #[error("Connection error: {0}")]
     ConnectionError(String),
+    /// Tidak ada koneksi aktif ke server
+    #[error("Not connected")]
+    NotConnected,
+    /// Handshake dengan server gagal
+    #[error("Handshake failed: {0}")]
+    HandshakeFailed(String),
     /// Kesalahan otentikasi
+    #[error("Authentication error: {0}")]
     AuthenticationError(String),
+    /// Server membalas IQ dengan `type="error"`; `code` 0 jika server tidak mengirim kode
+    #[error("Server returned error {code} ({text})")]
+    IqError { code: u16, text: String },
     /// Kesalahan enkripsi
+    #[error("Crypto error: {0}")]
     CryptoError(String),
+    /// Pesan dari/ke `jid` tidak bisa didekripsi
+    #[error("Decryption failed for {jid}: {kind}")]
+    DecryptionFailed { jid: String, kind: DecryptionFailure },
     /// Kesalahan dalam payload
+    #[error("Invalid payload: {0}")]
     InvalidPayload(String),
     /// Kesalahan protokol
+    #[error("Protocol error: {0}")]
     ProtocolError(String),
+    /// Atribut stanza hilang atau tidak bisa dibaca (lihat `node_attrs`)
+    #[error("Invalid attributes: {}", join_attr_errors(.0))]
+    InvalidAttributes(Vec<AttrError>),
     /// Kesalahan I/O
+    #[error("IO error: {0}")]
     IOError(String),
+    /// Format media tidak bisa ditampilkan penerima
+    #[error("Invalid media: expected {expected}, found {found}")]
+    InvalidMedia { expected: String, found: String },
+    /// Upload media ke CDN gagal
+    #[error("Media upload failed: {0}")]
+    MediaUploadFailed(String),
+    /// Perangkat penerima tidak memiliki prekey (JID perangkat)
+    #[error("No pre-keys available for {0}")]
+    NoPreKeys(String),
+    /// Identitas perangkat penerima berubah dan belum dipercaya (JID perangkat)
+    #[error("Untrusted identity for {0}")]
+    UntrustedIdentity(String),
+    /// Pengiriman melewati rate limit; coba lagi setelah `retry_after`
+    #[error("Rate limited: retry after {retry_after:?}")]
+    RateLimited { retry_after: Duration },
+    /// Antrean kirim penuh karena socket belum sempat menulis pesan sebelumnya
+    #[error("Send queue is full")]
+    QueueFull,
     /// Kesalahan lainnya
+    #[error("Error: {0}")]
     Other(String),
 }
 
-#[derive(Debug)]
+/// Penyebab `ErrorKind::DecryptionFailed`
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum DecryptionFailure {
+    #[error("no session")]
+    NoSession,
+    #[error("MAC mismatch")]
+    InvalidMac,
+    #[error("malformed message")]
+    InvalidMessage,
+    /// Penerima masih gagal setelah semua percobaan kirim ulang
+    #[error("gave up after {0} retries")]
+    RetriesExhausted(u32),
+}
+
+/// Satu atribut stanza yang hilang atau nilainya tidak valid
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum AttrError {
+    #[error("<{tag}> is missing attribute {attr:?}")]
+    Missing { tag: String, attr: String },
+    #[error("<{tag}> attribute {attr:?}={value:?} is not a valid {expected}")]
+    Invalid { tag: String, attr: String, value: String, expected: &'static str },
+}
+
+fn join_attr_errors(errors: &[AttrError]) -> String {
+    errors.iter().map(AttrError::to_string).collect::<Vec<_>>().join("; ")
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{kind}")]
 pub struct Error {
     pub kind: ErrorKind,
 }
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.kind {
-            ErrorKind::InvalidFormat(msg) => write!(f, "Invalid format: {}", msg),
-            ErrorKind::ConnectionError(msg) => write!(f, "Connection error: {}", msg),
-            ErrorKind::AuthenticationError(msg) => write!(f, "Authentication error: {}", msg),
-            ErrorKind::CryptoError(msg) => write!(f, "Crypto error: {}", msg),
-            ErrorKind::InvalidPayload(msg) => write!(f, "Invalid payload: {}", msg),
-            ErrorKind::ProtocolError(msg) => write!(f, "Protocol error: {}", msg),
-            ErrorKind::IOError(msg) => write!(f, "IO error: {}", msg),
-            ErrorKind::Other(msg) => write!(f, "Error: {}", msg),
+impl Error {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Kode error dari server, jika error berasal dari balasan IQ
+    pub fn server_code(&self) -> Option<u16> {
+        match self.kind {
+            ErrorKind::IqError { code, .. } => Some(code),
+            _ => None,
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error { kind }
+    }
+}
+
+impl From<AttrError> for Error {
+    fn from(error: AttrError) -> Self {
+        Error { kind: ErrorKind::InvalidAttributes(vec![error]) }
+    }
+}
 
 impl From<&str> for Error {
     fn from(s: &str) -> Self {
@@ -67,6 +148,12 @@ impl From<base64::DecodeError> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error { kind: ErrorKind::InvalidPayload(e.to_string()) }
+    }
+}
+
 #[macro_export]
 macro_rules! bail {
     ($msg:expr) => {
@@ -0,0 +1,138 @@
+//! Langganan event tanpa polling
+//!
+//! Setiap event yang dikirim ke antrean `poll_event` juga disiarkan ke semua
+//! pelanggan. `WhatsAppClient::events` mengembalikan `Stream` untuk kode async,
+//! `WhatsAppClient::subscribe` mengembalikan `EventSubscription` dengan
+//! `recv`/`recv_timeout` yang memblokir (juga bisa dipakai sebagai iterator).
+//!
+//! Pelanggan yang tertinggal lebih dari `EVENT_BROADCAST_CAPACITY` event
+//! kehilangan event terlama; jumlahnya dicatat lewat `log`.
+
+use std::time::Duration;
+
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::{Event, WhatsAppClient};
+
+/// Jumlah event yang ditahan untuk pelanggan yang lambat
+pub const EVENT_BROADCAST_CAPACITY: usize = 1024;
+
+pub(crate) fn channel() -> broadcast::Sender<Event> {
+    broadcast::channel(EVENT_BROADCAST_CAPACITY).0
+}
+
+fn lagged(skipped: u64) {
+    log::warn!("Event subscriber lagged behind, {} events dropped", skipped);
+}
+
+/// Langganan event yang memblokir thread pemanggil.
+/// Jangan dipakai dari dalam runtime async; gunakan `WhatsAppClient::events`.
+pub struct EventSubscription {
+    rx: broadcast::Receiver<Event>,
+    runtime: Runtime,
+}
+
+impl EventSubscription {
+    fn new(rx: broadcast::Receiver<Event>) -> Self {
+        let runtime = Builder::new_current_thread().enable_time().build().expect("Failed to build event runtime");
+        EventSubscription { rx, runtime }
+    }
+
+    /// Menunggu event berikutnya; `None` jika client sudah di-drop
+    pub fn recv(&mut self) -> Option<Event> {
+        loop {
+            match self.runtime.block_on(self.rx.recv()) {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(skipped)) => lagged(skipped),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Seperti `recv`, tetapi menyerah setelah `timeout`
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Option<Event> {
+        let rx = &mut self.rx;
+        // Timer harus dibuat di dalam runtime agar terdaftar di driver waktunya
+        self.runtime.block_on(async {
+            let next = async {
+                loop {
+                    match rx.recv().await {
+                        Ok(event) => return Some(event),
+                        Err(RecvError::Lagged(skipped)) => lagged(skipped),
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            };
+            tokio::time::timeout(timeout, next).await.ok().flatten()
+        })
+    }
+
+    /// Event berikutnya jika sudah tersedia
+    pub fn try_recv(&mut self) -> Option<Event> {
+        loop {
+            match self.rx.try_recv() {
+                Ok(event) => return Some(event),
+                Err(TryRecvError::Lagged(skipped)) => lagged(skipped),
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+impl Iterator for EventSubscription {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        self.recv()
+    }
+}
+
+fn stream(rx: broadcast::Receiver<Event>) -> impl Stream<Item = Event> + Send + 'static {
+    BroadcastStream::new(rx).filter_map(|received| match received {
+        Ok(event) => Some(event),
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+            lagged(skipped);
+            None
+        }
+    })
+}
+
+impl WhatsAppClient {
+    /// Stream event untuk kode async; setiap pemanggilan adalah pelanggan baru
+    pub fn events(&self) -> impl Stream<Item = Event> + Send + 'static {
+        stream(self.event_tx.subscribers.subscribe())
+    }
+
+    /// Langganan event yang memblokir, mulai dari event berikutnya
+    pub fn subscribe(&self) -> EventSubscription {
+        EventSubscription::new(self.event_tx.subscribers.subscribe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_subscriber_receives_events() {
+        let tx = channel();
+        let mut blocking = EventSubscription::new(tx.subscribe());
+        let mut events = Box::pin(stream(tx.subscribe()));
+
+        assert!(blocking.recv_timeout(Duration::from_millis(10)).is_none());
+        tx.send(Event::Connected).unwrap();
+        tx.send(Event::QrTimeout).unwrap();
+        assert!(matches!(blocking.recv_timeout(Duration::from_secs(1)), Some(Event::Connected)));
+        assert!(matches!(blocking.try_recv(), Some(Event::QrTimeout)));
+
+        let runtime = Builder::new_current_thread().build().unwrap();
+        assert!(matches!(runtime.block_on(events.next()), Some(Event::Connected)));
+        drop(tx);
+        assert!(matches!(runtime.block_on(events.next()), Some(Event::QrTimeout)));
+        assert!(runtime.block_on(events.next()).is_none());
+        assert!(blocking.recv().is_none());
+    }
+}
@@ -0,0 +1,208 @@
+//! Lapisan frame koneksi multi-device
+//!
+//! Setiap frame diawali panjang 3 byte big endian. Frame pertama yang dikirim
+//! client didahului header `WA` (magic dan versi kamus). Setelah handshake
+//! Noise selesai, isi frame adalah segmen transport AES-256-GCM dengan
+//! counter per arah sebagai IV.
+//!
+//! Satu pesan WebSocket bisa berisi beberapa frame atau hanya sebagian
+//! frame; `Framing::decode` menyimpan sisa byte sampai frame lengkap.
+//! Frame plaintext diawali byte flag; gunakan
+//! `node_protocol::decode_payload` untuk membacanya sebagai node.
+//!
+//! Semua node biner yang ditulis client melewati `send_frame`, dan semua
+//! pesan biner yang diterima `WsHandler` melewati `Framing::decode`.
+
+use std::borrow::Cow;
+use std::sync::Mutex;
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
+use ws::Sender;
+
+use crate::errors::*;
+
+/// Magic `WA`, versi protokol 6 dan versi kamus token 3
+pub const WA_HEADER: [u8; 4] = [b'W', b'A', 6, 3];
+/// Ukuran awalan panjang frame
+pub const FRAME_LENGTH_SIZE: usize = 3;
+/// Panjang frame terbesar yang bisa ditulis dalam 3 byte
+pub const MAX_FRAME_SIZE: usize = (1 << 24) - 1;
+
+/// Kunci transport Noise untuk kedua arah
+struct TransportCipher {
+    write_key: LessSafeKey,
+    read_key: LessSafeKey,
+    write_counter: u32,
+    read_counter: u32,
+}
+
+fn transport_key(key: &[u8]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&AES_256_GCM, key).map_err(|_| Error { kind: ErrorKind::CryptoError("Invalid transport key".to_string()) })?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// IV segmen: 8 byte nol diikuti counter 4 byte big endian
+fn nonce(counter: u32) -> Nonce {
+    let mut iv = [0u8; 12];
+    iv[8..].copy_from_slice(&counter.to_be_bytes());
+    Nonce::assume_unique_for_key(iv)
+}
+
+/// Status framing untuk satu koneksi
+#[derive(Default)]
+pub struct Framing {
+    header_sent: bool,
+    /// Sisi server: header `WA` dari client belum diterima
+    header_pending: bool,
+    buffer: Vec<u8>,
+    cipher: Option<TransportCipher>,
+}
+
+impl Framing {
+    pub fn new() -> Self {
+        Framing::default()
+    }
+
+    /// Framing sisi server (mock server): tidak mengirim header `WA` dan
+    /// membuangnya dari awal data yang diterima
+    pub fn server() -> Self {
+        Framing { header_sent: true, header_pending: true, ..Framing::default() }
+    }
+
+    /// Mengaktifkan enkripsi transport setelah handshake Noise selesai
+    pub fn set_transport_keys(&mut self, write_key: &[u8], read_key: &[u8]) -> Result<()> {
+        self.cipher = Some(TransportCipher {
+            write_key: transport_key(write_key)?,
+            read_key: transport_key(read_key)?,
+            write_counter: 0,
+            read_counter: 0,
+        });
+        Ok(())
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Membungkus payload menjadi satu pesan WebSocket
+    pub fn encode(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        // Ukuran diperiksa sebelum enkripsi agar counter tidak maju untuk frame yang ditolak
+        let tag_len = if self.cipher.is_some() { AES_256_GCM.tag_len() } else { 0 };
+        if payload.len() + tag_len > MAX_FRAME_SIZE {
+            return Err(format!("Frame of {} bytes exceeds the {} byte limit", payload.len() + tag_len, MAX_FRAME_SIZE).into());
+        }
+
+        let payload = match self.cipher {
+            Some(ref mut cipher) => {
+                let mut sealed = payload.to_vec();
+                cipher
+                    .write_key
+                    .seal_in_place_append_tag(nonce(cipher.write_counter), Aad::empty(), &mut sealed)
+                    .map_err(|_| Error { kind: ErrorKind::CryptoError("Failed to encrypt frame".to_string()) })?;
+                cipher.write_counter += 1;
+                Cow::Owned(sealed)
+            }
+            None => Cow::Borrowed(payload),
+        };
+
+        let mut frame = Vec::with_capacity(WA_HEADER.len() + FRAME_LENGTH_SIZE + payload.len());
+        if !self.header_sent {
+            frame.extend_from_slice(&WA_HEADER);
+            self.header_sent = true;
+        }
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes()[1..]);
+        frame.extend_from_slice(&payload);
+        Ok(frame)
+    }
+
+    /// Memisahkan frame lengkap dari pesan WebSocket yang diterima dan
+    /// mendekripsinya; byte sisa disimpan untuk pesan berikutnya
+    pub fn decode(&mut self, data: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.buffer.extend_from_slice(data);
+        if self.header_pending {
+            if self.buffer.len() < WA_HEADER.len() {
+                return Ok(Vec::new());
+            }
+            if self.buffer[..2] != WA_HEADER[..2] {
+                return Err("Connection did not start with the WA header".into());
+            }
+            self.buffer.drain(..WA_HEADER.len());
+            self.header_pending = false;
+        }
+        let mut frames = Vec::new();
+        let mut offset = 0;
+        while self.buffer.len() - offset >= FRAME_LENGTH_SIZE {
+            let header = &self.buffer[offset..offset + FRAME_LENGTH_SIZE];
+            let length = ((header[0] as usize) << 16) | ((header[1] as usize) << 8) | header[2] as usize;
+            let start = offset + FRAME_LENGTH_SIZE;
+            if self.buffer.len() - start < length {
+                break;
+            }
+            frames.push(self.buffer[start..start + length].to_vec());
+            offset = start + length;
+        }
+        self.buffer.drain(..offset);
+
+        if let Some(ref mut cipher) = self.cipher {
+            for frame in frames.iter_mut() {
+                let plain_len = cipher
+                    .read_key
+                    .open_in_place(nonce(cipher.read_counter), Aad::empty(), frame)
+                    .map_err(|_| Error { kind: ErrorKind::CryptoError(format!("Failed to decrypt frame {}", cipher.read_counter)) })?
+                    .len();
+                frame.truncate(plain_len);
+                cipher.read_counter += 1;
+            }
+        }
+        Ok(frames)
+    }
+}
+
+/// Membungkus `payload` menjadi frame dan menuliskannya ke socket. Lock
+/// framing dipegang sampai frame diserahkan ke `ws::Sender`, sehingga urutan
+/// frame di socket sama dengan urutan counter transport.
+pub fn send_frame(out: &Sender, framing: &Mutex<Framing>, payload: &[u8]) -> Result<()> {
+    let mut framing = framing.lock().unwrap();
+    let frame = framing.encode(payload)?;
+    out.send(frame).map_err(|e| Error::from(format!("Send error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_join_and_encrypt() {
+        let mut client = Framing::new();
+        let first = client.encode(&[1, 2, 3]).unwrap();
+        assert_eq!(first, [b'W', b'A', 6, 3, 0, 0, 3, 1, 2, 3]);
+        assert_eq!(client.encode(&[4]).unwrap(), [0, 0, 1, 4]);
+
+        // Header `WA`, dua frame dalam satu pesan, frame kedua terpotong
+        let mut server = Framing::server();
+        assert_eq!(server.decode(&[b'W', b'A', 6, 3, 0, 0, 2, 9, 8, 0, 0, 3, 7]).unwrap(), vec![vec![9, 8]]);
+        assert_eq!(server.decode(&[6, 5]).unwrap(), vec![vec![7, 6, 5]]);
+        assert_eq!(server.encode(&[1]).unwrap(), [0, 0, 1, 1]);
+
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+        client.set_transport_keys(&a, &b).unwrap();
+        server.set_transport_keys(&b, &a).unwrap();
+        for payload in [&b"halo"[..], &b"dunia"[..]] {
+            let frame = client.encode(payload).unwrap();
+            assert_ne!(&frame[FRAME_LENGTH_SIZE..], payload);
+            assert_eq!(server.decode(&frame).unwrap(), vec![payload.to_vec()]);
+        }
+        // Counter tidak sinkron (frame diputar ulang) ditolak
+        let frame = client.encode(b"x").unwrap();
+        server.decode(&frame).unwrap();
+        assert!(server.decode(&frame).is_err());
+
+        // Frame terlalu besar ditolak tanpa memajukan counter
+        let mut sender = Framing::new();
+        let mut receiver = Framing::server();
+        sender.set_transport_keys(&a, &b).unwrap();
+        receiver.set_transport_keys(&b, &a).unwrap();
+        assert!(sender.encode(&vec![0u8; MAX_FRAME_SIZE]).is_err());
+        assert_eq!(receiver.decode(&sender.encode(b"y").unwrap()).unwrap(), vec![b"y".to_vec()]);
+    }
+}
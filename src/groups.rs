@@ -0,0 +1,546 @@
+//! Manajemen grup lewat IQ `w:g2`
+
+use crate::errors::*;
+use crate::iq;
+use crate::node_protocol::Node;
+use crate::picture;
+use crate::protocol_constants;
+use crate::routing::{self, NodeContext};
+use crate::{utils, Event, GroupParticipantsChange, Jid, WhatsAppClient};
+
+/// Peserta grup
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupParticipant {
+    pub jid: Jid,
+    pub is_admin: bool,
+    pub is_super_admin: bool,
+}
+
+/// Metadata grup
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupMetadata {
+    pub jid: Jid,
+    pub subject: String,
+    pub creator: Option<Jid>,
+    /// Waktu pembuatan (detik sejak epoch)
+    pub creation_time: i64,
+    pub participants: Vec<GroupParticipant>,
+    /// Mode pengumuman: hanya admin yang bisa mengirim pesan
+    pub announce: bool,
+    pub description: Option<String>,
+    /// Id deskripsi saat ini; wajib dikirim sebagai `prev` saat mengubah deskripsi
+    pub description_id: Option<String>,
+    /// Grup induk komunitas
+    pub is_community: bool,
+    /// Komunitas tempat grup ini tertaut
+    pub parent_group: Option<Jid>,
+    /// Grup pengumuman default komunitas `parent_group`
+    pub is_default_subgroup: bool,
+}
+
+/// Perubahan info grup oleh admin
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupInfoChange {
+    Subject(String),
+    /// `None` jika deskripsi dihapus
+    Description(Option<String>),
+    /// Id foto baru; `None` jika foto dihapus
+    Picture(Option<String>),
+}
+
+/// Hasil perubahan untuk satu peserta
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticipantStatus {
+    pub jid: Jid,
+    /// Kode status server: 200 berhasil, 403 user membatasi siapa yang bisa
+    /// menambahkannya, 404 bukan anggota, 409 sudah menjadi anggota
+    pub code: u16,
+}
+
+impl ParticipantStatus {
+    pub fn is_success(&self) -> bool {
+        self.code == 200
+    }
+
+    /// Deskripsi kode status, mis. "conflict" untuk 409
+    pub fn description(&self) -> &'static str {
+        protocol_constants::status_description(self.code).unwrap_or("unknown")
+    }
+}
+
+/// Pengaturan grup yang bisa diubah admin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupSetting {
+    /// Hanya admin yang bisa mengirim pesan
+    Announce,
+    /// Hanya admin yang bisa mengubah info grup
+    Locked,
+    /// Permintaan bergabung harus disetujui admin
+    JoinApproval,
+}
+
+impl GroupSetting {
+    /// Node IQ/notifikasi untuk pengaturan ini
+    fn node(&self, enabled: bool) -> Node {
+        match (self, enabled) {
+            (GroupSetting::Announce, true) => Node::new("announcement"),
+            (GroupSetting::Announce, false) => Node::new("not_announcement"),
+            (GroupSetting::Locked, true) => Node::new("locked"),
+            (GroupSetting::Locked, false) => Node::new("unlocked"),
+            (GroupSetting::JoinApproval, enabled) => Node::new("membership_approval_mode")
+                .children(vec![Node::new("group_join").attr("state", if enabled { "on" } else { "off" })]),
+        }
+    }
+
+    /// Membaca child notifikasi `w:gp2`; None jika bukan perubahan pengaturan
+    pub fn from_node(node: &Node) -> Option<(GroupSetting, bool)> {
+        match node.tag.as_str() {
+            "announcement" => Some((GroupSetting::Announce, true)),
+            "not_announcement" => Some((GroupSetting::Announce, false)),
+            "locked" => Some((GroupSetting::Locked, true)),
+            "unlocked" => Some((GroupSetting::Locked, false)),
+            "membership_approval_mode" => {
+                let state = node.get_child("group_join")?.get_attr("state")?;
+                Some((GroupSetting::JoinApproval, state == "on"))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl GroupParticipantsChange {
+    /// Tag node IQ untuk perubahan ini
+    pub fn tag(&self) -> &'static str {
+        match self {
+            GroupParticipantsChange::Add => "add",
+            GroupParticipantsChange::Remove => "remove",
+            GroupParticipantsChange::Promote => "promote",
+            GroupParticipantsChange::Demote => "demote",
+        }
+    }
+}
+
+/// JID grup dari atribut `id`, yang bisa tanpa suffix `@g.us`
+fn group_jid(id: &str) -> Result<Jid> {
+    if id.contains('@') {
+        Jid::from_string(id)
+    } else {
+        Ok(Jid::group(id))
+    }
+}
+
+/// Membaca node `<group>` dari balasan server
+pub fn parse_group_metadata(group: &Node) -> Result<GroupMetadata> {
+    let invalid = |msg: &str| Error { kind: ErrorKind::InvalidPayload(msg.to_string()) };
+
+    let jid = group_jid(group.get_attr("id").ok_or_else(|| invalid("Group node without id"))?)?;
+    let participants = group
+        .get_children()
+        .iter()
+        .filter(|child| child.tag == "participant")
+        .filter_map(|child| {
+            let jid = Jid::from_string(child.get_attr("jid")?).ok()?;
+            let role = child.get_attr("type").unwrap_or_default();
+            Some(GroupParticipant {
+                jid,
+                is_admin: role == "admin" || role == "superadmin",
+                is_super_admin: role == "superadmin",
+            })
+        })
+        .collect();
+    let description = group.get_child("description");
+
+    Ok(GroupMetadata {
+        jid,
+        subject: group.get_attr("subject").unwrap_or_default().to_string(),
+        creator: group.get_attr("creator").and_then(|creator| Jid::from_string(creator).ok()),
+        creation_time: group.get_attr("creation").and_then(|t| t.parse().ok()).unwrap_or(0),
+        participants,
+        announce: group.get_child("announcement").is_some(),
+        description: description.and_then(description_text),
+        description_id: description.and_then(|description| description.get_attr("id")).map(|id| id.to_string()),
+        is_community: group.get_child("parent").is_some(),
+        parent_group: group.get_child("linked_parent").and_then(|parent| parent.get_attr("jid")).and_then(|jid| group_jid(jid).ok()),
+        is_default_subgroup: group.get_child("default_sub_group").is_some(),
+    })
+}
+
+/// Teks `<description><body>..</body></description>`
+fn description_text(description: &Node) -> Option<String> {
+    let body = description.get_child("body")?.get_bytes()?;
+    Some(String::from_utf8_lossy(body).into_owned())
+}
+
+/// IQ untuk membuat grup
+pub fn create_group_node(subject: &str, participants: &[Jid]) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "w:g2")
+        .attr("type", "set")
+        .attr("to", "g.us")
+        .children(vec![Node::new("create")
+            .attr("subject", subject)
+            .attr("key", &utils::generate_message_id())
+            .children(participants.iter().map(|jid| Node::new("participant").attr("jid", &jid.to_string())).collect())])
+}
+
+/// IQ untuk menambah/mengeluarkan/mempromosikan/menurunkan peserta
+pub fn participants_change_node(group: &Jid, change: GroupParticipantsChange, participants: &[Jid]) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "w:g2")
+        .attr("type", "set")
+        .attr("to", &group.to_string())
+        .children(vec![Node::new(change.tag())
+            .children(participants.iter().map(|jid| Node::new("participant").attr("jid", &jid.to_string())).collect())])
+}
+
+/// Membaca status per peserta dari balasan perubahan peserta.
+/// Peserta tanpa atribut `error` dianggap berhasil.
+pub fn parse_participant_statuses(response: &Node, change: GroupParticipantsChange) -> Result<Vec<ParticipantStatus>> {
+    let list = response.get_child(change.tag()).ok_or_else(|| format!("Participant response without {} node", change.tag()))?;
+    Ok(list
+        .get_children()
+        .iter()
+        .filter(|child| child.tag == "participant")
+        .filter_map(|child| {
+            let jid = Jid::from_string(child.get_attr("jid")?).ok()?;
+            let code = child.get_attr("error").and_then(|code| code.parse().ok()).unwrap_or(200);
+            Some(ParticipantStatus { jid, code })
+        })
+        .collect())
+}
+
+fn group_iq(group: &Jid, xmlns: &str, child: Node) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", xmlns)
+        .attr("type", "set")
+        .attr("to", &group.to_string())
+        .children(vec![child])
+}
+
+/// IQ untuk mengganti subject grup
+pub fn subject_node(group: &Jid, subject: &str) -> Node {
+    group_iq(group, "w:g2", Node::new("subject").bytes(subject.as_bytes().to_vec()))
+}
+
+/// IQ untuk mengganti atau menghapus (`None`) deskripsi grup.
+/// `prev` adalah id deskripsi saat ini, jika ada.
+pub fn description_node(group: &Jid, description: Option<&str>, prev: Option<&str>) -> Node {
+    let mut node = Node::new("description").attr("id", &utils::generate_message_id());
+    if let Some(prev) = prev {
+        node = node.attr("prev", prev);
+    }
+    let node = match description {
+        Some(text) => node.children(vec![Node::new("body").bytes(text.as_bytes().to_vec())]),
+        None => node.attr("delete", "true"),
+    };
+    group_iq(group, "w:g2", node)
+}
+
+/// IQ untuk mengganti foto grup
+pub fn picture_node(group: &Jid, jpeg: Vec<u8>) -> Node {
+    group_iq(group, "w:profile:picture", Node::new("picture").attr("type", "image").bytes(jpeg))
+}
+
+/// Membaca perubahan info grup dari child notifikasi `w:gp2` atau `picture`
+pub fn info_change(node: &Node) -> Option<GroupInfoChange> {
+    match node.tag.as_str() {
+        "subject" => Some(GroupInfoChange::Subject(node.get_attr("subject")?.to_string())),
+        "description" if node.get_attr("delete") == Some("true") => Some(GroupInfoChange::Description(None)),
+        "description" => Some(GroupInfoChange::Description(Some(description_text(node)?))),
+        "set" => Some(GroupInfoChange::Picture(Some(node.get_attr("id")?.to_string()))),
+        "delete" => Some(GroupInfoChange::Picture(None)),
+        _ => None,
+    }
+}
+
+/// Handler notifikasi `w:gp2` dan `picture` untuk perubahan subject,
+/// deskripsi dan foto grup
+pub fn info_notification_handler() -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let group = match node.get_attr("from").and_then(|from| Jid::from_string(from).ok()) {
+            Some(group) if group.is_group() => group,
+            _ => return Ok(()),
+        };
+        let changes: Vec<GroupInfoChange> = node.get_children().iter().filter_map(info_change).collect();
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(ack) = routing::ack_for(node) {
+            ctx.send_node(&ack)?;
+        }
+        let author = node.get_attr("participant").and_then(|participant| Jid::from_string(participant).ok());
+        for change in changes {
+            ctx.emit(Event::GroupInfoChanged {
+                group: group.clone(),
+                change,
+                author: author.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// IQ untuk mengambil metadata grup
+pub fn group_metadata_node(group: &Jid) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "w:g2")
+        .attr("type", "get")
+        .attr("to", &group.to_string())
+        .children(vec![Node::new("query").attr("request", "interactive")])
+}
+
+/// IQ untuk mengubah pengaturan grup
+pub fn setting_node(group: &Jid, setting: GroupSetting, enabled: bool) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "w:g2")
+        .attr("type", "set")
+        .attr("to", &group.to_string())
+        .children(vec![setting.node(enabled)])
+}
+
+/// Handler `notification type="w:gp2"` untuk perubahan pengaturan grup
+pub fn setting_notification_handler() -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let changes: Vec<(GroupSetting, bool)> = node.get_children().iter().filter_map(GroupSetting::from_node).collect();
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(ack) = routing::ack_for(node) {
+            ctx.send_node(&ack)?;
+        }
+        let group = match node.get_attr("from").and_then(|from| Jid::from_string(from).ok()) {
+            Some(group) => group,
+            None => return Ok(()),
+        };
+        let author = node.get_attr("participant").and_then(|participant| Jid::from_string(participant).ok());
+        for (setting, enabled) in changes {
+            ctx.emit(Event::GroupSettingChanged {
+                group: group.clone(),
+                setting,
+                enabled,
+                author: author.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Mengambil metadata grup dari server
+    pub fn group_metadata(&self, group: &Jid) -> Result<GroupMetadata> {
+        let response = self.query(&group_metadata_node(group), iq::DEFAULT_QUERY_TIMEOUT)?;
+        let node = response.get_child("group").ok_or("Group metadata response without group node")?;
+        parse_group_metadata(node)
+    }
+
+    /// Mengganti subject grup
+    pub fn set_group_subject(&self, group: &Jid, subject: &str) -> Result<()> {
+        if subject.trim().is_empty() {
+            return Err("Group subject must not be empty".into());
+        }
+        self.query(&subject_node(group, subject), iq::DEFAULT_QUERY_TIMEOUT)?;
+        self.groups.lock().unwrap().set_subject(&group.to_string(), subject);
+        Ok(())
+    }
+
+    /// Mengganti deskripsi grup; `None` menghapusnya. Id deskripsi saat ini
+    /// diambil dulu dari server karena wajib disertakan.
+    pub fn set_group_description(&self, group: &Jid, description: Option<&str>) -> Result<()> {
+        let current = self.group_metadata(group)?;
+        let node = description_node(group, description, current.description_id.as_deref());
+        self.query(&node, iq::DEFAULT_QUERY_TIMEOUT).map(|_| ())
+    }
+
+    /// Mengganti foto grup (JPEG; lihat `picture::prepare_picture`)
+    pub fn set_group_picture(&self, group: &Jid, image: &[u8]) -> Result<()> {
+        let jpeg = picture::prepare_picture(image)?;
+        self.query(&picture_node(group, jpeg), iq::DEFAULT_QUERY_TIMEOUT).map(|_| ())
+    }
+
+    /// Mengubah pengaturan grup (hanya admin)
+    pub fn set_group_setting(&self, group: &Jid, setting: GroupSetting, enabled: bool) -> Result<()> {
+        if !group.is_group() {
+            return Err("Group settings require a group JID".into());
+        }
+        self.query(&setting_node(group, setting, enabled), iq::DEFAULT_QUERY_TIMEOUT).map(|_| ())
+    }
+
+    /// Mengaktifkan/menonaktifkan mode pengumuman (hanya admin yang bisa mengirim)
+    pub fn set_group_announce(&self, group: &Jid, announce: bool) -> Result<()> {
+        self.set_group_setting(group, GroupSetting::Announce, announce)
+    }
+
+    /// Mengunci info grup sehingga hanya admin yang bisa mengubahnya
+    pub fn set_group_locked(&self, group: &Jid, locked: bool) -> Result<()> {
+        self.set_group_setting(group, GroupSetting::Locked, locked)
+    }
+
+    /// Mewajibkan persetujuan admin untuk permintaan bergabung
+    pub fn set_group_join_approval_mode(&self, group: &Jid, enabled: bool) -> Result<()> {
+        self.set_group_setting(group, GroupSetting::JoinApproval, enabled)
+    }
+
+    /// Mengirim pengumuman ke grup. Jika grup dalam mode pengumuman, mode
+    /// dinonaktifkan sementara lalu dipulihkan, juga saat pengiriman gagal.
+    pub fn announce(&self, group: &Jid, text: &str) -> Result<String> {
+        let meta = self.group_metadata(group)?;
+        if !meta.announce {
+            return self.send_text_message(group, text);
+        }
+
+        self.set_group_announce(group, false)?;
+        let sent = self.send_text_message(group, text);
+        let restored = self.set_group_announce(group, true);
+        let message_id = sent?;
+        restored?;
+        Ok(message_id)
+    }
+
+    /// Menjalankan perubahan peserta di server dan mengembalikan status per
+    /// peserta. `Event::GroupParticipantsChanged` dikirim untuk peserta yang berhasil.
+    pub fn change_participants(&self, group: &Jid, change: GroupParticipantsChange, participants: &[Jid]) -> Result<Vec<ParticipantStatus>> {
+        if !group.is_group() {
+            return Err("Participant changes require a group JID".into());
+        }
+        if participants.is_empty() {
+            return Err("No participants given".into());
+        }
+
+        let response = self.query(&participants_change_node(group, change, participants), iq::DEFAULT_QUERY_TIMEOUT)?;
+        let statuses = parse_participant_statuses(&response, change)?;
+
+        let changed: Vec<Jid> = statuses.iter().filter(|status| status.is_success()).map(|status| status.jid.clone()).collect();
+        if !changed.is_empty() {
+            self.event_tx
+                .send(Event::GroupParticipantsChanged {
+                    group: group.clone(),
+                    change_type: change,
+                    participants: changed,
+                })
+                .ok();
+        }
+        Ok(statuses)
+    }
+
+    pub fn add_participants(&self, group: &Jid, participants: &[Jid]) -> Result<Vec<ParticipantStatus>> {
+        self.change_participants(group, GroupParticipantsChange::Add, participants)
+    }
+
+    pub fn remove_participants(&self, group: &Jid, participants: &[Jid]) -> Result<Vec<ParticipantStatus>> {
+        self.change_participants(group, GroupParticipantsChange::Remove, participants)
+    }
+
+    pub fn promote_participants(&self, group: &Jid, participants: &[Jid]) -> Result<Vec<ParticipantStatus>> {
+        self.change_participants(group, GroupParticipantsChange::Promote, participants)
+    }
+
+    pub fn demote_participants(&self, group: &Jid, participants: &[Jid]) -> Result<Vec<ParticipantStatus>> {
+        self.change_participants(group, GroupParticipantsChange::Demote, participants)
+    }
+
+    /// Membuat grup baru dan mengembalikan metadatanya.
+    /// `Event::GroupIntroduce` juga dikirim ke event handler.
+    pub fn create_group(&self, subject: &str, participants: Vec<Jid>) -> Result<GroupMetadata> {
+        if subject.trim().is_empty() {
+            return Err("Group subject must not be empty".into());
+        }
+        if participants.iter().any(|jid| jid.is_group()) {
+            return Err("Group participants must be contacts, not groups".into());
+        }
+
+        let response = self.query(&create_group_node(subject, &participants), iq::DEFAULT_QUERY_TIMEOUT)?;
+        let group = response.get_child("group").ok_or("Create group response without group node")?;
+        let meta = parse_group_metadata(group)?;
+
+        self.groups.lock().unwrap().set_subject(&meta.jid.to_string(), &meta.subject);
+        if let Some(inducer) = self.get_own_jid() {
+            self.event_tx
+                .send(Event::GroupIntroduce {
+                    newly_created: true,
+                    inducer,
+                    meta: meta.clone(),
+                })
+                .ok();
+        }
+        Ok(meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_group_metadata() {
+        let group = Node::new("group")
+            .attr("id", "120363-1700000000")
+            .attr("subject", "Tim")
+            .attr("creation", "1700000000")
+            .attr("creator", "628111@s.whatsapp.net")
+            .children(vec![
+                Node::new("participant").attr("jid", "628111@s.whatsapp.net").attr("type", "superadmin"),
+                Node::new("participant").attr("jid", "628222@s.whatsapp.net"),
+            ]);
+
+        let meta = parse_group_metadata(&group).unwrap();
+        assert_eq!(meta.jid.to_string(), "120363-1700000000@g.us");
+        assert_eq!(meta.creation_time, 1700000000);
+        assert!(meta.participants[0].is_admin && meta.participants[0].is_super_admin);
+        assert!(!meta.participants[1].is_admin);
+        assert!(!meta.announce);
+    }
+
+    #[test]
+    fn test_description_requires_prev_and_roundtrips() {
+        let group = Jid::group("120363-1700000000");
+        let node = description_node(&group, Some("Aturan grup"), Some("D1"));
+        let description = node.get_child("description").unwrap();
+        assert_eq!(description.get_attr("prev"), Some("D1"));
+        assert_eq!(info_change(description), Some(GroupInfoChange::Description(Some("Aturan grup".to_string()))));
+
+        let cleared = description_node(&group, None, Some("D2"));
+        assert_eq!(info_change(cleared.get_child("description").unwrap()), Some(GroupInfoChange::Description(None)));
+    }
+
+    #[test]
+    fn test_setting_nodes_roundtrip() {
+        let group = Jid::group("120363-1700000000");
+        assert!(setting_node(&group, GroupSetting::Announce, true).get_child("announcement").is_some());
+        assert!(setting_node(&group, GroupSetting::Locked, false).get_child("unlocked").is_some());
+
+        for setting in [GroupSetting::Announce, GroupSetting::Locked, GroupSetting::JoinApproval] {
+            for enabled in [true, false] {
+                let node = setting_node(&group, setting, enabled);
+                assert_eq!(GroupSetting::from_node(&node.get_children()[0]), Some((setting, enabled)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_create_node_lists_participants() {
+        let node = create_group_node("Tim", &[Jid::user("628111")]);
+        let create = node.get_child("create").unwrap();
+        assert_eq!(create.get_attr("subject"), Some("Tim"));
+        assert_eq!(create.get_children().len(), 1);
+    }
+
+    #[test]
+    fn test_participant_statuses() {
+        let response = Node::new("iq").attr("type", "result").children(vec![Node::new("add").children(vec![
+            Node::new("participant").attr("jid", "628111@s.whatsapp.net"),
+            Node::new("participant").attr("jid", "628222@s.whatsapp.net").attr("error", "403"),
+        ])]);
+
+        let statuses = parse_participant_statuses(&response, GroupParticipantsChange::Add).unwrap();
+        assert!(statuses[0].is_success());
+        assert_eq!(statuses[1].code, 403);
+        assert!(parse_participant_statuses(&response, GroupParticipantsChange::Remove).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Serah terima sesi antar proses (blue-green deploy)
+//!
+//! Dua proses yang memakai sesi yang sama sekaligus saling memutus koneksi
+//! (server membalas 409 conflict). Lease di `StateStore` bersama memastikan
+//! hanya satu proses yang memegang sesi: lease punya pemilik dan waktu
+//! kedaluwarsa, diperpanjang di background selama dipegang, dan diambil alih
+//! dengan `StateStore::compare_and_swap`.
+//!
+//! Alur deploy:
+//! 1. proses baru memanggil `wait_for_session` (menunggu lease dilepas)
+//! 2. proses lama memanggil `hand_over`: menunggu antrean event kosong,
+//!    memutus koneksi, lalu melepas lease
+//! 3. proses baru mendapat lease dan memanggil `connect`
+//!
+//! Store harus benar-benar dipakai bersama (Redis, database); `FileStateStore`
+//! menyimpan cache di memori dan tidak cocok untuk beberapa proses.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::*;
+use crate::store::StateStore;
+use crate::{Event, WhatsAppClient};
+
+/// Key lease di store
+pub const LEASE_KEY: &str = "session:lease";
+
+/// Lama lease berlaku tanpa diperpanjang
+pub const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(30);
+
+/// Jeda antar percobaan mengambil lease di `wait_for_session`
+const ACQUIRE_POLL: Duration = Duration::from_millis(500);
+
+/// Isi lease di store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaseRecord {
+    pub owner: String,
+    /// Milidetik sejak epoch
+    pub expires_at: i64,
+}
+
+/// Mengambil atau memperpanjang lease untuk `owner`. Mengembalikan pemegang
+/// lease lain jika lease masih berlaku.
+pub fn try_acquire(store: &dyn StateStore, owner: &str, ttl: Duration, now_ms: i64) -> Result<std::result::Result<(), LeaseRecord>> {
+    let current = store.get(LEASE_KEY)?;
+    if let Some(ref bytes) = current {
+        let record: LeaseRecord =
+            serde_json::from_slice(bytes).map_err(|e| Error { kind: ErrorKind::InvalidFormat(format!("{}: {}", LEASE_KEY, e)) })?;
+        if record.owner != owner && record.expires_at > now_ms {
+            return Ok(Err(record));
+        }
+    }
+
+    let record = LeaseRecord {
+        owner: owner.to_string(),
+        expires_at: now_ms + ttl.as_millis() as i64,
+    };
+    let bytes = serde_json::to_vec(&record).map_err(|e| Error { kind: ErrorKind::InvalidFormat(e.to_string()) })?;
+    if store.compare_and_swap(LEASE_KEY, current.as_deref(), Some(&bytes))? {
+        Ok(Ok(()))
+    } else {
+        // Proses lain menulis lease di antara get dan swap
+        match store.get(LEASE_KEY)? {
+            Some(bytes) => Ok(Err(serde_json::from_slice(&bytes)
+                .map_err(|e| Error { kind: ErrorKind::InvalidFormat(format!("{}: {}", LEASE_KEY, e)) })?)),
+            None => Err("Session lease changed concurrently".into()),
+        }
+    }
+}
+
+/// Melepas lease jika masih dipegang `owner`
+pub fn release(store: &dyn StateStore, owner: &str) -> Result<()> {
+    let current = match store.get(LEASE_KEY)? {
+        Some(bytes) => bytes,
+        None => return Ok(()),
+    };
+    let owned = serde_json::from_slice::<LeaseRecord>(&current).map_or(false, |record| record.owner == owner);
+    if owned {
+        store.compare_and_swap(LEASE_KEY, Some(&current), None)?;
+    }
+    Ok(())
+}
+
+/// Lease sesi milik proses ini
+pub struct SessionLease {
+    store: Arc<dyn StateStore>,
+    owner: String,
+    ttl: Duration,
+    held: AtomicBool,
+}
+
+impl SessionLease {
+    pub fn new(store: Arc<dyn StateStore>, owner: &str, ttl: Duration) -> Self {
+        SessionLease {
+            store,
+            owner: owner.to_string(),
+            ttl,
+            held: AtomicBool::new(false),
+        }
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.held.load(Ordering::SeqCst)
+    }
+
+    fn try_acquire(&self) -> Result<std::result::Result<(), LeaseRecord>> {
+        let acquired = try_acquire(self.store.as_ref(), &self.owner, self.ttl, chrono::Utc::now().timestamp_millis())?;
+        self.held.store(acquired.is_ok(), Ordering::SeqCst);
+        Ok(acquired)
+    }
+
+    fn release(&self) -> Result<()> {
+        self.held.store(false, Ordering::SeqCst);
+        release(self.store.as_ref(), &self.owner)
+    }
+}
+
+fn held_by_other(holder: &LeaseRecord) -> Error {
+    Error {
+        kind: ErrorKind::ConnectionError(format!("Session is in use by another process ({})", holder.owner)),
+    }
+}
+
+impl WhatsAppClient {
+    /// Mengambil lease sebelum terhubung; dipanggil dari `connect`
+    pub(crate) fn acquire_session_lease(&self) -> Result<()> {
+        let lease = match self.lease {
+            Some(ref lease) => Arc::clone(lease),
+            None => return Ok(()),
+        };
+        let was_held = lease.is_held();
+        lease.try_acquire()?.map_err(|holder| held_by_other(&holder))?;
+        if !was_held {
+            self.spawn_lease_renewal(lease);
+        }
+        Ok(())
+    }
+
+    /// Memperpanjang lease selama dipegang; jika diambil proses lain, koneksi
+    /// diputus dan `Event::SessionLeaseLost` dikirim
+    fn spawn_lease_renewal(&self, lease: Arc<SessionLease>) {
+        let client = self.clone();
+        thread::spawn(move || loop {
+            thread::sleep(lease.ttl / 3);
+            if !lease.is_held() {
+                break;
+            }
+            match lease.try_acquire() {
+                Ok(Ok(())) => {}
+                Ok(Err(holder)) => {
+                    client.disconnect().ok();
+                    client.event_tx.send(Event::SessionLeaseLost { holder: holder.owner }).ok();
+                    break;
+                }
+                Err(e) => {
+                    // Store tidak terjangkau; coba lagi selagi lease belum kedaluwarsa
+                    client.event_tx.send(Event::Error(format!("Session lease renewal failed: {}", e))).ok();
+                }
+            }
+        });
+    }
+
+    /// Menunggu proses lain melepas sesi lalu mengambil lease-nya.
+    /// Tanpa `with_session_lease` langsung berhasil.
+    pub fn wait_for_session(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.acquire_session_lease() {
+                Err(Error { kind: ErrorKind::ConnectionError(_) }) if Instant::now() < deadline => thread::sleep(ACQUIRE_POLL),
+                result => return result,
+            }
+        }
+    }
+
+    /// Menyerahkan sesi ke proses lain: menunggu antrean event kosong (paling
+    /// lama `timeout`), memutus koneksi, lalu melepas lease
+    pub fn hand_over(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        while self.event_tx.queue_len() > 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
+        }
+        self.disconnect()?;
+        match self.lease {
+            Some(ref lease) => lease.release(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStateStore;
+
+    #[test]
+    fn test_lease_blocks_second_owner_until_released() {
+        let store = MemoryStateStore::new();
+        let ttl = Duration::from_secs(30);
+
+        assert_eq!(try_acquire(&store, "blue", ttl, 1_000).unwrap(), Ok(()));
+        let holder = try_acquire(&store, "green", ttl, 2_000).unwrap().unwrap_err();
+        assert_eq!(holder.owner, "blue");
+        // Pemilik yang sama memperpanjang
+        assert_eq!(try_acquire(&store, "blue", ttl, 2_000).unwrap(), Ok(()));
+
+        release(&store, "green").unwrap();
+        assert!(try_acquire(&store, "green", ttl, 3_000).unwrap().is_err());
+        release(&store, "blue").unwrap();
+        assert_eq!(try_acquire(&store, "green", ttl, 3_000).unwrap(), Ok(()));
+
+        // Lease kedaluwarsa bisa diambil alih
+        assert_eq!(try_acquire(&store, "blue", ttl, 3_000 + 31_000).unwrap(), Ok(()));
+    }
+}
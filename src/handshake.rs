@@ -55,7 +55,7 @@ impl Handshake {
     /// Memulai handshake Noise
     pub fn start(&mut self) -> Result<Vec<u8>> {
         if self.state != HandshakeState::Idle {
-            return Err("Handshake already started".into());
+            return Err(Error { kind: ErrorKind::HandshakeFailed("Handshake already started".to_string()) });
         }
 
         // Generate keys
@@ -74,11 +74,11 @@ impl Handshake {
     /// Proses ServerHello yang diterima dari server
     pub fn process_server_hello(&mut self, server_hello: &[u8]) -> Result<Vec<u8>> {
         if self.state != HandshakeState::ClientHelloSent {
-            return Err("Invalid handshake state for processing server hello".into());
+            return Err(Error { kind: ErrorKind::HandshakeFailed("Invalid handshake state for processing server hello".to_string()) });
         }
 
         if server_hello.len() < 1 {
-            return Err("Server hello message too short".into());
+            return Err(Error { kind: ErrorKind::HandshakeFailed("Server hello message too short".to_string()) });
         }
 
         // Parse server hello
@@ -116,7 +116,7 @@ impl Handshake {
     /// Selesaikan handshake setelah mengirim ClientFinish
     pub fn finalize(&mut self, session: &mut Session) -> Result<()> {
         if self.state != HandshakeState::ClientFinishSent {
-            return Err("Invalid handshake state for finalization".into());
+            return Err(Error { kind: ErrorKind::HandshakeFailed("Invalid handshake state for finalization".to_string()) });
         }
 
         // Generate final session keys
@@ -163,7 +163,7 @@ impl Handshake {
     fn parse_server_hello(&self, hello: &[u8]) -> Result<ServerKeys> {
         
         if hello.len() < 33 { // minimal 1 byte header + 32 byte public key
-            return Err("Server hello too short".into());
+            return Err(Error { kind: ErrorKind::HandshakeFailed("Server hello too short".to_string()) });
         }
 
         // Ambil public key dari server (32 byte)
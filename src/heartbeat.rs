@@ -0,0 +1,64 @@
+//! Heartbeat periodik untuk integrasi liveness (systemd watchdog, k8s probe)
+//!
+//! Heartbeat dijadwalkan dari event loop WebSocket itu sendiri, sehingga jika
+//! loop berhenti memproses (deadlock, handler macet), heartbeat juga berhenti
+//! walaupun koneksi TCP masih terlihat terbuka.
+
+use std::time::{Duration, SystemTime};
+
+use ws::util::Token;
+use ws::Sender;
+
+use crate::{ConnectionState, Event};
+
+/// Token timeout ws untuk heartbeat
+pub const HEARTBEAT_TOKEN: Token = Token(1);
+
+/// Interval minimal agar supervisor tidak dibanjiri event
+pub const MIN_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Memantau aktivitas koneksi dan menjadwalkan heartbeat
+pub struct HeartbeatMonitor {
+    interval: Option<Duration>,
+    last_rx: Option<SystemTime>,
+}
+
+impl HeartbeatMonitor {
+    /// `None` menonaktifkan heartbeat
+    pub fn new(interval: Option<Duration>) -> Self {
+        HeartbeatMonitor {
+            interval: interval.map(|i| i.max(MIN_HEARTBEAT_INTERVAL)),
+            last_rx: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.interval.is_some()
+    }
+
+    /// Dipanggil setiap kali frame diterima dari server
+    pub fn on_receive(&mut self) {
+        self.last_rx = Some(SystemTime::now());
+    }
+
+    pub fn last_rx(&self) -> Option<SystemTime> {
+        self.last_rx
+    }
+
+    /// Menjadwalkan heartbeat berikutnya di event loop ws
+    pub fn schedule(&self, out: &Sender) -> ws::Result<()> {
+        match self.interval {
+            Some(interval) => out.timeout(interval.as_millis() as u64, HEARTBEAT_TOKEN),
+            None => Ok(()),
+        }
+    }
+
+    /// Membangun event heartbeat
+    pub fn event(&self, state: ConnectionState, queue_len: usize) -> Event {
+        Event::Heartbeat {
+            state,
+            last_rx: self.last_rx,
+            queue_len,
+        }
+    }
+}
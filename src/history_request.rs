@@ -0,0 +1,166 @@
+//! Permintaan history on-demand (scroll ke pesan lama)
+//!
+//! History sync awal hanya memuat pesan terbaru. Pesan yang lebih lama diminta
+//! dari ponsel lewat `ProtocolMessage` peer data operation yang dikirim ke JID
+//! sendiri. Ponsel menjawab dengan `HistorySyncNotification` bertipe
+//! `OnDemand` yang membawa id permintaan di `peer_data_request_session_id`;
+//! blob-nya diunduh seperti history sync biasa lalu diteruskan ke penunggu.
+//!
+//! `request_history` memblokir thread pemanggil dan membutuhkan
+//! `MediaDownloader` (lihat `WhatsAppClient::set_media_downloader`).
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+use crate::errors::*;
+use crate::messages::{HistorySyncOnDemandRequest, Message, MessageKey, PeerDataOperationRequestMessage, ProtocolMessage, WebMessageInfo};
+use crate::{utils, Jid, WhatsAppClient};
+
+/// `ProtocolMessage.type` untuk permintaan ke perangkat utama
+pub const PROTOCOL_MESSAGE_PEER_DATA_OPERATION_REQUEST: u32 = 16;
+
+/// `peer_data_operation_request_type` untuk history on-demand
+pub const PEER_DATA_OPERATION_HISTORY_SYNC_ON_DEMAND: u32 = 3;
+
+/// Jumlah pesan maksimal per permintaan
+pub const MAX_ON_DEMAND_MESSAGES: u32 = 50;
+
+/// Batas waktu default menunggu jawaban ponsel
+pub const DEFAULT_HISTORY_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Penunggu jawaban history on-demand per id permintaan
+#[derive(Default)]
+pub struct PendingHistoryRequests {
+    waiters: HashMap<String, Sender<Vec<WebMessageInfo>>>,
+}
+
+impl PendingHistoryRequests {
+    pub fn new() -> Self {
+        PendingHistoryRequests::default()
+    }
+
+    pub fn register(&mut self, request_id: &str) -> Receiver<Vec<WebMessageInfo>> {
+        let (tx, rx) = mpsc::channel();
+        self.waiters.insert(request_id.to_string(), tx);
+        rx
+    }
+
+    pub fn cancel(&mut self, request_id: &str) {
+        self.waiters.remove(request_id);
+    }
+
+    /// Meneruskan pesan ke penunggu; false jika tidak ada yang menunggu
+    pub fn resolve(&mut self, request_id: &str, messages: Vec<WebMessageInfo>) -> bool {
+        match self.waiters.remove(request_id) {
+            Some(waiter) => waiter.send(messages).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Pesan permintaan `count` pesan sebelum `before` di `chat`
+pub fn request_message(chat: &Jid, before: &MessageKey, before_timestamp_ms: Option<i64>, count: u32) -> Message {
+    Message {
+        protocol_message: Some(ProtocolMessage {
+            key: MessageKey {
+                remote_jid: chat.to_string(),
+                from_me: true,
+                id: utils::generate_message_id(),
+                participant: None,
+            },
+            r#type: Some(PROTOCOL_MESSAGE_PEER_DATA_OPERATION_REQUEST),
+            ephemeral_expiration: None,
+            ephemeral_setting_timestamp: None,
+            history_sync_notification: None,
+            app_state_sync_key_share: None,
+            app_state_sync_key_request: None,
+            initial_security_notification_setting_sync: None,
+            app_state_fatal_exception_notification: None,
+            peer_data_operation_request_message: Some(PeerDataOperationRequestMessage {
+                peer_data_operation_request_type: Some(PEER_DATA_OPERATION_HISTORY_SYNC_ON_DEMAND),
+                history_sync_on_demand_request: Some(HistorySyncOnDemandRequest {
+                    chat_jid: chat.to_string(),
+                    oldest_msg_id: before.id.clone(),
+                    oldest_msg_from_me: before.from_me,
+                    on_demand_msg_count: count,
+                    oldest_msg_timestamp_ms: before_timestamp_ms,
+                }),
+            }),
+        }),
+        ..Default::default()
+    }
+}
+
+impl WhatsAppClient {
+    /// Meminta paling banyak `count` pesan di `chat` yang lebih lama dari
+    /// `before` ke ponsel, urut dari yang terlama. Pesan yang dijawab lewat
+    /// fungsi ini tidak dikirim lagi sebagai `Event::HistorySync`.
+    pub fn request_history(&self, chat: &Jid, before: &MessageKey, count: u32) -> Result<Vec<WebMessageInfo>> {
+        self.request_history_timeout(chat, before, count, DEFAULT_HISTORY_REQUEST_TIMEOUT)
+    }
+
+    pub fn request_history_timeout(&self, chat: &Jid, before: &MessageKey, count: u32, timeout: Duration) -> Result<Vec<WebMessageInfo>> {
+        if count == 0 || count > MAX_ON_DEMAND_MESSAGES {
+            return Err(format!("History request count must be between 1 and {}", MAX_ON_DEMAND_MESSAGES).into());
+        }
+        if self.downloader.lock().unwrap().is_none() {
+            return Err("No media downloader configured (see WhatsAppClient::set_media_downloader)".into());
+        }
+        let own_jid = self.get_own_jid().ok_or("Not logged in")?;
+        let before_timestamp_ms = self
+            .recent
+            .lock()
+            .unwrap()
+            .find(&chat.to_string(), &before.id)
+            .and_then(|message| message.message_timestamp)
+            .map(|timestamp| timestamp as i64 * 1000);
+
+        // Ponsel menjawab dengan id pesan permintaan sebagai session id. Lock
+        // dipegang selama kirim agar jawaban tidak mendahului pendaftaran.
+        let message = request_message(chat, before, before_timestamp_ms, count);
+        let (request_id, rx) = {
+            let mut pending = self.history_requests.lock().unwrap();
+            let request_id = self.send_prepared(&own_jid, message)?;
+            let rx = pending.register(&request_id);
+            (request_id, rx)
+        };
+
+        match rx.recv_timeout(timeout) {
+            Ok(messages) => Ok(messages),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => {
+                self.history_requests.lock().unwrap().cancel(&request_id);
+                Err(Error { kind: ErrorKind::ConnectionError(format!("Phone did not answer history request within {:?}", timeout)) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_message_and_resolve() {
+        let chat = Jid::from_string("628111@s.whatsapp.net").unwrap();
+        let before = MessageKey {
+            remote_jid: chat.to_string(),
+            from_me: false,
+            id: "3EB0OLD".to_string(),
+            participant: None,
+        };
+        let message = request_message(&chat, &before, Some(1_700_000_000_000), 20);
+        let protocol = message.protocol_message.unwrap();
+        assert_eq!(protocol.r#type, Some(PROTOCOL_MESSAGE_PEER_DATA_OPERATION_REQUEST));
+        let request = protocol.peer_data_operation_request_message.unwrap().history_sync_on_demand_request.unwrap();
+        assert_eq!(request.oldest_msg_id, "3EB0OLD");
+        assert_eq!(request.on_demand_msg_count, 20);
+
+        let mut pending = PendingHistoryRequests::new();
+        let rx = pending.register("req1");
+        assert!(!pending.resolve("other", Vec::new()));
+        assert!(pending.resolve("req1", Vec::new()));
+        assert!(rx.try_recv().unwrap().is_empty());
+        assert!(!pending.resolve("req1", Vec::new()));
+    }
+}
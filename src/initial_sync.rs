@@ -0,0 +1,492 @@
+//! Daftar kontak dan chat awal setelah otentikasi
+//!
+//! Kontak berasal dari aksi app state `contact` (koleksi
+//! `critical_unblock_low`) digabung dengan push name yang diketahui client, dan
+//! dikirim sekali sebagai `Event::ContactsInitial` setelah koleksi itu
+//! tersinkron. Daftar chat berasal dari history sync awal dan dikirim sekali
+//! sebagai `Event::ChatsInitial`.
+//!
+//! Ponsel membagikan history lewat `HistorySyncNotification`: payload awal
+//! kadang disertakan langsung, selebihnya berupa blob terenkripsi di
+//! `direct_path` yang diunduh lewat `MediaDownloader`, didekripsi dengan media
+//! key (`HISTORY_KEY_INFO`), di-inflate (zlib), lalu didekode sebagai protobuf
+//! `HistorySync`. Setiap payload dikirim sebagai satu `Event::HistorySync`,
+//! kecuali jawaban permintaan `WhatsAppClient::request_history` yang
+//! diteruskan ke pemanggilnya.
+
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use flate2::read::ZlibDecoder;
+use ring::digest;
+
+use crate::app_state::{AppStateStore, Collection};
+use crate::errors::*;
+use crate::history_request::PendingHistoryRequests;
+use crate::media_upload::{self, MediaDownloader, HISTORY_KEY_INFO};
+use crate::message_store::SharedMessageStore;
+use crate::messages::{HistorySyncNotification, WebMessageInfo};
+use crate::names::{ContactStore, GroupStore};
+use crate::node_protocol::Node;
+use crate::proto;
+use crate::routing::NodeContext;
+use crate::{Event, EventSender, Jid};
+
+/// Nama aksi app state untuk kontak
+pub const CONTACT_ACTION: &str = "contact";
+
+/// Kontak dari buku alamat ponsel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact {
+    pub jid: Jid,
+    /// Nama di buku alamat ponsel
+    pub name: Option<String>,
+    /// Push name yang diatur kontak sendiri
+    pub notify_name: Option<String>,
+}
+
+/// Ringkasan satu chat
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatInfo {
+    pub jid: Jid,
+    pub name: Option<String>,
+    pub unread_count: u32,
+    /// Waktu pesan terakhir (detik sejak epoch)
+    pub last_message_time: Option<i64>,
+}
+
+/// Jenis history sync (`sync_type`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistorySyncType {
+    InitialBootstrap,
+    InitialStatus,
+    Full,
+    Recent,
+    PushName,
+    NonBlockingData,
+    OnDemand,
+    Unknown(u32),
+}
+
+impl HistorySyncType {
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            0 => HistorySyncType::InitialBootstrap,
+            1 => HistorySyncType::InitialStatus,
+            2 => HistorySyncType::Full,
+            3 => HistorySyncType::Recent,
+            4 => HistorySyncType::PushName,
+            5 => HistorySyncType::NonBlockingData,
+            6 => HistorySyncType::OnDemand,
+            code => HistorySyncType::Unknown(code),
+        }
+    }
+}
+
+/// Satu percakapan beserta pesannya, terlama lebih dulu
+#[derive(Debug, Clone)]
+pub struct HistoryChat {
+    pub chat: ChatInfo,
+    pub messages: Vec<WebMessageInfo>,
+}
+
+/// Satu batch history sync
+#[derive(Debug, Clone)]
+pub struct HistorySyncChunk {
+    pub sync_type: HistorySyncType,
+    pub chunk_order: u32,
+    /// Persentase history yang sudah dikirim ponsel
+    pub progress: Option<u32>,
+    pub conversations: Vec<HistoryChat>,
+}
+
+#[derive(Debug)]
+struct HistoryConversation {
+    id: String,
+    name: Option<String>,
+    unread_count: u32,
+    conversation_timestamp: Option<i64>,
+    /// Terbaru lebih dulu, seperti dikirim ponsel
+    messages: Vec<WebMessageInfo>,
+}
+
+impl From<proto::Conversation> for HistoryConversation {
+    fn from(conversation: proto::Conversation) -> Self {
+        HistoryConversation {
+            id: conversation.id,
+            name: conversation.name,
+            unread_count: conversation.unread_count.unwrap_or(0),
+            conversation_timestamp: conversation.conversation_timestamp.map(|t| t as i64),
+            messages: conversation.messages.into_iter().filter_map(|message| message.message).map(WebMessageInfo::from).collect(),
+        }
+    }
+}
+
+impl HistoryConversation {
+    fn chat_info(&self) -> Option<ChatInfo> {
+        Some(ChatInfo {
+            jid: Jid::from_string(&self.id).ok()?,
+            name: self.name.clone(),
+            unread_count: self.unread_count,
+            last_message_time: self.conversation_timestamp,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct HistoryPushname {
+    id: String,
+    pushname: String,
+}
+
+/// Payload history sync
+#[derive(Debug)]
+pub struct HistorySync {
+    sync_type: Option<u32>,
+    chunk_order: Option<u32>,
+    progress: Option<u32>,
+    conversations: Vec<HistoryConversation>,
+    pushnames: Vec<HistoryPushname>,
+}
+
+impl HistorySync {
+    /// Mendekode protobuf `HistorySync`
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let history: proto::HistorySync = proto::decode(bytes)?;
+        Ok(HistorySync {
+            sync_type: history.sync_type,
+            chunk_order: history.chunk_order,
+            progress: history.progress,
+            conversations: history.conversations.into_iter().map(HistoryConversation::from).collect(),
+            pushnames: history
+                .pushnames
+                .into_iter()
+                .filter_map(|pushname| Some(HistoryPushname { id: pushname.id?, pushname: pushname.pushname? }))
+                .collect(),
+        })
+    }
+
+    /// Chat dalam payload, terbaru lebih dulu
+    pub fn chats(&self) -> Vec<ChatInfo> {
+        let mut chats: Vec<ChatInfo> = self.conversations.iter().filter_map(HistoryConversation::chat_info).collect();
+        chats.sort_by(|a, b| b.last_message_time.cmp(&a.last_message_time));
+        chats
+    }
+
+    /// Batch untuk `Event::HistorySync`; jenis dan urutan dari notifikasi
+    /// dipakai jika payload tidak menyertakannya
+    pub fn into_chunk(self, notification: &HistorySyncNotification) -> HistorySyncChunk {
+        let conversations = self
+            .conversations
+            .into_iter()
+            .filter_map(|conversation| {
+                let chat = conversation.chat_info()?;
+                let mut messages = conversation.messages;
+                messages.reverse();
+                Some(HistoryChat { chat, messages })
+            })
+            .collect();
+        HistorySyncChunk {
+            sync_type: HistorySyncType::from_code(self.sync_type.or(notification.sync_type).unwrap_or(0)),
+            chunk_order: self.chunk_order.or(notification.chunk_order).unwrap_or(0),
+            progress: self.progress,
+            conversations,
+        }
+    }
+}
+
+/// Mengunduh, mendekripsi, meng-inflate dan mem-parse blob history sync
+pub fn download_history(downloader: &dyn MediaDownloader, notification: &HistorySyncNotification) -> Result<HistorySync> {
+    let (direct_path, media_key) = match (&notification.direct_path, &notification.media_key) {
+        (Some(direct_path), Some(media_key)) => (direct_path, media_key),
+        _ => return Err(Error { kind: ErrorKind::InvalidPayload("History sync notification has no direct_path/media_key".to_string()) }),
+    };
+    let data = downloader.download(direct_path)?;
+    if let Some(ref expected) = notification.file_enc_sha256 {
+        if digest::digest(&digest::SHA256, &data).as_ref() != expected.as_slice() {
+            return Err(Error { kind: ErrorKind::CryptoError("History sync blob hash mismatch".to_string()) });
+        }
+    }
+
+    let compressed = media_upload::decrypt_media(&data, HISTORY_KEY_INFO, media_key)?;
+    let mut inflated = Vec::new();
+    ZlibDecoder::new(compressed.as_slice())
+        .read_to_end(&mut inflated)
+        .map_err(|e| Error { kind: ErrorKind::InvalidPayload(format!("Failed to inflate history sync: {}", e)) })?;
+    HistorySync::parse(&inflated)
+}
+
+/// Daftar kontak dari app state dan push name yang diketahui
+pub fn contacts_from_app_state(app_state: &AppStateStore, names: &ContactStore) -> Vec<Contact> {
+    app_state
+        .entries(Collection::CriticalUnblockLow, CONTACT_ACTION)
+        .into_iter()
+        .filter_map(|mutation| {
+            let jid = Jid::from_string(mutation.index.get(1)?).ok()?;
+            let name = mutation.value["fullName"].as_str().or_else(|| mutation.value["firstName"].as_str());
+            Some(Contact {
+                notify_name: names.name(&jid.to_string()).map(|name| name.to_string()),
+                name: name.map(|name| name.to_string()),
+                jid,
+            })
+        })
+        .collect()
+}
+
+/// Apakah daftar kontak/chat awal sudah dikirim ke aplikasi
+#[derive(Default)]
+pub struct InitialSync {
+    contacts_delivered: bool,
+    chats_delivered: bool,
+}
+
+impl InitialSync {
+    pub fn new() -> Self {
+        InitialSync::default()
+    }
+}
+
+fn synced_collection(node: &Node, collection: Collection) -> bool {
+    node.get_child("sync")
+        .map(|sync| sync.get_children().iter().any(|child| child.tag == "collection" && child.get_attr("name") == Some(collection.name())))
+        .unwrap_or(false)
+}
+
+/// Handler `iq type="result"` yang mengirim `ContactsInitial` setelah koleksi
+/// kontak tersinkron pertama kali. Didaftarkan setelah handler app state.
+pub fn contacts_handler(
+    state: Arc<Mutex<InitialSync>>,
+    app_state: Arc<Mutex<AppStateStore>>,
+    names: Arc<Mutex<ContactStore>>,
+) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if !synced_collection(node, Collection::CriticalUnblockLow) {
+            return Ok(());
+        }
+        let mut state = state.lock().unwrap();
+        if state.contacts_delivered {
+            return Ok(());
+        }
+        state.contacts_delivered = true;
+        let contacts = contacts_from_app_state(&app_state.lock().unwrap(), &names.lock().unwrap());
+        ctx.emit(Event::ContactsInitial(contacts));
+        Ok(())
+    }
+}
+
+/// Store yang diperbarui dari history sync
+#[derive(Clone)]
+struct HistoryTargets {
+    state: Arc<Mutex<InitialSync>>,
+    requests: Arc<Mutex<PendingHistoryRequests>>,
+    contacts: Arc<Mutex<ContactStore>>,
+    groups: Arc<Mutex<GroupStore>>,
+    store: SharedMessageStore,
+}
+
+impl HistoryTargets {
+    /// Mencatat push name dan nama grup, mengirim `ChatsInitial` sekali untuk
+    /// bootstrap, lalu `HistorySync` atau jawaban ke `request_history`
+    fn deliver(&self, history: HistorySync, notification: &HistorySyncNotification, event_tx: &EventSender) {
+        {
+            let mut contacts = self.contacts.lock().unwrap();
+            for pushname in &history.pushnames {
+                contacts.set_name(&pushname.id, &pushname.pushname);
+            }
+        }
+        let chats = history.chats();
+        {
+            let mut groups = self.groups.lock().unwrap();
+            for chat in chats.iter().filter(|chat| chat.jid.is_group()) {
+                if let Some(ref name) = chat.name {
+                    groups.set_subject(&chat.jid.to_string(), name);
+                }
+            }
+        }
+
+        let chunk = history.into_chunk(notification);
+        {
+            let store = self.store.read().unwrap();
+            for message in chunk.conversations.iter().flat_map(|conversation| conversation.messages.iter()) {
+                if let Err(e) = store.insert(message) {
+                    event_tx.send(Event::Error(format!("Failed to store history message: {}", e))).ok();
+                    break;
+                }
+            }
+        }
+        if let (HistorySyncType::OnDemand, Some(request_id)) = (chunk.sync_type, &notification.peer_data_request_session_id) {
+            let messages = chunk.conversations.iter().flat_map(|conversation| conversation.messages.iter().cloned()).collect();
+            if self.requests.lock().unwrap().resolve(request_id, messages) {
+                return;
+            }
+        }
+        if chunk.sync_type == HistorySyncType::InitialBootstrap {
+            let mut state = self.state.lock().unwrap();
+            if !state.chats_delivered {
+                state.chats_delivered = true;
+                event_tx.send(Event::ChatsInitial(chats)).ok();
+            }
+        }
+        event_tx.send(Event::HistorySync(chunk)).ok();
+    }
+}
+
+/// Handler `message` untuk `HistorySyncNotification` milik sendiri. Payload
+/// inline diproses langsung; blob di `direct_path` diunduh di thread terpisah
+/// agar thread koneksi tidak tertahan.
+pub fn history_handler(
+    state: Arc<Mutex<InitialSync>>,
+    downloader: Arc<Mutex<Option<Arc<dyn MediaDownloader>>>>,
+    requests: Arc<Mutex<PendingHistoryRequests>>,
+    contacts: Arc<Mutex<ContactStore>>,
+    groups: Arc<Mutex<GroupStore>>,
+    store: SharedMessageStore,
+) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    let targets = HistoryTargets { state, requests, contacts, groups, store };
+    move |node: &Node, ctx: &NodeContext| {
+        let web_message = match node.get_bytes().and_then(|bytes| WebMessageInfo::from_bytes(bytes).ok()) {
+            Some(web_message) if web_message.key.from_me => web_message,
+            _ => return Ok(()),
+        };
+        let notification = match web_message
+            .message
+            .and_then(|message| message.protocol_message)
+            .and_then(|protocol| protocol.history_sync_notification)
+        {
+            Some(notification) => notification,
+            None => return Ok(()),
+        };
+
+        if let Some(ref payload) = notification.initial_hist_bootstrap_inline_payload {
+            targets.deliver(HistorySync::parse(payload)?, &notification, ctx.event_tx);
+            return Ok(());
+        }
+        if notification.direct_path.is_none() {
+            return Ok(());
+        }
+        let downloader = downloader
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("No media downloader configured for history sync (see WhatsAppClient::set_media_downloader)")?;
+
+        let targets = targets.clone();
+        let event_tx = ctx.event_tx.clone();
+        thread::spawn(move || match download_history(downloader.as_ref(), &notification) {
+            Ok(history) => targets.deliver(history, &notification, &event_tx),
+            Err(e) => {
+                event_tx.send(Event::Error(format!("History sync download failed: {}", e))).ok();
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::Mutation;
+    use prost::Message as _;
+
+    fn conversation(id: &str, timestamp: u64) -> proto::Conversation {
+        proto::Conversation { id: id.to_string(), conversation_timestamp: Some(timestamp), ..Default::default() }
+    }
+
+    #[test]
+    fn test_history_chats_sorted_newest_first() {
+        let payload = proto::HistorySync {
+            conversations: vec![
+                proto::Conversation { unread_count: Some(2), ..conversation("628111@s.whatsapp.net", 100) },
+                proto::Conversation { name: Some("Tim".to_string()), ..conversation("120363@g.us", 200) },
+            ],
+            pushnames: vec![proto::Pushname { id: Some("628111@s.whatsapp.net".to_string()), pushname: Some("Budi".to_string()) }],
+            ..Default::default()
+        };
+        let history = HistorySync::parse(&payload.encode_to_vec()).unwrap();
+        let chats = history.chats();
+        assert_eq!(chats[0].name.as_deref(), Some("Tim"));
+        assert_eq!(chats[1].unread_count, 2);
+        assert_eq!(history.pushnames[0].pushname, "Budi");
+        assert!(HistorySync::parse(br#"{"conversations":[]}"#).is_err());
+    }
+
+    struct FixedDownloader(Vec<u8>);
+
+    impl MediaDownloader for FixedDownloader {
+        fn download(&self, direct_path: &str) -> Result<Vec<u8>> {
+            assert_eq!(direct_path, "/v/t62.7114-24/history");
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn test_download_history_blob() {
+        use flate2::write::ZlibEncoder;
+        use std::io::Write;
+
+        let message = |id: &str| proto::HistorySyncMsg {
+            message: Some(proto::WebMessageInfo {
+                key: proto::MessageKey { remote_jid: Some("628111@s.whatsapp.net".to_string()), id: Some(id.to_string()), ..Default::default() },
+                message: Some(proto::Message::text("halo")),
+                ..Default::default()
+            }),
+            msg_order_id: None,
+        };
+        let payload = proto::HistorySync {
+            sync_type: Some(3),
+            chunk_order: Some(2),
+            progress: Some(40),
+            conversations: vec![proto::Conversation { messages: vec![message("B"), message("A")], ..conversation("628111@s.whatsapp.net", 100) }],
+            ..Default::default()
+        };
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&payload.encode_to_vec()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let media = media_upload::encrypt_with_info(&compressed, HISTORY_KEY_INFO, &[3u8; 32]).unwrap();
+
+        let notification = HistorySyncNotification {
+            file_sha256: None,
+            file_length: None,
+            media_key: Some(media.media_key.clone()),
+            file_enc_sha256: Some(media.file_enc_sha256.clone()),
+            direct_path: Some("/v/t62.7114-24/history".to_string()),
+            sync_type: Some(3),
+            chunk_order: None,
+            original_message_id: None,
+            peer_data_request_session_id: None,
+            initial_hist_bootstrap_inline_payload: None,
+        };
+        let history = download_history(&FixedDownloader(media.data.clone()), &notification).unwrap();
+        let chunk = history.into_chunk(&notification);
+        assert_eq!(chunk.sync_type, HistorySyncType::Recent);
+        assert_eq!(chunk.chunk_order, 2);
+        assert_eq!(chunk.progress, Some(40));
+        assert_eq!(chunk.conversations[0].chat.jid.to_string(), "628111@s.whatsapp.net");
+        // Pesan diurutkan terlama lebih dulu
+        let ids: Vec<&str> = chunk.conversations[0].messages.iter().map(|message| message.key.id.as_str()).collect();
+        assert_eq!(ids, ["A", "B"]);
+
+        let mut corrupted = media.data.clone();
+        corrupted[0] ^= 1;
+        assert!(download_history(&FixedDownloader(corrupted), &notification).is_err());
+    }
+
+    #[test]
+    fn test_contacts_merge_push_names() {
+        let mut app_state = AppStateStore::new();
+        app_state.apply(
+            Collection::CriticalUnblockLow,
+            &Mutation::set(
+                vec![CONTACT_ACTION.to_string(), "628111@s.whatsapp.net".to_string()],
+                serde_json::json!({ "fullName": "Budi Santoso" }),
+            ),
+        );
+        let mut names = ContactStore::new();
+        names.set_name("628111@s.whatsapp.net", "Budi");
+
+        let contacts = contacts_from_app_state(&app_state, &names);
+        assert_eq!(contacts[0].name.as_deref(), Some("Budi Santoso"));
+        assert_eq!(contacts[0].notify_name.as_deref(), Some("Budi"));
+    }
+}
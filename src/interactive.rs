@@ -0,0 +1,199 @@
+//! Pesan interaktif native flow
+//!
+//! Setiap tombol native flow punya `name` (jenis alur) dan `button_params_json`
+//! yang formatnya berbeda per jenis. `InteractiveBuilder` membangun JSON itu
+//! untuk alur yang umum: pilihan tunggal, tombol URL, salin kode, dan balasan
+//! cepat. Jawaban pengguna (`InteractiveResponseMessage`) dikirim ke aplikasi
+//! sebagai `Event::InteractiveResponse` bersama id pesan yang dijawab.
+
+use serde_json::{json, Value};
+
+use crate::errors::*;
+use crate::messages::{
+    self, InteractiveMessage, InteractiveMessageBody, InteractiveMessageFooter, InteractiveMessageHeader, NativeFlowButton, NativeFlowMessage,
+    WebMessageInfo,
+};
+use crate::{Event, Jid, WhatsAppClient};
+
+/// Nama alur native flow
+pub const FLOW_SINGLE_SELECT: &str = "single_select";
+pub const FLOW_CTA_URL: &str = "cta_url";
+pub const FLOW_CTA_COPY: &str = "cta_copy";
+pub const FLOW_QUICK_REPLY: &str = "quick_reply";
+
+/// Baris pilihan pada alur `single_select`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectRow {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+}
+
+impl SelectRow {
+    pub fn new(id: &str, title: &str, description: &str) -> Self {
+        SelectRow { id: id.to_string(), title: title.to_string(), description: description.to_string() }
+    }
+}
+
+/// Builder pesan interaktif
+#[derive(Debug, Clone, Default)]
+pub struct InteractiveBuilder {
+    body: String,
+    header: Option<String>,
+    footer: Option<String>,
+    buttons: Vec<NativeFlowButton>,
+}
+
+impl InteractiveBuilder {
+    pub fn new(body: &str) -> Self {
+        InteractiveBuilder { body: body.to_string(), ..Default::default() }
+    }
+
+    pub fn header(mut self, title: &str) -> Self {
+        self.header = Some(title.to_string());
+        self
+    }
+
+    pub fn footer(mut self, text: &str) -> Self {
+        self.footer = Some(text.to_string());
+        self
+    }
+
+    fn button(mut self, name: &str, params: Value) -> Self {
+        self.buttons.push(NativeFlowButton { name: name.to_string(), button_params_json: params.to_string() });
+        self
+    }
+
+    /// Daftar pilihan; `sections` berisi judul bagian dan barisnya
+    pub fn single_select(self, button_text: &str, sections: &[(&str, Vec<SelectRow>)]) -> Self {
+        let sections: Vec<Value> = sections
+            .iter()
+            .map(|(title, rows)| {
+                let rows: Vec<Value> = rows
+                    .iter()
+                    .map(|row| json!({ "header": "", "title": row.title, "description": row.description, "id": row.id }))
+                    .collect();
+                json!({ "title": title, "highlight_label": "", "rows": rows })
+            })
+            .collect();
+        self.button(FLOW_SINGLE_SELECT, json!({ "title": button_text, "sections": sections }))
+    }
+
+    /// Tombol yang membuka `url`
+    pub fn cta_url(self, display_text: &str, url: &str) -> Self {
+        self.button(FLOW_CTA_URL, json!({ "display_text": display_text, "url": url, "merchant_url": url }))
+    }
+
+    /// Tombol yang menyalin `code` ke clipboard
+    pub fn copy_code(self, display_text: &str, code: &str) -> Self {
+        self.button(FLOW_CTA_COPY, json!({ "display_text": display_text, "id": code, "copy_code": code }))
+    }
+
+    /// Tombol balasan; `id` dikirim kembali dalam `InteractiveReply`
+    pub fn quick_reply(self, display_text: &str, id: &str) -> Self {
+        self.button(FLOW_QUICK_REPLY, json!({ "display_text": display_text, "id": id }))
+    }
+
+    pub fn build(&self) -> Result<messages::Message> {
+        if self.buttons.is_empty() {
+            return Err("Interactive message requires at least one button".into());
+        }
+        if self.body.trim().is_empty() {
+            return Err("Interactive message body must not be empty".into());
+        }
+        Ok(messages::Message {
+            interactive_message: Some(InteractiveMessage {
+                header: self.header.as_ref().map(|title| InteractiveMessageHeader {
+                    title: title.clone(),
+                    has_media_attachment: Some(false),
+                    ..Default::default()
+                }),
+                body: Some(InteractiveMessageBody { text: self.body.clone() }),
+                footer: self.footer.as_ref().map(|text| InteractiveMessageFooter { text: text.clone() }),
+                native_flow_message: Some(NativeFlowMessage { buttons: self.buttons.clone(), message_params_json: "{}".to_string() }),
+                context_info: None,
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+/// Jawaban pengguna atas pesan interaktif
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractiveReply {
+    /// Nama alur dari klien pengguna
+    pub name: String,
+    /// Id baris atau tombol yang dipilih
+    pub id: Option<String>,
+    /// `params_json` lengkap
+    pub params: Value,
+}
+
+/// Event untuk jawaban pesan interaktif; `None` jika bukan jawaban
+pub fn response_event(web_message: &WebMessageInfo) -> Option<Event> {
+    let response = web_message.message.as_ref()?.interactive_response_message.as_ref()?;
+    let flow = response.native_flow_response_message.as_ref()?;
+    let params: Value = serde_json::from_str(&flow.params_json).unwrap_or(Value::Null);
+    let prompt_id = response.context_info.as_ref().and_then(|context| context.stanza_id.clone());
+    Some(Event::InteractiveResponse {
+        prompt_id,
+        reply: InteractiveReply {
+            name: flow.name.clone(),
+            id: params["id"].as_str().map(|id| id.to_string()),
+            params,
+        },
+        message: web_message.clone(),
+    })
+}
+
+impl WhatsAppClient {
+    /// Mengirim pesan interaktif. Mengembalikan id pesan untuk dicocokkan dengan
+    /// `prompt_id` pada `Event::InteractiveResponse`.
+    pub fn send_interactive(&self, to: &Jid, builder: &InteractiveBuilder) -> Result<String> {
+        let message = builder.build()?;
+        self.send_message(to, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{InteractiveResponseMessage, MessageContextInfo, MessageKey, NativeFlowResponseMessage};
+
+    #[test]
+    fn test_button_params_and_response_pairing() {
+        let message = InteractiveBuilder::new("Pilih paket")
+            .single_select("Lihat paket", &[("Internet", vec![SelectRow::new("p1", "10 GB", "30 hari")])])
+            .copy_code("Salin kode", "HEMAT10")
+            .build()
+            .unwrap();
+        let buttons = message.interactive_message.unwrap().native_flow_message.unwrap().buttons;
+        let select: Value = serde_json::from_str(&buttons[0].button_params_json).unwrap();
+        assert_eq!(select["sections"][0]["rows"][0]["id"], "p1");
+        assert_eq!(buttons[1].name, FLOW_CTA_COPY);
+        assert!(InteractiveBuilder::new("kosong").build().is_err());
+
+        let reply = WebMessageInfo {
+            key: MessageKey { remote_jid: "628111@s.whatsapp.net".to_string(), from_me: false, id: "R1".to_string(), participant: None },
+            message: Some(messages::Message {
+                interactive_response_message: Some(InteractiveResponseMessage {
+                    native_flow_response_message: Some(NativeFlowResponseMessage {
+                        name: "menu_options".to_string(),
+                        params_json: r#"{"id":"p1"}"#.to_string(),
+                    }),
+                    context_info: Some(MessageContextInfo { stanza_id: Some("PROMPT".to_string()), ..Default::default() }),
+                    body: None,
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        match response_event(&reply) {
+            Some(Event::InteractiveResponse { prompt_id, reply, .. }) => {
+                assert_eq!(prompt_id.as_deref(), Some("PROMPT"));
+                assert_eq!(reply.id.as_deref(), Some("p1"));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
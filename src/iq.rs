@@ -0,0 +1,118 @@
+//! IQ request/response sinkron
+//!
+//! Operasi seperti membuat grup membutuhkan balasan server sebelum bisa
+//! mengembalikan hasil. `PendingQueries` menyimpan penunggu per id IQ dan
+//! handler router meneruskan balasan `result`/`error` ke penunggunya.
+//!
+//! `WhatsAppClient::query` memblokir thread pemanggil, jadi jangan dipanggil
+//! dari dalam node handler (balasan diproses di thread yang sama).
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::WhatsAppClient;
+
+/// Batas waktu default menunggu balasan IQ
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Penunggu balasan IQ per id
+#[derive(Default)]
+pub struct PendingQueries {
+    waiters: HashMap<String, Sender<Node>>,
+}
+
+impl PendingQueries {
+    pub fn new() -> Self {
+        PendingQueries::default()
+    }
+
+    /// Mendaftarkan penunggu untuk IQ `id`
+    pub fn register(&mut self, id: &str) -> Receiver<Node> {
+        let (tx, rx) = mpsc::channel();
+        self.waiters.insert(id.to_string(), tx);
+        rx
+    }
+
+    pub fn cancel(&mut self, id: &str) {
+        self.waiters.remove(id);
+    }
+
+    /// Meneruskan balasan ke penunggunya; false jika tidak ada yang menunggu
+    pub fn resolve(&mut self, node: &Node) -> bool {
+        match node.get_attr("id").and_then(|id| self.waiters.remove(id)) {
+            Some(waiter) => waiter.send(node.clone()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Handler `iq type="result"`/`type="error"` yang membangunkan penunggu
+pub fn response_handler(queries: Arc<Mutex<PendingQueries>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        queries.lock().unwrap().resolve(node);
+        Ok(())
+    }
+}
+
+/// Mengubah balasan `iq type="error"` menjadi `Err`
+pub fn check_response(node: &Node) -> Result<Node> {
+    if node.get_attr("type") != Some("error") {
+        return Ok(node.clone());
+    }
+    let error = node.get_child("error");
+    let code = error.and_then(|error| error.get_attr("code")).and_then(|code| code.parse().ok()).unwrap_or(0);
+    let text = error.and_then(|error| error.get_attr("text")).unwrap_or("no description").to_string();
+    Err(Error { kind: ErrorKind::IqError { code, text } })
+}
+
+impl WhatsAppClient {
+    /// Mengirim IQ dan menunggu balasannya. IQ harus memiliki atribut `id`.
+    pub(crate) fn query(&self, node: &Node, timeout: Duration) -> Result<Node> {
+        let id = node.get_attr("id").ok_or("IQ query requires an id")?.to_string();
+        let rx = self.queries.lock().unwrap().register(&id);
+
+        if let Err(e) = self.send_node(node) {
+            self.queries.lock().unwrap().cancel(&id);
+            return Err(e);
+        }
+
+        match rx.recv_timeout(timeout) {
+            Ok(response) => check_response(&response),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => {
+                self.queries.lock().unwrap().cancel(&id);
+                Err(Error { kind: ErrorKind::ConnectionError(format!("No response to IQ {} within {:?}", id, timeout)) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_wakes_waiter() {
+        let mut queries = PendingQueries::new();
+        let rx = queries.register("q1");
+
+        assert!(!queries.resolve(&Node::new("iq").attr("id", "other")));
+        assert!(queries.resolve(&Node::new("iq").attr("id", "q1").attr("type", "result")));
+        assert_eq!(rx.try_recv().unwrap().get_attr("type"), Some("result"));
+        // Sekali pakai
+        assert!(!queries.resolve(&Node::new("iq").attr("id", "q1")));
+    }
+
+    #[test]
+    fn test_error_response() {
+        let error = Node::new("iq")
+            .attr("type", "error")
+            .children(vec![Node::new("error").attr("code", "403").attr("text", "forbidden")]);
+        assert_eq!(check_response(&error).unwrap_err().server_code(), Some(403));
+        assert!(check_response(&Node::new("iq").attr("type", "result")).is_ok());
+    }
+}
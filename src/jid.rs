@@ -0,0 +1,204 @@
+//! JID (Jabber ID) pengguna, grup, dan perangkat
+//!
+//! Bentuk umum: `user[.agent][:device]@server`. Bagian `device` hanya ada
+//! pada JID perangkat multi-device (mis. `628123:12@s.whatsapp.net`);
+//! `to_non_ad` membuangnya untuk mendapatkan JID akun. JID tanpa `user`
+//! (mis. `s.whatsapp.net` pada atribut `to` iq) juga valid.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::*;
+
+/// Server (bagian setelah `@`) sebuah JID
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Server {
+    /// `s.whatsapp.net`; decoder lama menulisnya sebagai `c.us`
+    User,
+    /// `g.us`
+    Group,
+    /// `lid`, logical ID yang menyembunyikan nomor telepon
+    Lid,
+    /// `broadcast`, termasuk `status@broadcast`
+    Broadcast,
+    /// `newsletter` (channel)
+    Newsletter,
+    /// `hosted`, akun bisnis yang dihosting Meta
+    Hosted,
+}
+
+impl Server {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Server::User => "s.whatsapp.net",
+            Server::Group => "g.us",
+            Server::Lid => "lid",
+            Server::Broadcast => "broadcast",
+            Server::Newsletter => "newsletter",
+            Server::Hosted => "hosted",
+        }
+    }
+}
+
+impl FromStr for Server {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "s.whatsapp.net" | "c.us" => Ok(Server::User),
+            "g.us" => Ok(Server::Group),
+            "lid" => Ok(Server::Lid),
+            "broadcast" => Ok(Server::Broadcast),
+            "newsletter" => Ok(Server::Newsletter),
+            "hosted" => Ok(Server::Hosted),
+            _ => Err(format!("Unknown JID server {}", s).into()),
+        }
+    }
+}
+
+impl fmt::Display for Server {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Identitas pengguna, grup, atau perangkat di WhatsApp
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Jid {
+    pub id: String,
+    pub server: Server,
+    /// Perangkat multi-device; `None` untuk JID akun
+    pub device: Option<u16>,
+    /// Agent (domain) perangkat; `None` sama dengan 0
+    pub agent: Option<u8>,
+}
+
+impl Jid {
+    pub fn new(id: String, server: Server) -> Self {
+        Jid { id, server, device: None, agent: None }
+    }
+
+    /// JID pengguna (`@s.whatsapp.net`)
+    pub fn user(id: &str) -> Self {
+        Jid::new(id.to_string(), Server::User)
+    }
+
+    /// JID grup (`@g.us`)
+    pub fn group(id: &str) -> Self {
+        Jid::new(id.to_string(), Server::Group)
+    }
+
+    /// JID perangkat `device` milik akun ini
+    pub fn with_device(mut self, device: u16) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    pub fn from_string(jid_str: &str) -> Result<Self> {
+        jid_str.parse()
+    }
+
+    pub fn is_user(&self) -> bool {
+        self.server == Server::User
+    }
+
+    pub fn is_group(&self) -> bool {
+        self.server == Server::Group
+    }
+
+    pub fn is_lid(&self) -> bool {
+        self.server == Server::Lid
+    }
+
+    pub fn is_broadcast(&self) -> bool {
+        self.server == Server::Broadcast
+    }
+
+    pub fn is_newsletter(&self) -> bool {
+        self.server == Server::Newsletter
+    }
+
+    /// JID perangkat (AD JID), bukan JID akun
+    pub fn is_ad(&self) -> bool {
+        self.device.is_some() || self.agent.is_some()
+    }
+
+    /// JID akun tanpa bagian perangkat dan agent
+    pub fn to_non_ad(&self) -> Jid {
+        Jid::new(self.id.clone(), self.server)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        !self.id.is_empty() && self.id.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    }
+}
+
+impl fmt::Display for Jid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.id.is_empty() {
+            return f.write_str(self.server.as_str());
+        }
+        f.write_str(&self.id)?;
+        if let Some(agent) = self.agent.filter(|agent| *agent != 0) {
+            write!(f, ".{}", agent)?;
+        }
+        if let Some(device) = self.device {
+            write!(f, ":{}", device)?;
+        }
+        write!(f, "@{}", self.server)
+    }
+}
+
+impl FromStr for Jid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (user, server) = match s.split_once('@') {
+            Some((user, server)) => (user, server),
+            None => ("", s),
+        };
+        let server: Server = server.parse()?;
+        if user.is_empty() {
+            return Ok(Jid::new(String::new(), server));
+        }
+
+        let invalid = || -> Error { format!("Invalid JID format {}", s).into() };
+        let (user, device) = match user.split_once(':') {
+            Some((user, device)) => (user, Some(device.parse::<u16>().map_err(|_| invalid())?)),
+            None => (user, None),
+        };
+        // Hanya JID perangkat yang memiliki agent; titik di JID grup lama adalah bagian id
+        let (id, agent) = match user.split_once('.') {
+            // Agent 0 sama dengan tanpa agent, agar `Eq`/`Hash` cocok dengan `to_string`
+            Some((id, agent)) if device.is_some() => (id, Some(agent.parse::<u8>().map_err(|_| invalid())?).filter(|agent| *agent != 0)),
+            _ => (user, None),
+        };
+        if id.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Jid { id: id.to_string(), server, device, agent })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        for jid in ["628123@s.whatsapp.net", "628123:12@s.whatsapp.net", "628123.1:3@s.whatsapp.net", "123-456@g.us", "555:2@lid", "status@broadcast", "1203@newsletter", "g.us"] {
+            assert_eq!(jid.parse::<Jid>().unwrap().to_string(), jid);
+        }
+
+        let device: Jid = "628123.0:12@c.us".parse().unwrap();
+        assert_eq!((device.server, device.device, device.agent), (Server::User, Some(12), None));
+        assert_eq!(device.to_string(), "628123:12@s.whatsapp.net");
+        assert_eq!(device, "628123:12@s.whatsapp.net".parse().unwrap());
+        assert!(device.is_ad());
+        assert_eq!(device.to_non_ad(), Jid::user("628123"));
+
+        assert!("628123@example.com".parse::<Jid>().is_err());
+        assert!("628123:x@s.whatsapp.net".parse::<Jid>().is_err());
+        assert!(":1@s.whatsapp.net".parse::<Jid>().is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Jurnal pesan masuk untuk pemulihan setelah aplikasi crash
+//!
+//! Jika diaktifkan (`WhatsAppClientBuilder::with_event_journal`), setiap pesan
+//! masuk ditulis ke `StateStore` sebelum diserahkan ke aplikasi dan dikirim
+//! sebagai `Event::DurableMessage` alih-alih `Event::MessageReceived`. Entry
+//! tetap tersimpan sampai aplikasi memanggil `JournaledMessage::commit`.
+//! Setelah restart, `WhatsAppClient::replay_journal` mengirim ulang pesan yang
+//! belum di-commit.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::errors::*;
+use crate::messages::WebMessageInfo;
+use crate::store::{self, StateStore};
+use crate::{Event, WhatsAppClient};
+
+/// Prefix key jurnal di `StateStore`
+pub const JOURNAL_PREFIX: &str = "journal:msg:";
+
+fn journal_key(message: &WebMessageInfo) -> String {
+    format!("{}{}:{}", JOURNAL_PREFIX, message.key.remote_jid, message.key.id)
+}
+
+/// Jurnal pesan yang belum selesai diproses aplikasi
+pub struct EventJournal {
+    store: Arc<dyn StateStore>,
+}
+
+impl EventJournal {
+    pub fn new(store: Arc<dyn StateStore>) -> Self {
+        EventJournal { store }
+    }
+
+    pub fn record(&self, message: &WebMessageInfo) -> Result<()> {
+        store::save_json(self.store.as_ref(), &journal_key(message), message)
+    }
+
+    pub fn commit(&self, message: &WebMessageInfo) -> Result<()> {
+        self.store.remove(&journal_key(message))
+    }
+
+    /// Pesan yang belum di-commit, urut waktu
+    pub fn pending(&self) -> Result<Vec<WebMessageInfo>> {
+        let mut messages = Vec::new();
+        for key in self.store.keys(JOURNAL_PREFIX)? {
+            if let Some(message) = store::load_json::<WebMessageInfo>(self.store.as_ref(), &key)? {
+                messages.push(message);
+            }
+        }
+        messages.sort_by_key(|message| message.message_timestamp.unwrap_or(0));
+        Ok(messages)
+    }
+}
+
+/// Pesan masuk yang tercatat di jurnal
+#[derive(Clone)]
+pub struct JournaledMessage {
+    pub message: WebMessageInfo,
+    /// Dikirim ulang dari jurnal setelah restart
+    pub replayed: bool,
+    journal: Arc<EventJournal>,
+}
+
+impl JournaledMessage {
+    /// Menandai pesan selesai diproses; entry jurnal dihapus
+    pub fn commit(&self) -> Result<()> {
+        self.journal.commit(&self.message)
+    }
+}
+
+impl fmt::Debug for JournaledMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JournaledMessage")
+            .field("message", &self.message)
+            .field("replayed", &self.replayed)
+            .finish()
+    }
+}
+
+/// Mencatat `MessageReceived` ke jurnal dan mengubahnya menjadi `DurableMessage`.
+/// Jika penulisan jurnal gagal, event asli diteruskan agar pesan tidak hilang.
+pub fn journal_event(journal: &Arc<EventJournal>, event: Event) -> Event {
+    match event {
+        Event::MessageReceived(message) => match journal.record(&message) {
+            Ok(()) => Event::DurableMessage(JournaledMessage {
+                message,
+                replayed: false,
+                journal: Arc::clone(journal),
+            }),
+            Err(_) => Event::MessageReceived(message),
+        },
+        event => event,
+    }
+}
+
+impl WhatsAppClient {
+    /// Mengirim ulang pesan jurnal yang belum di-commit sebagai
+    /// `Event::DurableMessage` dengan `replayed = true`. Mengembalikan jumlahnya.
+    pub fn replay_journal(&self) -> Result<usize> {
+        let journal = self.event_tx.journal.clone().ok_or("Event journal is not enabled")?;
+        let pending = journal.pending()?;
+        let count = pending.len();
+        for message in pending {
+            self.event_tx
+                .send(Event::DurableMessage(JournaledMessage {
+                    message,
+                    replayed: true,
+                    journal: Arc::clone(&journal),
+                }))
+                .ok();
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::MessageKey;
+    use crate::store::MemoryStateStore;
+
+    fn message(id: &str, timestamp: u64) -> WebMessageInfo {
+        WebMessageInfo {
+            key: MessageKey {
+                remote_jid: "628111@s.whatsapp.net".to_string(),
+                from_me: false,
+                id: id.to_string(),
+                participant: None,
+            },
+            message_timestamp: Some(timestamp),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_uncommitted_messages_survive() {
+        let journal = Arc::new(EventJournal::new(Arc::new(MemoryStateStore::new())));
+
+        let first = journal_event(&journal, Event::MessageReceived(message("m2", 20)));
+        journal_event(&journal, Event::MessageReceived(message("m1", 10)));
+        assert!(matches!(journal_event(&journal, Event::QrTimeout), Event::QrTimeout));
+
+        let pending: Vec<String> = journal.pending().unwrap().into_iter().map(|m| m.key.id).collect();
+        assert_eq!(pending, vec!["m1", "m2"]);
+
+        match first {
+            Event::DurableMessage(durable) => durable.commit().unwrap(),
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert_eq!(journal.pending().unwrap().len(), 1);
+    }
+}
@@ -238,12 +238,7 @@ pub fn build_presence_subscribe(jid: &Jid) -> JsonValue {
 }
 
 pub fn build_file_upload_request(hash: &[u8], media_type: MediaType) -> JsonValue {
-    array!["action", "encr_upload", match media_type {
-        MediaType::Image => "image",
-        MediaType::Video => "video",
-        MediaType::Audio => "audio",
-        MediaType::Document => "document",
-    }, base64::encode(hash)]
+    array!["action", "encr_upload", media_type.upload_path(), base64::encode(hash)]
 }
 
 pub fn parse_file_upload_response<'a>(response: &'a JsonValue) -> Result<&'a str> {
@@ -0,0 +1,140 @@
+//! Keepalive: ping berkala dan deteksi koneksi mati
+//!
+//! Koneksi TCP yang setengah terbuka (mis. NAT kedaluwarsa, jaringan seluler
+//! berpindah) tidak pernah menutup socket, sehingga client tampak terhubung
+//! selamanya. Setelah terotentikasi, client mengirim IQ ping (`xmlns="w:p"`)
+//! setiap interval. Jika pong tidak datang dalam `PONG_TIMEOUT`, koneksi
+//! dianggap mati lalu ditutup dan client menyambung ulang.
+
+use std::time::{Duration, Instant};
+
+use ws::util::Token;
+use ws::Sender;
+
+use crate::node_protocol::Node;
+use crate::utils;
+
+/// Token timeout ws untuk keepalive
+pub const KEEPALIVE_TOKEN: Token = Token(3);
+
+/// Interval ping default
+pub const PING_INTERVAL: Duration = Duration::from_secs(25);
+
+/// Batas waktu menunggu pong sebelum koneksi dianggap mati
+pub const PONG_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// IQ ping ke server
+pub fn ping_node(id: &str) -> Node {
+    Node::new("iq")
+        .attr("id", id)
+        .attr("xmlns", "w:p")
+        .attr("type", "get")
+        .attr("to", "s.whatsapp.net")
+        .children(vec![Node::new("ping")])
+}
+
+/// Tindakan pada satu tick keepalive
+#[derive(Debug)]
+pub enum KeepaliveTick {
+    /// Kirim ping ini
+    Ping(Node),
+    /// Pong terakhir tidak dijawab; koneksi harus ditutup
+    Dead,
+    /// Masih menunggu pong
+    Waiting,
+}
+
+/// Status ping yang sedang berjalan
+pub struct KeepaliveMonitor {
+    interval: Option<Duration>,
+    timeout: Duration,
+    /// (id ping, waktu kirim)
+    outstanding: Option<(String, Instant)>,
+    last_pong: Option<Instant>,
+    last_rtt: Option<Duration>,
+}
+
+impl KeepaliveMonitor {
+    /// `None` menonaktifkan keepalive
+    pub fn new(interval: Option<Duration>) -> Self {
+        KeepaliveMonitor {
+            interval,
+            timeout: PONG_TIMEOUT,
+            outstanding: None,
+            last_pong: None,
+            last_rtt: None,
+        }
+    }
+
+    /// Menjadwalkan tick berikutnya; saat menunggu pong tick dijadwalkan di batas waktunya
+    pub fn schedule(&self, out: &Sender) -> ws::Result<()> {
+        let delay = match (self.interval, &self.outstanding) {
+            (None, _) => return Ok(()),
+            (Some(_), Some(_)) => self.timeout,
+            (Some(interval), None) => interval,
+        };
+        out.timeout(delay.as_millis() as u64, KEEPALIVE_TOKEN)
+    }
+
+    pub fn on_tick(&mut self, now: Instant) -> KeepaliveTick {
+        if let Some((_, sent_at)) = self.outstanding {
+            if now.saturating_duration_since(sent_at) >= self.timeout {
+                self.outstanding = None;
+                return KeepaliveTick::Dead;
+            }
+            return KeepaliveTick::Waiting;
+        }
+        let id = utils::generate_message_id();
+        self.outstanding = Some((id.clone(), now));
+        KeepaliveTick::Ping(ping_node(&id))
+    }
+
+    /// Mencatat pong; true jika `node` adalah jawaban ping kita
+    pub fn on_node(&mut self, node: &Node, now: Instant) -> bool {
+        let is_pong = node.tag == "iq"
+            && node.get_attr("type") == Some("result")
+            && matches!((&self.outstanding, node.get_attr("id")), (Some((id, _)), Some(found)) if id == found);
+        if is_pong {
+            if let Some((_, sent_at)) = self.outstanding.take() {
+                self.last_rtt = Some(now.saturating_duration_since(sent_at));
+            }
+            self.last_pong = Some(now);
+        }
+        is_pong
+    }
+
+    pub fn last_pong(&self) -> Option<Instant> {
+        self.last_pong
+    }
+
+    /// Round-trip ping terakhir yang dijawab
+    pub fn last_rtt(&self) -> Option<Duration> {
+        self.last_rtt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_pong_marks_connection_dead() {
+        let mut keepalive = KeepaliveMonitor::new(Some(PING_INTERVAL));
+        let start = Instant::now();
+
+        let ping = match keepalive.on_tick(start) {
+            KeepaliveTick::Ping(ping) => ping,
+            tick => panic!("unexpected {:?}", tick),
+        };
+        assert_eq!(ping.get_attr("xmlns"), Some("w:p"));
+        let pong = Node::new("iq").attr("id", ping.get_attr("id").unwrap()).attr("type", "result");
+        assert!(!keepalive.on_node(&Node::new("iq").attr("id", "other").attr("type", "result"), start));
+        assert!(keepalive.on_node(&pong, start));
+        assert_eq!(keepalive.last_pong(), Some(start));
+        assert_eq!(keepalive.last_rtt(), Some(Duration::ZERO));
+
+        assert!(matches!(keepalive.on_tick(start), KeepaliveTick::Ping(_)));
+        assert!(matches!(keepalive.on_tick(start + Duration::from_secs(5)), KeepaliveTick::Waiting));
+        assert!(matches!(keepalive.on_tick(start + PONG_TIMEOUT), KeepaliveTick::Dead));
+    }
+}
@@ -0,0 +1,228 @@
+//! Instrumentasi latensi per pesan keluar
+//!
+//! Setiap pesan dicatat waktunya di tiap tahap pipeline:
+//! enqueue → encrypt → socket write → server ack → delivered.
+//! Selisih antar tahap menunjukkan apakah lambatnya ada di kripto (termasuk
+//! menunggu kunci), jaringan/server, atau perangkat penerima.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+
+/// Jumlah pesan terakhir yang disimpan timing-nya
+const LATENCY_CAPACITY: usize = 4096;
+
+/// Tahap pipeline pengiriman
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Enqueued,
+    Encrypted,
+    Written,
+    ServerAck,
+    Delivered,
+}
+
+/// Waktu tiap tahap untuk satu pesan
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageTimings {
+    pub enqueued: Option<Instant>,
+    pub encrypted: Option<Instant>,
+    pub written: Option<Instant>,
+    pub server_ack: Option<Instant>,
+    pub delivered: Option<Instant>,
+}
+
+fn span(from: Option<Instant>, to: Option<Instant>) -> Option<Duration> {
+    Some(to?.saturating_duration_since(from?))
+}
+
+impl MessageTimings {
+    /// Tahap yang sudah tercatat tidak ditimpa (receipt/ack bisa datang berulang)
+    fn mark(&mut self, stage: Stage, at: Instant) {
+        let slot = match stage {
+            Stage::Enqueued => &mut self.enqueued,
+            Stage::Encrypted => &mut self.encrypted,
+            Stage::Written => &mut self.written,
+            Stage::ServerAck => &mut self.server_ack,
+            Stage::Delivered => &mut self.delivered,
+        };
+        slot.get_or_insert(at);
+    }
+
+    /// enqueue → encrypt, termasuk waktu menunggu kunci penerima
+    pub fn encrypt_time(&self) -> Option<Duration> {
+        span(self.enqueued, self.encrypted)
+    }
+
+    /// encrypt → socket write
+    pub fn write_time(&self) -> Option<Duration> {
+        span(self.encrypted, self.written)
+    }
+
+    /// socket write → server ack (jaringan dan server)
+    pub fn server_ack_time(&self) -> Option<Duration> {
+        span(self.written, self.server_ack)
+    }
+
+    /// socket write → delivered; server ack bisa tidak ada atau datang setelah receipt
+    pub fn delivery_time(&self) -> Option<Duration> {
+        span(self.written, self.delivered)
+    }
+
+    /// enqueue → delivered
+    pub fn total(&self) -> Option<Duration> {
+        span(self.enqueued, self.delivered)
+    }
+}
+
+/// Ringkasan satu tahap: jumlah sampel, rata-rata dan maksimum
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StageStats {
+    pub count: usize,
+    pub average: Duration,
+    pub max: Duration,
+}
+
+impl StageStats {
+    fn from_samples<I: Iterator<Item = Duration>>(samples: I) -> Self {
+        let mut stats = StageStats::default();
+        let mut sum = Duration::default();
+        for sample in samples {
+            stats.count += 1;
+            sum += sample;
+            stats.max = stats.max.max(sample);
+        }
+        if stats.count > 0 {
+            stats.average = sum / stats.count as u32;
+        }
+        stats
+    }
+}
+
+/// Statistik latensi atas pesan yang masih tersimpan di tracker
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyStats {
+    pub encrypt: StageStats,
+    pub write: StageStats,
+    pub server_ack: StageStats,
+    pub delivery: StageStats,
+    pub total: StageStats,
+}
+
+/// Menyimpan timing pesan terakhir (id pesan -> timing)
+pub struct LatencyTracker {
+    timings: HashMap<String, MessageTimings>,
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        LatencyTracker {
+            timings: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: LATENCY_CAPACITY,
+        }
+    }
+
+    /// Mencatat tahap untuk pesan. Tahap selain `Enqueued` untuk pesan yang
+    /// tidak dikenal diabaikan (misalnya receipt untuk pesan dari sesi lain).
+    pub fn mark(&mut self, message_id: &str, stage: Stage) {
+        let now = Instant::now();
+        if let Some(timings) = self.timings.get_mut(message_id) {
+            timings.mark(stage, now);
+            return;
+        }
+        if stage != Stage::Enqueued {
+            return;
+        }
+
+        let mut timings = MessageTimings::default();
+        timings.mark(stage, now);
+        self.timings.insert(message_id.to_string(), timings);
+        self.order.push_back(message_id.to_string());
+        while self.order.len() > self.capacity {
+            if let Some(old) = self.order.pop_front() {
+                self.timings.remove(&old);
+            }
+        }
+    }
+
+    pub fn timings(&self, message_id: &str) -> Option<MessageTimings> {
+        self.timings.get(message_id).cloned()
+    }
+
+    pub fn stats(&self) -> LatencyStats {
+        let all = || self.timings.values();
+        LatencyStats {
+            encrypt: StageStats::from_samples(all().filter_map(|t| t.encrypt_time())),
+            write: StageStats::from_samples(all().filter_map(|t| t.write_time())),
+            server_ack: StageStats::from_samples(all().filter_map(|t| t.server_ack_time())),
+            delivery: StageStats::from_samples(all().filter_map(|t| t.delivery_time())),
+            total: StageStats::from_samples(all().filter_map(|t| t.total())),
+        }
+    }
+}
+
+/// Handler `ack` dari server untuk pesan yang kita kirim
+pub fn ack_handler(latency: Arc<Mutex<LatencyTracker>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if matches!(node.get_attr("class"), None | Some("message")) {
+            if let Some(id) = node.get_attr("id") {
+                latency.lock().unwrap().mark(id, Stage::ServerAck);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Handler `receipt` yang mencatat tahap delivered
+pub fn receipt_handler(latency: Arc<Mutex<LatencyTracker>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if node.get_attr("type") == Some("error") {
+            return Ok(());
+        }
+        let mut latency = latency.lock().unwrap();
+        for id in crate::delivery::receipt_message_ids(node) {
+            latency.mark(id, Stage::Delivered);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_messages_ignored() {
+        let mut tracker = LatencyTracker::new();
+        tracker.mark("m1", Stage::Delivered);
+        assert!(tracker.timings("m1").is_none());
+
+        tracker.mark("m1", Stage::Enqueued);
+        tracker.mark("m1", Stage::Encrypted);
+        tracker.mark("m1", Stage::Written);
+        tracker.mark("m1", Stage::Delivered);
+        let timings = tracker.timings("m1").unwrap();
+        assert!(timings.total().is_some());
+        assert!(timings.server_ack_time().is_none());
+
+        let stats = tracker.stats();
+        assert_eq!(stats.total.count, 1);
+        assert_eq!(stats.server_ack.count, 0);
+    }
+
+    #[test]
+    fn test_first_mark_wins() {
+        let mut timings = MessageTimings::default();
+        let first = Instant::now();
+        timings.mark(Stage::Delivered, first);
+        timings.mark(Stage::Delivered, first + Duration::from_secs(1));
+        assert_eq!(timings.delivered, Some(first));
+    }
+}
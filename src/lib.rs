@@ -5,10 +5,10 @@
 //! untuk mengembangkan aplikasi WhatsApp seperti bot, gateway, atau layanan otomasi.
 
 use std::sync::Arc;
-use std::collections::HashMap;
 use std::thread;
-use std::sync::{Mutex, mpsc};
-use std::time::{SystemTime, Duration};
+use std::sync::{Mutex, RwLock, mpsc};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{SystemTime, Duration, Instant};
 
 use ws::{CloseCode, Handler, Sender, Message};
 use ring::{agreement, digest, hmac, hkdf, rand};
@@ -24,68 +24,166 @@ pub mod crypto;
 pub mod session;
 pub mod handshake;
 pub mod node_protocol;
+pub mod protocol_constants;
+pub mod proto;
+pub mod node_attrs;
+pub mod jid;
+pub mod framing;
 pub mod messages;
 pub mod errors;
+pub mod routing;
+pub mod pairing;
+pub mod device_props;
+pub mod logout;
+pub mod send_queue;
+pub mod delivery;
+pub mod heartbeat;
+pub mod qr;
+pub mod receipts;
+pub mod store;
+pub mod bot;
+pub mod outbox;
+pub mod devices;
+pub mod revoke;
+pub mod latency;
+pub mod message_builder;
+pub mod app_state;
+pub mod syncd;
+pub mod address_book;
+pub mod stickers;
+pub mod chats;
+pub mod message_store;
+pub mod chat_handle;
+pub mod initial_sync;
+pub mod history_request;
+pub mod ephemeral;
+pub mod panic_report;
+pub mod phone;
+pub mod presence;
+pub mod traffic;
+pub mod two_step;
+pub mod warmup;
+pub mod handover;
+pub mod send_options;
+pub mod dispatch;
+pub mod names;
+pub mod media_upload;
+pub mod media_pool;
+pub mod media_validation;
+pub mod voice;
+pub mod link_preview;
+pub mod status;
+pub mod support;
+pub mod broadcast;
+pub mod iq;
+pub mod usync;
+pub mod groups;
+pub mod communities;
+pub mod newsletter;
+pub mod calls;
+pub mod orders;
+pub mod interactive;
+pub mod buttons;
+pub mod templates;
+pub mod payments;
+pub mod journal;
+pub mod replay;
+pub mod dedup;
+pub mod prekeys;
+pub mod signal;
+pub mod retry;
+pub mod offline;
+pub mod rate_limit;
+pub mod keepalive;
+pub mod tls;
+pub mod wire_trace;
+pub mod telemetry;
+pub mod event_stream;
+pub mod middleware;
+mod trace;
+pub mod account;
+pub mod picture;
+pub mod polls;
+#[cfg(feature = "backup-keys")]
+pub mod backup;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "testing")]
+pub mod loopback;
+
+// Nama lama dari modul duplikat yang sudah digabung ke satu API; akan dihapus
+#[deprecated(note = "use `rustdi::node_protocol`")]
+pub mod node_protocol_new {
+    pub use crate::node_protocol::*;
+}
+#[deprecated(note = "use `rustdi::session`")]
+pub mod session_new {
+    pub use crate::session::*;
+}
+#[deprecated(note = "use `rustdi::crypto`")]
+pub mod crypto_new {
+    pub use crate::crypto::*;
+}
+#[deprecated(note = "use `rustdi::messages`")]
+pub mod messages_extended {
+    pub use crate::messages::*;
+}
+#[deprecated(note = "use the client types from the crate root")]
+pub mod client {
+    pub use crate::{Event, EventHandler, WhatsAppClient, WhatsAppClientBuilder};
+}
 
 pub use errors::*;
 
 // Re-eksport struktur penting
 pub use session::Session;
 pub use crypto::{SessionKeys, generate_keypair, derive_session_keys};
-pub use node_protocol::{Node, NodeEncoder, NodeDecoder};
+pub use node_protocol::{Node, NodeEncoder, NodeDecoder, NodeRef};
+pub use node_attrs::AttrParser;
 pub use messages::*;
+pub use routing::{NodeRouter, NodeHandler, NodeContext};
+pub use delivery::{DeliveryReport, DeliverySummary, MessageStatus, RecipientStatus};
+pub use latency::{LatencyStats, MessageTimings};
+pub use message_builder::MessageBuilder;
+pub use buttons::{ButtonsMessageBuilder, ListMessageBuilder};
+pub use jid::{Jid, Server};
+pub use traffic::{TrafficProfile, TrafficSettings, PresenceMode};
+pub use send_options::SendOptions;
+pub use presence::ChatState;
+pub use receipts::ReceiptType;
+pub use warmup::{WarmupLimit, WarmupSettings, WarmupStatus};
+pub use rate_limit::{Budget, RateLimit};
+pub use tls::TlsConfig;
+pub use device_props::{DeviceProps, Platform};
+pub use logout::DisconnectReason;
+pub use telemetry::ClientMetrics;
+pub use event_stream::EventSubscription;
+pub use app_state::Collection;
+pub use address_book::{ConflictPolicy, ContactEntry, ImportSummary};
+pub use status::{StatusAudience, StatusContent};
+pub use chat_handle::ChatHandle;
+pub use initial_sync::{ChatInfo, Contact, HistoryChat, HistorySyncChunk, HistorySyncType};
+pub use communities::LinkedGroup;
+pub use groups::{GroupInfoChange, GroupMetadata, GroupParticipant, GroupSetting, ParticipantStatus};
+pub use journal::JournaledMessage;
+pub use polls::PollResults;
+pub use prekeys::KeyRotation;
+pub use usync::OnWhatsAppResult;
+pub use signal::{EncryptedType, PreKeyBundle};
+pub use account::{Account, PrivacySetting, PrivacySettings, PrivacyValue};
+pub use media_upload::{MediaUploader, MediaDownloader, EncryptedMedia, UploadedMedia};
+pub use media_pool::{MediaPoolConfig, TransferLimiter};
+pub use names::{NameResolver, ContactStore, GroupStore, StoreNameResolver};
+pub use store::{StateStore, MemoryStateStore, FileStateStore};
+pub use message_store::{MessageStore, MemoryMessageStore};
+#[cfg(feature = "sqlite")]
+pub use message_store::SqliteMessageStore;
+pub use bot::{Bot, CommandContext, Role, requires_admin};
 
 // ========================
 // STRUKTUR DATA UTAMA
 // ========================
 
-/// Identitas pengguna atau grup di WhatsApp
-#[derive(Debug, Clone, PartialOrd, PartialEq)]
-pub struct Jid {
-    pub id: String,
-    pub is_group: bool,
-    pub is_lid: bool, // Logical ID untuk nomor internasional
-}
-
-impl Jid {
-    pub fn new(id: String, is_group: bool, is_lid: bool) -> Self {
-        Jid { id, is_group, is_lid }
-    }
-
-    pub fn to_string(&self) -> String {
-        if self.is_lid {
-            format!("{}@lid", self.id)
-        } else if self.is_group {
-            format!("{}@g.us", self.id)
-        } else {
-            format!("{}@s.whatsapp.net", self.id)
-        }
-    }
-
-    pub fn from_string(jid_str: &str) -> Result<Self> {
-        let parts: Vec<&str> = jid_str.split('@').collect();
-        if parts.len() != 2 {
-            return Err("Invalid JID format".into());
-        }
-
-        let id = parts[0].to_string();
-        let suffix = parts[1];
-
-        let (is_group, is_lid) = match suffix {
-            "s.whatsapp.net" => (false, false),
-            "g.us" => (true, false),
-            "lid" => (false, true),
-            _ => return Err("Unknown JID suffix".into()),
-        };
-
-        Ok(Jid { id, is_group, is_lid })
-    }
-
-    pub fn is_valid(&self) -> bool {
-        !self.id.is_empty() && self.id.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
-    }
-}
-
 /// Status kehadiran pengguna
 #[derive(Debug, Copy, Clone)]
 pub enum PresenceStatus {
@@ -96,12 +194,56 @@ pub enum PresenceStatus {
 }
 
 /// Jenis media yang didukung
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum MediaType {
     Image,
     Video,
     Audio,
     Document,
+    /// Stiker WebP
+    Sticker,
+    /// Voice note (push-to-talk)
+    Ptt,
+    /// Video MP4 yang diputar berulang seperti GIF
+    Gif,
+}
+
+impl MediaType {
+    /// Menebak jenis media dari mimetype; mimetype tak dikenal dianggap dokumen
+    pub fn from_mimetype(mimetype: &str) -> MediaType {
+        let essence = mimetype.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "image/webp" => MediaType::Sticker,
+            "image/gif" => MediaType::Gif,
+            "audio/ogg" if mimetype.contains("opus") => MediaType::Ptt,
+            _ if essence.starts_with("image/") => MediaType::Image,
+            _ if essence.starts_with("video/") => MediaType::Video,
+            _ if essence.starts_with("audio/") => MediaType::Audio,
+            _ => MediaType::Document,
+        }
+    }
+
+    /// Jenis dasar untuk upload dan kunci enkripsi: stiker diupload sebagai
+    /// gambar, voice note sebagai audio dan GIF sebagai video
+    pub fn upload_kind(self) -> MediaType {
+        match self {
+            MediaType::Sticker => MediaType::Image,
+            MediaType::Ptt => MediaType::Audio,
+            MediaType::Gif => MediaType::Video,
+            other => other,
+        }
+    }
+
+    /// Segmen path upload di server media
+    pub fn upload_path(self) -> &'static str {
+        match self.upload_kind() {
+            MediaType::Image => "image",
+            MediaType::Video => "video",
+            MediaType::Audio => "audio",
+            _ => "document",
+        }
+    }
 }
 
 /// Jenis perubahan participant grup
@@ -154,24 +296,138 @@ impl Clone for AuthMethod {
 // EVENT HANDLER
 // ========================
 
-/// Jenis event yang diterima oleh aplikasi
-#[derive(Debug)]
+/// Jenis event yang diterima oleh aplikasi: koneksi, pesan, receipt
+/// (`MessageStatusChanged`), presence, grup dan error.
+///
+/// Enum ini `non_exhaustive`; sertakan cabang `_` saat mencocokkan agar
+/// event baru tidak merusak kode aplikasi.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Event {
     Connected,
-    Disconnected,
+    Disconnected(DisconnectReason),
     Authenticating,
     Authenticated,
     MessageReceived(messages::WebMessageInfo),
-    MessageAck(messages::MessageAck),
+    /// Status pesan keluar naik (ack server, terkirim, dibaca, diputar)
+    MessageStatusChanged { message_id: String, status: delivery::MessageStatus },
     PresenceChanged(Jid, PresenceStatus, Option<NaiveDateTime>),
     GroupParticipantsChanged {
         group: Jid,
         change_type: GroupParticipantsChange,
         participants: Vec<Jid>,
     },
+    /// Akun menjadi anggota grup; `newly_created` jika grup baru dibuat oleh `inducer`
+    GroupIntroduce {
+        newly_created: bool,
+        inducer: Jid,
+        meta: groups::GroupMetadata,
+    },
     Error(String),
     QrCodeGenerated(String),
+    /// Semua QR ref kedaluwarsa tanpa dipindai; panggil `connect` lagi untuk mengulang
+    QrTimeout,
     PairingCodeGenerated(String),
+    DeliveryReportCompleted(delivery::DeliverySummary),
+    /// Dikirim periodik dari event loop koneksi (lihat `with_heartbeat_interval`)
+    Heartbeat {
+        state: ConnectionState,
+        last_rx: Option<SystemTime>,
+        queue_len: usize,
+    },
+    /// Pesan `key` di `chat` dihapus untuk semua orang oleh pengirimnya (atau admin grup)
+    MessageRevoked {
+        chat: Jid,
+        key: messages::MessageKey,
+    },
+    /// Pesan sementara di `chat` diaktifkan (`Some(durasi)`) atau dinonaktifkan (`None`)
+    EphemeralSettingChanged {
+        chat: Jid,
+        expiration: Option<Duration>,
+    },
+    /// `EventHandler::handle_event` untuk event `event` melewati batas waktu;
+    /// handler ditinggalkan dan event berikutnya ditangani worker baru
+    HandlerTimeout { event: String, timeout: Duration },
+    /// Media view once diterima; buka lalu panggil `mark_viewed`
+    ViewOnceReceived(messages::WebMessageInfo),
+    /// Ponsel (perangkat utama) tersambung atau terputus dari server
+    PhoneConnectionChanged { connected: bool },
+    /// Pesan dikirim saat ponsel sudah lama offline; pesan bisa tertahan di server
+    PhoneOfflineAdvisory { offline_for: Duration },
+    /// Lease sesi diambil proses lain (`holder`); koneksi sudah diputus
+    SessionLeaseLost { holder: String },
+    /// Verifikasi dua langkah (PIN) diaktifkan atau dinonaktifkan
+    TwoStepStatusChanged { enabled: bool },
+    /// Server meminta PIN dua langkah dimasukkan ulang di ponsel; pairing dan
+    /// login tertahan sampai itu dilakukan
+    TwoStepVerificationRequired,
+    /// Pesan masuk yang dikirim lewat daftar siaran, bukan chat langsung
+    BroadcastMessageReceived(messages::WebMessageInfo),
+    /// Pesan masuk yang tercatat di jurnal; panggil `commit` setelah selesai diproses
+    DurableMessage(journal::JournaledMessage),
+    /// Koleksi app state selesai disinkronkan dari server
+    AppStateSynced { collection: app_state::Collection },
+    /// Panic di thread library saat `context`; koneksi dipulihkan jika bisa
+    InternalError { context: String, backtrace: String },
+    /// Pengiriman ditolak karena batas pemanasan akun baru
+    WarmupLimitReached(warmup::WarmupLimit),
+    /// Masa pemanasan akun baru selesai; batas tidak berlaku lagi
+    WarmupCompleted,
+    /// Daftar kontak awal setelah otentikasi, dikirim sekali per client
+    ContactsInitial(Vec<initial_sync::Contact>),
+    /// Daftar chat awal dari history sync, terbaru lebih dulu
+    ChatsInitial(Vec<initial_sync::ChatInfo>),
+    /// Satu batch percakapan dan pesan dari history sync ponsel
+    HistorySync(initial_sync::HistorySyncChunk),
+    /// Pesan di grup pengumuman komunitas `community`
+    CommunityAnnouncement {
+        community: Jid,
+        message: messages::WebMessageInfo,
+    },
+    /// Subject, deskripsi atau foto grup diubah oleh `author`
+    GroupInfoChanged {
+        group: Jid,
+        change: groups::GroupInfoChange,
+        author: Option<Jid>,
+    },
+    /// Pengaturan grup diubah oleh `author` (admin)
+    GroupSettingChanged {
+        group: Jid,
+        setting: groups::GroupSetting,
+        enabled: bool,
+        author: Option<Jid>,
+    },
+    /// Pesan dari antrean offline terkirim setelah koneksi pulih
+    OfflineMessageSent { message_id: String, to: String },
+    /// Pesan di antrean offline dibuang karena melewati TTL
+    OfflineMessageExpired { message_id: String, to: String },
+    /// Stanza dari `from` dibuang karena terdeteksi sebagai replay
+    StanzaReplayRejected {
+        tag: String,
+        from: String,
+        id: String,
+        reason: replay::ReplayReason,
+    },
+    /// Pesan baru dari channel yang diikuti
+    NewsletterMessage(newsletter::NewsletterMessage),
+    /// Panggilan masuk; tolak dengan `WhatsAppClient::reject_call`
+    IncomingCall { from: Jid, call_id: String, is_video: bool },
+    /// Panggilan tak terjawab selama client offline
+    MissedCall { from: Jid, call_id: String, is_video: bool },
+    /// Panggilan berakhir (ditutup penelepon, timeout, ditolak perangkat lain)
+    CallTerminated { from: Jid, call_id: String, reason: Option<String> },
+    /// Pesanan baru dari pembeli (akun bisnis)
+    OrderReceived { order: orders::Order, message: messages::WebMessageInfo },
+    /// Jawaban atas pesan interaktif `prompt_id` (id dari `send_interactive`)
+    InteractiveResponse {
+        prompt_id: Option<String>,
+        reply: interactive::InteractiveReply,
+        message: messages::WebMessageInfo,
+    },
+    /// Status transaksi WhatsApp Pay berubah
+    PaymentUpdated(payments::PaymentUpdate),
+    /// Pesan diberi atau dihapus bintangnya dari perangkat lain
+    MessageStarred { key: messages::MessageKey, starred: bool },
 }
 
 /// Handler untuk menangani event dari server WhatsApp
@@ -179,6 +435,64 @@ pub trait EventHandler: Send + Sync + 'static {
     fn handle_event(&self, event: Event);
 }
 
+/// Versi WhatsApp Web yang dikirim saat inisialisasi
+pub const WEB_VERSION: [u32; 3] = [2, 3000, 1015901307];
+
+/// Pengirim event yang menghitung jumlah event yang belum di-poll aplikasi
+#[derive(Clone)]
+pub struct EventSender {
+    tx: mpsc::Sender<Event>,
+    pending: Arc<AtomicUsize>,
+    journal: Option<Arc<journal::EventJournal>>,
+    diagnostics: Arc<Mutex<support::Diagnostics>>,
+    wire: Arc<Mutex<wire_trace::WireTrace>>,
+    metrics: Arc<telemetry::Counters>,
+    /// Pelanggan `events`/`subscribe`
+    subscribers: tokio::sync::broadcast::Sender<Event>,
+    middleware: Arc<RwLock<Vec<middleware::EventMiddleware>>>,
+}
+
+impl EventSender {
+    pub fn send(&self, event: Event) -> std::result::Result<(), mpsc::SendError<Event>> {
+        self.diagnostics.lock().unwrap().record_event(&event);
+        // Salinan rantai agar middleware boleh menambah middleware lain
+        let chain = self.middleware.read().unwrap().clone();
+        let event = match middleware::apply(&chain, event) {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+        let event = match self.journal {
+            Some(ref journal) => journal::journal_event(journal, event),
+            None => event,
+        };
+        if self.subscribers.receiver_count() > 0 {
+            self.subscribers.send(event.clone()).ok();
+        }
+        // Hitung sebelum mengirim agar poll_event tidak pernah mengurangi di bawah nol
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        self.tx.send(event).map_err(|e| {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            e
+        })
+    }
+
+    /// Jumlah event di antrean yang belum diambil lewat `poll_event`
+    pub fn queue_len(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// Mencatat node masuk/keluar (id stanza, metrik) dan meneruskannya ke mode debug protokol
+    pub(crate) fn trace_node(&self, direction: wire_trace::Direction, node: &node_protocol::Node) {
+        trace::event!(debug, direction = direction, tag = node.tag, id = node.get_attr("id"), "stanza");
+        self.metrics.observe(direction, node);
+        // Hook dipanggil tanpa memegang lock
+        let trace = self.wire.lock().unwrap().clone();
+        if trace.is_active() {
+            trace.observe(direction, node);
+        }
+    }
+}
+
 // ========================
 // CLIENT UTAMA
 // ========================
@@ -198,10 +512,54 @@ pub struct WhatsAppClient {
     id: String,
     state: Arc<Mutex<ConnectionState>>,
     session: Arc<Mutex<Option<session::Session>>>,
+    pre_keys: Arc<Mutex<prekeys::PreKeyUploads>>,
     sender: Arc<Mutex<Option<Sender>>>,
     event_handler: Arc<dyn EventHandler>,
-    event_tx: mpsc::Sender<Event>,
-    event_rx: mpsc::Receiver<Event>,
+    event_tx: EventSender,
+    event_rx: Arc<Mutex<mpsc::Receiver<Event>>>,
+    handler_timeout: Duration,
+    dispatcher_running: Arc<AtomicBool>,
+    router: Arc<Mutex<routing::NodeRouter>>,
+    websocket_url: String,
+    delivery: Arc<Mutex<delivery::DeliveryTracker>>,
+    outbox: Arc<Mutex<outbox::PendingOutbox>>,
+    signal: Arc<Mutex<signal::SignalStore>>,
+    latency: Arc<Mutex<latency::LatencyTracker>>,
+    app_state: Arc<Mutex<app_state::AppStateStore>>,
+    sync_collections: Vec<app_state::Collection>,
+    unread: Arc<Mutex<chats::UnreadChats>>,
+    recent: Arc<Mutex<chats::RecentMessages>>,
+    message_store: message_store::SharedMessageStore,
+    ephemeral: Arc<Mutex<ephemeral::EphemeralSettings>>,
+    phone: Arc<Mutex<phone::PhoneMonitor>>,
+    two_step: Arc<Mutex<two_step::TwoStepMonitor>>,
+    traffic: Arc<Mutex<traffic::TrafficShaper>>,
+    contacts: Arc<Mutex<names::ContactStore>>,
+    groups: Arc<Mutex<names::GroupStore>>,
+    names: names::SharedNameResolver,
+    uploader: Arc<Mutex<Option<Arc<dyn media_upload::MediaUploader>>>>,
+    downloader: Arc<Mutex<Option<Arc<dyn media_upload::MediaDownloader>>>>,
+    history_requests: Arc<Mutex<history_request::PendingHistoryRequests>>,
+    queries: Arc<Mutex<iq::PendingQueries>>,
+    polls: Arc<Mutex<polls::PollTracker>>,
+    communities: Arc<Mutex<communities::CommunityRegistry>>,
+    presence: Arc<Mutex<presence::PresenceSubscriptions>>,
+    warmup: Arc<Mutex<warmup::WarmupScheduler>>,
+    chat_states: Arc<Mutex<presence::ChatStateTimers>>,
+    sent_log: Arc<Mutex<revoke::SentLog>>,
+    retries: Arc<Mutex<retry::RetryCache>>,
+    offline: Arc<Mutex<offline::OfflineOutbox>>,
+    rate_limiter: Arc<Mutex<rate_limit::RateLimiter>>,
+    lease: Option<Arc<handover::SessionLease>>,
+    heartbeat_interval: Option<Duration>,
+    keepalive_interval: Option<Duration>,
+    tls: Option<openssl::ssl::SslConnector>,
+    device: DeviceProps,
+    /// Diset `logout` agar penutupan koneksi dilaporkan sebagai `DisconnectReason::Removed`
+    logged_out: Arc<AtomicBool>,
+    /// Framing koneksi aktif; diganti baru setiap kali tersambung
+    framing: Arc<Mutex<framing::Framing>>,
+    send_queue: Arc<send_queue::SendQueue>,
 }
 
 impl WhatsAppClient {
@@ -213,134 +571,531 @@ impl WhatsAppClient {
         rand::SystemRandom::new().fill(&mut id_bytes).map_err(|_| "Failed to generate ID")?;
         let id = base64::encode_config(&id_bytes, base64::URL_SAFE);
 
+        let delivery = Arc::new(Mutex::new(delivery::DeliveryTracker::new()));
+        let session = Arc::new(Mutex::new(None));
+        let sender = Arc::new(Mutex::new(None));
+        let framing = Arc::new(Mutex::new(framing::Framing::new()));
+        let send_queue = Arc::new(send_queue::SendQueue::start(Arc::clone(&sender), Arc::clone(&framing), send_queue::DEFAULT_SEND_QUEUE_CAPACITY));
+        let pre_keys = Arc::new(Mutex::new(prekeys::PreKeyUploads::new()));
+        let communities = Arc::new(Mutex::new(communities::CommunityRegistry::new()));
+        let mut router = routing::NodeRouter::with_communities(Arc::clone(&communities));
+        router.register("iq", Some("result"), communities::metadata_handler(Arc::clone(&communities)));
+        let queries = Arc::new(Mutex::new(iq::PendingQueries::new()));
+        router.register("iq", Some("result"), iq::response_handler(Arc::clone(&queries)));
+        router.register("iq", Some("error"), iq::response_handler(Arc::clone(&queries)));
+        router.register("receipt", None, delivery::receipt_handler(Arc::clone(&delivery)));
+        router.register("ack", None, delivery::ack_handler(Arc::clone(&delivery)));
+        let latency = Arc::new(Mutex::new(latency::LatencyTracker::new()));
+        router.register("receipt", None, latency::receipt_handler(Arc::clone(&latency)));
+        router.register("ack", None, latency::ack_handler(Arc::clone(&latency)));
+        let contacts = Arc::new(Mutex::new(names::ContactStore::new()));
+        let groups = Arc::new(Mutex::new(names::GroupStore::new()));
+        let resolver: Arc<dyn names::NameResolver> = Arc::new(names::StoreNameResolver::new(Arc::clone(&contacts), Arc::clone(&groups)));
+        let names: names::SharedNameResolver = Arc::new(Mutex::new(resolver));
+        router.register("message", None, names::push_name_handler(Arc::clone(&contacts)));
+        router.register("iq", Some("result"), names::group_subject_handler(Arc::clone(&groups)));
+        router.register("notification", Some("w:gp2"), names::group_subject_handler(Arc::clone(&groups)));
+        let outbox = Arc::new(Mutex::new(outbox::PendingOutbox::new()));
+        let signal = Arc::new(Mutex::new(signal::SignalStore::new()));
+        for kind in ["result", "error"] {
+            router.register(
+                "iq",
+                Some(kind),
+                outbox::key_response_handler(Arc::clone(&outbox), Arc::clone(&signal), Arc::clone(&session), Arc::clone(&latency), Arc::clone(&names)),
+            );
+        }
+        router.register("notification", Some("encrypt"), outbox::identity_change_handler(Arc::clone(&outbox)));
+        router.register("notification", Some("encrypt"), signal::identity_change_handler(Arc::clone(&signal)));
+        let retries = Arc::new(Mutex::new(retry::RetryCache::new()));
+        router.register(
+            "receipt",
+            Some("retry"),
+            retry::retry_receipt_handler(Arc::clone(&retries), Arc::clone(&outbox), Arc::clone(&signal), Arc::clone(&session)),
+        );
+        router.register("notification", Some("devices"), devices::device_notification_handler(Arc::clone(&outbox)));
+        router.register("notification", Some("encrypt"), prekeys::low_pre_key_handler(Arc::clone(&session), Arc::clone(&pre_keys)));
+        router.register("iq", Some("result"), prekeys::upload_response_handler(Arc::clone(&session), Arc::clone(&pre_keys)));
+        router.register("iq", Some("error"), prekeys::upload_response_handler(Arc::clone(&session), Arc::clone(&pre_keys)));
+        router.register("message", None, outbox::inbound_session_handler(Arc::clone(&outbox)));
+        let app_state = Arc::new(Mutex::new(app_state::AppStateStore::new()));
+        router.register("iq", Some("result"), app_state::sync_response_handler(Arc::clone(&app_state)));
+        router.register("message", None, app_state::key_share_handler(Arc::clone(&app_state)));
+        let initial_sync = Arc::new(Mutex::new(initial_sync::InitialSync::new()));
+        router.register(
+            "iq",
+            Some("result"),
+            initial_sync::contacts_handler(Arc::clone(&initial_sync), Arc::clone(&app_state), Arc::clone(&contacts)),
+        );
+        let downloader = Arc::new(Mutex::new(None));
+        let history_requests = Arc::new(Mutex::new(history_request::PendingHistoryRequests::new()));
+        let message_store = message_store::shared(Arc::new(MemoryMessageStore::new()));
+        router.register(
+            "message",
+            None,
+            initial_sync::history_handler(
+                initial_sync,
+                Arc::clone(&downloader),
+                Arc::clone(&history_requests),
+                Arc::clone(&contacts),
+                Arc::clone(&groups),
+                Arc::clone(&message_store),
+            ),
+        );
+        let unread = Arc::new(Mutex::new(chats::UnreadChats::new()));
+        router.register("message", None, chats::unread_handler(Arc::clone(&unread)));
+        let recent = Arc::new(Mutex::new(chats::RecentMessages::new()));
+        router.register("message", None, chats::recent_handler(Arc::clone(&recent)));
+        router.register("message", None, message_store::store_handler(Arc::clone(&message_store)));
+        let ephemeral = Arc::new(Mutex::new(ephemeral::EphemeralSettings::new()));
+        router.register("message", None, ephemeral::message_handler(Arc::clone(&ephemeral)));
+        router.register("notification", Some("w:gp2"), ephemeral::group_notification_handler(Arc::clone(&ephemeral)));
+        router.register("notification", Some("w:gp2"), groups::setting_notification_handler());
+        router.register("notification", Some("w:gp2"), groups::info_notification_handler());
+        router.register("notification", Some("picture"), groups::info_notification_handler());
+        let phone = Arc::new(Mutex::new(phone::PhoneMonitor::new()));
+        router.register("iq", Some("error"), phone::iq_error_handler(Arc::clone(&phone)));
+        router.register("message", None, phone::own_message_handler(Arc::clone(&phone)));
+        let two_step = Arc::new(Mutex::new(two_step::TwoStepMonitor::new()));
+        router.register("notification", Some("account_sync"), two_step::notification_handler(Arc::clone(&two_step)));
+        let traffic = Arc::new(Mutex::new(traffic::TrafficShaper::default()));
+        router.register("message", None, traffic::read_receipt_handler(Arc::clone(&traffic)));
+        let warmup = Arc::new(Mutex::new(warmup::WarmupScheduler::new()));
+        router.register("message", None, warmup::inbound_handler(Arc::clone(&warmup)));
+        let polls = Arc::new(Mutex::new(polls::PollTracker::new()));
+        router.register("message", None, polls::vote_handler(Arc::clone(&polls)));
+        router.register("message", None, newsletter::message_handler());
+        router.register("call", None, calls::call_handler());
+        router.register("message", None, payments::update_handler());
+        router.register("presence", None, presence::presence_handler());
+        router.register("chatstate", None, presence::presence_handler());
+
         Ok(WhatsAppClient {
             id,
             state: Arc::new(Mutex::new(ConnectionState::Disconnected)),
-            session: Arc::new(Mutex::new(None)),
-            sender: Arc::new(Mutex::new(None)),
+            session,
+            pre_keys,
+            sender,
             event_handler: Arc::from(event_handler),
-            event_tx: tx,
-            event_rx: rx,
+            event_tx: EventSender {
+                tx,
+                pending: Arc::new(AtomicUsize::new(0)),
+                journal: None,
+                diagnostics: Arc::new(Mutex::new(support::Diagnostics::new())),
+                wire: Arc::new(Mutex::new(wire_trace::WireTrace::new())),
+                metrics: Arc::new(telemetry::Counters::new()),
+                subscribers: event_stream::channel(),
+                middleware: Arc::new(RwLock::new(Vec::new())),
+            },
+            event_rx: Arc::new(Mutex::new(rx)),
+            handler_timeout: dispatch::DEFAULT_HANDLER_TIMEOUT,
+            dispatcher_running: Arc::new(AtomicBool::new(false)),
+            router: Arc::new(Mutex::new(router)),
+            websocket_url: "wss://web.whatsapp.com/ws".to_string(),
+            delivery,
+            outbox,
+            signal,
+            latency,
+            app_state,
+            sync_collections: app_state::ALL_COLLECTIONS.to_vec(),
+            unread,
+            recent,
+            message_store,
+            ephemeral,
+            phone,
+            two_step,
+            traffic,
+            contacts,
+            groups,
+            names,
+            uploader: Arc::new(Mutex::new(None)),
+            downloader,
+            history_requests,
+            queries,
+            polls,
+            communities,
+            presence: Arc::new(Mutex::new(presence::PresenceSubscriptions::new())),
+            warmup,
+            chat_states: Arc::new(Mutex::new(presence::ChatStateTimers::new())),
+            sent_log: Arc::new(Mutex::new(revoke::SentLog::new())),
+            retries,
+            offline: Arc::new(Mutex::new(offline::OfflineOutbox::new())),
+            rate_limiter: Arc::new(Mutex::new(rate_limit::RateLimiter::new())),
+            lease: None,
+            heartbeat_interval: None,
+            keepalive_interval: Some(keepalive::PING_INTERVAL),
+            tls: None,
+            device: DeviceProps::default(),
+            logged_out: Arc::new(AtomicBool::new(false)),
+            framing,
+            send_queue,
         })
     }
 
     /// Menghubungkan ke server WhatsApp
     pub fn connect(&self, auth_method: AuthMethod) -> Result<()> {
+        self.acquire_session_lease()?;
         let state_clone = Arc::clone(&self.state);
         let sender_clone = Arc::clone(&self.sender);
         let session_clone = Arc::clone(&self.session);
+        let pre_keys_clone = Arc::clone(&self.pre_keys);
+        let router_clone = Arc::clone(&self.router);
+        let phone_clone = Arc::clone(&self.phone);
+        let two_step_clone = Arc::clone(&self.two_step);
+        let presence_mode = self.traffic.lock().unwrap().settings.presence;
+        let app_state_clone = Arc::clone(&self.app_state);
+        let sync_collections = self.sync_collections.clone();
+        let presence_clone = Arc::clone(&self.presence);
+        let outbox_clone = Arc::clone(&self.outbox);
+        let signal_clone = Arc::clone(&self.signal);
+        let latency_clone = Arc::clone(&self.latency);
+        let offline_clone = Arc::clone(&self.offline);
         let event_tx = self.event_tx.clone();
         let id = self.id.clone();
+        let websocket_url = self.websocket_url.clone();
+        let heartbeat_interval = self.heartbeat_interval;
+        let keepalive_interval = self.keepalive_interval;
+        let reconnect = Arc::new(AtomicBool::new(false));
+        let tls = self.tls.clone();
+        let device = self.device.clone();
+        let logged_out = Arc::clone(&self.logged_out);
+        let framing_clone = Arc::clone(&self.framing);
 
         thread::spawn(move || {
+            // Semua event koneksi ini, termasuk callback WsHandler, berada di span ini
+            #[cfg(feature = "tracing")]
+            let _span = tracing::info_span!("connection", client_id = %id).entered();
             *state_clone.lock().unwrap() = ConnectionState::Connecting;
             
-            let url = Url::parse("wss://web.whatsapp.com/ws")
+            let url = Url::parse(&websocket_url)
                 .map_err(|e| format!("Invalid WebSocket URL: {}", e))
                 .unwrap();
 
-            if let Err(e) = ws::connect(url, |out| {
-                *sender_clone.lock().unwrap() = Some(out.clone());
-                *state_clone.lock().unwrap() = ConnectionState::Authenticating;
-                
-                // Kirim event bahwa kita sedang otentikasi
-                event_tx.send(Event::Authenticating).ok();
-
-                // Kirim permintaan inisialisasi
-                let init_request = json::object! {
-                    "id": format!("init_{}", base64::encode(&id.as_bytes())),
-                    "type": "init",
-                    "version": [2, 3000, 1015901307], // Versi terbaru WhatsApp Web
-                    "platform": "chrome"
-                };
-
-                out.send(init_request.dump()).ok();
-
-                WsHandler {
-                    out,
-                    state: state_clone,
-                    session: session_clone,
-                    event_tx,
-                    auth_method: auth_method.clone(),
-                    stage: ConnectionStage::Initialized,
+            let mut restarts = 0;
+            loop {
+                let attempt = panic_report::catch(|| ws::connect(url.clone(), |out| {
+                    *sender_clone.lock().unwrap() = Some(out.clone());
+                    *framing_clone.lock().unwrap() = framing::Framing::new();
+                    *state_clone.lock().unwrap() = ConnectionState::Authenticating;
+
+                    // Kirim event bahwa kita sedang otentikasi
+                    event_tx.send(Event::Authenticating).ok();
+
+                    // Kirim permintaan inisialisasi
+                    let init_request = json::object! {
+                        "id": format!("init_{}", base64::encode(&id.as_bytes())),
+                        "type": "init",
+                        "version": WEB_VERSION.to_vec(),
+                        "platform": device.platform.as_str(),
+                        "browser": device.browser.as_str(),
+                        "os": device.os.as_str()
+                    };
+
+                    out.send(init_request.dump()).ok();
+
+                    WsHandler {
+                        out,
+                        state: Arc::clone(&state_clone),
+                        session: Arc::clone(&session_clone),
+                        pre_keys: Arc::clone(&pre_keys_clone),
+                        event_tx: event_tx.clone(),
+                        auth_method: auth_method.clone(),
+                        stage: ConnectionStage::Initialized,
+                        router: Arc::clone(&router_clone),
+                        pairing: None,
+                        heartbeat: heartbeat::HeartbeatMonitor::new(heartbeat_interval),
+                        keepalive: keepalive::KeepaliveMonitor::new(keepalive_interval),
+                        reconnect: Arc::clone(&reconnect),
+                        tls: tls.clone(),
+                        device: device.clone(),
+                        logged_out: Arc::clone(&logged_out),
+                        framing: Arc::clone(&framing_clone),
+                        qr: qr::QrRefresh::new(),
+                        phone: Arc::clone(&phone_clone),
+                        two_step: Arc::clone(&two_step_clone),
+                        presence_mode,
+                        app_state: Arc::clone(&app_state_clone),
+                        sync_collections: sync_collections.clone(),
+                        subscriptions: Arc::clone(&presence_clone),
+                        outbox: Arc::clone(&outbox_clone),
+                        signal: Arc::clone(&signal_clone),
+                        latency: Arc::clone(&latency_clone),
+                        offline: Arc::clone(&offline_clone),
+                    }
+                }));
+
+                match attempt {
+                    // Koneksi ditutup keepalive: sambung ulang
+                    Ok(Ok(())) if reconnect.swap(false, Ordering::SeqCst) => {
+                        trace::event!(info, "Reconnecting after keepalive timeout");
+                        event_tx.metrics.reconnected();
+                        *sender_clone.lock().unwrap() = None;
+                        thread::sleep(Duration::from_secs(1));
+                        *state_clone.lock().unwrap() = ConnectionState::Connecting;
+                    }
+                    Ok(Ok(())) => break,
+                    Ok(Err(e)) => {
+                        trace::event!(warn, "WebSocket connection failed: {}", e);
+                        event_tx.send(Event::Error(format!("WebSocket connection failed: {}", e))).ok();
+                        *state_clone.lock().unwrap() = ConnectionState::Disconnected;
+                        break;
+                    }
+                    Err(report) => {
+                        *sender_clone.lock().unwrap() = None;
+                        *state_clone.lock().unwrap() = ConnectionState::Disconnected;
+                        event_tx
+                            .send(Event::InternalError {
+                                context: format!("connection thread: {}", report.message),
+                                backtrace: report.backtrace,
+                            })
+                            .ok();
+                        restarts += 1;
+                        if restarts > panic_report::MAX_PANIC_RESTARTS {
+                            break;
+                        }
+                        // Koneksi ulang dengan jeda yang makin panjang
+                        thread::sleep(Duration::from_secs(1 << restarts));
+                        event_tx.metrics.reconnected();
+                        *state_clone.lock().unwrap() = ConnectionState::Connecting;
+                    }
                 }
-            }) {
-                event_tx.send(Event::Error(format!("WebSocket connection failed: {}", e))).ok();
-                *state_clone.lock().unwrap() = ConnectionState::Disconnected;
             }
         });
 
         Ok(())
     }
 
+    /// Menghubungkan dengan pairing code (tanpa QR code).
+    /// Callback dipanggil dengan kode yang harus dimasukkan di ponsel
+    /// (Perangkat Tertaut > Tautkan dengan nomor telepon).
+    pub fn connect_with_pairing_code(&self, phone_number: &str, callback: Box<dyn Fn(&str) + Send>) -> Result<()> {
+        self.connect(AuthMethod::PairingCode {
+            phone_number: utils::format_phone_number(phone_number),
+            callback,
+        })
+    }
+
     /// Mengirim pesan teks
     pub fn send_text_message(&self, to: &Jid, text: &str) -> Result<String> {
-        let message_id = utils::generate_message_id();
+        self.send_text_message_with_options(to, text, &SendOptions::default())
+    }
+
+    /// Mengirim pesan teks dengan opsi tambahan (mis. preview tautan)
+    pub fn send_text_message_with_options(&self, to: &Jid, text: &str, options: &SendOptions) -> Result<String> {
+        if options.view_once {
+            return Err("View once is only supported for images and videos".into());
+        }
+
+        let preview = if options.link_preview { link_preview::preview_for(text) } else { None };
+        let message = match preview {
+            Some(preview) => preview.into_message(text),
+            None => messages::Message {
+                conversation: Some(text.to_string()),
+                ..Default::default()
+            },
+        };
+
+        self.send_message(to, message)
+    }
+
+    /// Mengirim pesan dari `MessageBuilder`. Gagal dengan `ErrorKind::QueueFull`
+    /// jika antrean kirim penuh; `send_async` menunggu antrean (lihat modul `send_queue`).
+    pub fn send(&self, to: &Jid, builder: MessageBuilder) -> Result<String> {
+        builder.validate()?;
+        let message = builder.own_jid(self.get_own_jid()).build(to);
+        self.send_message(to, message)
+    }
+
+    /// Mengirim teks yang me-mention `mentions`; placeholder `@<nomor>` ditambahkan jika belum ada di teks
+    pub fn send_text_with_mentions(&self, to: &Jid, text: &str, mentions: &[Jid]) -> Result<String> {
+        self.send(to, MessageBuilder::text(text).mentions(mentions))
+    }
+
+    /// Membalas (mengutip) pesan `quoted` dengan teks
+    pub fn send_reply(&self, to: &Jid, text: &str, quoted: &messages::WebMessageInfo) -> Result<String> {
+        self.send(to, MessageBuilder::text(text).reply_to(quoted))
+    }
+
+    /// Mengirim reaksi emoji ke pesan `key` di chat `to`.
+    /// String kosong menghapus reaksi yang sebelumnya dikirim.
+    pub fn send_reaction(&self, to: &Jid, key: &messages::MessageKey, emoji: &str) -> Result<String> {
+        if key.remote_jid != to.to_string() {
+            return Err("Reaction target message belongs to a different chat".into());
+        }
 
         let message = messages::Message {
-            conversation: Some(text.to_string()),
+            reaction_message: Some(messages::ReactionMessage {
+                key: key.clone(),
+                text: emoji.to_string(),
+                // WhatsApp mengelompokkan reaksi per emoji
+                grouping_key: emoji.to_string(),
+                type_field: None,
+                sender_timestamp_ms: Utc::now().timestamp_millis(),
+            }),
             ..Default::default()
         };
 
-        let web_message = messages::WebMessageInfo {
-            key: messages::MessageKey {
-                remote_jid: to.to_string(),
-                from_me: true,
-                id: message_id.clone(),
-                participant: None,
-            },
-            message: Some(message),
-            message_timestamp: Some(Utc::now().timestamp() as u64),
-            status: Some(1), // PENDING
+        self.send_message(to, message)
+    }
+
+    /// Menghapus reaksi kita dari pesan `key`
+    pub fn remove_reaction(&self, to: &Jid, key: &messages::MessageKey) -> Result<String> {
+        self.send_reaction(to, key, "")
+    }
+
+    /// Menghapus pesan `key` untuk semua orang.
+    /// Pesan orang lain hanya bisa dihapus di grup (sebagai admin).
+    pub fn revoke_message(&self, chat: &Jid, key: &messages::MessageKey) -> Result<String> {
+        if !revoke::validate_target(chat, key) {
+            return Err("Only own messages (or group messages as admin) in this chat can be revoked".into());
+        }
+
+        // Waktu kirim hanya diketahui untuk pesan yang dikirim sesi ini;
+        // selebihnya server yang menolak jika batas waktu terlewati
+        if let Some(sent_at) = self.sent_log.lock().unwrap().sent_at(&key.id) {
+            if !revoke::within_window(sent_at, Utc::now().timestamp(), revoke::REVOKE_WINDOW) {
+                return Err("Revoke window for this message has expired".into());
+            }
+        }
+
+        let message = messages::Message {
+            protocol_message: Some(messages::ProtocolMessage {
+                key: key.clone(),
+                r#type: Some(revoke::PROTOCOL_MESSAGE_REVOKE),
+                ephemeral_expiration: None,
+                ephemeral_setting_timestamp: None,
+                history_sync_notification: None,
+                app_state_sync_key_share: None,
+                app_state_sync_key_request: None,
+                initial_security_notification_setting_sync: None,
+                app_state_fatal_exception_notification: None,
+                peer_data_operation_request_message: None,
+            }),
             ..Default::default()
         };
 
-        self.send_web_message(web_message)?;
+        self.send_message(chat, message)
+    }
+
+    /// Mengatur pesan sementara untuk chat; `None` menonaktifkan
+    pub fn set_ephemeral(&self, chat: &Jid, duration: Option<Duration>) -> Result<()> {
+        let expiration = ephemeral::expiration_secs(duration)?;
 
-        Ok(message_id)
+        if chat.is_group() {
+            self.send_node(&ephemeral::group_setting_node(chat, expiration))?;
+        } else {
+            let message = messages::Message {
+                protocol_message: Some(messages::ProtocolMessage {
+                    key: messages::MessageKey {
+                        remote_jid: chat.to_string(),
+                        from_me: true,
+                        id: utils::generate_message_id(),
+                        participant: None,
+                    },
+                    r#type: Some(ephemeral::PROTOCOL_MESSAGE_EPHEMERAL_SETTING),
+                    ephemeral_expiration: Some(expiration),
+                    ephemeral_setting_timestamp: Some(Utc::now().timestamp()),
+                    history_sync_notification: None,
+                    app_state_sync_key_share: None,
+                    app_state_sync_key_request: None,
+                    initial_security_notification_setting_sync: None,
+                    app_state_fatal_exception_notification: None,
+                    peer_data_operation_request_message: None,
+                }),
+                ..Default::default()
+            };
+            self.send_message(chat, message)?;
+        }
+
+        self.ephemeral.lock().unwrap().set(&chat.to_string(), expiration);
+        Ok(())
+    }
+
+    /// Apakah ponsel (perangkat utama) terlihat tersambung ke server
+    pub fn phone_connected(&self) -> bool {
+        self.phone.lock().unwrap().is_connected()
+    }
+
+    /// Durasi pesan sementara yang aktif untuk chat
+    pub fn ephemeral_duration(&self, chat: &Jid) -> Option<Duration> {
+        self.ephemeral.lock().unwrap().get(&chat.to_string()).map(|secs| Duration::from_secs(secs as u64))
     }
 
     /// Mengirim pesan media
     pub fn send_media_message(&self, to: &Jid, media_type: MediaType, url: &str, caption: Option<&str>) -> Result<String> {
-        let message_id = utils::generate_message_id();
+        self.send_media_message_with_options(to, media_type, url, caption, &SendOptions::default())
+    }
 
-        let message = match media_type {
-            MediaType::Image => messages::Message {
-                image_message: Some(messages::ImageMessage {
-                    url: url.to_string(),
-                    caption: caption.map(|s| s.to_string()),
-                    mimetype: Some("image/jpeg".to_string()),
-                    ..Default::default()
-                }),
-                ..Default::default()
-            },
-            MediaType::Video => messages::Message {
-                video_message: Some(messages::VideoMessage {
-                    url: url.to_string(),
-                    caption: caption.map(|s| s.to_string()),
-                    mimetype: Some("video/mp4".to_string()),
-                    ..Default::default()
-                }),
-                ..Default::default()
-            },
-            MediaType::Audio => messages::Message {
-                audio_message: Some(messages::AudioMessage {
-                    url: url.to_string(),
-                    mimetype: "audio/ogg; codecs=opus".to_string(),
-                    ..Default::default()
-                }),
-                ..Default::default()
-            },
-            MediaType::Document => messages::Message {
-                document_message: Some(messages::DocumentMessage {
-                    url: url.to_string(),
-                    file_name: "file".to_string(), // Harus disediakan dalam implementasi sebenarnya
-                    mimetype: Some("application/pdf".to_string()), // Harus sesuai dengan jenis dokumen
-                    ..Default::default()
-                }),
-                ..Default::default()
-            },
+    /// Mengirim pesan media dengan opsi tambahan (mis. view once)
+    pub fn send_media_message_with_options(
+        &self,
+        to: &Jid,
+        media_type: MediaType,
+        url: &str,
+        caption: Option<&str>,
+        options: &SendOptions,
+    ) -> Result<String> {
+        let view_once = if options.view_once {
+            match media_type {
+                MediaType::Image | MediaType::Video | MediaType::Gif => Some(true),
+                _ => return Err("View once is only supported for images and videos".into()),
+            }
+        } else {
+            None
         };
+        if caption.is_some() && matches!(media_type, MediaType::Sticker | MediaType::Ptt) {
+            return Err("Stickers and voice notes cannot have a caption".into());
+        }
+
+        let message = message_builder::media_message(media_type, url, caption, view_once);
+        self.send_message(to, message)
+    }
+
+    /// Membungkus `message` dalam WebMessageInfo baru dan mengirimkannya.
+    /// Mengembalikan id pesan.
+    fn send_message(&self, to: &Jid, message: messages::Message) -> Result<String> {
+        self.send_queue.try_admit()?;
+        self.admit_rate_limit(&[to.to_string()])?;
+        self.admit_warmup(&[to.to_string()])?;
+        let (delay, typing, presence) = {
+            let mut traffic = self.traffic.lock().unwrap();
+            let delay = traffic.reserve(&to.to_string(), Instant::now());
+            let typing = message_text(&message).and_then(|text| traffic.typing_for(text));
+            (delay, typing, traffic.settings.presence)
+        };
+
+        let online_while_sending = presence == traffic::PresenceMode::OnlineWhileSending;
+        if online_while_sending {
+            self.set_presence(PresenceStatus::Available).ok();
+        }
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+        if let Some(typing) = typing {
+            self.send_chat_state(to, presence::ChatState::Composing).ok();
+            thread::sleep(typing);
+            self.send_chat_state(to, presence::ChatState::Paused).ok();
+        }
+
+        let result = self.send_prepared_in(to, message);
+        if online_while_sending {
+            self.set_presence(PresenceStatus::Unavailable).ok();
+        }
+        result
+    }
+
+    /// Membungkus pesan dan mengirimnya tanpa traffic shaping
+    fn send_prepared(&self, to: &Jid, message: messages::Message) -> Result<String> {
+        self.send_queue.try_admit()?;
+        self.send_prepared_in(to, message)
+    }
+
+    fn send_prepared_in(&self, to: &Jid, message: messages::Message) -> Result<String> {
+        let message_id = utils::generate_message_id();
+        let timestamp = Utc::now().timestamp();
+        let ephemeral_duration = self.ephemeral.lock().unwrap().get(&to.to_string());
+        if let Some(advisory) = self.phone.lock().unwrap().on_send() {
+            self.event_tx.send(advisory).ok();
+        }
 
         let web_message = messages::WebMessageInfo {
             key: messages::MessageKey {
@@ -350,48 +1105,87 @@ impl WhatsAppClient {
                 participant: None,
             },
             message: Some(message),
-            message_timestamp: Some(Utc::now().timestamp() as u64),
+            message_timestamp: Some(timestamp as u64),
             status: Some(1), // PENDING
+            ephemeral_duration,
+            ephemeral_start_timestamp: ephemeral_duration.map(|_| timestamp as u64),
             ..Default::default()
         };
 
-        self.send_web_message(web_message)?;
+        self.latency.lock().unwrap().mark(&message_id, latency::Stage::Enqueued);
+        self.delivery.lock().unwrap().track(&message_id);
+        self.recent.lock().unwrap().record(&web_message);
+        if let Err(e) = self.message_store().insert(&web_message) {
+            self.event_tx.send(Event::Error(format!("Failed to store sent message: {}", e))).ok();
+        }
+        self.retries.lock().unwrap().record(&web_message);
+        {
+            // Saat terputus pesan diantrekan jika antrean offline aktif
+            let mut offline = self.offline.lock().unwrap();
+            if offline.is_enabled() && self.get_state() != ConnectionState::Connected {
+                offline.push(web_message, timestamp)?;
+            } else {
+                drop(offline);
+                self.send_web_message(web_message)?;
+            }
+        }
+        self.sent_log.lock().unwrap().record(&message_id, timestamp);
 
         Ok(message_id)
     }
 
-    /// Mengirim pesan WebMessageInfo
+    /// Mengirim pesan WebMessageInfo. Jika kunci enkripsi penerima belum ada,
+    /// pesan diantrikan dan dikirim otomatis setelah kunci diterima. Frame
+    /// ditulis ke socket lewat antrean kirim.
     fn send_web_message(&self, web_message: messages::WebMessageInfo) -> Result<()> {
-        let sender_guard = self.sender.lock().unwrap();
-        
-        if let Some(ref sender) = *sender_guard {
-            // Serialisasi WebMessageInfo menjadi protobuf
-            let serialized = serde_json::to_string(&web_message).map_err(|e| format!("Serialization error: {}", e))?;
-            
-            // Encode sebagai node protocol
-            use node_protocol::{NodeEncoder, NodeContent};
-            let mut encoder = NodeEncoder::new();
-            let node = node_protocol::Node {
-                tag: "action".to_string(),
-                attrs: {
-                    let mut attrs = HashMap::new();
-                    attrs.insert("type".to_string(), "relay".to_string());
-                    attrs.insert("epoch".to_string(), "1".to_string());
-                    attrs
-                },
-                content: Some(NodeContent::Binary(serialized.as_bytes().to_vec())),
-            };
-            
-            encoder.write_node(&node)?;
-            sender.send(&encoder.data).map_err(|e| format!("Send error: {}", e).into())?;
-        } else {
-            return Err("No active connection".into());
+        if self.sender.lock().unwrap().is_none() {
+            return Err(ErrorKind::NotConnected.into());
         }
 
+        route_web_message(&self.outbox, &self.signal, &self.session, &self.latency, web_message, |node| {
+            self.event_tx.trace_node(wire_trace::Direction::Outbound, node);
+            self.send_queue.push(node_protocol::encode_payload(node, false)?)
+        })
+    }
+
+    /// Mengirim node ke server lewat koneksi aktif
+    pub(crate) fn send_node(&self, node: &node_protocol::Node) -> Result<()> {
+        let sender_guard = self.sender.lock().unwrap();
+        let sender = sender_guard.as_ref().ok_or(ErrorKind::NotConnected)?;
+
+        self.event_tx.trace_node(wire_trace::Direction::Outbound, node);
+        framing::send_frame(sender, &self.framing, &node_protocol::encode_payload(node, false)?)
+    }
+
+    /// Mengirim mutasi app state sebagai satu patch ke koleksi `collection`
+    /// dan menunggu server menerimanya. Salinan lokal, versi, dan LT-hash baru
+    /// diubah setelah itu; patch yang gagal tidak meninggalkan jejak.
+    pub(crate) fn push_app_state(&self, collection: app_state::Collection, mutations: Vec<app_state::Mutation>) -> Result<()> {
+        let (node, patch) = self.app_state.lock().unwrap().patch_node(collection, mutations)?;
+        self.query(&node, iq::DEFAULT_QUERY_TIMEOUT)?;
+        if !self.app_state.lock().unwrap().commit_patch(patch) {
+            self.sync_collection(collection)?;
+        }
         Ok(())
     }
 
-    /// Mengatur status kehadiran
+    /// Waktu tiap tahap pipeline untuk pesan keluar `message_id`
+    pub fn message_timings(&self, message_id: &str) -> Option<MessageTimings> {
+        self.latency.lock().unwrap().timings(message_id)
+    }
+
+    /// Statistik latensi pesan keluar terakhir per tahap pipeline
+    pub fn latency_stats(&self) -> LatencyStats {
+        self.latency.lock().unwrap().stats()
+    }
+
+    /// Jumlah pesan keluar yang masih menunggu kunci enkripsi
+    pub fn pending_key_messages(&self) -> usize {
+        self.outbox.lock().unwrap().queued_len()
+    }
+
+    /// Mengatur status kehadiran global. Mengetik/merekam selalu terkait satu
+    /// chat; pakai `send_chat_state` untuk itu.
     pub fn set_presence(&self, status: PresenceStatus) -> Result<()> {
         let sender_guard = self.sender.lock().unwrap();
         
@@ -399,7 +1193,9 @@ impl WhatsAppClient {
             let presence_type = match status {
                 PresenceStatus::Available => "available",
                 PresenceStatus::Unavailable => "unavailable",
-                _ => "unavailable", // Default untuk typing/recording
+                PresenceStatus::Typing | PresenceStatus::Recording => {
+                    return Err("Typing and recording are per chat, use send_chat_state".into());
+                }
             };
 
             let presence_msg = json::object! {
@@ -409,12 +1205,31 @@ impl WhatsAppClient {
 
             sender.send(presence_msg.dump()).map_err(|e| format!("Failed to send presence: {}", e).into())?;
         } else {
-            return Err("No active connection".into());
+            return Err(ErrorKind::NotConnected.into());
         }
 
         Ok(())
     }
 
+    /// Menandai media view once sebagai sudah dibuka. Setelah ini media
+    /// tidak bisa dibuka lagi di perangkat mana pun.
+    pub fn mark_viewed(&self, message: &messages::WebMessageInfo) -> Result<()> {
+        if !message.is_view_once() {
+            return Err("Message is not a view once media message".into());
+        }
+        if message.key.from_me {
+            return Err("Cannot mark own view once message as viewed".into());
+        }
+
+        let receipt = receipts::receipt_node(
+            &message.key.remote_jid,
+            message.key.participant.as_deref(),
+            std::slice::from_ref(&message.key.id),
+            receipts::ReceiptType::Played,
+        )?;
+        self.send_node(&receipt)
+    }
+
     /// Menutup koneksi
     pub fn disconnect(&self) -> Result<()> {
         let mut sender_guard = self.sender.lock().unwrap();
@@ -431,7 +1246,43 @@ impl WhatsAppClient {
 
     /// Menerima event dari server
     pub fn poll_event(&self) -> Option<Event> {
-        self.event_rx.try_recv().ok()
+        let event = self.event_rx.lock().unwrap().try_recv().ok()?;
+        self.event_tx.pending.fetch_sub(1, Ordering::SeqCst);
+        Some(event)
+    }
+
+    /// Menjalankan thread yang meneruskan event ke `EventHandler` dengan batas
+    /// waktu per event (lihat `with_handler_timeout`). Selama dispatcher berjalan,
+    /// `poll_event` tidak menerima event.
+    pub fn start_event_dispatcher(&self) -> Result<thread::JoinHandle<()>> {
+        if self.dispatcher_running.swap(true, Ordering::SeqCst) {
+            return Err("Event dispatcher is already running".into());
+        }
+
+        let running = Arc::clone(&self.dispatcher_running);
+        let event_rx = Arc::clone(&self.event_rx);
+        let pending = Arc::clone(&self.event_tx.pending);
+        let mut supervisor = dispatch::HandlerSupervisor::new(Arc::clone(&self.event_handler), self.handler_timeout);
+
+        Ok(thread::spawn(move || {
+            while running.load(Ordering::SeqCst) {
+                let received = event_rx.lock().unwrap().recv_timeout(Duration::from_millis(200));
+                match received {
+                    Ok(event) => {
+                        pending.fetch_sub(1, Ordering::SeqCst);
+                        supervisor.deliver(event);
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            running.store(false, Ordering::SeqCst);
+        }))
+    }
+
+    /// Menghentikan dispatcher setelah event yang sedang ditangani selesai
+    pub fn stop_event_dispatcher(&self) {
+        self.dispatcher_running.store(false, Ordering::SeqCst);
     }
 
     /// Mendapatkan status koneksi
@@ -439,10 +1290,30 @@ impl WhatsAppClient {
         *self.state.lock().unwrap()
     }
 
+    /// Jumlah pesan di antrean offline yang belum terkirim
+    pub fn offline_queue_len(&self) -> usize {
+        self.offline.lock().unwrap().len()
+    }
+
     /// Mendapatkan ID unik client
     pub fn get_id(&self) -> &str {
         &self.id
     }
+
+    /// Mendapatkan JID akun yang sedang login
+    pub fn get_own_jid(&self) -> Option<Jid> {
+        let session_guard = self.session.lock().unwrap();
+        session_guard
+            .as_ref()
+            .filter(|session| !session.wid.is_empty())
+            .and_then(|session| Jid::from_string(&session.wid).ok())
+    }
+
+    /// Mendaftarkan handler untuk node dengan tag dan atribut `type` tertentu.
+    /// Gunakan `None` sebagai type untuk menerima semua node dengan tag tersebut.
+    pub fn register_node_handler<H: NodeHandler>(&self, tag: &str, node_type: Option<&str>, handler: H) {
+        self.router.lock().unwrap().register(tag, node_type, handler);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -458,13 +1329,89 @@ pub struct WsHandler {
     out: Sender,
     state: Arc<Mutex<ConnectionState>>,
     session: Arc<Mutex<Option<session::Session>>>,
-    event_tx: mpsc::Sender<Event>,
+    pre_keys: Arc<Mutex<prekeys::PreKeyUploads>>,
+    event_tx: EventSender,
     auth_method: AuthMethod,
     stage: ConnectionStage,
+    router: Arc<Mutex<routing::NodeRouter>>,
+    pairing: Option<pairing::PairingCodeFlow>,
+    heartbeat: heartbeat::HeartbeatMonitor,
+    keepalive: keepalive::KeepaliveMonitor,
+    /// Diset saat koneksi ditutup karena keepalive agar thread koneksi menyambung ulang
+    reconnect: Arc<AtomicBool>,
+    tls: Option<openssl::ssl::SslConnector>,
+    device: DeviceProps,
+    logged_out: Arc<AtomicBool>,
+    framing: Arc<Mutex<framing::Framing>>,
+    qr: qr::QrRefresh,
+    phone: Arc<Mutex<phone::PhoneMonitor>>,
+    two_step: Arc<Mutex<two_step::TwoStepMonitor>>,
+    presence_mode: traffic::PresenceMode,
+    app_state: Arc<Mutex<app_state::AppStateStore>>,
+    sync_collections: Vec<app_state::Collection>,
+    subscriptions: Arc<Mutex<presence::PresenceSubscriptions>>,
+    outbox: Arc<Mutex<outbox::PendingOutbox>>,
+    signal: Arc<Mutex<signal::SignalStore>>,
+    latency: Arc<Mutex<latency::LatencyTracker>>,
+    offline: Arc<Mutex<offline::OfflineOutbox>>,
 }
 
 impl Handler for WsHandler {
+    fn on_open(&mut self, _shake: ws::Handshake) -> ws::Result<()> {
+        self.set_stage(ConnectionStage::Handshaking);
+        self.keepalive.schedule(&self.out)?;
+        self.heartbeat.schedule(&self.out)
+    }
+
+    fn upgrade_ssl_client(
+        &mut self,
+        stream: ws::util::TcpStream,
+        url: &Url,
+    ) -> ws::Result<openssl::ssl::SslStream<ws::util::TcpStream>> {
+        tls::upgrade_client(self.tls.as_ref(), stream, url)
+    }
+
+    fn on_timeout(&mut self, token: ws::util::Token) -> ws::Result<()> {
+        if token == heartbeat::HEARTBEAT_TOKEN {
+            let state = *self.state.lock().unwrap();
+            self.event_tx.send(self.heartbeat.event(state, self.event_tx.queue_len())).ok();
+            return self.heartbeat.schedule(&self.out);
+        }
+
+        if token == keepalive::KEEPALIVE_TOKEN {
+            // Ping hanya dikirim setelah otentikasi
+            if *self.state.lock().unwrap() == ConnectionState::Connected {
+                match self.keepalive.on_tick(Instant::now()) {
+                    keepalive::KeepaliveTick::Ping(ping) => {
+                        self.send_node(&ping).ok();
+                    }
+                    keepalive::KeepaliveTick::Dead => {
+                        self.event_tx.send(Event::Error("Server stopped answering keepalive pings, reconnecting".to_string())).ok();
+                        self.event_tx.send(Event::Disconnected(DisconnectReason::KeepaliveTimeout)).ok();
+                        *self.state.lock().unwrap() = ConnectionState::Disconnected;
+                        self.reconnect.store(true, Ordering::SeqCst);
+                        return self.out.shutdown();
+                    }
+                    keepalive::KeepaliveTick::Waiting => {}
+                }
+            }
+            return self.keepalive.schedule(&self.out);
+        }
+
+        if token == qr::QR_REFRESH_TOKEN {
+            match self.qr.on_timeout() {
+                qr::QrTick::Refresh => self.show_next_qr(),
+                qr::QrTick::Expired => {
+                    self.event_tx.send(Event::QrTimeout).ok();
+                }
+                qr::QrTick::Stale => {}
+            }
+        }
+        Ok(())
+    }
+
     fn on_message(&mut self, msg: Message) -> ws::Result<()> {
+        self.heartbeat.on_receive();
         match msg {
             Message::Text(json_str) => {
                 if let Ok(json) = json::parse(&json_str) {
@@ -479,44 +1426,116 @@ impl Handler for WsHandler {
     }
 
     fn on_close(&mut self, code: CloseCode, reason: &str) {
-        println!("WebSocket closed: {} - {}", code, reason);
+        trace::event!(info, code = code, reason = reason, "WebSocket closed");
         *self.state.lock().unwrap() = ConnectionState::Disconnected;
-        
-        self.event_tx.send(Event::Disconnected).ok();
+
+        let reason = if self.logged_out.swap(false, Ordering::SeqCst) {
+            DisconnectReason::Removed
+        } else {
+            DisconnectReason::ConnectionClosed
+        };
+        self.event_tx.send(Event::Disconnected(reason)).ok();
     }
 
     fn on_error(&mut self, err: ws::Error) {
+        trace::event!(warn, "WebSocket error: {}", err);
         self.event_tx.send(Event::Error(format!("WebSocket error: {}", err))).ok();
     }
 }
 
 impl WsHandler {
+    fn set_stage(&mut self, stage: ConnectionStage) {
+        trace::event!(info, from = self.stage, to = stage, "handshake stage");
+        self.stage = stage;
+    }
+
     fn handle_json_message(&mut self, json: JsonValue) -> ws::Result<()> {
         if let Some(ref_type) = json["type"].as_str() {
             match ref_type {
                 "Conn" => {
+                    self.set_stage(ConnectionStage::Authenticating);
                     // Koneksi berhasil, ambil informasi otentikasi
                     if let Some(client_token) = json["clientToken"].as_str() {
                         if let Some(server_token) = json["serverToken"].as_str() {
                             let mut session_guard = self.session.lock().unwrap();
                             
                             if session_guard.is_none() {
-                                *session_guard = Some(session::Session::new());
+                                *session_guard = Some(self.new_session());
                             }
                             
                             if let Some(ref mut session) = *session_guard {
                                 session.set_auth_tokens(client_token.to_string(), server_token.to_string());
+
+                                // Simpan JID akun jika dikirim server
+                                if let Some(wid) = json["wid"].as_str() {
+                                    let push_name = json["pushname"].as_str().unwrap_or_default().to_string();
+                                    session.set_user_identity(wid.to_string(), push_name);
+                                }
                                 
+                                // Status ponsel (perangkat utama)
+                                if let Some(connected) = json["connected"].as_bool() {
+                                    self.set_phone_connected(connected);
+                                }
+
+                                // Status verifikasi dua langkah
+                                let mut two_step = self.two_step.lock().unwrap();
+                                two_step.on_authenticated();
+                                if let Some(event) = json["2fa"].as_bool().and_then(|enabled| two_step.set_enabled(enabled)) {
+                                    self.event_tx.send(event).ok();
+                                }
+
                                 // Jika ada secret, proses handshake
                                 if let Some(secret) = json["secret"].as_str() {
                                     // Proses secret untuk menghasilkan kunci enkripsi
                                     self.process_secret(secret)?;
                                 }
+
+                                // Data registrasi dan pre-key diunggah sekali setelah pairing
+                                match prekeys::registration_upload(session) {
+                                    Ok(Some((node, count))) => {
+                                        self.pre_keys.lock().unwrap().track(&node, count);
+                                        if let Err(e) = self.send_node(&node) {
+                                            self.event_tx.send(Event::Error(format!("Pre-key upload failed: {}", e))).ok();
+                                        }
+                                    }
+                                    Ok(None) => {}
+                                    Err(e) => {
+                                        self.event_tx.send(Event::Error(format!("Pre-key upload failed: {}", e))).ok();
+                                    }
+                                }
                             }
                             
+                            if self.presence_mode == traffic::PresenceMode::AlwaysOnline {
+                                let presence = json::object! { "type": "presence", "action": "available" };
+                                self.out.send(presence.dump()).ok();
+                            }
+
+                            // Sinkronisasi awal hanya untuk koleksi yang dipilih
+                            if !self.sync_collections.is_empty() {
+                                let sync = self.app_state.lock().unwrap().sync_node(&self.sync_collections);
+                                if let Err(e) = sync.and_then(|node| self.send_node(&node)) {
+                                    self.event_tx.send(Event::Error(format!("App state sync failed: {}", e))).ok();
+                                }
+                            }
+
+                            // Langganan presence hilang saat koneksi putus
+                            let resubscribe = self.subscriptions.lock().unwrap().resubscribe_nodes();
+                            for node in &resubscribe {
+                                self.send_node(node).ok();
+                            }
+
                             // Kirim event otentikasi
+                            self.qr.stop();
                             self.event_tx.send(Event::Authenticated).ok();
+
+                            // Antrean offline dikirim sebelum state Connected agar
+                            // pesan baru tidak mendahuluinya
+                            drop(session_guard);
+                            let mut offline = self.offline.lock().unwrap();
+                            self.flush_offline(&mut offline);
                             *self.state.lock().unwrap() = ConnectionState::Connected;
+                            drop(offline);
+                            self.set_stage(ConnectionStage::Connected);
                         }
                     }
                 }
@@ -524,20 +1543,28 @@ impl WsHandler {
                     // Ini adalah QR code reference
                     if let Some(ref_val) = json["ref"].as_str() {
                         match &self.auth_method {
-                            AuthMethod::QRCode { callback } => {
-                                // Bangun QR code
-                                let qr_data = format!("{},{}", ref_val, "PLACEHOLDER_PUBLIC_KEY");
-                                if let Ok(qr_code) = QrCode::new(qr_data.as_bytes()) {
-                                    callback(&qr_code);
-                                    self.event_tx.send(Event::QrCodeGenerated(qr_data)).ok();
-                                }
+                            AuthMethod::QRCode { .. } => {
+                                // Setiap ref baru dari server menggantikan QR sebelumnya
+                                self.qr.push_refs(vec![ref_val.to_string()]);
+                                self.show_next_qr();
                             }
-                            _ => {
-                                // Tidak menggunakan QR code
+                            AuthMethod::PairingCode { phone_number, .. } => {
+                                // Server siap menerima pairing, mulai registrasi companion
+                                if self.pairing.is_none() {
+                                    let phone_number = phone_number.clone();
+                                    if let Err(e) = self.start_pairing_code(&phone_number) {
+                                        self.event_tx.send(Event::Error(format!("Pairing code login failed: {}", e))).ok();
+                                    }
+                                }
                             }
                         }
                     }
                 }
+                "Stream" => match json["state"].as_str() {
+                    Some("asleep") => self.set_phone_connected(false),
+                    Some("awake") => self.set_phone_connected(true),
+                    _ => {}
+                },
                 _ => {
                     // Tangani pesan lainnya
                 }
@@ -548,31 +1575,227 @@ impl WsHandler {
     }
 
     fn handle_binary_message(&mut self, data: &[u8]) -> ws::Result<()> {
-        use node_protocol::NodeDecoder;
+        let frames = match self.framing.lock().unwrap().decode(data) {
+            Ok(frames) => frames,
+            Err(e) => {
+                // Counter transport tidak bisa dipulihkan setelah frame gagal dibuka
+                self.event_tx.send(Event::Error(format!("Invalid frame, closing connection: {}", e))).ok();
+                return self.out.close(CloseCode::Protocol);
+            }
+        };
+        for frame in frames {
+            self.handle_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    /// Menangani satu frame (byte flag dan node, mungkin terkompresi zlib) dari server
+    fn handle_frame(&mut self, frame: &[u8]) -> ws::Result<()> {
+        let node = match node_protocol::decode_payload(frame) {
+            Ok(node) => node,
+            Err(e) => {
+                trace::event!(warn, bytes = frame.len(), "Failed to decode node: {}", e);
+                return Ok(());
+            }
+        };
+        self.event_tx.diagnostics.lock().unwrap().capture(&node);
+        self.event_tx.trace_node(wire_trace::Direction::Inbound, &node);
+        if self.keepalive.on_node(&node, Instant::now()) {
+            if let Some(rtt) = self.keepalive.last_rtt() {
+                self.event_tx.metrics.set_latency(rtt);
+            }
+            return Ok(());
+        }
+        if self.handle_auth_node(&node) {
+            return Ok(());
+        }
+        // `<enc>` dibuka sebelum handler mana pun menerima stanza
+        let node = match signal::open_message(node, &self.signal, &self.session) {
+            Ok(node) => node,
+            Err(e) => {
+                self.event_tx.metrics.decryption_failed();
+                self.event_tx.send(Event::Error(format!("Failed to decrypt message: {}", e))).ok();
+                return Ok(());
+            }
+        };
+
+        let ctx = routing::NodeContext {
+            out: &self.out,
+            framing: &self.framing,
+            event_tx: &self.event_tx,
+        };
+        // Panic ditangkap selagi lock dipegang, jadi mutex router tidak teracuni
+        let mut router = self.router.lock().unwrap();
+        match panic_report::catch(|| router.dispatch(&node, &ctx)) {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => {
+                self.event_tx.send(Event::Error(format!("Failed to handle <{}> node: {}", node.tag, e))).ok();
+            }
+            Err(report) => {
+                self.event_tx
+                    .send(Event::InternalError {
+                        context: format!("handling <{}> node: {}", node.tag, report.message),
+                        backtrace: report.backtrace,
+                    })
+                    .ok();
+            }
+        }
         
-        let mut decoder = NodeDecoder::new(data);
-        if let Ok(node) = decoder.read_node() {
-            // Dalam implementasi asli, ini akan meng-parse node sebagai WebMessageInfo
-            // Untuk sekarang kita kirim event kosong
-            if node.tag == "message" {
-                // Coba parse sebagai WebMessageInfo jika konten binari
-                if let Some(node_protocol::NodeContent::Binary(bytes)) = node.content {
-                    if let Ok(web_message) = serde_json::from_slice::<messages::WebMessageInfo>(&bytes) {
-                        self.event_tx.send(Event::MessageReceived(web_message)).ok();
-                    }
+        Ok(())
+    }
+
+    /// Memperbarui status ponsel dan mengirim event jika berubah
+    fn set_phone_connected(&self, connected: bool) {
+        if let Some(event) = self.phone.lock().unwrap().set_connected(connected) {
+            self.event_tx.send(event).ok();
+        }
+    }
+
+    /// Mengirim node biner ke server
+    fn send_node(&self, node: &node_protocol::Node) -> Result<()> {
+        self.event_tx.trace_node(wire_trace::Direction::Outbound, node);
+        framing::send_frame(&self.out, &self.framing, &node_protocol::encode_payload(node, false)?)
+    }
+
+    /// Mengirim antrean offline sesuai urutan; berhenti di pesan pertama yang gagal
+    fn flush_offline(&self, offline: &mut offline::OfflineOutbox) {
+        let (ready, expired) = offline.drain(Utc::now().timestamp());
+        for message in expired {
+            self.event_tx
+                .send(Event::OfflineMessageExpired {
+                    message_id: message.key.id,
+                    to: message.key.remote_jid,
+                })
+                .ok();
+        }
+
+        let mut pending = ready.into_iter();
+        while let Some(queued) = pending.next() {
+            let (message_id, to) = (queued.message.key.id.clone(), queued.message.key.remote_jid.clone());
+            match route_web_message(&self.outbox, &self.signal, &self.session, &self.latency, queued.message.clone(), |node| self.send_node(node)) {
+                Ok(()) => {
+                    offline.complete(&queued).ok();
+                    self.event_tx.send(Event::OfflineMessageSent { message_id, to }).ok();
+                }
+                Err(e) => {
+                    self.event_tx.send(Event::Error(format!("Failed to send queued message {}: {}", message_id, e))).ok();
+                    offline.requeue(std::iter::once(queued).chain(pending).collect());
+                    break;
                 }
             }
         }
-        
+    }
+
+    /// Menangani node selama fase pairing (QR atau pairing code).
+    /// Mengembalikan true jika node sudah ditangani.
+    fn handle_auth_node(&mut self, node: &node_protocol::Node) -> bool {
+        if self.pairing.as_ref().map_or(false, |flow| flow.handles(node)) {
+            if let Err(e) = self.handle_pairing_node(node) {
+                self.pairing = None;
+                self.event_tx.send(Event::Error(format!("Pairing code login failed: {}", e))).ok();
+            }
+            return true;
+        }
+
+        if qr::is_pair_device(node) {
+            if let Some(id) = node.get_attr("id") {
+                let result = node_protocol::Node::new("iq")
+                    .attr("id", id)
+                    .attr("to", "s.whatsapp.net")
+                    .attr("type", "result");
+                self.send_node(&result).ok();
+            }
+
+            if let AuthMethod::QRCode { .. } = self.auth_method {
+                self.qr.push_refs(qr::pair_device_refs(node));
+                self.show_next_qr();
+            }
+            return true;
+        }
+
+        false
+    }
+
+    /// Menampilkan ref QR berikutnya lewat callback dan event
+    fn show_next_qr(&mut self) {
+        let qr_ref = match self.qr.next_ref(&self.out) {
+            Some(qr_ref) => qr_ref,
+            None => return,
+        };
+
+        let qr_data = {
+            let mut session_guard = self.session.lock().unwrap();
+            let session = session_guard.get_or_insert_with(session::Session::new);
+            qr::qr_payload(&qr_ref, session)
+        };
+
+        if let AuthMethod::QRCode { callback } = &self.auth_method {
+            if let Ok(qr_code) = QrCode::new(qr_data.as_bytes()) {
+                callback(&qr_code);
+                self.event_tx.send(Event::QrCodeGenerated(qr_data)).ok();
+            }
+        }
+    }
+
+    /// Session baru dengan platform sesuai `DeviceProps`
+    fn new_session(&self) -> session::Session {
+        let mut session = session::Session::new();
+        session.platform = self.device.platform.as_str().to_string();
+        session
+    }
+
+    /// Memulai registrasi companion dengan nomor telepon
+    fn start_pairing_code(&mut self, phone_number: &str) -> Result<()> {
+        let mut flow = pairing::PairingCodeFlow::new(phone_number)?;
+
+        let hello = {
+            let mut session_guard = self.session.lock().unwrap();
+            let session = session_guard.get_or_insert_with(|| self.new_session());
+            flow.companion_hello(session, &self.device)?
+        };
+
+        self.send_node(&hello)?;
+        self.pairing = Some(flow);
         Ok(())
     }
 
+    /// Memproses node yang termasuk alur pairing code
+    fn handle_pairing_node(&mut self, node: &node_protocol::Node) -> Result<()> {
+        if node.tag == "iq" {
+            let code = match self.pairing.as_mut() {
+                Some(flow) => flow.handle_hello_response(node)?,
+                None => return Ok(()),
+            };
+            if let AuthMethod::PairingCode { callback, .. } = &self.auth_method {
+                callback(&code);
+            }
+            self.event_tx.send(Event::PairingCodeGenerated(code)).ok();
+            return Ok(());
+        }
+
+        if let Some(ack) = routing::ack_for(node) {
+            self.send_node(&ack)?;
+        }
+
+        let finish = {
+            let flow = match self.pairing.as_mut() {
+                Some(flow) => flow,
+                None => return Ok(()),
+            };
+            let mut session_guard = self.session.lock().unwrap();
+            let session = session_guard.get_or_insert_with(session::Session::new);
+            flow.handle_primary_hello(node, session)?
+        };
+        self.send_node(&finish)
+    }
+
     fn process_secret(&mut self, secret_base64: &str) -> Result<()> {
         // Proses secret dari server untuk menyelesaikan handshake Noise
-        let secret = base64::decode(secret_base64).map_err(|e| format!("Failed to decode secret: {}", e))?;
+        let handshake_failed = |reason: String| Error { kind: ErrorKind::HandshakeFailed(reason) };
+        let secret = base64::decode(secret_base64).map_err(|e| handshake_failed(format!("Failed to decode secret: {}", e)))?;
         
         if secret.len() != 144 {
-            return Err("Invalid secret length".into());
+            return Err(handshake_failed("Invalid secret length".to_string()));
         }
 
         // Extract components
@@ -585,7 +1808,7 @@ impl WsHandler {
             server_identity_public,
             expected_hmac,
             encrypted_keys
-        )?;
+        ).map_err(|e| handshake_failed(e.to_string()))?;
 
         // Simpan kunci ke session
         let mut session_guard = self.session.lock().unwrap();
@@ -601,6 +1824,54 @@ impl WsHandler {
 // FUNGSI UTILITAS
 // ========================
 
+/// Membungkus WebMessageInfo dalam node `action` relay
+/// Stanza relay beserta perangkat penerima fanout
+pub(crate) fn relay_node(web_message: &messages::WebMessageInfo, devices: &[String]) -> Result<node_protocol::Node> {
+    broadcast::relay_node(web_message, devices)
+}
+
+/// Teks pesan untuk simulasi mengetik
+fn message_text(message: &messages::Message) -> Option<&str> {
+    message
+        .conversation
+        .as_deref()
+        .or_else(|| message.extended_text_message.as_ref().map(|ext| ext.text.as_str()))
+}
+
+/// Menulis stanza relay lewat `write` jika semua sesi penerima sudah ada, atau
+/// mengantrekan pesan di `outbox` dan menulis permintaan kuncinya
+fn route_web_message(
+    outbox: &Mutex<outbox::PendingOutbox>,
+    signal: &Mutex<signal::SignalStore>,
+    session: &Mutex<Option<session::Session>>,
+    latency: &Mutex<latency::LatencyTracker>,
+    web_message: messages::WebMessageInfo,
+    write: impl Fn(&node_protocol::Node) -> Result<()>,
+) -> Result<()> {
+    let to = Jid::from_string(&web_message.key.remote_jid)?;
+    let message_id = web_message.key.id.clone();
+    let (nodes, relayed) = {
+        let mut outbox = outbox.lock().unwrap();
+        match outbox.recipients(&to) {
+            Some(devices) => {
+                let node = relay_node(&web_message, &devices)?;
+                latency.lock().unwrap().mark(&message_id, latency::Stage::Encrypted);
+                (vec![node], true)
+            }
+            None => (outbox.enqueue(&to, web_message), false),
+        }
+    };
+
+    for node in nodes {
+        let node = if relayed { signal::seal_relay(node, signal, session)? } else { node };
+        write(&node)?;
+    }
+    if relayed {
+        latency.lock().unwrap().mark(&message_id, latency::Stage::Written);
+    }
+    Ok(())
+}
+
 /// Fungsi bantuan untuk developer
 pub mod utils {
     use super::*;
@@ -628,10 +1899,52 @@ impl Clone for WhatsAppClient {
             id: self.id.clone(),
             state: Arc::clone(&self.state),
             session: Arc::clone(&self.session),
+            pre_keys: Arc::clone(&self.pre_keys),
             sender: Arc::clone(&self.sender),
             event_handler: Arc::clone(&self.event_handler),
             event_tx: self.event_tx.clone(),
-            event_rx: self.event_rx.try_clone().unwrap(),
+            event_rx: Arc::clone(&self.event_rx),
+            handler_timeout: self.handler_timeout,
+            dispatcher_running: Arc::clone(&self.dispatcher_running),
+            router: Arc::clone(&self.router),
+            websocket_url: self.websocket_url.clone(),
+            delivery: Arc::clone(&self.delivery),
+            outbox: Arc::clone(&self.outbox),
+            signal: Arc::clone(&self.signal),
+            latency: Arc::clone(&self.latency),
+            app_state: Arc::clone(&self.app_state),
+            sync_collections: self.sync_collections.clone(),
+            unread: Arc::clone(&self.unread),
+            recent: Arc::clone(&self.recent),
+            message_store: Arc::clone(&self.message_store),
+            ephemeral: Arc::clone(&self.ephemeral),
+            phone: Arc::clone(&self.phone),
+            two_step: Arc::clone(&self.two_step),
+            traffic: Arc::clone(&self.traffic),
+            contacts: Arc::clone(&self.contacts),
+            groups: Arc::clone(&self.groups),
+            names: Arc::clone(&self.names),
+            uploader: Arc::clone(&self.uploader),
+            downloader: Arc::clone(&self.downloader),
+            history_requests: Arc::clone(&self.history_requests),
+            queries: Arc::clone(&self.queries),
+            polls: Arc::clone(&self.polls),
+            communities: Arc::clone(&self.communities),
+            presence: Arc::clone(&self.presence),
+            warmup: Arc::clone(&self.warmup),
+            chat_states: Arc::clone(&self.chat_states),
+            sent_log: Arc::clone(&self.sent_log),
+            retries: Arc::clone(&self.retries),
+            offline: Arc::clone(&self.offline),
+            rate_limiter: Arc::clone(&self.rate_limiter),
+            lease: self.lease.clone(),
+            heartbeat_interval: self.heartbeat_interval,
+            keepalive_interval: self.keepalive_interval,
+            tls: self.tls.clone(),
+            device: self.device.clone(),
+            logged_out: Arc::clone(&self.logged_out),
+            framing: Arc::clone(&self.framing),
+            send_queue: Arc::clone(&self.send_queue),
         }
     }
 }
@@ -639,12 +1952,54 @@ impl Clone for WhatsAppClient {
 // Builder untuk WhatsAppClient
 pub struct WhatsAppClientBuilder {
     event_handler: Option<Box<dyn EventHandler>>,
+    websocket_url: Option<String>,
+    heartbeat_interval: Option<Duration>,
+    traffic: Option<TrafficSettings>,
+    handler_timeout: Option<Duration>,
+    app_state_collections: Option<Vec<app_state::Collection>>,
+    journal: Option<Arc<dyn StateStore>>,
+    replay_window: Option<Duration>,
+    dedup: Option<(usize, Duration)>,
+    warmup: Option<warmup::WarmupSettings>,
+    lease: Option<(Arc<dyn StateStore>, String)>,
+    protocol_capture: Option<usize>,
+    max_message_retries: Option<u32>,
+    offline_outbox: Option<(Duration, Option<Arc<dyn StateStore>>)>,
+    rate_limit: Option<rate_limit::RateLimit>,
+    keepalive_interval: Option<Option<Duration>>,
+    tls: Option<tls::TlsConfig>,
+    device: Option<DeviceProps>,
+    send_queue_capacity: Option<usize>,
+    wire_trace: bool,
+    raw_node_hook: Option<wire_trace::RawNodeHook>,
+    message_store: Option<Arc<dyn MessageStore>>,
 }
 
 impl WhatsAppClientBuilder {
     pub fn new() -> Self {
         WhatsAppClientBuilder {
             event_handler: None,
+            websocket_url: None,
+            heartbeat_interval: None,
+            traffic: None,
+            handler_timeout: None,
+            app_state_collections: None,
+            journal: None,
+            replay_window: None,
+            dedup: None,
+            warmup: None,
+            lease: None,
+            protocol_capture: None,
+            max_message_retries: None,
+            offline_outbox: None,
+            rate_limit: None,
+            keepalive_interval: None,
+            tls: None,
+            device: None,
+            send_queue_capacity: None,
+            wire_trace: false,
+            raw_node_hook: None,
+            message_store: None,
         }
     }
 
@@ -653,10 +2008,217 @@ impl WhatsAppClientBuilder {
         self
     }
 
+    /// Mengganti URL WebSocket server (mis. untuk mock server saat testing)
+    pub fn with_websocket_url(mut self, url: &str) -> Self {
+        self.websocket_url = Some(url.to_string());
+        self
+    }
+
+    /// Mengaktifkan `Event::Heartbeat` dengan interval tertentu (minimal 1 detik)
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// Mengganti interval ping keepalive (default 25 detik); `None` mematikan keepalive
+    pub fn with_keepalive_interval(mut self, interval: Option<Duration>) -> Self {
+        self.keepalive_interval = Some(interval);
+        self
+    }
+
+    /// Memakai root CA dan certificate pinning sendiri untuk koneksi `wss://`
+    pub fn with_tls_config(mut self, config: tls::TlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// Platform, nama browser, dan OS yang ditampilkan di daftar Perangkat
+    /// Tertaut ponsel (default `Chrome (Linux)`)
+    pub fn with_device_props(mut self, props: DeviceProps) -> Self {
+        self.device = Some(props);
+        self
+    }
+
+    /// Jumlah byte yang boleh menunggu ditulis ke socket sebelum pengiriman
+    /// ditahan (default `send_queue::DEFAULT_SEND_QUEUE_CAPACITY`)
+    pub fn with_send_queue_capacity(mut self, capacity: usize) -> Self {
+        self.send_queue_capacity = Some(capacity);
+        self
+    }
+
+    /// Memakai preset traffic shaping (jeda kirim, simulasi mengetik, presence, read receipt)
+    pub fn with_traffic_profile(self, profile: TrafficProfile) -> Self {
+        self.with_traffic_settings(profile.settings())
+    }
+
+    /// Memakai pengaturan traffic shaping kustom
+    pub fn with_traffic_settings(mut self, settings: TrafficSettings) -> Self {
+        self.traffic = Some(settings);
+        self
+    }
+
+    /// Batas waktu satu panggilan `handle_event` saat memakai `start_event_dispatcher`
+    pub fn with_handler_timeout(mut self, timeout: Duration) -> Self {
+        self.handler_timeout = Some(timeout);
+        self
+    }
+
+    /// Koleksi app state yang disinkronkan saat terhubung (default semua).
+    /// Koleksi lain bisa disinkronkan nanti dengan `WhatsAppClient::sync_collection`.
+    pub fn with_app_state_collections(mut self, collections: &[Collection]) -> Self {
+        self.app_state_collections = Some(collections.to_vec());
+        self
+    }
+
+    /// Mencatat pesan masuk ke `store` sampai aplikasi memanggil
+    /// `JournaledMessage::commit` (lihat modul `journal`)
+    pub fn with_event_journal(mut self, store: Arc<dyn StateStore>) -> Self {
+        self.journal = Some(store);
+        self
+    }
+
+    /// Jendela proteksi replay per pengirim (default 10 menit)
+    pub fn with_replay_window(mut self, window: Duration) -> Self {
+        self.replay_window = Some(window);
+        self
+    }
+
+    /// Jendela deduplikasi stanza masuk: `capacity` stanza terakhir diingat
+    /// selama `ttl` (default `dedup::DEFAULT_DEDUP_CAPACITY` dan
+    /// `dedup::DEFAULT_DEDUP_TTL`); `capacity` 0 mematikannya
+    pub fn with_dedup(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.dedup = Some((capacity, ttl));
+        self
+    }
+
+    /// Mengaktifkan batas pemanasan untuk akun yang baru dipasangkan
+    pub fn with_warmup(mut self, settings: warmup::WarmupSettings) -> Self {
+        self.warmup = Some(settings);
+        self
+    }
+
+    /// Menyimpan ringkasan `capacity` node masuk terakhir (tanpa isi) untuk
+    /// `WhatsAppClient::generate_support_bundle`
+    pub fn with_protocol_capture(mut self, capacity: usize) -> Self {
+        self.protocol_capture = Some(capacity);
+        self
+    }
+
+    /// Mencatat setiap node masuk dan keluar (bentuk mirip XML) ke log target
+    /// `rustdi::wire` level debug. Isi node tidak disamarkan.
+    pub fn with_wire_trace(mut self, enabled: bool) -> Self {
+        self.wire_trace = enabled;
+        self
+    }
+
+    /// Memanggil `hook` untuk setiap node masuk (setelah didekode) dan keluar
+    pub fn with_raw_node_hook(mut self, hook: impl Fn(wire_trace::Direction, &node_protocol::Node) + Send + Sync + 'static) -> Self {
+        self.raw_node_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Memakai lease sesi di `store` bersama agar hanya satu proses (`owner`)
+    /// yang terhubung dengan sesi ini (lihat modul `handover`)
+    pub fn with_session_lease(mut self, store: Arc<dyn StateStore>, owner: &str) -> Self {
+        self.lease = Some((store, owner.to_string()));
+        self
+    }
+
+    /// Batas kirim ulang satu pesan ke perangkat yang gagal mendekripsinya
+    /// (default `retry::DEFAULT_MAX_RETRIES`)
+    pub fn with_max_message_retries(mut self, max_retries: u32) -> Self {
+        self.max_message_retries = Some(max_retries);
+        self
+    }
+
+    /// Mengantrekan pesan yang dikirim saat terputus dan mengirimnya setelah
+    /// koneksi pulih (lihat modul `offline`). Pesan dibuang setelah `ttl`;
+    /// dengan `store` antrean bertahan setelah restart.
+    pub fn with_offline_outbox(mut self, ttl: Duration, store: Option<Arc<dyn StateStore>>) -> Self {
+        self.offline_outbox = Some((ttl, store));
+        self
+    }
+
+    /// Menolak pengiriman yang melewati budget per chat atau global dengan
+    /// `ErrorKind::RateLimited` (lihat modul `rate_limit`)
+    pub fn with_rate_limit(mut self, limit: rate_limit::RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Menyimpan riwayat pesan ke `store` alih-alih `MemoryMessageStore`
+    /// bawaan (lihat modul `message_store`)
+    pub fn with_message_store(mut self, store: Arc<dyn MessageStore>) -> Self {
+        self.message_store = Some(store);
+        self
+    }
+
     pub fn build(self) -> Result<WhatsAppClient> {
-        match self.event_handler {
-            Some(handler) => WhatsAppClient::new(handler),
-            None => Err("Event handler is required".into()),
+        let mut client = match self.event_handler {
+            Some(handler) => WhatsAppClient::new(handler)?,
+            None => return Err("Event handler is required".into()),
+        };
+
+        if let Some(url) = self.websocket_url {
+            client.websocket_url = url;
+        }
+        client.heartbeat_interval = self.heartbeat_interval;
+        if let Some(interval) = self.keepalive_interval {
+            client.keepalive_interval = interval;
+        }
+        if let Some(capacity) = self.send_queue_capacity {
+            client.send_queue = Arc::new(send_queue::SendQueue::start(Arc::clone(&client.sender), Arc::clone(&client.framing), capacity));
+        }
+        if let Some(props) = self.device {
+            props.validate()?;
+            client.device = props;
+        }
+        if let Some(config) = self.tls {
+            client.tls = Some(config.connector()?);
+        }
+        if let Some(timeout) = self.handler_timeout {
+            client.handler_timeout = timeout;
+        }
+        if let Some(settings) = self.traffic {
+            client.traffic.lock().unwrap().settings = settings;
         }
+        if let Some(collections) = self.app_state_collections {
+            client.sync_collections = collections;
+        }
+        if let Some(store) = self.journal {
+            client.event_tx.journal = Some(Arc::new(journal::EventJournal::new(store)));
+        }
+        if let Some(window) = self.replay_window {
+            client.router.lock().unwrap().set_replay_window(window);
+        }
+        if let Some((capacity, ttl)) = self.dedup {
+            client.router.lock().unwrap().set_dedup(capacity, ttl);
+        }
+        client.warmup.lock().unwrap().set_settings(self.warmup);
+        {
+            let mut wire = client.event_tx.wire.lock().unwrap();
+            wire.set_enabled(self.wire_trace);
+            wire.set_hook(self.raw_node_hook);
+        }
+        if let Some(capacity) = self.protocol_capture {
+            client.event_tx.diagnostics.lock().unwrap().set_capture_capacity(capacity);
+        }
+        if let Some(limit) = self.rate_limit {
+            client.set_rate_limit(limit);
+        }
+        if let Some(store) = self.message_store {
+            *client.message_store.write().unwrap() = store;
+        }
+        if let Some((ttl, store)) = self.offline_outbox {
+            client.offline.lock().unwrap().enable(ttl, store)?;
+        }
+        if let Some(max_retries) = self.max_message_retries {
+            client.retries.lock().unwrap().set_max_retries(max_retries);
+        }
+        if let Some((store, owner)) = self.lease {
+            client.lease = Some(Arc::new(handover::SessionLease::new(store, &owner, handover::DEFAULT_LEASE_TTL)));
+        }
+
+        Ok(client)
     }
 }
\ No newline at end of file
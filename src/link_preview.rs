@@ -0,0 +1,212 @@
+//! Preview tautan untuk pesan teks keluar
+//!
+//! Jika teks mengandung URL dan `SendOptions::link_preview` aktif, halaman
+//! diambil (fitur `reqwest`), lalu judul, deskripsi, dan thumbnail dari tag
+//! Open Graph/`<title>` dikirim sebagai `ExtendedTextMessage`. Tanpa fitur
+//! `reqwest`, hanya `matched_text`/`canonical_url` yang diisi.
+
+use crate::messages;
+
+/// Batas ukuran thumbnail yang disisipkan langsung ke pesan
+pub const MAX_THUMBNAIL_BYTES: usize = 64 * 1024;
+/// Batas bagian halaman yang dibaca; tag Open Graph ada di `<head>`
+pub const MAX_HTML_BYTES: usize = 512 * 1024;
+
+/// Metadata preview untuk satu URL
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkPreview {
+    /// URL persis seperti di teks
+    pub matched_text: String,
+    pub canonical_url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub jpeg_thumbnail: Option<Vec<u8>>,
+}
+
+impl LinkPreview {
+    pub fn new(url: &str) -> Self {
+        LinkPreview {
+            matched_text: url.to_string(),
+            canonical_url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Pesan teks dengan preview
+    pub fn into_message(self, text: &str) -> messages::Message {
+        messages::Message {
+            extended_text_message: Some(messages::ExtendedTextMessage {
+                text: text.to_string(),
+                matched_text: Some(self.matched_text),
+                canonical_url: Some(self.canonical_url),
+                title: self.title,
+                description: self.description,
+                jpeg_thumbnail: self.jpeg_thumbnail,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// URL http(s) pertama di teks
+pub fn first_url(text: &str) -> Option<&str> {
+    text.split_whitespace()
+        .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+        .map(|word| word.trim_end_matches(|c: char| matches!(c, '.' | ',' | ';' | ':' | '!' | '?' | ')' | '"' | '\'')))
+}
+
+/// Nilai atribut `name="..."` atau `name='...'` di dalam satu tag
+fn attr_value<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let lower = tag.to_ascii_lowercase();
+    let start = lower.find(&format!("{}=", name))? + name.len() + 1;
+    let rest = &tag[start..];
+    let quote = rest.chars().next()?;
+    if quote == '"' || quote == '\'' {
+        let end = rest[1..].find(quote)?;
+        Some(&rest[1..1 + end])
+    } else {
+        rest.split(|c: char| c.is_whitespace() || c == '>').next()
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&amp;", "&")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .trim()
+        .to_string()
+}
+
+/// Mengisi judul, deskripsi, dan URL gambar dari HTML halaman
+pub fn parse_html(url: &str, html: &str) -> LinkPreview {
+    let mut preview = LinkPreview::new(url);
+    let mut fallback_description = None;
+
+    for tag in html.split('<').filter(|tag| tag.to_ascii_lowercase().starts_with("meta ")) {
+        let key = match attr_value(tag, "property").or_else(|| attr_value(tag, "name")) {
+            Some(key) => key.to_ascii_lowercase(),
+            None => continue,
+        };
+        let content = match attr_value(tag, "content") {
+            Some(content) => unescape(content),
+            None => continue,
+        };
+        match key.as_str() {
+            "og:title" => preview.title = Some(content),
+            "og:description" => preview.description = Some(content),
+            "og:image" => preview.image_url = Some(content),
+            "og:url" => preview.canonical_url = content,
+            "description" => fallback_description = Some(content),
+            _ => {}
+        }
+    }
+
+    if preview.title.is_none() {
+        let lower = html.to_ascii_lowercase();
+        if let Some(start) = lower.find("<title") {
+            if let Some(end) = lower[start..].find("</title>").map(|end| start + end) {
+                if let Some(open_end) = lower[start..end].find('>') {
+                    preview.title = Some(unescape(&html[start + open_end + 1..end]));
+                }
+            }
+        }
+    }
+    if preview.description.is_none() {
+        preview.description = fallback_description;
+    }
+    preview
+}
+
+/// Membaca paling banyak `limit` byte body; `None` jika body lebih panjang
+#[cfg(feature = "reqwest")]
+fn read_limited(response: reqwest::blocking::Response, limit: usize) -> Option<Vec<u8>> {
+    use std::io::Read;
+
+    let mut body = Vec::new();
+    response.take(limit as u64 + 1).read_to_end(&mut body).ok()?;
+    if body.len() > limit {
+        return None;
+    }
+    Some(body)
+}
+
+/// Mengambil halaman dan thumbnail-nya
+#[cfg(feature = "reqwest")]
+pub fn fetch_preview(url: &str) -> crate::errors::Result<LinkPreview> {
+    use std::io::Read;
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let response = client.get(url).send().map_err(|e| format!("Failed to fetch link preview: {}", e))?;
+    // Halaman yang terlalu panjang hanya dibaca bagian awalnya
+    let mut body = Vec::new();
+    response
+        .take(MAX_HTML_BYTES as u64)
+        .read_to_end(&mut body)
+        .map_err(|e| format!("Failed to fetch link preview: {}", e))?;
+    let html = String::from_utf8_lossy(&body);
+
+    let mut preview = parse_html(url, &html);
+    preview.matched_text = url.to_string();
+    if let Some(ref image_url) = preview.image_url {
+        // Thumbnail opsional: kegagalan tidak membatalkan preview
+        if let Ok(response) = client.get(image_url).send() {
+            let is_jpeg = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map_or(false, |value| value.starts_with("image/jpeg"));
+            if is_jpeg {
+                preview.jpeg_thumbnail = read_limited(response, MAX_THUMBNAIL_BYTES);
+            }
+        }
+    }
+    Ok(preview)
+}
+
+/// Preview untuk URL pertama di teks. Kegagalan mengambil halaman tidak
+/// menggagalkan pengiriman; preview minimal (URL saja) dipakai.
+pub fn preview_for(text: &str) -> Option<LinkPreview> {
+    let url = first_url(text)?;
+    #[cfg(feature = "reqwest")]
+    {
+        if let Ok(preview) = fetch_preview(url) {
+            return Some(preview);
+        }
+    }
+    Some(LinkPreview::new(url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_url() {
+        assert_eq!(first_url("lihat https://example.com/a."), Some("https://example.com/a"));
+        assert_eq!(first_url("tanpa tautan"), None);
+    }
+
+    #[test]
+    fn test_parse_html() {
+        let html = r#"<html><head><title>Fallback</title>
+            <meta property="og:title" content="Judul &amp; Isi">
+            <meta name="description" content='Deskripsi'>
+            <meta property="og:image" content="https://example.com/t.jpg"></head></html>"#;
+        let preview = parse_html("https://example.com", html);
+        assert_eq!(preview.title.as_deref(), Some("Judul & Isi"));
+        assert_eq!(preview.description.as_deref(), Some("Deskripsi"));
+        assert_eq!(preview.image_url.as_deref(), Some("https://example.com/t.jpg"));
+
+        // Penutup sebelum pembuka tidak boleh membuat slice terbalik
+        let preview = parse_html("https://example.com", "</title><title>Judul</title>");
+        assert_eq!(preview.title.as_deref(), Some("Judul"));
+        assert_eq!(parse_html("https://example.com", "</title><title>").title, None);
+    }
+}
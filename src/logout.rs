@@ -0,0 +1,69 @@
+//! Logout: melepas perangkat ini dari akun
+//!
+//! Client mengirim `remove-companion-device` untuk JID perangkatnya sendiri,
+//! menghapus session, kunci Signal, dan kunci app state lokal, lalu menutup
+//! koneksi. Aplikasi menerima `Event::Disconnected(DisconnectReason::Removed)`
+//! dan harus memasangkan ulang (QR atau pairing code) untuk terhubung lagi.
+
+use std::sync::atomic::Ordering;
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::{app_state, iq, prekeys, signal, utils, ConnectionState, Jid, WhatsAppClient};
+
+/// Alasan koneksi terputus
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// Koneksi ditutup oleh server, jaringan, atau `disconnect`
+    ConnectionClosed,
+    /// Server berhenti menjawab ping keepalive; client menyambung ulang
+    KeepaliveTimeout,
+    /// Perangkat ini dilepas dari akun lewat `logout`
+    Removed,
+}
+
+/// IQ yang melepas perangkat `device` dari akun
+pub fn remove_device_node(device: &Jid) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("to", "s.whatsapp.net")
+        .attr("type", "set")
+        .attr("xmlns", "md")
+        .children(vec![Node::new("remove-companion-device").attr("jid", &device.to_string()).attr("reason", "user_initiated")])
+}
+
+impl WhatsAppClient {
+    /// Melepas perangkat ini dari akun dan menghapus data sesi lokal.
+    /// Setelah berhasil client harus dipasangkan ulang.
+    pub fn logout(&self) -> Result<()> {
+        if self.get_state() != ConnectionState::Connected {
+            return Err(Error { kind: ErrorKind::NotConnected });
+        }
+        let device = self.get_own_jid().ok_or("Logout requires a paired session")?;
+        self.query(&remove_device_node(&device), iq::DEFAULT_QUERY_TIMEOUT)?;
+
+        *self.session.lock().unwrap() = None;
+        *self.signal.lock().unwrap() = signal::SignalStore::new();
+        *self.pre_keys.lock().unwrap() = prekeys::PreKeyUploads::new();
+        *self.app_state.lock().unwrap() = app_state::AppStateStore::new();
+
+        // `on_close` melaporkan `DisconnectReason::Removed` untuk penutupan ini
+        self.logged_out.store(true, Ordering::SeqCst);
+        self.disconnect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_device_node() {
+        let device = Jid::user("628111").with_device(3);
+        let node = remove_device_node(&device);
+        assert_eq!((node.get_attr("type"), node.get_attr("xmlns")), (Some("set"), Some("md")));
+        let remove = node.get_child("remove-companion-device").unwrap();
+        assert_eq!(remove.get_attr("jid"), Some("628111:3@s.whatsapp.net"));
+        assert_eq!(remove.get_attr("reason"), Some("user_initiated"));
+    }
+}
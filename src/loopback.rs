@@ -0,0 +1,178 @@
+//! Penerima simulasi untuk pengembangan lokal
+//!
+//! `LoopbackTransport` menjalankan mock server dengan satu peer lokal. Pesan
+//! yang dikirim client ke peer disegel dengan X25519 + AES-256-GCM ke
+//! identitas peer, dibuka oleh peer, lalu balasannya disegel kembali ke
+//! identitas perangkat lokal dan masuk ke pipeline inbound client seperti
+//! pesan dari kontak sungguhan. Dengan begitu alur kirim/terima lengkap bisa
+//! dicoba tanpa akun WhatsApp.
+//!
+//! Aktifkan dengan feature `testing`.
+
+use openssl::derive::Deriver;
+use openssl::pkey::{Id, PKey, Private};
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::errors::*;
+use crate::messages::{Message, WebMessageInfo};
+use crate::testing::{self, MockServer};
+use crate::{utils, Jid, WhatsAppClient};
+
+/// Info derivasi kunci pesan loopback
+const KEY_INFO: &[u8] = b"WhatsApp Loopback Message Keys";
+
+/// Menentukan balasan peer untuk pesan masuk; `None` berarti tidak membalas
+pub type Responder = Box<dyn Fn(&WebMessageInfo) -> Option<Message> + Send>;
+
+fn crypto_error(context: &str, e: impl std::fmt::Display) -> Error {
+    Error { kind: ErrorKind::CryptoError(format!("{}: {}", context, e)) }
+}
+
+/// Pasangan kunci X25519 statis
+struct Identity {
+    private: PKey<Private>,
+    public: Vec<u8>,
+}
+
+impl Identity {
+    fn generate() -> Result<Self> {
+        let private = PKey::generate_x25519().map_err(|e| crypto_error("Failed to generate identity", e))?;
+        let public = private.raw_public_key().map_err(|e| crypto_error("Failed to read identity", e))?;
+        Ok(Identity { private, public })
+    }
+
+    /// Kunci AES dari ECDH dengan `their_public`
+    fn message_key(&self, their_public: &[u8]) -> Result<Vec<u8>> {
+        let theirs = PKey::public_key_from_raw_bytes(their_public, Id::X25519).map_err(|e| crypto_error("Invalid public key", e))?;
+        let mut deriver = Deriver::new(&self.private).map_err(|e| crypto_error("Key agreement failed", e))?;
+        deriver.set_peer(&theirs).map_err(|e| crypto_error("Key agreement failed", e))?;
+        let shared = deriver.derive_to_vec().map_err(|e| crypto_error("Key agreement failed", e))?;
+
+        let prk = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &[0u8; 32]), &shared);
+        let info = [KEY_INFO, &[1]].concat();
+        Ok(hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, prk.as_ref()), &info).as_ref().to_vec())
+    }
+
+    /// iv (12) || ciphertext || tag (16)
+    fn seal(&self, their_public: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut iv = [0u8; 12];
+        SystemRandom::new().fill(&mut iv).map_err(|_| "Failed to generate IV")?;
+        let mut tag = [0u8; 16];
+        let ciphertext = encrypt_aead(Cipher::aes_256_gcm(), &self.message_key(their_public)?, Some(&iv), &[], plaintext, &mut tag)
+            .map_err(|e| crypto_error("Loopback encryption failed", e))?;
+        Ok([&iv[..], &ciphertext, &tag].concat())
+    }
+
+    fn open(&self, their_public: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < 12 + 16 {
+            return Err(Error { kind: ErrorKind::CryptoError("Sealed loopback message too short".to_string()) });
+        }
+        let (iv, rest) = sealed.split_at(12);
+        let (ciphertext, tag) = rest.split_at(rest.len() - 16);
+        decrypt_aead(Cipher::aes_256_gcm(), &self.message_key(their_public)?, Some(iv), &[], ciphertext, tag)
+            .map_err(|e| crypto_error("Loopback decryption failed", e))
+    }
+}
+
+/// Peer simulasi di dalam mock server
+pub(crate) struct LoopbackPeer {
+    jid: Jid,
+    identity: Identity,
+    device: Identity,
+    responder: Responder,
+}
+
+impl LoopbackPeer {
+    pub(crate) fn jid(&self) -> &Jid {
+        &self.jid
+    }
+
+    /// Mengantar `web_message` ke peer dan mengembalikan balasannya (jika ada)
+    /// dalam bentuk yang diterima perangkat lokal
+    pub(crate) fn deliver(&self, web_message: &WebMessageInfo) -> Result<Option<WebMessageInfo>> {
+        let sealed = self.device.seal(&self.identity.public, &web_message.to_bytes())?;
+        let received = WebMessageInfo::from_bytes(&self.identity.open(&self.device.public, &sealed)?)?;
+
+        let message = match (self.responder)(&received) {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+        let reply = WebMessageInfo {
+            key: crate::messages::MessageKey {
+                remote_jid: self.jid.to_string(),
+                from_me: false,
+                id: utils::generate_message_id(),
+                participant: None,
+            },
+            message: Some(message),
+            message_timestamp: Some(chrono::Utc::now().timestamp() as u64),
+            ..Default::default()
+        };
+
+        let sealed = self.identity.seal(&self.device.public, &reply.to_bytes())?;
+        let opened = self.device.open(&self.identity.public, &sealed)?;
+        WebMessageInfo::from_bytes(&opened).map(Some)
+    }
+}
+
+/// Mock server dengan satu penerima simulasi
+pub struct LoopbackTransport {
+    server: MockServer,
+    peer: Jid,
+}
+
+impl LoopbackTransport {
+    /// Menjalankan transport dengan peer `peer` yang membalas setiap pesan
+    /// dengan isi yang sama (echo)
+    pub fn start(peer: &Jid) -> Result<Self> {
+        Self::with_responder(peer, Box::new(|received| received.message.clone()))
+    }
+
+    /// Menjalankan transport dengan balasan dari `responder`
+    pub fn with_responder(peer: &Jid, responder: Responder) -> Result<Self> {
+        if peer.is_group() {
+            return Err("Loopback peer must be a contact, not a group".into());
+        }
+        let server = MockServer::start()?;
+        server.set_loopback_peer(LoopbackPeer {
+            jid: peer.clone(),
+            identity: Identity::generate()?,
+            device: Identity::generate()?,
+            responder,
+        });
+        Ok(LoopbackTransport { server, peer: peer.clone() })
+    }
+
+    pub fn peer(&self) -> &Jid {
+        &self.peer
+    }
+
+    /// URL WebSocket untuk `WhatsAppClientBuilder::with_websocket_url`
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+
+    /// Membuat client sebagai `own` yang langsung terotentikasi ke transport
+    pub fn connect(&self, own: &Jid) -> Result<WhatsAppClient> {
+        testing::connect_client(&self.server, own)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open_between_identities() {
+        let device = Identity::generate().unwrap();
+        let peer = Identity::generate().unwrap();
+
+        let sealed = device.seal(&peer.public, b"halo").unwrap();
+        assert_eq!(peer.open(&device.public, &sealed).unwrap(), b"halo");
+
+        let stranger = Identity::generate().unwrap();
+        assert!(stranger.open(&device.public, &sealed).is_err());
+    }
+}
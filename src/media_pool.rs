@@ -0,0 +1,226 @@
+//! Pool koneksi HTTP untuk transfer media
+//!
+//! Semua upload/download media berbagi satu HTTP client sehingga koneksi ke
+//! host media yang sama dipakai ulang (keep-alive), dan jumlah transfer yang
+//! berjalan bersamaan dibatasi `MediaPoolConfig::max_parallel`. Transport HTTP
+//! hanya tersedia dengan fitur `reqwest`; pembatas paralelisme bisa dipakai
+//! oleh `MediaUploader` kustom.
+
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+/// Pengaturan pool media
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaPoolConfig {
+    /// Transfer maksimal yang berjalan bersamaan
+    pub max_parallel: usize,
+    /// Koneksi idle yang disimpan per host media
+    pub max_idle_per_host: usize,
+    pub timeout: Duration,
+}
+
+impl Default for MediaPoolConfig {
+    fn default() -> Self {
+        MediaPoolConfig {
+            max_parallel: 4,
+            max_idle_per_host: 4,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Pembatas jumlah transfer bersamaan
+pub struct TransferLimiter {
+    max: usize,
+    active: Mutex<usize>,
+    released: Condvar,
+}
+
+/// Slot transfer; dilepas otomatis saat di-drop
+pub struct TransferPermit<'a> {
+    limiter: &'a TransferLimiter,
+}
+
+impl TransferLimiter {
+    pub fn new(max: usize) -> Self {
+        TransferLimiter {
+            max: max.max(1),
+            active: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Menunggu sampai ada slot kosong
+    pub fn acquire(&self) -> TransferPermit<'_> {
+        let mut active = self.active.lock().unwrap();
+        while *active >= self.max {
+            active = self.released.wait(active).unwrap();
+        }
+        *active += 1;
+        TransferPermit { limiter: self }
+    }
+
+    pub fn active(&self) -> usize {
+        *self.active.lock().unwrap()
+    }
+}
+
+impl Drop for TransferPermit<'_> {
+    fn drop(&mut self) {
+        *self.limiter.active.lock().unwrap() -= 1;
+        self.limiter.released.notify_one();
+    }
+}
+
+#[cfg(feature = "reqwest")]
+pub use self::http::{HttpMediaDownloader, HttpMediaUploader, MediaHttpPool};
+
+#[cfg(feature = "reqwest")]
+mod http {
+    use super::*;
+
+    use base64::Engine as _;
+
+    use crate::errors::*;
+    use crate::media_upload::{EncryptedMedia, MediaDownloader, MediaUploader, UploadedMedia};
+    use crate::MediaType;
+
+    /// HTTP client bersama untuk semua transfer media
+    pub struct MediaHttpPool {
+        client: reqwest::blocking::Client,
+        limiter: TransferLimiter,
+    }
+
+    impl MediaHttpPool {
+        pub fn new(config: MediaPoolConfig) -> Result<Self> {
+            let client = reqwest::blocking::Client::builder()
+                .pool_max_idle_per_host(config.max_idle_per_host)
+                .timeout(config.timeout)
+                .build()
+                .map_err(|e| format!("Failed to build media HTTP client: {}", e))?;
+            Ok(MediaHttpPool {
+                client,
+                limiter: TransferLimiter::new(config.max_parallel),
+            })
+        }
+
+        /// Mengunduh file terenkripsi dari CDN media
+        pub fn download(&self, url: &str) -> Result<Vec<u8>> {
+            let _permit = self.limiter.acquire();
+            let response = self
+                .client
+                .get(url)
+                .send()
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("Media download failed: {}", e))?;
+            let bytes = response.bytes().map_err(|e| format!("Media download failed: {}", e))?;
+            Ok(bytes.to_vec())
+        }
+
+        /// Mengirim body ke `url` dan mengembalikan balasan JSON
+        pub fn post(&self, url: &str, body: Vec<u8>) -> Result<serde_json::Value> {
+            let _permit = self.limiter.acquire();
+            self.client
+                .post(url)
+                .header(reqwest::header::ORIGIN, "https://web.whatsapp.com")
+                .body(body)
+                .send()
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.json::<serde_json::Value>())
+                .map_err(|e| Error { kind: ErrorKind::MediaUploadFailed(e.to_string()) })
+        }
+    }
+
+    /// `MediaUploader` berbasis `MediaHttpPool`. Host dan token auth berasal
+    /// dari balasan `media_conn` server.
+    pub struct HttpMediaUploader {
+        pool: std::sync::Arc<MediaHttpPool>,
+        host: String,
+        auth: String,
+    }
+
+    impl HttpMediaUploader {
+        pub fn new(pool: std::sync::Arc<MediaHttpPool>, host: &str, auth: &str) -> Self {
+            HttpMediaUploader {
+                pool,
+                host: host.to_string(),
+                auth: auth.to_string(),
+            }
+        }
+    }
+
+    impl MediaUploader for HttpMediaUploader {
+        fn upload(&self, media: &EncryptedMedia, media_type: MediaType) -> Result<UploadedMedia> {
+            let path = media_type.upload_path();
+            let token = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&media.file_enc_sha256);
+            let url = format!("https://{}/mms/{}/{}?auth={}&token={}", self.host, path, token, self.auth, token);
+
+            let response = self.pool.post(&url, media.data.clone())?;
+            match (response["url"].as_str(), response["direct_path"].as_str()) {
+                (Some(url), Some(direct_path)) => Ok(UploadedMedia {
+                    url: url.to_string(),
+                    direct_path: direct_path.to_string(),
+                }),
+                _ => Err("Media upload response is missing url/direct_path".into()),
+            }
+        }
+    }
+
+    /// `MediaDownloader` berbasis `MediaHttpPool`
+    pub struct HttpMediaDownloader {
+        pool: std::sync::Arc<MediaHttpPool>,
+        host: String,
+    }
+
+    impl HttpMediaDownloader {
+        pub fn new(pool: std::sync::Arc<MediaHttpPool>) -> Self {
+            HttpMediaDownloader::with_host(pool, "mmg.whatsapp.net")
+        }
+
+        pub fn with_host(pool: std::sync::Arc<MediaHttpPool>, host: &str) -> Self {
+            HttpMediaDownloader {
+                pool,
+                host: host.to_string(),
+            }
+        }
+    }
+
+    impl MediaDownloader for HttpMediaDownloader {
+        fn download(&self, direct_path: &str) -> Result<Vec<u8>> {
+            self.pool.download(&format!("https://{}{}", self.host, direct_path))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_limiter_caps_parallel_transfers() {
+        let limiter = Arc::new(TransferLimiter::new(2));
+        let peak = Arc::new(Mutex::new(0));
+
+        let workers: Vec<_> = (0..6)
+            .map(|_| {
+                let limiter = Arc::clone(&limiter);
+                let peak = Arc::clone(&peak);
+                thread::spawn(move || {
+                    let _permit = limiter.acquire();
+                    let mut peak = peak.lock().unwrap();
+                    *peak = (*peak).max(limiter.active());
+                    drop(peak);
+                    thread::sleep(Duration::from_millis(20));
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert!(*peak.lock().unwrap() <= 2);
+        assert_eq!(limiter.active(), 0);
+    }
+}
@@ -0,0 +1,206 @@
+//! Enkripsi, upload dan unduhan media
+//!
+//! Media dienkripsi dengan media key acak (HKDF-SHA256 → iv, cipher key, mac
+//! key; AES-256-CBC; HMAC-SHA256 terpotong 10 byte) lalu diserahkan ke
+//! `MediaUploader` yang dipasang aplikasi. Unduhan (mis. blob history sync)
+//! lewat `MediaDownloader`. Transport HTTP sengaja tidak dibawa crate ini agar
+//! pengguna bebas memilih HTTP client.
+
+use std::sync::Arc;
+
+use openssl::memcmp;
+use openssl::symm::{decrypt, encrypt, Cipher};
+use ring::{digest, hkdf, hmac, rand};
+use ring::rand::SecureRandom;
+
+use crate::errors::*;
+use crate::{MediaType, WhatsAppClient};
+
+/// Panjang tag HMAC yang ditempelkan di akhir file terenkripsi
+pub const MEDIA_MAC_LENGTH: usize = 10;
+
+/// Info HKDF per jenis media
+pub fn media_key_info(media_type: MediaType) -> &'static [u8] {
+    match media_type.upload_kind() {
+        MediaType::Image => b"WhatsApp Image Keys",
+        MediaType::Video => b"WhatsApp Video Keys",
+        MediaType::Audio => b"WhatsApp Audio Keys",
+        _ => b"WhatsApp Document Keys",
+    }
+}
+
+/// Info HKDF untuk blob history sync
+pub const HISTORY_KEY_INFO: &[u8] = b"WhatsApp History Keys";
+
+/// Media yang sudah dienkripsi dan siap diupload
+#[derive(Debug, Clone)]
+pub struct EncryptedMedia {
+    pub media_key: Vec<u8>,
+    /// Ciphertext diikuti tag HMAC
+    pub data: Vec<u8>,
+    pub file_sha256: Vec<u8>,
+    pub file_enc_sha256: Vec<u8>,
+    pub file_length: u64,
+}
+
+/// Lokasi media setelah diupload
+#[derive(Debug, Clone)]
+pub struct UploadedMedia {
+    pub url: String,
+    pub direct_path: String,
+}
+
+/// Transport upload media yang dipasang aplikasi
+pub trait MediaUploader: Send + Sync {
+    fn upload(&self, media: &EncryptedMedia, media_type: MediaType) -> Result<UploadedMedia>;
+}
+
+/// Transport unduhan media yang dipasang aplikasi
+pub trait MediaDownloader: Send + Sync {
+    /// Mengunduh file terenkripsi di `direct_path` (mis. `/v/t62.7114-24/...`)
+    fn download(&self, direct_path: &str) -> Result<Vec<u8>>;
+}
+
+struct OutputLength(usize);
+
+impl hkdf::KeyType for OutputLength {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+fn crypto_error(msg: &str) -> Error {
+    Error { kind: ErrorKind::CryptoError(msg.to_string()) }
+}
+
+/// Menurunkan (iv, cipher key, mac key) dari media key
+fn expand_media_key(media_key: &[u8], info: &[u8]) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &[]).extract(media_key);
+    let info = [info];
+    let okm = prk.expand(&info, OutputLength(112)).map_err(|_| crypto_error("HKDF expand failed"))?;
+    let mut expanded = [0u8; 112];
+    okm.fill(&mut expanded).map_err(|_| crypto_error("HKDF fill failed"))?;
+    Ok((expanded[..16].to_vec(), expanded[16..48].to_vec(), expanded[48..80].to_vec()))
+}
+
+/// Mengenkripsi media dengan media key baru
+pub fn encrypt_media(plaintext: &[u8], media_type: MediaType) -> Result<EncryptedMedia> {
+    let mut media_key = [0u8; 32];
+    rand::SystemRandom::new().fill(&mut media_key).map_err(|_| crypto_error("Failed to generate media key"))?;
+    encrypt_media_with_key(plaintext, media_type, &media_key)
+}
+
+/// Mengenkripsi media dengan media key tertentu
+pub fn encrypt_media_with_key(plaintext: &[u8], media_type: MediaType, media_key: &[u8]) -> Result<EncryptedMedia> {
+    encrypt_with_info(plaintext, media_key_info(media_type), media_key)
+}
+
+pub(crate) fn encrypt_with_info(plaintext: &[u8], info: &[u8], media_key: &[u8]) -> Result<EncryptedMedia> {
+    let (iv, cipher_key, mac_key) = expand_media_key(media_key, info)?;
+    let ciphertext = encrypt(Cipher::aes_256_cbc(), &cipher_key, Some(&iv), plaintext)
+        .map_err(|e| crypto_error(&format!("Failed to encrypt media: {}", e)))?;
+
+    let mut signed = iv.clone();
+    signed.extend_from_slice(&ciphertext);
+    let mac = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &mac_key), &signed);
+
+    let mut data = ciphertext;
+    data.extend_from_slice(&mac.as_ref()[..MEDIA_MAC_LENGTH]);
+
+    Ok(EncryptedMedia {
+        media_key: media_key.to_vec(),
+        file_sha256: digest::digest(&digest::SHA256, plaintext).as_ref().to_vec(),
+        file_enc_sha256: digest::digest(&digest::SHA256, &data).as_ref().to_vec(),
+        file_length: plaintext.len() as u64,
+        data,
+    })
+}
+
+/// Memeriksa tag HMAC lalu mendekripsi file hasil unduhan. `info` adalah
+/// `media_key_info` atau `HISTORY_KEY_INFO`.
+pub fn decrypt_media(data: &[u8], info: &[u8], media_key: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < MEDIA_MAC_LENGTH {
+        return Err(crypto_error("Encrypted media is too short"));
+    }
+    let (ciphertext, mac) = data.split_at(data.len() - MEDIA_MAC_LENGTH);
+    let (iv, cipher_key, mac_key) = expand_media_key(media_key, info)?;
+
+    let mut signed = iv.clone();
+    signed.extend_from_slice(ciphertext);
+    let expected = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &mac_key), &signed);
+    if !memcmp::eq(&expected.as_ref()[..MEDIA_MAC_LENGTH], mac) {
+        return Err(crypto_error("Media MAC mismatch"));
+    }
+    decrypt(Cipher::aes_256_cbc(), &cipher_key, Some(&iv), ciphertext).map_err(|e| crypto_error(&format!("Failed to decrypt media: {}", e)))
+}
+
+impl WhatsAppClient {
+    /// Memasang transport unduhan media
+    pub fn set_media_downloader(&self, downloader: Arc<dyn MediaDownloader>) {
+        *self.downloader.lock().unwrap() = Some(downloader);
+    }
+
+    /// Memasang transport upload media
+    pub fn set_media_uploader(&self, uploader: Arc<dyn MediaUploader>) {
+        *self.uploader.lock().unwrap() = Some(uploader);
+    }
+
+    /// Mengenkripsi dan mengupload media; mengembalikan (media terenkripsi, lokasi)
+    pub(crate) fn upload_media(&self, plaintext: &[u8], media_type: MediaType) -> Result<(EncryptedMedia, UploadedMedia)> {
+        let uploader = self
+            .uploader
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("No media uploader configured (see WhatsAppClient::set_media_uploader)")?;
+        let media = encrypt_media(plaintext, media_type)?;
+        let uploaded = uploader.upload(&media, media_type).map_err(|e| match e.kind {
+            ErrorKind::MediaUploadFailed(_) => e,
+            _ => Error { kind: ErrorKind::MediaUploadFailed(e.to_string()) },
+        })?;
+        Ok((media, uploaded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_layout() {
+        let media = encrypt_media_with_key(b"voice", MediaType::Audio, &[7u8; 32]).unwrap();
+        // Satu blok AES (padding PKCS7) + tag HMAC
+        assert_eq!(media.data.len(), 16 + MEDIA_MAC_LENGTH);
+        assert_eq!(media.file_length, 5);
+
+        let other = encrypt_media_with_key(b"voice", MediaType::Image, &[7u8; 32]).unwrap();
+        assert_ne!(media.data, other.data);
+
+        // Voice note memakai kunci audio
+        let ptt = encrypt_media_with_key(b"voice", MediaType::Ptt, &[7u8; 32]).unwrap();
+        assert_eq!(media.data, ptt.data);
+    }
+
+    #[test]
+    fn test_decrypt_checks_mac() {
+        let media = encrypt_media_with_key(b"voice", MediaType::Audio, &[7u8; 32]).unwrap();
+        let info = media_key_info(MediaType::Audio);
+        assert_eq!(decrypt_media(&media.data, info, &media.media_key).unwrap(), b"voice");
+
+        let mut tampered = media.data.clone();
+        tampered[0] ^= 1;
+        assert!(decrypt_media(&tampered, info, &media.media_key).is_err());
+        assert!(decrypt_media(&media.data, HISTORY_KEY_INFO, &media.media_key).is_err());
+    }
+
+    #[test]
+    fn test_media_type_from_mimetype() {
+        assert_eq!(MediaType::from_mimetype("image/webp"), MediaType::Sticker);
+        assert_eq!(MediaType::from_mimetype("audio/ogg; codecs=opus"), MediaType::Ptt);
+        assert_eq!(MediaType::from_mimetype("audio/mpeg"), MediaType::Audio);
+        assert_eq!(MediaType::from_mimetype("image/gif"), MediaType::Gif);
+        assert_eq!(MediaType::from_mimetype("application/zip"), MediaType::Document);
+        assert_eq!(MediaType::Gif.upload_path(), "video");
+        assert_eq!(serde_json::to_string(&MediaType::Ptt).unwrap(), "\"ptt\"");
+    }
+}
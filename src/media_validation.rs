@@ -0,0 +1,165 @@
+//! Validasi format media sebelum upload
+//!
+//! Media dengan format salah tetap diterima server tetapi tidak bisa
+//! ditampilkan di sisi penerima, tanpa error apa pun ke pengirim. Fungsi di
+//! sini memeriksa isi file (bukan nama/ekstensi) dan mengembalikan
+//! `ErrorKind::InvalidMedia` sebelum media diupload.
+
+use crate::errors::*;
+use crate::picture;
+
+/// Sisi stiker (piksel); stiker harus persegi
+pub const STICKER_SIZE: u32 = 512;
+
+/// Mimetype voice note yang bisa diputar client
+pub const VOICE_NOTE_MIMETYPE: &str = "audio/ogg; codecs=opus";
+
+fn invalid_media(expected: &str, found: &str) -> Error {
+    Error {
+        kind: ErrorKind::InvalidMedia {
+            expected: expected.to_string(),
+            found: found.to_string(),
+        },
+    }
+}
+
+/// Menebak mimetype dari beberapa byte pertama file
+pub fn detect_mimetype(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if bytes.starts_with(b"GIF8") {
+        "image/gif"
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        "image/webp"
+    } else if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        "video/mp4"
+    } else if bytes.starts_with(b"OggS") {
+        if ogg_codec(bytes) == Some("opus") { VOICE_NOTE_MIMETYPE } else { "audio/ogg" }
+    } else if bytes.starts_with(b"ID3") || bytes.starts_with(&[0xFF, 0xFB]) {
+        "audio/mpeg"
+    } else if bytes.starts_with(b"%PDF") {
+        "application/pdf"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Codec dari paket pertama halaman Ogg pertama
+fn ogg_codec(bytes: &[u8]) -> Option<&'static str> {
+    let segments = *bytes.get(26)? as usize;
+    let packet = bytes.get(27 + segments..)?;
+    if packet.starts_with(b"OpusHead") {
+        Some("opus")
+    } else if packet.starts_with(b"\x01vorbis") {
+        Some("vorbis")
+    } else {
+        None
+    }
+}
+
+/// Lebar dan tinggi gambar WebP (VP8, VP8L atau VP8X)
+pub fn webp_dimensions(webp: &[u8]) -> Option<(u32, u32)> {
+    if webp.len() < 30 || &webp[..4] != b"RIFF" || &webp[8..12] != b"WEBP" {
+        return None;
+    }
+    let chunk = &webp[12..16];
+    let data = &webp[20..];
+    match chunk {
+        b"VP8 " => {
+            let width = u16::from_le_bytes([data[6], data[7]]) & 0x3FFF;
+            let height = u16::from_le_bytes([data[8], data[9]]) & 0x3FFF;
+            Some((width as u32, height as u32))
+        }
+        b"VP8L" => {
+            let bits = u32::from_le_bytes([data[1], data[2], data[3], data[4]]);
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        b"VP8X" => {
+            let width = u32::from_le_bytes([data[4], data[5], data[6], 0]) + 1;
+            let height = u32::from_le_bytes([data[7], data[8], data[9], 0]) + 1;
+            Some((width, height))
+        }
+        _ => None,
+    }
+}
+
+/// Stiker harus WebP berukuran `STICKER_SIZE` x `STICKER_SIZE`
+pub fn validate_sticker(bytes: &[u8]) -> Result<(u32, u32)> {
+    let found = detect_mimetype(bytes);
+    if found != "image/webp" {
+        return Err(invalid_media("image/webp", found));
+    }
+    let (width, height) = webp_dimensions(bytes).ok_or_else(|| invalid_media("image/webp", "unreadable WebP header"))?;
+    if width != STICKER_SIZE || height != STICKER_SIZE {
+        return Err(invalid_media(&format!("{}x{} WebP", STICKER_SIZE, STICKER_SIZE), &format!("{}x{} WebP", width, height)));
+    }
+    Ok((width, height))
+}
+
+/// Voice note harus Ogg Opus; Ogg Vorbis, MP3 dsb. tidak bisa diputar
+pub fn validate_voice_note(bytes: &[u8]) -> Result<()> {
+    match detect_mimetype(bytes) {
+        VOICE_NOTE_MIMETYPE => Ok(()),
+        "audio/ogg" => Err(invalid_media(VOICE_NOTE_MIMETYPE, &format!("audio/ogg; codecs={}", ogg_codec(bytes).unwrap_or("unknown")))),
+        found => Err(invalid_media(VOICE_NOTE_MIMETYPE, found)),
+    }
+}
+
+/// Gambar harus JPEG atau PNG; mengembalikan mimetype dan ukuran jika terbaca
+pub fn validate_image(bytes: &[u8]) -> Result<(&'static str, Option<(u32, u32)>)> {
+    match detect_mimetype(bytes) {
+        "image/jpeg" => Ok(("image/jpeg", picture::jpeg_dimensions(bytes))),
+        "image/png" => {
+            let dimensions = bytes.get(16..24).map(|ihdr| {
+                (u32::from_be_bytes([ihdr[0], ihdr[1], ihdr[2], ihdr[3]]), u32::from_be_bytes([ihdr[4], ihdr[5], ihdr[6], ihdr[7]]))
+            });
+            Ok(("image/png", dimensions))
+        }
+        found => Err(invalid_media("image/jpeg or image/png", found)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webp_vp8x(width: u32, height: u32) -> Vec<u8> {
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0".to_vec();
+        webp.extend_from_slice(&(width - 1).to_le_bytes()[..3]);
+        webp.extend_from_slice(&(height - 1).to_le_bytes()[..3]);
+        webp
+    }
+
+    #[test]
+    fn test_sticker_must_be_512_webp() {
+        assert_eq!(validate_sticker(&webp_vp8x(512, 512)).unwrap(), (512, 512));
+
+        match validate_sticker(&webp_vp8x(512, 300)).unwrap_err().kind {
+            ErrorKind::InvalidMedia { expected, found } => {
+                assert_eq!(expected, "512x512 WebP");
+                assert_eq!(found, "512x300 WebP");
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        assert!(matches!(
+            validate_sticker(b"\x89PNG\r\n\x1a\n").unwrap_err().kind,
+            ErrorKind::InvalidMedia { ref found, .. } if found == "image/png"
+        ));
+    }
+
+    #[test]
+    fn test_voice_note_rejects_vorbis() {
+        let mut vorbis = b"OggS".to_vec();
+        vorbis.extend_from_slice(&[0; 22]);
+        vorbis.push(1);
+        vorbis.push(30);
+        vorbis.extend_from_slice(b"\x01vorbis");
+        match validate_voice_note(&vorbis).unwrap_err().kind {
+            ErrorKind::InvalidMedia { found, .. } => assert_eq!(found, "audio/ogg; codecs=vorbis"),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        assert!(validate_image(b"GIF89a").is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//! Builder untuk pesan keluar: teks, media, list dan tombol
+//!
+//! `ContextInfo` untuk reply harus berisi id pesan yang dikutip (`stanza_id`),
+//! pengirimnya (`participant`), dan salinan pesan tersebut. Builder ini
+//! menurunkan semua field itu dari `WebMessageInfo` aslinya.
+//!
+//! Mention membutuhkan dua hal: JID di `mentioned_jid` dan placeholder `@<nomor>`
+//! di teks (atau caption). Placeholder yang belum ada ditambahkan di akhir.
+//!
+//! ```ignore
+//! let builder = MessageBuilder::image(&url).caption("foto").mention(&jid).quote(&msg).ephemeral(day);
+//! client.send(&chat, builder)?;
+//! ```
+
+use std::time::Duration;
+
+use crate::errors::*;
+use crate::buttons::{ButtonsHeader, ButtonsMessageBuilder, ListMessageBuilder};
+use crate::messages::{
+    AudioMessage, DocumentMessage, ExtendedTextMessage, ImageMessage, Message, MessageContextInfo, StickerMessage, VideoMessage, WebMessageInfo,
+};
+use crate::{ephemeral, Jid, MediaType};
+
+/// Pengirim pesan `quoted` dari sudut pandang penerima reply.
+/// `own_jid` dipakai untuk pesan yang kita kirim sendiri.
+pub fn quoted_participant(quoted: &WebMessageInfo, own_jid: Option<&Jid>) -> Option<String> {
+    if let Some(participant) = quoted.key.participant.as_ref().or(quoted.participant.as_ref()) {
+        return Some(participant.clone());
+    }
+    if quoted.key.from_me {
+        own_jid.map(|jid| jid.to_string())
+    } else {
+        Some(quoted.key.remote_jid.clone())
+    }
+}
+
+/// Pesan media dari URL yang sudah diunggah; mimetype mengikuti jenis media
+pub fn media_message(media_type: MediaType, url: &str, caption: Option<&str>, view_once: Option<bool>) -> Message {
+    let caption = caption.map(|s| s.to_string());
+    match media_type {
+        MediaType::Image => Message {
+            image_message: Some(ImageMessage {
+                url: url.to_string(),
+                caption,
+                mimetype: Some("image/jpeg".to_string()),
+                view_once,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        MediaType::Video | MediaType::Gif => Message {
+            video_message: Some(VideoMessage {
+                url: url.to_string(),
+                caption,
+                mimetype: "video/mp4".to_string(),
+                gif_playback: if media_type == MediaType::Gif { Some(true) } else { None },
+                view_once,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        MediaType::Audio | MediaType::Ptt => Message {
+            audio_message: Some(AudioMessage {
+                url: url.to_string(),
+                mimetype: "audio/ogg; codecs=opus".to_string(),
+                ptt: media_type == MediaType::Ptt,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        MediaType::Sticker => Message {
+            sticker_message: Some(StickerMessage {
+                url: url.to_string(),
+                mimetype: "image/webp".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        MediaType::Document => Message {
+            document_message: Some(DocumentMessage {
+                url: url.to_string(),
+                file_name: "file".to_string(), // Harus disediakan dalam implementasi sebenarnya
+                mimetype: "application/pdf".to_string(), // Harus sesuai dengan jenis dokumen
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    }
+}
+
+/// Isi pesan yang dibangun
+#[derive(Debug, Clone)]
+enum Content {
+    Text(String),
+    Media { media_type: MediaType, url: String, caption: Option<String> },
+    List(ListMessageBuilder),
+    Buttons(ButtonsMessageBuilder),
+}
+
+impl Default for Content {
+    fn default() -> Self {
+        Content::Text(String::new())
+    }
+}
+
+/// Builder pesan keluar
+#[derive(Debug, Clone, Default)]
+pub struct MessageBuilder {
+    content: Content,
+    quoted: Option<WebMessageInfo>,
+    mentions: Vec<Jid>,
+    own_jid: Option<Jid>,
+    ephemeral: Option<Duration>,
+}
+
+/// Placeholder mention di teks untuk `jid`
+pub fn mention_placeholder(jid: &Jid) -> String {
+    format!("@{}", jid.id)
+}
+
+impl MessageBuilder {
+    pub fn text(text: &str) -> Self {
+        MessageBuilder {
+            content: Content::Text(text.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Media dari URL yang sudah diunggah
+    pub fn media(media_type: MediaType, url: &str) -> Self {
+        MessageBuilder {
+            content: Content::Media { media_type, url: url.to_string(), caption: None },
+            ..Default::default()
+        }
+    }
+
+    pub fn image(url: &str) -> Self {
+        MessageBuilder::media(MediaType::Image, url)
+    }
+
+    pub fn video(url: &str) -> Self {
+        MessageBuilder::media(MediaType::Video, url)
+    }
+
+    pub fn document(url: &str) -> Self {
+        MessageBuilder::media(MediaType::Document, url)
+    }
+
+    /// Pesan list; tambahkan pilihan dengan `section`
+    pub fn list(text: &str, button_text: &str) -> Self {
+        MessageBuilder {
+            content: Content::List(ListMessageBuilder::new(text, button_text)),
+            ..Default::default()
+        }
+    }
+
+    /// Pesan tombol balasan; tambahkan tombol dengan `button`
+    pub fn buttons(text: &str) -> Self {
+        MessageBuilder {
+            content: Content::Buttons(ButtonsMessageBuilder::new(text)),
+            ..Default::default()
+        }
+    }
+
+    /// Caption media (diabaikan untuk jenis pesan lain)
+    pub fn caption(mut self, text: &str) -> Self {
+        if let Content::Media { ref mut caption, .. } = self.content {
+            *caption = Some(text.to_string());
+        }
+        self
+    }
+
+    /// Bagian pesan list berisi baris `(id, judul, deskripsi)`
+    pub fn section(mut self, title: &str, rows: &[(&str, &str, &str)]) -> Self {
+        self.content = match self.content {
+            Content::List(list) => Content::List(list.section(title, rows)),
+            content => content,
+        };
+        self
+    }
+
+    /// Tombol balasan dengan `id` yang dikirim kembali saat ditekan
+    pub fn button(mut self, id: &str, text: &str) -> Self {
+        self.content = match self.content {
+            Content::Buttons(buttons) => Content::Buttons(buttons.button(id, text)),
+            content => content,
+        };
+        self
+    }
+
+    /// Header teks atau media pesan tombol
+    pub fn header(mut self, header: ButtonsHeader) -> Self {
+        self.content = match self.content {
+            Content::Buttons(buttons) => Content::Buttons(buttons.header(header)),
+            content => content,
+        };
+        self
+    }
+
+    /// Footer pesan list atau tombol
+    pub fn footer(mut self, text: &str) -> Self {
+        self.content = match self.content {
+            Content::List(list) => Content::List(list.footer(text)),
+            Content::Buttons(buttons) => Content::Buttons(buttons.footer(text)),
+            content => content,
+        };
+        self
+    }
+
+    /// Mengutip pesan `quoted` (reply)
+    pub fn reply_to(mut self, quoted: &WebMessageInfo) -> Self {
+        self.quoted = Some(quoted.clone());
+        self
+    }
+
+    /// Sama dengan `reply_to`
+    pub fn quote(self, quoted: &WebMessageInfo) -> Self {
+        self.reply_to(quoted)
+    }
+
+    /// Me-mention `jid`
+    pub fn mention(mut self, jid: &Jid) -> Self {
+        if !self.mentions.contains(jid) {
+            self.mentions.push(jid.clone());
+        }
+        self
+    }
+
+    /// Me-mention beberapa JID sekaligus
+    pub fn mentions(self, jids: &[Jid]) -> Self {
+        jids.iter().fold(self, |builder, jid| builder.mention(jid))
+    }
+
+    /// Pesan sementara yang hilang setelah `duration` (24 jam, 7 hari atau 90 hari)
+    pub fn ephemeral(mut self, duration: Duration) -> Self {
+        self.ephemeral = Some(duration);
+        self
+    }
+
+    /// JID akun sendiri, diperlukan untuk mengutip pesan yang kita kirim di chat pribadi.
+    /// `WhatsAppClient::send` mengisinya otomatis.
+    pub fn own_jid(mut self, jid: Option<Jid>) -> Self {
+        if self.own_jid.is_none() {
+            self.own_jid = jid;
+        }
+        self
+    }
+
+    /// Memeriksa kombinasi yang akan ditolak server atau tidak bisa ditampilkan
+    pub fn validate(&self) -> Result<()> {
+        ephemeral::expiration_secs(self.ephemeral)?;
+        match self.content {
+            Content::Media { media_type: MediaType::Sticker | MediaType::Ptt, caption: Some(_), .. } => {
+                Err("Stickers and voice notes cannot have a caption".into())
+            }
+            Content::List(ref list) => list.validate(),
+            Content::Buttons(ref buttons) => buttons.validate(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Tujuan reply harus chat yang sama dengan pesan yang dikutip, kecuali diisi `remote_jid`
+    fn context_info(&self, to: &Jid) -> Option<MessageContextInfo> {
+        if self.quoted.is_none() && self.mentions.is_empty() && self.ephemeral.is_none() {
+            return None;
+        }
+
+        let mut context = MessageContextInfo {
+            mentioned_jid: self.mentions.iter().map(|jid| jid.to_string()).collect(),
+            expiration: self.ephemeral.map(|duration| duration.as_secs() as u32),
+            ..Default::default()
+        };
+        if let Some(ref quoted) = self.quoted {
+            context.stanza_id = Some(quoted.key.id.clone());
+            context.participant = quoted_participant(quoted, self.own_jid.as_ref());
+            context.quoted_message = quoted.message.clone().map(Box::new);
+            if quoted.key.remote_jid != to.to_string() {
+                context.remote_jid = Some(quoted.key.remote_jid.clone());
+            }
+        }
+        Some(context)
+    }
+
+    /// Teks dengan placeholder untuk setiap mention
+    fn rendered_text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for jid in &self.mentions {
+            let placeholder = mention_placeholder(jid);
+            if !text.contains(&placeholder) {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(&placeholder);
+            }
+        }
+        text
+    }
+
+    /// Membangun pesan untuk dikirim ke `to`.
+    /// Teks tanpa konteks dikirim sebagai `conversation` biasa.
+    pub fn build(&self, to: &Jid) -> Message {
+        let context_info = self.context_info(to);
+        match self.content {
+            Content::Text(ref text) => match context_info {
+                Some(context_info) => Message {
+                    extended_text_message: Some(ExtendedTextMessage {
+                        text: self.rendered_text(text),
+                        context_info: Some(context_info),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                None => Message {
+                    conversation: Some(text.clone()),
+                    ..Default::default()
+                },
+            },
+            Content::Media { media_type, ref url, ref caption } => {
+                let caption = match caption {
+                    Some(caption) => Some(self.rendered_text(caption)),
+                    None if self.mentions.is_empty() => None,
+                    None => Some(self.rendered_text("")),
+                };
+                let mut message = media_message(media_type, url, caption.as_deref(), None);
+                let slot = message
+                    .image_message
+                    .as_mut()
+                    .map(|m| &mut m.context_info)
+                    .or_else(|| message.video_message.as_mut().map(|m| &mut m.context_info))
+                    .or_else(|| message.audio_message.as_mut().map(|m| &mut m.context_info))
+                    .or_else(|| message.sticker_message.as_mut().map(|m| &mut m.context_info))
+                    .or_else(|| message.document_message.as_mut().map(|m| &mut m.context_info));
+                if let Some(slot) = slot {
+                    *slot = context_info;
+                }
+                message
+            }
+            Content::List(ref list) => {
+                let mut list_message = list.list_message(self.rendered_text(list.text()));
+                list_message.context_info = context_info;
+                Message {
+                    list_message: Some(list_message),
+                    ..Default::default()
+                }
+            }
+            Content::Buttons(ref buttons) => {
+                let mut buttons_message = buttons.buttons_message(self.rendered_text(buttons.text()));
+                buttons_message.context_info = context_info;
+                Message {
+                    buttons_message: Some(buttons_message),
+                    ..Default::default()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::MessageKey;
+
+    fn incoming(remote_jid: &str, participant: Option<&str>, from_me: bool) -> WebMessageInfo {
+        WebMessageInfo {
+            key: MessageKey {
+                remote_jid: remote_jid.to_string(),
+                from_me,
+                id: "QUOTED".to_string(),
+                participant: participant.map(|p| p.to_string()),
+            },
+            message: Some(Message {
+                conversation: Some("halo".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_reply_uses_participant() {
+        let group = Jid::group("123-456");
+        let quoted = incoming("123-456@g.us", Some("628111@s.whatsapp.net"), false);
+
+        let message = MessageBuilder::text("oke").reply_to(&quoted).build(&group);
+        let context = message.extended_text_message.unwrap().context_info.unwrap();
+        assert_eq!(context.stanza_id.as_deref(), Some("QUOTED"));
+        assert_eq!(context.participant.as_deref(), Some("628111@s.whatsapp.net"));
+        assert!(context.remote_jid.is_none());
+        assert!(context.quoted_message.is_some());
+    }
+
+    #[test]
+    fn test_mentions_fill_context_and_placeholders() {
+        let group = Jid::group("123-456");
+        let alice = Jid::user("628111");
+        let bob = Jid::user("628222");
+
+        let message = MessageBuilder::text("halo @628111").mentions(&[alice.clone(), bob.clone()]).build(&group);
+        let text = message.extended_text_message.unwrap();
+        assert_eq!(text.text, "halo @628111 @628222");
+        assert_eq!(text.context_info.unwrap().mentioned_jid, vec![alice.to_string(), bob.to_string()]);
+    }
+
+    #[test]
+    fn test_reply_to_own_message_in_private_chat() {
+        let chat = Jid::user("628222");
+        let own = Jid::user("628999");
+        let quoted = incoming("628222@s.whatsapp.net", None, true);
+
+        let message = MessageBuilder::text("ralat").reply_to(&quoted).own_jid(Some(own.clone())).build(&chat);
+        let context = message.extended_text_message.unwrap().context_info.unwrap();
+        assert_eq!(context.participant, Some(own.to_string()));
+    }
+
+    #[test]
+    fn test_media_and_buttons_with_context() {
+        let chat = Jid::user("628222");
+        let alice = Jid::user("628111");
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        let builder = MessageBuilder::image("https://mmg.whatsapp.net/x").caption("foto").mention(&alice).ephemeral(day);
+        builder.validate().unwrap();
+        let image = builder.build(&chat).image_message.unwrap();
+        assert_eq!(image.caption.as_deref(), Some("foto @628111"));
+        assert_eq!(image.context_info.unwrap().expiration, Some(24 * 60 * 60));
+
+        let buttons = MessageBuilder::buttons("pilih").button("ya", "Ya").button("tidak", "Tidak").footer("bot");
+        let message = buttons.build(&chat).buttons_message.unwrap();
+        assert_eq!(message.buttons.len(), 2);
+        assert_eq!(message.footer_text.as_deref(), Some("bot"));
+
+        assert!(MessageBuilder::buttons("kosong").validate().is_err());
+        assert!(MessageBuilder::list("menu", "Buka").section("A", &[]).validate().is_err());
+        assert!(MessageBuilder::text("x").ephemeral(Duration::from_secs(60)).validate().is_err());
+        assert!(MessageBuilder::media(MediaType::Sticker, "u").caption("c").validate().is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Riwayat pesan yang bisa diganti backend-nya
+//!
+//! Client mengisi `MessageStore` dengan pesan masuk, pesan yang dikirim, dan
+//! pesan dari history sync, sehingga aplikasi bisa mengambil pesan berdasarkan
+//! key, rentang waktu, atau N pesan terakhir per chat. Bawaan adalah
+//! `MemoryMessageStore` (ring buffer per chat, hilang saat restart). Dengan
+//! feature `sqlite`, `SqliteMessageStore` menyimpan pesan ke berkas SQLite.
+//!
+//! Pesan dengan key yang sama (chat dan id) menimpa pesan sebelumnya, jadi
+//! pesan yang muncul lagi lewat history sync tidak tercatat dua kali.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::errors::*;
+use crate::messages::{MessageKey, WebMessageInfo};
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::WhatsAppClient;
+
+/// Jumlah pesan per chat yang disimpan `MemoryMessageStore` secara default
+pub const DEFAULT_MESSAGES_PER_CHAT: usize = 1000;
+
+/// Backend riwayat pesan
+pub trait MessageStore: Send + Sync {
+    /// Menyimpan pesan; pesan dengan key yang sama ditimpa
+    fn insert(&self, message: &WebMessageInfo) -> Result<()>;
+    fn get(&self, key: &MessageKey) -> Result<Option<WebMessageInfo>>;
+    /// Pesan `chat` dengan timestamp `from <= t < to` (detik), urut dari yang terlama
+    fn range(&self, chat: &str, from: u64, to: u64) -> Result<Vec<WebMessageInfo>>;
+    /// Paling banyak `limit` pesan terakhir `chat`, urut dari yang terlama
+    fn last(&self, chat: &str, limit: usize) -> Result<Vec<WebMessageInfo>>;
+}
+
+/// Store yang dipakai client; bisa diganti lewat builder
+pub type SharedMessageStore = Arc<RwLock<Arc<dyn MessageStore>>>;
+
+pub(crate) fn shared(store: Arc<dyn MessageStore>) -> SharedMessageStore {
+    Arc::new(RwLock::new(store))
+}
+
+fn timestamp(message: &WebMessageInfo) -> u64 {
+    message.message_timestamp.unwrap_or(0)
+}
+
+/// Ring buffer per chat di memori, urut menurut timestamp
+pub struct MemoryMessageStore {
+    capacity: usize,
+    chats: Mutex<HashMap<String, VecDeque<WebMessageInfo>>>,
+}
+
+impl MemoryMessageStore {
+    pub fn new() -> Self {
+        MemoryMessageStore::with_capacity(DEFAULT_MESSAGES_PER_CHAT)
+    }
+
+    /// Menyimpan paling banyak `capacity` pesan per chat; yang terlama dibuang
+    pub fn with_capacity(capacity: usize) -> Self {
+        MemoryMessageStore { capacity: capacity.max(1), chats: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl Default for MemoryMessageStore {
+    fn default() -> Self {
+        MemoryMessageStore::new()
+    }
+}
+
+impl MessageStore for MemoryMessageStore {
+    fn insert(&self, message: &WebMessageInfo) -> Result<()> {
+        let mut chats = self.chats.lock().unwrap();
+        let messages = chats.entry(message.key.remote_jid.clone()).or_default();
+        if let Some(index) = messages.iter().position(|stored| stored.key.id == message.key.id) {
+            messages.remove(index);
+        }
+        // History sync mengirim pesan lama setelah pesan baru, jadi sisipkan sesuai timestamp
+        let index = messages.partition_point(|stored| timestamp(stored) <= timestamp(message));
+        messages.insert(index, message.clone());
+        if messages.len() > self.capacity {
+            messages.pop_front();
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &MessageKey) -> Result<Option<WebMessageInfo>> {
+        let chats = self.chats.lock().unwrap();
+        Ok(chats.get(&key.remote_jid).and_then(|messages| messages.iter().find(|message| message.key.id == key.id).cloned()))
+    }
+
+    fn range(&self, chat: &str, from: u64, to: u64) -> Result<Vec<WebMessageInfo>> {
+        let chats = self.chats.lock().unwrap();
+        Ok(chats
+            .get(chat)
+            .map(|messages| messages.iter().filter(|message| (from..to).contains(&timestamp(message))).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn last(&self, chat: &str, limit: usize) -> Result<Vec<WebMessageInfo>> {
+        let chats = self.chats.lock().unwrap();
+        Ok(chats
+            .get(chat)
+            .map(|messages| messages.iter().skip(messages.len().saturating_sub(limit)).cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::SqliteMessageStore;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    use rusqlite::{params, Connection, OptionalExtension};
+
+    use super::{timestamp, MessageStore};
+    use crate::errors::*;
+    use crate::messages::{MessageKey, WebMessageInfo};
+
+    const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS messages (
+            chat TEXT NOT NULL,
+            id TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            data TEXT NOT NULL,
+            PRIMARY KEY (chat, id)
+        );
+        CREATE INDEX IF NOT EXISTS messages_chat_timestamp ON messages (chat, timestamp);";
+
+    fn sqlite_error(e: rusqlite::Error) -> Error {
+        Error { kind: ErrorKind::IOError(format!("SQLite: {}", e)) }
+    }
+
+    /// Riwayat pesan di SQLite; pesan disimpan sebagai JSON
+    pub struct SqliteMessageStore {
+        conn: Mutex<Connection>,
+    }
+
+    impl SqliteMessageStore {
+        /// Membuka (atau membuat) database di `path`
+        pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+            SqliteMessageStore::init(Connection::open(path).map_err(sqlite_error)?)
+        }
+
+        /// Database di memori, untuk pengujian
+        pub fn open_in_memory() -> Result<Self> {
+            SqliteMessageStore::init(Connection::open_in_memory().map_err(sqlite_error)?)
+        }
+
+        fn init(conn: Connection) -> Result<Self> {
+            conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+            Ok(SqliteMessageStore { conn: Mutex::new(conn) })
+        }
+
+        fn query(&self, sql: &str, params: &[&dyn rusqlite::ToSql]) -> Result<Vec<WebMessageInfo>> {
+            let conn = self.conn.lock().unwrap();
+            let mut statement = conn.prepare_cached(sql).map_err(sqlite_error)?;
+            let rows = statement.query_map(params, |row| row.get::<_, String>(0)).map_err(sqlite_error)?;
+            rows.map(|data| Ok(serde_json::from_str(&data.map_err(sqlite_error)?)?)).collect()
+        }
+    }
+
+    impl MessageStore for SqliteMessageStore {
+        fn insert(&self, message: &WebMessageInfo) -> Result<()> {
+            let data = serde_json::to_string(message)?;
+            self.conn
+                .lock()
+                .unwrap()
+                .execute(
+                    "INSERT OR REPLACE INTO messages (chat, id, timestamp, data) VALUES (?1, ?2, ?3, ?4)",
+                    params![message.key.remote_jid, message.key.id, timestamp(message) as i64, data],
+                )
+                .map_err(sqlite_error)?;
+            Ok(())
+        }
+
+        fn get(&self, key: &MessageKey) -> Result<Option<WebMessageInfo>> {
+            let data: Option<String> = self
+                .conn
+                .lock()
+                .unwrap()
+                .query_row("SELECT data FROM messages WHERE chat = ?1 AND id = ?2", params![key.remote_jid, key.id], |row| row.get(0))
+                .optional()
+                .map_err(sqlite_error)?;
+            match data {
+                Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+                None => Ok(None),
+            }
+        }
+
+        fn range(&self, chat: &str, from: u64, to: u64) -> Result<Vec<WebMessageInfo>> {
+            self.query(
+                "SELECT data FROM messages WHERE chat = ?1 AND timestamp >= ?2 AND timestamp < ?3 ORDER BY timestamp, rowid",
+                params![chat, from as i64, to.min(i64::MAX as u64) as i64],
+            )
+        }
+
+        fn last(&self, chat: &str, limit: usize) -> Result<Vec<WebMessageInfo>> {
+            let mut messages = self.query(
+                "SELECT data FROM messages WHERE chat = ?1 ORDER BY timestamp DESC, rowid DESC LIMIT ?2",
+                params![chat, limit as i64],
+            )?;
+            messages.reverse();
+            Ok(messages)
+        }
+    }
+}
+
+/// Handler `message` yang menyimpan pesan masuk ke store
+pub fn store_handler(store: SharedMessageStore) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        match node.get_bytes().and_then(|bytes| serde_json::from_slice::<WebMessageInfo>(bytes).ok()) {
+            Some(web_message) => store.read().unwrap().insert(&web_message),
+            None => Ok(()),
+        }
+    }
+}
+
+impl WhatsAppClient {
+    /// Store riwayat pesan yang sedang dipakai client
+    pub fn message_store(&self) -> Arc<dyn MessageStore> {
+        Arc::clone(&self.message_store.read().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, timestamp: u64) -> WebMessageInfo {
+        WebMessageInfo {
+            key: MessageKey { remote_jid: "628111@s.whatsapp.net".to_string(), from_me: false, id: id.to_string(), participant: None },
+            message_timestamp: Some(timestamp),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_memory_store_ordering_and_eviction() {
+        let store = MemoryMessageStore::with_capacity(3);
+        for (id, timestamp) in [("C", 30), ("D", 40), ("A", 10), ("B", 20)] {
+            store.insert(&message(id, timestamp)).unwrap();
+        }
+        // "A" paling lama sehingga dibuang saat kapasitas penuh
+        assert!(store.get(&message("A", 10).key).unwrap().is_none());
+        let ids = |messages: Vec<WebMessageInfo>| messages.into_iter().map(|message| message.key.id).collect::<Vec<_>>();
+        assert_eq!(ids(store.last("628111@s.whatsapp.net", 2).unwrap()), ["C", "D"]);
+        assert_eq!(ids(store.range("628111@s.whatsapp.net", 20, 40).unwrap()), ["B", "C"]);
+
+        store.insert(&message("B", 20)).unwrap();
+        assert_eq!(store.last("628111@s.whatsapp.net", 10).unwrap().len(), 3);
+        assert!(store.last("120363@g.us", 10).unwrap().is_empty());
+    }
+}
@@ -1,8 +1,9 @@
 use crate::errors::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Representasi struktur WebMessageInfo (protobuf root)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WebMessageInfo {
     pub key: MessageKey,
     pub message: Option<Message>,
@@ -33,8 +34,62 @@ pub struct WebMessageInfo {
     pub verified_biz_name: Option<String>,
 }
 
+impl WebMessageInfo {
+    /// Mendekode protobuf `WebMessageInfo` (lihat `crate::proto`)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        crate::proto::WebMessageInfo::from_bytes(bytes).map(Self::from)
+    }
+
+    /// Bentuk protobuf `WebMessageInfo` untuk dikirim
+    pub fn to_bytes(&self) -> Vec<u8> {
+        crate::proto::WebMessageInfo::from(self).to_bytes()
+    }
+
+    /// ContextInfo dari isi pesan (teks, media, stiker)
+    pub fn context_info(&self) -> Option<&MessageContextInfo> {
+        let message = self.message.as_ref()?;
+        message
+            .extended_text_message
+            .as_ref()
+            .and_then(|m| m.context_info.as_ref())
+            .or_else(|| message.image_message.as_ref().and_then(|m| m.context_info.as_ref()))
+            .or_else(|| message.video_message.as_ref().and_then(|m| m.context_info.as_ref()))
+            .or_else(|| message.document_message.as_ref().and_then(|m| m.context_info.as_ref()))
+            .or_else(|| message.audio_message.as_ref().and_then(|m| m.context_info.as_ref()))
+            .or_else(|| message.sticker_message.as_ref().and_then(|m| m.context_info.as_ref()))
+    }
+
+    /// Cek apakah pesan berisi media view once
+    pub fn is_view_once(&self) -> bool {
+        self.message.as_ref().map_or(false, |message| {
+            message.image_message.as_ref().and_then(|m| m.view_once).unwrap_or(false)
+                || message.video_message.as_ref().and_then(|m| m.view_once).unwrap_or(false)
+        })
+    }
+
+    /// JID yang di-mention (`@`) dalam pesan
+    pub fn mentions(&self) -> Vec<crate::Jid> {
+        self.context_info()
+            .map(|context| {
+                context
+                    .mentioned_jid
+                    .iter()
+                    .filter_map(|jid| crate::Jid::from_string(jid).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Cek apakah `jid` di-mention dalam pesan
+    pub fn is_mentioned(&self, jid: &crate::Jid) -> bool {
+        let jid = jid.to_string();
+        self.context_info()
+            .map_or(false, |context| context.mentioned_jid.iter().any(|mentioned| *mentioned == jid))
+    }
+}
+
 /// Kunci pesan
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MessageKey {
     pub remote_jid: String,
     pub from_me: bool,
@@ -43,7 +98,7 @@ pub struct MessageKey {
 }
 
 /// Struktur pesan utama
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Message {
     pub conversation: Option<String>,
     pub image_message: Option<ImageMessage>,
@@ -58,6 +113,7 @@ pub struct Message {
     pub protocol_message: Option<ProtocolMessage>,
     pub contacts_array_message: Option<ContactsArrayMessage>,
     pub highly_structured_message: Option<HighlyStructuredMessage>,
+    pub sender_key_distribution_message: Option<SenderKeyDistributionMessage>,
     pub fast_ratchet_key_sender_key_distribution_message: Option<SenderKeyDistributionMessage>,
     pub send_payment_message: Option<SendPaymentMessage>,
     pub live_location_message: Option<LiveLocationMessage>,
@@ -83,9 +139,22 @@ pub struct Message {
     pub poll_creation_message: Option<PollCreationMessage>,
     pub poll_update_message: Option<PollUpdateMessage>,
     pub keep_in_chat_message: Option<KeepInChatMessage>,
+    pub order_message: Option<OrderMessage>,
+}
+
+impl Message {
+    /// Mendekode protobuf `Message`; view once dan pesan sementara dibuka
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        crate::proto::Message::from_bytes(bytes).map(Self::from)
+    }
+
+    /// Bentuk protobuf `Message` untuk dikirim
+    pub fn to_bytes(&self) -> Vec<u8> {
+        crate::proto::Message::from(self).to_bytes()
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ImageMessage {
     pub url: String,
     pub mimetype: Option<String>,
@@ -104,14 +173,14 @@ pub struct ImageMessage {
     pub view_once: Option<bool>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ContactMessage {
     pub display_name: String,
     pub vcard: String,
     pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LocationMessage {
     pub degrees_latitude: f64,
     pub degrees_longitude: f64,
@@ -127,7 +196,7 @@ pub struct LocationMessage {
     pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExtendedTextMessage {
     pub text: String,
     pub matched_text: Option<String>,
@@ -143,7 +212,7 @@ pub struct ExtendedTextMessage {
     pub do_not_play_inline: Option<bool>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DocumentMessage {
     pub url: String,
     pub mimetype: String,
@@ -163,7 +232,7 @@ pub struct DocumentMessage {
     pub thumbnail_enc_sha256: Option<Vec<u8>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AudioMessage {
     pub url: String,
     pub mimetype: String,
@@ -177,9 +246,11 @@ pub struct AudioMessage {
     pub media_key_timestamp: i64,
     pub context_info: Option<MessageContextInfo>,
     pub streaming_sidecar: Option<Vec<u8>>,
+    /// Nilai 0..=100 untuk tampilan gelombang voice note
+    pub waveform: Option<Vec<u8>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct VideoMessage {
     pub url: String,
     pub mimetype: String,
@@ -204,18 +275,18 @@ pub struct VideoMessage {
     pub thumbnail_enc_sha256: Option<Vec<u8>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Call {
     pub call_key: Vec<u8>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Chat {
     pub display_name: String,
     pub id: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProtocolMessage {
     pub key: MessageKey,
     pub r#type: Option<u32>,
@@ -226,9 +297,10 @@ pub struct ProtocolMessage {
     pub app_state_sync_key_request: Option<AppStateSyncKeyRequest>,
     pub initial_security_notification_setting_sync: Option<InitialSecurityNotificationSettingSync>,
     pub app_state_fatal_exception_notification: Option<AppStateFatalExceptionNotification>,
+    pub peer_data_operation_request_message: Option<PeerDataOperationRequestMessage>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HistorySyncNotification {
     pub file_sha256: Option<Vec<u8>>,
     pub file_length: Option<u64>,
@@ -238,33 +310,46 @@ pub struct HistorySyncNotification {
     pub sync_type: Option<u32>,
     pub chunk_order: Option<u32>,
     pub original_message_id: Option<String>,
+    /// Id permintaan history on-demand yang dijawab notifikasi ini
+    pub peer_data_request_session_id: Option<String>,
+    /// Payload history sync awal yang disertakan langsung, tanpa unduhan
+    pub initial_hist_bootstrap_inline_payload: Option<Vec<u8>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ContactsArrayMessage {
     pub display_name: String,
     pub contacts: Vec<ContactMessage>,
     pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SenderKeyDistributionMessage {
     pub group_id: String,
     pub axolotl_sender_key_distribution_message: Vec<u8>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MessageContextInfo {
     pub device_list_metadata: Option<DeviceListMetadata>,
+    /// Id pesan yang dikutip (reply)
+    pub stanza_id: Option<String>,
+    pub quoted_message: Option<Box<Message>>,
+    /// Chat asal pesan yang dikutip, hanya jika berbeda dari chat tujuan
+    pub remote_jid: Option<String>,
     pub mentioned_jid: Vec<String>,
     pub is_forwarded: Option<bool>,
     pub forwarded_source_from: Option<String>,
     pub participant: Option<String>,
     pub orphaned_device_sent_message_number: Option<u32>,
     pub orphaned_device_sent_message_epoch: Option<u32>,
+    /// Secret acak per pesan, dipakai untuk mengenkripsi vote polling
+    pub message_secret: Option<Vec<u8>>,
+    /// Masa berlaku pesan sementara dalam detik
+    pub expiration: Option<u32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DeviceListMetadata {
     pub sender_key_hash: Option<Vec<u8>>,
     pub sender_timestamp: Option<u64>,
@@ -274,7 +359,7 @@ pub struct DeviceListMetadata {
     pub recipient_epoch: Option<u32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PaymentInfo {
     pub currency: String,
     pub amount_1000: u64,
@@ -287,7 +372,7 @@ pub struct PaymentInfo {
     pub currency_code_iso4217: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LiveLocationMessage {
     pub degrees_latitude: f64,
     pub degrees_longitude: f64,
@@ -301,37 +386,37 @@ pub struct LiveLocationMessage {
     pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppStateSyncKeyShare {
     pub keys: Vec<AppStateSyncKey>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppStateSyncKey {
     pub key_id: Option<AppStateSyncKeyId>,
     pub key_data: Option<AppStateSyncKeyData>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppStateSyncKeyId {
     pub key_id: Vec<u8>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppStateSyncKeyData {
     pub key_data: Vec<u8>,
     pub fingerprint: Option<AppStateSyncKeyFingerprint>,
     pub timestamp: Option<i64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppStateSyncKeyFingerprint {
     pub raw_id: u32,
     pub current_index: u32,
     pub device_indexes: Vec<u32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GroupInviteMessage {
     pub group_jid: String,
     pub invite_code: String,
@@ -342,14 +427,14 @@ pub struct GroupInviteMessage {
     pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TemplateMessage {
     pub context_info: Option<MessageContextInfo>,
     pub hydrated_template: Option<HydratedFourRowTemplate>,
     pub template_id: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HydratedFourRowTemplate {
     pub hydrated_content_text: Option<String>,
     pub hydrated_footer_text: Option<String>,
@@ -360,7 +445,7 @@ pub struct HydratedFourRowTemplate {
     pub hydrated_image_caption: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HydratedTemplateButton {
     pub index: u32,
     pub quick_reply_button: Option<HydratedQuickReplyButton>,
@@ -369,31 +454,31 @@ pub struct HydratedTemplateButton {
     pub currency_button: Option<HydratedCurrencyButton>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HydratedQuickReplyButton {
     pub display_text: String,
     pub id: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HydratedURLButton {
     pub display_text: String,
     pub url: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HydratedCallButton {
     pub display_text: String,
     pub phone_number: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HydratedCurrencyButton {
     pub display_text: String,
     pub currency: HydratedCurrency,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HydratedCurrency {
     pub currency_code: String,
     pub amount_1000: i64,
@@ -401,7 +486,7 @@ pub struct HydratedCurrency {
     pub total_amount: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ListMessage {
     pub title: String,
     pub description: String,
@@ -413,20 +498,20 @@ pub struct ListMessage {
     pub carousel_selection_header: Option<CarouselMessageHeader>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ListSection {
     pub title: String,
     pub rows: Vec<ListRow>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ListRow {
     pub title: String,
     pub description: String,
     pub row_id: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CarouselMessageHeader {
     pub image_message: Option<ImageMessage>,
     pub video_message: Option<VideoMessage>,
@@ -434,8 +519,10 @@ pub struct CarouselMessageHeader {
     pub location_message: Option<LocationMessage>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ButtonsMessage {
+    /// Header teks (`header_type` 2)
+    pub text: Option<String>,
     pub content_text: String,
     pub footer_text: Option<String>,
     pub context_info: Option<MessageContextInfo>,
@@ -448,14 +535,14 @@ pub struct ButtonsMessage {
     pub contact_message: Option<ContactMessage>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Button {
     pub button_id: String,
     pub button_text: String,
     pub r#type: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ListResponseMessage {
     pub title: String,
     pub list_type: u32,
@@ -464,12 +551,21 @@ pub struct ListResponseMessage {
     pub description: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// Jawaban atas `ButtonsMessage`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ButtonsResponseMessage {
+    pub selected_button_id: String,
+    pub selected_display_text: Option<String>,
+    pub context_info: Option<MessageContextInfo>,
+    pub r#type: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SingleSelectReply {
     pub selected_row_id: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StickerMessage {
     pub url: String,
     pub file_sha256: Vec<u8>,
@@ -488,7 +584,7 @@ pub struct StickerMessage {
     pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ReactionMessage {
     pub key: MessageKey,
     pub text: String,
@@ -497,7 +593,7 @@ pub struct ReactionMessage {
     pub sender_timestamp_ms: i64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StickerSyncRMRMessage {
     pub rmr_reason: u32,
     pub requesting_phone_number: String,
@@ -505,7 +601,7 @@ pub struct StickerSyncRMRMessage {
     pub total_requested: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PollCreationMessage {
     pub name: String,
     pub selectable_count: u32,
@@ -513,39 +609,41 @@ pub struct PollCreationMessage {
     pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PollOption {
     pub option_name: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PollUpdateMessage {
+    /// Pesan polling yang divote
+    pub poll_creation_message_key: Option<MessageKey>,
     pub poll_update: PollUpdate,
-    pub message: Option<Message>,
+    pub message: Option<Box<Message>>,
     pub sender_timestamp_ms: i64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PollUpdate {
     pub vote: PollEncValue,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PollEncValue {
     pub enc_iv: Vec<u8>,
     pub enc_payload: Vec<u8>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct KeepInChatMessage {
     pub key: MessageKey,
     pub action: u32,
     pub timestamp_ms: i64,
-    pub message: Option<Message>,
+    pub message: Option<Box<Message>>,
     pub sender_timestamp_ms: i64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InteractiveMessage {
     pub header: Option<InteractiveMessageHeader>,
     pub body: Option<InteractiveMessageBody>,
@@ -554,7 +652,7 @@ pub struct InteractiveMessage {
     pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InteractiveMessageHeader {
     pub title: String,
     pub subtitle: Option<String>,
@@ -564,40 +662,43 @@ pub struct InteractiveMessageHeader {
     pub document_message: Option<DocumentMessage>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InteractiveMessageBody {
     pub text: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InteractiveMessageFooter {
     pub text: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NativeFlowMessage {
     pub buttons: Vec<NativeFlowButton>,
     pub message_params_json: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NativeFlowButton {
     pub name: String,
     pub button_params_json: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InteractiveResponseMessage {
+    pub body: Option<InteractiveMessageBody>,
     pub native_flow_response_message: Option<NativeFlowResponseMessage>,
+    /// `stanza_id` menunjuk pesan interaktif yang dijawab
+    pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NativeFlowResponseMessage {
     pub name: String,
     pub params_json: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HighlyStructuredMessage {
     pub namespace: String,
     pub element_name: String,
@@ -610,20 +711,20 @@ pub struct HighlyStructuredMessage {
     pub hydrated_hsm: Option<TemplateMessage>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HSMLocalizableParameter {
     pub default: String,
     pub currency: Option<HSMCurrency>,
     pub date_time_component: Option<HSMDateTimeComponent>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HSMCurrency {
     pub currency_code: String,
     pub amount_1000: i64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HSMDateTimeComponent {
     pub day_of_week: u32,
     pub year: u32,
@@ -634,15 +735,15 @@ pub struct HSMDateTimeComponent {
     pub calendar: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SendPaymentMessage {
-    pub note_message: Option<Message>,
+    pub note_message: Option<Box<Message>>,
     pub request_message_key: Option<MessageKey>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RequestPaymentMessage {
-    pub note_message: Option<Message>,
+    pub note_message: Option<Box<Message>>,
     pub currency_code_iso4217: String,
     pub amount_1000: u64,
     pub request_from: String,
@@ -653,24 +754,24 @@ pub struct RequestPaymentMessage {
     pub text_attribution: Option<u32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DeclinePaymentRequestMessage {
     pub key: MessageKey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CancelPaymentRequestMessage {
     pub key: MessageKey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PaymentMoney {
     pub value: i64,
     pub offset: u32,
     pub currency_code: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProductMessage {
     pub product_snapshot: ProductSnapshot,
     pub business_owner_jid: String,
@@ -678,7 +779,27 @@ pub struct ProductMessage {
     pub context_info: Option<MessageContextInfo>,
 }
 
-#[derive(Debug, Clone)]
+/// Pesanan (checkout keranjang) ke akun bisnis, atau update statusnya
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderMessage {
+    pub order_id: String,
+    pub thumbnail: Option<Vec<u8>>,
+    pub item_count: u32,
+    /// 1 = inquiry, 2 = accepted, 3 = declined
+    pub status: u32,
+    /// 1 = katalog
+    pub surface: u32,
+    pub message: Option<String>,
+    pub order_title: Option<String>,
+    pub seller_jid: String,
+    /// Token untuk mengambil detail pesanan
+    pub token: String,
+    pub total_amount_1000: Option<i64>,
+    pub total_currency_code: Option<String>,
+    pub context_info: Option<MessageContextInfo>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProductSnapshot {
     pub product_image: ImageMessage,
     pub product_title: String,
@@ -696,22 +817,22 @@ pub struct ProductSnapshot {
     pub secondary_sub_title: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DeviceSentMessage {
     pub destination_jid: Option<String>,
-    pub message: Option<Message>,
+    pub message: Option<Box<Message>>,
     pub phash: Option<String>,
     pub broadcast_ephemeral_settings: Option<BroadcastEphemeralSettings>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BroadcastEphemeralSettings {
     pub chat_jid: String,
     pub ephemeral_expiration: Option<u32>,
     pub ephemeral_setting_timestamp: Option<i64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TemplateButtonReplyMessage {
     pub selected_id: String,
     pub selected_display_text: String,
@@ -719,12 +840,12 @@ pub struct TemplateButtonReplyMessage {
     pub selected_index: Option<u32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PaymentInvitationMessage {
     pub currency: String,
     pub amount_1000: u64,
     pub receiver_jid: String,
-    pub note_message: Option<Message>,
+    pub note_message: Option<Box<Message>>,
     pub expiry_timestamp: i64,
     pub amount: Option<PaymentMoney>,
     pub payment_terms: Option<String>,
@@ -732,29 +853,46 @@ pub struct PaymentInvitationMessage {
     pub currency_code: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InitialSecurityNotificationSettingSync {
     pub security_notification_enabled: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppStateFatalExceptionNotification {
     pub collection_names: Vec<String>,
     pub timestamp: Option<i64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppStateSyncKeyRequest {
     pub key_ids: Vec<AppStateSyncKeyId>,
 }
 
-#[derive(Debug, Clone)]
+/// Permintaan data ke perangkat utama (ponsel)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerDataOperationRequestMessage {
+    pub peer_data_operation_request_type: Option<u32>,
+    pub history_sync_on_demand_request: Option<HistorySyncOnDemandRequest>,
+}
+
+/// Permintaan pesan yang lebih lama dari `oldest_msg_id`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistorySyncOnDemandRequest {
+    pub chat_jid: String,
+    pub oldest_msg_id: String,
+    pub oldest_msg_from_me: bool,
+    pub on_demand_msg_count: u32,
+    pub oldest_msg_timestamp_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DeviceListMetadataCollection {
     pub r#type: u32,
     pub user_devices: Vec<DeviceInfo>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DeviceInfo {
     pub jid: String,
     pub device_id: Vec<u32>,
@@ -768,7 +906,7 @@ pub struct DeviceInfo {
     pub is_planned_account_migration_device: Option<bool>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NullDevice {
     pub jid: String,
     pub device_id: Vec<u32>,
@@ -0,0 +1,106 @@
+//! Middleware event
+//!
+//! Middleware dijalankan berurutan untuk setiap event sebelum event masuk ke
+//! jurnal, antrean `poll_event`, pelanggan `events`/`subscribe` dan
+//! `EventHandler`. Middleware boleh mengubah event atau membuangnya
+//! (`None`); middleware berikutnya tidak dipanggil untuk event yang dibuang.
+//!
+//! Middleware bawaan: `ignore_status_broadcast` dan `dedupe_messages`.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::messages::WebMessageInfo;
+use crate::{Event, WhatsAppClient};
+
+/// JID status (story) kontak
+pub const STATUS_BROADCAST_JID: &str = "status@broadcast";
+
+/// Fungsi middleware event
+pub type EventMiddleware = Arc<dyn Fn(Event) -> Option<Event> + Send + Sync>;
+
+/// Pesan masuk yang dibawa event, jika ada
+fn incoming_message(event: &Event) -> Option<&WebMessageInfo> {
+    match event {
+        Event::MessageReceived(message)
+        | Event::ViewOnceReceived(message)
+        | Event::BroadcastMessageReceived(message)
+        | Event::CommunityAnnouncement { message, .. }
+        | Event::OrderReceived { message, .. }
+        | Event::InteractiveResponse { message, .. } => Some(message),
+        Event::DurableMessage(durable) => Some(&durable.message),
+        _ => None,
+    }
+}
+
+/// Menjalankan rantai middleware
+pub(crate) fn apply(middleware: &[EventMiddleware], event: Event) -> Option<Event> {
+    middleware.iter().try_fold(event, |event, step| step(event))
+}
+
+/// Membuang pesan status (story) dari `status@broadcast`
+pub fn ignore_status_broadcast() -> impl Fn(Event) -> Option<Event> + Send + Sync + 'static {
+    |event: Event| match incoming_message(&event) {
+        Some(message) if message.key.remote_jid == STATUS_BROADCAST_JID => None,
+        _ => Some(event),
+    }
+}
+
+/// Membuang pesan masuk yang id-nya sudah terlihat di antara `capacity` pesan terakhir
+/// (mis. dikirim ulang server setelah reconnect)
+pub fn dedupe_messages(capacity: usize) -> impl Fn(Event) -> Option<Event> + Send + Sync + 'static {
+    let seen: Mutex<(HashSet<(String, String)>, VecDeque<(String, String)>)> = Mutex::new(Default::default());
+    move |event: Event| {
+        let key = match incoming_message(&event) {
+            Some(message) => (message.key.remote_jid.clone(), message.key.id.clone()),
+            None => return Some(event),
+        };
+        let mut seen = seen.lock().unwrap();
+        let (ref mut ids, ref mut order) = *seen;
+        if !ids.insert(key.clone()) {
+            return None;
+        }
+        order.push_back(key);
+        if order.len() > capacity {
+            if let Some(oldest) = order.pop_front() {
+                ids.remove(&oldest);
+            }
+        }
+        Some(event)
+    }
+}
+
+impl WhatsAppClient {
+    /// Menambahkan middleware di akhir rantai; berlaku untuk event berikutnya
+    pub fn add_event_middleware(&self, middleware: impl Fn(Event) -> Option<Event> + Send + Sync + 'static) {
+        self.event_tx.middleware.write().unwrap().push(Arc::new(middleware));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::MessageKey;
+
+    fn message(chat: &str, id: &str) -> Event {
+        Event::MessageReceived(WebMessageInfo {
+            key: MessageKey { remote_jid: chat.to_string(), from_me: false, id: id.to_string(), participant: None },
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_chain_filters_status_and_duplicates() {
+        let chain: Vec<EventMiddleware> = vec![Arc::new(ignore_status_broadcast()), Arc::new(dedupe_messages(2))];
+        let chat = "628111@s.whatsapp.net";
+
+        assert!(apply(&chain, message(STATUS_BROADCAST_JID, "s1")).is_none());
+        assert!(apply(&chain, message(chat, "m1")).is_some());
+        assert!(apply(&chain, message(chat, "m1")).is_none());
+        assert!(apply(&chain, Event::Connected).is_some());
+        // m1 keluar dari jendela setelah dua pesan lain
+        assert!(apply(&chain, message(chat, "m2")).is_some());
+        assert!(apply(&chain, message(chat, "m3")).is_some());
+        assert!(apply(&chain, message(chat, "m1")).is_some());
+    }
+}
@@ -0,0 +1,180 @@
+//! Penerjemah JID ke nama untuk log, laporan, dan ekspor
+//!
+//! `NameResolver` dipakai di mana pun crate menampilkan JID ke manusia sehingga
+//! keluarannya konsisten ("Alice (+49…)"). Implementasi default membaca
+//! `ContactStore` (push name dari pesan masuk) dan `GroupStore` (subject grup).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{Jid, WhatsAppClient};
+
+/// Resolver yang bisa diganti saat client berjalan
+pub type SharedNameResolver = Arc<Mutex<Arc<dyn NameResolver>>>;
+
+/// Menerjemahkan JID menjadi nama yang bisa dibaca
+pub trait NameResolver: Send + Sync {
+    /// Nama untuk JID, `None` jika tidak diketahui
+    fn resolve(&self, jid: &Jid) -> Option<String>;
+
+    /// Format tampilan: "Nama (+nomor)" untuk kontak, "Subject (id@g.us)" untuk grup
+    fn display(&self, jid: &Jid) -> String {
+        let id = if jid.is_group() || jid.is_lid() { jid.to_string() } else { format!("+{}", jid.id) };
+        match self.resolve(jid) {
+            Some(name) => format!("{} ({})", name, id),
+            None => id,
+        }
+    }
+}
+
+/// Format tampilan untuk JID dalam bentuk string (mis. dari `MessageKey`)
+pub fn display_str(resolver: &dyn NameResolver, jid: &str) -> String {
+    match Jid::from_string(jid) {
+        Ok(jid) => resolver.display(&jid),
+        Err(_) => jid.to_string(),
+    }
+}
+
+/// Nama kontak yang diketahui client
+#[derive(Default)]
+pub struct ContactStore {
+    names: HashMap<String, String>,
+}
+
+impl ContactStore {
+    pub fn new() -> Self {
+        ContactStore::default()
+    }
+
+    pub fn set_name(&mut self, jid: &str, name: &str) {
+        if !name.is_empty() {
+            self.names.insert(jid.to_string(), name.to_string());
+        }
+    }
+
+    pub fn name(&self, jid: &str) -> Option<&str> {
+        self.names.get(jid).map(|name| name.as_str())
+    }
+}
+
+/// Subject grup yang diketahui client
+#[derive(Default)]
+pub struct GroupStore {
+    subjects: HashMap<String, String>,
+}
+
+impl GroupStore {
+    pub fn new() -> Self {
+        GroupStore::default()
+    }
+
+    pub fn set_subject(&mut self, group: &str, subject: &str) {
+        self.subjects.insert(group.to_string(), subject.to_string());
+    }
+
+    pub fn subject(&self, group: &str) -> Option<&str> {
+        self.subjects.get(group).map(|subject| subject.as_str())
+    }
+}
+
+/// Resolver default berbasis `ContactStore` dan `GroupStore`
+pub struct StoreNameResolver {
+    contacts: Arc<Mutex<ContactStore>>,
+    groups: Arc<Mutex<GroupStore>>,
+}
+
+impl StoreNameResolver {
+    pub fn new(contacts: Arc<Mutex<ContactStore>>, groups: Arc<Mutex<GroupStore>>) -> Self {
+        StoreNameResolver { contacts, groups }
+    }
+}
+
+impl NameResolver for StoreNameResolver {
+    fn resolve(&self, jid: &Jid) -> Option<String> {
+        let key = jid.to_string();
+        if jid.is_group() {
+            self.groups.lock().unwrap().subject(&key).map(|subject| subject.to_string())
+        } else {
+            self.contacts.lock().unwrap().name(&key).map(|name| name.to_string())
+        }
+    }
+}
+
+/// Handler `message` yang mencatat push name pengirim
+pub fn push_name_handler(contacts: Arc<Mutex<ContactStore>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        let web_message = match node.get_bytes().and_then(|bytes| crate::messages::WebMessageInfo::from_bytes(bytes).ok()) {
+            Some(web_message) => web_message,
+            None => return Ok(()),
+        };
+        if web_message.key.from_me {
+            return Ok(());
+        }
+        if let Some(ref push_name) = web_message.push_name {
+            let sender = web_message.key.participant.as_ref().unwrap_or(&web_message.key.remote_jid);
+            contacts.lock().unwrap().set_name(sender, push_name);
+        }
+        Ok(())
+    }
+}
+
+/// Handler yang mencatat subject grup dari metadata (`<group subject=...>`)
+/// dan notifikasi perubahan subject (`<subject subject=...>`)
+pub fn group_subject_handler(groups: Arc<Mutex<GroupStore>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if let Some(group) = node.get_child("group") {
+            if let (Some(id), Some(subject)) = (group.get_attr("id"), group.get_attr("subject")) {
+                let jid = if id.contains('@') { id.to_string() } else { format!("{}@g.us", id) };
+                groups.lock().unwrap().set_subject(&jid, subject);
+            }
+        }
+        if let (Some(from), Some(subject)) = (node.get_attr("from"), node.get_child("subject").and_then(|s| s.get_attr("subject"))) {
+            groups.lock().unwrap().set_subject(from, subject);
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Mengganti resolver nama yang dipakai untuk log dan laporan
+    pub fn set_name_resolver(&self, resolver: Arc<dyn NameResolver>) {
+        *self.names.lock().unwrap() = resolver;
+    }
+
+    /// Push name kontak yang terakhir terlihat
+    pub fn contact_name(&self, jid: &Jid) -> Option<String> {
+        self.contacts.lock().unwrap().name(&jid.to_string()).map(|name| name.to_string())
+    }
+
+    /// Subject grup yang terakhir terlihat
+    pub fn group_subject(&self, group: &Jid) -> Option<String> {
+        self.groups.lock().unwrap().subject(&group.to_string()).map(|subject| subject.to_string())
+    }
+
+    /// Nama tampilan untuk JID, mis. "Alice (+49…)"
+    pub fn display_name(&self, jid: &Jid) -> String {
+        let resolver = Arc::clone(&self.names.lock().unwrap());
+        resolver.display(jid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_formats() {
+        let contacts = Arc::new(Mutex::new(ContactStore::new()));
+        let groups = Arc::new(Mutex::new(GroupStore::new()));
+        contacts.lock().unwrap().set_name("49151@s.whatsapp.net", "Alice");
+        groups.lock().unwrap().set_subject("123-456@g.us", "Tim");
+        let resolver = StoreNameResolver::new(contacts, groups);
+
+        assert_eq!(resolver.display(&Jid::user("49151")), "Alice (+49151)");
+        assert_eq!(resolver.display(&Jid::user("49152")), "+49152");
+        assert_eq!(resolver.display(&Jid::group("123-456")), "Tim (123-456@g.us)");
+    }
+}
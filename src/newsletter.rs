@@ -0,0 +1,248 @@
+//! Newsletter (WhatsApp Channels)
+//!
+//! Channel memakai JID `@newsletter`. Pesan channel tidak dienkripsi Signal:
+//! isinya protobuf `Message` di dalam `<plaintext>`, dan setiap pesan punya
+//! `server_id` berurutan selain id stanza. Metadata, follow dan unfollow
+//! berjalan lewat query GraphQL (`w:mex`) dengan variabel JSON.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::{json, Value};
+
+use crate::errors::*;
+use crate::iq;
+use crate::messages::Message;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{utils, Event, Jid, WhatsAppClient};
+
+/// Id query GraphQL yang dipakai aplikasi resmi
+const QUERY_NEWSLETTER_METADATA: &str = "6563316087068696";
+const QUERY_FOLLOW_NEWSLETTER: &str = "7871414976211147";
+const QUERY_UNFOLLOW_NEWSLETTER: &str = "7238632346214362";
+
+/// Peran akun di sebuah channel
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NewsletterRole {
+    Owner,
+    Admin,
+    Subscriber,
+    Guest,
+}
+
+impl NewsletterRole {
+    fn parse(value: &str) -> NewsletterRole {
+        match value {
+            "OWNER" => NewsletterRole::Owner,
+            "ADMIN" => NewsletterRole::Admin,
+            "SUBSCRIBER" => NewsletterRole::Subscriber,
+            _ => NewsletterRole::Guest,
+        }
+    }
+
+    /// Owner dan admin boleh mengirim post
+    pub fn can_post(&self) -> bool {
+        matches!(self, NewsletterRole::Owner | NewsletterRole::Admin)
+    }
+}
+
+/// Metadata channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewsletterMetadata {
+    pub jid: Jid,
+    pub name: String,
+    pub description: String,
+    /// Kode undangan (`https://whatsapp.com/channel/<kode>`)
+    pub invite_code: Option<String>,
+    pub subscribers: u64,
+    pub verified: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub role: NewsletterRole,
+}
+
+/// Pesan yang diterima dari channel
+#[derive(Debug, Clone)]
+pub struct NewsletterMessage {
+    pub newsletter: Jid,
+    pub id: String,
+    /// Nomor urut pesan di server channel
+    pub server_id: u64,
+    pub timestamp: i64,
+    pub message: Message,
+}
+
+fn mex_query(query_id: &str, variables: Value) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "w:mex")
+        .attr("type", "get")
+        .attr("to", "s.whatsapp.net")
+        .children(vec![Node::new("query")
+            .attr("query_id", query_id)
+            .bytes(json!({ "variables": variables }).to_string().into_bytes())])
+}
+
+/// IQ untuk mengambil metadata channel
+pub fn metadata_node(newsletter: &Jid) -> Node {
+    mex_query(
+        QUERY_NEWSLETTER_METADATA,
+        json!({
+            "input": { "key": newsletter.to_string(), "type": "JID", "view_role": "GUEST" },
+            "fetch_viewer_metadata": true,
+            "fetch_full_image": false,
+            "fetch_creation_time": true,
+        }),
+    )
+}
+
+/// IQ untuk mengikuti (`follow = true`) atau berhenti mengikuti channel
+pub fn follow_node(newsletter: &Jid, follow: bool) -> Node {
+    let query_id = if follow { QUERY_FOLLOW_NEWSLETTER } else { QUERY_UNFOLLOW_NEWSLETTER };
+    mex_query(query_id, json!({ "newsletter_id": newsletter.to_string() }))
+}
+
+/// Isi JSON `data.<field>` dari `<result>` query GraphQL
+fn mex_result(response: &Node, field: &str) -> Result<Value> {
+    let bytes = response.get_child("result").and_then(|result| result.get_bytes()).ok_or("GraphQL response without result")?;
+    let mut body: Value = serde_json::from_slice(bytes).map_err(|e| format!("Invalid GraphQL response: {}", e))?;
+    if let Some(error) = body.get("errors").and_then(|errors| errors.get(0)) {
+        let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+        return Err(format!("GraphQL query failed: {}", message).into());
+    }
+    match body["data"][field].take() {
+        Value::Null => Err(format!("GraphQL response without {}", field).into()),
+        value => Ok(value),
+    }
+}
+
+/// Angka di JSON GraphQL sering dikirim sebagai string
+fn number(value: &Value) -> Option<u64> {
+    value.as_u64().or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// Membaca objek `xwa2_newsletter`
+pub fn parse_metadata(value: &Value) -> Result<NewsletterMetadata> {
+    let jid = value["id"].as_str().ok_or("Newsletter metadata without id")?;
+    let thread = &value["thread_metadata"];
+    let text = |field: &str| thread[field]["text"].as_str().unwrap_or_default().to_string();
+    Ok(NewsletterMetadata {
+        jid: Jid::from_string(jid)?,
+        name: text("name"),
+        description: text("description"),
+        invite_code: thread["invite"].as_str().map(|code| code.to_string()),
+        subscribers: number(&thread["subscribers_count"]).unwrap_or(0),
+        verified: thread["verification"].as_str() == Some("VERIFIED"),
+        created_at: number(&thread["creation_time"]).and_then(|secs| Utc.timestamp_opt(secs as i64, 0).single()),
+        role: NewsletterRole::parse(value["viewer_metadata"]["role"].as_str().unwrap_or_default()),
+    })
+}
+
+/// Membaca `<message from="..@newsletter" server_id><plaintext>..</plaintext></message>`
+pub fn parse_message(node: &Node) -> Result<Option<NewsletterMessage>> {
+    let newsletter = match node.get_attr("from").map(Jid::from_string) {
+        Some(Ok(jid)) if jid.is_newsletter() => jid,
+        _ => return Ok(None),
+    };
+    let plaintext = match node.get_child("plaintext").and_then(|plaintext| plaintext.get_bytes()) {
+        Some(bytes) => bytes,
+        // Update reaksi/view count tanpa isi pesan
+        None => return Ok(None),
+    };
+    Ok(Some(NewsletterMessage {
+        newsletter,
+        id: node.get_attr("id").unwrap_or_default().to_string(),
+        server_id: node.get_attr("server_id").and_then(|id| id.parse().ok()).unwrap_or(0),
+        timestamp: node.get_attr("t").and_then(|t| t.parse().ok()).unwrap_or(0),
+        message: Message::from_bytes(plaintext)?,
+    }))
+}
+
+/// Handler stanza `message` dari channel
+pub fn message_handler() -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if let Some(message) = parse_message(node)? {
+            ctx.emit(Event::NewsletterMessage(message));
+        }
+        Ok(())
+    }
+}
+
+/// Stanza post channel; jenisnya `text` atau `media`
+pub fn post_node(newsletter: &Jid, id: &str, message: &Message) -> Node {
+    let is_text = message.conversation.is_some() || message.extended_text_message.is_some();
+    Node::new("message")
+        .attr("id", id)
+        .attr("to", &newsletter.to_string())
+        .attr("type", if is_text { "text" } else { "media" })
+        .children(vec![Node::new("plaintext").bytes(message.to_bytes())])
+}
+
+impl WhatsAppClient {
+    /// Metadata channel, termasuk peran akun ini di channel tersebut
+    pub fn newsletter_metadata(&self, newsletter: &Jid) -> Result<NewsletterMetadata> {
+        if !newsletter.is_newsletter() {
+            return Err("Not a newsletter JID".into());
+        }
+        let response = self.query(&metadata_node(newsletter), iq::DEFAULT_QUERY_TIMEOUT)?;
+        parse_metadata(&mex_result(&response, "xwa2_newsletter")?)
+    }
+
+    /// Mengikuti channel; pesan barunya diterima sebagai `Event::NewsletterMessage`
+    pub fn follow_newsletter(&self, newsletter: &Jid) -> Result<()> {
+        self.change_follow(newsletter, true)
+    }
+
+    pub fn unfollow_newsletter(&self, newsletter: &Jid) -> Result<()> {
+        self.change_follow(newsletter, false)
+    }
+
+    fn change_follow(&self, newsletter: &Jid, follow: bool) -> Result<()> {
+        if !newsletter.is_newsletter() {
+            return Err("Not a newsletter JID".into());
+        }
+        let response = self.query(&follow_node(newsletter, follow), iq::DEFAULT_QUERY_TIMEOUT)?;
+        mex_result(&response, if follow { "xwa2_newsletter_join_v2" } else { "xwa2_newsletter_leave_v2" })?;
+        Ok(())
+    }
+
+    /// Mengirim post ke channel milik sendiri (owner atau admin). Mengembalikan id pesan.
+    pub fn send_newsletter_post(&self, newsletter: &Jid, message: &Message) -> Result<String> {
+        if !newsletter.is_newsletter() {
+            return Err("Not a newsletter JID".into());
+        }
+        let id = utils::generate_message_id();
+        self.send_node(&post_node(newsletter, &id, message))?;
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_and_plaintext_message() {
+        let response = Node::new("iq").children(vec![Node::new("result").bytes(
+            br#"{"data":{"xwa2_newsletter":{"id":"1203630@newsletter","thread_metadata":{"name":{"text":"Info"},"subscribers_count":"42","verification":"VERIFIED","creation_time":"1700000000"},"viewer_metadata":{"role":"OWNER"}}}}"#.to_vec(),
+        )]);
+        let meta = parse_metadata(&mex_result(&response, "xwa2_newsletter").unwrap()).unwrap();
+        assert_eq!(meta.jid.to_string(), "1203630@newsletter");
+        assert_eq!((meta.name.as_str(), meta.subscribers, meta.verified), ("Info", 42, true));
+        assert!(meta.role.can_post());
+        assert!(mex_result(&response, "xwa2_newsletter_join_v2").is_err());
+
+        let newsletter = meta.jid;
+        let text = Message { conversation: Some("halo".to_string()), ..Default::default() };
+        let post = post_node(&newsletter, "ID1", &text);
+        assert_eq!(post.get_attr("type"), Some("text"));
+        let incoming = Node::new("message")
+            .attr("from", "1203630@newsletter")
+            .attr("id", "ID1")
+            .attr("server_id", "105")
+            .children(vec![post.get_child("plaintext").unwrap().clone()]);
+        let message = parse_message(&incoming).unwrap().unwrap();
+        assert_eq!((message.server_id, message.message.conversation.as_deref()), (105, Some("halo")));
+
+        let private = Node::new("message").attr("from", "628111@s.whatsapp.net");
+        assert!(parse_message(&private).unwrap().is_none());
+    }
+}
@@ -0,0 +1,191 @@
+//! Pembacaan atribut node bertipe
+//!
+//! `Node::attr_required`, `attr_jid`, `attr_u64` dan `attr_bool` mengembalikan
+//! `ErrorKind::InvalidAttributes` yang menyebutkan tag dan nama atribut.
+//! Untuk stanza dengan banyak atribut, `AttrParser` mengumpulkan semua
+//! kesalahan lalu melaporkannya sekaligus lewat `finish`:
+//!
+//! ```ignore
+//! let mut attrs = node.attr_parser();
+//! let from = attrs.jid("from");
+//! let timestamp = attrs.u64("t");
+//! let offline = attrs.optional_bool("offline").unwrap_or(false);
+//! attrs.finish()?;
+//! ```
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::{Jid, Server};
+
+fn missing(node: &Node, attr: &str) -> AttrError {
+    AttrError::Missing { tag: node.tag.clone(), attr: attr.to_string() }
+}
+
+fn invalid(node: &Node, attr: &str, value: &str, expected: &'static str) -> AttrError {
+    AttrError::Invalid { tag: node.tag.clone(), attr: attr.to_string(), value: value.to_string(), expected }
+}
+
+/// JID dari atribut; `c.us` dari decoder dibaca sebagai `s.whatsapp.net`
+fn parse_jid(value: &str) -> Option<Jid> {
+    Jid::from_string(value).ok()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Membaca atribut opsional: `Ok(None)` jika tidak ada, error jika nilainya salah
+fn typed<T>(node: &Node, attr: &str, expected: &'static str, parse: fn(&str) -> Option<T>) -> std::result::Result<Option<T>, AttrError> {
+    match node.get_attr(attr) {
+        None => Ok(None),
+        Some(value) => parse(value).map(Some).ok_or_else(|| invalid(node, attr, value, expected)),
+    }
+}
+
+fn required<T>(node: &Node, attr: &str, expected: &'static str, parse: fn(&str) -> Option<T>) -> std::result::Result<T, AttrError> {
+    typed(node, attr, expected, parse)?.ok_or_else(|| missing(node, attr))
+}
+
+impl Node {
+    /// Nilai atribut yang wajib ada
+    pub fn attr_required(&self, attr: &str) -> Result<&str> {
+        Ok(self.get_attr(attr).ok_or_else(|| missing(self, attr))?)
+    }
+
+    /// Atribut JID (`from`, `participant`, ...)
+    pub fn attr_jid(&self, attr: &str) -> Result<Jid> {
+        Ok(required(self, attr, "JID", parse_jid)?)
+    }
+
+    /// Atribut angka (`t`, `count`, ...)
+    pub fn attr_u64(&self, attr: &str) -> Result<u64> {
+        Ok(required(self, attr, "unsigned integer", |value| value.parse().ok())?)
+    }
+
+    /// Atribut boolean (`true`/`false` atau `1`/`0`)
+    pub fn attr_bool(&self, attr: &str) -> Result<bool> {
+        Ok(required(self, attr, "boolean", parse_bool)?)
+    }
+
+    /// Parser yang mengumpulkan semua kesalahan atribut node ini
+    pub fn attr_parser(&self) -> AttrParser<'_> {
+        AttrParser { node: self, errors: Vec::new() }
+    }
+}
+
+/// Membaca banyak atribut sekaligus dan mencatat setiap kesalahan.
+/// Atribut wajib yang gagal dibaca menghasilkan nilai kosong; periksa
+/// `finish` sebelum memakai hasilnya.
+pub struct AttrParser<'a> {
+    node: &'a Node,
+    errors: Vec<AttrError>,
+}
+
+impl<'a> AttrParser<'a> {
+    fn record<T>(&mut self, result: std::result::Result<T, AttrError>) -> Option<T> {
+        result.map_err(|error| self.errors.push(error)).ok()
+    }
+
+    pub fn string(&mut self, attr: &str) -> &'a str {
+        let node = self.node;
+        self.record(node.get_attr(attr).ok_or_else(|| missing(node, attr))).unwrap_or_default()
+    }
+
+    pub fn optional_string(&mut self, attr: &str) -> Option<&'a str> {
+        self.node.get_attr(attr)
+    }
+
+    pub fn jid(&mut self, attr: &str) -> Jid {
+        let jid = required(self.node, attr, "JID", parse_jid);
+        self.record(jid).unwrap_or_else(|| Jid::new(String::new(), Server::User))
+    }
+
+    pub fn optional_jid(&mut self, attr: &str) -> Option<Jid> {
+        let jid = typed(self.node, attr, "JID", parse_jid);
+        self.record(jid).flatten()
+    }
+
+    pub fn u64(&mut self, attr: &str) -> u64 {
+        let number = required(self.node, attr, "unsigned integer", |value| value.parse().ok());
+        self.record(number).unwrap_or_default()
+    }
+
+    pub fn optional_u64(&mut self, attr: &str) -> Option<u64> {
+        let number = typed(self.node, attr, "unsigned integer", |value| value.parse().ok());
+        self.record(number).flatten()
+    }
+
+    pub fn bool(&mut self, attr: &str) -> bool {
+        let flag = required(self.node, attr, "boolean", parse_bool);
+        self.record(flag).unwrap_or_default()
+    }
+
+    pub fn optional_bool(&mut self, attr: &str) -> Option<bool> {
+        let flag = typed(self.node, attr, "boolean", parse_bool);
+        self.record(flag).flatten()
+    }
+
+    /// Kesalahan yang tercatat sejauh ini
+    pub fn errors(&self) -> &[AttrError] {
+        &self.errors
+    }
+
+    pub fn ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// `Err(InvalidAttributes)` berisi semua kesalahan jika ada
+    pub fn finish(self) -> Result<()> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ErrorKind::InvalidAttributes(self.errors).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_attributes_and_parser() {
+        let node = Node::new("message")
+            .attr("from", "628123@c.us")
+            .attr("t", "1700000001")
+            .attr("offline", "1")
+            .attr("count", "many");
+
+        assert_eq!(node.attr_jid("from").unwrap().to_string(), "628123@s.whatsapp.net");
+        assert_eq!(node.attr_u64("t").unwrap(), 1700000001);
+        assert!(node.attr_bool("offline").unwrap());
+        let err = node.attr_required("id").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid attributes: <message> is missing attribute \"id\"");
+
+        let mut attrs = node.attr_parser();
+        assert_eq!(attrs.jid("from").id, "628123");
+        assert_eq!(attrs.optional_u64("edit"), None);
+        assert_eq!(attrs.u64("count"), 0);
+        assert_eq!(attrs.string("id"), "");
+        assert!(!attrs.ok());
+        match attrs.finish().unwrap_err().kind {
+            ErrorKind::InvalidAttributes(errors) => assert_eq!(
+                errors,
+                vec![
+                    AttrError::Invalid {
+                        tag: "message".to_string(),
+                        attr: "count".to_string(),
+                        value: "many".to_string(),
+                        expected: "unsigned integer",
+                    },
+                    AttrError::Missing { tag: "message".to_string(), attr: "id".to_string() },
+                ]
+            ),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -1,5 +1,10 @@
 use crate::errors::*;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::io::{Read, Write};
 
 pub const LIST_EMPTY: u8 = 0;
 pub const STREAM_END: u8 = 2;
@@ -16,37 +21,29 @@ pub const BINARY_20: u8 = 253;
 pub const BINARY_32: u8 = 254;
 pub const NIBBLE_8: u8 = 255;
 
-pub const SINGLE_BYTE_MAX: u8 = 256;
+pub const SINGLE_BYTE_MAX: u16 = 256;
 pub const PACKED_MAX: u8 = 254;
 
-pub const SINGLE_BYTE_TOKENS: &[&str] = &[
-    "", "", "", "200", "400", "404", "500", "501", "502", "action", "add",
-    "after", "archive", "author", "available", "battery", "before", "body",
-    "broadcast", "chat", "clear", "code", "composing", "contacts", "count",
-    "create", "debug", "delete", "demote", "duplicate", "encoding", "error",
-    "false", "filehash", "from", "g.us", "group", "groups_v2", "height", "id",
-    "image", "in", "index", "invis", "item", "jid", "kind", "last", "leave",
-    "live", "log", "media", "message", "mimetype", "missing", "modify", "name",
-    "notification", "notify", "out", "owner", "participant", "paused",
-    "picture", "played", "presence", "preview", "promote", "query", "raw",
-    "read", "receipt", "received", "recipient", "recording", "relay",
-    "remove", "response", "resume", "retry", "s.whatsapp.net", "seconds",
-    "set", "size", "status", "subject", "subscribe", "t", "text", "to", "true",
-    "type", "unarchive", "unavailable", "url", "user", "value", "web", "width",
-    "mute", "read_only", "admin", "creator", "short", "update", "powersave",
-    "checksum", "epoch", "block", "previous", "409", "replaced", "reason",
-    "spam", "modify_tag", "message_info", "delivery", "emoji", "title",
-    "description", "canonical-url", "matched-text", "star", "unstar",
-    "media_key", "filename", "identity", "unread", "page", "page_count",
-    "search", "media_message", "security", "call_log", "profile", "ciphertext",
-    "invite", "gif", "vcard", "frequent", "privacy", "blacklist", "whitelist",
-    "verify", "location", "document", "elapsed", "revoke_invite", "expiration",
-    "unsubscribe", "disable", "vname", "old_jid", "new_jid", "announcement",
-    "locked", "prop", "label", "color", "call", "offer", "call-id",
-    "quick_reply", "sticker", "pay_t", "accept", "reject", "sticker_pack",
-    "invalid", "canceled", "missed", "connected", "result", "audio",
-    "video", "recent"
-];
+/// Bit pada byte flag payload: isi node di-deflate zlib
+pub const FLAG_COMPRESSED: u8 = 2;
+/// Batas hasil dekompresi, mencegah payload kecil mengembang tanpa batas
+pub const MAX_INFLATED_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Token kamus satu byte dan kamus ganda (DICTIONARY_0..=DICTIONARY_3),
+/// dibangkitkan dari `proto/constants.json`
+pub use crate::protocol_constants::{DOUBLE_BYTE_TOKENS, SINGLE_BYTE_TOKENS};
+
+/// Posisi token di tabel satu byte; posisi sama dengan byte di wire
+fn single_byte_token(s: &str) -> Option<u8> {
+    SINGLE_BYTE_TOKENS.iter().skip(3).position(|&t| t == s).map(|index| (index + 3) as u8)
+}
+
+/// Kamus dan indeks token di tabel ganda-byte
+fn double_byte_token(dictionaries: &[&[&str]], s: &str) -> Option<(u8, u8)> {
+    dictionaries.iter().enumerate().find_map(|(dictionary, tokens)| {
+        tokens.iter().position(|&t| t == s).map(|index| (dictionary as u8, index as u8))
+    })
+}
 
 #[derive(Debug, Clone)]
 pub struct Node {
@@ -62,12 +59,104 @@ pub enum NodeContent {
     List(Vec<Node>),
 }
 
+impl Node {
+    /// Membuat node tanpa atribut dan konten
+    pub fn new(tag: &str) -> Self {
+        Node {
+            tag: tag.to_string(),
+            attrs: HashMap::new(),
+            content: None,
+        }
+    }
+
+    /// Menambahkan atribut (gaya builder)
+    pub fn attr(mut self, key: &str, value: &str) -> Self {
+        self.attrs.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Mengisi konten dengan node anak
+    pub fn children(mut self, children: Vec<Node>) -> Self {
+        self.content = Some(NodeContent::List(children));
+        self
+    }
+
+    /// Mengisi konten dengan data biner
+    pub fn bytes(mut self, data: Vec<u8>) -> Self {
+        self.content = Some(NodeContent::Binary(data));
+        self
+    }
+
+    /// Mengambil nilai atribut
+    pub fn get_attr(&self, key: &str) -> Option<&str> {
+        self.attrs.get(key).map(|v| v.as_str())
+    }
+
+    /// Mengambil semua node anak (kosong jika konten bukan list)
+    pub fn get_children(&self) -> &[Node] {
+        match self.content {
+            Some(NodeContent::List(ref nodes)) => nodes,
+            _ => &[],
+        }
+    }
+
+    /// Mengambil node anak pertama dengan tag tertentu
+    pub fn get_child(&self, tag: &str) -> Option<&Node> {
+        self.get_children().iter().find(|child| child.tag == tag)
+    }
+
+    /// Mengambil konten biner (atau teks sebagai byte)
+    pub fn get_bytes(&self) -> Option<&[u8]> {
+        match self.content {
+            Some(NodeContent::Binary(ref data)) => Some(data),
+            Some(NodeContent::Text(ref text)) => Some(text.as_bytes()),
+            _ => None,
+        }
+    }
+}
+
+/// Node hasil decode yang meminjam buffer input
+#[derive(Debug, Clone)]
+pub struct NodeRef<'a> {
+    pub tag: Cow<'a, str>,
+    pub attrs: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub content: Option<NodeRefContent<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeRefContent<'a> {
+    Text(Cow<'a, str>),
+    Binary(&'a [u8]),
+    List(Vec<NodeRef<'a>>),
+}
+
+impl<'a> NodeRef<'a> {
+    pub fn get_attr(&self, key: &str) -> Option<&str> {
+        self.attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_ref())
+    }
+
+    /// Menyalin node ke `Node` yang tidak bergantung pada buffer input
+    pub fn into_owned(self) -> Node {
+        Node {
+            tag: self.tag.into_owned(),
+            attrs: self.attrs.into_iter().map(|(k, v)| (k.into_owned(), v.into_owned())).collect(),
+            content: self.content.map(|content| match content {
+                NodeRefContent::Text(text) => NodeContent::Text(text.into_owned()),
+                NodeRefContent::Binary(data) => NodeContent::Binary(data.to_vec()),
+                NodeRefContent::List(nodes) => NodeContent::List(nodes.into_iter().map(NodeRef::into_owned).collect()),
+            }),
+        }
+    }
+}
+
 pub struct NodeEncoder {
     pub data: Vec<u8>,
 }
 
-pub struct NodeDecoder {
-    pub data: Vec<u8>,
+/// Decoder yang meminjam buffer frame; string dan bytes tidak disalin
+/// sampai node diubah menjadi `Node` lewat `NodeRef::into_owned`
+pub struct NodeDecoder<'a> {
+    pub data: &'a [u8],
     pub index: usize,
 }
 
@@ -117,26 +206,21 @@ impl NodeEncoder {
     }
 
     fn write_string(&mut self, s: &str, i: bool) -> Result<()> {
-        if !i && s == "s.whatsapp.net" {
+        if s.is_empty() {
+            self.write_token(LIST_EMPTY)?;
+        } else if !i && s == "s.whatsapp.net" {
             // Ganti s.whatsapp.net menjadi c.us
-            self.write_token(SINGLE_BYTE_TOKENS.iter().position(|&t| t == "s.whatsapp.net").unwrap() as u8)?;
-        } else if let Some(token_index) = SINGLE_BYTE_TOKENS.iter().position(|&t| t == s) {
-            if token_index < SINGLE_BYTE_MAX as usize {
-                self.write_token(token_index as u8)?;
-            } else {
-                // Gunakan kamus ganda-byte
-                let overflow = token_index - SINGLE_BYTE_MAX as usize;
-                let dictionary_index = (overflow >> 8) as u8;
-                if dictionary_index < 4 {
-                    self.write_token(DICTIONARY_0 + dictionary_index)?;
-                    self.write_token((overflow % 256) as u8)?;
-                } else {
-                    return Err("Dictionary token out of range".into());
-                }
-            }
+            self.write_token(single_byte_token("s.whatsapp.net").unwrap())?;
+        } else if let Some(token) = single_byte_token(s) {
+            self.write_token(token)?;
+        } else if let Some((dictionary, index)) = double_byte_token(&DOUBLE_BYTE_TOKENS, s) {
+            self.write_token(DICTIONARY_0 + dictionary)?;
+            self.write_token(index)?;
         } else {
-            // Periksa apakah ini JID
-            if let Some(pos) = s.find('@') {
+            // Angka dan hex dikemas seperti client resmi, selain itu periksa JID
+            if let Some(tag) = packed_tag(s) {
+                self.write_packed(s, tag)?;
+            } else if let Some(pos) = s.find('@') {
                 let (left, right) = s.split_at(pos);
                 let right = &right[1..]; // Hilangkan '@'
                 self.write_jid(left, right)?;
@@ -148,11 +232,7 @@ impl NodeEncoder {
     }
 
     fn write_token(&mut self, token: u8) -> Result<()> {
-        if token < SINGLE_BYTE_MAX as u8 {
-            self.data.push(token);
-        } else {
-            return Err("Invalid token".into());
-        }
+        self.data.push(token);
         Ok(())
     }
 
@@ -167,6 +247,26 @@ impl NodeEncoder {
         Ok(())
     }
 
+    fn write_packed(&mut self, s: &str, tag: u8) -> Result<()> {
+        let bytes = s.as_bytes();
+        let mut length_byte = bytes.len().div_ceil(2) as u8;
+        if bytes.len() % 2 == 1 {
+            length_byte |= 0x80;
+        }
+        self.data.push(tag);
+        self.data.push(length_byte);
+        for pair in bytes.chunks(2) {
+            let high = pack_nibble(pair[0], tag)?;
+            // Karakter terakhir yang ganjil dipasangkan dengan nibble pengisi
+            let low = match pair.get(1) {
+                Some(&c) => pack_nibble(c, tag)?,
+                None => 15,
+            };
+            self.data.push((high << 4) | low);
+        }
+        Ok(())
+    }
+
     fn write_string_raw(&mut self, s: &str) -> Result<()> {
         let bytes = s.as_bytes();
         self.write_byte_length(bytes.len())?;
@@ -218,15 +318,56 @@ impl NodeEncoder {
     }
 }
 
-impl NodeDecoder {
-    pub fn new(data: &[u8]) -> Self {
-        NodeDecoder {
-            data: data.to_vec(),
-            index: 0,
-        }
+/// Tag paket untuk string yang bisa dikemas dua karakter per byte:
+/// `NIBBLE_8` untuk angka (nomor telepon, timestamp), `HEX_8` untuk hex kapital
+fn packed_tag(s: &str) -> Option<u8> {
+    if s.is_empty() || s.len() > PACKED_MAX as usize {
+        return None;
+    }
+    if s.bytes().all(|c| c.is_ascii_digit() || c == b'-' || c == b'.') {
+        Some(NIBBLE_8)
+    } else if s.bytes().all(|c| c.is_ascii_digit() || (b'A'..=b'F').contains(&c)) {
+        Some(HEX_8)
+    } else {
+        None
+    }
+}
+
+fn pack_nibble(c: u8, tag: u8) -> Result<u8> {
+    match (tag, c) {
+        (_, b'0'..=b'9') => Ok(c - b'0'),
+        (NIBBLE_8, b'-') => Ok(10),
+        (NIBBLE_8, b'.') => Ok(11),
+        (HEX_8, b'A'..=b'F') => Ok(c - b'A' + 10),
+        _ => Err("Character cannot be packed".into()),
+    }
+}
+
+fn unpack_nibble(nibble: u8, tag: u8) -> Result<char> {
+    match (tag, nibble) {
+        (_, 0..=9) => Ok((b'0' + nibble) as char),
+        (NIBBLE_8, 10) => Ok('-'),
+        (NIBBLE_8, 11) => Ok('.'),
+        (NIBBLE_8, 15) => Ok('\0'), // Pengisi untuk panjang ganjil
+        (HEX_8, 10..=15) => Ok((b'A' + nibble - 10) as char),
+        (NIBBLE_8, _) => Err("Invalid nibble".into()),
+        (HEX_8, _) => Err("Invalid hex nibble".into()),
+        _ => Err("Invalid packed string tag".into()),
+    }
+}
+
+impl<'a> NodeDecoder<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        NodeDecoder { data, index: 0 }
     }
 
+    /// Membaca satu node sebagai `Node` milik sendiri
     pub fn read_node(&mut self) -> Result<Node> {
+        Ok(self.read_node_ref()?.into_owned())
+    }
+
+    /// Membaca satu node tanpa menyalin string dan bytes dari buffer
+    pub fn read_node_ref(&mut self) -> Result<NodeRef<'a>> {
         // Baca ukuran list
         let list_size_tag = self.read_byte()?;
         let list_size = self.read_list_size(list_size_tag)?;
@@ -241,7 +382,7 @@ impl NodeDecoder {
 
         // Hitung jumlah atribut
         let num_attrs = (list_size - 1) >> 1;
-        let mut attrs = HashMap::new();
+        let mut attrs = Vec::with_capacity(num_attrs);
 
         // Baca pasangan (key, value) atribut
         for _ in 0..num_attrs {
@@ -249,7 +390,7 @@ impl NodeDecoder {
             let key = self.read_string(key_token)?;
             let value_token = self.read_byte()?;
             let value = self.read_string(value_token)?;
-            attrs.insert(key, value);
+            attrs.push((key, value));
         }
 
         // Cek apakah ada konten
@@ -261,7 +402,7 @@ impl NodeDecoder {
             None
         };
 
-        Ok(Node {
+        Ok(NodeRef {
             tag,
             attrs,
             content,
@@ -290,32 +431,24 @@ impl NodeDecoder {
         }
     }
 
-    fn read_string(&mut self, tag: u8) -> Result<String> {
-        if tag >= 3 && tag < SINGLE_BYTE_MAX as u8 {
-            let token_idx = (tag - 3) as usize;
-            if token_idx < SINGLE_BYTE_TOKENS.len() {
-                let token = SINGLE_BYTE_TOKENS[token_idx];
-                if token == "s.whatsapp.net" {
-                    Ok("c.us".to_string()) // Ganti kembali ke c.us
-                } else {
-                    Ok(token.to_string())
-                }
-            } else {
-                Err("Invalid token index".into())
+    fn read_string(&mut self, tag: u8) -> Result<Cow<'a, str>> {
+        if tag >= 3 && tag < DICTIONARY_0 {
+            match SINGLE_BYTE_TOKENS.get(tag as usize) {
+                Some(&"s.whatsapp.net") => Ok(Cow::Borrowed("c.us")), // Ganti kembali ke c.us
+                Some(token) => Ok(Cow::Borrowed(token)),
+                None => Err(format!("Unknown single-byte token {}", tag).into()),
             }
         } else {
             match tag {
                 DICTIONARY_0..=DICTIONARY_3 => {
-                    let dict_index = tag - DICTIONARY_0;
-                    let next_byte = self.read_byte()?;
-                    let token_idx = (dict_index as usize) * 256 + next_byte as usize;
-                    if token_idx < SINGLE_BYTE_TOKENS.len() {
-                        Ok(SINGLE_BYTE_TOKENS[token_idx].to_string())
-                    } else {
-                        Err("Invalid dictionary token".into())
+                    let dictionary = (tag - DICTIONARY_0) as usize;
+                    let index = self.read_byte()? as usize;
+                    match DOUBLE_BYTE_TOKENS[dictionary].get(index) {
+                        Some(token) => Ok(Cow::Borrowed(token)),
+                        None => Err(format!("Unknown double-byte token {} in dictionary {}", index, dictionary).into()),
                     }
                 },
-                LIST_EMPTY => Ok(String::new()),
+                LIST_EMPTY => Ok(Cow::Borrowed("")),
                 BINARY_8 => {
                     let length = self.read_byte()?;
                     self.read_string_from_chars(length as usize)
@@ -341,22 +474,29 @@ impl NodeDecoder {
                     if left.is_empty() && right.is_empty() {
                         Err("Invalid JID pair".into())
                     } else {
-                        Ok(format!("{}@{}", left, right))
+                        Ok(Cow::Owned(format!("{}@{}", left, right)))
                     }
                 },
-                HEX_8 | NIBBLE_8 => self.read_packed_string(tag),
+                HEX_8 | NIBBLE_8 => self.read_packed_string(tag).map(Cow::Owned),
                 _ => Err("Invalid string tag".into()),
             }
         }
     }
 
-    fn read_string_from_chars(&mut self, length: usize) -> Result<String> {
+    fn read_string_from_chars(&mut self, length: usize) -> Result<Cow<'a, str>> {
+        let string_bytes = self.read_slice(length)?;
+        Ok(Cow::Borrowed(std::str::from_utf8(string_bytes).map_err(|_| "Invalid UTF8")?))
+    }
+
+    /// Meminjam `length` byte berikutnya dari buffer
+    fn read_slice(&mut self, length: usize) -> Result<&'a [u8]> {
         if self.index + length > self.data.len() {
             return Err("End of stream".into());
         }
-        let string_bytes = &self.data[self.index..self.index + length];
+        let data: &'a [u8] = self.data;
+        let slice = &data[self.index..self.index + length];
         self.index += length;
-        Ok(String::from_utf8(string_bytes.to_vec()).map_err(|_| "Invalid UTF8")?)
+        Ok(slice)
     }
 
     fn read_int20(&mut self) -> Result<u32> {
@@ -370,62 +510,29 @@ impl NodeDecoder {
         Ok(value)
     }
 
+    /// String terpaket: byte panjang (bit 7 = jumlah karakter ganjil, 7 bit
+    /// bawah = jumlah byte) lalu dua karakter per byte, nibble atas dahulu
     fn read_packed_string(&mut self, tag: u8) -> Result<String> {
-        // Baca panjang paket
         let length_byte = self.read_byte()?;
-        let length = (length_byte & 0x7F) as usize; // Hapus MSB
+        let is_odd_length = (length_byte & 0x80) != 0;
+        let packed = self.read_slice((length_byte & 0x7F) as usize)?;
 
-        let mut result = String::new();
-        let mut is_odd_length = (length_byte & 0x80) != 0; // LSB menunjukkan ganjil
-
-        for i in 0..length {
-            if self.index >= self.data.len() {
-                break; // Perlindungan dari buffer overrun
-            }
-            let byte = self.data[self.index];
-            self.index += 1;
-
-            // Ambil nibble atas dan bawah
-            let high_nibble = (byte >> 4) & 0x0F;
-            let low_nibble = byte & 0x0F;
-
-            // Interpretasikan nibble sebagai karakter
-            result.push(self.unpack_nibble(high_nibble, tag)?);
-            
-            if i + 1 < length || !is_odd_length {
-                result.push(self.unpack_nibble(low_nibble, tag)?);
-            }
+        let mut result = String::with_capacity(packed.len() * 2);
+        for byte in packed {
+            result.push(unpack_nibble(byte >> 4, tag)?);
+            result.push(unpack_nibble(byte & 0x0F, tag)?);
         }
-
-        Ok(result)
-    }
-
-    fn unpack_nibble(&self, nibble: u8, tag: u8) -> Result<char> {
-        match tag {
-            NIBBLE_8 => {
-                match nibble {
-                    0..=9 => Ok(std::char::from_digit(nibble as u32, 10).unwrap()),
-                    10 => Ok('-'),
-                    11 => Ok('.'),
-                    15 => Ok('\0'), // EOF
-                    _ => Err("Invalid nibble".into()),
-                }
-            },
-            HEX_8 => {
-                match nibble {
-                    0..=9 => Ok(std::char::from_digit(nibble as u32, 10).unwrap()),
-                    10..=15 => Ok(std::char::from_digit(nibble as u32, 16).unwrap().to_ascii_uppercase()),
-                    _ => Err("Invalid hex nibble".into()),
-                }
-            },
-            _ => Err("Invalid packed string tag".into()),
+        // Karakter ganjil terakhir adalah nibble pengisi
+        if is_odd_length {
+            result.pop();
         }
+        Ok(result)
     }
 
-    fn read_content(&mut self, tag: u8) -> Result<NodeContent> {
+    fn read_content(&mut self, tag: u8) -> Result<NodeRefContent<'a>> {
         if self.is_list_tag(tag) {
             let nodes = self.read_list_nodes(tag)?;
-            Ok(NodeContent::List(nodes))
+            Ok(NodeRefContent::List(nodes))
         } else {
             match tag {
                 BINARY_8 => {
@@ -447,7 +554,7 @@ impl NodeDecoder {
                 },
                 _ => {
                     let string_content = self.read_string(tag)?;
-                    Ok(NodeContent::Text(string_content))
+                    Ok(NodeRefContent::Text(string_content))
                 }
             }
         }
@@ -457,24 +564,152 @@ impl NodeDecoder {
         tag == LIST_EMPTY || tag == LIST_8 || tag == LIST_16
     }
 
-    fn read_list_nodes(&mut self, tag: u8) -> Result<Vec<Node>> {
+    fn read_list_nodes(&mut self, tag: u8) -> Result<Vec<NodeRef<'a>>> {
         let size = self.read_list_size(tag)?;
         let mut nodes = Vec::with_capacity(size);
         
         for _ in 0..size {
-            nodes.push(self.read_node()?);
+            nodes.push(self.read_node_ref()?);
         }
         
         Ok(nodes)
     }
 
-    fn read_binary_content(&mut self, length: usize) -> Result<NodeContent> {
-        if self.index + length > self.data.len() {
-            return Err("End of stream".into());
+    fn read_binary_content(&mut self, length: usize) -> Result<NodeRefContent<'a>> {
+        Ok(NodeRefContent::Binary(self.read_slice(length)?))
+    }
+}
+
+/// Melepas byte flag payload node dan mendekompresi isinya jika perlu.
+/// Stanza besar (app state, history) selalu dikirim terkompresi.
+pub fn unpack_payload(payload: &[u8]) -> Result<Cow<'_, [u8]>> {
+    let (&flags, data) = payload.split_first().ok_or("Empty node payload")?;
+    if flags & FLAG_COMPRESSED == 0 {
+        return Ok(Cow::Borrowed(data));
+    }
+    let mut inflated = Vec::new();
+    ZlibDecoder::new(data).take(MAX_INFLATED_SIZE + 1).read_to_end(&mut inflated)?;
+    if inflated.len() as u64 > MAX_INFLATED_SIZE {
+        return Err(format!("Compressed node exceeds {} bytes", MAX_INFLATED_SIZE).into());
+    }
+    Ok(Cow::Owned(inflated))
+}
+
+/// Menambahkan byte flag ke node yang sudah dienkode; `compress` men-deflate isinya
+pub fn pack_payload(encoded: &[u8], compress: bool) -> Result<Vec<u8>> {
+    if !compress {
+        let mut payload = Vec::with_capacity(encoded.len() + 1);
+        payload.push(0);
+        payload.extend_from_slice(encoded);
+        return Ok(payload);
+    }
+    let mut zlib = ZlibEncoder::new(vec![FLAG_COMPRESSED], Compression::default());
+    zlib.write_all(encoded)?;
+    Ok(zlib.finish()?)
+}
+
+/// Mendekode node dari payload berflag
+pub fn decode_payload(payload: &[u8]) -> Result<Node> {
+    NodeDecoder::new(&unpack_payload(payload)?).read_node()
+}
+
+/// Mengenkode node menjadi payload berflag
+pub fn encode_payload(node: &Node, compress: bool) -> Result<Vec<u8>> {
+    let mut encoder = NodeEncoder::new();
+    encoder.write_node(node)?;
+    pack_payload(&encoder.data, compress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_borrows_input() {
+        let node = Node::new("message")
+            .attr("id", "m.3eb0c431")
+            .children(vec![Node::new("enc").attr("to", "628123@c.us").bytes(vec![1, 2, 3])]);
+        let mut encoder = NodeEncoder::new();
+        encoder.write_node(&node).unwrap();
+
+        let mut decoder = NodeDecoder::new(&encoder.data);
+        let decoded = decoder.read_node_ref().unwrap();
+        assert!(matches!(decoded.get_attr("id"), Some("m.3eb0c431")));
+        assert!(matches!(decoded.attrs[0].1, Cow::Borrowed(_)));
+        let enc = match decoded.content {
+            Some(NodeRefContent::List(ref children)) => &children[0],
+            ref other => panic!("unexpected {:?}", other),
+        };
+        match enc.content {
+            Some(NodeRefContent::Binary(bytes)) => {
+                assert_eq!(bytes, &[1, 2, 3]);
+                assert!(encoder.data.as_ptr_range().contains(&bytes.as_ptr()));
+            }
+            ref other => panic!("unexpected {:?}", other),
         }
-        
-        let content = self.data[self.index..self.index + length].to_vec();
-        self.index += length;
-        Ok(NodeContent::Binary(content))
+
+        let owned = decoded.into_owned();
+        assert_eq!(owned.get_child("enc").unwrap().get_attr("to"), Some("628123@c.us"));
+        assert_eq!(NodeDecoder::new(&encoder.data).read_node().unwrap().get_attr("id"), Some("m.3eb0c431"));
+    }
+
+    #[test]
+    fn test_numeric_strings_are_packed() {
+        let node = Node::new("receipt").attr("t", "1700000001").attr("id", "3EB0C").attr("code", "-1.5");
+        let mut encoder = NodeEncoder::new();
+        encoder.write_node(&node).unwrap();
+        // Timestamp 10 digit = 5 byte nibble, panjang genap
+        let timestamp = [NIBBLE_8, 5, 0x17, 0x00, 0x00, 0x00, 0x01];
+        assert!(encoder.data.windows(timestamp.len()).any(|w| w == timestamp));
+        // Hex ganjil: bit 7 panjang diset dan nibble terakhir pengisi
+        let hex = [HEX_8, 0x83, 0x3E, 0xB0, 0xCF];
+        assert!(encoder.data.windows(hex.len()).any(|w| w == hex));
+
+        let decoded = NodeDecoder::new(&encoder.data).read_node().unwrap();
+        assert_eq!(decoded.get_attr("t"), Some("1700000001"));
+        assert_eq!(decoded.get_attr("id"), Some("3EB0C"));
+        assert_eq!(decoded.get_attr("code"), Some("-1.5"));
+
+        // Byte terpotong tidak diam-diam menghasilkan string pendek
+        assert!(NodeDecoder::new(&[3, 0x12]).read_packed_string(NIBBLE_8).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_dictionary_tokens() {
+        // Byte token satu byte sama dengan posisinya di tabel
+        let mut encoder = NodeEncoder::new();
+        encoder.write_string("action", true).unwrap();
+        let position = SINGLE_BYTE_TOKENS.iter().position(|&t| t == "action").unwrap();
+        assert_eq!(encoder.data, vec![position as u8]);
+        let mut decoder = NodeDecoder::new(&encoder.data);
+        let tag = decoder.read_byte().unwrap();
+        assert_eq!(decoder.read_string(tag).unwrap(), "action");
+
+        let dictionaries: [&[&str]; 4] = [&["read-self"], &[], &["unavailable", "w:stats"], &[]];
+        assert_eq!(double_byte_token(&dictionaries, "w:stats"), Some((2, 1)));
+        assert_eq!(double_byte_token(&dictionaries, "chat"), None);
+
+        // Indeks di luar kamus menyebutkan kamus dan indeksnya
+        let index = DOUBLE_BYTE_TOKENS[1].len() as u8;
+        let err = NodeDecoder::new(&[index]).read_string(DICTIONARY_1).unwrap_err();
+        assert!(err.to_string().contains("dictionary 1"));
+    }
+
+    #[test]
+    fn test_compressed_payload() {
+        let node = Node::new("sync").children((0..50).map(|i| Node::new("collection").attr("name", &format!("regular_{}", i))).collect());
+        let plain = encode_payload(&node, false).unwrap();
+        let compressed = encode_payload(&node, true).unwrap();
+        assert_eq!(plain[0], 0);
+        assert_eq!(compressed[0], FLAG_COMPRESSED);
+        assert!(compressed.len() < plain.len());
+
+        for payload in [plain, compressed] {
+            let decoded = decode_payload(&payload).unwrap();
+            assert_eq!(decoded.get_children().len(), 50);
+            assert_eq!(decoded.get_children()[49].get_attr("name"), Some("regular_49"));
+        }
+        assert!(unpack_payload(&[]).is_err());
+        assert!(unpack_payload(&[FLAG_COMPRESSED, 1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Antrean pesan keluar selama koneksi terputus
+//!
+//! Jika diaktifkan (`WhatsAppClientBuilder::with_offline_outbox`), pesan yang
+//! dikirim saat client belum `ConnectionState::Connected` (terputus atau
+//! sedang menyambung ulang) tidak ditolak tetapi diantrekan. Begitu koneksi
+//! terotentikasi, antrean dikirim sesuai urutan sebelum pesan baru, lalu
+//! `Event::OfflineMessageSent` dikirim per pesan. Pesan yang melewati TTL-nya
+//! dibuang dengan `Event::OfflineMessageExpired`.
+//!
+//! Dengan `StateStore`, antrean ikut bertahan setelah aplikasi restart.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::*;
+use crate::messages::WebMessageInfo;
+use crate::store::{self, StateStore};
+
+/// Prefix key antrean di `StateStore`
+pub const OFFLINE_PREFIX: &str = "offline:msg:";
+
+/// TTL default pesan di antrean
+pub const DEFAULT_OFFLINE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Pesan di antrean offline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedMessage {
+    pub message: WebMessageInfo,
+    /// Detik unix; setelah ini pesan dibuang
+    pub expires_at: i64,
+    seq: u64,
+}
+
+impl QueuedMessage {
+    fn key(&self) -> String {
+        format!("{}{:020}:{}", OFFLINE_PREFIX, self.seq, self.message.key.id)
+    }
+}
+
+/// Antrean pesan keluar yang menunggu koneksi
+#[derive(Default)]
+pub struct OfflineOutbox {
+    /// `None` jika antrean tidak diaktifkan
+    ttl: Option<Duration>,
+    store: Option<Arc<dyn StateStore>>,
+    queue: VecDeque<QueuedMessage>,
+    next_seq: u64,
+}
+
+impl OfflineOutbox {
+    pub fn new() -> Self {
+        OfflineOutbox::default()
+    }
+
+    /// Mengaktifkan antrean; pesan yang tersimpan di `store` dimuat kembali
+    pub fn enable(&mut self, ttl: Duration, store: Option<Arc<dyn StateStore>>) -> Result<()> {
+        if let Some(ref store) = store {
+            let mut keys = store.keys(OFFLINE_PREFIX)?;
+            keys.sort();
+            for key in keys {
+                if let Some(queued) = store::load_json::<QueuedMessage>(store.as_ref(), &key)? {
+                    self.next_seq = self.next_seq.max(queued.seq + 1);
+                    self.queue.push_back(queued);
+                }
+            }
+        }
+        self.ttl = Some(ttl);
+        self.store = store;
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.ttl.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Menambahkan pesan ke akhir antrean dengan TTL dari `now` (detik unix)
+    pub fn push(&mut self, message: WebMessageInfo, now: i64) -> Result<()> {
+        let ttl = self.ttl.ok_or("Offline outbox is not enabled")?;
+        let queued = QueuedMessage {
+            message,
+            expires_at: now + ttl.as_secs() as i64,
+            seq: self.next_seq,
+        };
+        if let Some(ref store) = self.store {
+            store::save_json(store.as_ref(), &queued.key(), &queued)?;
+        }
+        self.next_seq += 1;
+        self.queue.push_back(queued);
+        Ok(())
+    }
+
+    /// Mengambil seluruh antrean: (pesan yang masih berlaku, pesan kedaluwarsa).
+    /// Pesan kedaluwarsa langsung dihapus dari store; pesan lain dihapus lewat
+    /// `complete` setelah terkirim.
+    pub fn drain(&mut self, now: i64) -> (Vec<QueuedMessage>, Vec<WebMessageInfo>) {
+        let mut ready = Vec::new();
+        let mut expired = Vec::new();
+        for queued in self.queue.drain(..) {
+            if queued.expires_at > now {
+                ready.push(queued);
+                continue;
+            }
+            if let Some(ref store) = self.store {
+                store.remove(&queued.key()).ok();
+            }
+            expired.push(queued.message);
+        }
+        (ready, expired)
+    }
+
+    /// Pesan sudah terkirim; hapus dari store
+    pub fn complete(&mut self, queued: &QueuedMessage) -> Result<()> {
+        match self.store {
+            Some(ref store) => store.remove(&queued.key()),
+            None => Ok(()),
+        }
+    }
+
+    /// Mengembalikan pesan yang belum terkirim ke depan antrean
+    pub fn requeue(&mut self, pending: Vec<QueuedMessage>) {
+        for queued in pending.into_iter().rev() {
+            self.queue.push_front(queued);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStateStore;
+
+    fn message(id: &str) -> WebMessageInfo {
+        WebMessageInfo {
+            key: crate::messages::MessageKey {
+                remote_jid: "628111@s.whatsapp.net".to_string(),
+                from_me: true,
+                id: id.to_string(),
+                participant: None,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_queue_survives_restart_and_expires() {
+        let store: Arc<dyn StateStore> = Arc::new(MemoryStateStore::new());
+        let mut outbox = OfflineOutbox::new();
+        assert!(outbox.push(message("m0"), 0).is_err());
+
+        outbox.enable(Duration::from_secs(60), Some(Arc::clone(&store))).unwrap();
+        outbox.push(message("m1"), 0).unwrap();
+        outbox.push(message("m2"), 30).unwrap();
+
+        // Restart: antrean dimuat ulang sesuai urutan
+        let mut restored = OfflineOutbox::new();
+        restored.enable(Duration::from_secs(60), Some(Arc::clone(&store))).unwrap();
+        assert_eq!(restored.len(), 2);
+
+        let (ready, expired) = restored.drain(70);
+        assert_eq!(expired[0].key.id, "m1");
+        assert_eq!(ready[0].message.key.id, "m2");
+        restored.requeue(ready);
+        assert_eq!(store.keys(OFFLINE_PREFIX).unwrap().len(), 1);
+
+        let (ready, _) = restored.drain(70);
+        restored.complete(&ready[0]).unwrap();
+        assert!(store.keys(OFFLINE_PREFIX).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,249 @@
+//! Pesanan untuk akun bisnis
+//!
+//! Pembeli yang checkout keranjang dari katalog mengirim `OrderMessage`
+//! berisi id pesanan, jumlah item, total, dan token. Isi keranjang tidak ada
+//! di pesan; ambil dengan `order_details` memakai token tersebut. Penjual
+//! menjawab dengan `OrderMessage` yang sama berstatus diterima atau ditolak.
+
+use crate::errors::*;
+use crate::iq;
+use crate::message_builder::quoted_participant;
+use crate::messages::{self, MessageContextInfo, OrderMessage, WebMessageInfo};
+use crate::node_protocol::Node;
+use crate::{utils, Event, Jid, WhatsAppClient};
+
+/// Nilai `OrderMessage.surface` untuk pesanan dari katalog
+const ORDER_SURFACE_CATALOG: u32 = 1;
+/// Ukuran gambar produk yang diminta pada detail pesanan
+const PRODUCT_IMAGE_SIZE: &str = "100";
+
+/// Status pesanan
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OrderStatus {
+    /// Pesanan baru dari pembeli
+    Inquiry,
+    Accepted,
+    Declined,
+}
+
+impl OrderStatus {
+    pub fn from_u32(value: u32) -> Option<OrderStatus> {
+        match value {
+            1 => Some(OrderStatus::Inquiry),
+            2 => Some(OrderStatus::Accepted),
+            3 => Some(OrderStatus::Declined),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        match self {
+            OrderStatus::Inquiry => 1,
+            OrderStatus::Accepted => 2,
+            OrderStatus::Declined => 3,
+        }
+    }
+}
+
+/// Ringkasan pesanan dari `OrderMessage`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub order_id: String,
+    pub token: String,
+    pub seller: Option<Jid>,
+    pub status: Option<OrderStatus>,
+    pub item_count: u32,
+    /// Total dalam seperseribu unit mata uang
+    pub total_amount_1000: Option<i64>,
+    pub currency: Option<String>,
+    /// Catatan pembeli
+    pub note: Option<String>,
+}
+
+impl Order {
+    pub fn from_message(order: &OrderMessage) -> Order {
+        Order {
+            order_id: order.order_id.clone(),
+            token: order.token.clone(),
+            seller: Jid::from_string(&order.seller_jid).ok(),
+            status: OrderStatus::from_u32(order.status),
+            item_count: order.item_count,
+            total_amount_1000: order.total_amount_1000,
+            currency: order.total_currency_code.clone(),
+            note: order.message.clone().filter(|note| !note.is_empty()),
+        }
+    }
+}
+
+/// Satu produk dalam detail pesanan
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderProduct {
+    pub id: String,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub price_1000: Option<i64>,
+    pub currency: Option<String>,
+    pub quantity: u32,
+}
+
+/// Isi keranjang dan total pesanan
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderDetails {
+    pub products: Vec<OrderProduct>,
+    pub subtotal_1000: Option<i64>,
+    pub total_1000: Option<i64>,
+    pub currency: Option<String>,
+}
+
+/// Event untuk pesanan baru dari pembeli
+pub fn order_event(web_message: &WebMessageInfo) -> Option<Event> {
+    let order = web_message.message.as_ref()?.order_message.as_ref()?;
+    if OrderStatus::from_u32(order.status) != Some(OrderStatus::Inquiry) || web_message.key.from_me {
+        return None;
+    }
+    Some(Event::OrderReceived {
+        order: Order::from_message(order),
+        message: web_message.clone(),
+    })
+}
+
+/// IQ detail pesanan
+pub fn details_node(order_id: &str, token: &str) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "fb:thrift_iq")
+        .attr("type", "get")
+        .attr("to", "s.whatsapp.net")
+        .attr("smax_id", "5")
+        .children(vec![Node::new("order").attr("op", "get").attr("id", order_id).children(vec![
+            Node::new("image_dimensions").children(vec![
+                Node::new("width").bytes(PRODUCT_IMAGE_SIZE.as_bytes().to_vec()),
+                Node::new("height").bytes(PRODUCT_IMAGE_SIZE.as_bytes().to_vec()),
+            ]),
+            Node::new("token").bytes(token.as_bytes().to_vec()),
+        ])])
+}
+
+fn child_text(node: &Node, tag: &str) -> Option<String> {
+    node.get_child(tag).and_then(|child| child.get_bytes()).map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+}
+
+fn child_number<T: std::str::FromStr>(node: &Node, tag: &str) -> Option<T> {
+    child_text(node, tag).and_then(|text| text.trim().parse().ok())
+}
+
+/// Membaca `<order><product>..</product><price>..</price></order>`
+pub fn parse_details(response: &Node) -> Result<OrderDetails> {
+    let order = response.get_child("order").ok_or("Order response without order node")?;
+    let products = order
+        .get_children()
+        .iter()
+        .filter(|child| child.tag == "product")
+        .map(|product| OrderProduct {
+            id: child_text(product, "id").unwrap_or_default(),
+            name: child_text(product, "name").unwrap_or_default(),
+            image_url: product.get_child("image").and_then(|image| child_text(image, "url")),
+            price_1000: child_number(product, "price"),
+            currency: child_text(product, "currency"),
+            quantity: child_number(product, "quantity").unwrap_or(1),
+        })
+        .collect();
+    let price = order.get_child("price");
+    Ok(OrderDetails {
+        products,
+        subtotal_1000: price.and_then(|price| child_number(price, "subtotal")),
+        total_1000: price.and_then(|price| child_number(price, "total")),
+        currency: price.and_then(|price| child_text(price, "currency")),
+    })
+}
+
+/// Update status untuk pesanan `order`, mengutip pesan pesanannya
+pub fn status_message(order: &WebMessageInfo, status: OrderStatus, note: Option<&str>, own_jid: Option<&Jid>) -> Result<messages::Message> {
+    let original = order.message.as_ref().and_then(|message| message.order_message.as_ref()).ok_or("Not an order message")?;
+    Ok(messages::Message {
+        order_message: Some(OrderMessage {
+            order_id: original.order_id.clone(),
+            item_count: original.item_count,
+            status: status.as_u32(),
+            surface: ORDER_SURFACE_CATALOG,
+            message: note.map(|note| note.to_string()),
+            order_title: original.order_title.clone(),
+            seller_jid: original.seller_jid.clone(),
+            token: original.token.clone(),
+            total_amount_1000: original.total_amount_1000,
+            total_currency_code: original.total_currency_code.clone(),
+            context_info: Some(MessageContextInfo {
+                stanza_id: Some(order.key.id.clone()),
+                participant: quoted_participant(order, own_jid),
+                quoted_message: order.message.clone().map(Box::new),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
+
+impl WhatsAppClient {
+    /// Isi keranjang pesanan; hanya untuk akun bisnis penjualnya
+    pub fn order_details(&self, order: &Order) -> Result<OrderDetails> {
+        let response = self.query(&details_node(&order.order_id, &order.token), iq::DEFAULT_QUERY_TIMEOUT)?;
+        parse_details(&response)
+    }
+
+    /// Menerima atau menolak pesanan dari pesan `order`. Mengembalikan id pesan.
+    pub fn send_order_status(&self, order: &WebMessageInfo, status: OrderStatus, note: Option<&str>) -> Result<String> {
+        if status == OrderStatus::Inquiry {
+            return Err("Order status update must accept or decline the order".into());
+        }
+        let buyer = Jid::from_string(&order.key.remote_jid)?;
+        let message = status_message(order, status, note, self.get_own_jid().as_ref())?;
+        self.send_message(&buyer, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::MessageKey;
+
+    #[test]
+    fn test_order_event_details_and_status() {
+        let incoming = WebMessageInfo {
+            key: MessageKey { remote_jid: "628111@s.whatsapp.net".to_string(), from_me: false, id: "ORDER1".to_string(), participant: None },
+            message: Some(messages::Message {
+                order_message: Some(OrderMessage {
+                    order_id: "9001".to_string(),
+                    item_count: 2,
+                    status: 1,
+                    seller_jid: "628999@s.whatsapp.net".to_string(),
+                    token: "dG9rZW4=".to_string(),
+                    total_amount_1000: Some(150_000_000),
+                    total_currency_code: Some("IDR".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        match order_event(&incoming) {
+            Some(Event::OrderReceived { order, .. }) => {
+                assert_eq!((order.order_id.as_str(), order.item_count, order.status), ("9001", 2, Some(OrderStatus::Inquiry)));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        let text = |tag: &str, value: &str| Node::new(tag).bytes(value.as_bytes().to_vec());
+        let response = Node::new("iq").children(vec![Node::new("order").children(vec![
+            Node::new("product").children(vec![text("id", "P1"), text("name", "Kopi"), text("price", "75000000"), text("quantity", "2")]),
+            Node::new("price").children(vec![text("total", "150000000"), text("currency", "IDR")]),
+        ])]);
+        let details = parse_details(&response).unwrap();
+        assert_eq!(details.products[0].quantity, 2);
+        assert_eq!(details.total_1000, Some(150_000_000));
+
+        let reply = status_message(&incoming, OrderStatus::Accepted, Some("Diproses"), None).unwrap().order_message.unwrap();
+        assert_eq!((reply.status, reply.token.as_str()), (2, "dG9rZW4="));
+        assert_eq!(reply.context_info.unwrap().stanza_id.as_deref(), Some("ORDER1"));
+    }
+}
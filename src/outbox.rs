@@ -0,0 +1,700 @@
+//! Antrian pesan keluar yang menunggu kunci enkripsi
+//!
+//! Pesan ke penerima yang belum memiliki sesi (atau grup yang sender key-nya
+//! belum didistribusikan) tidak langsung ditolak. Pesan disimpan di antrian,
+//! kunci diminta ke server, dan antrian dikirim otomatis begitu kunci tersedia.
+//!
+//! Pesan dienkripsi per perangkat (lihat `devices`): untuk setiap penerima
+//! diambil dulu daftar perangkatnya (`usync`), lalu kunci perangkat yang belum
+//! memiliki sesi (`encrypt`). Penerima siap setelah semua perangkatnya
+//! memiliki sesi; stanza relay menyertakan daftar perangkat tersebut. Sesi
+//! Signal dibuat dari prekey bundle di balasan `encrypt` (lihat `signal`).
+//!
+//! Alur untuk grup: ambil daftar peserta (`w:g2`), lalu lanjut seperti di atas
+//! untuk setiap peserta. Grup dianggap siap setelah semua peserta siap.
+//!
+//! Daftar siaran dan status (`@broadcast`) memakai alur yang sama dengan daftar
+//! penerima dari pemanggil: pesan dikirim setelah semua penerima memiliki sesi.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::errors::*;
+use crate::latency::{LatencyTracker, Stage};
+use crate::messages::WebMessageInfo;
+use crate::names::{self, SharedNameResolver};
+use crate::node_protocol::Node;
+use crate::routing::{self, NodeContext};
+use crate::broadcast;
+use crate::devices::{self, DeviceCache};
+use crate::session::Session;
+use crate::signal::{self, SignalStore};
+use crate::{utils, Event, Jid};
+
+/// Permintaan kunci yang sedang berjalan
+#[derive(Debug, Clone, PartialEq)]
+enum KeyRequest {
+    /// Daftar perangkat untuk daftar user
+    Devices(Vec<String>),
+    /// Prekey bundle untuk daftar perangkat
+    Keys(Vec<String>),
+    /// Daftar peserta grup
+    GroupParticipants(String),
+}
+
+/// Hasil pemrosesan balasan permintaan kunci
+#[derive(Debug, Default)]
+pub struct KeyFetchOutcome {
+    /// Pesan yang sekarang bisa dikirim (termasuk kirim ulang karena retry
+    /// receipt), beserta perangkat penerimanya
+    pub ready: Vec<(WebMessageInfo, Vec<String>)>,
+    /// Permintaan lanjutan yang harus dikirim ke server
+    pub requests: Vec<Node>,
+    /// Pesan broadcast (daftar siaran/status) yang sekarang bisa dikirim, beserta perangkat penerimanya
+    pub broadcasts: Vec<(WebMessageInfo, Vec<String>)>,
+    /// Pesan yang dibuang karena kunci tidak bisa didapat, beserta alasannya
+    pub failed: Vec<(WebMessageInfo, String)>,
+}
+
+/// Pesan broadcast yang menunggu sesi penerima
+struct PendingBroadcast {
+    message: WebMessageInfo,
+    recipients: Vec<String>,
+    waiting: HashSet<String>,
+}
+
+/// State sesi per perangkat penerima dan antrian pesan yang menunggu
+#[derive(Default)]
+pub struct PendingOutbox {
+    /// Perangkat yang sudah memiliki sesi
+    sessions: HashSet<String>,
+    devices: DeviceCache,
+    /// grup -> peserta, selama sender key masih berlaku
+    groups: HashMap<String, Vec<String>>,
+    queued: HashMap<String, Vec<WebMessageInfo>>,
+    in_flight: HashMap<String, KeyRequest>,
+    /// user, perangkat atau grup yang permintaannya sedang berjalan
+    fetching: HashSet<String>,
+    /// tujuan (user atau grup) -> user yang belum siap
+    waiting: HashMap<String, HashSet<String>>,
+    /// id pesan -> broadcast yang menunggu sesi penerima
+    broadcast_waiting: HashMap<String, PendingBroadcast>,
+    /// perangkat -> pesan yang dikirim ulang setelah sesinya dibuat ulang
+    retrying: HashMap<String, Vec<WebMessageInfo>>,
+}
+
+impl PendingOutbox {
+    pub fn new() -> Self {
+        PendingOutbox::default()
+    }
+
+    /// Perangkat tujuan pesan ke `to`; `None` jika kunci belum lengkap
+    pub fn recipients(&self, to: &Jid) -> Option<Vec<String>> {
+        let jid = to.to_string();
+        if to.is_group() {
+            self.fanout(self.groups.get(&jid)?)
+        } else {
+            self.fanout(&[jid])
+        }
+    }
+
+    /// Apakah pesan ke `to` bisa langsung dikirim
+    pub fn is_ready(&self, to: &Jid) -> bool {
+        self.recipients(to).is_some()
+    }
+
+    /// Jumlah pesan yang sedang menunggu kunci
+    pub fn queued_len(&self) -> usize {
+        self.queued.values().map(|messages| messages.len()).sum()
+    }
+
+    pub fn devices(&self) -> &DeviceCache {
+        &self.devices
+    }
+
+    pub fn set_devices(&mut self, user: &str, devices: Vec<u32>) {
+        self.devices.set(user, devices);
+    }
+
+    /// Menandai sesi dengan perangkat `jid` sudah ada (misalnya setelah
+    /// menerima pesan darinya)
+    pub fn mark_session(&mut self, jid: &str) {
+        self.sessions.insert(jid.to_string());
+    }
+
+    /// Membuang sesi semua perangkat `user` (identitas berubah); grup harus
+    /// mendistribusikan ulang sender key
+    pub fn invalidate_session(&mut self, user: &str) {
+        let user = devices::split_device_jid(user).0;
+        self.sessions.retain(|jid| devices::split_device_jid(jid).0 != user);
+        self.groups.clear();
+    }
+
+    /// Membuang daftar perangkat `user` (perangkat ditambah/dihapus); grup
+    /// yang berisi user ini mendistribusikan ulang sender key
+    pub fn invalidate_devices(&mut self, user: &str) {
+        self.devices.invalidate(user);
+        self.groups.retain(|_, members| !members.iter().any(|member| member == user));
+    }
+
+    /// Mengirim ulang `message` ke satu perangkat yang gagal mendekripsinya;
+    /// untuk grup sender key didistribusikan ulang pada pesan berikutnya.
+    /// Jika sesi baru sudah dibuat (`session_ready`) stanza relay langsung
+    /// dikembalikan, jika belum permintaan prekey perangkat tersebut.
+    pub fn enqueue_retry(&mut self, device: &str, message: WebMessageInfo, session_ready: bool) -> Result<Vec<Node>> {
+        self.groups.remove(&message.key.remote_jid);
+        if session_ready {
+            self.sessions.insert(device.to_string());
+            return Ok(vec![crate::relay_node(&message, &[device.to_string()])?]);
+        }
+
+        self.sessions.remove(device);
+        self.retrying.entry(device.to_string()).or_default().push(message);
+        if !self.fetching.insert(device.to_string()) {
+            return Ok(Vec::new());
+        }
+        Ok(vec![self.request(KeyRequest::Keys(vec![device.to_string()]))])
+    }
+
+    fn user_ready(&self, user: &str) -> bool {
+        match self.devices.device_jids(user) {
+            Some(jids) => !jids.is_empty() && jids.iter().all(|jid| self.sessions.contains(jid)),
+            None => false,
+        }
+    }
+
+    /// Apakah masih ada permintaan berjalan untuk `user` atau perangkatnya
+    fn user_pending(&self, user: &str) -> bool {
+        self.fetching.contains(user)
+            || self.devices.device_jids(user).is_some_and(|jids| jids.iter().any(|jid| self.fetching.contains(jid)))
+    }
+
+    /// Semua perangkat `users` jika semuanya memiliki sesi
+    fn fanout(&self, users: &[String]) -> Option<Vec<String>> {
+        let mut jids = Vec::new();
+        for user in users {
+            if !self.user_ready(user) {
+                return None;
+            }
+            jids.extend(self.devices.device_jids(user)?);
+        }
+        Some(jids)
+    }
+
+    /// Permintaan daftar perangkat atau prekey untuk `users` yang belum siap
+    fn fetch_missing<'a>(&mut self, users: impl IntoIterator<Item = &'a String>) -> Vec<Node> {
+        let mut device_queries = Vec::new();
+        let mut key_queries = Vec::new();
+        for user in users {
+            match self.devices.device_jids(user) {
+                None => {
+                    if self.fetching.insert(user.clone()) {
+                        device_queries.push(user.clone());
+                    }
+                }
+                Some(jids) => {
+                    for jid in jids {
+                        if !self.sessions.contains(&jid) && self.fetching.insert(jid.clone()) {
+                            key_queries.push(jid);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut requests = Vec::new();
+        if !device_queries.is_empty() {
+            requests.push(self.request(KeyRequest::Devices(device_queries)));
+        }
+        if !key_queries.is_empty() {
+            requests.push(self.request(KeyRequest::Keys(key_queries)));
+        }
+        requests
+    }
+
+    /// User yang belum siap beserta permintaan yang harus dikirim
+    fn require(&mut self, users: &[String]) -> (HashSet<String>, Vec<Node>) {
+        let missing: HashSet<String> = users.iter().filter(|user| !self.user_ready(user)).cloned().collect();
+        let requests = self.fetch_missing(&missing);
+        (missing, requests)
+    }
+
+    /// Menyimpan pesan ke antrian. Mengembalikan permintaan (daftar perangkat,
+    /// prekey atau peserta grup) yang belum berjalan untuk penerima ini.
+    pub fn enqueue(&mut self, to: &Jid, message: WebMessageInfo) -> Vec<Node> {
+        let jid = to.to_string();
+        self.queued.entry(jid.clone()).or_default().push(message);
+
+        if self.waiting.contains_key(&jid) || (to.is_group() && self.fetching.contains(&jid)) {
+            return Vec::new();
+        }
+        if to.is_group() {
+            match self.groups.get(&jid).cloned() {
+                Some(members) => {
+                    let (missing, requests) = self.require(&members);
+                    self.waiting.insert(jid, missing);
+                    requests
+                }
+                None => {
+                    self.fetching.insert(jid.clone());
+                    vec![self.request(KeyRequest::GroupParticipants(jid))]
+                }
+            }
+        } else {
+            let (missing, requests) = self.require(&[jid.clone()]);
+            self.waiting.insert(jid, missing);
+            requests
+        }
+    }
+
+    /// Menyiapkan pesan broadcast ke `recipients`. Mengembalikan node yang
+    /// harus dikirim sekarang: stanza relay jika semua sesi sudah ada, atau
+    /// permintaan kunci untuk penerima yang belum siap.
+    pub fn enqueue_broadcast(&mut self, recipients: &[String], message: WebMessageInfo) -> Result<Vec<Node>> {
+        if let Some(devices) = self.fanout(recipients) {
+            return Ok(vec![broadcast::relay_node(&message, &devices)?]);
+        }
+
+        let (missing, requests) = self.require(recipients);
+        self.broadcast_waiting.insert(
+            message.key.id.clone(),
+            PendingBroadcast {
+                message,
+                recipients: recipients.to_vec(),
+                waiting: missing,
+            },
+        );
+        Ok(requests)
+    }
+
+    fn request(&mut self, request: KeyRequest) -> Node {
+        let id = utils::generate_message_id();
+        let node = match request {
+            KeyRequest::Devices(ref users) => devices::device_query_node(&id, users),
+            KeyRequest::Keys(ref jids) => signal::bundle_query_node(&id, jids),
+            KeyRequest::GroupParticipants(ref group) => Node::new("iq")
+                .attr("id", &id)
+                .attr("xmlns", "w:g2")
+                .attr("type", "get")
+                .attr("to", group)
+                .children(vec![Node::new("query").attr("request", "interactive")]),
+        };
+        self.in_flight.insert(id, request);
+        node
+    }
+
+    /// Apakah node adalah balasan untuk permintaan kunci kita
+    pub fn handles(&self, node: &Node) -> bool {
+        node.tag == "iq" && node.get_attr("id").map_or(false, |id| self.in_flight.contains_key(id))
+    }
+
+    /// Memproses balasan `iq` (result atau error) untuk permintaan kunci
+    pub fn handle_response(&mut self, node: &Node) -> KeyFetchOutcome {
+        self.handle_response_rejecting(node, &HashSet::new())
+    }
+
+    /// Seperti `handle_response`, tetapi perangkat di `rejected` (sesi Signal
+    /// gagal dibuat) diperlakukan seperti perangkat tanpa prekey
+    pub fn handle_response_rejecting(&mut self, node: &Node, rejected: &HashSet<String>) -> KeyFetchOutcome {
+        let mut outcome = KeyFetchOutcome::default();
+        let request = match node.get_attr("id").and_then(|id| self.in_flight.remove(id)) {
+            Some(request) => request,
+            None => return outcome,
+        };
+        let is_error = node.get_attr("type") == Some("error");
+
+        match request {
+            KeyRequest::Devices(users) => {
+                let lists = if is_error { Vec::new() } else { devices::parse_device_response(node) };
+                for user in users {
+                    self.fetching.remove(&user);
+                    // Tanpa daftar perangkat: hanya perangkat utama
+                    let wanted = Jid::from_string(&user).map(|jid| jid.to_non_ad()).ok();
+                    let list = lists
+                        .iter()
+                        .find(|(found, _)| wanted.as_ref() == Some(found))
+                        .map(|(_, list)| list.clone())
+                        .unwrap_or_else(|| vec![devices::PRIMARY_DEVICE]);
+                    self.devices.set(&user, list);
+                }
+            }
+            KeyRequest::Keys(jids) => {
+                // Decoder menulis `s.whatsapp.net` sebagai `c.us`; bandingkan JID yang sudah dinormalkan
+                let fetched: HashSet<String> = if is_error {
+                    HashSet::new()
+                } else {
+                    node.get_child("list")
+                        .map(|list| {
+                            list.get_children()
+                                .iter()
+                                .filter(|user| user.tag == "user" && user.get_child("error").is_none())
+                                .filter_map(|user| user.attr_jid("jid").ok())
+                                .map(|jid| jid.to_string())
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+
+                for jid in jids {
+                    self.fetching.remove(&jid);
+                    let available = fetched.contains(&jid) && !rejected.contains(&jid);
+                    for message in self.retrying.remove(&jid).unwrap_or_default() {
+                        if available {
+                            outcome.ready.push((message, vec![jid.clone()]));
+                        } else {
+                            outcome.failed.push((message, format!("No encryption keys available to retry for {}", jid)));
+                        }
+                    }
+                    if available {
+                        self.sessions.insert(jid);
+                    } else {
+                        // Perangkat tanpa prekey dilewati; user tanpa perangkat tersisa gagal
+                        self.devices.remove_device(&jid);
+                    }
+                }
+            }
+            KeyRequest::GroupParticipants(group) => {
+                self.fetching.remove(&group);
+                if is_error {
+                    self.fail(&group, "Failed to fetch group participants", &mut outcome);
+                    return outcome;
+                }
+
+                let participants: Vec<String> = node
+                    .get_child("group")
+                    .map(|info| {
+                        info.get_children()
+                            .iter()
+                            .filter(|child| child.tag == "participant")
+                            .filter_map(|child| child.attr_jid("jid").ok())
+                            .map(|jid| jid.to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                self.waiting.insert(group.clone(), participants.iter().cloned().collect());
+                self.groups.insert(group, participants);
+            }
+        }
+
+        self.update_waiting(&mut outcome);
+        outcome
+    }
+
+    fn fail(&mut self, jid: &str, reason: &str, outcome: &mut KeyFetchOutcome) {
+        self.waiting.remove(jid);
+        for message in self.queued.remove(jid).unwrap_or_default() {
+            outcome.failed.push((message, reason.to_string()));
+        }
+    }
+
+    /// Melanjutkan permintaan untuk user yang menunggu, lalu mengirim tujuan
+    /// yang semua usernya sudah siap
+    fn update_waiting(&mut self, outcome: &mut KeyFetchOutcome) {
+        let users: HashSet<String> = self
+            .waiting
+            .values()
+            .chain(self.broadcast_waiting.values().map(|broadcast| &broadcast.waiting))
+            .flatten()
+            .cloned()
+            .collect();
+        outcome.requests.extend(self.fetch_missing(&users));
+
+        let mut done = Vec::new();
+        let mut failed = Vec::new();
+        // User tanpa perangkat yang berkunci; daftarnya diambil ulang lain kali
+        let mut unreachable = Vec::new();
+        for (destination, waiting) in self.waiting.iter() {
+            let waiting: Vec<&String> = waiting.iter().filter(|user| !self.user_ready(user)).collect();
+            if waiting.is_empty() {
+                done.push(destination.clone());
+            } else if waiting.iter().all(|user| !self.user_pending(user)) {
+                // Semua permintaan selesai tetapi ada user tanpa kunci
+                failed.push(destination.clone());
+                unreachable.extend(waiting.into_iter().cloned());
+            }
+        }
+        for destination in done {
+            self.waiting.remove(&destination);
+            let to = match Jid::from_string(&destination) {
+                Ok(to) => to,
+                Err(_) => continue,
+            };
+            let devices = self.recipients(&to).unwrap_or_default();
+            for message in self.queued.remove(&destination).unwrap_or_default() {
+                outcome.ready.push((message, devices.clone()));
+            }
+        }
+        for destination in failed {
+            let reason = if destination.ends_with("@g.us") {
+                "No encryption keys available for some group participants"
+            } else {
+                "No encryption keys available for recipient"
+            };
+            self.fail(&destination, reason, outcome);
+        }
+
+        let mut done = Vec::new();
+        let mut failed = Vec::new();
+        for (id, broadcast) in self.broadcast_waiting.iter() {
+            let waiting: Vec<&String> = broadcast.waiting.iter().filter(|user| !self.user_ready(user)).collect();
+            if waiting.is_empty() {
+                done.push(id.clone());
+            } else if waiting.iter().all(|user| !self.user_pending(user)) {
+                failed.push(id.clone());
+                unreachable.extend(waiting.into_iter().cloned());
+            }
+        }
+        for id in done {
+            if let Some(broadcast) = self.broadcast_waiting.remove(&id) {
+                let devices = self.fanout(&broadcast.recipients).unwrap_or_default();
+                outcome.broadcasts.push((broadcast.message, devices));
+            }
+        }
+        for id in failed {
+            if let Some(broadcast) = self.broadcast_waiting.remove(&id) {
+                outcome.failed.push((broadcast.message, "No encryption keys available for some broadcast recipients".to_string()));
+            }
+        }
+        for user in unreachable {
+            self.devices.invalidate(&user);
+        }
+    }
+}
+
+/// Handler balasan `iq` untuk permintaan kunci: membuat sesi Signal dari
+/// prekey bundle lalu mengirim antrian yang siap
+pub fn key_response_handler(
+    outbox: Arc<Mutex<PendingOutbox>>,
+    signal: Arc<Mutex<SignalStore>>,
+    session: Arc<Mutex<Option<Session>>>,
+    latency: Arc<Mutex<LatencyTracker>>,
+    names: SharedNameResolver,
+) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if !outbox.lock().unwrap().handles(node) {
+            return Ok(());
+        }
+        // Balasan tanpa bundle (mis. server uji) dianggap sesi sudah ada
+        let failures = match session.lock().unwrap().as_ref() {
+            Some(local) => signal::process_response(&mut signal.lock().unwrap(), local, node),
+            None => Vec::new(),
+        };
+        let rejected: HashSet<String> = failures.iter().map(|(device, _)| device.clone()).collect();
+        for (device, error) in failures {
+            ctx.emit(Event::Error(format!("No Signal session with {}: {}", device, error)));
+        }
+        let outcome = outbox.lock().unwrap().handle_response_rejecting(node, &rejected);
+
+        for request in &outcome.requests {
+            ctx.send_node(request)?;
+        }
+        for (message, devices) in &outcome.ready {
+            let node = signal::seal_relay(crate::relay_node(message, devices)?, &signal, &session)?;
+            latency.lock().unwrap().mark(&message.key.id, Stage::Encrypted);
+            ctx.send_node(&node)?;
+            latency.lock().unwrap().mark(&message.key.id, Stage::Written);
+        }
+        for (message, recipients) in &outcome.broadcasts {
+            let node = signal::seal_relay(broadcast::relay_node(message, recipients)?, &signal, &session)?;
+            latency.lock().unwrap().mark(&message.key.id, Stage::Encrypted);
+            ctx.send_node(&node)?;
+            latency.lock().unwrap().mark(&message.key.id, Stage::Written);
+        }
+        for (message, reason) in outcome.failed {
+            let recipient = names::display_str(names.lock().unwrap().as_ref(), &message.key.remote_jid);
+            ctx.emit(Event::Error(format!("Message {} to {} dropped: {}", message.key.id, recipient, reason)));
+        }
+        Ok(())
+    }
+}
+
+/// Handler `notification type="encrypt"`: identitas kontak berubah, sesi lama dibuang
+pub fn identity_change_handler(outbox: Arc<Mutex<PendingOutbox>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if let Some(ack) = routing::ack_for(node) {
+            ctx.send_node(&ack)?;
+        }
+        if node.get_child("identity").is_some() {
+            if let Some(from) = node.get_attr("from") {
+                outbox.lock().unwrap().invalidate_session(from);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Handler `message`: pesan masuk dari kontak membuktikan sesi dengannya sudah ada
+pub fn inbound_session_handler(outbox: Arc<Mutex<PendingOutbox>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if let Ok(sender) = node.attr_jid("participant").or_else(|_| node.attr_jid("from")) {
+            if !sender.is_group() {
+                outbox.lock().unwrap().mark_session(&sender.to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(to: &str, id: &str) -> WebMessageInfo {
+        WebMessageInfo {
+            key: crate::messages::MessageKey {
+                remote_jid: to.to_string(),
+                from_me: true,
+                id: id.to_string(),
+                participant: None,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn result_for(request: &Node, child: Node) -> Node {
+        Node::new("iq")
+            .attr("id", request.get_attr("id").unwrap())
+            .attr("type", "result")
+            .children(vec![child])
+    }
+
+    fn device_list(user: &str, ids: &[&str]) -> Node {
+        Node::new("usync").children(vec![Node::new("list").children(vec![Node::new("user").attr("jid", user).children(vec![
+            Node::new("devices").children(vec![Node::new("device-list").children(ids.iter().map(|id| Node::new("device").attr("id", id)).collect())]),
+        ])])])
+    }
+
+    fn key_list(jids: &[&str]) -> Node {
+        Node::new("list").children(jids.iter().map(|jid| Node::new("user").attr("jid", jid)).collect())
+    }
+
+    #[test]
+    fn test_broadcast_waits_for_all_recipients() {
+        let mut outbox = PendingOutbox::new();
+        outbox.set_devices("1@s.whatsapp.net", vec![0]);
+        outbox.mark_session("1@s.whatsapp.net");
+        let recipients = vec!["1@s.whatsapp.net".to_string(), "2@s.whatsapp.net".to_string()];
+
+        let requests = outbox.enqueue_broadcast(&recipients, message(crate::status::STATUS_BROADCAST, "s1")).unwrap();
+        assert_eq!(requests[0].get_attr("xmlns"), Some("usync"));
+
+        let outcome = outbox.handle_response(&result_for(&requests[0], device_list("2@s.whatsapp.net", &["0"])));
+        assert_eq!(outcome.requests[0].get_attr("xmlns"), Some("encrypt"));
+        let outcome = outbox.handle_response(&result_for(&outcome.requests[0], key_list(&["2@s.whatsapp.net"])));
+        assert_eq!(outcome.broadcasts.len(), 1);
+        assert_eq!(outcome.broadcasts[0].1, recipients);
+
+        // Semua sesi sudah ada: langsung stanza relay
+        let relay = outbox.enqueue_broadcast(&recipients, message(crate::status::STATUS_BROADCAST, "s2")).unwrap();
+        assert_eq!(relay[0].get_attr("type"), Some("relay"));
+    }
+
+    #[test]
+    fn test_user_messages_flush_after_keys_arrive() {
+        let mut outbox = PendingOutbox::new();
+        let user = Jid::user("628111");
+
+        let requests = outbox.enqueue(&user, message(&user.to_string(), "m1"));
+        assert_eq!(requests.len(), 1);
+        // Permintaan kedua untuk penerima yang sama tidak mengirim fetch ulang
+        assert!(outbox.enqueue(&user, message(&user.to_string(), "m2")).is_empty());
+        assert_eq!(outbox.queued_len(), 2);
+
+        let response = result_for(&requests[0], device_list("628111@s.whatsapp.net", &["0", "2"]));
+        assert!(outbox.handles(&response));
+        let outcome = outbox.handle_response(&response);
+        assert!(outcome.ready.is_empty());
+        let keys = outcome.requests[0].get_child("key").unwrap();
+        assert_eq!(keys.get_children().len(), 2);
+
+        let outcome = outbox.handle_response(&result_for(&outcome.requests[0], key_list(&["628111@s.whatsapp.net", "628111:2@s.whatsapp.net"])));
+        assert_eq!(outcome.ready.len(), 2);
+        assert_eq!(outcome.ready[0].1, vec!["628111@s.whatsapp.net".to_string(), "628111:2@s.whatsapp.net".to_string()]);
+        assert!(outbox.is_ready(&user));
+        assert_eq!(outbox.queued_len(), 0);
+    }
+
+    #[test]
+    fn test_group_waits_for_participant_sessions() {
+        let mut outbox = PendingOutbox::new();
+        let group = Jid::group("123-456");
+        let member = "628222@s.whatsapp.net";
+
+        let request = outbox.enqueue(&group, message(&group.to_string(), "g1")).remove(0);
+        let response = result_for(&request, Node::new("group").children(vec![Node::new("participant").attr("jid", member)]));
+        let outcome = outbox.handle_response(&response);
+        assert!(outcome.ready.is_empty());
+        assert_eq!(outcome.requests.len(), 1);
+
+        let outcome = outbox.handle_response(&result_for(&outcome.requests[0], device_list(member, &["0"])));
+        let outcome = outbox.handle_response(&result_for(&outcome.requests[0], key_list(&[member])));
+        assert_eq!(outcome.ready.len(), 1);
+        assert!(outbox.is_ready(&group));
+    }
+
+    #[test]
+    fn test_device_change_refetches_device_list() {
+        let mut outbox = PendingOutbox::new();
+        let user = Jid::user("628444");
+        outbox.set_devices(&user.to_string(), vec![0]);
+        outbox.mark_session(&user.to_string());
+        assert_eq!(outbox.recipients(&user), Some(vec![user.to_string()]));
+
+        outbox.invalidate_devices(&user.to_string());
+        assert!(!outbox.is_ready(&user));
+        let requests = outbox.enqueue(&user, message(&user.to_string(), "m1"));
+        assert_eq!(requests[0].get_attr("xmlns"), Some("usync"));
+
+        // Perangkat utama sudah punya sesi; hanya perangkat baru yang diminta kuncinya
+        let outcome = outbox.handle_response(&result_for(&requests[0], device_list("628444@s.whatsapp.net", &["0", "5"])));
+        let keys = outcome.requests[0].get_child("key").unwrap();
+        assert_eq!(keys.get_children()[0].get_attr("jid"), Some("628444:5@s.whatsapp.net"));
+
+        // Perangkat tanpa prekey dilewati
+        let outcome = outbox.handle_response(&result_for(&outcome.requests[0], key_list(&[])));
+        assert_eq!(outcome.ready.len(), 1);
+        assert_eq!(outcome.ready[0].1, vec![user.to_string()]);
+    }
+
+    #[test]
+    fn test_server_device_list_fans_out_to_all_devices() {
+        let mut outbox = PendingOutbox::new();
+        let user = Jid::user("628666");
+
+        let request = outbox.enqueue(&user, message(&user.to_string(), "m1")).remove(0);
+        let outcome = outbox.handle_response(&result_for(&request, device_list("628666@s.whatsapp.net", &["0", "4"])));
+        let outcome = outbox.handle_response(&result_for(&outcome.requests[0], key_list(&["628666@c.us", "628666:4@c.us"])));
+        assert_eq!(outcome.ready[0].1, vec![user.to_string(), "628666:4@s.whatsapp.net".to_string()]);
+    }
+
+    #[test]
+    fn test_decoded_user_jids_match_requests() {
+        let mut outbox = PendingOutbox::new();
+        let user = Jid::user("628555");
+
+        // Balasan dari decoder memakai `c.us` untuk `s.whatsapp.net`
+        let request = outbox.enqueue(&user, message(&user.to_string(), "m1")).remove(0);
+        let outcome = outbox.handle_response(&result_for(&request, device_list("628555@c.us", &["0"])));
+        let outcome = outbox.handle_response(&result_for(&outcome.requests[0], key_list(&["628555@c.us"])));
+        assert_eq!(outcome.ready.len(), 1);
+        assert_eq!(outcome.ready[0].1, vec![user.to_string()]);
+        assert!(outbox.is_ready(&user));
+    }
+
+    #[test]
+    fn test_missing_keys_drop_messages() {
+        let mut outbox = PendingOutbox::new();
+        let user = Jid::user("628333");
+
+        let request = outbox.enqueue(&user, message(&user.to_string(), "m1")).remove(0);
+        let error = |request: &Node| Node::new("iq").attr("id", request.get_attr("id").unwrap()).attr("type", "error");
+        // Tanpa daftar perangkat: hanya perangkat utama yang diminta kuncinya
+        let outcome = outbox.handle_response(&error(&request));
+        assert_eq!(outcome.requests.len(), 1);
+        let outcome = outbox.handle_response(&error(&outcome.requests[0]));
+
+        assert_eq!(outcome.failed.len(), 1);
+        assert!(!outbox.is_ready(&user));
+        assert_eq!(outbox.queued_len(), 0);
+    }
+}
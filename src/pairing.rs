@@ -0,0 +1,360 @@
+//! Login dengan pairing code (nomor telepon) tanpa QR code
+//!
+//! Alur companion registration:
+//! 1. Client membuat kode 8 karakter dan kunci ephemeral, lalu mengirim
+//!    `link_code_companion_reg` stage `companion_hello` berisi kunci ephemeral
+//!    yang dibungkus dengan kode tersebut.
+//! 2. Server membalas dengan `link_code_pairing_ref`; kode baru valid setelah
+//!    langkah ini dan ditampilkan ke pengguna.
+//! 3. Setelah kode dimasukkan di ponsel, server mengirim notifikasi
+//!    `primary_hello` berisi kunci ephemeral ponsel (dibungkus dengan kode yang sama).
+//! 4. Client menurunkan shared secret dan mengirim `companion_finish` berisi
+//!    key bundle terenkripsi. Server lalu menyelesaikan pairing seperti QR.
+
+use std::num::NonZeroU32;
+
+use openssl::derive::Deriver;
+use openssl::pkey::{Id, PKey};
+use openssl::symm::{decrypt, encrypt, Cipher};
+use ring::rand::SecureRandom;
+use ring::{aead, agreement, hkdf, pbkdf2, rand};
+
+use crate::device_props::DeviceProps;
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::session::Session;
+use crate::utils;
+
+/// Alfabet kode pairing (tanpa karakter yang mudah tertukar: 0, O, I, U)
+const PAIRING_CODE_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTVWXYZ";
+/// Panjang kode pairing
+pub const PAIRING_CODE_LENGTH: usize = 8;
+/// Iterasi PBKDF2 untuk kunci pembungkus (2 << 16)
+const PAIRING_PBKDF2_ITERATIONS: u32 = 2 << 16;
+
+const KEY_BUNDLE_INFO: &[u8] = b"link_code_pairing_key_bundle_encryption_key";
+const ADV_SECRET_INFO: &[u8] = b"adv_secret";
+
+/// Tahapan alur pairing code
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PairingStage {
+    /// Menunggu balasan companion_hello dari server
+    HelloSent,
+    /// Kode sudah valid, menunggu pengguna memasukkan kode di ponsel
+    WaitingForPrimary,
+    /// companion_finish sudah dikirim
+    Finished,
+}
+
+/// State alur login dengan pairing code
+pub struct PairingCodeFlow {
+    phone_number: String,
+    code: String,
+    ephemeral_private: Option<agreement::EphemeralPrivateKey>,
+    ephemeral_public: Vec<u8>,
+    pairing_ref: Option<Vec<u8>>,
+    hello_iq_id: Option<String>,
+    stage: PairingStage,
+}
+
+impl PairingCodeFlow {
+    /// Memulai alur baru untuk nomor telepon tertentu
+    pub fn new(phone_number: &str) -> Result<Self> {
+        let phone_number = utils::format_phone_number(phone_number);
+        if phone_number.len() < 7 {
+            return Err("Invalid phone number for pairing code".into());
+        }
+
+        let rng = rand::SystemRandom::new();
+        let ephemeral_private = agreement::EphemeralPrivateKey::generate(&agreement::X25519, &rng)
+            .map_err(|_| "Failed to generate pairing ephemeral key")?;
+        let ephemeral_public = ephemeral_private
+            .compute_public_key()
+            .map_err(|_| "Failed to compute pairing ephemeral key")?
+            .as_ref()
+            .to_vec();
+
+        Ok(PairingCodeFlow {
+            phone_number,
+            code: generate_pairing_code()?,
+            ephemeral_private: Some(ephemeral_private),
+            ephemeral_public,
+            pairing_ref: None,
+            hello_iq_id: None,
+            stage: PairingStage::HelloSent,
+        })
+    }
+
+    /// Kode pairing dalam format tampilan "ABCD-EFGH"
+    pub fn display_code(&self) -> String {
+        format!("{}-{}", &self.code[..4], &self.code[4..])
+    }
+
+    pub fn stage(&self) -> PairingStage {
+        self.stage
+    }
+
+    /// Membangun IQ `companion_hello`; `device` menentukan nama di daftar Perangkat Tertaut
+    pub fn companion_hello(&mut self, session: &Session, device: &DeviceProps) -> Result<Node> {
+        let wrapped_ephemeral = wrap_with_code(&self.code, &self.ephemeral_public)?;
+        let iq_id = utils::generate_message_id();
+        self.hello_iq_id = Some(iq_id.clone());
+
+        let reg = Node::new("link_code_companion_reg")
+            .attr("jid", &format!("{}@s.whatsapp.net", self.phone_number))
+            .attr("stage", "companion_hello")
+            .attr("should_show_push_notification", "true")
+            .children(vec![
+                Node::new("link_code_pairing_wrapped_companion_ephemeral_pub").bytes(wrapped_ephemeral),
+                Node::new("companion_server_auth_key_pub").bytes(session.noise_key_pair.public_key.clone()),
+                Node::new("companion_platform_id").bytes(device.platform.id().to_string().into_bytes()),
+                Node::new("companion_platform_display").bytes(device.display_name().into_bytes()),
+                Node::new("link_code_pairing_nonce").bytes(b"0".to_vec()),
+            ]);
+
+        Ok(Node::new("iq")
+            .attr("id", &iq_id)
+            .attr("to", "s.whatsapp.net")
+            .attr("type", "set")
+            .attr("xmlns", "md")
+            .children(vec![reg]))
+    }
+
+    /// Cek apakah node ini bagian dari alur pairing code
+    pub fn handles(&self, node: &Node) -> bool {
+        match node.tag.as_str() {
+            "iq" => node.get_attr("id").is_some() && node.get_attr("id") == self.hello_iq_id.as_deref(),
+            "notification" => node.get_attr("type") == Some("link_code_companion_reg"),
+            _ => false,
+        }
+    }
+
+    /// Memproses balasan server atas companion_hello.
+    /// Mengembalikan kode pairing yang siap ditampilkan ke pengguna.
+    pub fn handle_hello_response(&mut self, node: &Node) -> Result<String> {
+        if node.get_attr("type") == Some("error") {
+            let code = node.get_child("error").and_then(|e| e.get_attr("code")).unwrap_or("unknown");
+            return Err(Error {
+                kind: ErrorKind::AuthenticationError(format!("Server rejected pairing code request ({})", code)),
+            });
+        }
+
+        let pairing_ref = node
+            .get_child("link_code_companion_reg")
+            .and_then(|reg| reg.get_child("link_code_pairing_ref"))
+            .and_then(|r| r.get_bytes())
+            .ok_or("Missing link_code_pairing_ref in pairing response")?;
+
+        self.pairing_ref = Some(pairing_ref.to_vec());
+        self.stage = PairingStage::WaitingForPrimary;
+        Ok(self.display_code())
+    }
+
+    /// Memproses notifikasi `primary_hello` dan membangun IQ `companion_finish`
+    pub fn handle_primary_hello(&mut self, node: &Node, session: &mut Session) -> Result<Node> {
+        if self.stage != PairingStage::WaitingForPrimary {
+            return Err("Unexpected primary_hello before pairing code was registered".into());
+        }
+
+        let reg = node
+            .get_child("link_code_companion_reg")
+            .ok_or("Missing link_code_companion_reg in notification")?;
+        if reg.get_attr("stage") != Some("primary_hello") {
+            return Err("Unexpected pairing stage from server".into());
+        }
+
+        let wrapped_primary = reg
+            .get_child("link_code_pairing_wrapped_primary_ephemeral_pub")
+            .and_then(|n| n.get_bytes())
+            .ok_or("Missing primary ephemeral key")?;
+        let primary_identity_pub = reg
+            .get_child("primary_identity_pub")
+            .and_then(|n| n.get_bytes())
+            .ok_or("Missing primary identity key")?;
+        let pairing_ref = self.pairing_ref.clone().ok_or("Missing pairing ref")?;
+
+        let primary_ephemeral_pub = unwrap_with_code(&self.code, wrapped_primary)?;
+
+        // Shared secret ephemeral (kunci ephemeral hanya dipakai sekali)
+        let ephemeral_private = self.ephemeral_private.take().ok_or("Pairing ephemeral key already used")?;
+        let primary_public = agreement::UnparsedPublicKey::new(&agreement::X25519, &primary_ephemeral_pub);
+        let ephemeral_shared = agreement::agree_ephemeral(ephemeral_private, &primary_public, |s| s.to_vec())
+            .map_err(|_| crypto_error("Failed to compute pairing shared secret"))?;
+
+        let rng = rand::SystemRandom::new();
+        let mut random_key = [0u8; 32];
+        rng.fill(&mut random_key).map_err(|_| "Failed to generate random key")?;
+
+        // Key bundle: identitas companion + identitas ponsel + kunci acak
+        let (companion_identity_pub, identity_shared) =
+            identity_agreement(&session.identity_key_pair.private_key, primary_identity_pub)?;
+        let mut bundle = Vec::with_capacity(96);
+        bundle.extend_from_slice(&companion_identity_pub);
+        bundle.extend_from_slice(primary_identity_pub);
+        bundle.extend_from_slice(&random_key);
+
+        let mut bundle_salt = [0u8; 32];
+        let mut bundle_iv = [0u8; 12];
+        rng.fill(&mut bundle_salt).map_err(|_| "Failed to generate salt")?;
+        rng.fill(&mut bundle_iv).map_err(|_| "Failed to generate iv")?;
+        let bundle_key = hkdf_sha256(&ephemeral_shared, &bundle_salt, KEY_BUNDLE_INFO)?;
+
+        let sealing_key = aead::LessSafeKey::new(
+            aead::UnboundKey::new(&aead::AES_256_GCM, &bundle_key).map_err(|_| crypto_error("Invalid bundle key"))?,
+        );
+        sealing_key
+            .seal_in_place_append_tag(aead::Nonce::assume_unique_for_key(bundle_iv), aead::Aad::empty(), &mut bundle)
+            .map_err(|_| crypto_error("Failed to encrypt key bundle"))?;
+
+        let mut wrapped_bundle = Vec::with_capacity(32 + 12 + bundle.len());
+        wrapped_bundle.extend_from_slice(&bundle_salt);
+        wrapped_bundle.extend_from_slice(&bundle_iv);
+        wrapped_bundle.extend_from_slice(&bundle);
+
+        // Secret untuk verifikasi ADV saat pair-success
+        let mut adv_material = Vec::with_capacity(96);
+        adv_material.extend_from_slice(&ephemeral_shared);
+        adv_material.extend_from_slice(&identity_shared);
+        adv_material.extend_from_slice(&random_key);
+        session.adv_secret_key = hkdf_sha256(&adv_material, &[], ADV_SECRET_INFO)?.to_vec();
+
+        let finish = Node::new("link_code_companion_reg")
+            .attr("jid", &format!("{}@s.whatsapp.net", self.phone_number))
+            .attr("stage", "companion_finish")
+            .children(vec![
+                Node::new("link_code_pairing_wrapped_key_bundle").bytes(wrapped_bundle),
+                Node::new("companion_identity_public").bytes(companion_identity_pub),
+                Node::new("link_code_pairing_ref").bytes(pairing_ref),
+            ]);
+
+        self.stage = PairingStage::Finished;
+        Ok(Node::new("iq")
+            .attr("id", &utils::generate_message_id())
+            .attr("to", "s.whatsapp.net")
+            .attr("type", "set")
+            .attr("xmlns", "md")
+            .children(vec![finish]))
+    }
+}
+
+fn crypto_error(msg: &str) -> Error {
+    Error { kind: ErrorKind::CryptoError(msg.to_string()) }
+}
+
+/// Membuat kode pairing acak dari alfabet pairing
+fn generate_pairing_code() -> Result<String> {
+    let mut bytes = [0u8; PAIRING_CODE_LENGTH];
+    rand::SystemRandom::new().fill(&mut bytes).map_err(|_| "Failed to generate pairing code")?;
+    Ok(bytes
+        .iter()
+        .map(|b| PAIRING_CODE_ALPHABET[(*b as usize) % PAIRING_CODE_ALPHABET.len()] as char)
+        .collect())
+}
+
+/// Kunci pembungkus dari kode pairing: PBKDF2-SHA256(code, salt)
+fn derive_code_key(code: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PAIRING_PBKDF2_ITERATIONS).unwrap(),
+        salt,
+        code.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+/// Membungkus kunci: salt(32) || iv(16) || AES-256-CTR(key)
+fn wrap_with_code(code: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let rng = rand::SystemRandom::new();
+    let mut salt = [0u8; 32];
+    let mut iv = [0u8; 16];
+    rng.fill(&mut salt).map_err(|_| "Failed to generate salt")?;
+    rng.fill(&mut iv).map_err(|_| "Failed to generate iv")?;
+
+    let key = derive_code_key(code, &salt);
+    let ciphertext = encrypt(Cipher::aes_256_ctr(), &key, Some(&iv), plaintext)
+        .map_err(|e| crypto_error(&format!("Failed to wrap pairing key: {}", e)))?;
+
+    let mut wrapped = Vec::with_capacity(48 + ciphertext.len());
+    wrapped.extend_from_slice(&salt);
+    wrapped.extend_from_slice(&iv);
+    wrapped.extend_from_slice(&ciphertext);
+    Ok(wrapped)
+}
+
+/// Kebalikan dari `wrap_with_code`
+fn unwrap_with_code(code: &str, wrapped: &[u8]) -> Result<Vec<u8>> {
+    if wrapped.len() != 80 {
+        return Err(crypto_error("Invalid wrapped pairing key length"));
+    }
+
+    let key = derive_code_key(code, &wrapped[..32]);
+    decrypt(Cipher::aes_256_ctr(), &key, Some(&wrapped[32..48]), &wrapped[48..])
+        .map_err(|e| crypto_error(&format!("Failed to unwrap pairing key: {}", e)))
+}
+
+/// X25519 dengan kunci identitas statis; mengembalikan (public key companion, shared secret)
+fn identity_agreement(identity_private: &[u8], peer_public: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let private = PKey::private_key_from_raw_bytes(identity_private, Id::X25519)
+        .map_err(|_| crypto_error("Invalid identity private key"))?;
+    let public = private.raw_public_key().map_err(|_| crypto_error("Invalid identity key"))?;
+    let peer = PKey::public_key_from_raw_bytes(peer_public, Id::X25519)
+        .map_err(|_| crypto_error("Invalid primary identity key"))?;
+
+    let mut deriver = Deriver::new(&private).map_err(|_| crypto_error("Failed to init key agreement"))?;
+    deriver.set_peer(&peer).map_err(|_| crypto_error("Failed to set peer key"))?;
+    let shared = deriver.derive_to_vec().map_err(|_| crypto_error("Failed to derive identity secret"))?;
+    Ok((public, shared))
+}
+
+fn hkdf_sha256(ikm: &[u8], salt: &[u8], info: &[u8]) -> Result<[u8; 32]> {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, salt).extract(ikm);
+    let info = [info];
+    let okm = prk
+        .expand(&info, hkdf::HKDF_SHA256)
+        .map_err(|_| crypto_error("HKDF expand failed"))?;
+    let mut out = [0u8; 32];
+    okm.fill(&mut out).map_err(|_| crypto_error("HKDF fill failed"))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pairing_code_format() {
+        let code = generate_pairing_code().unwrap();
+        assert_eq!(code.len(), PAIRING_CODE_LENGTH);
+        assert!(code.bytes().all(|c| PAIRING_CODE_ALPHABET.contains(&c)));
+    }
+
+    #[test]
+    fn test_wrap_roundtrip() {
+        let key = [7u8; 32];
+        let wrapped = wrap_with_code("ABCD1234", &key).unwrap();
+        assert_eq!(wrapped.len(), 80);
+        assert_eq!(unwrap_with_code("ABCD1234", &wrapped).unwrap(), key.to_vec());
+        assert_ne!(unwrap_with_code("WXYZ9876", &wrapped).unwrap(), key.to_vec());
+    }
+
+    #[test]
+    fn test_hello_response_registers_code() {
+        let mut flow = PairingCodeFlow::new("+62 811-0000-0001").unwrap();
+        let session = Session::new();
+        let hello = flow.companion_hello(&session, &DeviceProps::default()).unwrap();
+        let iq_id = hello.get_attr("id").unwrap().to_string();
+
+        let response = Node::new("iq")
+            .attr("id", &iq_id)
+            .attr("type", "result")
+            .children(vec![Node::new("link_code_companion_reg")
+                .children(vec![Node::new("link_code_pairing_ref").bytes(b"ref-1".to_vec())])]);
+
+        assert!(flow.handles(&response));
+        let code = flow.handle_hello_response(&response).unwrap();
+        assert_eq!(code, flow.display_code());
+        assert_eq!(flow.stage(), PairingStage::WaitingForPrimary);
+    }
+}
@@ -0,0 +1,75 @@
+//! Pelaporan panic di thread milik library
+//!
+//! Panic di thread koneksi (mis. saat mem-parse node yang aneh) sebelumnya
+//! mematikan thread tanpa jejak. Kode yang dijalankan lewat `catch` ditangkap
+//! dan dikembalikan sebagai `PanicReport` berisi pesan dan backtrace, untuk
+//! dikirim ke aplikasi sebagai `Event::InternalError`. Panic hook global hanya
+//! dipasang sekali dan meneruskan panic di luar `catch` ke hook sebelumnya.
+
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+/// Batas percobaan koneksi ulang setelah thread koneksi panic
+pub const MAX_PANIC_RESTARTS: u32 = 3;
+
+thread_local! {
+    static GUARDED: Cell<bool> = const { Cell::new(false) };
+    static LAST_BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Panic yang ditangkap
+#[derive(Debug, Clone)]
+pub struct PanicReport {
+    pub message: String,
+    pub backtrace: String,
+}
+
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if GUARDED.with(|guarded| guarded.get()) {
+                LAST_BACKTRACE.with(|last| *last.borrow_mut() = Some(Backtrace::force_capture().to_string()));
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
+
+/// Menjalankan `f` dan menangkap panic di dalamnya
+pub fn catch<F, R>(f: F) -> Result<R, PanicReport>
+where
+    F: FnOnce() -> R,
+{
+    install_hook();
+    let was_guarded = GUARDED.with(|guarded| guarded.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    GUARDED.with(|guarded| guarded.set(was_guarded));
+
+    result.map_err(|payload| PanicReport {
+        message: payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string()),
+        backtrace: LAST_BACKTRACE.with(|last| last.borrow_mut().take()).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_reports_message_and_backtrace() {
+        let report = catch(|| -> u32 { panic!("node rusak") }).unwrap_err();
+        assert_eq!(report.message, "node rusak");
+        assert!(!report.backtrace.is_empty());
+        assert_eq!(catch(|| 7).unwrap(), 7);
+    }
+}
@@ -0,0 +1,245 @@
+//! Permintaan dan pembayaran WhatsApp Pay
+//!
+//! Hanya tersedia di negara tempat WhatsApp Pay aktif. Library tidak
+//! memproses transaksi; ia hanya membuat pesan permintaan pembayaran dan
+//! melaporkan perubahan status dari `payment_info` pesan yang diterima
+//! sebagai `Event::PaymentUpdated`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::Utc;
+
+use crate::errors::*;
+use crate::messages::{
+    self, CancelPaymentRequestMessage, DeclinePaymentRequestMessage, ExtendedTextMessage, MessageKey, PaymentInfo, PaymentMoney,
+    RequestPaymentMessage, WebMessageInfo,
+};
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{Event, Jid, WhatsAppClient};
+
+/// Masa berlaku permintaan pembayaran
+pub const REQUEST_EXPIRY_SECS: i64 = 7 * 24 * 60 * 60;
+/// Jumlah transaksi yang statusnya diingat
+const TRACKED_PAYMENTS: usize = 256;
+
+/// Status transaksi (`PaymentInfo.status`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PaymentStatus {
+    Processing,
+    Sent,
+    /// Penerima harus menerima pembayaran
+    NeedToAccept,
+    Complete,
+    CouldNotComplete,
+    Refunded,
+    Expired,
+    Rejected,
+    Cancelled,
+    WaitingForPayer,
+    Waiting,
+}
+
+impl PaymentStatus {
+    pub fn from_u32(value: u32) -> Option<PaymentStatus> {
+        match value {
+            1 => Some(PaymentStatus::Processing),
+            2 => Some(PaymentStatus::Sent),
+            3 => Some(PaymentStatus::NeedToAccept),
+            4 => Some(PaymentStatus::Complete),
+            5 => Some(PaymentStatus::CouldNotComplete),
+            6 => Some(PaymentStatus::Refunded),
+            7 => Some(PaymentStatus::Expired),
+            8 => Some(PaymentStatus::Rejected),
+            9 => Some(PaymentStatus::Cancelled),
+            10 => Some(PaymentStatus::WaitingForPayer),
+            11 => Some(PaymentStatus::Waiting),
+            _ => None,
+        }
+    }
+
+    /// Status akhir; tidak akan berubah lagi
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            PaymentStatus::Complete
+                | PaymentStatus::CouldNotComplete
+                | PaymentStatus::Refunded
+                | PaymentStatus::Expired
+                | PaymentStatus::Rejected
+                | PaymentStatus::Cancelled
+        )
+    }
+}
+
+/// Perubahan status satu transaksi
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentUpdate {
+    /// Id pesan permintaan, atau id pesan pembayaran jika tanpa permintaan
+    pub message_id: String,
+    pub chat: String,
+    pub receiver: String,
+    pub amount_1000: u64,
+    pub currency: String,
+    /// `None` untuk status pertama yang terlihat
+    pub previous: Option<PaymentStatus>,
+    pub status: PaymentStatus,
+}
+
+/// Status terakhir setiap transaksi untuk mendeteksi transisi
+#[derive(Default)]
+pub struct PaymentTracker {
+    statuses: HashMap<String, PaymentStatus>,
+    order: Vec<String>,
+}
+
+impl PaymentTracker {
+    pub fn new() -> Self {
+        PaymentTracker::default()
+    }
+
+    /// Mencatat `payment_info` pesan; `None` jika status tidak berubah
+    pub fn record(&mut self, web_message: &WebMessageInfo) -> Option<PaymentUpdate> {
+        let info: &PaymentInfo = web_message.payment_info.as_ref().or(web_message.quoted_payment_info.as_ref())?;
+        let status = PaymentStatus::from_u32(info.status)?;
+        let message_id = info.request_message_key.as_ref().map_or(&web_message.key.id, |key| &key.id).clone();
+
+        let previous = self.statuses.insert(message_id.clone(), status);
+        if previous == Some(status) {
+            return None;
+        }
+        if previous.is_none() {
+            self.order.push(message_id.clone());
+            if self.order.len() > TRACKED_PAYMENTS {
+                let oldest = self.order.remove(0);
+                self.statuses.remove(&oldest);
+            }
+        }
+        let currency = if info.currency_code_iso4217.is_empty() { &info.currency } else { &info.currency_code_iso4217 };
+        Some(PaymentUpdate {
+            message_id,
+            chat: web_message.key.remote_jid.clone(),
+            receiver: info.receiver_jid.clone(),
+            amount_1000: info.amount_1000,
+            currency: currency.clone(),
+            previous,
+            status,
+        })
+    }
+}
+
+/// Handler stanza `message` yang melaporkan perubahan status pembayaran
+pub fn update_handler() -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    let tracker = Mutex::new(PaymentTracker::new());
+    move |node: &Node, ctx: &NodeContext| {
+        let web_message = match node.get_bytes().and_then(|bytes| WebMessageInfo::from_bytes(bytes).ok()) {
+            Some(web_message) => web_message,
+            None => return Ok(()),
+        };
+        if let Some(update) = tracker.lock().unwrap().record(&web_message) {
+            ctx.emit(Event::PaymentUpdated(update));
+        }
+        Ok(())
+    }
+}
+
+/// Pesan permintaan pembayaran sebesar `amount_1000` (seperseribu unit `currency`) dari `from`
+pub fn request_message(from: &Jid, amount_1000: u64, currency: &str, note: Option<&str>, now: i64) -> Result<messages::Message> {
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(format!("Invalid ISO 4217 currency code {:?}", currency).into());
+    }
+    if amount_1000 == 0 {
+        return Err("Payment amount must be positive".into());
+    }
+    let note_message = note.map(|note| Box::new(messages::Message {
+        extended_text_message: Some(ExtendedTextMessage { text: note.to_string(), ..Default::default() }),
+        ..Default::default()
+    }));
+    Ok(messages::Message {
+        request_payment_message: Some(RequestPaymentMessage {
+            note_message,
+            currency_code_iso4217: currency.to_string(),
+            amount_1000,
+            request_from: from.to_string(),
+            expiry_timestamp: now + REQUEST_EXPIRY_SECS,
+            amount: Some(PaymentMoney { value: amount_1000 as i64, offset: 1000, currency_code: currency.to_string() }),
+            request_status: None,
+            background_url: None,
+            text_attribution: None,
+        }),
+        ..Default::default()
+    })
+}
+
+impl WhatsAppClient {
+    /// Meminta pembayaran dari `jid`. Mengembalikan id pesan permintaan, yang
+    /// dipakai sebagai `message_id` pada `Event::PaymentUpdated`.
+    pub fn request_payment(&self, jid: &Jid, amount_1000: u64, currency: &str, note: Option<&str>) -> Result<String> {
+        let message = request_message(jid, amount_1000, currency, note, Utc::now().timestamp())?;
+        self.send_message(jid, message)
+    }
+
+    /// Membatalkan permintaan pembayaran yang kita kirim
+    pub fn cancel_payment_request(&self, chat: &Jid, request: &MessageKey) -> Result<String> {
+        let message = messages::Message {
+            cancel_payment_message: Some(CancelPaymentRequestMessage { key: request.clone() }),
+            ..Default::default()
+        };
+        self.send_message(chat, message)
+    }
+
+    /// Menolak permintaan pembayaran yang diterima
+    pub fn decline_payment_request(&self, chat: &Jid, request: &MessageKey) -> Result<String> {
+        let message = messages::Message {
+            decline_payment_message: Some(DeclinePaymentRequestMessage { key: request.clone() }),
+            ..Default::default()
+        };
+        self.send_message(chat, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(id: &str, status: u32) -> WebMessageInfo {
+        WebMessageInfo {
+            key: MessageKey { remote_jid: "628111@s.whatsapp.net".to_string(), from_me: false, id: id.to_string(), participant: None },
+            payment_info: Some(PaymentInfo {
+                currency: String::new(),
+                amount_1000: 25_000_000,
+                receiver_jid: "628999@s.whatsapp.net".to_string(),
+                status,
+                transaction_timestamp: 0,
+                request_message_key: Some(MessageKey {
+                    remote_jid: "628111@s.whatsapp.net".to_string(),
+                    from_me: true,
+                    id: "REQ1".to_string(),
+                    participant: None,
+                }),
+                expiry_timestamp: 0,
+                futureproofed: false,
+                currency_code_iso4217: "INR".to_string(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_status_transitions_and_request() {
+        let mut tracker = PaymentTracker::new();
+        let first = tracker.record(&update("P1", 1)).unwrap();
+        assert_eq!((first.message_id.as_str(), first.previous, first.status), ("REQ1", None, PaymentStatus::Processing));
+        assert!(tracker.record(&update("P1", 1)).is_none());
+        let done = tracker.record(&update("P2", 4)).unwrap();
+        assert_eq!((done.previous, done.status), (Some(PaymentStatus::Processing), PaymentStatus::Complete));
+        assert!(done.status.is_final());
+
+        let payer = Jid::user("628111");
+        let request = request_message(&payer, 25_000_000, "INR", Some("Makan siang"), 1_700_000_000).unwrap().request_payment_message.unwrap();
+        assert_eq!(request.expiry_timestamp, 1_700_000_000 + REQUEST_EXPIRY_SECS);
+        assert_eq!(request.request_from, "628111@s.whatsapp.net");
+        assert!(request_message(&payer, 1, "inr", None, 0).is_err());
+    }
+}
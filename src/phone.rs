@@ -0,0 +1,136 @@
+//! Deteksi ponsel (perangkat utama) offline
+//!
+//! Jika ponsel terlalu lama offline, server berhenti menerima sebagian operasi
+//! dan pesan keluar tertahan. Sinyal yang dipantau:
+//! - `Conn` dengan field `connected` dan pesan `Stream` (`asleep`/`awake`)
+//! - balasan `iq` error dengan kode 599 (ponsel tidak merespons)
+//! - pesan apa pun yang dikirim dari akun sendiri (ponsel online)
+//!
+//! Perubahan status dikirim sebagai `Event::PhoneConnectionChanged`. Jika ada
+//! pengiriman saat ponsel sudah offline lebih dari `ADVISORY_DELAY`,
+//! `Event::PhoneOfflineAdvisory` dikirim sekali per periode offline agar
+//! operator bisa mengisi daya/menyambungkan ulang ponsel.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::Event;
+
+/// Kode error server saat ponsel tidak merespons
+pub const PHONE_UNREACHABLE_CODE: &str = "599";
+
+/// Lama offline sebelum pengiriman memicu advisory
+pub const ADVISORY_DELAY: Duration = Duration::from_secs(60);
+
+/// Status koneksi ponsel
+pub struct PhoneMonitor {
+    connected: bool,
+    offline_since: Option<SystemTime>,
+    advised: bool,
+}
+
+impl PhoneMonitor {
+    /// Ponsel dianggap online sampai ada sinyal sebaliknya
+    pub fn new() -> Self {
+        PhoneMonitor {
+            connected: true,
+            offline_since: None,
+            advised: false,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub fn offline_since(&self) -> Option<SystemTime> {
+        self.offline_since
+    }
+
+    /// Memperbarui status; mengembalikan event jika status berubah
+    pub fn set_connected(&mut self, connected: bool) -> Option<Event> {
+        if self.connected == connected {
+            return None;
+        }
+        self.connected = connected;
+        self.offline_since = if connected { None } else { Some(SystemTime::now()) };
+        self.advised = false;
+        Some(Event::PhoneConnectionChanged { connected })
+    }
+
+    /// Dipanggil sebelum mengirim; mengembalikan advisory sekali per periode offline
+    pub fn on_send(&mut self) -> Option<Event> {
+        if self.connected || self.advised {
+            return None;
+        }
+        let offline_for = self.offline_since?.elapsed().unwrap_or_default();
+        if offline_for < ADVISORY_DELAY {
+            return None;
+        }
+        self.advised = true;
+        Some(Event::PhoneOfflineAdvisory { offline_for })
+    }
+}
+
+/// Handler `iq type="error"`: kode 599 berarti ponsel tidak merespons
+pub fn iq_error_handler(phone: Arc<Mutex<PhoneMonitor>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let unreachable = node
+            .get_child("error")
+            .and_then(|error| error.get_attr("code"))
+            .map_or(false, |code| code == PHONE_UNREACHABLE_CODE);
+        if unreachable {
+            if let Some(event) = phone.lock().unwrap().set_connected(false) {
+                ctx.emit(event);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Handler `message`: pesan dari akun sendiri membuktikan ponsel online
+pub fn own_message_handler(phone: Arc<Mutex<PhoneMonitor>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let from_me = node
+            .get_bytes()
+            .and_then(|bytes| crate::messages::WebMessageInfo::from_bytes(bytes).ok())
+            .map_or(false, |web_message| web_message.key.from_me);
+        if from_me {
+            if let Some(event) = phone.lock().unwrap().set_connected(true) {
+                ctx.emit(event);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_events_only_on_transition() {
+        let mut phone = PhoneMonitor::new();
+        assert!(phone.set_connected(true).is_none());
+        assert!(matches!(phone.set_connected(false), Some(Event::PhoneConnectionChanged { connected: false })));
+        assert!(phone.set_connected(false).is_none());
+        assert!(phone.offline_since().is_some());
+        assert!(phone.set_connected(true).is_some());
+        assert!(phone.offline_since().is_none());
+    }
+
+    #[test]
+    fn test_advisory_waits_for_delay() {
+        let mut phone = PhoneMonitor::new();
+        phone.set_connected(false);
+        assert!(phone.on_send().is_none());
+
+        phone.offline_since = Some(SystemTime::now() - ADVISORY_DELAY);
+        assert!(phone.on_send().is_some());
+        // Sekali per periode offline
+        assert!(phone.on_send().is_none());
+    }
+}
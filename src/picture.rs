@@ -0,0 +1,87 @@
+//! Persiapan foto profil (akun dan grup)
+//!
+//! Server menerima JPEG persegi dengan sisi maksimal `MAX_PICTURE_SIZE`.
+//! Dengan fitur `image`, gambar dipotong ke tengah dan diperkecil otomatis;
+//! tanpa fitur itu, JPEG yang terlalu besar ditolak.
+
+use crate::errors::*;
+
+/// Sisi maksimal foto profil (piksel)
+pub const MAX_PICTURE_SIZE: u32 = 640;
+
+/// Membaca lebar dan tinggi dari marker SOF JPEG
+pub fn jpeg_dimensions(jpeg: &[u8]) -> Option<(u32, u32)> {
+    if jpeg.get(..2)? != [0xFF, 0xD8] {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= jpeg.len() {
+        if jpeg[pos] != 0xFF {
+            return None;
+        }
+        let marker = jpeg[pos + 1];
+        let length = u16::from_be_bytes([jpeg[pos + 2], jpeg[pos + 3]]) as usize;
+        // SOF0..SOF15 kecuali DHT (C4), JPG (C8) dan DAC (CC)
+        if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+            let sof = jpeg.get(pos + 5..pos + 9)?;
+            let height = u16::from_be_bytes([sof[0], sof[1]]) as u32;
+            let width = u16::from_be_bytes([sof[2], sof[3]]) as u32;
+            return Some((width, height));
+        }
+        pos += 2 + length;
+    }
+    None
+}
+
+/// Menyiapkan JPEG untuk diunggah sebagai foto profil
+#[cfg(feature = "image")]
+pub fn prepare_picture(data: &[u8]) -> Result<Vec<u8>> {
+    use image::imageops::FilterType;
+
+    let image = image::load_from_memory(data).map_err(|e| Error { kind: ErrorKind::InvalidFormat(format!("Unsupported picture: {}", e)) })?;
+    let side = image.width().min(image.height());
+    let square = image.crop_imm((image.width() - side) / 2, (image.height() - side) / 2, side, side);
+    let resized = if side > MAX_PICTURE_SIZE { square.resize_exact(MAX_PICTURE_SIZE, MAX_PICTURE_SIZE, FilterType::Triangle) } else { square };
+
+    let mut jpeg = Vec::new();
+    resized
+        .to_rgb8()
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageOutputFormat::Jpeg(85))
+        .map_err(|e| format!("Failed to encode picture: {}", e))?;
+    Ok(jpeg)
+}
+
+/// Menyiapkan JPEG untuk diunggah sebagai foto profil
+#[cfg(not(feature = "image"))]
+pub fn prepare_picture(data: &[u8]) -> Result<Vec<u8>> {
+    let (width, height) = jpeg_dimensions(data).ok_or_else(|| Error { kind: ErrorKind::InvalidFormat("Profile picture must be a JPEG".to_string()) })?;
+    if width > MAX_PICTURE_SIZE || height > MAX_PICTURE_SIZE {
+        return Err(Error {
+            kind: ErrorKind::InvalidFormat(format!(
+                "Profile picture is {}x{}, at most {}x{} allowed (enable the `image` feature to resize automatically)",
+                width, height, MAX_PICTURE_SIZE, MAX_PICTURE_SIZE
+            )),
+        });
+    }
+    Ok(data.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// JPEG minimal: SOI, APP0 kosong, SOF0 dengan ukuran tertentu
+    fn jpeg_header(width: u16, height: u16) -> Vec<u8> {
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x02, 0xFF, 0xC0, 0x00, 0x11, 0x08];
+        jpeg.extend_from_slice(&height.to_be_bytes());
+        jpeg.extend_from_slice(&width.to_be_bytes());
+        jpeg.extend_from_slice(&[0x03; 12]);
+        jpeg
+    }
+
+    #[test]
+    fn test_jpeg_dimensions() {
+        assert_eq!(jpeg_dimensions(&jpeg_header(800, 600)), Some((800, 600)));
+        assert_eq!(jpeg_dimensions(b"\x89PNG\r\n"), None);
+    }
+}
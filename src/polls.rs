@@ -0,0 +1,276 @@
+//! Polling grup untuk bot admin
+//!
+//! Vote polling dienkripsi dengan `message_secret` milik pesan polling. Kunci
+//! per pemilih diturunkan dengan HMAC-SHA256 dari secret, id polling, JID
+//! pembuat dan JID pemilih, lalu payload didekripsi dengan AES-256-GCM.
+//! Isi vote adalah daftar hash SHA-256 dari nama opsi yang dipilih.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use ring::{digest, hmac, rand};
+use ring::rand::SecureRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::*;
+use crate::messages::{self, MessageContextInfo, PollCreationMessage, PollEncValue, PollOption, WebMessageInfo};
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{Jid, WhatsAppClient};
+
+/// Isi vote setelah didekripsi
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PollVote {
+    selected_options: Vec<Vec<u8>>,
+}
+
+/// Hash SHA-256 nama opsi, sebagaimana dikirim di dalam vote
+pub fn option_hash(option: &str) -> Vec<u8> {
+    digest::digest(&digest::SHA256, option.as_bytes()).as_ref().to_vec()
+}
+
+/// Kunci AES-GCM untuk vote `voter` pada polling `poll_id`
+fn vote_key(secret: &[u8], poll_id: &str, creator: &str, voter: &str) -> Vec<u8> {
+    let base = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &[0u8; 32]), secret);
+    let mut info = Vec::new();
+    info.extend_from_slice(poll_id.as_bytes());
+    info.extend_from_slice(creator.as_bytes());
+    info.extend_from_slice(voter.as_bytes());
+    info.extend_from_slice(b"Poll Vote");
+    info.push(1);
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, base.as_ref()), &info).as_ref().to_vec()
+}
+
+fn vote_aad(poll_id: &str, voter: &str) -> Vec<u8> {
+    format!("{}\u{0}{}", poll_id, voter).into_bytes()
+}
+
+/// Mengenkripsi vote (hash opsi) untuk polling `poll_id`
+pub fn encrypt_vote(secret: &[u8], poll_id: &str, creator: &str, voter: &str, selected: &[&str]) -> Result<PollEncValue> {
+    let vote = PollVote {
+        selected_options: selected.iter().map(|option| option_hash(option)).collect(),
+    };
+    let plaintext = serde_json::to_vec(&vote).map_err(|e| format!("Serialization error: {}", e))?;
+
+    let mut iv = [0u8; 12];
+    rand::SystemRandom::new().fill(&mut iv).map_err(|_| "Failed to generate vote IV")?;
+    let mut tag = [0u8; 16];
+    let key = vote_key(secret, poll_id, creator, voter);
+    let mut payload = encrypt_aead(Cipher::aes_256_gcm(), &key, Some(&iv), &vote_aad(poll_id, voter), &plaintext, &mut tag)
+        .map_err(|e| Error { kind: ErrorKind::CryptoError(format!("Vote encryption failed: {}", e)) })?;
+    payload.extend_from_slice(&tag);
+
+    Ok(PollEncValue {
+        enc_iv: iv.to_vec(),
+        enc_payload: payload,
+    })
+}
+
+/// Mendekripsi vote dan mengembalikan hash opsi yang dipilih
+pub fn decrypt_vote(secret: &[u8], poll_id: &str, creator: &str, voter: &str, vote: &PollEncValue) -> Result<Vec<Vec<u8>>> {
+    if vote.enc_payload.len() < 16 {
+        return Err(Error { kind: ErrorKind::InvalidPayload("Poll vote payload too short".to_string()) });
+    }
+    let (ciphertext, tag) = vote.enc_payload.split_at(vote.enc_payload.len() - 16);
+    let key = vote_key(secret, poll_id, creator, voter);
+    let plaintext = decrypt_aead(Cipher::aes_256_gcm(), &key, Some(&vote.enc_iv), &vote_aad(poll_id, voter), ciphertext, tag)
+        .map_err(|e| Error { kind: ErrorKind::CryptoError(format!("Vote decryption failed: {}", e)) })?;
+    let vote: PollVote = serde_json::from_slice(&plaintext).map_err(|e| Error { kind: ErrorKind::InvalidPayload(format!("Invalid poll vote: {}", e)) })?;
+    Ok(vote.selected_options)
+}
+
+/// Hasil polling
+#[derive(Debug, Clone, PartialEq)]
+pub struct PollResults {
+    pub question: String,
+    /// Jumlah vote per opsi, urut sesuai opsi polling
+    pub tallies: Vec<(String, usize)>,
+    /// Opsi terakhir yang dipilih setiap pemilih
+    pub votes: HashMap<String, Vec<String>>,
+}
+
+struct OpenPoll {
+    creator: String,
+    secret: Vec<u8>,
+    question: String,
+    options: Vec<String>,
+    votes: HashMap<String, Vec<String>>,
+}
+
+impl OpenPoll {
+    fn results(&self) -> PollResults {
+        let tallies = self
+            .options
+            .iter()
+            .map(|option| (option.clone(), self.votes.values().filter(|selected| selected.contains(option)).count()))
+            .collect();
+        PollResults {
+            question: self.question.clone(),
+            tallies,
+            votes: self.votes.clone(),
+        }
+    }
+}
+
+/// Polling yang sedang dikumpulkan vote-nya, per id pesan polling
+#[derive(Default)]
+pub struct PollTracker {
+    polls: HashMap<String, OpenPoll>,
+}
+
+impl PollTracker {
+    pub fn new() -> Self {
+        PollTracker::default()
+    }
+
+    pub fn open(&mut self, poll_id: &str, creator: &str, secret: Vec<u8>, question: &str, options: &[String]) {
+        self.polls.insert(
+            poll_id.to_string(),
+            OpenPoll {
+                creator: creator.to_string(),
+                secret,
+                question: question.to_string(),
+                options: options.to_vec(),
+                votes: HashMap::new(),
+            },
+        );
+    }
+
+    /// Mencatat vote terenkripsi. Vote baru dari pemilih yang sama menggantikan
+    /// vote sebelumnya; vote kosong berarti pemilih menarik pilihannya.
+    pub fn record(&mut self, poll_id: &str, voter: &str, vote: &PollEncValue) -> Result<bool> {
+        let poll = match self.polls.get_mut(poll_id) {
+            Some(poll) => poll,
+            None => return Ok(false),
+        };
+        let hashes = decrypt_vote(&poll.secret, poll_id, &poll.creator, voter, vote)?;
+        let selected: Vec<String> = poll.options.iter().filter(|option| hashes.contains(&option_hash(option))).cloned().collect();
+        if selected.is_empty() {
+            poll.votes.remove(voter);
+        } else {
+            poll.votes.insert(voter.to_string(), selected);
+        }
+        Ok(true)
+    }
+
+    pub fn results(&self, poll_id: &str) -> Option<PollResults> {
+        self.polls.get(poll_id).map(OpenPoll::results)
+    }
+
+    /// Menutup polling; vote berikutnya diabaikan
+    pub fn close(&mut self, poll_id: &str) -> Option<PollResults> {
+        self.polls.remove(poll_id).map(|poll| poll.results())
+    }
+}
+
+/// Handler pesan yang mencatat vote untuk polling yang masih terbuka
+pub fn vote_handler(polls: Arc<Mutex<PollTracker>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        let web_message = match node.get_bytes().and_then(|bytes| WebMessageInfo::from_bytes(bytes).ok()) {
+            Some(web_message) => web_message,
+            None => return Ok(()),
+        };
+        let update = match web_message.message.as_ref().and_then(|message| message.poll_update_message.as_ref()) {
+            Some(update) => update,
+            None => return Ok(()),
+        };
+        if let Some(ref poll_key) = update.poll_creation_message_key {
+            let voter = web_message.key.participant.as_ref().unwrap_or(&web_message.key.remote_jid);
+            // Vote yang gagal didekripsi (mis. polling lain dengan id sama) diabaikan
+            polls.lock().unwrap().record(&poll_key.id, voter, &update.poll_update.vote).ok();
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Mengirim polling dan mulai mengumpulkan vote-nya. Mengembalikan id pesan.
+    pub fn send_poll(&self, to: &Jid, question: &str, options: &[&str], selectable_count: u32) -> Result<String> {
+        if options.len() < 2 {
+            return Err("Poll requires at least two options".into());
+        }
+        let creator = self.get_own_jid().ok_or("Not logged in")?.to_string();
+
+        let mut secret = vec![0u8; 32];
+        rand::SystemRandom::new().fill(&mut secret).map_err(|_| "Failed to generate poll secret")?;
+        let message = messages::Message {
+            poll_creation_message: Some(PollCreationMessage {
+                name: question.to_string(),
+                selectable_count,
+                options: options.iter().map(|option| PollOption { option_name: option.to_string() }).collect(),
+                context_info: None,
+            }),
+            message_context_info: Some(MessageContextInfo {
+                message_secret: Some(secret.clone()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let message_id = self.send_message(to, message)?;
+        let options: Vec<String> = options.iter().map(|option| option.to_string()).collect();
+        self.polls.lock().unwrap().open(&message_id, &creator, secret, question, &options);
+        Ok(message_id)
+    }
+
+    /// Hasil sementara polling yang masih terbuka
+    pub fn poll_results(&self, poll_id: &str) -> Option<PollResults> {
+        self.polls.lock().unwrap().results(poll_id)
+    }
+
+    /// Berhenti mengumpulkan vote dan mengembalikan hasil akhir
+    pub fn close_poll(&self, poll_id: &str) -> Option<PollResults> {
+        self.polls.lock().unwrap().close(poll_id)
+    }
+
+    /// Membuat polling satu pilihan di grup, mengumpulkan vote selama
+    /// `duration`, lalu menutupnya dan mengembalikan hasilnya.
+    /// Memblokir thread pemanggil; jangan dipanggil dari event handler.
+    pub fn run_poll(&self, group: &Jid, question: &str, options: &[&str], duration: Duration) -> Result<PollResults> {
+        if !group.is_group() {
+            return Err("run_poll requires a group JID".into());
+        }
+        let poll_id = self.send_poll(group, question, options, 1)?;
+        thread::sleep(duration);
+        self.close_poll(&poll_id).ok_or_else(|| "Poll was closed before it finished".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CREATOR: &str = "628000@s.whatsapp.net";
+
+    fn tracker() -> PollTracker {
+        let mut polls = PollTracker::new();
+        polls.open("p1", CREATOR, vec![7u8; 32], "Makan di mana?", &["Soto".to_string(), "Bakso".to_string()]);
+        polls
+    }
+
+    #[test]
+    fn test_vote_roundtrip() {
+        let vote = encrypt_vote(&[7u8; 32], "p1", CREATOR, "628111@s.whatsapp.net", &["Bakso"]).unwrap();
+        let hashes = decrypt_vote(&[7u8; 32], "p1", CREATOR, "628111@s.whatsapp.net", &vote).unwrap();
+        assert_eq!(hashes, vec![option_hash("Bakso")]);
+        // Kunci terikat ke pemilih
+        assert!(decrypt_vote(&[7u8; 32], "p1", CREATOR, "628222@s.whatsapp.net", &vote).is_err());
+    }
+
+    #[test]
+    fn test_tally_uses_latest_vote() {
+        let mut polls = tracker();
+        let voter = "628111@s.whatsapp.net";
+        for (who, choice) in [(voter, "Soto"), ("628222@s.whatsapp.net", "Bakso"), (voter, "Bakso")] {
+            let vote = encrypt_vote(&[7u8; 32], "p1", CREATOR, who, &[choice]).unwrap();
+            assert!(polls.record("p1", who, &vote).unwrap());
+        }
+
+        let results = polls.close("p1").unwrap();
+        assert_eq!(results.tallies, vec![("Soto".to_string(), 0), ("Bakso".to_string(), 2)]);
+        assert!(polls.results("p1").is_none());
+    }
+}
@@ -0,0 +1,309 @@
+//! Registrasi pre-key, pengisian ulang dan rotasi kunci
+//!
+//! Setelah pairing, registration id, identity key, signed pre-key dan
+//! `ONE_TIME_KEY_TARGET` one-time pre-key diunggah sekali ke server. Server
+//! mengirim `notification type="encrypt"` berisi `<count>` saat persediaan
+//! one-time pre-key menipis; di bawah `MIN_PRE_KEY_COUNT` kunci baru dibuat
+//! dan diunggah otomatis.
+//!
+//! `WhatsAppClient::rotate_keys` membuat signed pre-key baru, melengkapi
+//! persediaan one-time pre-key, lalu mengunggah ulang data registrasi ke
+//! server. Signed pre-key lama tetap diterima selama `SIGNED_PRE_KEY_GRACE`
+//! agar pesan yang dienkripsi ke kunci lama masih bisa dibuka; selama masa
+//! tenggang itu rotasi berikutnya ditolak.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::errors::*;
+use crate::iq;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::session::Session;
+use crate::{utils, Event, WhatsAppClient};
+
+/// Masa tenggang sebelum signed pre-key lama dipensiunkan
+pub const SIGNED_PRE_KEY_GRACE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Jumlah one-time pre-key yang dijaga tersedia di server
+pub const ONE_TIME_KEY_TARGET: usize = 30;
+
+/// Sisa one-time pre-key di server yang memicu pengisian ulang
+pub const MIN_PRE_KEY_COUNT: usize = 5;
+
+/// Hasil satu rotasi kunci
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyRotation {
+    pub signed_pre_key_id: u32,
+    /// Signed pre-key lama yang dipensiunkan pada rotasi ini
+    pub retired_signed_pre_key_id: Option<u32>,
+    /// Jumlah one-time pre-key baru yang diunggah
+    pub uploaded_one_time_keys: usize,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Id pre-key dalam format 3 byte big-endian
+fn key_id_bytes(key_id: u32) -> Vec<u8> {
+    key_id.to_be_bytes()[1..].to_vec()
+}
+
+/// Memensiunkan signed pre-key sebelumnya jika masa tenggangnya sudah habis.
+/// Masa tenggang dihitung sejak kunci penggantinya dibuat.
+pub fn retire_previous_signed_pre_key(session: &mut Session, now: u64, grace: Duration) -> Option<u32> {
+    let rotated_at = session.signed_pre_key.timestamp;
+    match session.previous_signed_pre_key {
+        Some(ref previous) if now >= rotated_at + grace.as_secs() => {
+            let key_id = previous.key_id;
+            session.previous_signed_pre_key = None;
+            Some(key_id)
+        }
+        _ => None,
+    }
+}
+
+/// Merotasi signed pre-key dan melengkapi one-time pre-key.
+/// Mengembalikan hasil rotasi dan id one-time pre-key baru. Gagal jika
+/// signed pre-key sebelumnya masih dalam masa tenggang.
+pub fn rotate_session_keys(session: &mut Session, now: u64) -> Result<(KeyRotation, Vec<u32>)> {
+    let retired = retire_previous_signed_pre_key(session, now, SIGNED_PRE_KEY_GRACE);
+    if session.previous_signed_pre_key.is_some() {
+        return Err("Previous signed pre-key is still in its grace period".into());
+    }
+    let signed_pre_key_id = session.rotate_signed_pre_key().key_id;
+
+    let missing = ONE_TIME_KEY_TARGET.saturating_sub(session.one_time_keys.len());
+    let new_keys = (0..missing).map(|_| session.add_one_time_key()).collect::<Result<Vec<u32>>>()?;
+
+    Ok((
+        KeyRotation {
+            signed_pre_key_id,
+            retired_signed_pre_key_id: retired,
+            uploaded_one_time_keys: new_keys.len(),
+        },
+        new_keys,
+    ))
+}
+
+/// IQ `encrypt` untuk mengunggah data registrasi dan pre-key
+pub fn upload_node(session: &Session, one_time_key_ids: &[u32]) -> Node {
+    let signed = &session.signed_pre_key;
+    let keys = one_time_key_ids
+        .iter()
+        .filter_map(|id| session.one_time_keys.get(id))
+        .map(|key| {
+            Node::new("key").children(vec![
+                Node::new("id").bytes(key_id_bytes(key.key_id)),
+                Node::new("value").bytes(key.public_key.clone()),
+            ])
+        })
+        .collect();
+
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "encrypt")
+        .attr("type", "set")
+        .attr("to", "s.whatsapp.net")
+        .children(vec![
+            Node::new("registration").bytes(session.registration_id.to_be_bytes().to_vec()),
+            Node::new("type").bytes(vec![5]),
+            Node::new("identity").bytes(session.identity_key_pair.public_key.clone()),
+            Node::new("list").children(keys),
+            Node::new("skey").children(vec![
+                Node::new("id").bytes(key_id_bytes(signed.key_id)),
+                Node::new("value").bytes(signed.public_key.clone()),
+                Node::new("signature").bytes(signed.signature.clone()),
+            ]),
+        ])
+}
+
+/// Node upload data registrasi pertama; `None` jika sudah pernah diunggah.
+/// One-time pre-key dilengkapi sampai `ONE_TIME_KEY_TARGET`.
+pub fn registration_upload(session: &mut Session) -> Result<Option<(Node, usize)>> {
+    if session.pre_keys_uploaded {
+        return Ok(None);
+    }
+    let missing = ONE_TIME_KEY_TARGET.saturating_sub(session.one_time_keys.len());
+    for _ in 0..missing {
+        session.add_one_time_key()?;
+    }
+    let mut key_ids: Vec<u32> = session.one_time_keys.keys().copied().collect();
+    key_ids.sort_unstable();
+    Ok(Some((upload_node(session, &key_ids), key_ids.len())))
+}
+
+/// Node upload one-time pre-key baru jika sisa di server (`server_count`)
+/// di bawah `MIN_PRE_KEY_COUNT`
+pub fn replenish_upload(session: &mut Session, server_count: usize) -> Result<Option<(Node, usize)>> {
+    if server_count >= MIN_PRE_KEY_COUNT {
+        return Ok(None);
+    }
+    let new_keys = (0..ONE_TIME_KEY_TARGET - server_count).map(|_| session.add_one_time_key()).collect::<Result<Vec<u32>>>()?;
+    Ok(Some((upload_node(session, &new_keys), new_keys.len())))
+}
+
+/// Membaca `<notification type="encrypt"><count value="N"/></notification>`
+pub fn parse_pre_key_count(node: &Node) -> Option<usize> {
+    node.get_child("count")?.get_attr("value")?.parse().ok()
+}
+
+/// Upload pre-key yang menunggu balasan server (id IQ -> jumlah kunci)
+#[derive(Default)]
+pub struct PreKeyUploads {
+    pending: HashMap<String, usize>,
+}
+
+impl PreKeyUploads {
+    pub fn new() -> Self {
+        PreKeyUploads::default()
+    }
+
+    pub fn track(&mut self, node: &Node, count: usize) {
+        if let Some(id) = node.get_attr("id") {
+            self.pending.insert(id.to_string(), count);
+        }
+    }
+
+    pub fn take(&mut self, node: &Node) -> Option<usize> {
+        self.pending.remove(node.get_attr("id")?)
+    }
+}
+
+/// Handler `notification type="encrypt"` yang mengunggah one-time pre-key
+/// baru saat persediaan di server menipis. Ack dikirim oleh
+/// `outbox::identity_change_handler`.
+pub fn low_pre_key_handler(
+    session: Arc<Mutex<Option<Session>>>,
+    uploads: Arc<Mutex<PreKeyUploads>>,
+) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let count = match parse_pre_key_count(node) {
+            Some(count) => count,
+            None => return Ok(()),
+        };
+        let upload = match session.lock().unwrap().as_mut() {
+            Some(session) => replenish_upload(session, count)?,
+            None => None,
+        };
+        if let Some((node, count)) = upload {
+            uploads.lock().unwrap().track(&node, count);
+            ctx.send_node(&node)?;
+        }
+        Ok(())
+    }
+}
+
+/// Handler balasan `iq` upload pre-key: registrasi ditandai selesai, atau
+/// error dikirim ke aplikasi
+pub fn upload_response_handler(
+    session: Arc<Mutex<Option<Session>>>,
+    uploads: Arc<Mutex<PreKeyUploads>>,
+) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let count = match uploads.lock().unwrap().take(node) {
+            Some(count) => count,
+            None => return Ok(()),
+        };
+        match iq::check_response(node) {
+            Ok(_) => {
+                if let Some(session) = session.lock().unwrap().as_mut() {
+                    session.pre_keys_uploaded = true;
+                }
+            }
+            Err(e) => ctx.emit(Event::Error(format!("Uploading {} pre-keys failed: {}", count, e))),
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Membuat signed pre-key baru, melengkapi one-time pre-key, dan
+    /// mengunggah ulang data registrasi. Aman dipanggil berkala dari timer.
+    pub fn rotate_keys(&self) -> Result<KeyRotation> {
+        let (rotation, node) = {
+            let mut session_guard = self.session.lock().unwrap();
+            let session = session_guard.as_mut().ok_or("Not logged in")?;
+            let (rotation, new_keys) = rotate_session_keys(session, now_secs())?;
+            (rotation, upload_node(session, &new_keys))
+        };
+
+        self.query(&node, iq::DEFAULT_QUERY_TIMEOUT)?;
+        Ok(rotation)
+    }
+
+    /// Merotasi kunci hanya jika signed pre-key saat ini lebih tua dari
+    /// `max_age` dan masa tenggang kunci sebelumnya sudah habis
+    pub fn rotate_keys_if_due(&self, max_age: Duration) -> Result<Option<KeyRotation>> {
+        let (created, in_grace) = {
+            let session_guard = self.session.lock().unwrap();
+            let session = session_guard.as_ref().ok_or("Not logged in")?;
+            (session.signed_pre_key.timestamp, session.previous_signed_pre_key.is_some())
+        };
+        let max_age = if in_grace { max_age.max(SIGNED_PRE_KEY_GRACE) } else { max_age };
+        if now_secs() < created + max_age.as_secs() {
+            return Ok(None);
+        }
+        self.rotate_keys().map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_previous_key_during_grace() {
+        let mut session = Session::new();
+        let first_id = session.signed_pre_key.key_id;
+        let now = session.signed_pre_key.timestamp;
+
+        let (rotation, new_keys) = rotate_session_keys(&mut session, now).unwrap();
+        assert_ne!(rotation.signed_pre_key_id, first_id);
+        assert_eq!(rotation.retired_signed_pre_key_id, None);
+        assert_eq!(new_keys.len(), ONE_TIME_KEY_TARGET);
+        assert!(session.signed_pre_key_by_id(first_id).is_some());
+
+        // Rotasi kedua dalam masa tenggang tidak boleh menggeser kunci pertama
+        let second_id = rotation.signed_pre_key_id;
+        assert!(rotate_session_keys(&mut session, now + 60).is_err());
+        assert_eq!(session.signed_pre_key.key_id, second_id);
+        assert!(session.signed_pre_key_by_id(first_id).is_some());
+
+        let later = session.signed_pre_key.timestamp + SIGNED_PRE_KEY_GRACE.as_secs();
+        assert_eq!(retire_previous_signed_pre_key(&mut session, later, SIGNED_PRE_KEY_GRACE), Some(first_id));
+        assert!(session.signed_pre_key_by_id(first_id).is_none());
+    }
+
+    #[test]
+    fn test_upload_node_contains_new_keys() {
+        let mut session = Session::new();
+        let (_, new_keys) = rotate_session_keys(&mut session, 0).unwrap();
+        let node = upload_node(&session, &new_keys);
+        assert_eq!(node.get_child("list").unwrap().get_children().len(), ONE_TIME_KEY_TARGET);
+        assert_eq!(node.get_child("skey").and_then(|skey| skey.get_child("id")).and_then(|id| id.get_bytes()).map(|b| b.len()), Some(3));
+    }
+
+    #[test]
+    fn test_registration_and_replenish_uploads() {
+        let mut session = Session::new();
+        let (node, count) = registration_upload(&mut session).unwrap().unwrap();
+        assert_eq!(count, ONE_TIME_KEY_TARGET);
+        assert!(node.get_child("registration").is_some());
+        assert!(node.get_child("identity").is_some());
+
+        session.pre_keys_uploaded = true;
+        assert!(registration_upload(&mut session).unwrap().is_none());
+
+        let low = Node::new("notification").attr("type", "encrypt").children(vec![Node::new("count").attr("value", "3")]);
+        let count = parse_pre_key_count(&low).unwrap();
+        let (node, uploaded) = replenish_upload(&mut session, count).unwrap().unwrap();
+        assert_eq!(uploaded, ONE_TIME_KEY_TARGET - 3);
+        // Hanya kunci baru yang diunggah
+        let first = node.get_child("list").unwrap().get_children()[0].get_child("id").unwrap().get_bytes().unwrap().to_vec();
+        assert_eq!(first, key_id_bytes(ONE_TIME_KEY_TARGET as u32 + 1));
+        assert!(replenish_upload(&mut session, MIN_PRE_KEY_COUNT).unwrap().is_none());
+    }
+}
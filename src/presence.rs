@@ -0,0 +1,214 @@
+//! Langganan presence kontak
+//!
+//! Server hanya mengirim presence (online, terakhir dilihat, mengetik) untuk
+//! kontak yang dilanggan di koneksi yang sedang aktif. Daftar langganan
+//! disimpan client dan dikirim ulang otomatis setiap kali terotentikasi
+//! setelah reconnect.
+//!
+//! Indikator mengetik/merekam (`ChatState`) dikirim per chat; presence global
+//! tanpa `to` tidak pernah ditampilkan sebagai "mengetik…" oleh penerima.
+
+use std::collections::{HashMap, HashSet};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime};
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{Event, Jid, PresenceStatus, WhatsAppClient};
+
+/// Kontak yang presence-nya dilanggan
+#[derive(Default)]
+pub struct PresenceSubscriptions {
+    jids: HashSet<String>,
+}
+
+impl PresenceSubscriptions {
+    pub fn new() -> Self {
+        PresenceSubscriptions::default()
+    }
+
+    /// Mengembalikan false jika `jid` sudah dilanggan
+    pub fn add(&mut self, jid: &str) -> bool {
+        self.jids.insert(jid.to_string())
+    }
+
+    pub fn remove(&mut self, jid: &str) -> bool {
+        self.jids.remove(jid)
+    }
+
+    /// Node langganan untuk semua kontak, dikirim ulang setelah reconnect
+    pub fn resubscribe_nodes(&self) -> Vec<Node> {
+        self.jids.iter().map(|jid| subscribe_node(jid)).collect()
+    }
+}
+
+/// Indikator aktivitas di satu chat
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChatState {
+    Composing,
+    Paused,
+    /// Merekam voice note
+    Recording,
+}
+
+pub fn chat_state_node(to: &str, state: ChatState) -> Node {
+    let child = match state {
+        ChatState::Composing => Node::new("composing"),
+        ChatState::Paused => Node::new("paused"),
+        ChatState::Recording => Node::new("composing").attr("media", "audio"),
+    };
+    Node::new("chatstate").attr("to", to).children(vec![child])
+}
+
+/// Generasi chat state per chat; jeda otomatis hanya dikirim jika tidak ada
+/// chat state yang lebih baru sejak timer dipasang
+#[derive(Default)]
+pub struct ChatStateTimers {
+    generations: HashMap<String, u64>,
+}
+
+impl ChatStateTimers {
+    pub fn new() -> Self {
+        ChatStateTimers::default()
+    }
+
+    pub fn bump(&mut self, chat: &str) -> u64 {
+        let generation = self.generations.entry(chat.to_string()).or_insert(0);
+        *generation += 1;
+        *generation
+    }
+
+    pub fn is_current(&self, chat: &str, generation: u64) -> bool {
+        self.generations.get(chat) == Some(&generation)
+    }
+}
+
+pub fn subscribe_node(jid: &str) -> Node {
+    Node::new("presence").attr("type", "subscribe").attr("to", jid)
+}
+
+pub fn unsubscribe_node(jid: &str) -> Node {
+    Node::new("presence").attr("type", "unsubscribe").attr("to", jid)
+}
+
+/// Atribut `last` berisi detik sejak epoch; `deny`/`none` jika disembunyikan
+pub fn parse_last_seen(last: &str) -> Option<NaiveDateTime> {
+    let secs = last.parse::<i64>().ok().filter(|secs| *secs > 0)?;
+    DateTime::from_timestamp(secs, 0).map(|time| time.naive_utc())
+}
+
+/// Membaca `<presence from type [last]/>` dan `<chatstate from><composing [media]/></chatstate>`
+pub fn parse_presence(node: &Node) -> Option<(Jid, PresenceStatus, Option<NaiveDateTime>)> {
+    let from = Jid::from_string(node.get_attr("from")?).ok()?;
+    match node.tag.as_str() {
+        "presence" => {
+            let status = match node.get_attr("type") {
+                Some("unavailable") => PresenceStatus::Unavailable,
+                Some("available") | None => PresenceStatus::Available,
+                Some(_) => return None,
+            };
+            Some((from, status, node.get_attr("last").and_then(parse_last_seen)))
+        }
+        "chatstate" => {
+            let state = node.get_children().first()?;
+            let status = match (state.tag.as_str(), state.get_attr("media")) {
+                ("composing", Some("audio")) => PresenceStatus::Recording,
+                ("composing", _) => PresenceStatus::Typing,
+                ("paused", _) => PresenceStatus::Available,
+                _ => return None,
+            };
+            Some((from, status, None))
+        }
+        _ => None,
+    }
+}
+
+/// Handler `presence` dan `chatstate` yang mengirim `Event::PresenceChanged`
+pub fn presence_handler() -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if let Some((jid, status, last_seen)) = parse_presence(node) {
+            ctx.emit(Event::PresenceChanged(jid, status, last_seen));
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Mengirim indikator mengetik/merekam/jeda ke chat `to`
+    pub fn send_chat_state(&self, to: &Jid, state: ChatState) -> Result<()> {
+        self.chat_states.lock().unwrap().bump(&to.to_string());
+        self.send_node(&chat_state_node(&to.to_string(), state))
+    }
+
+    /// Seperti `send_chat_state`, lalu otomatis mengirim `Paused` setelah
+    /// `pause_after` kecuali ada chat state lain untuk chat ini sebelumnya
+    pub fn send_chat_state_for(&self, to: &Jid, state: ChatState, pause_after: Duration) -> Result<()> {
+        let chat = to.to_string();
+        let generation = self.chat_states.lock().unwrap().bump(&chat);
+        self.send_node(&chat_state_node(&chat, state))?;
+        if state == ChatState::Paused {
+            return Ok(());
+        }
+
+        let client = self.clone();
+        thread::spawn(move || {
+            thread::sleep(pause_after);
+            if client.chat_states.lock().unwrap().is_current(&chat, generation) {
+                client.send_node(&chat_state_node(&chat, ChatState::Paused)).ok();
+            }
+        });
+        Ok(())
+    }
+
+    /// Berlangganan presence `jid`; perubahan datang sebagai
+    /// `Event::PresenceChanged`. Langganan bertahan setelah reconnect.
+    pub fn subscribe_presence(&self, jid: &Jid) -> Result<()> {
+        if jid.is_group() {
+            return Err("Presence can only be subscribed for contacts".into());
+        }
+        self.presence.lock().unwrap().add(&jid.to_string());
+        self.send_node(&subscribe_node(&jid.to_string()))
+    }
+
+    pub fn unsubscribe_presence(&self, jid: &Jid) -> Result<()> {
+        if self.presence.lock().unwrap().remove(&jid.to_string()) {
+            self.send_node(&unsubscribe_node(&jid.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_last_seen() {
+        let node = Node::new("presence").attr("from", "628111@s.whatsapp.net").attr("type", "unavailable").attr("last", "1700000000");
+        let (jid, status, last_seen) = parse_presence(&node).unwrap();
+        assert_eq!(jid.to_string(), "628111@s.whatsapp.net");
+        assert!(matches!(status, PresenceStatus::Unavailable));
+        assert_eq!(last_seen.unwrap().to_string(), "2023-11-14 22:13:20");
+
+        assert_eq!(parse_last_seen("deny"), None);
+        let recording = Node::new("chatstate")
+            .attr("from", "628111@s.whatsapp.net")
+            .children(vec![Node::new("composing").attr("media", "audio")]);
+        assert!(matches!(parse_presence(&recording), Some((_, PresenceStatus::Recording, None))));
+    }
+
+    #[test]
+    fn test_newer_chat_state_cancels_auto_pause() {
+        let mut timers = ChatStateTimers::new();
+        let typing = timers.bump("628111@s.whatsapp.net");
+        assert!(timers.is_current("628111@s.whatsapp.net", typing));
+        timers.bump("628111@s.whatsapp.net");
+        assert!(!timers.is_current("628111@s.whatsapp.net", typing));
+
+        let node = chat_state_node("628111@s.whatsapp.net", ChatState::Recording);
+        assert_eq!(node.get_child("composing").and_then(|c| c.get_attr("media")), Some("audio"));
+    }
+}
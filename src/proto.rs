@@ -0,0 +1,1865 @@
+//! Tipe protobuf pesan, dibangkitkan dari `proto/wa_message.proto`
+//!
+//! Struct di modul ini dibuat oleh prost saat build, sehingga nomor field
+//! selalu sama dengan definisi protokol. Untuk mengikuti perubahan upstream,
+//! perbarui file `.proto`-nya; jangan mengedit struct secara manual.
+//! Fungsi di bawah hanya pembungkus kecil untuk kasus yang sering dipakai.
+//!
+//! Field yang membentuk siklus (mis. `Message` -> `ImageMessage` ->
+//! `ContextInfo` -> `Message`) dibungkus `Box` oleh prost.
+//!
+//! Struct di `crate::messages` tetap menjadi API publik; bentuk wire-nya
+//! selalu lewat konversi `From` di bawah, jadi `WebMessageInfo::to_bytes`
+//! dan `from_bytes` di sana menghasilkan protobuf yang sama dengan upstream.
+//! Field `messages` yang tidak ada di WAProto tidak ikut dikirim.
+
+include!(concat!(env!("OUT_DIR"), "/waproto.rs"));
+
+use prost::Message as _;
+
+use crate::errors::*;
+use crate::messages;
+
+/// Mendekode pesan protobuf apa pun dari bytes
+pub fn decode<M: prost::Message + Default>(bytes: &[u8]) -> Result<M> {
+    M::decode(bytes).map_err(|e| Error { kind: ErrorKind::InvalidPayload(format!("Invalid protobuf: {}", e)) })
+}
+
+impl WebMessageInfo {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode(bytes)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    /// Isi pesan setelah membuka pembungkus view once dan ephemeral
+    pub fn content(&self) -> Option<&Message> {
+        self.message.as_ref().map(Message::unwrap_future_proof)
+    }
+}
+
+impl Message {
+    /// Pesan teks biasa
+    pub fn text(text: &str) -> Self {
+        Message { conversation: Some(text.to_string()), ..Message::default() }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        decode(bytes)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+
+    /// Membuka pembungkus `viewOnceMessage`/`ephemeralMessage` (bisa bertingkat)
+    pub fn unwrap_future_proof(&self) -> &Message {
+        let inner = self
+            .view_once_message
+            .as_ref()
+            .or(self.ephemeral_message.as_ref())
+            .and_then(|wrapper| wrapper.message.as_deref());
+        match inner {
+            Some(message) => message.unwrap_future_proof(),
+            None => self,
+        }
+    }
+
+    /// Teks pesan: `conversation`, teks panjang, atau caption media
+    pub fn body(&self) -> Option<&str> {
+        let message = self.unwrap_future_proof();
+        message
+            .conversation
+            .as_deref()
+            .or_else(|| message.extended_text_message.as_ref().and_then(|m| m.text.as_deref()))
+            .or_else(|| message.image_message.as_ref().and_then(|m| m.caption.as_deref()))
+            .or_else(|| message.video_message.as_ref().and_then(|m| m.caption.as_deref()))
+    }
+
+    /// ContextInfo (reply, mention, forward) dari jenis pesan yang memilikinya
+    pub fn context_info(&self) -> Option<&ContextInfo> {
+        let message = self.unwrap_future_proof();
+        message
+            .extended_text_message
+            .as_ref()
+            .and_then(|m| m.context_info.as_deref())
+            .or_else(|| message.image_message.as_ref().and_then(|m| m.context_info.as_deref()))
+            .or_else(|| message.video_message.as_ref().and_then(|m| m.context_info.as_deref()))
+            .or_else(|| message.document_message.as_ref().and_then(|m| m.context_info.as_deref()))
+            .or_else(|| message.audio_message.as_ref().and_then(|m| m.context_info.as_deref()))
+            .or_else(|| message.sticker_message.as_ref().and_then(|m| m.context_info.as_deref()))
+    }
+}
+
+// ========================
+// KONVERSI DARI/KE `messages`
+// ========================
+
+fn encoded<'a, T: 'a, P: From<&'a T>>(value: &'a Option<T>) -> Option<P> {
+    value.as_ref().map(P::from)
+}
+
+fn boxed<'a, T: 'a, P: From<&'a T>>(value: &'a Option<T>) -> Option<Box<P>> {
+    value.as_ref().map(|value| Box::new(P::from(value)))
+}
+
+fn decoded<P, T: From<P>>(value: Option<P>) -> Option<T> {
+    value.map(T::from)
+}
+
+fn unboxed<P, T: From<P>>(value: Option<Box<P>>) -> Option<T> {
+    value.map(|value| T::from(*value))
+}
+
+fn encoded_all<'a, T: 'a, P: From<&'a T>>(values: &'a [T]) -> Vec<P> {
+    values.iter().map(P::from).collect()
+}
+
+fn decoded_all<P, T: From<P>>(values: Vec<P>) -> Vec<T> {
+    values.into_iter().map(T::from).collect()
+}
+
+impl From<&messages::WebMessageInfo> for WebMessageInfo {
+    fn from(info: &messages::WebMessageInfo) -> Self {
+        WebMessageInfo {
+            key: (&info.key).into(),
+            message: encoded(&info.message),
+            message_timestamp: info.message_timestamp,
+            status: info.status.map(|status| status as i32),
+            participant: info.participant.clone(),
+            ignore: info.ignore,
+            starred: info.starred,
+            broadcast: info.broadcast,
+            push_name: info.push_name.clone(),
+            media_ciphertext_sha256: info.media_ciphertext_sha256.clone(),
+            multicast: info.multicast,
+            url_text: info.url_text,
+            url_number: info.url_number,
+            message_stub_type: info.message_stub_type,
+            clear_media: info.clear_media,
+            message_stub_parameters: info.message_stub_parameters.clone(),
+            duration: info.duration,
+            labels: info.labels.clone(),
+            payment_info: encoded(&info.payment_info),
+            final_live_location: encoded(&info.final_live_location),
+            quoted_payment_info: encoded(&info.quoted_payment_info),
+            ephemeral_start_timestamp: info.ephemeral_start_timestamp,
+            ephemeral_duration: info.ephemeral_duration,
+            ephemeral_off_to_on: info.ephemeral_off_to_on,
+            ephemeral_out_of_sync: info.ephemeral_out_of_sync,
+            biz_privacy_status: info.biz_privacy_status,
+            verified_biz_name: info.verified_biz_name.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<WebMessageInfo> for messages::WebMessageInfo {
+    fn from(info: WebMessageInfo) -> Self {
+        messages::WebMessageInfo {
+            key: info.key.into(),
+            message: decoded(info.message),
+            message_timestamp: info.message_timestamp,
+            status: info.status.map(|status| status as u32),
+            participant: info.participant,
+            ignore: info.ignore,
+            starred: info.starred,
+            broadcast: info.broadcast,
+            push_name: info.push_name,
+            media_ciphertext_sha256: info.media_ciphertext_sha256,
+            multicast: info.multicast,
+            url_text: info.url_text,
+            url_number: info.url_number,
+            message_stub_type: info.message_stub_type,
+            clear_media: info.clear_media,
+            message_stub_parameters: info.message_stub_parameters,
+            duration: info.duration,
+            labels: info.labels,
+            payment_info: decoded(info.payment_info),
+            final_live_location: decoded(info.final_live_location),
+            quoted_payment_info: decoded(info.quoted_payment_info),
+            ephemeral_start_timestamp: info.ephemeral_start_timestamp,
+            ephemeral_duration: info.ephemeral_duration,
+            ephemeral_off_to_on: info.ephemeral_off_to_on,
+            ephemeral_out_of_sync: info.ephemeral_out_of_sync,
+            biz_privacy_status: info.biz_privacy_status,
+            verified_biz_name: info.verified_biz_name,
+        }
+    }
+}
+
+impl From<&messages::MessageKey> for MessageKey {
+    fn from(key: &messages::MessageKey) -> Self {
+        MessageKey {
+            remote_jid: Some(key.remote_jid.clone()),
+            from_me: Some(key.from_me),
+            id: Some(key.id.clone()),
+            participant: key.participant.clone(),
+        }
+    }
+}
+
+impl From<MessageKey> for messages::MessageKey {
+    fn from(key: MessageKey) -> Self {
+        messages::MessageKey {
+            remote_jid: key.remote_jid.unwrap_or_default(),
+            from_me: key.from_me.unwrap_or_default(),
+            id: key.id.unwrap_or_default(),
+            participant: key.participant,
+        }
+    }
+}
+
+impl From<&messages::PaymentInfo> for PaymentInfo {
+    fn from(info: &messages::PaymentInfo) -> Self {
+        PaymentInfo {
+            amount1000: Some(info.amount_1000),
+            receiver_jid: Some(info.receiver_jid.clone()),
+            status: Some(info.status),
+            transaction_timestamp: Some(info.transaction_timestamp),
+            request_message_key: info.request_message_key.as_ref().map(MessageKey::from),
+            expiry_timestamp: Some(info.expiry_timestamp),
+            futureproofed: Some(info.futureproofed),
+            currency: Some(if info.currency_code_iso4217.is_empty() { info.currency.clone() } else { info.currency_code_iso4217.clone() }),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<PaymentInfo> for messages::PaymentInfo {
+    fn from(info: PaymentInfo) -> Self {
+        let currency = info.currency.unwrap_or_default();
+        messages::PaymentInfo {
+            currency: currency.clone(),
+            amount_1000: info.amount1000.unwrap_or_default(),
+            receiver_jid: info.receiver_jid.unwrap_or_default(),
+            status: info.status.unwrap_or_default(),
+            transaction_timestamp: info.transaction_timestamp.unwrap_or_default(),
+            request_message_key: decoded(info.request_message_key),
+            expiry_timestamp: info.expiry_timestamp.unwrap_or_default(),
+            futureproofed: info.futureproofed.unwrap_or_default(),
+            currency_code_iso4217: currency,
+        }
+    }
+}
+
+impl From<&messages::Message> for Message {
+    fn from(message: &messages::Message) -> Self {
+        Message {
+            conversation: message.conversation.clone(),
+            sender_key_distribution_message: encoded(&message.sender_key_distribution_message),
+            image_message: boxed(&message.image_message),
+            contact_message: boxed(&message.contact_message),
+            location_message: boxed(&message.location_message),
+            extended_text_message: boxed(&message.extended_text_message),
+            document_message: boxed(&message.document_message),
+            audio_message: boxed(&message.audio_message),
+            video_message: boxed(&message.video_message),
+            call: encoded(&message.call),
+            chat: encoded(&message.chat),
+            protocol_message: encoded(&message.protocol_message),
+            contacts_array_message: boxed(&message.contacts_array_message),
+            highly_structured_message: boxed(&message.highly_structured_message),
+            fast_ratchet_key_sender_key_distribution_message: encoded(&message.fast_ratchet_key_sender_key_distribution_message),
+            send_payment_message: boxed(&message.send_payment_message),
+            live_location_message: boxed(&message.live_location_message),
+            request_payment_message: boxed(&message.request_payment_message),
+            decline_payment_request_message: encoded(&message.decline_payment_message),
+            cancel_payment_request_message: encoded(&message.cancel_payment_message),
+            template_message: boxed(&message.template_message),
+            sticker_message: boxed(&message.sticker_message),
+            group_invite_message: boxed(&message.group_invite_message),
+            template_button_reply_message: boxed(&message.template_button_reply_message),
+            product_message: boxed(&message.product_message),
+            device_sent_message: boxed(&message.device_sent_message),
+            message_context_info: encoded(&message.message_context_info),
+            list_message: boxed(&message.list_message),
+            order_message: boxed(&message.order_message),
+            list_response_message: boxed(&message.list_response_message),
+            buttons_message: boxed(&message.buttons_message),
+            buttons_response_message: boxed(&message.buttons_response_message),
+            payment_invite_message: encoded(&message.payment_invitation_message),
+            interactive_message: boxed(&message.interactive_message),
+            reaction_message: encoded(&message.reaction_message),
+            interactive_response_message: boxed(&message.interactive_response_message),
+            poll_creation_message: boxed(&message.poll_creation_message),
+            poll_update_message: encoded(&message.poll_update_message),
+            keep_in_chat_message: encoded(&message.keep_in_chat_message),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<Message> for messages::Message {
+    fn from(mut message: Message) -> Self {
+        // View once dan pesan sementara dibuka; isinya dipakai langsung
+        let wrapped = message.view_once_message.take().or_else(|| message.ephemeral_message.take());
+        if let Some(inner) = wrapped.and_then(|wrapper| wrapper.message) {
+            let mut inner = messages::Message::from(*inner);
+            if inner.message_context_info.is_none() {
+                inner.message_context_info = decoded(message.message_context_info);
+            }
+            return inner;
+        }
+        messages::Message {
+            conversation: message.conversation,
+            image_message: unboxed(message.image_message),
+            contact_message: unboxed(message.contact_message),
+            location_message: unboxed(message.location_message),
+            extended_text_message: unboxed(message.extended_text_message),
+            document_message: unboxed(message.document_message),
+            audio_message: unboxed(message.audio_message),
+            video_message: unboxed(message.video_message),
+            call: decoded(message.call),
+            chat: decoded(message.chat),
+            protocol_message: decoded(message.protocol_message),
+            contacts_array_message: unboxed(message.contacts_array_message),
+            highly_structured_message: unboxed(message.highly_structured_message),
+            sender_key_distribution_message: decoded(message.sender_key_distribution_message),
+            fast_ratchet_key_sender_key_distribution_message: decoded(message.fast_ratchet_key_sender_key_distribution_message),
+            send_payment_message: unboxed(message.send_payment_message),
+            live_location_message: unboxed(message.live_location_message),
+            request_payment_message: unboxed(message.request_payment_message),
+            decline_payment_message: decoded(message.decline_payment_request_message),
+            cancel_payment_message: decoded(message.cancel_payment_request_message),
+            template_message: unboxed(message.template_message),
+            sticker_message: unboxed(message.sticker_message),
+            group_invite_message: unboxed(message.group_invite_message),
+            template_button_reply_message: unboxed(message.template_button_reply_message),
+            product_message: unboxed(message.product_message),
+            device_sent_message: unboxed(message.device_sent_message),
+            message_context_info: decoded(message.message_context_info),
+            list_message: unboxed(message.list_message),
+            list_response_message: unboxed(message.list_response_message),
+            buttons_response_message: unboxed(message.buttons_response_message),
+            buttons_message: unboxed(message.buttons_message),
+            payment_invitation_message: decoded(message.payment_invite_message),
+            interactive_message: unboxed(message.interactive_message),
+            reaction_message: decoded(message.reaction_message),
+            interactive_response_message: unboxed(message.interactive_response_message),
+            poll_creation_message: unboxed(message.poll_creation_message),
+            poll_update_message: decoded(message.poll_update_message),
+            keep_in_chat_message: decoded(message.keep_in_chat_message),
+            order_message: unboxed(message.order_message),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::MessageContextInfo> for ContextInfo {
+    fn from(context: &messages::MessageContextInfo) -> Self {
+        ContextInfo {
+            stanza_id: context.stanza_id.clone(),
+            participant: context.participant.clone(),
+            quoted_message: context.quoted_message.as_deref().map(|quoted| Box::new(quoted.into())),
+            remote_jid: context.remote_jid.clone(),
+            mentioned_jid: context.mentioned_jid.clone(),
+            is_forwarded: context.is_forwarded,
+            expiration: context.expiration,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<ContextInfo> for messages::MessageContextInfo {
+    fn from(context: ContextInfo) -> Self {
+        messages::MessageContextInfo {
+            stanza_id: context.stanza_id,
+            quoted_message: context.quoted_message.map(|quoted| Box::new((*quoted).into())),
+            remote_jid: context.remote_jid,
+            mentioned_jid: context.mentioned_jid,
+            is_forwarded: context.is_forwarded,
+            participant: context.participant,
+            expiration: context.expiration,
+            ..Default::default()
+        }
+    }
+}
+
+/// `Message.messageContextInfo` hanya membawa metadata perangkat dan secret pesan
+impl From<&messages::MessageContextInfo> for MessageContextInfo {
+    fn from(context: &messages::MessageContextInfo) -> Self {
+        MessageContextInfo {
+            device_list_metadata: encoded(&context.device_list_metadata),
+            message_secret: context.message_secret.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<MessageContextInfo> for messages::MessageContextInfo {
+    fn from(context: MessageContextInfo) -> Self {
+        messages::MessageContextInfo {
+            device_list_metadata: decoded(context.device_list_metadata),
+            message_secret: context.message_secret,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::DeviceListMetadata> for DeviceListMetadata {
+    fn from(metadata: &messages::DeviceListMetadata) -> Self {
+        DeviceListMetadata {
+            sender_key_hash: metadata.sender_key_hash.clone(),
+            sender_timestamp: metadata.sender_timestamp,
+            recipient_key_hash: metadata.recipient_key_hash.clone(),
+            recipient_timestamp: metadata.recipient_timestamp,
+        }
+    }
+}
+
+impl From<DeviceListMetadata> for messages::DeviceListMetadata {
+    fn from(metadata: DeviceListMetadata) -> Self {
+        messages::DeviceListMetadata {
+            sender_key_hash: metadata.sender_key_hash,
+            sender_timestamp: metadata.sender_timestamp,
+            recipient_key_hash: metadata.recipient_key_hash,
+            recipient_timestamp: metadata.recipient_timestamp,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::SenderKeyDistributionMessage> for SenderKeyDistributionMessage {
+    fn from(message: &messages::SenderKeyDistributionMessage) -> Self {
+        SenderKeyDistributionMessage {
+            group_id: Some(message.group_id.clone()),
+            axolotl_sender_key_distribution_message: Some(message.axolotl_sender_key_distribution_message.clone()),
+        }
+    }
+}
+
+impl From<SenderKeyDistributionMessage> for messages::SenderKeyDistributionMessage {
+    fn from(message: SenderKeyDistributionMessage) -> Self {
+        messages::SenderKeyDistributionMessage {
+            group_id: message.group_id.unwrap_or_default(),
+            axolotl_sender_key_distribution_message: message.axolotl_sender_key_distribution_message.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&messages::ImageMessage> for ImageMessage {
+    fn from(image: &messages::ImageMessage) -> Self {
+        ImageMessage {
+            url: Some(image.url.clone()),
+            mimetype: image.mimetype.clone(),
+            caption: image.caption.clone(),
+            file_sha256: Some(image.file_sha256.clone()),
+            file_length: Some(image.file_length),
+            height: Some(image.height),
+            width: Some(image.width),
+            media_key: Some(image.media_key.clone()),
+            file_enc_sha256: Some(image.file_enc_sha256.clone()),
+            direct_path: Some(image.direct_path.clone()),
+            media_key_timestamp: Some(image.media_key_timestamp),
+            jpeg_thumbnail: image.jpeg_thumbnail.clone(),
+            context_info: boxed(&image.context_info),
+            view_once: image.view_once,
+        }
+    }
+}
+
+impl From<ImageMessage> for messages::ImageMessage {
+    fn from(image: ImageMessage) -> Self {
+        messages::ImageMessage {
+            url: image.url.unwrap_or_default(),
+            mimetype: image.mimetype,
+            caption: image.caption,
+            file_sha256: image.file_sha256.unwrap_or_default(),
+            file_length: image.file_length.unwrap_or_default(),
+            height: image.height.unwrap_or_default(),
+            width: image.width.unwrap_or_default(),
+            media_key: image.media_key.unwrap_or_default(),
+            file_enc_sha256: image.file_enc_sha256.unwrap_or_default(),
+            direct_path: image.direct_path.unwrap_or_default(),
+            media_key_timestamp: image.media_key_timestamp.unwrap_or_default(),
+            jpeg_thumbnail: image.jpeg_thumbnail,
+            context_info: unboxed(image.context_info),
+            view_once: image.view_once,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::ContactMessage> for ContactMessage {
+    fn from(contact: &messages::ContactMessage) -> Self {
+        ContactMessage {
+            display_name: Some(contact.display_name.clone()),
+            vcard: Some(contact.vcard.clone()),
+            context_info: boxed(&contact.context_info),
+        }
+    }
+}
+
+impl From<ContactMessage> for messages::ContactMessage {
+    fn from(contact: ContactMessage) -> Self {
+        messages::ContactMessage {
+            display_name: contact.display_name.unwrap_or_default(),
+            vcard: contact.vcard.unwrap_or_default(),
+            context_info: unboxed(contact.context_info),
+        }
+    }
+}
+
+impl From<&messages::ContactsArrayMessage> for ContactsArrayMessage {
+    fn from(contacts: &messages::ContactsArrayMessage) -> Self {
+        ContactsArrayMessage {
+            display_name: Some(contacts.display_name.clone()),
+            contacts: encoded_all(&contacts.contacts),
+            context_info: boxed(&contacts.context_info),
+        }
+    }
+}
+
+impl From<ContactsArrayMessage> for messages::ContactsArrayMessage {
+    fn from(contacts: ContactsArrayMessage) -> Self {
+        messages::ContactsArrayMessage {
+            display_name: contacts.display_name.unwrap_or_default(),
+            contacts: decoded_all(contacts.contacts),
+            context_info: unboxed(contacts.context_info),
+        }
+    }
+}
+
+impl From<&messages::LocationMessage> for LocationMessage {
+    fn from(location: &messages::LocationMessage) -> Self {
+        LocationMessage {
+            degrees_latitude: Some(location.degrees_latitude),
+            degrees_longitude: Some(location.degrees_longitude),
+            name: location.name.clone(),
+            address: location.address.clone(),
+            url: location.url.clone(),
+            is_live: location.is_live,
+            accuracy_in_meters: location.accuracy_in_meters,
+            speed_in_mps: location.speed_in_mps,
+            degrees_clockwise_from_magnetic_north: location.degrees_clockwise_from_magnetic_north,
+            comment: location.comment.clone(),
+            jpeg_thumbnail: location.jpeg_thumbnail.clone(),
+            context_info: boxed(&location.context_info),
+        }
+    }
+}
+
+impl From<LocationMessage> for messages::LocationMessage {
+    fn from(location: LocationMessage) -> Self {
+        messages::LocationMessage {
+            degrees_latitude: location.degrees_latitude.unwrap_or_default(),
+            degrees_longitude: location.degrees_longitude.unwrap_or_default(),
+            name: location.name,
+            address: location.address,
+            url: location.url,
+            is_live: location.is_live,
+            accuracy_in_meters: location.accuracy_in_meters,
+            speed_in_mps: location.speed_in_mps,
+            degrees_clockwise_from_magnetic_north: location.degrees_clockwise_from_magnetic_north,
+            comment: location.comment,
+            jpeg_thumbnail: location.jpeg_thumbnail,
+            context_info: unboxed(location.context_info),
+        }
+    }
+}
+
+impl From<&messages::LiveLocationMessage> for LiveLocationMessage {
+    fn from(location: &messages::LiveLocationMessage) -> Self {
+        LiveLocationMessage {
+            degrees_latitude: Some(location.degrees_latitude),
+            degrees_longitude: Some(location.degrees_longitude),
+            accuracy_in_meters: location.accuracy_in_meters,
+            speed_in_mps: location.speed_in_mps,
+            degrees_clockwise_from_magnetic_north: location.degrees_clockwise_from_magnetic_north,
+            caption: location.caption.clone(),
+            sequence_number: location.sequence_number,
+            time_offset: location.time_offset,
+            jpeg_thumbnail: location.jpeg_thumbnail.clone(),
+            context_info: boxed(&location.context_info),
+        }
+    }
+}
+
+impl From<LiveLocationMessage> for messages::LiveLocationMessage {
+    fn from(location: LiveLocationMessage) -> Self {
+        messages::LiveLocationMessage {
+            degrees_latitude: location.degrees_latitude.unwrap_or_default(),
+            degrees_longitude: location.degrees_longitude.unwrap_or_default(),
+            accuracy_in_meters: location.accuracy_in_meters,
+            speed_in_mps: location.speed_in_mps,
+            degrees_clockwise_from_magnetic_north: location.degrees_clockwise_from_magnetic_north,
+            caption: location.caption,
+            sequence_number: location.sequence_number,
+            time_offset: location.time_offset,
+            jpeg_thumbnail: location.jpeg_thumbnail,
+            context_info: unboxed(location.context_info),
+        }
+    }
+}
+
+impl From<&messages::ExtendedTextMessage> for ExtendedTextMessage {
+    fn from(text: &messages::ExtendedTextMessage) -> Self {
+        ExtendedTextMessage {
+            text: Some(text.text.clone()),
+            matched_text: text.matched_text.clone(),
+            canonical_url: text.canonical_url.clone(),
+            description: text.description.clone(),
+            title: text.title.clone(),
+            text_argb: text.text_argb,
+            background_argb: text.background_argb,
+            font: text.font,
+            preview_type: text.preview_type,
+            jpeg_thumbnail: text.jpeg_thumbnail.clone(),
+            context_info: boxed(&text.context_info),
+            do_not_play_inline: text.do_not_play_inline,
+        }
+    }
+}
+
+impl From<ExtendedTextMessage> for messages::ExtendedTextMessage {
+    fn from(text: ExtendedTextMessage) -> Self {
+        messages::ExtendedTextMessage {
+            text: text.text.unwrap_or_default(),
+            matched_text: text.matched_text,
+            canonical_url: text.canonical_url,
+            description: text.description,
+            title: text.title,
+            text_argb: text.text_argb,
+            background_argb: text.background_argb,
+            font: text.font,
+            preview_type: text.preview_type,
+            jpeg_thumbnail: text.jpeg_thumbnail,
+            context_info: unboxed(text.context_info),
+            do_not_play_inline: text.do_not_play_inline,
+        }
+    }
+}
+
+impl From<&messages::DocumentMessage> for DocumentMessage {
+    fn from(document: &messages::DocumentMessage) -> Self {
+        DocumentMessage {
+            url: Some(document.url.clone()),
+            mimetype: Some(document.mimetype.clone()),
+            title: Some(document.title.clone()),
+            file_sha256: Some(document.file_sha256.clone()),
+            file_length: Some(document.file_length),
+            page_count: document.page_count,
+            media_key: Some(document.media_key.clone()),
+            file_name: Some(document.file_name.clone()),
+            file_enc_sha256: Some(document.file_enc_sha256.clone()),
+            direct_path: Some(document.direct_path.clone()),
+            media_key_timestamp: Some(document.media_key_timestamp),
+            thumbnail_direct_path: document.thumbnail_direct_path.clone(),
+            thumbnail_sha256: document.thumbnail_sha256.clone(),
+            thumbnail_enc_sha256: document.thumbnail_enc_sha256.clone(),
+            jpeg_thumbnail: document.jpeg_thumbnail.clone(),
+            context_info: boxed(&document.context_info),
+        }
+    }
+}
+
+impl From<DocumentMessage> for messages::DocumentMessage {
+    fn from(document: DocumentMessage) -> Self {
+        messages::DocumentMessage {
+            url: document.url.unwrap_or_default(),
+            mimetype: document.mimetype.unwrap_or_default(),
+            title: document.title.unwrap_or_default(),
+            file_sha256: document.file_sha256.unwrap_or_default(),
+            file_length: document.file_length.unwrap_or_default(),
+            page_count: document.page_count,
+            media_key: document.media_key.unwrap_or_default(),
+            file_name: document.file_name.unwrap_or_default(),
+            file_enc_sha256: document.file_enc_sha256.unwrap_or_default(),
+            direct_path: document.direct_path.unwrap_or_default(),
+            media_key_timestamp: document.media_key_timestamp.unwrap_or_default(),
+            jpeg_thumbnail: document.jpeg_thumbnail,
+            context_info: unboxed(document.context_info),
+            thumbnail_direct_path: document.thumbnail_direct_path,
+            thumbnail_sha256: document.thumbnail_sha256,
+            thumbnail_enc_sha256: document.thumbnail_enc_sha256,
+        }
+    }
+}
+
+impl From<&messages::AudioMessage> for AudioMessage {
+    fn from(audio: &messages::AudioMessage) -> Self {
+        AudioMessage {
+            url: Some(audio.url.clone()),
+            mimetype: Some(audio.mimetype.clone()),
+            file_sha256: Some(audio.file_sha256.clone()),
+            file_length: Some(audio.file_length),
+            seconds: Some(audio.seconds),
+            ptt: Some(audio.ptt),
+            media_key: Some(audio.media_key.clone()),
+            file_enc_sha256: Some(audio.file_enc_sha256.clone()),
+            direct_path: Some(audio.direct_path.clone()),
+            media_key_timestamp: Some(audio.media_key_timestamp),
+            context_info: boxed(&audio.context_info),
+            streaming_sidecar: audio.streaming_sidecar.clone(),
+            waveform: audio.waveform.clone(),
+        }
+    }
+}
+
+impl From<AudioMessage> for messages::AudioMessage {
+    fn from(audio: AudioMessage) -> Self {
+        messages::AudioMessage {
+            url: audio.url.unwrap_or_default(),
+            mimetype: audio.mimetype.unwrap_or_default(),
+            file_sha256: audio.file_sha256.unwrap_or_default(),
+            file_length: audio.file_length.unwrap_or_default(),
+            seconds: audio.seconds.unwrap_or_default(),
+            ptt: audio.ptt.unwrap_or_default(),
+            media_key: audio.media_key.unwrap_or_default(),
+            file_enc_sha256: audio.file_enc_sha256.unwrap_or_default(),
+            direct_path: audio.direct_path.unwrap_or_default(),
+            media_key_timestamp: audio.media_key_timestamp.unwrap_or_default(),
+            context_info: unboxed(audio.context_info),
+            streaming_sidecar: audio.streaming_sidecar,
+            waveform: audio.waveform,
+        }
+    }
+}
+
+impl From<&messages::VideoMessage> for VideoMessage {
+    fn from(video: &messages::VideoMessage) -> Self {
+        VideoMessage {
+            url: Some(video.url.clone()),
+            mimetype: Some(video.mimetype.clone()),
+            file_sha256: Some(video.file_sha256.clone()),
+            file_length: Some(video.file_length),
+            seconds: Some(video.seconds),
+            media_key: Some(video.media_key.clone()),
+            caption: video.caption.clone(),
+            gif_playback: video.gif_playback,
+            height: Some(video.height),
+            width: Some(video.width),
+            file_enc_sha256: Some(video.file_enc_sha256.clone()),
+            direct_path: Some(video.direct_path.clone()),
+            media_key_timestamp: Some(video.media_key_timestamp),
+            jpeg_thumbnail: video.jpeg_thumbnail.clone(),
+            context_info: boxed(&video.context_info),
+            streaming_sidecar: video.streaming_sidecar.clone(),
+            gif_attribution: video.gif_attribution,
+            view_once: video.view_once,
+            thumbnail_direct_path: video.thumbnail_direct_path.clone(),
+            thumbnail_sha256: video.thumbnail_sha256.clone(),
+            thumbnail_enc_sha256: video.thumbnail_enc_sha256.clone(),
+        }
+    }
+}
+
+impl From<VideoMessage> for messages::VideoMessage {
+    fn from(video: VideoMessage) -> Self {
+        messages::VideoMessage {
+            url: video.url.unwrap_or_default(),
+            mimetype: video.mimetype.unwrap_or_default(),
+            file_sha256: video.file_sha256.unwrap_or_default(),
+            file_length: video.file_length.unwrap_or_default(),
+            seconds: video.seconds.unwrap_or_default(),
+            media_key: video.media_key.unwrap_or_default(),
+            caption: video.caption,
+            gif_playback: video.gif_playback,
+            height: video.height.unwrap_or_default(),
+            width: video.width.unwrap_or_default(),
+            file_enc_sha256: video.file_enc_sha256.unwrap_or_default(),
+            direct_path: video.direct_path.unwrap_or_default(),
+            media_key_timestamp: video.media_key_timestamp.unwrap_or_default(),
+            jpeg_thumbnail: video.jpeg_thumbnail,
+            context_info: unboxed(video.context_info),
+            streaming_sidecar: video.streaming_sidecar,
+            gif_attribution: video.gif_attribution,
+            view_once: video.view_once,
+            thumbnail_direct_path: video.thumbnail_direct_path,
+            thumbnail_sha256: video.thumbnail_sha256,
+            thumbnail_enc_sha256: video.thumbnail_enc_sha256,
+        }
+    }
+}
+
+impl From<&messages::StickerMessage> for StickerMessage {
+    fn from(sticker: &messages::StickerMessage) -> Self {
+        StickerMessage {
+            url: Some(sticker.url.clone()),
+            file_sha256: Some(sticker.file_sha256.clone()),
+            file_enc_sha256: Some(sticker.file_enc_sha256.clone()),
+            media_key: Some(sticker.media_key.clone()),
+            mimetype: Some(sticker.mimetype.clone()),
+            height: Some(sticker.height),
+            width: Some(sticker.width),
+            direct_path: Some(sticker.direct_path.clone()),
+            file_length: Some(sticker.file_length),
+            media_key_timestamp: Some(sticker.media_key_timestamp),
+            first_frame_length: sticker.first_frame_length,
+            first_frame_sidecar: sticker.first_frame_sidecar.clone(),
+            is_animated: sticker.is_animated,
+            png_thumbnail: sticker.png_thumbnail.clone(),
+            context_info: boxed(&sticker.context_info),
+        }
+    }
+}
+
+impl From<StickerMessage> for messages::StickerMessage {
+    fn from(sticker: StickerMessage) -> Self {
+        messages::StickerMessage {
+            url: sticker.url.unwrap_or_default(),
+            file_sha256: sticker.file_sha256.unwrap_or_default(),
+            file_enc_sha256: sticker.file_enc_sha256.unwrap_or_default(),
+            media_key: sticker.media_key.unwrap_or_default(),
+            mimetype: sticker.mimetype.unwrap_or_default(),
+            height: sticker.height.unwrap_or_default(),
+            width: sticker.width.unwrap_or_default(),
+            direct_path: sticker.direct_path.unwrap_or_default(),
+            file_length: sticker.file_length.unwrap_or_default(),
+            media_key_timestamp: sticker.media_key_timestamp.unwrap_or_default(),
+            first_frame_length: sticker.first_frame_length,
+            first_frame_sidecar: sticker.first_frame_sidecar,
+            is_animated: sticker.is_animated,
+            png_thumbnail: sticker.png_thumbnail,
+            context_info: unboxed(sticker.context_info),
+        }
+    }
+}
+
+impl From<&messages::Call> for Call {
+    fn from(call: &messages::Call) -> Self {
+        Call { call_key: Some(call.call_key.clone()) }
+    }
+}
+
+impl From<Call> for messages::Call {
+    fn from(call: Call) -> Self {
+        messages::Call { call_key: call.call_key.unwrap_or_default() }
+    }
+}
+
+impl From<&messages::Chat> for Chat {
+    fn from(chat: &messages::Chat) -> Self {
+        Chat { display_name: Some(chat.display_name.clone()), id: Some(chat.id.clone()) }
+    }
+}
+
+impl From<Chat> for messages::Chat {
+    fn from(chat: Chat) -> Self {
+        messages::Chat { display_name: chat.display_name.unwrap_or_default(), id: chat.id.unwrap_or_default() }
+    }
+}
+
+impl From<&messages::GroupInviteMessage> for GroupInviteMessage {
+    fn from(invite: &messages::GroupInviteMessage) -> Self {
+        GroupInviteMessage {
+            group_jid: Some(invite.group_jid.clone()),
+            invite_code: Some(invite.invite_code.clone()),
+            invite_expiration: Some(invite.invite_expiration),
+            group_name: Some(invite.group_name.clone()),
+            jpeg_thumbnail: invite.jpeg_thumbnail.clone(),
+            caption: invite.caption.clone(),
+            context_info: boxed(&invite.context_info),
+        }
+    }
+}
+
+impl From<GroupInviteMessage> for messages::GroupInviteMessage {
+    fn from(invite: GroupInviteMessage) -> Self {
+        messages::GroupInviteMessage {
+            group_jid: invite.group_jid.unwrap_or_default(),
+            invite_code: invite.invite_code.unwrap_or_default(),
+            invite_expiration: invite.invite_expiration.unwrap_or_default(),
+            group_name: invite.group_name.unwrap_or_default(),
+            jpeg_thumbnail: invite.jpeg_thumbnail,
+            caption: invite.caption,
+            context_info: unboxed(invite.context_info),
+        }
+    }
+}
+
+impl From<&messages::DeviceSentMessage> for DeviceSentMessage {
+    fn from(sent: &messages::DeviceSentMessage) -> Self {
+        DeviceSentMessage {
+            destination_jid: sent.destination_jid.clone(),
+            message: sent.message.as_deref().map(|message| Box::new(message.into())),
+            phash: sent.phash.clone(),
+        }
+    }
+}
+
+impl From<DeviceSentMessage> for messages::DeviceSentMessage {
+    fn from(sent: DeviceSentMessage) -> Self {
+        messages::DeviceSentMessage {
+            destination_jid: sent.destination_jid,
+            message: sent.message.map(|message| Box::new((*message).into())),
+            phash: sent.phash,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::ReactionMessage> for ReactionMessage {
+    fn from(reaction: &messages::ReactionMessage) -> Self {
+        ReactionMessage {
+            key: Some((&reaction.key).into()),
+            text: Some(reaction.text.clone()),
+            grouping_key: Some(reaction.grouping_key.clone()),
+            sender_timestamp_ms: Some(reaction.sender_timestamp_ms),
+        }
+    }
+}
+
+impl From<ReactionMessage> for messages::ReactionMessage {
+    fn from(reaction: ReactionMessage) -> Self {
+        messages::ReactionMessage {
+            key: reaction.key.unwrap_or_default().into(),
+            text: reaction.text.unwrap_or_default(),
+            grouping_key: reaction.grouping_key.unwrap_or_default(),
+            sender_timestamp_ms: reaction.sender_timestamp_ms.unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::PollCreationMessage> for PollCreationMessage {
+    fn from(poll: &messages::PollCreationMessage) -> Self {
+        PollCreationMessage {
+            name: Some(poll.name.clone()),
+            options: poll
+                .options
+                .iter()
+                .map(|option| poll_creation_message::Option { option_name: Some(option.option_name.clone()) })
+                .collect(),
+            selectable_options_count: Some(poll.selectable_count),
+            context_info: boxed(&poll.context_info),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<PollCreationMessage> for messages::PollCreationMessage {
+    fn from(poll: PollCreationMessage) -> Self {
+        messages::PollCreationMessage {
+            name: poll.name.unwrap_or_default(),
+            selectable_count: poll.selectable_options_count.unwrap_or_default(),
+            options: poll
+                .options
+                .into_iter()
+                .map(|option| messages::PollOption { option_name: option.option_name.unwrap_or_default() })
+                .collect(),
+            context_info: unboxed(poll.context_info),
+        }
+    }
+}
+
+impl From<&messages::PollUpdateMessage> for PollUpdateMessage {
+    fn from(update: &messages::PollUpdateMessage) -> Self {
+        PollUpdateMessage {
+            poll_creation_message_key: encoded(&update.poll_creation_message_key),
+            vote: Some(PollEncValue {
+                enc_payload: Some(update.poll_update.vote.enc_payload.clone()),
+                enc_iv: Some(update.poll_update.vote.enc_iv.clone()),
+            }),
+            sender_timestamp_ms: Some(update.sender_timestamp_ms),
+        }
+    }
+}
+
+impl From<PollUpdateMessage> for messages::PollUpdateMessage {
+    fn from(update: PollUpdateMessage) -> Self {
+        let vote = update.vote.unwrap_or_default();
+        messages::PollUpdateMessage {
+            poll_creation_message_key: decoded(update.poll_creation_message_key),
+            poll_update: messages::PollUpdate {
+                vote: messages::PollEncValue { enc_iv: vote.enc_iv.unwrap_or_default(), enc_payload: vote.enc_payload.unwrap_or_default() },
+            },
+            sender_timestamp_ms: update.sender_timestamp_ms.unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::KeepInChatMessage> for KeepInChatMessage {
+    fn from(keep: &messages::KeepInChatMessage) -> Self {
+        KeepInChatMessage {
+            key: Some((&keep.key).into()),
+            keep_type: Some(keep.action),
+            timestamp_ms: Some(keep.timestamp_ms),
+        }
+    }
+}
+
+impl From<KeepInChatMessage> for messages::KeepInChatMessage {
+    fn from(keep: KeepInChatMessage) -> Self {
+        let timestamp_ms = keep.timestamp_ms.unwrap_or_default();
+        messages::KeepInChatMessage {
+            key: keep.key.unwrap_or_default().into(),
+            action: keep.keep_type.unwrap_or_default(),
+            timestamp_ms,
+            sender_timestamp_ms: timestamp_ms,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::HighlyStructuredMessage> for HighlyStructuredMessage {
+    fn from(hsm: &messages::HighlyStructuredMessage) -> Self {
+        use highly_structured_message::hsm_localizable_parameter::{hsm_date_time, HsmCurrency, HsmDateTime};
+        use highly_structured_message::HsmLocalizableParameter;
+
+        HighlyStructuredMessage {
+            namespace: Some(hsm.namespace.clone()),
+            element_name: Some(hsm.element_name.clone()),
+            params: hsm.params.clone(),
+            fallback_lg: Some(hsm.fallback_lg.clone()),
+            fallback_lc: Some(hsm.fallback_lc.clone()),
+            localizable_params: hsm
+                .localizable_params
+                .iter()
+                .map(|param| HsmLocalizableParameter {
+                    default: Some(param.default.clone()),
+                    currency: param
+                        .currency
+                        .as_ref()
+                        .map(|currency| HsmCurrency { currency_code: Some(currency.currency_code.clone()), amount1000: Some(currency.amount_1000) }),
+                    date_time: param.date_time_component.as_ref().map(|component| HsmDateTime {
+                        component: Some(hsm_date_time::HsmDateTimeComponent {
+                            day_of_week: Some(component.day_of_week),
+                            year: Some(component.year),
+                            month: Some(component.month),
+                            day_of_month: Some(component.day_of_month),
+                            hour: Some(component.hour),
+                            minute: Some(component.minute),
+                            calendar: Some(component.calendar),
+                        }),
+                    }),
+                })
+                .collect(),
+            deterministic_lg: hsm.deterministic_lg.clone(),
+            deterministic_lc: hsm.deterministic_lc.clone(),
+            hydrated_hsm: boxed(&hsm.hydrated_hsm),
+        }
+    }
+}
+
+impl From<HighlyStructuredMessage> for messages::HighlyStructuredMessage {
+    fn from(hsm: HighlyStructuredMessage) -> Self {
+        messages::HighlyStructuredMessage {
+            namespace: hsm.namespace.unwrap_or_default(),
+            element_name: hsm.element_name.unwrap_or_default(),
+            params: hsm.params,
+            fallback_lg: hsm.fallback_lg.unwrap_or_default(),
+            fallback_lc: hsm.fallback_lc.unwrap_or_default(),
+            localizable_params: hsm
+                .localizable_params
+                .into_iter()
+                .map(|param| messages::HSMLocalizableParameter {
+                    default: param.default.unwrap_or_default(),
+                    currency: param.currency.map(|currency| messages::HSMCurrency {
+                        currency_code: currency.currency_code.unwrap_or_default(),
+                        amount_1000: currency.amount1000.unwrap_or_default(),
+                    }),
+                    date_time_component: param.date_time.and_then(|date_time| date_time.component).map(|component| messages::HSMDateTimeComponent {
+                        day_of_week: component.day_of_week.unwrap_or_default(),
+                        year: component.year.unwrap_or_default(),
+                        month: component.month.unwrap_or_default(),
+                        day_of_month: component.day_of_month.unwrap_or_default(),
+                        hour: component.hour.unwrap_or_default(),
+                        minute: component.minute.unwrap_or_default(),
+                        calendar: component.calendar.unwrap_or_default(),
+                    }),
+                })
+                .collect(),
+            deterministic_lg: hsm.deterministic_lg,
+            deterministic_lc: hsm.deterministic_lc,
+            hydrated_hsm: unboxed(hsm.hydrated_hsm),
+        }
+    }
+}
+
+impl From<&messages::TemplateMessage> for TemplateMessage {
+    fn from(template: &messages::TemplateMessage) -> Self {
+        TemplateMessage {
+            context_info: boxed(&template.context_info),
+            hydrated_template: encoded(&template.hydrated_template),
+            template_id: template.template_id.clone(),
+        }
+    }
+}
+
+impl From<TemplateMessage> for messages::TemplateMessage {
+    fn from(template: TemplateMessage) -> Self {
+        messages::TemplateMessage {
+            context_info: unboxed(template.context_info),
+            hydrated_template: decoded(template.hydrated_template),
+            template_id: template.template_id,
+        }
+    }
+}
+
+impl From<&messages::HydratedFourRowTemplate> for HydratedFourRowTemplate {
+    fn from(template: &messages::HydratedFourRowTemplate) -> Self {
+        HydratedFourRowTemplate {
+            hydrated_title_text: template.hydrated_title_text.clone(),
+            hydrated_content_text: template.hydrated_content_text.clone(),
+            hydrated_footer_text: template.hydrated_footer_text.clone(),
+            hydrated_buttons: encoded_all(&template.hydrated_buttons),
+            template_id: template.template_id.clone(),
+        }
+    }
+}
+
+impl From<HydratedFourRowTemplate> for messages::HydratedFourRowTemplate {
+    fn from(template: HydratedFourRowTemplate) -> Self {
+        messages::HydratedFourRowTemplate {
+            hydrated_content_text: template.hydrated_content_text,
+            hydrated_footer_text: template.hydrated_footer_text,
+            hydrated_buttons: decoded_all(template.hydrated_buttons),
+            template_id: template.template_id,
+            hydrated_title_text: template.hydrated_title_text,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::HydratedTemplateButton> for HydratedTemplateButton {
+    fn from(button: &messages::HydratedTemplateButton) -> Self {
+        use hydrated_template_button::{HydratedCallButton, HydratedQuickReplyButton, HydratedUrlButton};
+
+        HydratedTemplateButton {
+            quick_reply_button: button
+                .quick_reply_button
+                .as_ref()
+                .map(|reply| HydratedQuickReplyButton { display_text: Some(reply.display_text.clone()), id: Some(reply.id.clone()) }),
+            url_button: button
+                .url_button
+                .as_ref()
+                .map(|url| HydratedUrlButton { display_text: Some(url.display_text.clone()), url: Some(url.url.clone()) }),
+            call_button: button
+                .call_button
+                .as_ref()
+                .map(|call| HydratedCallButton { display_text: Some(call.display_text.clone()), phone_number: Some(call.phone_number.clone()) }),
+            index: Some(button.index),
+        }
+    }
+}
+
+impl From<HydratedTemplateButton> for messages::HydratedTemplateButton {
+    fn from(button: HydratedTemplateButton) -> Self {
+        messages::HydratedTemplateButton {
+            index: button.index.unwrap_or_default(),
+            quick_reply_button: button.quick_reply_button.map(|reply| messages::HydratedQuickReplyButton {
+                display_text: reply.display_text.unwrap_or_default(),
+                id: reply.id.unwrap_or_default(),
+            }),
+            url_button: button.url_button.map(|url| messages::HydratedURLButton {
+                display_text: url.display_text.unwrap_or_default(),
+                url: url.url.unwrap_or_default(),
+            }),
+            call_button: button.call_button.map(|call| messages::HydratedCallButton {
+                display_text: call.display_text.unwrap_or_default(),
+                phone_number: call.phone_number.unwrap_or_default(),
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::TemplateButtonReplyMessage> for TemplateButtonReplyMessage {
+    fn from(reply: &messages::TemplateButtonReplyMessage) -> Self {
+        TemplateButtonReplyMessage {
+            selected_id: Some(reply.selected_id.clone()),
+            selected_display_text: Some(reply.selected_display_text.clone()),
+            context_info: boxed(&reply.context_info),
+            selected_index: reply.selected_index,
+        }
+    }
+}
+
+impl From<TemplateButtonReplyMessage> for messages::TemplateButtonReplyMessage {
+    fn from(reply: TemplateButtonReplyMessage) -> Self {
+        messages::TemplateButtonReplyMessage {
+            selected_id: reply.selected_id.unwrap_or_default(),
+            selected_display_text: reply.selected_display_text.unwrap_or_default(),
+            context_info: unboxed(reply.context_info),
+            selected_index: reply.selected_index,
+        }
+    }
+}
+
+impl From<&messages::PaymentMoney> for Money {
+    fn from(money: &messages::PaymentMoney) -> Self {
+        Money { value: Some(money.value), offset: Some(money.offset), currency_code: Some(money.currency_code.clone()) }
+    }
+}
+
+impl From<Money> for messages::PaymentMoney {
+    fn from(money: Money) -> Self {
+        messages::PaymentMoney {
+            value: money.value.unwrap_or_default(),
+            offset: money.offset.unwrap_or_default(),
+            currency_code: money.currency_code.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&messages::SendPaymentMessage> for SendPaymentMessage {
+    fn from(payment: &messages::SendPaymentMessage) -> Self {
+        SendPaymentMessage {
+            note_message: payment.note_message.as_deref().map(|note| Box::new(note.into())),
+            request_message_key: encoded(&payment.request_message_key),
+        }
+    }
+}
+
+impl From<SendPaymentMessage> for messages::SendPaymentMessage {
+    fn from(payment: SendPaymentMessage) -> Self {
+        messages::SendPaymentMessage {
+            note_message: payment.note_message.map(|note| Box::new((*note).into())),
+            request_message_key: decoded(payment.request_message_key),
+        }
+    }
+}
+
+impl From<&messages::RequestPaymentMessage> for RequestPaymentMessage {
+    fn from(request: &messages::RequestPaymentMessage) -> Self {
+        RequestPaymentMessage {
+            currency_code_iso4217: Some(request.currency_code_iso4217.clone()),
+            amount1000: Some(request.amount_1000),
+            request_from: Some(request.request_from.clone()),
+            note_message: request.note_message.as_deref().map(|note| Box::new(note.into())),
+            expiry_timestamp: Some(request.expiry_timestamp),
+            amount: encoded(&request.amount),
+        }
+    }
+}
+
+impl From<RequestPaymentMessage> for messages::RequestPaymentMessage {
+    fn from(request: RequestPaymentMessage) -> Self {
+        messages::RequestPaymentMessage {
+            note_message: request.note_message.map(|note| Box::new((*note).into())),
+            currency_code_iso4217: request.currency_code_iso4217.unwrap_or_default(),
+            amount_1000: request.amount1000.unwrap_or_default(),
+            request_from: request.request_from.unwrap_or_default(),
+            expiry_timestamp: request.expiry_timestamp.unwrap_or_default(),
+            amount: decoded(request.amount),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::DeclinePaymentRequestMessage> for DeclinePaymentRequestMessage {
+    fn from(decline: &messages::DeclinePaymentRequestMessage) -> Self {
+        DeclinePaymentRequestMessage { key: Some((&decline.key).into()) }
+    }
+}
+
+impl From<DeclinePaymentRequestMessage> for messages::DeclinePaymentRequestMessage {
+    fn from(decline: DeclinePaymentRequestMessage) -> Self {
+        messages::DeclinePaymentRequestMessage { key: decline.key.unwrap_or_default().into() }
+    }
+}
+
+impl From<&messages::CancelPaymentRequestMessage> for CancelPaymentRequestMessage {
+    fn from(cancel: &messages::CancelPaymentRequestMessage) -> Self {
+        CancelPaymentRequestMessage { key: Some((&cancel.key).into()) }
+    }
+}
+
+impl From<CancelPaymentRequestMessage> for messages::CancelPaymentRequestMessage {
+    fn from(cancel: CancelPaymentRequestMessage) -> Self {
+        messages::CancelPaymentRequestMessage { key: cancel.key.unwrap_or_default().into() }
+    }
+}
+
+impl From<&messages::PaymentInvitationMessage> for PaymentInviteMessage {
+    fn from(invite: &messages::PaymentInvitationMessage) -> Self {
+        PaymentInviteMessage { expiry_timestamp: Some(invite.expiry_timestamp), ..Default::default() }
+    }
+}
+
+impl From<PaymentInviteMessage> for messages::PaymentInvitationMessage {
+    fn from(invite: PaymentInviteMessage) -> Self {
+        messages::PaymentInvitationMessage { expiry_timestamp: invite.expiry_timestamp.unwrap_or_default(), ..Default::default() }
+    }
+}
+
+impl From<&messages::ProductMessage> for ProductMessage {
+    fn from(product: &messages::ProductMessage) -> Self {
+        let snapshot = &product.product_snapshot;
+        ProductMessage {
+            product: Some(Box::new(product_message::ProductSnapshot {
+                product_image: Some(Box::new((&snapshot.product_image).into())),
+                product_id: Some(snapshot.product_id.clone()),
+                title: Some(snapshot.product_title.clone()),
+                description: Some(snapshot.product_description.clone()),
+                currency_code: Some(snapshot.product_currency_code.clone()),
+                price_amount1000: Some(snapshot.product_price_amount_1000),
+                retailer_id: snapshot.product_retailer_id.clone(),
+                url: snapshot.url.clone(),
+                product_image_count: Some(snapshot.product_additional_image_count),
+            })),
+            business_owner_jid: Some(product.business_owner_jid.clone()),
+            context_info: boxed(&product.context_info),
+        }
+    }
+}
+
+impl From<ProductMessage> for messages::ProductMessage {
+    fn from(product: ProductMessage) -> Self {
+        let snapshot = product.product.map(|snapshot| *snapshot).unwrap_or_default();
+        messages::ProductMessage {
+            product_snapshot: messages::ProductSnapshot {
+                product_image: snapshot.product_image.map(|image| (*image).into()).unwrap_or_default(),
+                product_title: snapshot.title.unwrap_or_default(),
+                product_description: snapshot.description.unwrap_or_default(),
+                product_currency_code: snapshot.currency_code.unwrap_or_default(),
+                product_price_amount_1000: snapshot.price_amount1000.unwrap_or_default(),
+                product_id: snapshot.product_id.unwrap_or_default(),
+                product_additional_image_count: snapshot.product_image_count.unwrap_or_default(),
+                product_retailer_id: snapshot.retailer_id,
+                url: snapshot.url,
+                ..Default::default()
+            },
+            business_owner_jid: product.business_owner_jid.unwrap_or_default(),
+            context_info: unboxed(product.context_info),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::OrderMessage> for OrderMessage {
+    fn from(order: &messages::OrderMessage) -> Self {
+        OrderMessage {
+            order_id: Some(order.order_id.clone()),
+            thumbnail: order.thumbnail.clone(),
+            item_count: Some(order.item_count as i32),
+            status: Some(order.status),
+            surface: Some(order.surface),
+            message: order.message.clone(),
+            order_title: order.order_title.clone(),
+            seller_jid: Some(order.seller_jid.clone()),
+            token: Some(order.token.clone()),
+            total_amount1000: order.total_amount_1000,
+            total_currency_code: order.total_currency_code.clone(),
+            context_info: boxed(&order.context_info),
+        }
+    }
+}
+
+impl From<OrderMessage> for messages::OrderMessage {
+    fn from(order: OrderMessage) -> Self {
+        messages::OrderMessage {
+            order_id: order.order_id.unwrap_or_default(),
+            thumbnail: order.thumbnail,
+            item_count: order.item_count.unwrap_or_default().max(0) as u32,
+            status: order.status.unwrap_or_default(),
+            surface: order.surface.unwrap_or_default(),
+            message: order.message,
+            order_title: order.order_title,
+            seller_jid: order.seller_jid.unwrap_or_default(),
+            token: order.token.unwrap_or_default(),
+            total_amount_1000: order.total_amount1000,
+            total_currency_code: order.total_currency_code,
+            context_info: unboxed(order.context_info),
+        }
+    }
+}
+
+impl From<&messages::ListMessage> for ListMessage {
+    fn from(list: &messages::ListMessage) -> Self {
+        ListMessage {
+            title: Some(list.title.clone()),
+            description: Some(list.description.clone()),
+            button_text: Some(list.button_text.clone()),
+            list_type: Some(list.list_type),
+            sections: list
+                .sections
+                .iter()
+                .map(|section| list_message::Section {
+                    title: Some(section.title.clone()),
+                    rows: section
+                        .rows
+                        .iter()
+                        .map(|row| list_message::Row {
+                            title: Some(row.title.clone()),
+                            description: Some(row.description.clone()),
+                            row_id: Some(row.row_id.clone()),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            footer_text: list.footer_text.clone(),
+            context_info: boxed(&list.context_info),
+        }
+    }
+}
+
+impl From<ListMessage> for messages::ListMessage {
+    fn from(list: ListMessage) -> Self {
+        messages::ListMessage {
+            title: list.title.unwrap_or_default(),
+            description: list.description.unwrap_or_default(),
+            button_text: list.button_text.unwrap_or_default(),
+            list_type: list.list_type.unwrap_or_default(),
+            sections: list
+                .sections
+                .into_iter()
+                .map(|section| messages::ListSection {
+                    title: section.title.unwrap_or_default(),
+                    rows: section
+                        .rows
+                        .into_iter()
+                        .map(|row| messages::ListRow {
+                            title: row.title.unwrap_or_default(),
+                            description: row.description.unwrap_or_default(),
+                            row_id: row.row_id.unwrap_or_default(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            context_info: unboxed(list.context_info),
+            footer_text: list.footer_text,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::ListResponseMessage> for ListResponseMessage {
+    fn from(response: &messages::ListResponseMessage) -> Self {
+        ListResponseMessage {
+            title: Some(response.title.clone()),
+            list_type: Some(response.list_type),
+            single_select_reply: response
+                .single_select_reply
+                .as_ref()
+                .map(|reply| list_response_message::SingleSelectReply { selected_row_id: Some(reply.selected_row_id.clone()) }),
+            context_info: boxed(&response.context_info),
+            description: response.description.clone(),
+        }
+    }
+}
+
+impl From<ListResponseMessage> for messages::ListResponseMessage {
+    fn from(response: ListResponseMessage) -> Self {
+        messages::ListResponseMessage {
+            title: response.title.unwrap_or_default(),
+            list_type: response.list_type.unwrap_or_default(),
+            single_select_reply: response
+                .single_select_reply
+                .map(|reply| messages::SingleSelectReply { selected_row_id: reply.selected_row_id.unwrap_or_default() }),
+            context_info: unboxed(response.context_info),
+            description: response.description,
+        }
+    }
+}
+
+impl From<&messages::ButtonsMessage> for ButtonsMessage {
+    fn from(buttons: &messages::ButtonsMessage) -> Self {
+        ButtonsMessage {
+            text: buttons.text.clone(),
+            document_message: boxed(&buttons.document_message),
+            image_message: boxed(&buttons.image_message),
+            video_message: boxed(&buttons.video_message),
+            location_message: boxed(&buttons.location_message),
+            content_text: Some(buttons.content_text.clone()),
+            footer_text: buttons.footer_text.clone(),
+            context_info: boxed(&buttons.context_info),
+            buttons: buttons
+                .buttons
+                .iter()
+                .map(|button| buttons_message::Button {
+                    button_id: Some(button.button_id.clone()),
+                    button_text: Some(buttons_message::button::ButtonText { display_text: Some(button.button_text.clone()) }),
+                    r#type: Some(button.r#type),
+                })
+                .collect(),
+            header_type: Some(buttons.header_type),
+        }
+    }
+}
+
+impl From<ButtonsMessage> for messages::ButtonsMessage {
+    fn from(buttons: ButtonsMessage) -> Self {
+        messages::ButtonsMessage {
+            text: buttons.text,
+            content_text: buttons.content_text.unwrap_or_default(),
+            footer_text: buttons.footer_text,
+            context_info: unboxed(buttons.context_info),
+            buttons: buttons
+                .buttons
+                .into_iter()
+                .map(|button| messages::Button {
+                    button_id: button.button_id.unwrap_or_default(),
+                    button_text: button.button_text.and_then(|text| text.display_text).unwrap_or_default(),
+                    r#type: button.r#type.unwrap_or_default(),
+                })
+                .collect(),
+            header_type: buttons.header_type.unwrap_or_default(),
+            image_message: unboxed(buttons.image_message),
+            video_message: unboxed(buttons.video_message),
+            document_message: unboxed(buttons.document_message),
+            location_message: unboxed(buttons.location_message),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&messages::ButtonsResponseMessage> for ButtonsResponseMessage {
+    fn from(response: &messages::ButtonsResponseMessage) -> Self {
+        ButtonsResponseMessage {
+            selected_button_id: Some(response.selected_button_id.clone()),
+            selected_display_text: response.selected_display_text.clone(),
+            context_info: boxed(&response.context_info),
+            r#type: Some(response.r#type),
+        }
+    }
+}
+
+impl From<ButtonsResponseMessage> for messages::ButtonsResponseMessage {
+    fn from(response: ButtonsResponseMessage) -> Self {
+        messages::ButtonsResponseMessage {
+            selected_button_id: response.selected_button_id.unwrap_or_default(),
+            selected_display_text: response.selected_display_text,
+            context_info: unboxed(response.context_info),
+            r#type: response.r#type.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&messages::InteractiveMessage> for InteractiveMessage {
+    fn from(interactive: &messages::InteractiveMessage) -> Self {
+        use interactive_message::{native_flow_message::NativeFlowButton, Body, Footer, Header, NativeFlowMessage};
+
+        InteractiveMessage {
+            header: interactive.header.as_ref().map(|header| {
+                Box::new(Header {
+                    title: Some(header.title.clone()),
+                    subtitle: header.subtitle.clone(),
+                    document_message: boxed(&header.document_message),
+                    image_message: boxed(&header.image_message),
+                    has_media_attachment: header.has_media_attachment,
+                    video_message: boxed(&header.video_message),
+                })
+            }),
+            body: interactive.body.as_ref().map(|body| Body { text: Some(body.text.clone()) }),
+            footer: interactive.footer.as_ref().map(|footer| Footer { text: Some(footer.text.clone()) }),
+            native_flow_message: interactive.native_flow_message.as_ref().map(|flow| NativeFlowMessage {
+                buttons: flow
+                    .buttons
+                    .iter()
+                    .map(|button| NativeFlowButton { name: Some(button.name.clone()), button_params_json: Some(button.button_params_json.clone()) })
+                    .collect(),
+                message_params_json: Some(flow.message_params_json.clone()),
+            }),
+            context_info: boxed(&interactive.context_info),
+        }
+    }
+}
+
+impl From<InteractiveMessage> for messages::InteractiveMessage {
+    fn from(interactive: InteractiveMessage) -> Self {
+        messages::InteractiveMessage {
+            header: interactive.header.map(|header| messages::InteractiveMessageHeader {
+                title: header.title.unwrap_or_default(),
+                subtitle: header.subtitle,
+                has_media_attachment: header.has_media_attachment,
+                image_message: unboxed(header.image_message),
+                video_message: unboxed(header.video_message),
+                document_message: unboxed(header.document_message),
+            }),
+            body: interactive.body.map(|body| messages::InteractiveMessageBody { text: body.text.unwrap_or_default() }),
+            footer: interactive.footer.map(|footer| messages::InteractiveMessageFooter { text: footer.text.unwrap_or_default() }),
+            native_flow_message: interactive.native_flow_message.map(|flow| messages::NativeFlowMessage {
+                buttons: flow
+                    .buttons
+                    .into_iter()
+                    .map(|button| messages::NativeFlowButton {
+                        name: button.name.unwrap_or_default(),
+                        button_params_json: button.button_params_json.unwrap_or_default(),
+                    })
+                    .collect(),
+                message_params_json: flow.message_params_json.unwrap_or_default(),
+            }),
+            context_info: unboxed(interactive.context_info),
+        }
+    }
+}
+
+impl From<&messages::InteractiveResponseMessage> for InteractiveResponseMessage {
+    fn from(response: &messages::InteractiveResponseMessage) -> Self {
+        use interactive_response_message::{Body, NativeFlowResponseMessage};
+
+        InteractiveResponseMessage {
+            body: response.body.as_ref().map(|body| Body { text: Some(body.text.clone()) }),
+            native_flow_response_message: response
+                .native_flow_response_message
+                .as_ref()
+                .map(|flow| NativeFlowResponseMessage { name: Some(flow.name.clone()), params_json: Some(flow.params_json.clone()) }),
+            context_info: boxed(&response.context_info),
+        }
+    }
+}
+
+impl From<InteractiveResponseMessage> for messages::InteractiveResponseMessage {
+    fn from(response: InteractiveResponseMessage) -> Self {
+        messages::InteractiveResponseMessage {
+            body: response.body.map(|body| messages::InteractiveMessageBody { text: body.text.unwrap_or_default() }),
+            native_flow_response_message: response.native_flow_response_message.map(|flow| messages::NativeFlowResponseMessage {
+                name: flow.name.unwrap_or_default(),
+                params_json: flow.params_json.unwrap_or_default(),
+            }),
+            context_info: unboxed(response.context_info),
+        }
+    }
+}
+
+impl From<&messages::ProtocolMessage> for ProtocolMessage {
+    fn from(protocol: &messages::ProtocolMessage) -> Self {
+        ProtocolMessage {
+            key: Some((&protocol.key).into()),
+            r#type: protocol.r#type.map(|kind| kind as i32),
+            ephemeral_expiration: protocol.ephemeral_expiration,
+            ephemeral_setting_timestamp: protocol.ephemeral_setting_timestamp,
+            history_sync_notification: encoded(&protocol.history_sync_notification),
+            app_state_sync_key_share: encoded(&protocol.app_state_sync_key_share),
+            app_state_sync_key_request: encoded(&protocol.app_state_sync_key_request),
+            initial_security_notification_setting_sync: protocol
+                .initial_security_notification_setting_sync
+                .as_ref()
+                .map(|sync| InitialSecurityNotificationSettingSync { security_notification_enabled: Some(sync.security_notification_enabled) }),
+            app_state_fatal_exception_notification: protocol.app_state_fatal_exception_notification.as_ref().map(|notification| {
+                AppStateFatalExceptionNotification { collection_names: notification.collection_names.clone(), timestamp: notification.timestamp }
+            }),
+            peer_data_operation_request_message: encoded(&protocol.peer_data_operation_request_message),
+        }
+    }
+}
+
+impl From<ProtocolMessage> for messages::ProtocolMessage {
+    fn from(protocol: ProtocolMessage) -> Self {
+        messages::ProtocolMessage {
+            key: protocol.key.unwrap_or_default().into(),
+            r#type: protocol.r#type.map(|kind| kind as u32),
+            ephemeral_expiration: protocol.ephemeral_expiration,
+            ephemeral_setting_timestamp: protocol.ephemeral_setting_timestamp,
+            history_sync_notification: decoded(protocol.history_sync_notification),
+            app_state_sync_key_share: decoded(protocol.app_state_sync_key_share),
+            app_state_sync_key_request: decoded(protocol.app_state_sync_key_request),
+            initial_security_notification_setting_sync: protocol.initial_security_notification_setting_sync.map(|sync| {
+                messages::InitialSecurityNotificationSettingSync { security_notification_enabled: sync.security_notification_enabled.unwrap_or_default() }
+            }),
+            app_state_fatal_exception_notification: protocol.app_state_fatal_exception_notification.map(|notification| {
+                messages::AppStateFatalExceptionNotification { collection_names: notification.collection_names, timestamp: notification.timestamp }
+            }),
+            peer_data_operation_request_message: decoded(protocol.peer_data_operation_request_message),
+        }
+    }
+}
+
+impl From<&messages::HistorySyncNotification> for HistorySyncNotification {
+    fn from(notification: &messages::HistorySyncNotification) -> Self {
+        HistorySyncNotification {
+            file_sha256: notification.file_sha256.clone(),
+            file_length: notification.file_length,
+            media_key: notification.media_key.clone(),
+            file_enc_sha256: notification.file_enc_sha256.clone(),
+            direct_path: notification.direct_path.clone(),
+            sync_type: notification.sync_type,
+            chunk_order: notification.chunk_order,
+            original_message_id: notification.original_message_id.clone(),
+            initial_hist_bootstrap_inline_payload: notification.initial_hist_bootstrap_inline_payload.clone(),
+            peer_data_request_session_id: notification.peer_data_request_session_id.clone(),
+        }
+    }
+}
+
+impl From<HistorySyncNotification> for messages::HistorySyncNotification {
+    fn from(notification: HistorySyncNotification) -> Self {
+        messages::HistorySyncNotification {
+            file_sha256: notification.file_sha256,
+            file_length: notification.file_length,
+            media_key: notification.media_key,
+            file_enc_sha256: notification.file_enc_sha256,
+            direct_path: notification.direct_path,
+            sync_type: notification.sync_type,
+            chunk_order: notification.chunk_order,
+            original_message_id: notification.original_message_id,
+            peer_data_request_session_id: notification.peer_data_request_session_id,
+            initial_hist_bootstrap_inline_payload: notification.initial_hist_bootstrap_inline_payload,
+        }
+    }
+}
+
+impl From<&messages::AppStateSyncKeyShare> for AppStateSyncKeyShare {
+    fn from(share: &messages::AppStateSyncKeyShare) -> Self {
+        AppStateSyncKeyShare { keys: encoded_all(&share.keys) }
+    }
+}
+
+impl From<AppStateSyncKeyShare> for messages::AppStateSyncKeyShare {
+    fn from(share: AppStateSyncKeyShare) -> Self {
+        messages::AppStateSyncKeyShare { keys: decoded_all(share.keys) }
+    }
+}
+
+impl From<&messages::AppStateSyncKeyRequest> for AppStateSyncKeyRequest {
+    fn from(request: &messages::AppStateSyncKeyRequest) -> Self {
+        AppStateSyncKeyRequest { key_ids: encoded_all(&request.key_ids) }
+    }
+}
+
+impl From<AppStateSyncKeyRequest> for messages::AppStateSyncKeyRequest {
+    fn from(request: AppStateSyncKeyRequest) -> Self {
+        messages::AppStateSyncKeyRequest { key_ids: decoded_all(request.key_ids) }
+    }
+}
+
+impl From<&messages::AppStateSyncKey> for AppStateSyncKey {
+    fn from(key: &messages::AppStateSyncKey) -> Self {
+        AppStateSyncKey { key_id: encoded(&key.key_id), key_data: encoded(&key.key_data) }
+    }
+}
+
+impl From<AppStateSyncKey> for messages::AppStateSyncKey {
+    fn from(key: AppStateSyncKey) -> Self {
+        messages::AppStateSyncKey { key_id: decoded(key.key_id), key_data: decoded(key.key_data) }
+    }
+}
+
+impl From<&messages::AppStateSyncKeyId> for AppStateSyncKeyId {
+    fn from(id: &messages::AppStateSyncKeyId) -> Self {
+        AppStateSyncKeyId { key_id: Some(id.key_id.clone()) }
+    }
+}
+
+impl From<AppStateSyncKeyId> for messages::AppStateSyncKeyId {
+    fn from(id: AppStateSyncKeyId) -> Self {
+        messages::AppStateSyncKeyId { key_id: id.key_id.unwrap_or_default() }
+    }
+}
+
+impl From<&messages::AppStateSyncKeyData> for AppStateSyncKeyData {
+    fn from(data: &messages::AppStateSyncKeyData) -> Self {
+        AppStateSyncKeyData {
+            key_data: Some(data.key_data.clone()),
+            fingerprint: data.fingerprint.as_ref().map(|fingerprint| AppStateSyncKeyFingerprint {
+                raw_id: Some(fingerprint.raw_id),
+                current_index: Some(fingerprint.current_index),
+                device_indexes: fingerprint.device_indexes.clone(),
+            }),
+            timestamp: data.timestamp,
+        }
+    }
+}
+
+impl From<AppStateSyncKeyData> for messages::AppStateSyncKeyData {
+    fn from(data: AppStateSyncKeyData) -> Self {
+        messages::AppStateSyncKeyData {
+            key_data: data.key_data.unwrap_or_default(),
+            fingerprint: data.fingerprint.map(|fingerprint| messages::AppStateSyncKeyFingerprint {
+                raw_id: fingerprint.raw_id.unwrap_or_default(),
+                current_index: fingerprint.current_index.unwrap_or_default(),
+                device_indexes: fingerprint.device_indexes,
+            }),
+            timestamp: data.timestamp,
+        }
+    }
+}
+
+impl From<&messages::PeerDataOperationRequestMessage> for PeerDataOperationRequestMessage {
+    fn from(request: &messages::PeerDataOperationRequestMessage) -> Self {
+        PeerDataOperationRequestMessage {
+            peer_data_operation_request_type: request.peer_data_operation_request_type,
+            history_sync_on_demand_request: request.history_sync_on_demand_request.as_ref().map(|on_demand| HistorySyncOnDemandRequest {
+                chat_jid: Some(on_demand.chat_jid.clone()),
+                oldest_msg_id: Some(on_demand.oldest_msg_id.clone()),
+                oldest_msg_from_me: Some(on_demand.oldest_msg_from_me),
+                on_demand_msg_count: Some(on_demand.on_demand_msg_count as i32),
+                oldest_msg_timestamp_ms: on_demand.oldest_msg_timestamp_ms,
+            }),
+        }
+    }
+}
+
+impl From<PeerDataOperationRequestMessage> for messages::PeerDataOperationRequestMessage {
+    fn from(request: PeerDataOperationRequestMessage) -> Self {
+        messages::PeerDataOperationRequestMessage {
+            peer_data_operation_request_type: request.peer_data_operation_request_type,
+            history_sync_on_demand_request: request.history_sync_on_demand_request.map(|on_demand| messages::HistorySyncOnDemandRequest {
+                chat_jid: on_demand.chat_jid.unwrap_or_default(),
+                oldest_msg_id: on_demand.oldest_msg_id.unwrap_or_default(),
+                oldest_msg_from_me: on_demand.oldest_msg_from_me.unwrap_or_default(),
+                on_demand_msg_count: on_demand.on_demand_msg_count.unwrap_or_default().max(0) as u32,
+                oldest_msg_timestamp_ms: on_demand.oldest_msg_timestamp_ms,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_numbers_and_wrappers() {
+        let info = WebMessageInfo {
+            key: MessageKey { remote_jid: Some("628123@s.whatsapp.net".to_string()), from_me: Some(false), id: Some("3EB0C431".to_string()), participant: None },
+            message: Some(Message {
+                view_once_message: Some(Box::new(FutureProofMessage {
+                    message: Some(Box::new(Message {
+                        image_message: Some(Box::new(ImageMessage { caption: Some("foto".to_string()), ..ImageMessage::default() })),
+                        ..Message::default()
+                    })),
+                })),
+                ..Message::default()
+            }),
+            push_name: Some("Budi".to_string()),
+            ..WebMessageInfo::default()
+        };
+
+        let bytes = info.to_bytes();
+        // pushName = field 19, wire type 2: tag varint 0x9a 0x01
+        assert!(bytes.windows(3).any(|w| w == [0x9a, 0x01, 4]));
+        let decoded = WebMessageInfo::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, info);
+        assert_eq!(decoded.content().and_then(Message::body), Some("foto"));
+
+        assert_eq!(Message::from_bytes(&Message::text("halo").to_bytes()).unwrap().body(), Some("halo"));
+        assert!(WebMessageInfo::from_bytes(&[0x0a, 0x05]).is_err());
+    }
+
+    #[test]
+    fn test_messages_round_trip_through_protobuf() {
+        let context = messages::MessageContextInfo {
+            stanza_id: Some("3EB0AAAA".to_string()),
+            participant: Some("628222@s.whatsapp.net".to_string()),
+            mentioned_jid: vec!["628333@s.whatsapp.net".to_string()],
+            ..Default::default()
+        };
+        let info = messages::WebMessageInfo {
+            key: messages::MessageKey { remote_jid: "628111@s.whatsapp.net".to_string(), from_me: true, id: "3EB0BBBB".to_string(), participant: None },
+            message: Some(messages::Message {
+                extended_text_message: Some(messages::ExtendedTextMessage {
+                    text: "halo @628333".to_string(),
+                    context_info: Some(context),
+                    ..Default::default()
+                }),
+                message_context_info: Some(messages::MessageContextInfo { message_secret: Some(vec![7; 32]), ..Default::default() }),
+                ..Default::default()
+            }),
+            message_timestamp: Some(1_700_000_000),
+            push_name: Some("Budi".to_string()),
+            ..Default::default()
+        };
+
+        let bytes = info.to_bytes();
+        // Bytes protobuf yang sama dibaca oleh tipe hasil codegen
+        let wire = WebMessageInfo::from_bytes(&bytes).unwrap();
+        assert_eq!(wire.content().and_then(Message::body), Some("halo @628333"));
+        assert_eq!(wire.content().and_then(Message::context_info).and_then(|c| c.stanza_id.as_deref()), Some("3EB0AAAA"));
+
+        let decoded = messages::WebMessageInfo::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.key.id, "3EB0BBBB");
+        assert!(decoded.key.from_me);
+        assert_eq!(decoded.message_timestamp, Some(1_700_000_000));
+        assert_eq!(decoded.mentions(), vec![crate::Jid::user("628333")]);
+        let message = decoded.message.unwrap();
+        assert_eq!(message.message_context_info.and_then(|c| c.message_secret), Some(vec![7; 32]));
+        assert!(messages::WebMessageInfo::from_bytes(b"{\"key\":{}}").is_err());
+    }
+}
@@ -0,0 +1,42 @@
+//! Konstanta protokol yang dibangkitkan dari `proto/constants.json`
+//!
+//! Token kamus, tipe stub pesan dan kode status server dikelola sebagai data.
+//! Untuk mengikuti perubahan protokol, ubah file JSON tersebut; `build.rs`
+//! memvalidasi isinya dan membangkitkan tabel di bawah.
+
+include!(concat!(env!("OUT_DIR"), "/protocol_constants.rs"));
+
+/// Nama tipe stub (`WebMessageInfo.message_stub_type`), mis. `GROUP_CREATE`
+pub fn stub_type_name(stub_type: u32) -> Option<&'static str> {
+    STUB_TYPES.iter().find(|(number, _)| *number == stub_type).map(|(_, name)| *name)
+}
+
+/// Nomor tipe stub dari namanya
+pub fn stub_type(name: &str) -> Option<u32> {
+    STUB_TYPES.iter().find(|(_, stub)| *stub == name).map(|(number, _)| *number)
+}
+
+/// Deskripsi kode status server (IQ error, status peserta grup)
+pub fn status_description(code: u16) -> Option<&'static str> {
+    STATUS_CODES.iter().find(|(status, _)| *status == code).map(|(_, description)| *description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_protocol::DICTIONARY_0;
+
+    #[test]
+    fn test_token_table_fits_single_byte_range() {
+        assert!(SINGLE_BYTE_TOKENS.len() <= DICTIONARY_0 as usize);
+        assert_eq!(SINGLE_BYTE_TOKENS.iter().position(|&t| t == "s.whatsapp.net"), Some(80));
+    }
+
+    #[test]
+    fn test_lookups() {
+        assert_eq!(stub_type("GROUP_CREATE"), Some(20));
+        assert_eq!(stub_type_name(1), Some("REVOKE"));
+        assert_eq!(status_description(409), Some("conflict"));
+        assert_eq!(status_description(999), None);
+    }
+}
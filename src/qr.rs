@@ -0,0 +1,177 @@
+//! Rotasi QR code selama pairing
+//!
+//! Server mengirim beberapa ref sekaligus (`<pair-device>`) atau satu per satu
+//! (pesan JSON `ref`). Ref pertama berlaku ~60 detik, ref berikutnya ~20 detik.
+//! Setelah ref terakhir kedaluwarsa tanpa ada pemindaian, `Event::QrTimeout`
+//! dikirim agar aplikasi bisa memulai pairing ulang.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ws::util::Token;
+use ws::Sender;
+
+use crate::node_protocol::Node;
+use crate::session::Session;
+
+/// Token timeout ws untuk rotasi QR
+pub const QR_REFRESH_TOKEN: Token = Token(2);
+/// Masa berlaku ref pertama
+pub const FIRST_QR_TIMEOUT: Duration = Duration::from_secs(60);
+/// Masa berlaku ref berikutnya
+pub const QR_REFRESH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Hasil ketika timer QR berbunyi
+#[derive(Debug, PartialEq)]
+pub enum QrTick {
+    /// Timer lama yang sudah digantikan, abaikan
+    Stale,
+    /// Tampilkan ref berikutnya (lewat `next_ref`)
+    Refresh,
+    /// Semua ref sudah kedaluwarsa
+    Expired,
+}
+
+/// State rotasi QR code
+pub struct QrRefresh {
+    refs: VecDeque<String>,
+    shown: usize,
+    deadline: Option<Instant>,
+    active: bool,
+}
+
+impl QrRefresh {
+    pub fn new() -> Self {
+        QrRefresh {
+            refs: VecDeque::new(),
+            shown: 0,
+            deadline: None,
+            active: true,
+        }
+    }
+
+    /// Menambahkan ref dari server
+    pub fn push_refs<I: IntoIterator<Item = String>>(&mut self, refs: I) {
+        if self.active {
+            self.refs.extend(refs);
+        }
+    }
+
+    /// Mengambil ref berikutnya untuk ditampilkan dan menjadwalkan kedaluwarsanya
+    pub fn next_ref(&mut self, out: &Sender) -> Option<String> {
+        if !self.active {
+            return None;
+        }
+
+        let next = self.refs.pop_front()?;
+        let ttl = if self.shown == 0 { FIRST_QR_TIMEOUT } else { QR_REFRESH_TIMEOUT };
+        self.shown += 1;
+        self.deadline = Some(Instant::now() + ttl);
+        out.timeout(ttl.as_millis() as u64, QR_REFRESH_TOKEN).ok();
+        Some(next)
+    }
+
+    /// Dipanggil dari `on_timeout` ws
+    pub fn on_timeout(&mut self) -> QrTick {
+        let deadline = match self.deadline {
+            Some(deadline) if self.active => deadline,
+            _ => return QrTick::Stale,
+        };
+        if Instant::now() < deadline {
+            return QrTick::Stale;
+        }
+
+        if self.refs.is_empty() {
+            self.stop();
+            QrTick::Expired
+        } else {
+            QrTick::Refresh
+        }
+    }
+
+    /// Menghentikan rotasi (pairing berhasil atau dibatalkan)
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.deadline = None;
+        self.refs.clear();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+/// Cek apakah node adalah IQ `pair-device` berisi ref QR
+pub fn is_pair_device(node: &Node) -> bool {
+    node.tag == "iq" && node.get_child("pair-device").is_some()
+}
+
+/// Mengambil semua ref dari `<pair-device><ref>...</ref></pair-device>`
+pub fn pair_device_refs(node: &Node) -> Vec<String> {
+    node.get_child("pair-device")
+        .map(|pair| {
+            pair.get_children()
+                .iter()
+                .filter(|child| child.tag == "ref")
+                .filter_map(|child| child.get_bytes())
+                .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Isi QR code: ref, kunci publik noise, kunci identitas, dan adv secret (base64)
+pub fn qr_payload(qr_ref: &str, session: &mut Session) -> String {
+    if session.adv_secret_key.is_empty() {
+        use ring::rand::SecureRandom;
+        let mut adv_secret = [0u8; 32];
+        ring::rand::SystemRandom::new().fill(&mut adv_secret).ok();
+        session.adv_secret_key = adv_secret.to_vec();
+    }
+
+    format!(
+        "{},{},{},{}",
+        qr_ref,
+        base64::encode(&session.noise_key_pair.public_key),
+        base64::encode(&session.identity_key_pair.public_key),
+        base64::encode(&session.adv_secret_key)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_device_refs() {
+        let node = Node::new("iq").attr("type", "set").children(vec![Node::new("pair-device").children(vec![
+            Node::new("ref").bytes(b"ref-1".to_vec()),
+            Node::new("ref").bytes(b"ref-2".to_vec()),
+        ])]);
+
+        assert!(is_pair_device(&node));
+        assert_eq!(pair_device_refs(&node), vec!["ref-1".to_string(), "ref-2".to_string()]);
+    }
+
+    #[test]
+    fn test_qr_payload_fields() {
+        let mut session = Session::new();
+        let payload = qr_payload("ref-1", &mut session);
+        let fields: Vec<&str> = payload.split(',').collect();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[0], "ref-1");
+        assert_eq!(fields[1], base64::encode(&session.noise_key_pair.public_key));
+        assert_eq!(fields[2], base64::encode(&session.identity_key_pair.public_key));
+        assert_ne!(fields[1], fields[2]);
+        assert_eq!(fields[3], base64::encode(&session.adv_secret_key));
+    }
+
+    #[test]
+    fn test_stopped_refresh_ignores_timers() {
+        let mut refresh = QrRefresh::new();
+        refresh.push_refs(vec!["ref-1".to_string()]);
+        refresh.stop();
+        assert!(!refresh.is_active());
+        assert_eq!(refresh.on_timeout(), QrTick::Stale);
+    }
+}
@@ -0,0 +1,168 @@
+//! Batas laju pengiriman (token bucket) per chat dan global
+//!
+//! Berbeda dengan `traffic` yang menunda pengiriman, rate limiter menolak
+//! pesan yang melewati budget dengan `ErrorKind::RateLimited { retry_after }`
+//! sehingga bot massal bisa menjadwalkan ulang sendiri alih-alih mengirim
+//! beruntun. Setiap pesan memakai satu token dari bucket global dan satu dari
+//! bucket setiap penerima; pesan yang ditolak tidak memakai token apa pun.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::errors::*;
+use crate::WhatsAppClient;
+
+/// Jumlah bucket per chat yang disimpan sebelum bucket penuh dibuang
+const MAX_TRACKED_CHATS: usize = 4096;
+
+/// Satu budget: paling banyak `burst` pesan beruntun, lalu satu pesan per `interval`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Budget {
+    pub burst: u32,
+    pub interval: Duration,
+}
+
+/// Pengaturan rate limiter; budget `None` tidak dibatasi
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RateLimit {
+    pub per_chat: Option<Budget>,
+    pub global: Option<Budget>,
+}
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(budget: &Budget, now: Instant) -> Self {
+        Bucket {
+            tokens: budget.burst as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, budget: &Budget, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        let interval = budget.interval.as_secs_f64().max(f64::EPSILON);
+        self.tokens = (self.tokens + elapsed / interval).min(budget.burst as f64);
+        self.updated = now;
+    }
+
+    /// Waktu sampai satu token tersedia
+    fn wait(&self, budget: &Budget) -> Duration {
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+        budget.interval.mul_f64(1.0 - self.tokens)
+    }
+}
+
+/// State token bucket
+#[derive(Default)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    global: Option<Bucket>,
+    chats: HashMap<String, Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        RateLimiter::default()
+    }
+
+    pub fn set_limit(&mut self, limit: RateLimit) {
+        self.limit = limit;
+        self.global = None;
+        self.chats.clear();
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Memakai satu token per bucket untuk pesan ke `recipients`, atau
+    /// mengembalikan lama tunggu sampai semua bucket punya token
+    pub fn admit(&mut self, recipients: &[String], now: Instant) -> std::result::Result<(), Duration> {
+        let mut retry_after = Duration::ZERO;
+        if let Some(budget) = self.limit.global {
+            let bucket = self.global.get_or_insert_with(|| Bucket::full(&budget, now));
+            bucket.refill(&budget, now);
+            retry_after = retry_after.max(bucket.wait(&budget));
+        }
+        if let Some(budget) = self.limit.per_chat {
+            if self.chats.len() > MAX_TRACKED_CHATS {
+                self.chats.retain(|_, bucket| {
+                    bucket.refill(&budget, now);
+                    bucket.tokens < budget.burst as f64
+                });
+            }
+            for recipient in recipients {
+                let bucket = self.chats.entry(recipient.clone()).or_insert_with(|| Bucket::full(&budget, now));
+                bucket.refill(&budget, now);
+                retry_after = retry_after.max(bucket.wait(&budget));
+            }
+        }
+        if !retry_after.is_zero() {
+            return Err(retry_after);
+        }
+
+        if let Some(bucket) = self.global.as_mut() {
+            bucket.tokens -= 1.0;
+        }
+        if self.limit.per_chat.is_some() {
+            for recipient in recipients {
+                if let Some(bucket) = self.chats.get_mut(recipient) {
+                    bucket.tokens -= 1.0;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Menolak pengiriman ke `recipients` yang melewati rate limit
+    pub(crate) fn admit_rate_limit(&self, recipients: &[String]) -> Result<()> {
+        self.rate_limiter
+            .lock()
+            .unwrap()
+            .admit(recipients, Instant::now())
+            .map_err(|retry_after| Error { kind: ErrorKind::RateLimited { retry_after } })
+    }
+
+    /// Mengganti rate limit saat client berjalan; budget yang sedang berjalan direset
+    pub fn set_rate_limit(&self, limit: RateLimit) {
+        self.rate_limiter.lock().unwrap().set_limit(limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_chat_and_global_budgets() {
+        let mut limiter = RateLimiter::new();
+        limiter.set_limit(RateLimit {
+            per_chat: Some(Budget { burst: 2, interval: Duration::from_secs(10) }),
+            global: Some(Budget { burst: 3, interval: Duration::from_secs(1) }),
+        });
+        let start = Instant::now();
+        let a = vec!["a@s.whatsapp.net".to_string()];
+        let b = vec!["b@s.whatsapp.net".to_string()];
+
+        assert!(limiter.admit(&a, start).is_ok());
+        assert!(limiter.admit(&a, start).is_ok());
+        // Budget chat habis: tunggu satu interval chat
+        assert_eq!(limiter.admit(&a, start), Err(Duration::from_secs(10)));
+        assert!(limiter.admit(&b, start).is_ok());
+        // Budget global habis walaupun chat `b` masih punya token
+        assert_eq!(limiter.admit(&b, start), Err(Duration::from_secs(1)));
+
+        let later = start + Duration::from_secs(1);
+        assert!(limiter.admit(&b, later).is_ok());
+        assert!(limiter.admit(&a, later + Duration::from_secs(9)).is_ok());
+    }
+}
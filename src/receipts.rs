@@ -0,0 +1,99 @@
+//! Read receipt dan played receipt
+//!
+//! Satu stanza `receipt` bisa menandai banyak pesan di chat yang sama: id
+//! pertama di atribut `id`, sisanya di `<list><item id/></list>`. Di grup,
+//! receipt dikirim per pengirim (`participant`).
+
+use std::collections::BTreeMap;
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::{Jid, WhatsAppClient};
+
+/// Jenis receipt yang dikirim untuk pesan masuk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReceiptType {
+    Read,
+    /// Voice note diputar atau media view once dibuka
+    Played,
+}
+
+impl ReceiptType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReceiptType::Read => "read",
+            ReceiptType::Played => "played",
+        }
+    }
+}
+
+/// Stanza receipt untuk `ids` di `chat`; `participant` wajib untuk pesan grup
+pub fn receipt_node(chat: &str, participant: Option<&str>, ids: &[String], receipt_type: ReceiptType) -> Result<Node> {
+    let (first, rest) = ids.split_first().ok_or("Receipt requires at least one message id")?;
+    let mut receipt = Node::new("receipt")
+        .attr("id", first)
+        .attr("to", chat)
+        .attr("type", receipt_type.name())
+        .attr("t", &chrono::Utc::now().timestamp().to_string());
+    if let Some(participant) = participant {
+        receipt = receipt.attr("participant", participant);
+    }
+    if !rest.is_empty() {
+        receipt = receipt.children(vec![Node::new("list").children(rest.iter().map(|id| Node::new("item").attr("id", id)).collect())]);
+    }
+    Ok(receipt)
+}
+
+impl WhatsAppClient {
+    /// Mengirim read receipt untuk beberapa pesan di satu chat. Untuk grup,
+    /// `participant` adalah pengirim pesan-pesan tersebut.
+    pub fn send_read_receipt(&self, chat: &Jid, participant: Option<&Jid>, ids: &[String]) -> Result<()> {
+        self.send_receipt(chat, participant, ids, ReceiptType::Read)
+    }
+
+    /// Menandai voice note atau media view once sudah diputar/dibuka
+    pub fn send_played_receipt(&self, chat: &Jid, participant: Option<&Jid>, ids: &[String]) -> Result<()> {
+        self.send_receipt(chat, participant, ids, ReceiptType::Played)
+    }
+
+    fn send_receipt(&self, chat: &Jid, participant: Option<&Jid>, ids: &[String], receipt_type: ReceiptType) -> Result<()> {
+        if chat.is_group() && participant.is_none() {
+            return Err("Group receipts require the sender as participant".into());
+        }
+        let participant = participant.map(|jid| jid.to_string());
+        self.send_node(&receipt_node(&chat.to_string(), participant.as_deref(), ids, receipt_type)?)
+    }
+
+    /// Mengirim read receipt untuk semua pesan belum dibaca di `chat` dan
+    /// menandai chat sudah dibaca di semua perangkat. Mengembalikan jumlah pesan.
+    pub fn mark_chat_read(&self, chat: &Jid) -> Result<usize> {
+        let unread = self.unread.lock().unwrap().clear(&chat.to_string());
+        let mut by_sender: BTreeMap<Option<String>, Vec<String>> = BTreeMap::new();
+        for message in &unread {
+            by_sender.entry(message.participant.clone()).or_default().push(message.id.clone());
+        }
+        for (participant, ids) in &by_sender {
+            self.send_node(&receipt_node(&chat.to_string(), participant.as_deref(), ids, ReceiptType::Read)?)?;
+        }
+        self.mark_chats_read(std::slice::from_ref(chat))?;
+        Ok(unread.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_receipt_lists_remaining_ids() {
+        let ids = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let node = receipt_node("120363@g.us", Some("628111@s.whatsapp.net"), &ids, ReceiptType::Played).unwrap();
+        assert_eq!(node.get_attr("id"), Some("A"));
+        assert_eq!(node.get_attr("type"), Some("played"));
+        assert_eq!(node.get_attr("participant"), Some("628111@s.whatsapp.net"));
+        let items: Vec<_> = node.get_child("list").unwrap().get_children().iter().filter_map(|item| item.get_attr("id")).collect();
+        assert_eq!(items, vec!["B", "C"]);
+
+        assert!(receipt_node("628111@s.whatsapp.net", None, &[], ReceiptType::Read).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! Proteksi replay stanza
+//!
+//! Kiriman ulang stanza yang masih diingat dibuang oleh `dedup::StanzaDedup`.
+//! `ReplayGuard` mengingat timestamp (`t`) terbaru per pengirim, sehingga
+//! stanza lama yang diputar ulang oleh perantara (proxy bermasalah atau
+//! penyerang) setelah terlupa oleh dedup tetap dikenali dan dibuang. Riwayat
+//! disimpan untuk paling banyak `MAX_SENDERS` pengirim; pengirim terlama
+//! dilupakan lebih dulu.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::node_protocol::Node;
+
+/// Jendela default: stanza yang lebih tua dari ini dibanding stanza terbaru pengirim ditolak
+pub const DEFAULT_REPLAY_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Jumlah pengirim yang riwayatnya diingat
+pub const MAX_SENDERS: usize = 4096;
+
+/// Alasan stanza ditolak
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayReason {
+    /// Timestamp stanza di luar jendela dibanding stanza terbaru pengirim
+    OutOfWindow,
+}
+
+/// Timestamp terbaru per (pengirim, tag)
+pub struct ReplayGuard {
+    window: Duration,
+    latest: HashMap<(String, String), i64>,
+    sender_order: VecDeque<(String, String)>,
+}
+
+impl ReplayGuard {
+    pub fn new(window: Duration) -> Self {
+        ReplayGuard {
+            window,
+            latest: HashMap::new(),
+            sender_order: VecDeque::new(),
+        }
+    }
+
+    /// Memeriksa stanza; `Some(alasan)` jika stanza harus dibuang.
+    /// Stanza tanpa `from` atau `t` tidak diperiksa.
+    pub fn check(&mut self, node: &Node) -> Option<ReplayReason> {
+        let from = node.get_attr("from")?;
+        let timestamp: i64 = node.get_attr("t")?.parse().ok()?;
+
+        let sender = (from.to_string(), node.tag.clone());
+        if !self.latest.contains_key(&sender) {
+            self.sender_order.push_back(sender.clone());
+            if self.sender_order.len() > MAX_SENDERS {
+                if let Some(old) = self.sender_order.pop_front() {
+                    self.latest.remove(&old);
+                }
+            }
+        }
+        let latest = self.latest.entry(sender).or_insert(timestamp);
+        if timestamp < *latest - self.window.as_secs() as i64 {
+            return Some(ReplayReason::OutOfWindow);
+        }
+        *latest = (*latest).max(timestamp);
+        None
+    }
+}
+
+impl Default for ReplayGuard {
+    fn default() -> Self {
+        ReplayGuard::new(DEFAULT_REPLAY_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stanza(from: &str, id: &str, t: i64) -> Node {
+        Node::new("message").attr("from", from).attr("id", id).attr("t", &t.to_string())
+    }
+
+    #[test]
+    fn test_rejects_stale_stanzas() {
+        let mut guard = ReplayGuard::new(Duration::from_secs(60));
+        assert_eq!(guard.check(&stanza("628111@s.whatsapp.net", "a", 1000)), None);
+        assert_eq!(guard.check(&stanza("628111@s.whatsapp.net", "b", 990)), None);
+        assert_eq!(guard.check(&stanza("628111@s.whatsapp.net", "c", 900)), Some(ReplayReason::OutOfWindow));
+        // Riwayat terpisah per pengirim
+        assert_eq!(guard.check(&stanza("628222@s.whatsapp.net", "c", 900)), None);
+    }
+
+    #[test]
+    fn test_sender_history_is_bounded() {
+        let mut guard = ReplayGuard::new(Duration::from_secs(60));
+        guard.check(&stanza("0@s.whatsapp.net", "a", 1000));
+        for sender in 1..MAX_SENDERS + 1 {
+            guard.check(&stanza(&format!("{}@s.whatsapp.net", sender), "a", 1000));
+        }
+        assert_eq!(guard.latest.len(), MAX_SENDERS);
+        // Pengirim pertama sudah dilupakan
+        assert_eq!(guard.check(&stanza("0@s.whatsapp.net", "b", 900)), None);
+    }
+}
@@ -0,0 +1,258 @@
+//! Retry receipt: kirim ulang pesan yang gagal didekripsi penerima
+//!
+//! Perangkat yang gagal mendekripsi pesan (sesi rusak, sender key grup belum
+//! diterima) mengirim `receipt type="retry"`. Pesan asli diambil dari cache
+//! pesan keluar, sesi dengan perangkat tersebut dibuat ulang (langsung dari
+//! prekey di receipt, atau lewat IQ `encrypt`), lalu pesan dienkripsi ulang
+//! dan dikirim hanya ke perangkat itu. Setelah `max_retries` percobaan untuk
+//! pasangan pesan dan perangkat yang sama, pesan dianggap gagal dan
+//! `Event::Error` dikirim. Untuk pesan grup, sender key distribution kita
+//! disertakan lagi agar perangkat itu bisa membuka pesan grup berikutnya.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::errors::*;
+use crate::messages::{SenderKeyDistributionMessage, WebMessageInfo};
+use crate::node_protocol::Node;
+use crate::outbox::PendingOutbox;
+use crate::routing::NodeContext;
+use crate::session::Session;
+use crate::signal::{self, PreKeyBundle, SignalStore};
+use crate::Event;
+
+/// Jumlah percobaan kirim ulang default per pesan dan perangkat
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
+
+/// Jumlah pesan keluar terakhir yang disimpan untuk dikirim ulang
+const RETRY_CACHE_CAPACITY: usize = 1024;
+
+/// Isi `receipt type="retry"`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryReceipt {
+    pub message_id: String,
+    /// Chat pesan asli (user atau grup)
+    pub chat: String,
+    /// Perangkat yang gagal mendekripsi
+    pub device: String,
+    /// Hitungan retry menurut penerima
+    pub count: u32,
+    /// Prekey bundle baru dari penerima, jika disertakan
+    pub bundle: Option<PreKeyBundle>,
+}
+
+/// Membaca `<receipt type="retry" id from participant><retry count/><registration/><keys/></receipt>`
+pub fn parse_retry_receipt(node: &Node) -> Option<RetryReceipt> {
+    if node.tag != "receipt" || node.get_attr("type") != Some("retry") {
+        return None;
+    }
+    let retry = node.get_child("retry")?;
+    let chat = node.attr_jid("from").ok()?.to_string();
+    let device = node.attr_jid("participant").map_or_else(|_| chat.clone(), |participant| participant.to_string());
+    let bundle = node
+        .get_child("keys")
+        .and_then(|keys| signal::parse_keys(&device, signal::read_registration_id(node)?, keys));
+    Some(RetryReceipt {
+        message_id: retry.get_attr("id").or_else(|| node.get_attr("id"))?.to_string(),
+        chat,
+        device,
+        count: retry.get_attr("count").and_then(|count| count.parse().ok()).unwrap_or(1),
+        bundle,
+    })
+}
+
+/// Menyertakan sender key distribution `group` ke pesan yang dikirim ulang
+pub fn attach_sender_key(mut message: WebMessageInfo, group: &str, distribution: Vec<u8>) -> WebMessageInfo {
+    if let Some(ref mut content) = message.message {
+        content.sender_key_distribution_message = Some(SenderKeyDistributionMessage {
+            group_id: group.to_string(),
+            axolotl_sender_key_distribution_message: distribution,
+        });
+    }
+    message
+}
+
+/// Keputusan untuk satu retry receipt
+#[derive(Debug)]
+pub enum RetryDecision {
+    Resend(WebMessageInfo),
+    /// Batas percobaan terlampaui
+    GiveUp,
+    /// Pesan sudah tidak ada di cache
+    Unknown,
+}
+
+/// Pesan keluar terakhir dan jumlah percobaan kirim ulang per perangkat
+pub struct RetryCache {
+    messages: HashMap<String, WebMessageInfo>,
+    order: VecDeque<String>,
+    /// (id pesan, perangkat) -> percobaan
+    attempts: HashMap<(String, String), u32>,
+    max_retries: u32,
+}
+
+impl Default for RetryCache {
+    fn default() -> Self {
+        RetryCache {
+            messages: HashMap::new(),
+            order: VecDeque::new(),
+            attempts: HashMap::new(),
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+impl RetryCache {
+    pub fn new() -> Self {
+        RetryCache::default()
+    }
+
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    pub fn record(&mut self, message: &WebMessageInfo) {
+        if self.messages.insert(message.key.id.clone(), message.clone()).is_none() {
+            self.order.push_back(message.key.id.clone());
+        }
+        while self.order.len() > RETRY_CACHE_CAPACITY {
+            if let Some(old) = self.order.pop_front() {
+                self.messages.remove(&old);
+                self.attempts.retain(|(id, _), _| *id != old);
+            }
+        }
+    }
+
+    /// Mencatat satu percobaan kirim ulang untuk `receipt`
+    pub fn attempt(&mut self, receipt: &RetryReceipt) -> RetryDecision {
+        let message = match self.messages.get(&receipt.message_id) {
+            Some(message) => message.clone(),
+            None => return RetryDecision::Unknown,
+        };
+        let attempts = self.attempts.entry((receipt.message_id.clone(), receipt.device.clone())).or_insert(0);
+        *attempts += 1;
+        if *attempts > self.max_retries {
+            return RetryDecision::GiveUp;
+        }
+        RetryDecision::Resend(message)
+    }
+}
+
+/// Handler `receipt type="retry"`: membuat ulang sesi lalu mengirim ulang pesan
+pub fn retry_receipt_handler(
+    retries: Arc<Mutex<RetryCache>>,
+    outbox: Arc<Mutex<PendingOutbox>>,
+    signal: Arc<Mutex<SignalStore>>,
+    session: Arc<Mutex<Option<Session>>>,
+) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let receipt = match parse_retry_receipt(node) {
+            Some(receipt) => receipt,
+            None => return Ok(()),
+        };
+        let message = match retries.lock().unwrap().attempt(&receipt) {
+            RetryDecision::Resend(message) => message,
+            RetryDecision::GiveUp => {
+                let error = Error {
+                    kind: ErrorKind::DecryptionFailed {
+                        jid: receipt.device.clone(),
+                        kind: DecryptionFailure::RetriesExhausted(receipt.count),
+                    },
+                };
+                ctx.emit(Event::Error(format!("Message {} not delivered: {}", receipt.message_id, error)));
+                return Ok(());
+            }
+            RetryDecision::Unknown => return Ok(()),
+        };
+        let message = if receipt.chat.ends_with("@g.us") {
+            let distribution = signal.lock().unwrap().sender_key_distribution(&receipt.chat)?;
+            attach_sender_key(message, &receipt.chat, distribution)
+        } else {
+            message
+        };
+
+        // Sesi lama dibuang; prekey di receipt menghemat satu IQ
+        let session_ready = {
+            let session = session.lock().unwrap();
+            let mut signal = signal.lock().unwrap();
+            signal.remove_session(&receipt.device);
+            match (session.as_ref(), &receipt.bundle) {
+                (Some(local), Some(bundle)) => match signal.process_bundle(local, bundle) {
+                    Ok(()) => true,
+                    Err(error) => {
+                        ctx.emit(Event::Error(format!("Message {} not resent to {}: {}", receipt.message_id, receipt.device, error)));
+                        return Ok(());
+                    }
+                },
+                _ => false,
+            }
+        };
+
+        let nodes = outbox.lock().unwrap().enqueue_retry(&receipt.device, message, session_ready)?;
+        for node in nodes {
+            let node = if node.tag == "action" { signal::seal_relay(node, &signal, &session)? } else { node };
+            ctx.send_node(&node)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_resends_to_failing_device_only() {
+        let member = "628222:3@s.whatsapp.net";
+        let node = Node::new("receipt")
+            .attr("type", "retry")
+            .attr("id", "g1")
+            .attr("from", "123-456@g.us")
+            .attr("participant", member)
+            .children(vec![Node::new("retry").attr("count", "1").attr("id", "g1"), Node::new("registration").bytes(vec![0, 0, 0, 9])]);
+        let receipt = parse_retry_receipt(&node).unwrap();
+        assert_eq!(receipt.chat, "123-456@g.us");
+        assert_eq!(receipt.device, member);
+        assert_eq!(receipt.bundle, None);
+
+        let mut cache = RetryCache::new();
+        cache.set_max_retries(1);
+        assert!(matches!(cache.attempt(&receipt), RetryDecision::Unknown));
+        let message = WebMessageInfo {
+            key: crate::messages::MessageKey {
+                remote_jid: "123-456@g.us".to_string(),
+                from_me: true,
+                id: "g1".to_string(),
+                participant: None,
+            },
+            ..Default::default()
+        };
+        cache.record(&message);
+        let message = match cache.attempt(&receipt) {
+            RetryDecision::Resend(message) => message,
+            decision => panic!("unexpected {:?}", decision),
+        };
+        assert!(matches!(cache.attempt(&receipt), RetryDecision::GiveUp));
+
+        // Pesan grup dikirim ulang bersama sender key yang sama setiap kali
+        let mut signal = SignalStore::new();
+        let distribution = signal.sender_key_distribution(&receipt.chat).unwrap();
+        assert_eq!(signal.sender_key_distribution(&receipt.chat).unwrap(), distribution);
+        let content = crate::messages::Message { conversation: Some("halo".to_string()), ..Default::default() };
+        let resent = attach_sender_key(WebMessageInfo { message: Some(content), ..message.clone() }, &receipt.chat, distribution);
+        let attached = resent.message.and_then(|content| content.sender_key_distribution_message).unwrap();
+        assert_eq!(attached.group_id, "123-456@g.us");
+
+        // Tanpa prekey di receipt: kunci perangkat diminta dulu
+        let mut outbox = PendingOutbox::new();
+        let requests = outbox.enqueue_retry(member, message, false).unwrap();
+        assert_eq!(requests[0].get_attr("xmlns"), Some("encrypt"));
+        let response = Node::new("iq")
+            .attr("id", requests[0].get_attr("id").unwrap())
+            .attr("type", "result")
+            .children(vec![Node::new("list").children(vec![Node::new("user").attr("jid", member)])]);
+        let outcome = outbox.handle_response(&response);
+        assert_eq!(outcome.ready.len(), 1);
+        assert_eq!(outcome.ready[0].1, vec![member.to_string()]);
+    }
+}
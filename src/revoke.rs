@@ -0,0 +1,101 @@
+//! Hapus untuk semua orang (revoke)
+//!
+//! Revoke dikirim sebagai `ProtocolMessage` bertipe REVOKE yang merujuk ke
+//! kunci pesan asli. Server menolak revoke di luar batas waktu, jadi client
+//! memeriksa batas itu lebih dulu untuk pesan yang waktu kirimnya diketahui.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::messages::{MessageKey, WebMessageInfo};
+use crate::{Event, Jid};
+
+/// Nilai `ProtocolMessage.type` untuk revoke
+pub const PROTOCOL_MESSAGE_REVOKE: u32 = 0;
+
+/// Batas waktu hapus untuk semua orang yang diterapkan server
+pub const REVOKE_WINDOW: Duration = Duration::from_secs(60 * 60 * 60);
+
+/// Jumlah pesan keluar terakhir yang diingat waktu kirimnya
+const SENT_LOG_CAPACITY: usize = 4096;
+
+/// Cek apakah pesan yang dikirim pada `sent_at` masih bisa di-revoke pada `now` (detik unix)
+pub fn within_window(sent_at: i64, now: i64, window: Duration) -> bool {
+    now.saturating_sub(sent_at) <= window.as_secs() as i64
+}
+
+/// Catatan waktu kirim pesan keluar terakhir (id pesan -> detik unix)
+pub struct SentLog {
+    sent_at: HashMap<String, i64>,
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl SentLog {
+    pub fn new() -> Self {
+        SentLog {
+            sent_at: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: SENT_LOG_CAPACITY,
+        }
+    }
+
+    pub fn record(&mut self, message_id: &str, timestamp: i64) {
+        if self.sent_at.insert(message_id.to_string(), timestamp).is_none() {
+            self.order.push_back(message_id.to_string());
+        }
+        while self.order.len() > self.capacity {
+            if let Some(old) = self.order.pop_front() {
+                self.sent_at.remove(&old);
+            }
+        }
+    }
+
+    pub fn sent_at(&self, message_id: &str) -> Option<i64> {
+        self.sent_at.get(message_id).copied()
+    }
+}
+
+/// Mengubah pesan revoke masuk menjadi `Event::MessageRevoked`
+pub fn revoke_event(web_message: &WebMessageInfo) -> Option<Event> {
+    let protocol = web_message.message.as_ref()?.protocol_message.as_ref()?;
+    if protocol.r#type != Some(PROTOCOL_MESSAGE_REVOKE) {
+        return None;
+    }
+
+    let chat = Jid::from_string(&web_message.key.remote_jid).ok()?;
+    let mut key = protocol.key.clone();
+    // Kunci dalam revoke dilihat dari sisi pengirim revoke
+    if key.remote_jid.is_empty() {
+        key.remote_jid = web_message.key.remote_jid.clone();
+    }
+    Some(Event::MessageRevoked { chat, key })
+}
+
+/// Kunci pesan target revoke harus berada di chat yang sama
+pub fn validate_target(chat: &Jid, key: &MessageKey) -> bool {
+    key.remote_jid == chat.to_string() && (key.from_me || chat.is_group())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window() {
+        let window = Duration::from_secs(60);
+        assert!(within_window(1_000, 1_060, window));
+        assert!(!within_window(1_000, 1_061, window));
+    }
+
+    #[test]
+    fn test_sent_log_is_bounded() {
+        let mut log = SentLog::new();
+        log.capacity = 2;
+        log.record("a", 1);
+        log.record("b", 2);
+        log.record("c", 3);
+        assert_eq!(log.sent_at("a"), None);
+        assert_eq!(log.sent_at("c"), Some(3));
+    }
+}
@@ -0,0 +1,263 @@
+//! Tabel routing untuk node yang diterima dari server
+//!
+//! Setiap stanza (message, notification, receipt, dll.) diarahkan ke handler
+//! berdasarkan pasangan (tag, atribut `type`). Stanza yang dikirim ulang oleh
+//! server hanya diproses sekali (lihat `dedup::StanzaDedup`). Stanza yang
+//! diputar ulang di luar jendela deduplikasi ditolak oleh `ReplayGuard` dan
+//! dilaporkan sebagai `Event::StanzaReplayRejected`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use ws::Sender;
+
+use std::sync::{Arc, Mutex};
+
+use crate::communities::{self, CommunityRegistry};
+use crate::dedup::StanzaDedup;
+use crate::errors::*;
+use crate::framing::{self, Framing};
+use crate::node_protocol::Node;
+use crate::replay::ReplayGuard;
+use crate::{Event, EventSender};
+
+/// Konteks yang diberikan ke handler node
+pub struct NodeContext<'a> {
+    pub out: &'a Sender,
+    pub framing: &'a Mutex<Framing>,
+    pub event_tx: &'a EventSender,
+}
+
+impl<'a> NodeContext<'a> {
+    /// Mengirim event ke aplikasi
+    pub fn emit(&self, event: Event) {
+        self.event_tx.send(event).ok();
+    }
+
+    /// Mengirim node balasan ke server
+    pub fn send_node(&self, node: &Node) -> Result<()> {
+        self.event_tx.trace_node(crate::wire_trace::Direction::Outbound, node);
+        framing::send_frame(self.out, self.framing, &crate::node_protocol::encode_payload(node, false)?)
+    }
+}
+
+/// Membangun ack untuk stanza dari server (class = tag stanza)
+pub fn ack_for(node: &Node) -> Option<Node> {
+    let id = node.get_attr("id")?;
+    let mut ack = Node::new("ack").attr("id", id).attr("class", &node.tag);
+    if let Some(node_type) = node.get_attr("type") {
+        ack = ack.attr("type", node_type);
+    }
+    if let Some(from) = node.get_attr("from") {
+        ack = ack.attr("to", from);
+    }
+    if let Some(participant) = node.get_attr("participant") {
+        ack = ack.attr("participant", participant);
+    }
+    Some(ack)
+}
+
+/// Handler untuk satu jenis stanza
+pub trait NodeHandler: Send + 'static {
+    fn handle_node(&self, node: &Node, ctx: &NodeContext) -> Result<()>;
+}
+
+impl<F> NodeHandler for F
+where
+    F: Fn(&Node, &NodeContext) -> Result<()> + Send + 'static,
+{
+    fn handle_node(&self, node: &Node, ctx: &NodeContext) -> Result<()> {
+        self(node, ctx)
+    }
+}
+
+/// Kunci routing: tag node dan atribut `type` (None berarti semua type)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RouteKey {
+    pub tag: String,
+    pub node_type: Option<String>,
+}
+
+impl RouteKey {
+    pub fn new(tag: &str, node_type: Option<&str>) -> Self {
+        RouteKey {
+            tag: tag.to_string(),
+            node_type: node_type.map(|t| t.to_string()),
+        }
+    }
+}
+
+/// Registry handler node dengan deduplikasi stanza
+pub struct NodeRouter {
+    routes: HashMap<RouteKey, Vec<Box<dyn NodeHandler>>>,
+    dedup: StanzaDedup,
+    replay: ReplayGuard,
+}
+
+impl NodeRouter {
+    /// Membuat router kosong tanpa handler bawaan
+    pub fn new() -> Self {
+        NodeRouter {
+            routes: HashMap::new(),
+            dedup: StanzaDedup::default(),
+            replay: ReplayGuard::default(),
+        }
+    }
+
+    /// Membuat router dengan handler bawaan library
+    pub fn with_default_handlers() -> Self {
+        NodeRouter::with_communities(Arc::new(Mutex::new(CommunityRegistry::new())))
+    }
+
+    /// Seperti `with_default_handlers`, dengan registry komunitas bersama
+    /// untuk mengenali pesan di grup pengumuman komunitas
+    pub fn with_communities(communities: Arc<Mutex<CommunityRegistry>>) -> Self {
+        let mut router = NodeRouter::new();
+        router.register("message", None, message_handler(communities));
+        router
+    }
+
+    /// Mengatur jendela deduplikasi stanza; `capacity` 0 mematikannya
+    pub fn set_dedup(&mut self, capacity: usize, ttl: Duration) {
+        self.dedup = StanzaDedup::new(capacity, ttl);
+    }
+
+    /// Mengatur jendela proteksi replay per pengirim
+    pub fn set_replay_window(&mut self, window: Duration) {
+        self.replay = ReplayGuard::new(window);
+    }
+
+    /// Mendaftarkan handler untuk (tag, type). Handler dengan kunci yang sama dipanggil berurutan.
+    pub fn register<H: NodeHandler>(&mut self, tag: &str, node_type: Option<&str>, handler: H) {
+        self.routes
+            .entry(RouteKey::new(tag, node_type))
+            .or_insert_with(Vec::new)
+            .push(Box::new(handler));
+    }
+
+    /// Menghapus semua handler untuk (tag, type)
+    pub fn unregister(&mut self, tag: &str, node_type: Option<&str>) {
+        self.routes.remove(&RouteKey::new(tag, node_type));
+    }
+
+    /// Cek apakah ada handler yang akan menerima node ini
+    pub fn has_route(&self, node: &Node) -> bool {
+        self.handlers_for(node).is_some()
+    }
+
+    /// Mengarahkan node ke handler yang sesuai.
+    /// Mengembalikan false jika node duplikat atau tidak ada handler. Handler
+    /// yang gagal tidak menghentikan handler berikutnya; error-nya dilaporkan
+    /// sebagai `Event::Error`.
+    pub fn dispatch(&mut self, node: &Node, ctx: &NodeContext) -> Result<bool> {
+        if !self.dedup.first_seen(node, Instant::now()) {
+            return Ok(false);
+        }
+        if let Some(reason) = self.replay.check(node) {
+            ctx.emit(Event::StanzaReplayRejected {
+                tag: node.tag.clone(),
+                from: node.get_attr("from").unwrap_or_default().to_string(),
+                id: node.get_attr("id").unwrap_or_default().to_string(),
+                reason,
+            });
+            return Ok(false);
+        }
+        match self.handlers_for(node) {
+            Some(handlers) => {
+                for handler in handlers {
+                    if let Err(e) = handler.handle_node(node, ctx) {
+                        log::warn!("Handler for <{}> node failed: {}", node.tag, e);
+                        ctx.emit(Event::Error(format!("Failed to handle <{}> node: {}", node.tag, e)));
+                    }
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Handler spesifik (tag, type) didahulukan, lalu handler (tag, semua type)
+    fn handlers_for(&self, node: &Node) -> Option<&Vec<Box<dyn NodeHandler>>> {
+        let node_type = node.attrs.get("type").map(|t| t.as_str());
+        if node_type.is_some() {
+            if let Some(handlers) = self.routes.get(&RouteKey::new(&node.tag, node_type)) {
+                return Some(handlers);
+            }
+        }
+        self.routes.get(&RouteKey::new(&node.tag, None))
+    }
+}
+
+/// Handler bawaan untuk stanza `message`
+fn message_handler(communities: Arc<Mutex<CommunityRegistry>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        // Coba parse sebagai WebMessageInfo jika konten binari
+        if let Some(crate::node_protocol::NodeContent::Binary(ref bytes)) = node.content {
+            let parsed = crate::messages::WebMessageInfo::from_bytes(bytes);
+            if parsed.is_err() {
+                ctx.event_tx.metrics.malformed_stanza();
+            }
+            if let Ok(web_message) = parsed {
+                let special = crate::revoke::revoke_event(&web_message)
+                    .or_else(|| crate::ephemeral::setting_event(&web_message))
+                    .or_else(|| communities::announcement_event(&communities.lock().unwrap(), &web_message))
+                    .or_else(|| crate::orders::order_event(&web_message))
+                    .or_else(|| crate::interactive::response_event(&web_message));
+                match special {
+                    Some(event) => ctx.emit(event),
+                    None if web_message.is_view_once() => ctx.emit(Event::ViewOnceReceived(web_message)),
+                    None if crate::broadcast::is_list_message(&web_message) => ctx.emit(Event::BroadcastMessageReceived(web_message)),
+                    None => ctx.emit(Event::MessageReceived(web_message)),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn node(tag: &str, node_type: Option<&str>, id: Option<&str>) -> Node {
+        let mut attrs = HashMap::new();
+        if let Some(t) = node_type {
+            attrs.insert("type".to_string(), t.to_string());
+        }
+        if let Some(i) = id {
+            attrs.insert("id".to_string(), i.to_string());
+        }
+        Node { tag: tag.to_string(), attrs, content: None }
+    }
+
+    fn counting_router(hits: Arc<Mutex<Vec<&'static str>>>) -> NodeRouter {
+        let mut router = NodeRouter::new();
+        let specific = Arc::clone(&hits);
+        router.register("notification", Some("picture"), move |_: &Node, _: &NodeContext| {
+            specific.lock().unwrap().push("picture");
+            Ok(())
+        });
+        let fallback = Arc::clone(&hits);
+        router.register("notification", None, move |_: &Node, _: &NodeContext| {
+            fallback.lock().unwrap().push("any");
+            Ok(())
+        });
+        router
+    }
+
+    #[test]
+    fn test_route_lookup_prefers_specific_type() {
+        let hits = Arc::new(Mutex::new(Vec::new()));
+        let router = counting_router(Arc::clone(&hits));
+
+        let picture = node("notification", Some("picture"), None);
+        let devices = node("notification", Some("devices"), None);
+        let receipt = node("receipt", None, None);
+
+        let specific = &router.routes[&RouteKey::new("notification", Some("picture"))];
+        let fallback = &router.routes[&RouteKey::new("notification", None)];
+        assert!(std::ptr::eq(router.handlers_for(&picture).unwrap(), specific));
+        assert!(std::ptr::eq(router.handlers_for(&devices).unwrap(), fallback));
+        assert!(!router.has_route(&receipt));
+    }
+}
@@ -0,0 +1,26 @@
+//! Opsi tambahan untuk pengiriman pesan
+
+/// Opsi pengiriman yang tidak mengubah isi utama pesan
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SendOptions {
+    /// Media hanya bisa dibuka sekali oleh penerima (gambar dan video)
+    pub view_once: bool,
+    /// Menambahkan preview untuk URL pertama di teks (lihat `link_preview`)
+    pub link_preview: bool,
+}
+
+impl SendOptions {
+    pub fn new() -> Self {
+        SendOptions::default()
+    }
+
+    pub fn view_once(mut self, view_once: bool) -> Self {
+        self.view_once = view_once;
+        self
+    }
+
+    pub fn link_preview(mut self, link_preview: bool) -> Self {
+        self.link_preview = link_preview;
+        self
+    }
+}
@@ -0,0 +1,242 @@
+//! Antrean kirim berbatas untuk pesan keluar
+//!
+//! Frame pesan tidak ditulis langsung ke socket oleh thread pemanggil; frame
+//! masuk antrean dan ditulis satu per satu oleh thread penulis. Antrean
+//! menghitung byte yang belum ditulis: bertambah saat frame masuk dan
+//! berkurang setiap kali thread penulis selesai menyerahkan satu frame ke
+//! websocket. Saat jaringan macet, penulisan tertahan, byte menumpuk, dan
+//! pengiriman baru ditahan alih-alih menumpuk di memori:
+//!
+//! - `WhatsAppClient::send_async` menunggu antrean lega tanpa memblokir thread
+//! - `WhatsAppClient::try_send` tidak pernah menunggu: gagal dengan
+//!   `ErrorKind::QueueFull` jika antrean penuh, atau `ErrorKind::RateLimited`
+//!   jika traffic shaping meminta jeda
+//! - fungsi kirim sinkron lain gagal dengan `ErrorKind::QueueFull` jika
+//!   antrean penuh, lalu menunggu jeda traffic shaping seperti biasa
+//!
+//! Batas diperiksa sebelum pesan masuk, jadi satu pesan boleh membuat antrean
+//! sedikit melewati kapasitas. Node protokol (receipt, ack, IQ) tetap dikirim
+//! langsung dan tidak dihitung.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Instant;
+
+use ws::Sender;
+
+use crate::errors::*;
+use crate::framing::{self, Framing};
+use crate::{presence, traffic, Jid, MessageBuilder, PresenceStatus, WhatsAppClient};
+
+/// Jumlah byte default yang boleh menunggu ditulis ke socket
+pub const DEFAULT_SEND_QUEUE_CAPACITY: usize = 1024 * 1024;
+
+#[derive(Default)]
+struct InFlight {
+    bytes: usize,
+    /// Pemanggil `send_async` yang menunggu antrean lega
+    waiters: Vec<Waker>,
+}
+
+pub(crate) struct SendQueue {
+    capacity: usize,
+    in_flight: Arc<Mutex<InFlight>>,
+    frames: mpsc::Sender<Vec<u8>>,
+}
+
+/// Mengurangi byte yang belum ditulis dan membangunkan pemanggil yang menunggu
+fn release(in_flight: &Mutex<InFlight>, bytes: usize) {
+    let waiters = {
+        let mut in_flight = in_flight.lock().unwrap();
+        in_flight.bytes = in_flight.bytes.saturating_sub(bytes);
+        std::mem::take(&mut in_flight.waiters)
+    };
+    for waiter in waiters {
+        waiter.wake();
+    }
+}
+
+impl SendQueue {
+    /// Membuat antrean dan thread penulisnya; thread berhenti saat antrean di-drop
+    pub(crate) fn start(socket: Arc<Mutex<Option<Sender>>>, framing: Arc<Mutex<Framing>>, capacity: usize) -> Self {
+        let in_flight = Arc::new(Mutex::new(InFlight::default()));
+        let (frames, rx) = mpsc::channel::<Vec<u8>>();
+        let writer_in_flight = Arc::clone(&in_flight);
+        thread::spawn(move || {
+            for frame in rx {
+                // Sender disalin agar `disconnect` tidak tertahan saat penulisan macet
+                let sender = socket.lock().unwrap().clone();
+                match sender {
+                    Some(sender) => {
+                        if let Err(e) = framing::send_frame(&sender, &framing, &frame) {
+                            log::warn!("Failed to write queued frame: {}", e);
+                        }
+                    }
+                    None => log::warn!("Dropping queued frame, connection is closed"),
+                }
+                release(&writer_in_flight, frame.len());
+            }
+        });
+        SendQueue { capacity: capacity.max(1), in_flight, frames }
+    }
+
+    /// Gagal dengan `ErrorKind::QueueFull` jika antrean penuh
+    pub(crate) fn try_admit(&self) -> Result<()> {
+        if self.len() >= self.capacity {
+            return Err(Error { kind: ErrorKind::QueueFull });
+        }
+        Ok(())
+    }
+
+    /// Selesai saat antrean tidak penuh
+    pub(crate) fn ready(&self) -> Ready<'_> {
+        Ready { queue: self }
+    }
+
+    pub(crate) fn push(&self, data: Vec<u8>) -> Result<()> {
+        let bytes = data.len();
+        self.in_flight.lock().unwrap().bytes += bytes;
+        self.frames.send(data).map_err(|_| {
+            release(&self.in_flight, bytes);
+            "Send queue writer stopped".into()
+        })
+    }
+
+    /// Jumlah byte yang menunggu ditulis
+    pub(crate) fn len(&self) -> usize {
+        self.in_flight.lock().unwrap().bytes
+    }
+}
+
+/// Future dari `SendQueue::ready`
+pub(crate) struct Ready<'a> {
+    queue: &'a SendQueue,
+}
+
+impl Future for Ready<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut in_flight = self.queue.in_flight.lock().unwrap();
+        if in_flight.bytes < self.queue.capacity {
+            return Poll::Ready(());
+        }
+        in_flight.waiters.push(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl WhatsAppClient {
+    /// Mengirim pesan dari `MessageBuilder`; menunggu jika antrean kirim
+    /// penuh atau traffic shaping meminta jeda, tanpa memblokir thread.
+    pub async fn send_async(&self, to: &Jid, builder: MessageBuilder) -> Result<String> {
+        builder.validate()?;
+        let message = builder.own_jid(self.get_own_jid()).build(to);
+        let chat = to.to_string();
+        loop {
+            self.send_queue.ready().await;
+            let wait = self.traffic.lock().unwrap().wait_for(&chat, Instant::now());
+            if wait.is_zero() {
+                break;
+            }
+            tokio::time::sleep(wait).await;
+        }
+        // Slot traffic baru dipesan setelah rate limit dan pemanasan lolos,
+        // agar pesan yang ditolak tidak menahan pengiriman berikutnya
+        self.admit_rate_limit(&[chat.clone()])?;
+        self.admit_warmup(&[chat.clone()])?;
+        let wait = self.traffic.lock().unwrap().reserve(&chat, Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+
+        let (typing, presence) = {
+            let traffic = self.traffic.lock().unwrap();
+            (crate::message_text(&message).and_then(|text| traffic.typing_for(text)), traffic.settings.presence)
+        };
+        let online_while_sending = presence == traffic::PresenceMode::OnlineWhileSending;
+        if online_while_sending {
+            self.set_presence(PresenceStatus::Available).ok();
+        }
+        if let Some(typing) = typing {
+            self.send_chat_state(to, presence::ChatState::Composing).ok();
+            tokio::time::sleep(typing).await;
+            self.send_chat_state(to, presence::ChatState::Paused).ok();
+        }
+        let result = self.send_prepared_in(to, message);
+        if online_while_sending {
+            self.set_presence(PresenceStatus::Unavailable).ok();
+        }
+        result
+    }
+
+    /// Mengirim tanpa menunggu: gagal dengan `ErrorKind::QueueFull` jika
+    /// antrean kirim penuh dan `ErrorKind::RateLimited` jika traffic shaping
+    /// meminta jeda. Simulasi mengetik dan presence dilewati.
+    pub fn try_send(&self, to: &Jid, builder: MessageBuilder) -> Result<String> {
+        builder.validate()?;
+        let message = builder.own_jid(self.get_own_jid()).build(to);
+        self.send_queue.try_admit()?;
+        let chat = to.to_string();
+        let rate_limited = |retry_after| Error { kind: ErrorKind::RateLimited { retry_after } };
+        let wait = self.traffic.lock().unwrap().wait_for(&chat, Instant::now());
+        if !wait.is_zero() {
+            return Err(rate_limited(wait));
+        }
+        // Slot traffic baru dipesan setelah rate limit dan pemanasan lolos,
+        // agar pesan yang ditolak tidak menahan pengiriman berikutnya
+        self.admit_rate_limit(&[chat.clone()])?;
+        self.admit_warmup(&[chat.clone()])?;
+        self.traffic.lock().unwrap().try_reserve(&chat, Instant::now()).map_err(rate_limited)?;
+        self.send_prepared_in(to, message)
+    }
+
+    /// Jumlah byte pesan yang menunggu ditulis ke socket
+    pub fn send_queue_len(&self) -> usize {
+        self.send_queue.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Wake;
+    use std::time::Duration;
+
+    struct Flag(Mutex<bool>);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            *self.0.lock().unwrap() = true;
+        }
+    }
+
+    #[test]
+    fn test_bytes_are_released_after_write() {
+        let queue = SendQueue::start(Arc::new(Mutex::new(None)), Arc::new(Mutex::new(Framing::new())), 4);
+        assert!(queue.try_admit().is_ok());
+        // Batas diperiksa sebelum pesan masuk; antrean boleh sedikit melewatinya
+        queue.in_flight.lock().unwrap().bytes = 4;
+        assert!(matches!(queue.try_admit(), Err(Error { kind: ErrorKind::QueueFull })));
+
+        let flag = Arc::new(Flag(Mutex::new(false)));
+        let waker = Waker::from(Arc::clone(&flag));
+        let mut ready = queue.ready();
+        assert!(Pin::new(&mut ready).poll(&mut Context::from_waker(&waker)).is_pending());
+
+        // Frame dibuang karena tidak terhubung, lalu byte-nya dilepas
+        queue.in_flight.lock().unwrap().bytes = 0;
+        queue.push(vec![1, 2, 3, 4, 5]).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while queue.len() > 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(queue.len(), 0);
+        assert!(*flag.0.lock().unwrap());
+        assert!(Pin::new(&mut ready).poll(&mut Context::from_waker(&waker)).is_ready());
+    }
+}
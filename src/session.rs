@@ -16,10 +16,19 @@ pub struct Session {
     pub phone_info: Option<PhoneInfo>,
     pub is_logged_in: bool,
     pub registration_id: u32,
+    /// Kunci statis Noise; server mengenali perangkat dari kunci ini
+    pub noise_key_pair: KeyPair,
     pub identity_key_pair: KeyPair,
     pub signed_pre_key: SignedPreKey,
+    /// Signed pre-key sebelum rotasi terakhir; tetap diterima sampai masa tenggang habis
+    pub previous_signed_pre_key: Option<SignedPreKey>,
     pub one_time_keys: HashMap<u32, Key>,
     pub next_pre_key_id: u32,
+    pub adv_secret_key: Vec<u8>,
+    /// Waktu pairing pertama (detik sejak epoch)
+    pub paired_at: Option<i64>,
+    /// Data registrasi dan pre-key sudah diterima server
+    pub pre_keys_uploaded: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +52,7 @@ pub struct KeyPair {
 pub struct SignedPreKey {
     pub key_id: u32,
     pub public_key: Vec<u8>,
+    pub private_key: Vec<u8>,
     pub signature: Vec<u8>,
     pub timestamp: u64,
 }
@@ -51,6 +61,7 @@ pub struct SignedPreKey {
 pub struct Key {
     pub key_id: u32,
     pub public_key: Vec<u8>,
+    pub private_key: Vec<u8>,
 }
 
 impl Session {
@@ -58,6 +69,8 @@ impl Session {
     pub fn new() -> Self {
         let mut client_id_bytes = [0u8; 16];
         ring::rand::SystemRandom::new().fill(&mut client_id_bytes).unwrap();
+        let identity_key_pair = generate_identity_key_pair();
+        let signed_pre_key = generate_signed_pre_key(&identity_key_pair, 1);
         
         Self {
             client_id: base64::encode(&client_id_bytes),
@@ -71,10 +84,15 @@ impl Session {
             phone_info: None,
             is_logged_in: false,
             registration_id: generate_registration_id(),
-            identity_key_pair: generate_identity_key_pair(),
-            signed_pre_key: generate_signed_pre_key(),
+            noise_key_pair: crate::signal::generate_key_pair().unwrap(),
+            identity_key_pair,
+            signed_pre_key,
+            previous_signed_pre_key: None,
             one_time_keys: HashMap::new(),
             next_pre_key_id: 1,
+            adv_secret_key: Vec::new(),
+            paired_at: None,
+            pre_keys_uploaded: false,
         }
     }
 
@@ -94,6 +112,22 @@ impl Session {
     pub fn set_user_identity(&mut self, wid: String, push_name: String) {
         self.wid = wid;
         self.push_name = push_name;
+        self.paired_at.get_or_insert_with(|| chrono::Utc::now().timestamp());
+    }
+
+    /// Mengganti signed pre-key dengan yang baru; kunci lama disimpan sebagai
+    /// `previous_signed_pre_key` sampai dipensiunkan
+    pub fn rotate_signed_pre_key(&mut self) -> &SignedPreKey {
+        let next = generate_signed_pre_key(&self.identity_key_pair, self.signed_pre_key.key_id.wrapping_add(1).max(1));
+        self.previous_signed_pre_key = Some(std::mem::replace(&mut self.signed_pre_key, next));
+        &self.signed_pre_key
+    }
+
+    /// Signed pre-key dengan id `key_id` (saat ini atau sebelumnya)
+    pub fn signed_pre_key_by_id(&self, key_id: u32) -> Option<&SignedPreKey> {
+        std::iter::once(&self.signed_pre_key)
+            .chain(self.previous_signed_pre_key.as_ref())
+            .find(|key| key.key_id == key_id)
     }
 
     /// Cek apakah session valid
@@ -114,29 +148,21 @@ fn generate_registration_id() -> u32 {
 
 /// Fungsi bantu untuk menghasilkan pasangan kunci identitas
 fn generate_identity_key_pair() -> KeyPair {
-    // Dalam implementasi sebenarnya akan menggunakan fungsi kriptografi yang tepat
-    let mut public_key = [0u8; 32];
-    let mut private_key = [0u8; 32];
-    ring::rand::SystemRandom::new().fill(&mut public_key).unwrap();
-    ring::rand::SystemRandom::new().fill(&mut private_key).unwrap();
-    
-    KeyPair {
-        public_key: public_key.to_vec(),
-        private_key: private_key.to_vec(),
-    }
+    // X25519 sungguhan: kunci privat dipakai untuk X3DH (lihat `signal`)
+    crate::signal::generate_key_pair().unwrap()
 }
 
-/// Fungsi bantu untuk menghasilkan signed pre-key
-fn generate_signed_pre_key() -> SignedPreKey {
-    let mut public_key = [0u8; 32];
-    let mut signature = [0u8; 64];
-    ring::rand::SystemRandom::new().fill(&mut public_key).unwrap();
-    ring::rand::SystemRandom::new().fill(&mut signature).unwrap();
+/// Fungsi bantu untuk menghasilkan signed pre-key, ditandatangani (XEdDSA)
+/// dengan kunci identitas
+fn generate_signed_pre_key(identity: &KeyPair, key_id: u32) -> SignedPreKey {
+    let key_pair = crate::signal::generate_key_pair().unwrap();
+    let signature = crate::signal::sign_pre_key(identity, &key_pair.public_key).unwrap();
     
     SignedPreKey {
-        key_id: 1, // Default key ID
-        public_key: public_key.to_vec(),
-        signature: signature.to_vec(),
+        key_id,
+        public_key: key_pair.public_key,
+        private_key: key_pair.private_key,
+        signature,
         timestamp: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -149,12 +175,12 @@ fn generate_signed_pre_key() -> SignedPreKey {
         let key_id = self.next_pre_key_id;
         self.next_pre_key_id += 1;
         
-        let mut public_key = [0u8; 32];
-        ring::rand::SystemRandom::new().fill(&mut public_key).unwrap();
+        let key_pair = crate::signal::generate_key_pair()?;
         
         self.one_time_keys.insert(key_id, Key {
             key_id,
-            public_key: public_key.to_vec(),
+            public_key: key_pair.public_key,
+            private_key: key_pair.private_key,
         });
         
         Ok(key_id)
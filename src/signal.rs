@@ -0,0 +1,1078 @@
+//! Sesi Signal keluar (X3DH) per perangkat penerima
+//!
+//! Untuk perangkat yang belum memiliki sesi, prekey bundle-nya diambil lewat
+//! IQ `encrypt`. Tanda tangan signed prekey diverifikasi (XEdDSA) dengan kunci
+//! identitas perangkat, lalu X3DH menghasilkan root key dan chain key awal.
+//! Selama penerima belum membalas, setiap pesan dikirim sebagai `pkmsg`
+//! (PreKeySignalMessage) yang membawa base key kita agar penerima bisa
+//! menurunkan sesi yang sama.
+//!
+//! Arah sebaliknya, `open_message` membuka `<enc>` di stanza `message`: `pkmsg`
+//! pertama dari sebuah perangkat membuat sesi masuk dari signed prekey dan
+//! one-time prekey kita (X3DH sisi penerima), lalu chain key sesi itu dipakai
+//! untuk pesan berikutnya.
+//!
+//! Identitas perangkat disimpan saat sesi pertama dibuat (trust on first use).
+//! Bundle dengan identitas berbeda ditolak (`ErrorKind::UntrustedIdentity`)
+//! sampai server mengirim `notification type="encrypt"` berisi `identity`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use openssl::bn::{BigNum, BigNumContext, BigNumRef};
+use openssl::derive::Deriver;
+use openssl::pkey::{Id, PKey};
+use openssl::sign::Verifier;
+use openssl::memcmp;
+use openssl::symm::{decrypt, encrypt, Cipher};
+use ring::rand::SecureRandom;
+use ring::{hkdf, hmac};
+use sha2::{Digest, Sha512};
+
+use crate::devices;
+use crate::errors::*;
+use crate::iq;
+use crate::messages::WebMessageInfo;
+use crate::node_protocol::{Node, NodeContent};
+use crate::routing::NodeContext;
+use crate::session::{KeyPair, Session};
+use crate::{utils, WhatsAppClient};
+
+/// Prefix tipe kunci Curve25519 pada kunci publik terserialisasi
+pub const DJB_TYPE: u8 = 5;
+
+/// Versi pesan Signal (3) di nibble atas dan bawah
+const MESSAGE_VERSION: u8 = 0x33;
+
+/// Panjang MAC pesan Signal
+const MAC_LENGTH: usize = 8;
+
+/// Jumlah pesan yang boleh dilewati dalam satu chain penerima
+const MAX_SKIP: u32 = 2000;
+
+/// Prekey bundle satu perangkat dari balasan IQ `encrypt`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreKeyBundle {
+    /// JID perangkat
+    pub device: String,
+    pub registration_id: u32,
+    pub identity_key: Vec<u8>,
+    pub signed_pre_key_id: u32,
+    pub signed_pre_key: Vec<u8>,
+    pub signed_pre_key_signature: Vec<u8>,
+    /// One-time prekey (id, kunci publik); bisa habis di server
+    pub pre_key: Option<(u32, Vec<u8>)>,
+}
+
+/// Jenis ciphertext di `<enc type>`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EncryptedType {
+    /// PreKeySignalMessage, sebelum penerima membalas
+    PreKey,
+    /// SignalMessage biasa
+    Message,
+}
+
+impl EncryptedType {
+    pub fn tag(&self) -> &'static str {
+        match self {
+            EncryptedType::PreKey => "pkmsg",
+            EncryptedType::Message => "msg",
+        }
+    }
+}
+
+/// Data X3DH yang disertakan di `pkmsg` sampai penerima membalas
+#[derive(Debug, Clone)]
+struct PendingPreKey {
+    pre_key_id: Option<u32>,
+    signed_pre_key_id: u32,
+    base_key: Vec<u8>,
+}
+
+/// Sesi keluar satu perangkat
+#[derive(Debug, Clone)]
+struct OutboundSession {
+    remote_identity: Vec<u8>,
+    chain_key: Vec<u8>,
+    counter: u32,
+    ratchet: KeyPair,
+    pending: Option<PendingPreKey>,
+}
+
+/// Sesi masuk dari satu perangkat pengirim
+#[derive(Debug, Clone)]
+struct InboundSession {
+    remote_identity: Vec<u8>,
+    /// Base key X3DH pengirim; `pkmsg` dengan base key lain membuat sesi baru
+    base_key: Vec<u8>,
+    ratchet_key: Vec<u8>,
+    chain_key: Vec<u8>,
+    counter: u32,
+}
+
+/// SignalMessage yang sudah dipecah
+struct SignalMessage<'a> {
+    ratchet_key: &'a [u8],
+    counter: u32,
+    ciphertext: &'a [u8],
+    /// Versi dan isi pesan, yang ditandatangani MAC
+    signed: &'a [u8],
+    mac: &'a [u8],
+}
+
+/// Sender key kita untuk satu grup
+#[derive(Debug, Clone)]
+struct SenderKey {
+    key_id: u32,
+    iteration: u32,
+    chain_key: Vec<u8>,
+    signing_key: KeyPair,
+}
+
+/// Sesi Signal dan identitas yang dipercaya, per JID perangkat
+#[derive(Default)]
+pub struct SignalStore {
+    sessions: HashMap<String, OutboundSession>,
+    inbound: HashMap<String, InboundSession>,
+    identities: HashMap<String, Vec<u8>>,
+    /// JID grup -> sender key kita
+    sender_keys: HashMap<String, SenderKey>,
+}
+
+fn crypto_error(context: &str, e: impl std::fmt::Display) -> Error {
+    Error { kind: ErrorKind::CryptoError(format!("{}: {}", context, e)) }
+}
+
+/// Pasangan kunci X25519 baru
+pub fn generate_key_pair() -> Result<KeyPair> {
+    let private = PKey::generate_x25519().map_err(|e| crypto_error("Failed to generate key pair", e))?;
+    Ok(KeyPair {
+        public_key: private.raw_public_key().map_err(|e| crypto_error("Failed to read public key", e))?,
+        private_key: private.raw_private_key().map_err(|e| crypto_error("Failed to read private key", e))?,
+    })
+}
+
+/// Kunci publik terserialisasi (`0x05 || kunci`)
+fn serialize_key(public_key: &[u8]) -> Vec<u8> {
+    let mut serialized = Vec::with_capacity(33);
+    serialized.push(DJB_TYPE);
+    serialized.extend_from_slice(strip_key_type(public_key));
+    serialized
+}
+
+fn strip_key_type(public_key: &[u8]) -> &[u8] {
+    match public_key {
+        [DJB_TYPE, rest @ ..] if rest.len() == 32 => rest,
+        _ => public_key,
+    }
+}
+
+/// X25519 antara kunci privat mentah dan kunci publik (dengan atau tanpa prefix)
+fn agree(private_key: &[u8], public_key: &[u8]) -> Result<Vec<u8>> {
+    let ours = PKey::private_key_from_raw_bytes(private_key, Id::X25519).map_err(|e| crypto_error("Invalid private key", e))?;
+    let theirs = PKey::public_key_from_raw_bytes(strip_key_type(public_key), Id::X25519).map_err(|e| crypto_error("Invalid public key", e))?;
+    let mut deriver = Deriver::new(&ours).map_err(|e| crypto_error("Key agreement failed", e))?;
+    deriver.set_peer(&theirs).map_err(|e| crypto_error("Key agreement failed", e))?;
+    deriver.derive_to_vec().map_err(|e| crypto_error("Key agreement failed", e))
+}
+
+struct OutputLength(usize);
+
+impl hkdf::KeyType for OutputLength {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+fn derive(input: &[u8], salt: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>> {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, salt).extract(input);
+    let info = [info];
+    let okm = prk.expand(&info, OutputLength(length)).map_err(|_| crypto_error("HKDF expand failed", "invalid length"))?;
+    let mut derived = vec![0u8; length];
+    okm.fill(&mut derived).map_err(|_| crypto_error("HKDF fill failed", "invalid length"))?;
+    Ok(derived)
+}
+
+/// Koordinat y Edwards dari koordinat u Montgomery: y = (u - 1) / (u + 1) mod p
+fn montgomery_to_edwards(u: &[u8]) -> Result<[u8; 32]> {
+    let bn = |e| crypto_error("Big number arithmetic failed", e);
+    let mut ctx = BigNumContext::new().map_err(bn)?;
+    let one = BigNum::from_u32(1).map_err(bn)?;
+    let mut p = BigNum::new().map_err(bn)?;
+    p.lshift(&one, 255).map_err(bn)?;
+    p.sub_word(19).map_err(bn)?;
+
+    let mut little_endian = [0u8; 32];
+    little_endian.copy_from_slice(u);
+    little_endian[31] &= 0x7f;
+    little_endian.reverse();
+    let u = BigNum::from_slice(&little_endian).map_err(bn)?;
+
+    let mut numerator = BigNum::new().map_err(bn)?;
+    numerator.mod_sub(&u, &one, &p, &mut ctx).map_err(bn)?;
+    let mut denominator = BigNum::new().map_err(bn)?;
+    denominator.mod_add(&u, &one, &p, &mut ctx).map_err(bn)?;
+    let mut inverse = BigNum::new().map_err(bn)?;
+    inverse.mod_inverse(&denominator, &p, &mut ctx).map_err(bn)?;
+    let mut y = BigNum::new().map_err(bn)?;
+    y.mod_mul(&numerator, &inverse, &p, &mut ctx).map_err(bn)?;
+
+    let mut encoded = [0u8; 32];
+    encoded.copy_from_slice(&y.to_vec_padded(32).map_err(bn)?);
+    encoded.reverse();
+    Ok(encoded)
+}
+
+/// Verifikasi tanda tangan XEdDSA atas `message` dengan kunci identitas
+/// Curve25519. Bit tanda kunci Edwards dibawa bit teratas tanda tangan.
+pub fn verify_signature(identity_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let identity_key = strip_key_type(identity_key);
+    if identity_key.len() != 32 || signature.len() != 64 {
+        return false;
+    }
+    let mut edwards = match montgomery_to_edwards(identity_key) {
+        Ok(edwards) => edwards,
+        Err(_) => return false,
+    };
+    edwards[31] |= signature[63] & 0x80;
+    let mut signature = signature.to_vec();
+    signature[63] &= 0x7f;
+
+    PKey::public_key_from_raw_bytes(&edwards, Id::ED25519)
+        .and_then(|key| Verifier::new_without_digest(&key)?.verify_oneshot(&signature, message))
+        .unwrap_or(false)
+}
+
+/// Koordinat titik dasar Ed25519 dan orde subgrupnya (desimal)
+const BASE_X: &str = "15112221349535400772501151409588531511454012693041857206046113283949847762202";
+const BASE_Y: &str = "46316835694926478169428394003475163141307993866256225615783033603165251855960";
+const GROUP_ORDER: &str = "7237005577332262213973186563042994240857116359379907606001950938285454250989";
+
+type Point = (BigNum, BigNum);
+
+fn bn_error(e: openssl::error::ErrorStack) -> Error {
+    crypto_error("Big number arithmetic failed", e)
+}
+
+/// Angka little-endian (skalar dan koordinat Curve25519)
+fn from_le_bytes(bytes: &[u8]) -> Result<BigNum> {
+    let mut big_endian = bytes.to_vec();
+    big_endian.reverse();
+    BigNum::from_slice(&big_endian).map_err(bn_error)
+}
+
+fn to_le_bytes(n: &BigNumRef) -> Result<[u8; 32]> {
+    let mut encoded = [0u8; 32];
+    encoded.copy_from_slice(&n.to_vec_padded(32).map_err(bn_error)?);
+    encoded.reverse();
+    Ok(encoded)
+}
+
+/// Aritmetika Edwards25519 dalam koordinat afin, cukup untuk menandatangani
+/// signed pre-key sesekali
+struct Edwards {
+    p: BigNum,
+    d: BigNum,
+    ctx: BigNumContext,
+}
+
+impl Edwards {
+    fn new() -> Result<Self> {
+        let mut ctx = BigNumContext::new().map_err(bn_error)?;
+        let (zero, one) = (BigNum::new().map_err(bn_error)?, BigNum::from_u32(1).map_err(bn_error)?);
+        let mut p = BigNum::new().map_err(bn_error)?;
+        p.lshift(&one, 255).map_err(bn_error)?;
+        p.sub_word(19).map_err(bn_error)?;
+
+        // d = -121665 / 121666 mod p
+        let (numerator, denominator) = (BigNum::from_u32(121665).map_err(bn_error)?, BigNum::from_u32(121666).map_err(bn_error)?);
+        let mut inverse = BigNum::new().map_err(bn_error)?;
+        inverse.mod_inverse(&denominator, &p, &mut ctx).map_err(bn_error)?;
+        let mut ratio = BigNum::new().map_err(bn_error)?;
+        ratio.mod_mul(&numerator, &inverse, &p, &mut ctx).map_err(bn_error)?;
+        let mut d = BigNum::new().map_err(bn_error)?;
+        d.mod_sub(&zero, &ratio, &p, &mut ctx).map_err(bn_error)?;
+        Ok(Edwards { p, d, ctx })
+    }
+
+    fn mul(&mut self, a: &BigNumRef, b: &BigNumRef) -> Result<BigNum> {
+        let mut r = BigNum::new().map_err(bn_error)?;
+        r.mod_mul(a, b, &self.p, &mut self.ctx).map_err(bn_error)?;
+        Ok(r)
+    }
+
+    fn add_mod(&mut self, a: &BigNumRef, b: &BigNumRef) -> Result<BigNum> {
+        let mut r = BigNum::new().map_err(bn_error)?;
+        r.mod_add(a, b, &self.p, &mut self.ctx).map_err(bn_error)?;
+        Ok(r)
+    }
+
+    fn div(&mut self, a: &BigNumRef, b: &BigNumRef) -> Result<BigNum> {
+        let mut inverse = BigNum::new().map_err(bn_error)?;
+        inverse.mod_inverse(b, &self.p, &mut self.ctx).map_err(bn_error)?;
+        self.mul(a, &inverse)
+    }
+
+    /// Penjumlahan titik lengkap untuk a = -1:
+    /// x3 = (x1y2 + y1x2) / (1 + dx1x2y1y2), y3 = (y1y2 + x1x2) / (1 - dx1x2y1y2)
+    fn add(&mut self, (x1, y1): &Point, (x2, y2): &Point) -> Result<Point> {
+        let x1x2 = self.mul(x1, x2)?;
+        let y1y2 = self.mul(y1, y2)?;
+        let d = self.d.to_owned().map_err(bn_error)?;
+        let dxy = self.mul(&d, &x1x2)?;
+        let t = self.mul(&dxy, &y1y2)?;
+        let one = BigNum::from_u32(1).map_err(bn_error)?;
+
+        let x1y2 = self.mul(x1, y2)?;
+        let y1x2 = self.mul(y1, x2)?;
+        let x_numerator = self.add_mod(&x1y2, &y1x2)?;
+        let x_denominator = self.add_mod(&one, &t)?;
+        let y_numerator = self.add_mod(&y1y2, &x1x2)?;
+        let mut y_denominator = BigNum::new().map_err(bn_error)?;
+        y_denominator.mod_sub(&one, &t, &self.p, &mut self.ctx).map_err(bn_error)?;
+        Ok((self.div(&x_numerator, &x_denominator)?, self.div(&y_numerator, &y_denominator)?))
+    }
+
+    /// `scalar` x titik dasar, double-and-add dari bit teratas
+    fn mul_base(&mut self, scalar: &BigNumRef) -> Result<Point> {
+        let base = (BigNum::from_dec_str(BASE_X).map_err(bn_error)?, BigNum::from_dec_str(BASE_Y).map_err(bn_error)?);
+        let mut acc = (BigNum::new().map_err(bn_error)?, BigNum::from_u32(1).map_err(bn_error)?);
+        for bit in (0..scalar.num_bits()).rev() {
+            acc = self.add(&acc, &acc)?;
+            if scalar.is_bit_set(bit) {
+                acc = self.add(&acc, &base)?;
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Koordinat y little-endian dengan paritas x di bit teratas
+    fn encode((x, y): &Point) -> Result<[u8; 32]> {
+        let mut encoded = to_le_bytes(y)?;
+        if x.is_bit_set(0) {
+            encoded[31] |= 0x80;
+        }
+        Ok(encoded)
+    }
+}
+
+/// `SHA-512(prefix || parts...) mod q`
+fn hash_scalar(prefix: &[u8], parts: &[&[u8]], order: &BigNumRef, ctx: &mut BigNumContext) -> Result<BigNum> {
+    let mut hasher = Sha512::new();
+    hasher.update(prefix);
+    for part in parts {
+        hasher.update(part);
+    }
+    let digest = from_le_bytes(&hasher.finalize())?;
+    let mut reduced = BigNum::new().map_err(bn_error)?;
+    reduced.nnmod(&digest, order, ctx).map_err(bn_error)?;
+    Ok(reduced)
+}
+
+/// Tanda tangan XEdDSA atas `message` dengan kunci privat Curve25519.
+/// Kunci publik Edwards dibuat bertanda positif (skalar dinegasikan bila
+/// perlu), sehingga bit teratas tanda tangan selalu nol dan tanda tangan
+/// lolos `verify_signature`.
+pub fn sign(private_key: &[u8], message: &[u8]) -> Result<Vec<u8>> {
+    if private_key.len() != 32 {
+        return Err(crypto_error("Invalid private key", "expected 32 bytes"));
+    }
+    let mut clamped = [0u8; 32];
+    clamped.copy_from_slice(private_key);
+    clamped[0] &= 248;
+    clamped[31] &= 127;
+    clamped[31] |= 64;
+
+    let mut curve = Edwards::new()?;
+    let mut ctx = BigNumContext::new().map_err(bn_error)?;
+    let order = BigNum::from_dec_str(GROUP_ORDER).map_err(bn_error)?;
+    let k = from_le_bytes(&clamped)?;
+    let mut public_key = Edwards::encode(&curve.mul_base(&k)?)?;
+    let negative = public_key[31] & 0x80 != 0;
+    public_key[31] &= 0x7f;
+
+    let mut a = BigNum::new().map_err(bn_error)?;
+    a.nnmod(&k, &order, &mut ctx).map_err(bn_error)?;
+    if negative {
+        let zero = BigNum::new().map_err(bn_error)?;
+        let mut negated = BigNum::new().map_err(bn_error)?;
+        negated.mod_sub(&zero, &a, &order, &mut ctx).map_err(bn_error)?;
+        a = negated;
+    }
+    let a_bytes = to_le_bytes(&a)?;
+
+    let mut nonce = [0u8; 64];
+    ring::rand::SystemRandom::new().fill(&mut nonce).map_err(|_| crypto_error("Failed to generate nonce", "rng failure"))?;
+    let mut hash1_prefix = [0xffu8; 32];
+    hash1_prefix[0] = 0xfe;
+    let r = hash_scalar(&hash1_prefix, &[&a_bytes, message, &nonce], &order, &mut ctx)?;
+    let big_r = Edwards::encode(&curve.mul_base(&r)?)?;
+    let h = hash_scalar(&[], &[&big_r, &public_key, message], &order, &mut ctx)?;
+
+    let mut ha = BigNum::new().map_err(bn_error)?;
+    ha.mod_mul(&h, &a, &order, &mut ctx).map_err(bn_error)?;
+    let mut s = BigNum::new().map_err(bn_error)?;
+    s.mod_add(&r, &ha, &order, &mut ctx).map_err(bn_error)?;
+
+    let mut signature = big_r.to_vec();
+    signature.extend_from_slice(&to_le_bytes(&s)?);
+    Ok(signature)
+}
+
+/// Tanda tangan signed pre-key: XEdDSA atas kunci publik terserialisasi
+pub fn sign_pre_key(identity: &KeyPair, public_key: &[u8]) -> Result<Vec<u8>> {
+    sign(&identity.private_key, &serialize_key(public_key))
+}
+
+/// Angka big-endian (id prekey 3 byte, registration id 4 byte)
+fn read_uint(bytes: &[u8]) -> Option<u32> {
+    if bytes.is_empty() || bytes.len() > 4 {
+        return None;
+    }
+    Some(bytes.iter().fold(0u32, |value, byte| (value << 8) | *byte as u32))
+}
+
+fn parse_key(node: &Node) -> Option<(u32, Vec<u8>)> {
+    let id = read_uint(node.get_child("id")?.get_bytes()?)?;
+    Some((id, node.get_child("value")?.get_bytes()?.to_vec()))
+}
+
+/// Membaca bundle `<user jid><registration/><identity/><skey/><key/></user>`;
+/// `None` jika user tidak membawa signed prekey (atau membawa `error`)
+pub fn parse_bundle(user: &Node) -> Option<PreKeyBundle> {
+    if user.get_child("error").is_some() {
+        return None;
+    }
+    parse_keys(&user.attr_jid("jid").ok()?.to_string(), read_registration_id(user)?, user)
+}
+
+/// Membaca `<identity/><skey/><key/>` di dalam `keys` (balasan IQ atau retry receipt)
+pub fn parse_keys(device: &str, registration_id: u32, keys: &Node) -> Option<PreKeyBundle> {
+    let skey = keys.get_child("skey")?;
+    let (signed_pre_key_id, signed_pre_key) = parse_key(skey)?;
+    Some(PreKeyBundle {
+        device: device.to_string(),
+        registration_id,
+        identity_key: keys.get_child("identity")?.get_bytes()?.to_vec(),
+        signed_pre_key_id,
+        signed_pre_key,
+        signed_pre_key_signature: skey.get_child("signature")?.get_bytes()?.to_vec(),
+        pre_key: keys.get_child("key").and_then(parse_key),
+    })
+}
+
+pub(crate) fn read_registration_id(node: &Node) -> Option<u32> {
+    read_uint(node.get_child("registration")?.get_bytes()?)
+}
+
+/// IQ `encrypt` untuk prekey bundle perangkat `devices`
+pub fn bundle_query_node(id: &str, devices: &[String]) -> Node {
+    Node::new("iq")
+        .attr("id", id)
+        .attr("xmlns", "encrypt")
+        .attr("type", "get")
+        .attr("to", "s.whatsapp.net")
+        .children(vec![Node::new("key").children(devices.iter().map(|jid| Node::new("user").attr("jid", jid)).collect())])
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn put_uint(out: &mut Vec<u8>, field: u64, value: u32) {
+    put_varint(out, field << 3);
+    put_varint(out, value as u64);
+}
+
+fn put_bytes(out: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    put_varint(out, field << 3 | 2);
+    put_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+/// Field protobuf pesan Signal: varint atau bytes
+enum Field<'a> {
+    Uint(u64),
+    Bytes(&'a [u8]),
+}
+
+fn get_varint(data: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (byte, rest) = data.split_first()?;
+        *data = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Membaca field pesan Signal setelah byte versi; `None` jika rusak
+fn read_fields(data: &[u8]) -> Option<HashMap<u64, Field<'_>>> {
+    let (version, mut data) = data.split_first()?;
+    if *version != MESSAGE_VERSION {
+        return None;
+    }
+    let mut fields = HashMap::new();
+    while !data.is_empty() {
+        let key = get_varint(&mut data)?;
+        let value = match key & 7 {
+            0 => Field::Uint(get_varint(&mut data)?),
+            2 => {
+                let length = get_varint(&mut data)? as usize;
+                if length > data.len() {
+                    return None;
+                }
+                let (bytes, rest) = data.split_at(length);
+                data = rest;
+                Field::Bytes(bytes)
+            }
+            _ => return None,
+        };
+        fields.insert(key >> 3, value);
+    }
+    Some(fields)
+}
+
+fn uint_field(fields: &HashMap<u64, Field>, field: u64) -> Option<u32> {
+    match fields.get(&field)? {
+        Field::Uint(value) => u32::try_from(*value).ok(),
+        Field::Bytes(_) => None,
+    }
+}
+
+fn bytes_field<'a>(fields: &HashMap<u64, Field<'a>>, field: u64) -> Option<&'a [u8]> {
+    match fields.get(&field)? {
+        Field::Bytes(bytes) => Some(bytes),
+        Field::Uint(_) => None,
+    }
+}
+
+fn parse_signal_message(data: &[u8]) -> Result<SignalMessage<'_>> {
+    let invalid = || crypto_error("Invalid SignalMessage", format!("{} bytes", data.len()));
+    if data.len() <= MAC_LENGTH {
+        return Err(invalid());
+    }
+    let (signed, mac) = data.split_at(data.len() - MAC_LENGTH);
+    let fields = read_fields(signed).ok_or_else(invalid)?;
+    Ok(SignalMessage {
+        ratchet_key: bytes_field(&fields, 1).ok_or_else(invalid)?,
+        counter: uint_field(&fields, 2).ok_or_else(invalid)?,
+        ciphertext: bytes_field(&fields, 4).ok_or_else(invalid)?,
+        signed,
+        mac,
+    })
+}
+
+/// Shared secret X3DH sisi pengirim:
+/// `0xFF*32 || DH(IKa, SPKb) || DH(EKa, IKb) || DH(EKa, SPKb) [|| DH(EKa, OPKb)]`
+fn x3dh_secret(identity: &KeyPair, base: &KeyPair, bundle: &PreKeyBundle) -> Result<Vec<u8>> {
+    let mut secret = vec![0xffu8; 32];
+    secret.extend(agree(&identity.private_key, &bundle.signed_pre_key)?);
+    secret.extend(agree(&base.private_key, &bundle.identity_key)?);
+    secret.extend(agree(&base.private_key, &bundle.signed_pre_key)?);
+    if let Some((_, pre_key)) = &bundle.pre_key {
+        secret.extend(agree(&base.private_key, pre_key)?);
+    }
+    Ok(secret)
+}
+
+impl OutboundSession {
+    /// Sesi Alice: X3DH lalu satu langkah ratchet ke signed prekey penerima
+    fn initiate(identity: &KeyPair, bundle: &PreKeyBundle) -> Result<Self> {
+        let base = generate_key_pair()?;
+        let derived = derive(&x3dh_secret(identity, &base, bundle)?, &[], b"WhisperText", 64)?;
+
+        let ratchet = generate_key_pair()?;
+        let shared = agree(&ratchet.private_key, &bundle.signed_pre_key)?;
+        let sending = derive(&shared, &derived[..32], b"WhisperRatchet", 64)?;
+
+        Ok(OutboundSession {
+            remote_identity: strip_key_type(&bundle.identity_key).to_vec(),
+            chain_key: sending[32..].to_vec(),
+            counter: 0,
+            ratchet,
+            pending: Some(PendingPreKey {
+                pre_key_id: bundle.pre_key.as_ref().map(|(id, _)| *id),
+                signed_pre_key_id: bundle.signed_pre_key_id,
+                base_key: base.public_key,
+            }),
+        })
+    }
+
+    /// Mengenkripsi satu pesan dan memajukan chain key
+    fn encrypt(&mut self, local: &Session, plaintext: &[u8]) -> Result<(EncryptedType, Vec<u8>)> {
+        let chain = hmac::Key::new(hmac::HMAC_SHA256, &self.chain_key);
+        let seed = hmac::sign(&chain, &[0x01]);
+        let keys = derive(seed.as_ref(), &[], b"WhisperMessageKeys", 80)?;
+        self.chain_key = hmac::sign(&chain, &[0x02]).as_ref().to_vec();
+        let counter = self.counter;
+        self.counter += 1;
+
+        let ciphertext = encrypt(Cipher::aes_256_cbc(), &keys[..32], Some(&keys[64..80]), plaintext).map_err(|e| crypto_error("Message encryption failed", e))?;
+
+        let mut message = vec![MESSAGE_VERSION];
+        put_bytes(&mut message, 1, &serialize_key(&self.ratchet.public_key));
+        put_uint(&mut message, 2, counter);
+        put_uint(&mut message, 3, 0);
+        put_bytes(&mut message, 4, &ciphertext);
+
+        let mut signed = serialize_key(&local.identity_key_pair.public_key);
+        signed.extend(serialize_key(&self.remote_identity));
+        signed.extend_from_slice(&message);
+        let mac = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &keys[32..64]), &signed);
+        message.extend_from_slice(&mac.as_ref()[..MAC_LENGTH]);
+
+        let pending = match &self.pending {
+            Some(pending) => pending,
+            None => return Ok((EncryptedType::Message, message)),
+        };
+        let mut pre_key_message = vec![MESSAGE_VERSION];
+        put_uint(&mut pre_key_message, 5, local.registration_id);
+        if let Some(pre_key_id) = pending.pre_key_id {
+            put_uint(&mut pre_key_message, 1, pre_key_id);
+        }
+        put_uint(&mut pre_key_message, 6, pending.signed_pre_key_id);
+        put_bytes(&mut pre_key_message, 2, &serialize_key(&pending.base_key));
+        put_bytes(&mut pre_key_message, 3, &serialize_key(&local.identity_key_pair.public_key));
+        put_bytes(&mut pre_key_message, 4, &message);
+        Ok((EncryptedType::PreKey, pre_key_message))
+    }
+}
+
+impl InboundSession {
+    /// Membuka satu pesan; chain key hanya maju jika MAC dan dekripsi berhasil
+    fn decrypt(&mut self, local: &Session, message: &SignalMessage) -> Result<Vec<u8>> {
+        if strip_key_type(message.ratchet_key) != self.ratchet_key.as_slice() {
+            return Err(crypto_error("Unknown ratchet key", "sender started a new chain"));
+        }
+        if message.counter < self.counter {
+            return Err(crypto_error("Message key already used", message.counter));
+        }
+        if message.counter - self.counter > MAX_SKIP {
+            return Err(crypto_error("Too many skipped messages", message.counter - self.counter));
+        }
+
+        let mut chain_key = self.chain_key.clone();
+        for _ in self.counter..message.counter {
+            chain_key = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &chain_key), &[0x02]).as_ref().to_vec();
+        }
+        let chain = hmac::Key::new(hmac::HMAC_SHA256, &chain_key);
+        let seed = hmac::sign(&chain, &[0x01]);
+        let keys = derive(seed.as_ref(), &[], b"WhisperMessageKeys", 80)?;
+
+        let mut signed = serialize_key(&self.remote_identity);
+        signed.extend(serialize_key(&local.identity_key_pair.public_key));
+        signed.extend_from_slice(message.signed);
+        let mac = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &keys[32..64]), &signed);
+        if !memcmp::eq(&mac.as_ref()[..MAC_LENGTH], message.mac) {
+            return Err(crypto_error("Message MAC mismatch", message.counter));
+        }
+        let plaintext = decrypt(Cipher::aes_256_cbc(), &keys[..32], Some(&keys[64..80]), message.ciphertext).map_err(|e| crypto_error("Message decryption failed", e))?;
+
+        self.chain_key = hmac::sign(&chain, &[0x02]).as_ref().to_vec();
+        self.counter = message.counter + 1;
+        Ok(plaintext)
+    }
+}
+
+impl SignalStore {
+    pub fn new() -> Self {
+        SignalStore::default()
+    }
+
+    pub fn has_session(&self, device: &str) -> bool {
+        self.sessions.contains_key(device)
+    }
+
+    /// Membuat sesi keluar dari bundle. Gagal dengan `NoPreKeys` jika bundle
+    /// kosong dan `UntrustedIdentity` jika identitas perangkat berubah.
+    pub fn process_bundle(&mut self, local: &Session, bundle: &PreKeyBundle) -> Result<()> {
+        if bundle.signed_pre_key.is_empty() || bundle.identity_key.is_empty() {
+            return Err(Error { kind: ErrorKind::NoPreKeys(bundle.device.clone()) });
+        }
+        let identity = strip_key_type(&bundle.identity_key);
+        if let Some(trusted) = self.identities.get(&bundle.device) {
+            if trusted.as_slice() != identity {
+                return Err(Error { kind: ErrorKind::UntrustedIdentity(bundle.device.clone()) });
+            }
+        }
+        if !verify_signature(identity, &serialize_key(&bundle.signed_pre_key), &bundle.signed_pre_key_signature) {
+            return Err(crypto_error("Invalid signed pre-key signature", &bundle.device));
+        }
+
+        let session = OutboundSession::initiate(&local.identity_key_pair, bundle)?;
+        self.identities.insert(bundle.device.clone(), identity.to_vec());
+        self.sessions.insert(bundle.device.clone(), session);
+        Ok(())
+    }
+
+    /// Mengenkripsi `plaintext` untuk `device`; `pkmsg` selama penerima belum membalas
+    pub fn encrypt(&mut self, local: &Session, device: &str, plaintext: &[u8]) -> Result<(EncryptedType, Vec<u8>)> {
+        let session = self.sessions.get_mut(device).ok_or_else(|| Error { kind: ErrorKind::NoPreKeys(device.to_string()) })?;
+        session.encrypt(local, plaintext)
+    }
+
+    /// Mendekripsi ciphertext dari perangkat `sender`. `pkmsg` dengan base key
+    /// baru membuat sesi masuk; one-time prekey yang dipakainya dihapus dari
+    /// `local` setelah pesan berhasil dibuka.
+    pub fn decrypt(&mut self, local: &mut Session, sender: &str, kind: EncryptedType, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let (mut session, message, used_pre_key) = match kind {
+            EncryptedType::PreKey => self.accept_pre_key_message(local, sender, ciphertext)?,
+            EncryptedType::Message => {
+                let session = self.inbound.get(sender).cloned().ok_or_else(|| crypto_error("No session with sender", sender))?;
+                (session, parse_signal_message(ciphertext)?, None)
+            }
+        };
+        let plaintext = session.decrypt(local, &message)?;
+
+        if let Some(pre_key_id) = used_pre_key {
+            local.remove_used_key(pre_key_id);
+        }
+        self.identities.insert(sender.to_string(), session.remote_identity.clone());
+        self.inbound.insert(sender.to_string(), session);
+        Ok(plaintext)
+    }
+
+    /// Sesi untuk PreKeySignalMessage: sesi yang ada jika base key-nya sama,
+    /// atau sesi baru dari X3DH sisi penerima beserta id one-time prekey-nya
+    fn accept_pre_key_message<'a>(&self, local: &Session, sender: &str, data: &'a [u8]) -> Result<(InboundSession, SignalMessage<'a>, Option<u32>)> {
+        let invalid = || crypto_error("Invalid PreKeySignalMessage", sender);
+        let fields = read_fields(data).ok_or_else(invalid)?;
+        let base_key = bytes_field(&fields, 2).ok_or_else(invalid)?;
+        let identity_key = bytes_field(&fields, 3).ok_or_else(invalid)?;
+        let message = parse_signal_message(bytes_field(&fields, 4).ok_or_else(invalid)?)?;
+
+        match self.inbound.get(sender) {
+            Some(session) if session.base_key.as_slice() == strip_key_type(base_key) => return Ok((session.clone(), message, None)),
+            _ => {}
+        }
+        let identity = strip_key_type(identity_key);
+        if let Some(trusted) = self.identities.get(sender) {
+            if trusted.as_slice() != identity {
+                return Err(Error { kind: ErrorKind::UntrustedIdentity(sender.to_string()) });
+            }
+        }
+
+        let signed_pre_key_id = uint_field(&fields, 6).ok_or_else(invalid)?;
+        let signed_pre_key = &local
+            .signed_pre_key_by_id(signed_pre_key_id)
+            .ok_or_else(|| crypto_error("Unknown signed pre-key", signed_pre_key_id))?
+            .private_key;
+        let pre_key_id = uint_field(&fields, 1);
+        let pre_key = match pre_key_id {
+            Some(id) => Some(&local.one_time_keys.get(&id).ok_or_else(|| crypto_error("Unknown one-time pre-key", id))?.private_key),
+            None => None,
+        };
+
+        let mut secret = vec![0xffu8; 32];
+        secret.extend(agree(signed_pre_key, identity_key)?);
+        secret.extend(agree(&local.identity_key_pair.private_key, base_key)?);
+        secret.extend(agree(signed_pre_key, base_key)?);
+        if let Some(pre_key) = pre_key {
+            secret.extend(agree(pre_key, base_key)?);
+        }
+        let derived = derive(&secret, &[], b"WhisperText", 64)?;
+        let receiving = derive(&agree(signed_pre_key, message.ratchet_key)?, &derived[..32], b"WhisperRatchet", 64)?;
+
+        let session = InboundSession {
+            remote_identity: identity.to_vec(),
+            base_key: strip_key_type(base_key).to_vec(),
+            ratchet_key: strip_key_type(message.ratchet_key).to_vec(),
+            chain_key: receiving[32..].to_vec(),
+            counter: 0,
+        };
+        Ok((session, message, pre_key_id))
+    }
+
+    /// Membuang sesi satu perangkat (penerima gagal mendekripsi); identitasnya
+    /// tetap dipercaya
+    pub fn remove_session(&mut self, device: &str) {
+        self.sessions.remove(device);
+    }
+
+    /// SenderKeyDistributionMessage terserialisasi untuk `group`; sender key
+    /// dibuat saat pertama kali dibutuhkan
+    pub fn sender_key_distribution(&mut self, group: &str) -> Result<Vec<u8>> {
+        if !self.sender_keys.contains_key(group) {
+            let rng = ring::rand::SystemRandom::new();
+            let mut key_id = [0u8; 4];
+            let mut chain_key = vec![0u8; 32];
+            rng.fill(&mut key_id).map_err(|_| crypto_error("Failed to generate sender key", "rng failure"))?;
+            rng.fill(&mut chain_key).map_err(|_| crypto_error("Failed to generate sender key", "rng failure"))?;
+            let sender_key = SenderKey {
+                key_id: u32::from_be_bytes(key_id) >> 1,
+                iteration: 0,
+                chain_key,
+                signing_key: generate_key_pair()?,
+            };
+            self.sender_keys.insert(group.to_string(), sender_key);
+        }
+
+        let sender_key = &self.sender_keys[group];
+        let mut message = vec![MESSAGE_VERSION];
+        put_uint(&mut message, 1, sender_key.key_id);
+        put_uint(&mut message, 2, sender_key.iteration);
+        put_bytes(&mut message, 3, &sender_key.chain_key);
+        put_bytes(&mut message, 4, &serialize_key(&sender_key.signing_key.public_key));
+        Ok(message)
+    }
+
+    /// Membuang sesi dan identitas tepercaya semua perangkat `user`
+    pub fn forget(&mut self, user: &str) {
+        let user = devices::split_device_jid(user).0;
+        self.sessions.retain(|jid, _| devices::split_device_jid(jid).0 != user);
+        self.inbound.retain(|jid, _| devices::split_device_jid(jid).0 != user);
+        self.identities.retain(|jid, _| devices::split_device_jid(jid).0 != user);
+    }
+}
+
+/// Memproses bundle di balasan IQ `encrypt`. Mengembalikan perangkat yang
+/// gagal beserta alasannya; perangkat tanpa bundle dibiarkan ke pemanggil.
+pub fn process_response(store: &mut SignalStore, local: &Session, response: &Node) -> Vec<(String, Error)> {
+    let users = match response.get_child("list") {
+        Some(list) => list.get_children(),
+        None => return Vec::new(),
+    };
+    users
+        .iter()
+        .filter(|user| user.tag == "user" && user.get_child("skey").is_some())
+        .filter_map(|user| {
+            // JID dinormalkan (`c.us` dari decoder) agar cocok dengan kunci sesi
+            let device = user.attr_jid("jid").ok()?.to_string();
+            let result = match parse_bundle(user) {
+                Some(bundle) => store.process_bundle(local, &bundle),
+                None => Err(Error { kind: ErrorKind::NoPreKeys(device.clone()) }),
+            };
+            result.err().map(|error| (device, error))
+        })
+        .collect()
+}
+
+/// Menambahkan `<enc type="pkmsg|msg">` ke setiap `<to>` di stanza relay
+/// yang perangkatnya memiliki sesi Signal
+pub fn seal_relay(relay: Node, store: &Mutex<SignalStore>, session: &Mutex<Option<Session>>) -> Result<Node> {
+    let session = session.lock().unwrap();
+    let local = match session.as_ref() {
+        Some(local) => local,
+        None => return Ok(relay),
+    };
+    let plaintext = match relay.get_child("message").and_then(|message| message.get_bytes()) {
+        Some(plaintext) => plaintext.to_vec(),
+        None => return Ok(relay),
+    };
+
+    let mut store = store.lock().unwrap();
+    let mut children = Vec::new();
+    for child in relay.get_children() {
+        if child.tag != "participants" {
+            children.push(child.clone());
+            continue;
+        }
+        let mut targets = Vec::new();
+        for to in child.get_children() {
+            let jid = to.get_attr("jid").unwrap_or_default();
+            if !store.has_session(jid) {
+                targets.push(to.clone());
+                continue;
+            }
+            let (kind, ciphertext) = store.encrypt(local, jid, &plaintext)?;
+            targets.push(Node::new("to").attr("jid", jid).children(vec![Node::new("enc").attr("v", "2").attr("type", kind.tag()).bytes(ciphertext)]));
+        }
+        children.push(Node::new("participants").children(targets));
+    }
+
+    let mut sealed = Node::new(&relay.tag).children(children);
+    sealed.attrs = relay.attrs.clone();
+    Ok(sealed)
+}
+
+/// Membuka `<enc>` di stanza `message` dari perangkat pengirim (`participant`
+/// untuk grup, selain itu `from`). Isinya diganti bytes `WebMessageInfo`
+/// seperti stanza tanpa enkripsi; kunci pesan diambil dari atribut stanza.
+/// Stanza lain dikembalikan apa adanya.
+pub fn open_message(mut node: Node, store: &Mutex<SignalStore>, session: &Mutex<Option<Session>>) -> Result<Node> {
+    let enc = match node.get_child("enc") {
+        Some(enc) if node.tag == "message" => enc,
+        _ => return Ok(node),
+    };
+    let kind = match enc.get_attr("type") {
+        Some("pkmsg") => EncryptedType::PreKey,
+        Some("msg") => EncryptedType::Message,
+        other => return Err(crypto_error("Unsupported enc type", other.unwrap_or_default())),
+    };
+    let ciphertext = enc.get_bytes().ok_or("Empty enc node")?;
+    let chat = node.attr_jid("from")?;
+    let participant = match node.get_attr("participant") {
+        Some(_) => Some(node.attr_jid("participant")?.to_string()),
+        None => None,
+    };
+    let sender = participant.clone().unwrap_or_else(|| chat.to_string());
+
+    let plaintext = {
+        let mut session = session.lock().unwrap();
+        let local = session.as_mut().ok_or("Not logged in")?;
+        store.lock().unwrap().decrypt(local, &sender, kind, ciphertext)?
+    };
+    let mut web_message = WebMessageInfo::from_bytes(&plaintext)?;
+    web_message.key.remote_jid = chat.to_string();
+    web_message.key.from_me = false;
+    web_message.key.participant = participant.clone();
+    web_message.participant = participant;
+
+    node.content = Some(NodeContent::Binary(web_message.to_bytes()));
+    Ok(node)
+}
+
+/// Handler `notification type="encrypt"`: identitas baru kontak dipercaya
+pub fn identity_change_handler(store: Arc<Mutex<SignalStore>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if node.get_child("identity").is_some() {
+            if let Some(from) = node.get_attr("from") {
+                store.lock().unwrap().forget(from);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Memastikan sesi Signal dengan perangkat `device` (`user:device@server`)
+    /// ada; prekey bundle diambil dari server jika belum
+    pub fn establish_session(&self, device: &str) -> Result<()> {
+        if self.signal.lock().unwrap().has_session(device) {
+            return Ok(());
+        }
+        let response = self.query(&bundle_query_node(&utils::generate_message_id(), &[device.to_string()]), iq::DEFAULT_QUERY_TIMEOUT)?;
+        let bundle = response
+            .get_child("list")
+            .and_then(|list| list.get_children().iter().find(|user| user.get_attr("jid") == Some(device)))
+            .and_then(parse_bundle)
+            .ok_or_else(|| Error { kind: ErrorKind::NoPreKeys(device.to_string()) })?;
+
+        {
+            let session = self.session.lock().unwrap();
+            let local = session.as_ref().ok_or("Not logged in")?;
+            self.signal.lock().unwrap().process_bundle(local, &bundle)?;
+        }
+        self.outbox.lock().unwrap().mark_session(device);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn responder_secret(identity: &KeyPair, signed_pre_key: &KeyPair, pre_key: &KeyPair, alice_identity: &[u8], base_key: &[u8]) -> Vec<u8> {
+        let mut secret = vec![0xffu8; 32];
+        secret.extend(agree(&signed_pre_key.private_key, alice_identity).unwrap());
+        secret.extend(agree(&identity.private_key, base_key).unwrap());
+        secret.extend(agree(&signed_pre_key.private_key, base_key).unwrap());
+        secret.extend(agree(&pre_key.private_key, base_key).unwrap());
+        secret
+    }
+
+    #[test]
+    fn test_x3dh_matches_responder() {
+        let alice = generate_key_pair().unwrap();
+        let base = generate_key_pair().unwrap();
+        let (identity, signed_pre_key, pre_key) = (generate_key_pair().unwrap(), generate_key_pair().unwrap(), generate_key_pair().unwrap());
+        let bundle = PreKeyBundle {
+            device: "628111:2@s.whatsapp.net".to_string(),
+            registration_id: 7,
+            identity_key: identity.public_key.clone(),
+            signed_pre_key_id: 1,
+            signed_pre_key: signed_pre_key.public_key.clone(),
+            signed_pre_key_signature: vec![0; 64],
+            pre_key: Some((9, pre_key.public_key.clone())),
+        };
+        assert_eq!(
+            x3dh_secret(&alice, &base, &bundle).unwrap(),
+            responder_secret(&identity, &signed_pre_key, &pre_key, &serialize_key(&alice.public_key), &base.public_key)
+        );
+
+        // Tanda tangan palsu ditolak sebelum sesi dibuat
+        let mut local = Session::new();
+        local.identity_key_pair = alice;
+        let mut store = SignalStore::new();
+        assert!(store.process_bundle(&local, &bundle).is_err());
+        assert!(!store.has_session(&bundle.device));
+    }
+
+    #[test]
+    fn test_receiver_opens_pre_key_messages() {
+        let alice = Session::new();
+        let mut bob = Session::new();
+        let pre_key_id = bob.add_one_time_key().unwrap();
+        let bundle = PreKeyBundle {
+            device: "628222@s.whatsapp.net".to_string(),
+            registration_id: bob.registration_id,
+            identity_key: bob.identity_key_pair.public_key.clone(),
+            signed_pre_key_id: bob.signed_pre_key.key_id,
+            signed_pre_key: bob.signed_pre_key.public_key.clone(),
+            signed_pre_key_signature: bob.signed_pre_key.signature.clone(),
+            pre_key: Some((pre_key_id, bob.one_time_keys[&pre_key_id].public_key.clone())),
+        };
+        let mut sender = SignalStore::new();
+        sender.process_bundle(&alice, &bundle).unwrap();
+        let (kind, first) = sender.encrypt(&alice, &bundle.device, b"halo").unwrap();
+        let (_, second) = sender.encrypt(&alice, &bundle.device, b"apa kabar").unwrap();
+        assert_eq!(kind, EncryptedType::PreKey);
+
+        let mut receiver = SignalStore::new();
+        let from = "628111@s.whatsapp.net";
+        assert_eq!(receiver.decrypt(&mut bob, from, kind, &first).unwrap(), b"halo");
+        assert!(!bob.one_time_keys.contains_key(&pre_key_id));
+        // Kunci pesan tidak bisa dipakai dua kali
+        assert!(receiver.decrypt(&mut bob, from, kind, &first).is_err());
+
+        // MAC rusak ditolak tanpa memajukan chain
+        let mut tampered = second.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(receiver.decrypt(&mut bob, from, kind, &tampered).is_err());
+        assert_eq!(receiver.decrypt(&mut bob, from, kind, &second).unwrap(), b"apa kabar");
+    }
+
+    #[test]
+    fn test_signature_roundtrip() {
+        let identity = generate_key_pair().unwrap();
+        let signed_pre_key = generate_key_pair().unwrap();
+        let signature = sign_pre_key(&identity, &signed_pre_key.public_key).unwrap();
+        assert!(verify_signature(&identity.public_key, &serialize_key(&signed_pre_key.public_key), &signature));
+        assert!(!verify_signature(&identity.public_key, &serialize_key(&identity.public_key), &signature));
+        assert!(!verify_signature(&signed_pre_key.public_key, &serialize_key(&signed_pre_key.public_key), &signature));
+    }
+
+    #[test]
+    fn test_edwards_conversion_and_bundle_parsing() {
+        // Titik dasar Curve25519 (u = 9) adalah titik dasar Ed25519
+        let mut u = [0u8; 32];
+        u[0] = 9;
+        let mut base = [0x66u8; 32];
+        base[0] = 0x58;
+        assert_eq!(montgomery_to_edwards(&u).unwrap(), base);
+
+        let user = Node::new("user").attr("jid", "628111@s.whatsapp.net").children(vec![
+            Node::new("registration").bytes(vec![0, 0, 1, 2]),
+            Node::new("identity").bytes(vec![1; 32]),
+            Node::new("skey").children(vec![
+                Node::new("id").bytes(vec![0, 0, 3]),
+                Node::new("value").bytes(vec![2; 32]),
+                Node::new("signature").bytes(vec![3; 64]),
+            ]),
+        ]);
+        let bundle = parse_bundle(&user).unwrap();
+        assert_eq!(bundle.registration_id, 258);
+        assert_eq!(bundle.signed_pre_key_id, 3);
+        assert_eq!(bundle.pre_key, None);
+        assert_eq!(parse_bundle(&Node::new("user").attr("jid", "628111@s.whatsapp.net").children(vec![Node::new("error")])), None);
+    }
+}
@@ -0,0 +1,233 @@
+//! Status (stories)
+//!
+//! Status dikirim ke `status@broadcast` dan di-fanout ke daftar privasi yang
+//! diberikan pemanggil lewat stanza yang sama dengan daftar siaran (lihat
+//! `broadcast`). Kunci penerima yang belum ada diminta lewat `PendingOutbox`
+//! dan status dikirim setelah semua kunci tersedia.
+//!
+//! Audiens default status (`StatusAudience`) disimpan di server dan berlaku
+//! di semua perangkat; `send_status_to_audience` memakai audiens itu untuk
+//! menyusun daftar penerima dari kontak yang diketahui client.
+
+use chrono::Utc;
+
+use crate::errors::*;
+use crate::messages;
+use crate::node_protocol::Node;
+use crate::{initial_sync, iq, media_validation, utils, Jid, MediaType, WhatsAppClient};
+
+/// JID broadcast untuk status
+pub const STATUS_BROADCAST: &str = "status@broadcast";
+
+/// Warna teks default untuk status teks (putih, ARGB)
+pub const DEFAULT_TEXT_ARGB: u32 = 0xFFFF_FFFF;
+
+/// Isi status
+#[derive(Debug, Clone)]
+pub enum StatusContent {
+    Text {
+        text: String,
+        text_argb: u32,
+        background_argb: u32,
+        font: Option<u32>,
+    },
+    Image { data: Vec<u8>, caption: Option<String> },
+    Video { data: Vec<u8>, caption: Option<String> },
+}
+
+impl StatusContent {
+    /// Status teks putih di atas `background_argb`
+    pub fn text(text: &str, background_argb: u32) -> Self {
+        StatusContent::Text {
+            text: text.to_string(),
+            text_argb: DEFAULT_TEXT_ARGB,
+            background_argb,
+            font: None,
+        }
+    }
+}
+
+/// Siapa yang melihat status
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatusAudience {
+    /// Semua kontak
+    Contacts,
+    /// Semua kontak kecuali daftar ini
+    ContactsExcept(Vec<Jid>),
+    /// Hanya daftar ini
+    OnlyShareWith(Vec<Jid>),
+}
+
+impl StatusAudience {
+    fn list_type(&self) -> &'static str {
+        match self {
+            StatusAudience::Contacts => "contacts",
+            StatusAudience::ContactsExcept(_) => "blacklist",
+            StatusAudience::OnlyShareWith(_) => "whitelist",
+        }
+    }
+
+    fn users(&self) -> &[Jid] {
+        match self {
+            StatusAudience::Contacts => &[],
+            StatusAudience::ContactsExcept(users) | StatusAudience::OnlyShareWith(users) => users,
+        }
+    }
+
+    /// Penerima status dari `contacts` sesuai audiens
+    pub fn recipients(&self, contacts: &[Jid]) -> Vec<Jid> {
+        match self {
+            StatusAudience::Contacts => contacts.to_vec(),
+            StatusAudience::ContactsExcept(excluded) => contacts.iter().filter(|jid| !excluded.contains(jid)).cloned().collect(),
+            StatusAudience::OnlyShareWith(users) => users.clone(),
+        }
+    }
+}
+
+fn privacy_iq(iq_type: &str, children: Vec<Node>) -> Node {
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "status")
+        .attr("type", iq_type)
+        .attr("to", "s.whatsapp.net")
+        .children(vec![Node::new("privacy").children(children)])
+}
+
+/// IQ untuk mengganti audiens status
+pub fn set_privacy_node(audience: &StatusAudience) -> Node {
+    let users = audience.users().iter().map(|jid| Node::new("user").attr("jid", &jid.to_string())).collect();
+    privacy_iq("set", vec![Node::new("list").attr("type", audience.list_type()).children(users)])
+}
+
+/// Membaca `<privacy><list type [default]><user jid/>...</list>...</privacy>`.
+/// Server mengirim semua daftar; yang dipakai adalah yang bertanda `default`.
+pub fn parse_privacy(response: &Node) -> Result<StatusAudience> {
+    let privacy = response.get_child("privacy").ok_or("Status privacy response without privacy node")?;
+    let lists = privacy.get_children();
+    let list = lists
+        .iter()
+        .filter(|child| child.tag == "list")
+        .find(|list| list.get_attr("default") == Some("true"))
+        .or_else(|| lists.iter().find(|child| child.tag == "list"))
+        .ok_or("Status privacy response without list")?;
+    let users = list
+        .get_children()
+        .iter()
+        .filter_map(|user| user.get_attr("jid").and_then(|jid| Jid::from_string(jid).ok()))
+        .collect();
+    match list.get_attr("type") {
+        Some("contacts") => Ok(StatusAudience::Contacts),
+        Some("blacklist") => Ok(StatusAudience::ContactsExcept(users)),
+        Some("whitelist") => Ok(StatusAudience::OnlyShareWith(users)),
+        other => Err(Error { kind: ErrorKind::ProtocolError(format!("Unknown status privacy list {:?}", other)) }),
+    }
+}
+
+impl WhatsAppClient {
+    /// Audiens status yang tersimpan di server
+    pub fn status_privacy(&self) -> Result<StatusAudience> {
+        let response = self.query(&privacy_iq("get", Vec::new()), iq::DEFAULT_QUERY_TIMEOUT)?;
+        parse_privacy(&response)
+    }
+
+    /// Mengganti audiens status untuk semua perangkat
+    pub fn set_status_privacy(&self, audience: &StatusAudience) -> Result<()> {
+        if audience.users().iter().any(|jid| jid.is_group()) {
+            return Err("Status privacy lists must contain contacts, not groups".into());
+        }
+        self.query(&set_privacy_node(audience), iq::DEFAULT_QUERY_TIMEOUT).map(|_| ())
+    }
+
+    /// Memposting status ke `audience`, atau ke audiens tersimpan jika `None`.
+    /// Penerima diambil dari daftar kontak yang sudah tersinkron.
+    pub fn send_status_to_audience(&self, content: StatusContent, audience: Option<&StatusAudience>) -> Result<String> {
+        let audience = match audience {
+            Some(audience) => audience.clone(),
+            None => self.status_privacy()?,
+        };
+        let contacts: Vec<Jid> = {
+            let app_state = self.app_state.lock().unwrap();
+            let names = self.contacts.lock().unwrap();
+            initial_sync::contacts_from_app_state(&app_state, &names).into_iter().map(|contact| contact.jid).collect()
+        };
+        self.send_status(content, &audience.recipients(&contacts))
+    }
+
+    /// Memposting status yang hanya terlihat oleh `privacy_list`. Status
+    /// gambar/video membutuhkan `MediaUploader` (lihat `set_media_uploader`).
+    pub fn send_status(&self, content: StatusContent, privacy_list: &[Jid]) -> Result<String> {
+        let message = match content {
+            StatusContent::Text { text, text_argb, background_argb, font } => messages::Message {
+                extended_text_message: Some(messages::ExtendedTextMessage {
+                    text,
+                    text_argb: Some(text_argb),
+                    background_argb: Some(background_argb),
+                    font,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            StatusContent::Image { data, caption } => {
+                let (mimetype, dimensions) = media_validation::validate_image(&data)?;
+                let (width, height) = dimensions.unwrap_or_default();
+                let (media, uploaded) = self.upload_media(&data, MediaType::Image)?;
+                messages::Message {
+                    image_message: Some(messages::ImageMessage {
+                        url: uploaded.url,
+                        direct_path: uploaded.direct_path,
+                        mimetype: Some(mimetype.to_string()),
+                        caption,
+                        width,
+                        height,
+                        file_sha256: media.file_sha256,
+                        file_enc_sha256: media.file_enc_sha256,
+                        file_length: media.file_length,
+                        media_key: media.media_key,
+                        media_key_timestamp: Utc::now().timestamp(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }
+            }
+            StatusContent::Video { data, caption } => {
+                let (media, uploaded) = self.upload_media(&data, MediaType::Video)?;
+                messages::Message {
+                    video_message: Some(messages::VideoMessage {
+                        url: uploaded.url,
+                        direct_path: uploaded.direct_path,
+                        mimetype: "video/mp4".to_string(),
+                        caption,
+                        file_sha256: media.file_sha256,
+                        file_enc_sha256: media.file_enc_sha256,
+                        file_length: media.file_length,
+                        media_key: media.media_key,
+                        media_key_timestamp: Utc::now().timestamp(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }
+            }
+        };
+
+        self.send_fanout(STATUS_BROADCAST, privacy_list, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_privacy_uses_default_list() {
+        let response = Node::new("iq").children(vec![Node::new("privacy").children(vec![
+            Node::new("list").attr("type", "whitelist").children(vec![Node::new("user").attr("jid", "628111@s.whatsapp.net")]),
+            Node::new("list").attr("type", "blacklist").attr("default", "true").children(vec![Node::new("user").attr("jid", "628222@s.whatsapp.net")]),
+        ])]);
+        let excluded = Jid::from_string("628222@s.whatsapp.net").unwrap();
+        let audience = parse_privacy(&response).unwrap();
+        assert_eq!(audience, StatusAudience::ContactsExcept(vec![excluded.clone()]));
+
+        let friend = Jid::from_string("628333@s.whatsapp.net").unwrap();
+        assert_eq!(audience.recipients(&[excluded, friend.clone()]), vec![friend]);
+    }
+}
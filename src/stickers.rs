@@ -0,0 +1,66 @@
+//! Pengiriman stiker dan stiker favorit yang tersinkron ke semua perangkat
+
+use crate::app_state::{Collection, Mutation};
+use crate::errors::*;
+use crate::messages::{self, StickerMessage};
+use crate::{media_validation, Jid, MediaType, WhatsAppClient};
+
+/// Nama aksi app state untuk stiker favorit
+pub const FAVORITE_STICKER_ACTION: &str = "favoriteSticker";
+
+/// Index mutasi: stiker diidentifikasi oleh hash filenya
+fn sticker_index(sticker: &StickerMessage) -> Result<Vec<String>> {
+    if sticker.file_sha256.is_empty() {
+        return Err("Sticker has no file hash".into());
+    }
+    Ok(vec![FAVORITE_STICKER_ACTION.to_string(), base64::encode(&sticker.file_sha256)])
+}
+
+impl WhatsAppClient {
+    /// Mengirim stiker WebP 512x512.
+    /// Membutuhkan `MediaUploader` (lihat `set_media_uploader`).
+    pub fn send_sticker(&self, to: &Jid, webp: &[u8]) -> Result<String> {
+        let (width, height) = media_validation::validate_sticker(webp)?;
+        let (media, uploaded) = self.upload_media(webp, MediaType::Sticker)?;
+
+        let message = messages::Message {
+            sticker_message: Some(StickerMessage {
+                url: uploaded.url,
+                direct_path: uploaded.direct_path,
+                mimetype: "image/webp".to_string(),
+                width,
+                height,
+                file_sha256: media.file_sha256,
+                file_enc_sha256: media.file_enc_sha256,
+                file_length: media.file_length,
+                media_key: media.media_key,
+                media_key_timestamp: chrono::Utc::now().timestamp(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        self.send_message(to, message)
+    }
+
+    /// Menyimpan stiker ke favorit sehingga muncul di semua perangkat
+    pub fn save_sticker(&self, sticker: &StickerMessage) -> Result<()> {
+        let value = serde_json::to_value(sticker).map_err(|e| Error { kind: ErrorKind::InvalidFormat(e.to_string()) })?;
+        self.push_app_state(Collection::RegularLow, vec![Mutation::set(sticker_index(sticker)?, value)])
+    }
+
+    /// Menghapus stiker dari favorit
+    pub fn unsave_sticker(&self, sticker: &StickerMessage) -> Result<()> {
+        self.push_app_state(Collection::RegularLow, vec![Mutation::remove(sticker_index(sticker)?)])
+    }
+
+    /// Daftar stiker favorit yang diketahui client, terbaru lebih dulu
+    pub fn list_saved_stickers(&self) -> Vec<StickerMessage> {
+        let app_state = self.app_state.lock().unwrap();
+        let mut entries = app_state.entries(Collection::RegularLow, FAVORITE_STICKER_ACTION);
+        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        entries
+            .into_iter()
+            .filter_map(|mutation| serde_json::from_value(mutation.value.clone()).ok())
+            .collect()
+    }
+}
@@ -0,0 +1,195 @@
+//! Penyimpanan state key-value untuk data yang harus bertahan antar restart
+//!
+//! `StateStore` sengaja dibuat sederhana (key string, value bytes) agar mudah
+//! diimplementasikan di atas Redis, database, atau sistem berkas. Data terstruktur
+//! disimpan sebagai JSON lewat `load_json`/`save_json`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::errors::*;
+
+/// Backend penyimpanan state
+pub trait StateStore: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    fn set(&self, key: &str, value: &[u8]) -> Result<()>;
+    fn remove(&self, key: &str) -> Result<()>;
+    /// Semua key yang diawali `prefix`
+    fn keys(&self, prefix: &str) -> Result<Vec<String>>;
+
+    /// Mengganti value `key` dengan `value` (`None` menghapus) hanya jika value
+    /// saat ini sama dengan `expected`. Mengembalikan false jika tidak cocok.
+    ///
+    /// Implementasi bawaan tidak atomik; backend yang dipakai bersama beberapa
+    /// proses (mis. untuk `handover`) harus menggantinya dengan operasi atomik
+    /// (mis. `WATCH`/`MULTI` di Redis atau transaksi database).
+    fn compare_and_swap(&self, key: &str, expected: Option<&[u8]>, value: Option<&[u8]>) -> Result<bool> {
+        if self.get(key)?.as_deref() != expected {
+            return Ok(false);
+        }
+        match value {
+            Some(value) => self.set(key, value)?,
+            None => self.remove(key)?,
+        }
+        Ok(true)
+    }
+}
+
+/// Membaca value JSON dari store
+pub fn load_json<T: DeserializeOwned>(store: &dyn StateStore, key: &str) -> Result<Option<T>> {
+    match store.get(key)? {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| Error { kind: ErrorKind::InvalidFormat(format!("{}: {}", key, e)) }),
+        None => Ok(None),
+    }
+}
+
+/// Menyimpan value sebagai JSON ke store
+pub fn save_json<T: Serialize>(store: &dyn StateStore, key: &str, value: &T) -> Result<()> {
+    let bytes = serde_json::to_vec(value).map_err(|e| Error { kind: ErrorKind::InvalidFormat(format!("{}: {}", key, e)) })?;
+    store.set(key, &bytes)
+}
+
+/// Store di memori, hilang saat proses berhenti
+#[derive(Default)]
+pub struct MemoryStateStore {
+    entries: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStateStore {
+    pub fn new() -> Self {
+        MemoryStateStore::default()
+    }
+}
+
+impl StateStore for MemoryStateStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &[u8]) -> Result<()> {
+        self.entries.lock().unwrap().insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn keys(&self, prefix: &str) -> Result<Vec<String>> {
+        Ok(self.entries.lock().unwrap().keys().filter(|key| key.starts_with(prefix)).cloned().collect())
+    }
+
+    fn compare_and_swap(&self, key: &str, expected: Option<&[u8]>, value: Option<&[u8]>) -> Result<bool> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.get(key).map(|current| current.as_slice()) != expected {
+            return Ok(false);
+        }
+        match value {
+            Some(value) => entries.insert(key.to_string(), value.to_vec()),
+            None => entries.remove(key),
+        };
+        Ok(true)
+    }
+}
+
+/// Store berbasis satu berkas JSON (value di-encode base64).
+/// Berkas ditulis ulang secara atomik (tulis ke berkas sementara lalu rename) setiap perubahan.
+pub struct FileStateStore {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, String>>,
+}
+
+impl FileStateStore {
+    /// Membuka store; berkas dibuat saat penulisan pertama jika belum ada
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = if path.exists() {
+            let data = fs::read(&path)?;
+            serde_json::from_slice(&data).map_err(|e| Error { kind: ErrorKind::InvalidFormat(e.to_string()) })?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(FileStateStore {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    fn flush(&self, entries: &HashMap<String, String>) -> Result<()> {
+        let data = serde_json::to_vec_pretty(entries).map_err(|e| Error { kind: ErrorKind::InvalidFormat(e.to_string()) })?;
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+impl StateStore for FileStateStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self.entries.lock().unwrap().get(key) {
+            Some(value) => Ok(Some(base64::decode(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn set(&self, key: &str, value: &[u8]) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key.to_string(), base64::encode(value));
+        self.flush(&entries)
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.remove(key).is_some() {
+            self.flush(&entries)?;
+        }
+        Ok(())
+    }
+
+    fn keys(&self, prefix: &str) -> Result<Vec<String>> {
+        Ok(self.entries.lock().unwrap().keys().filter(|key| key.starts_with(prefix)).cloned().collect())
+    }
+
+    fn compare_and_swap(&self, key: &str, expected: Option<&[u8]>, value: Option<&[u8]>) -> Result<bool> {
+        let mut entries = self.entries.lock().unwrap();
+        let current = entries.get(key).map(|value| base64::decode(value)).transpose()?;
+        if current.as_deref() != expected {
+            return Ok(false);
+        }
+        match value {
+            Some(value) => entries.insert(key.to_string(), base64::encode(value)),
+            None => entries.remove(key),
+        };
+        self.flush(&entries)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_store_persists() {
+        let path = std::env::temp_dir().join(format!("rustdi-store-{}.json", std::process::id()));
+        {
+            let store = FileStateStore::open(&path).unwrap();
+            save_json(&store, "bot:owner", &"628123@s.whatsapp.net".to_string()).unwrap();
+        }
+
+        let store = FileStateStore::open(&path).unwrap();
+        let owner: Option<String> = load_json(&store, "bot:owner").unwrap();
+        assert_eq!(owner.as_deref(), Some("628123@s.whatsapp.net"));
+        assert_eq!(store.keys("bot:").unwrap(), vec!["bot:owner".to_string()]);
+        fs::remove_file(&path).ok();
+    }
+}
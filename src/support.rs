@@ -0,0 +1,239 @@
+//! Bundle diagnostik untuk laporan bug
+//!
+//! `WhatsAppClient::generate_support_bundle` menulis satu arsip tar berisi:
+//! - `version.json`: versi crate, versi WhatsApp Web, OS dan arsitektur
+//! - `session.json`: metadata sesi dan status client, tanpa kunci atau token
+//! - `stats.json`: statistik latensi dan antrean event
+//! - `log.txt`: event error/koneksi terakhir
+//! - `captures.txt`: ringkasan node masuk terakhir, hanya jika diaktifkan
+//!   lewat `WhatsAppClientBuilder::with_protocol_capture`
+//!
+//! Nomor telepon di log dan capture disamarkan dan isi node (bytes) tidak
+//! pernah disimpan, jadi bundle aman dilampirkan ke issue publik.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::{app_state, Event, WhatsAppClient, WEB_VERSION};
+
+/// Jumlah baris log yang disimpan
+pub const LOG_CAPACITY: usize = 200;
+
+/// Log event terakhir dan capture protokol (opsional)
+pub struct Diagnostics {
+    log: VecDeque<String>,
+    captures: VecDeque<String>,
+    capture_capacity: usize,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Diagnostics {
+            log: VecDeque::new(),
+            captures: VecDeque::new(),
+            capture_capacity: 0,
+        }
+    }
+}
+
+fn push_bounded(lines: &mut VecDeque<String>, line: String, capacity: usize) {
+    lines.push_back(line);
+    while lines.len() > capacity {
+        lines.pop_front();
+    }
+}
+
+fn timestamped(line: &str) -> String {
+    format!("{} {}", chrono::Utc::now().to_rfc3339(), redact(line))
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Diagnostics::default()
+    }
+
+    /// Menyimpan `capacity` node masuk terakhir; 0 menonaktifkan capture
+    pub fn set_capture_capacity(&mut self, capacity: usize) {
+        self.capture_capacity = capacity;
+        while self.captures.len() > capacity {
+            self.captures.pop_front();
+        }
+    }
+
+    /// Mencatat event yang berguna untuk diagnosis; event lain diabaikan
+    pub fn record_event(&mut self, event: &Event) {
+        let line = match event {
+            Event::Connected | Event::Disconnected(_) | Event::Authenticating | Event::Authenticated => format!("{:?}", event),
+            Event::Error(message) => format!("error: {}", message),
+            Event::InternalError { context, backtrace } => format!("internal error: {}\n{}", context, backtrace),
+            _ => return,
+        };
+        push_bounded(&mut self.log, timestamped(&line), LOG_CAPACITY);
+    }
+
+    pub fn capture(&mut self, node: &Node) {
+        if self.capture_capacity > 0 {
+            push_bounded(&mut self.captures, timestamped(&node_summary(node)), self.capture_capacity);
+        }
+    }
+}
+
+/// Menyamarkan deretan 7 digit atau lebih (nomor telepon), menyisakan 2
+/// digit awal dan akhir
+pub fn redact(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut digits = String::new();
+    let flush = |digits: &mut String, out: &mut String| {
+        if digits.len() >= 7 {
+            out.push_str(&digits[..2]);
+            out.push_str(&"*".repeat(digits.len() - 4));
+            out.push_str(&digits[digits.len() - 2..]);
+        } else {
+            out.push_str(digits);
+        }
+        digits.clear();
+    };
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+        } else {
+            flush(&mut digits, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut digits, &mut out);
+    out
+}
+
+/// `<tag attr="value"> children=[..] bytes=N` tanpa isi bytes
+fn node_summary(node: &Node) -> String {
+    let mut summary = format!("<{}", node.tag);
+    let mut attrs: Vec<_> = node.attrs.iter().collect();
+    attrs.sort();
+    for (key, value) in attrs {
+        summary.push_str(&format!(" {}=\"{}\"", key, value));
+    }
+    summary.push('>');
+    let children: Vec<&str> = node.get_children().iter().map(|child| child.tag.as_str()).collect();
+    if !children.is_empty() {
+        summary.push_str(&format!(" children=[{}]", children.join(",")));
+    }
+    if let Some(bytes) = node.get_bytes() {
+        summary.push_str(&format!(" bytes={}", bytes.len()));
+    }
+    summary
+}
+
+/// Menambahkan satu berkas ke arsip tar (format ustar)
+fn tar_entry(archive: &mut Vec<u8>, name: &str, data: &[u8]) {
+    let mut header = [0u8; 512];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..107].copy_from_slice(b"0000644");
+    header[108..115].copy_from_slice(b"0000000");
+    header[116..123].copy_from_slice(b"0000000");
+    header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+    header[136..147].copy_from_slice(format!("{:011o}", chrono::Utc::now().timestamp()).as_bytes());
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // Checksum dihitung dengan field checksum berisi spasi
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+    header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+
+    archive.extend_from_slice(&header);
+    archive.extend_from_slice(data);
+    archive.resize(archive.len().div_ceil(512) * 512, 0);
+}
+
+fn to_json(value: &serde_json::Value) -> Vec<u8> {
+    serde_json::to_vec_pretty(value).unwrap_or_default()
+}
+
+impl WhatsAppClient {
+    /// Menulis bundle diagnostik (arsip tar) ke `path`
+    pub fn generate_support_bundle<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let version = serde_json::json!({
+            "crate": env!("CARGO_PKG_VERSION"),
+            "whatsapp_web": WEB_VERSION,
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+        });
+
+        let session = {
+            let session = self.session.lock().unwrap();
+            let app_state = self.app_state.lock().unwrap();
+            let versions: serde_json::Map<String, serde_json::Value> = app_state::ALL_COLLECTIONS
+                .iter()
+                .map(|collection| (collection.name().to_string(), app_state.version(*collection).into()))
+                .collect();
+            serde_json::json!({
+                "state": format!("{:?}", self.get_state()),
+                "logged_in": session.is_some(),
+                "own_jid": self.get_own_jid().map(|jid| redact(&jid.to_string())),
+                "paired_at": session.as_ref().and_then(|session| session.paired_at),
+                "phone_connected": self.phone_connected(),
+                "two_step_enabled": self.two_step_enabled(),
+                "app_state_versions": versions,
+                "warmup": self.warmup_status().map(|status| format!("{:?}", status)),
+            })
+        };
+
+        let stats = serde_json::json!({
+            "latency": format!("{:?}", self.latency_stats()),
+            "event_queue": self.event_tx.queue_len(),
+        });
+
+        let (log, captures) = {
+            let diagnostics = self.event_tx.diagnostics.lock().unwrap();
+            let join = |lines: &VecDeque<String>| lines.iter().map(|line| format!("{}\n", line)).collect::<String>();
+            (join(&diagnostics.log), (diagnostics.capture_capacity > 0).then(|| join(&diagnostics.captures)))
+        };
+
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "version.json", &to_json(&version));
+        tar_entry(&mut archive, "session.json", &to_json(&session));
+        tar_entry(&mut archive, "stats.json", &to_json(&stats));
+        tar_entry(&mut archive, "log.txt", log.as_bytes());
+        if let Some(captures) = captures {
+            tar_entry(&mut archive, "captures.txt", captures.as_bytes());
+        }
+        archive.extend_from_slice(&[0u8; 1024]);
+
+        fs::write(path, archive)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_phone_numbers_and_payloads() {
+        assert_eq!(redact("send to 628123456789@s.whatsapp.net failed (code 599)"), "send to 62********89@s.whatsapp.net failed (code 599)");
+
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.capture(&Node::new("message"));
+        assert!(diagnostics.captures.is_empty());
+
+        diagnostics.set_capture_capacity(1);
+        diagnostics.capture(&Node::new("message").attr("from", "628123456789@s.whatsapp.net").bytes(b"rahasia".to_vec()));
+        let capture = diagnostics.captures.back().unwrap();
+        assert!(capture.ends_with("<message from=\"62********89@s.whatsapp.net\"> bytes=7"));
+        assert!(!capture.contains("rahasia"));
+    }
+
+    #[test]
+    fn test_tar_entries_are_block_aligned() {
+        let mut archive = Vec::new();
+        tar_entry(&mut archive, "log.txt", b"halo");
+        assert_eq!(archive.len(), 1024);
+        assert_eq!(&archive[..7], b"log.txt");
+        assert_eq!(&archive[512..516], b"halo");
+    }
+}
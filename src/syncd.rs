@@ -0,0 +1,310 @@
+//! Kriptografi app state (syncd)
+//!
+//! Setiap mutasi app state dienkripsi dengan kunci sinkronisasi yang dibagikan
+//! ponsel (`appStateSyncKeyShare`). Dari satu kunci diturunkan lima kunci
+//! (HKDF "WhatsApp Mutation Keys"):
+//! - index key: HMAC-SHA256 atas index JSON, dipakai server sebagai id entry
+//! - value encryption key: AES-256-CBC atas isi mutasi
+//! - value MAC key: HMAC-SHA512 (dipotong 32 byte) atas iv + ciphertext
+//! - snapshot MAC key dan patch MAC key
+//!
+//! Integritas seluruh koleksi dijaga LT-hash: jumlah (per elemen u16) dari
+//! ekspansi HKDF setiap value MAC yang aktif. Snapshot MAC mengikat LT-hash ke
+//! versi dan nama koleksi; patch MAC mengikat snapshot MAC ke value MAC
+//! semua mutasi dalam patch.
+
+use std::collections::HashMap;
+
+use openssl::symm::{decrypt, encrypt, Cipher};
+use ring::rand::SecureRandom;
+use ring::{hkdf, hmac, rand};
+use serde::{Deserialize, Serialize};
+
+use crate::app_state::{Mutation, MutationOperation};
+use crate::errors::*;
+
+/// Info HKDF untuk menurunkan kunci mutasi
+const MUTATION_KEYS_INFO: &[u8] = b"WhatsApp Mutation Keys";
+
+/// Info HKDF untuk ekspansi value MAC ke LT-hash
+const LT_HASH_INFO: &[u8] = b"WhatsApp Patch Integrity";
+
+/// Ukuran LT-hash (64 elemen u16)
+pub const LT_HASH_SIZE: usize = 128;
+
+/// Panjang value MAC di akhir value blob
+pub const VALUE_MAC_LENGTH: usize = 32;
+
+const IV_LENGTH: usize = 16;
+
+struct OutputLength(usize);
+
+impl hkdf::KeyType for OutputLength {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+fn crypto_error(msg: &str) -> Error {
+    Error { kind: ErrorKind::CryptoError(msg.to_string()) }
+}
+
+fn hkdf_expand(ikm: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>> {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &[]).extract(ikm);
+    let info = [info];
+    let okm = prk.expand(&info, OutputLength(len)).map_err(|_| crypto_error("HKDF expand failed"))?;
+    let mut expanded = vec![0u8; len];
+    okm.fill(&mut expanded).map_err(|_| crypto_error("HKDF fill failed"))?;
+    Ok(expanded)
+}
+
+/// Kunci turunan dari satu kunci sinkronisasi
+pub struct MutationKeys {
+    index: Vec<u8>,
+    value_encryption: Vec<u8>,
+    value_mac: Vec<u8>,
+    snapshot_mac: Vec<u8>,
+    patch_mac: Vec<u8>,
+}
+
+impl MutationKeys {
+    pub fn expand(key_data: &[u8]) -> Result<Self> {
+        let expanded = hkdf_expand(key_data, MUTATION_KEYS_INFO, 160)?;
+        Ok(MutationKeys {
+            index: expanded[..32].to_vec(),
+            value_encryption: expanded[32..64].to_vec(),
+            value_mac: expanded[64..96].to_vec(),
+            snapshot_mac: expanded[96..128].to_vec(),
+            patch_mac: expanded[128..160].to_vec(),
+        })
+    }
+}
+
+/// LT-hash koleksi
+#[derive(Debug, Clone, PartialEq)]
+pub struct LtHash(Vec<u8>);
+
+impl Default for LtHash {
+    fn default() -> Self {
+        LtHash(vec![0u8; LT_HASH_SIZE])
+    }
+}
+
+impl LtHash {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn add(&mut self, value_mac: &[u8]) -> Result<()> {
+        self.combine(value_mac, u16::wrapping_add)
+    }
+
+    pub fn subtract(&mut self, value_mac: &[u8]) -> Result<()> {
+        self.combine(value_mac, u16::wrapping_sub)
+    }
+
+    fn combine(&mut self, value_mac: &[u8], op: fn(u16, u16) -> u16) -> Result<()> {
+        let point = hkdf_expand(value_mac, LT_HASH_INFO, LT_HASH_SIZE)?;
+        for (current, point) in self.0.chunks_exact_mut(2).zip(point.chunks_exact(2)) {
+            let sum = op(u16::from_le_bytes([current[0], current[1]]), u16::from_le_bytes([point[0], point[1]]));
+            current.copy_from_slice(&sum.to_le_bytes());
+        }
+        Ok(())
+    }
+}
+
+/// Satu entry terenkripsi: index MAC dan iv || ciphertext || value MAC
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncdRecord {
+    pub index_mac: Vec<u8>,
+    pub value_blob: Vec<u8>,
+    pub key_id: Vec<u8>,
+}
+
+impl SyncdRecord {
+    pub fn value_mac(&self) -> &[u8] {
+        &self.value_blob[self.value_blob.len().saturating_sub(VALUE_MAC_LENGTH)..]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncdMutation {
+    pub operation: MutationOperation,
+    pub record: SyncdRecord,
+}
+
+/// Patch terenkripsi seperti dikirim/diterima server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncdPatch {
+    pub version: u64,
+    pub mutations: Vec<SyncdMutation>,
+    pub snapshot_mac: Vec<u8>,
+    pub patch_mac: Vec<u8>,
+    pub key_id: Vec<u8>,
+}
+
+/// Snapshot terenkripsi berisi semua entry aktif koleksi
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncdSnapshot {
+    pub version: u64,
+    pub records: Vec<SyncdRecord>,
+    pub mac: Vec<u8>,
+    pub key_id: Vec<u8>,
+}
+
+fn operation_byte(operation: MutationOperation) -> u8 {
+    match operation {
+        MutationOperation::Set => 0x01,
+        MutationOperation::Remove => 0x02,
+    }
+}
+
+pub fn index_mac(keys: &MutationKeys, index: &[String]) -> Result<Vec<u8>> {
+    let index = serde_json::to_vec(index).map_err(|e| Error { kind: ErrorKind::InvalidFormat(e.to_string()) })?;
+    Ok(hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &keys.index), &index).as_ref().to_vec())
+}
+
+/// HMAC-SHA512 atas (operasi || key id) || iv || ciphertext || panjang (operasi || key id)
+pub fn value_mac(keys: &MutationKeys, operation: MutationOperation, key_id: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut ctx = hmac::Context::with_key(&hmac::Key::new(hmac::HMAC_SHA512, &keys.value_mac));
+    ctx.update(&[operation_byte(operation)]);
+    ctx.update(key_id);
+    ctx.update(data);
+    ctx.update(&((key_id.len() + 1) as u64).to_be_bytes());
+    ctx.sign().as_ref()[..VALUE_MAC_LENGTH].to_vec()
+}
+
+pub fn snapshot_mac(keys: &MutationKeys, lt_hash: &LtHash, version: u64, collection: &str) -> Vec<u8> {
+    let mut ctx = hmac::Context::with_key(&hmac::Key::new(hmac::HMAC_SHA256, &keys.snapshot_mac));
+    ctx.update(lt_hash.as_bytes());
+    ctx.update(&version.to_be_bytes());
+    ctx.update(collection.as_bytes());
+    ctx.sign().as_ref().to_vec()
+}
+
+pub fn patch_mac(keys: &MutationKeys, snapshot_mac: &[u8], value_macs: &[&[u8]], version: u64, collection: &str) -> Vec<u8> {
+    let mut ctx = hmac::Context::with_key(&hmac::Key::new(hmac::HMAC_SHA256, &keys.patch_mac));
+    ctx.update(snapshot_mac);
+    for value_mac in value_macs {
+        ctx.update(value_mac);
+    }
+    ctx.update(&version.to_be_bytes());
+    ctx.update(collection.as_bytes());
+    ctx.sign().as_ref().to_vec()
+}
+
+/// Membandingkan MAC dalam waktu konstan
+pub fn verify_mac(expected: &[u8], found: &[u8], what: &str) -> Result<()> {
+    if expected.len() == found.len() && openssl::memcmp::eq(expected, found) {
+        Ok(())
+    } else {
+        Err(crypto_error(&format!("App state {} MAC mismatch", what)))
+    }
+}
+
+pub fn encrypt_mutation(keys: &MutationKeys, key_id: &[u8], mutation: &Mutation) -> Result<SyncdMutation> {
+    let plaintext = serde_json::to_vec(mutation).map_err(|e| Error { kind: ErrorKind::InvalidFormat(e.to_string()) })?;
+    let mut iv = [0u8; IV_LENGTH];
+    rand::SystemRandom::new().fill(&mut iv).map_err(|_| crypto_error("Failed to generate IV"))?;
+    let ciphertext = encrypt(Cipher::aes_256_cbc(), &keys.value_encryption, Some(&iv), &plaintext)
+        .map_err(|e| crypto_error(&format!("Failed to encrypt mutation: {}", e)))?;
+
+    let mut value_blob = iv.to_vec();
+    value_blob.extend_from_slice(&ciphertext);
+    let mac = value_mac(keys, mutation.operation, key_id, &value_blob);
+    value_blob.extend_from_slice(&mac);
+
+    Ok(SyncdMutation {
+        operation: mutation.operation,
+        record: SyncdRecord {
+            index_mac: index_mac(keys, &mutation.index)?,
+            value_blob,
+            key_id: key_id.to_vec(),
+        },
+    })
+}
+
+/// Memverifikasi value MAC, mendekripsi, lalu memastikan index cocok dengan index MAC
+pub fn decrypt_record(keys: &MutationKeys, operation: MutationOperation, record: &SyncdRecord) -> Result<Mutation> {
+    if record.value_blob.len() < IV_LENGTH + VALUE_MAC_LENGTH {
+        return Err(crypto_error("App state value blob too short"));
+    }
+    let (data, mac) = record.value_blob.split_at(record.value_blob.len() - VALUE_MAC_LENGTH);
+    verify_mac(&value_mac(keys, operation, &record.key_id, data), mac, "value")?;
+
+    let (iv, ciphertext) = data.split_at(IV_LENGTH);
+    let plaintext = decrypt(Cipher::aes_256_cbc(), &keys.value_encryption, Some(iv), ciphertext)
+        .map_err(|e| crypto_error(&format!("Failed to decrypt mutation: {}", e)))?;
+    let mut mutation: Mutation = serde_json::from_slice(&plaintext)
+        .map_err(|e| Error { kind: ErrorKind::InvalidPayload(format!("Invalid app state mutation: {}", e)) })?;
+    verify_mac(&index_mac(keys, &mutation.index)?, &record.index_mac, "index")?;
+    mutation.operation = operation;
+    Ok(mutation)
+}
+
+/// LT-hash koleksi beserta value MAC aktif per index MAC, untuk mengurangi
+/// nilai lama saat entry ditimpa atau dihapus
+#[derive(Debug, Clone, Default)]
+pub struct CollectionHash {
+    lt_hash: LtHash,
+    value_macs: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl CollectionHash {
+    pub fn lt_hash(&self) -> &LtHash {
+        &self.lt_hash
+    }
+
+    /// State baru setelah `mutations`; state ini tidak diubah
+    pub fn updated(&self, mutations: &[SyncdMutation]) -> Result<CollectionHash> {
+        let mut next = self.clone();
+        for mutation in mutations {
+            let record = &mutation.record;
+            if let Some(previous) = next.value_macs.remove(&record.index_mac) {
+                next.lt_hash.subtract(&previous)?;
+            }
+            if mutation.operation == MutationOperation::Set {
+                next.lt_hash.add(record.value_mac())?;
+                next.value_macs.insert(record.index_mac.clone(), record.value_mac().to_vec());
+            }
+        }
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutation_roundtrip_and_tamper_detection() {
+        let keys = MutationKeys::expand(&[3u8; 32]).unwrap();
+        let mutation = Mutation::set(vec!["pin_v1".to_string(), "628111@s.whatsapp.net".to_string()], serde_json::json!({ "pinned": true }));
+
+        let encrypted = encrypt_mutation(&keys, b"key-1", &mutation).unwrap();
+        let decrypted = decrypt_record(&keys, MutationOperation::Set, &encrypted.record).unwrap();
+        assert_eq!(decrypted, mutation);
+
+        let mut tampered = encrypted.record.clone();
+        tampered.value_blob[IV_LENGTH] ^= 1;
+        assert!(decrypt_record(&keys, MutationOperation::Set, &tampered).is_err());
+        // Operasi berbeda menghasilkan value MAC berbeda
+        assert!(decrypt_record(&keys, MutationOperation::Remove, &encrypted.record).is_err());
+    }
+
+    #[test]
+    fn test_lt_hash_remove_restores_previous_state() {
+        let keys = MutationKeys::expand(&[3u8; 32]).unwrap();
+        let set_index = || vec!["archive".to_string(), "628111@s.whatsapp.net".to_string()];
+        let set = encrypt_mutation(&keys, b"key-1", &Mutation::set(set_index(), serde_json::json!({ "archived": true }))).unwrap();
+        let remove = encrypt_mutation(&keys, b"key-1", &Mutation::remove(set_index())).unwrap();
+
+        let empty = CollectionHash::default();
+        let with_entry = empty.updated(&[set]).unwrap();
+        assert_ne!(with_entry.lt_hash(), empty.lt_hash());
+        assert_eq!(with_entry.updated(&[remove]).unwrap().lt_hash(), empty.lt_hash());
+        // Menimpa entry yang sama tidak menumpuk nilai lama
+        let unarchive = encrypt_mutation(&keys, b"key-1", &Mutation::set(set_index(), serde_json::json!({ "archived": false }))).unwrap();
+        assert_eq!(with_entry.updated(&[unarchive.clone()]).unwrap().lt_hash(), empty.updated(&[unarchive]).unwrap().lt_hash());
+    }
+}
@@ -0,0 +1,160 @@
+//! Metrik kesehatan client untuk operator gateway
+//!
+//! Counter dihitung dari node yang lewat di koneksi (stanza `message` masuk
+//! dan relay keluar), jadi tidak perlu membungkus setiap pemanggilan API.
+//! `WhatsAppClient::metrics` mengembalikan snapshot `ClientMetrics`.
+//!
+//! Dengan feature `metrics`, nilai yang sama juga dilaporkan ke recorder
+//! crate `metrics` milik aplikasi (Prometheus, StatsD, dll.):
+//! - counter `rustdi_messages_sent_total`, `rustdi_messages_received_total`,
+//!   `rustdi_reconnects_total`, `rustdi_decryption_failures_total`,
+//!   `rustdi_malformed_stanzas_total`
+//! - gauge `rustdi_latency_seconds` (round-trip ping keepalive terakhir)
+//! - gauge `rustdi_event_queue`, `rustdi_offline_queue`, `rustdi_pending_keys`,
+//!   diperbarui setiap `WhatsAppClient::metrics` dipanggil
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::node_protocol::Node;
+use crate::wire_trace::Direction;
+use crate::WhatsAppClient;
+
+/// Snapshot metrik client
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientMetrics {
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    /// Koneksi ulang otomatis (keepalive atau thread koneksi di-restart)
+    pub reconnects: u64,
+    /// Stanza `message` masuk yang gagal didekripsi
+    pub decryption_failures: u64,
+    /// Stanza `message` masuk yang isinya bukan `WebMessageInfo` yang valid
+    pub malformed_stanzas: u64,
+    /// Round-trip ping keepalive terakhir
+    pub latency: Option<Duration>,
+    /// Event yang belum diambil lewat `poll_event`
+    pub event_queue: usize,
+    /// Pesan di antrean offline
+    pub offline_queue: usize,
+    /// Pesan yang menunggu kunci enkripsi penerima
+    pub pending_keys: usize,
+}
+
+/// Counter bersama antara event loop koneksi dan client
+#[derive(Default)]
+pub struct Counters {
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    reconnects: AtomicU64,
+    decryption_failures: AtomicU64,
+    malformed_stanzas: AtomicU64,
+    /// Mikrodetik; 0 berarti belum ada pengukuran
+    latency_micros: AtomicU64,
+}
+
+impl Counters {
+    pub fn new() -> Self {
+        Counters::default()
+    }
+
+    /// Menghitung stanza pesan yang masuk atau keluar
+    pub fn observe(&self, direction: Direction, node: &Node) {
+        match (direction, node.tag.as_str()) {
+            (Direction::Inbound, "message") => {
+                self.messages_received.fetch_add(1, Ordering::Relaxed);
+                #[cfg(feature = "metrics")]
+                ::metrics::counter!("rustdi_messages_received_total").increment(1);
+            }
+            (Direction::Outbound, "action") if node.get_attr("type") == Some("relay") => {
+                self.messages_sent.fetch_add(1, Ordering::Relaxed);
+                #[cfg(feature = "metrics")]
+                ::metrics::counter!("rustdi_messages_sent_total").increment(1);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn reconnected(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("rustdi_reconnects_total").increment(1);
+    }
+
+    pub fn decryption_failed(&self) {
+        self.decryption_failures.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("rustdi_decryption_failures_total").increment(1);
+    }
+
+    pub fn malformed_stanza(&self) {
+        self.malformed_stanzas.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!("rustdi_malformed_stanzas_total").increment(1);
+    }
+
+    pub fn set_latency(&self, latency: Duration) {
+        self.latency_micros.store((latency.as_micros() as u64).max(1), Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        ::metrics::gauge!("rustdi_latency_seconds").set(latency.as_secs_f64());
+    }
+
+    /// Snapshot counter; antrean diisi oleh pemanggil
+    pub fn snapshot(&self) -> ClientMetrics {
+        let latency = self.latency_micros.load(Ordering::Relaxed);
+        ClientMetrics {
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            decryption_failures: self.decryption_failures.load(Ordering::Relaxed),
+            malformed_stanzas: self.malformed_stanzas.load(Ordering::Relaxed),
+            latency: (latency > 0).then(|| Duration::from_micros(latency)),
+            ..ClientMetrics::default()
+        }
+    }
+}
+
+impl WhatsAppClient {
+    /// Snapshot metrik koneksi dan pengiriman
+    pub fn metrics(&self) -> ClientMetrics {
+        let mut metrics = self.event_tx.metrics.snapshot();
+        metrics.event_queue = self.event_tx.queue_len();
+        metrics.offline_queue = self.offline.lock().unwrap().len();
+        metrics.pending_keys = self.outbox.lock().unwrap().queued_len();
+        #[cfg(feature = "metrics")]
+        {
+            ::metrics::gauge!("rustdi_event_queue").set(metrics.event_queue as f64);
+            ::metrics::gauge!("rustdi_offline_queue").set(metrics.offline_queue as f64);
+            ::metrics::gauge!("rustdi_pending_keys").set(metrics.pending_keys as f64);
+        }
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_message_stanzas_only() {
+        let counters = Counters::new();
+        counters.observe(Direction::Inbound, &Node::new("message").attr("id", "m1"));
+        counters.observe(Direction::Inbound, &Node::new("receipt").attr("id", "m1"));
+        counters.observe(Direction::Outbound, &Node::new("action").attr("type", "relay"));
+        counters.observe(Direction::Outbound, &Node::new("action").attr("type", "set"));
+        counters.decryption_failed();
+        counters.malformed_stanza();
+        counters.malformed_stanza();
+        counters.reconnected();
+        assert_eq!(counters.snapshot().latency, None);
+        counters.set_latency(Duration::from_millis(120));
+
+        let snapshot = counters.snapshot();
+        assert_eq!(snapshot.messages_received, 1);
+        assert_eq!(snapshot.messages_sent, 1);
+        assert_eq!(snapshot.decryption_failures, 1);
+        assert_eq!(snapshot.malformed_stanzas, 2);
+        assert_eq!(snapshot.reconnects, 1);
+        assert_eq!(snapshot.latency, Some(Duration::from_millis(120)));
+    }
+}
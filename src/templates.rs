@@ -0,0 +1,270 @@
+//! Pesan template (HSM) bisnis
+//!
+//! Template yang sudah disetujui diidentifikasi dengan `namespace` dan
+//! `element_name`. Klien penerima melokalkan template sendiri dari parameter
+//! `localizable_params`; setiap parameter punya teks `default` sebagai
+//! cadangan dan, untuk mata uang atau tanggal, nilai terstruktur yang
+//! diformat sesuai bahasa penerima. Teks yang sudah diisi (`hydrated_hsm`)
+//! dikirim juga untuk klien yang tidak mengenal template tersebut.
+//!
+//! Placeholder di teks template ditulis `{{1}}`, `{{2}}`, dst. sesuai urutan
+//! parameter.
+
+use chrono::{Datelike, NaiveDateTime, Timelike};
+
+use crate::errors::*;
+use crate::messages::{
+    self, HSMCurrency, HSMDateTimeComponent, HSMLocalizableParameter, HighlyStructuredMessage, HydratedCallButton, HydratedFourRowTemplate,
+    HydratedQuickReplyButton, HydratedTemplateButton, HydratedURLButton, TemplateMessage,
+};
+use crate::{Jid, WhatsAppClient};
+
+/// Jumlah tombol terbanyak pada template
+pub const MAX_TEMPLATE_BUTTONS: usize = 3;
+/// `HSMDateTimeComponent.calendar` untuk kalender Gregorian
+const CALENDAR_GREGORIAN: u32 = 1;
+
+/// Parameter template
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateParam {
+    Text(String),
+    /// Nominal dalam seperseribu unit mata uang `code` (ISO 4217)
+    Currency { code: String, amount_1000: i64, fallback: String },
+    DateTime { value: NaiveDateTime, fallback: String },
+}
+
+impl TemplateParam {
+    pub fn text(text: &str) -> Self {
+        TemplateParam::Text(text.to_string())
+    }
+
+    /// Mata uang; `fallback` ditampilkan jika penerima tidak bisa memformatnya
+    pub fn currency(code: &str, amount_1000: i64, fallback: &str) -> Self {
+        TemplateParam::Currency { code: code.to_string(), amount_1000, fallback: fallback.to_string() }
+    }
+
+    /// Tanggal dan jam; cadangannya `YYYY-MM-DD HH:MM`
+    pub fn date_time(value: NaiveDateTime) -> Self {
+        TemplateParam::DateTime { value, fallback: value.format("%Y-%m-%d %H:%M").to_string() }
+    }
+
+    /// Teks cadangan parameter
+    pub fn fallback(&self) -> &str {
+        match self {
+            TemplateParam::Text(text) => text,
+            TemplateParam::Currency { fallback, .. } | TemplateParam::DateTime { fallback, .. } => fallback,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let TemplateParam::Currency { code, .. } = self {
+            if code.len() != 3 || !code.chars().all(|c| c.is_ascii_uppercase()) {
+                return Err(format!("Invalid ISO 4217 currency code {:?}", code).into());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_localizable(&self) -> HSMLocalizableParameter {
+        let mut param = HSMLocalizableParameter { default: self.fallback().to_string(), currency: None, date_time_component: None };
+        match self {
+            TemplateParam::Text(_) => {}
+            TemplateParam::Currency { code, amount_1000, .. } => {
+                param.currency = Some(HSMCurrency { currency_code: code.clone(), amount_1000: *amount_1000 });
+            }
+            TemplateParam::DateTime { value, .. } => {
+                param.date_time_component = Some(HSMDateTimeComponent {
+                    // 1 = Senin .. 7 = Minggu
+                    day_of_week: value.weekday().number_from_monday(),
+                    year: value.year() as u32,
+                    month: value.month(),
+                    day_of_month: value.day(),
+                    hour: value.hour(),
+                    minute: value.minute(),
+                    calendar: CALENDAR_GREGORIAN,
+                });
+            }
+        }
+        param
+    }
+}
+
+/// Tombol template
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateButton {
+    QuickReply { text: String, id: String },
+    Url { text: String, url: String },
+    Call { text: String, phone_number: String },
+}
+
+/// Builder pesan template
+#[derive(Debug, Clone)]
+pub struct TemplateBuilder {
+    namespace: String,
+    element_name: String,
+    language: String,
+    body: String,
+    footer: Option<String>,
+    params: Vec<TemplateParam>,
+    buttons: Vec<TemplateButton>,
+}
+
+impl TemplateBuilder {
+    /// `language` berupa kode bahasa seperti `id` atau `en_US`; `body` adalah
+    /// teks template yang disetujui dengan placeholder `{{n}}`
+    pub fn new(namespace: &str, element_name: &str, language: &str, body: &str) -> Self {
+        TemplateBuilder {
+            namespace: namespace.to_string(),
+            element_name: element_name.to_string(),
+            language: language.to_string(),
+            body: body.to_string(),
+            footer: None,
+            params: Vec::new(),
+            buttons: Vec::new(),
+        }
+    }
+
+    pub fn param(mut self, param: TemplateParam) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    pub fn footer(mut self, text: &str) -> Self {
+        self.footer = Some(text.to_string());
+        self
+    }
+
+    pub fn button(mut self, button: TemplateButton) -> Self {
+        self.buttons.push(button);
+        self
+    }
+
+    /// Bahasa dan wilayah (`en_US` -> `en`, `US`)
+    fn language_parts(&self) -> (String, String) {
+        match self.language.split_once(|c| c == '_' || c == '-') {
+            Some((lang, region)) => (lang.to_string(), region.to_string()),
+            None => (self.language.clone(), String::new()),
+        }
+    }
+
+    /// Teks template dengan placeholder diganti teks cadangan parameter
+    pub fn hydrated_text(&self) -> String {
+        // Dari belakang agar `{{1}}` tidak mengganti bagian dari `{{10}}`
+        self.params.iter().enumerate().rev().fold(self.body.clone(), |text, (index, param)| {
+            text.replace(&format!("{{{{{}}}}}", index + 1), param.fallback())
+        })
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.namespace.is_empty() || self.element_name.is_empty() {
+            return Err("Template namespace and element name are required".into());
+        }
+        if self.language_parts().0.is_empty() {
+            return Err("Template language is required".into());
+        }
+        for index in 1..=self.params.len() {
+            if !self.body.contains(&format!("{{{{{}}}}}", index)) {
+                return Err(format!("Template body has no placeholder for parameter {}", index).into());
+            }
+        }
+        if self.body.contains(&format!("{{{{{}}}}}", self.params.len() + 1)) {
+            return Err(format!("Template body expects more than {} parameters", self.params.len()).into());
+        }
+        if self.buttons.len() > MAX_TEMPLATE_BUTTONS {
+            return Err(format!("Template supports at most {} buttons", MAX_TEMPLATE_BUTTONS).into());
+        }
+        self.params.iter().try_for_each(TemplateParam::validate)
+    }
+
+    fn hydrated_buttons(&self) -> Vec<HydratedTemplateButton> {
+        self.buttons
+            .iter()
+            .enumerate()
+            .map(|(index, button)| {
+                let mut hydrated = HydratedTemplateButton {
+                    index: index as u32,
+                    quick_reply_button: None,
+                    url_button: None,
+                    call_button: None,
+                    currency_button: None,
+                };
+                match button.clone() {
+                    TemplateButton::QuickReply { text, id } => hydrated.quick_reply_button = Some(HydratedQuickReplyButton { display_text: text, id }),
+                    TemplateButton::Url { text, url } => hydrated.url_button = Some(HydratedURLButton { display_text: text, url }),
+                    TemplateButton::Call { text, phone_number } => {
+                        hydrated.call_button = Some(HydratedCallButton { display_text: text, phone_number })
+                    }
+                }
+                hydrated
+            })
+            .collect()
+    }
+
+    pub fn build(&self) -> Result<messages::Message> {
+        self.validate()?;
+        let (lang, region) = self.language_parts();
+        let hydrated = TemplateMessage {
+            context_info: None,
+            hydrated_template: Some(HydratedFourRowTemplate {
+                hydrated_content_text: Some(self.hydrated_text()),
+                hydrated_footer_text: self.footer.clone(),
+                hydrated_buttons: self.hydrated_buttons(),
+                template_id: Some(self.element_name.clone()),
+                hydrated_title_text: None,
+                hydrated_subtitle_text: None,
+                hydrated_image_caption: None,
+            }),
+            template_id: Some(self.element_name.clone()),
+        };
+        Ok(messages::Message {
+            highly_structured_message: Some(HighlyStructuredMessage {
+                namespace: self.namespace.clone(),
+                element_name: self.element_name.clone(),
+                params: self.params.iter().map(|param| param.fallback().to_string()).collect(),
+                fallback_lg: lang.clone(),
+                fallback_lc: region.clone(),
+                localizable_params: self.params.iter().map(TemplateParam::to_localizable).collect(),
+                deterministic_lg: Some(lang),
+                deterministic_lc: Some(region),
+                hydrated_hsm: Some(hydrated),
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+impl WhatsAppClient {
+    /// Mengirim template yang sudah disetujui. Mengembalikan id pesan.
+    pub fn send_template(&self, to: &Jid, template: &TemplateBuilder) -> Result<String> {
+        let message = template.build()?;
+        self.send_message(to, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_localizable_params_and_hydration() {
+        let due = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        let template = TemplateBuilder::new("ns-toko", "tagihan", "id_ID", "Halo {{1}}, tagihan {{2}} jatuh tempo {{3}}.")
+            .param(TemplateParam::text("Budi"))
+            .param(TemplateParam::currency("IDR", 150_000_000, "Rp150.000"))
+            .param(TemplateParam::date_time(due))
+            .button(TemplateButton::QuickReply { text: "Bayar".to_string(), id: "pay".to_string() });
+
+        assert_eq!(template.hydrated_text(), "Halo Budi, tagihan Rp150.000 jatuh tempo 2024-03-15 09:30.");
+        let hsm = template.build().unwrap().highly_structured_message.unwrap();
+        assert_eq!((hsm.fallback_lg.as_str(), hsm.fallback_lc.as_str()), ("id", "ID"));
+        assert_eq!(hsm.localizable_params[1].currency.as_ref().unwrap().amount_1000, 150_000_000);
+        let date = hsm.localizable_params[2].date_time_component.as_ref().unwrap();
+        // 15 Maret 2024 adalah hari Jumat
+        assert_eq!((date.day_of_week, date.hour, date.minute), (5, 9, 30));
+
+        assert!(template.clone().param(TemplateParam::text("lebih")).validate().is_err());
+        let bad_currency = TemplateBuilder::new("ns", "x", "en", "{{1}}").param(TemplateParam::currency("rupiah", 1, "1"));
+        assert!(bad_currency.validate().is_err());
+    }
+}
@@ -0,0 +1,517 @@
+//! Utilitas testing: mock server WhatsApp dan harness untuk beberapa client
+//!
+//! Mock server berjalan di localhost dan meniru alur minimal server asli:
+//! menjawab `init` dengan QR ref dan `Conn`, menyimpan prekey yang diunggah
+//! setiap client, lalu menjawab `usync` dan `encrypt` dengan daftar perangkat
+//! dan prekey bundle sungguhan. JID di jawaban memakai bentuk server
+//! (`@s.whatsapp.net`).
+//!
+//! Stanza relay tidak dibaca isinya: setiap `<enc>` di `<participants>`
+//! diteruskan ke perangkat tujuannya sebagai stanza `message`, jadi pesan
+//! antar client melewati X3DH, enkripsi, dan dekripsi Signal yang sama dengan
+//! server asli. Fan-out grup dilakukan client dari daftar peserta `w:g2`.
+//! Hanya peer loopback (lihat `loopback`) yang menerima plaintext `<message>`.
+//!
+//! Aktifkan dengan feature `testing`.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use json::JsonValue;
+use ws::{CloseCode, Handler, Message, Sender};
+
+use crate::errors::*;
+use crate::framing::Framing;
+use crate::loopback::LoopbackPeer;
+use crate::messages::WebMessageInfo;
+use crate::node_protocol::{self, Node, NodeContent};
+use crate::{signal, AuthMethod, Event, EventHandler, Jid, WhatsAppClient, WhatsAppClientBuilder};
+
+/// Batas waktu default saat menunggu event di test
+pub const DEFAULT_EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Data registrasi Signal yang diunggah client (`iq xmlns="encrypt" type="set"`)
+struct UploadedKeys {
+    registration: Node,
+    identity: Node,
+    signed_pre_key: Node,
+    /// One-time prekey; setiap bundle yang dibagikan memakai satu
+    one_time_keys: VecDeque<Node>,
+}
+
+#[derive(Default)]
+struct MockState {
+    clients: HashMap<String, Sender>,
+    pending_jids: VecDeque<String>,
+    groups: HashMap<String, Vec<String>>,
+    keys: HashMap<String, UploadedKeys>,
+    loopback: Option<LoopbackPeer>,
+    next_stanza_id: u64,
+}
+
+impl MockState {
+    fn next_id(&mut self) -> String {
+        self.next_stanza_id += 1;
+        format!("mock_{}", self.next_stanza_id)
+    }
+}
+
+/// Mock server WhatsApp yang berjalan di thread terpisah
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    broadcaster: Sender,
+}
+
+impl MockServer {
+    /// Menjalankan mock server di port acak pada localhost
+    pub fn start() -> Result<Self> {
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let factory_state = Arc::clone(&state);
+
+        let socket = ws::Builder::new()
+            .build(move |out: Sender| MockConnection {
+                out,
+                state: Arc::clone(&factory_state),
+                jid: None,
+                framing: Framing::server(),
+            })
+            .map_err(|e| format!("Failed to build mock server: {}", e))?
+            .bind("127.0.0.1:0")
+            .map_err(|e| format!("Failed to bind mock server: {}", e))?;
+
+        let addr = socket.local_addr()?;
+        let broadcaster = socket.broadcaster();
+
+        thread::spawn(move || {
+            socket.run().ok();
+        });
+
+        Ok(MockServer { addr, state, broadcaster })
+    }
+
+    /// URL WebSocket untuk diberikan ke `WhatsAppClientBuilder::with_websocket_url`
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Menentukan JID untuk client berikutnya yang melakukan pairing
+    pub fn expect_client(&self, jid: &Jid) {
+        self.state.lock().unwrap().pending_jids.push_back(jid.to_string());
+    }
+
+    /// Mendaftarkan grup beserta anggotanya
+    pub fn add_group(&self, group: &Jid, members: &[Jid]) {
+        self.state
+            .lock()
+            .unwrap()
+            .groups
+            .insert(group.to_string(), members.iter().map(|m| m.to_string()).collect());
+    }
+
+    /// Memasang penerima simulasi (lihat `loopback::LoopbackTransport`)
+    pub(crate) fn set_loopback_peer(&self, peer: LoopbackPeer) {
+        self.state.lock().unwrap().loopback = Some(peer);
+    }
+
+    /// Cek apakah client dengan JID tertentu sedang terhubung
+    pub fn is_connected(&self, jid: &Jid) -> bool {
+        self.state.lock().unwrap().clients.contains_key(&jid.to_string())
+    }
+
+    /// Menghentikan mock server
+    pub fn shutdown(&self) {
+        self.broadcaster.shutdown().ok();
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Koneksi satu client ke mock server
+struct MockConnection {
+    out: Sender,
+    state: Arc<Mutex<MockState>>,
+    jid: Option<String>,
+    framing: Framing,
+}
+
+impl Handler for MockConnection {
+    fn on_message(&mut self, msg: Message) -> ws::Result<()> {
+        match msg {
+            Message::Text(text) => {
+                if let Ok(json) = json::parse(&text) {
+                    self.handle_json(json)?;
+                }
+            }
+            Message::Binary(data) => {
+                let frames = match self.framing.decode(&data) {
+                    Ok(frames) => frames,
+                    Err(_) => return self.out.close(CloseCode::Protocol),
+                };
+                for frame in frames {
+                    if let Ok(node) = node_protocol::decode_payload(&frame) {
+                        self.handle_node(node)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn on_close(&mut self, _code: CloseCode, _reason: &str) {
+        if let Some(ref jid) = self.jid {
+            self.state.lock().unwrap().clients.remove(jid);
+        }
+    }
+}
+
+impl MockConnection {
+    fn handle_json(&mut self, json: JsonValue) -> ws::Result<()> {
+        if json["type"].as_str() != Some("init") {
+            return Ok(());
+        }
+
+        let jid = {
+            let mut state = self.state.lock().unwrap();
+            match state.pending_jids.pop_front() {
+                Some(jid) => jid,
+                None => return self.out.close(CloseCode::Policy),
+            }
+        };
+
+        // QR ref lalu Conn, seperti ketika ponsel memindai QR
+        let qr_ref = json::object! {
+            "type": "ref",
+            "ref": format!("mock-ref-{}", jid),
+        };
+        self.out.send(qr_ref.dump())?;
+
+        let conn = json::object! {
+            "type": "Conn",
+            "clientToken": format!("client-token-{}", jid),
+            "serverToken": format!("server-token-{}", jid),
+            "wid": jid.clone(),
+            "pushname": "mock",
+        };
+        self.out.send(conn.dump())?;
+
+        self.state.lock().unwrap().clients.insert(jid.clone(), self.out.clone());
+        self.jid = Some(jid);
+        Ok(())
+    }
+
+    fn handle_node(&mut self, node: Node) -> ws::Result<()> {
+        let sender_jid = match self.jid {
+            Some(ref jid) => jid.clone(),
+            None => return Ok(()),
+        };
+
+        if node.tag == "iq" {
+            return self.handle_iq(node);
+        }
+        if node.tag != "action" || node.get_attr("type") != Some("relay") {
+            return Ok(());
+        }
+        let chat = match node.attr_jid("to") {
+            Ok(chat) => chat,
+            Err(_) => return Ok(()),
+        };
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(peer) = state.loopback.as_ref().filter(|peer| *peer.jid() == chat) {
+            let web_message = match node.get_child("message").and_then(|message| message.get_bytes()).map(WebMessageInfo::from_bytes) {
+                Some(Ok(web_message)) => web_message,
+                _ => return Ok(()),
+            };
+            let reply = match peer.deliver(&web_message) {
+                Ok(Some(reply)) => reply,
+                _ => return Ok(()),
+            };
+            let id = state.next_id();
+            return self.out.send(message_data(&id, &reply));
+        }
+
+        // Server hanya meneruskan ciphertext; perangkat tanpa `<enc>` tidak menerima apa pun
+        let id = match node.get_attr("id") {
+            Some(id) => id.to_string(),
+            None => state.next_id(),
+        };
+        let targets = node.get_child("participants").map(|participants| participants.get_children()).unwrap_or_default();
+        for to in targets {
+            let (device, enc) = match (to.attr_jid("jid"), to.get_child("enc")) {
+                (Ok(device), Some(enc)) => (device, enc),
+                _ => continue,
+            };
+            let recipient = device.to_non_ad().to_string();
+            if recipient == sender_jid {
+                continue;
+            }
+            let out = match state.clients.get(&recipient) {
+                Some(out) => out.clone(),
+                None => continue,
+            };
+            let stanza = Node::new("message").attr("id", &id).attr("t", &chrono::Utc::now().timestamp().to_string());
+            let stanza = if chat.is_group() {
+                stanza.attr("from", &chat.to_string()).attr("participant", &sender_jid)
+            } else {
+                stanza.attr("from", &sender_jid)
+            };
+            out.send(node_data(&stanza.children(vec![enc.clone()]))).ok();
+        }
+
+        Ok(())
+    }
+
+    /// Membuat grup dengan pembuat sebagai superadmin
+    fn create_group(&mut self, create: &Node) -> Node {
+        let creator = self.jid.clone().unwrap_or_default();
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id().replace("mock_", "120363");
+        let group = format!("{}@g.us", id);
+
+        let mut members = vec![creator.clone()];
+        members.extend(participant_jids(create));
+        state.groups.insert(group.clone(), members.clone());
+
+        Node::new("group")
+            .attr("id", &id)
+            .attr("subject", create.get_attr("subject").unwrap_or_default())
+            .attr("creator", &creator)
+            .attr("creation", &chrono::Utc::now().timestamp().to_string())
+            .children(
+                members
+                    .iter()
+                    .map(|member| {
+                        let participant = Node::new("participant").attr("jid", member);
+                        if *member == creator { participant.attr("type", "superadmin") } else { participant }
+                    })
+                    .collect(),
+            )
+    }
+
+    /// Mengubah anggota grup; 409 jika sudah anggota, 404 jika bukan anggota
+    fn change_participants(&mut self, group: &str, change: &Node) -> Node {
+        let mut state = self.state.lock().unwrap();
+        let members = state.groups.entry(group.to_string()).or_default();
+
+        let results = participant_jids(change)
+            .into_iter()
+            .map(|jid| {
+                let is_member = members.contains(&jid);
+                let error = match change.tag.as_str() {
+                    "add" if is_member => Some("409"),
+                    "add" => {
+                        members.push(jid.clone());
+                        None
+                    }
+                    _ if !is_member => Some("404"),
+                    "remove" => {
+                        members.retain(|member| *member != jid);
+                        None
+                    }
+                    _ => None,
+                };
+                let participant = Node::new("participant").attr("jid", &jid);
+                match error {
+                    Some(code) => participant.attr("error", code),
+                    None => participant,
+                }
+            })
+            .collect();
+        Node::new(&change.tag).children(results)
+    }
+
+    /// Menyimpan data registrasi dan prekey yang diunggah client. Upload
+    /// berikutnya (pengisian ulang atau rotasi) menambah one-time prekey.
+    fn store_keys(&mut self, upload: &Node) {
+        let jid = match self.jid {
+            Some(ref jid) => jid.clone(),
+            None => return,
+        };
+        let (registration, identity, signed_pre_key) = match (upload.get_child("registration"), upload.get_child("identity"), upload.get_child("skey")) {
+            (Some(registration), Some(identity), Some(skey)) => (registration.clone(), identity.clone(), skey.clone()),
+            _ => return,
+        };
+        let one_time_keys: Vec<Node> = upload.get_child("list").map(|list| list.get_children().to_vec()).unwrap_or_default();
+
+        let mut state = self.state.lock().unwrap();
+        let keys = state.keys.entry(jid).or_insert_with(|| UploadedKeys {
+            registration: registration.clone(),
+            identity: identity.clone(),
+            signed_pre_key: signed_pre_key.clone(),
+            one_time_keys: VecDeque::new(),
+        });
+        keys.registration = registration;
+        keys.identity = identity;
+        keys.signed_pre_key = signed_pre_key;
+        keys.one_time_keys.extend(one_time_keys);
+    }
+
+    /// Prekey bundle untuk setiap `<user jid>`; setiap bundle memakai satu
+    /// one-time prekey. Peer loopback tidak punya kunci dan dijawab tanpa
+    /// bundle, perangkat lain yang tidak dikenal dengan `error` 404.
+    fn pre_key_bundles(&mut self, users: &[Node]) -> Node {
+        let mut state = self.state.lock().unwrap();
+        let loopback = state.loopback.as_ref().map(|peer| peer.jid().to_string());
+        let bundles = users
+            .iter()
+            .filter_map(|user| user.attr_jid("jid").ok())
+            .map(|device| {
+                let jid = device.to_string();
+                let user = Node::new("user").attr("jid", &jid);
+                match state.keys.get_mut(&jid) {
+                    Some(keys) => {
+                        let mut bundle = vec![
+                            keys.registration.clone(),
+                            Node::new("type").bytes(vec![signal::DJB_TYPE]),
+                            keys.identity.clone(),
+                            keys.signed_pre_key.clone(),
+                        ];
+                        bundle.extend(keys.one_time_keys.pop_front());
+                        user.children(bundle)
+                    }
+                    None if loopback.as_deref() == Some(jid.as_str()) => user,
+                    None => user.children(vec![Node::new("error").attr("code", "404").attr("text", "item-not-found")]),
+                }
+            })
+            .collect();
+        Node::new("list").children(bundles)
+    }
+
+    /// Menjawab permintaan daftar perangkat (`usync`), upload dan permintaan
+    /// prekey (`encrypt`) serta daftar peserta grup (`w:g2`)
+    fn handle_iq(&mut self, node: Node) -> ws::Result<()> {
+        let id = match node.get_attr("id") {
+            Some(id) => id.to_string(),
+            None => return Ok(()),
+        };
+        let result = Node::new("iq").attr("id", &id).attr("type", "result");
+
+        // Pong keepalive tanpa isi
+        if node.get_attr("xmlns") == Some("w:p") {
+            return self.out.send(node_data(&result));
+        }
+        if node.get_attr("xmlns") == Some("encrypt") && node.get_attr("type") == Some("set") {
+            self.store_keys(&node);
+            return self.out.send(node_data(&result));
+        }
+
+        let child = match node.get_attr("xmlns") {
+            // Setiap user hanya punya perangkat utama
+            Some("usync") => Node::new("usync").children(vec![Node::new("list").children(
+                node.get_child("usync")
+                    .and_then(|usync| usync.get_child("list"))
+                    .map(|list| {
+                        list.get_children()
+                            .iter()
+                            .filter_map(|user| user.attr_jid("jid").ok())
+                            .map(|jid| {
+                                Node::new("user").attr("jid", &jid.to_non_ad().to_string()).children(vec![Node::new("devices")
+                                    .children(vec![Node::new("device-list").children(vec![Node::new("device").attr("id", "0")])])])
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            )]),
+            Some("encrypt") => {
+                let users = node.get_child("key").map(|key| key.get_children().to_vec()).unwrap_or_default();
+                self.pre_key_bundles(&users)
+            }
+            Some("w:g2") if node.get_child("create").is_some() => self.create_group(node.get_child("create").unwrap()),
+            Some("w:g2") if node.get_children().iter().any(|c| ["add", "remove", "promote", "demote"].contains(&c.tag.as_str())) => {
+                self.change_participants(node.get_attr("to").unwrap_or_default(), &node.get_children()[0])
+            }
+            Some("w:g2") => {
+                let group = node.get_attr("to").unwrap_or_default();
+                let members = self.state.lock().unwrap().groups.get(group).cloned().unwrap_or_default();
+                Node::new("group")
+                    .attr("id", group)
+                    .children(members.iter().map(|member| Node::new("participant").attr("jid", member)).collect())
+            }
+            _ => return Ok(()),
+        };
+
+        self.out.send(node_data(&result.children(vec![child])))
+    }
+}
+
+/// JID `<participant jid>` dalam bentuk server (`c.us` dari decoder dinormalkan)
+fn participant_jids(node: &Node) -> Vec<String> {
+    node.get_children().iter().filter_map(|participant| participant.attr_jid("jid").ok()).map(|jid| jid.to_string()).collect()
+}
+
+/// Node terenkode dalam satu frame sisi server. Mock server tidak memakai
+/// enkripsi transport, jadi framing cukup dibuat per pesan.
+fn node_data(node: &Node) -> Vec<u8> {
+    let payload = node_protocol::encode_payload(node, false).unwrap_or_default();
+    Framing::server().encode(&payload).unwrap_or_default()
+}
+
+/// Node `message` plaintext dari peer loopback
+fn message_data(id: &str, web_message: &WebMessageInfo) -> Vec<u8> {
+    let body = web_message.to_bytes();
+    let mut attrs = HashMap::new();
+    attrs.insert("id".to_string(), id.to_string());
+    attrs.insert("from".to_string(), web_message.key.remote_jid.clone());
+
+    node_data(&Node {
+        tag: "message".to_string(),
+        attrs,
+        content: Some(NodeContent::Binary(body)),
+    })
+}
+
+/// Event handler kosong untuk client di test
+pub struct NoopEventHandler;
+
+impl EventHandler for NoopEventHandler {
+    fn handle_event(&self, _event: Event) {}
+}
+
+/// Membuat client yang terhubung ke mock server dan menunggu sampai terotentikasi
+pub fn connect_client(server: &MockServer, jid: &Jid) -> Result<WhatsAppClient> {
+    server.expect_client(jid);
+
+    let client = WhatsAppClientBuilder::new()
+        .with_event_handler(Box::new(NoopEventHandler))
+        .with_websocket_url(&server.url())
+        .build()?;
+
+    client.connect(AuthMethod::QRCode { callback: Box::new(|_| {}) })?;
+
+    match wait_for_event(&client, DEFAULT_EVENT_TIMEOUT, |event| matches!(event, Event::Authenticated)) {
+        Some(_) => Ok(client),
+        None => Err(format!("Client {} did not authenticate against mock server", jid.to_string()).into()),
+    }
+}
+
+/// Menunggu event yang cocok dengan predikat, event lain diabaikan
+pub fn wait_for_event<F>(client: &WhatsAppClient, timeout: Duration, predicate: F) -> Option<Event>
+where
+    F: Fn(&Event) -> bool,
+{
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        match client.poll_event() {
+            Some(event) if predicate(&event) => return Some(event),
+            Some(_) => continue,
+            None => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+    None
+}
+
+/// Menunggu pesan masuk dan mengembalikan WebMessageInfo-nya
+pub fn wait_for_message(client: &WhatsAppClient, timeout: Duration) -> Option<WebMessageInfo> {
+    match wait_for_event(client, timeout, |event| matches!(event, Event::MessageReceived(_))) {
+        Some(Event::MessageReceived(web_message)) => Some(web_message),
+        _ => None,
+    }
+}
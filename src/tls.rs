@@ -0,0 +1,168 @@
+//! Konfigurasi TLS koneksi WebSocket dan certificate pinning
+//!
+//! Secara default koneksi `wss://` memakai root store sistem. `TlsConfig`
+//! memungkinkan aplikasi menentukan sendiri root CA yang dipercaya (mis. CA
+//! proxy MITM perusahaan yang harus diizinkan secara eksplisit), memakai
+//! `SslConnectorBuilder` openssl yang sudah diatur sendiri, dan mem-pin
+//! sertifikat server: handshake hanya diterima jika salah satu sertifikat di
+//! rantai yang terverifikasi memiliki hash SHA-256 SubjectPublicKeyInfo yang
+//! terdaftar.
+
+use openssl::hash::{hash, MessageDigest};
+use openssl::ssl::{SslConnector, SslConnectorBuilder, SslMethod, SslStream, SslVerifyMode};
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::{X509Ref, X509};
+use url::Url;
+use ws::util::TcpStream;
+
+use crate::errors::*;
+
+/// Pengaturan TLS untuk koneksi ke server
+pub struct TlsConfig {
+    builder: Option<SslConnectorBuilder>,
+    system_roots: bool,
+    roots: Vec<X509>,
+    /// SHA-256 dari SubjectPublicKeyInfo (DER)
+    pins: Vec<[u8; 32]>,
+}
+
+fn tls_error(e: impl std::fmt::Display) -> Error {
+    Error { kind: ErrorKind::ConnectionError(format!("TLS configuration failed: {}", e)) }
+}
+
+impl TlsConfig {
+    /// Root store sistem tanpa pinning
+    pub fn new() -> Self {
+        TlsConfig {
+            builder: None,
+            system_roots: true,
+            roots: Vec::new(),
+            pins: Vec::new(),
+        }
+    }
+
+    /// Memakai connector yang sudah diatur aplikasi (cipher, sertifikat client, root store)
+    pub fn from_builder(builder: SslConnectorBuilder) -> Self {
+        TlsConfig {
+            builder: Some(builder),
+            ..TlsConfig::new()
+        }
+    }
+
+    /// Menambahkan root CA dari PEM (boleh berisi beberapa sertifikat)
+    pub fn add_root_pem(mut self, pem: &[u8]) -> Result<Self> {
+        let certs = X509::stack_from_pem(pem).map_err(tls_error)?;
+        if certs.is_empty() {
+            return Err(tls_error("no certificate in PEM"));
+        }
+        self.roots.extend(certs);
+        Ok(self)
+    }
+
+    /// Hanya mempercayai root yang ditambahkan lewat `add_root_pem`
+    pub fn without_system_roots(mut self) -> Self {
+        self.system_roots = false;
+        self
+    }
+
+    /// Mem-pin hash SHA-256 SubjectPublicKeyInfo; boleh dipanggil beberapa kali untuk rotasi kunci
+    pub fn pin_spki_sha256(mut self, pin: [u8; 32]) -> Self {
+        self.pins.push(pin);
+        self
+    }
+
+    /// Membangun connector openssl sesuai pengaturan
+    pub fn connector(self) -> Result<SslConnector> {
+        let mut builder = match self.builder {
+            Some(builder) => builder,
+            None => SslConnector::builder(SslMethod::tls()).map_err(tls_error)?,
+        };
+        if !self.system_roots {
+            builder.set_cert_store(X509StoreBuilder::new().map_err(tls_error)?.build());
+        }
+        for root in self.roots {
+            builder.cert_store_mut().add_cert(root).map_err(tls_error)?;
+        }
+        if !self.pins.is_empty() {
+            let pins = self.pins;
+            builder.set_verify_callback(SslVerifyMode::PEER, move |preverified, ctx| {
+                // Pin diperiksa sekali, saat sertifikat server (kedalaman 0) diverifikasi
+                if !preverified || ctx.error_depth() != 0 {
+                    return preverified;
+                }
+                ctx.chain().is_some_and(|chain| chain.iter().any(|cert| is_pinned(cert, &pins)))
+            });
+        }
+        Ok(builder.build())
+    }
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        TlsConfig::new()
+    }
+}
+
+/// Hash SHA-256 SubjectPublicKeyInfo sertifikat, format yang dipakai `pin_spki_sha256`
+pub fn spki_sha256(cert: &X509Ref) -> Result<[u8; 32]> {
+    let spki = cert.public_key().and_then(|key| key.public_key_to_der()).map_err(tls_error)?;
+    let digest = hash(MessageDigest::sha256(), &spki).map_err(tls_error)?;
+    let mut pin = [0u8; 32];
+    pin.copy_from_slice(&digest);
+    Ok(pin)
+}
+
+fn is_pinned(cert: &X509Ref, pins: &[[u8; 32]]) -> bool {
+    spki_sha256(cert).map(|pin| pins.contains(&pin)).unwrap_or(false)
+}
+
+/// Membungkus socket client dengan TLS memakai `connector`, atau connector default openssl
+pub(crate) fn upgrade_client(connector: Option<&SslConnector>, stream: TcpStream, url: &Url) -> ws::Result<SslStream<TcpStream>> {
+    let domain = url
+        .domain()
+        .ok_or_else(|| ws::Error::new(ws::ErrorKind::Protocol, format!("Unable to parse domain from {}. Needed for SSL.", url)))?;
+    let connector = match connector {
+        Some(connector) => connector.clone(),
+        None => SslConnector::builder(SslMethod::tls())
+            .map_err(|e| ws::Error::new(ws::ErrorKind::Internal, format!("Failed to upgrade client to SSL: {}", e)))?
+            .build(),
+    };
+    connector.connect(domain, stream).map_err(ws::Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::asn1::Asn1Time;
+    use openssl::pkey::PKey;
+    use openssl::rsa::Rsa;
+    use openssl::x509::X509Builder;
+
+    fn self_signed() -> X509 {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut builder = X509Builder::new().unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        builder.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        builder.build()
+    }
+
+    #[test]
+    fn test_pinning_and_custom_roots() {
+        let root = self_signed();
+        let other = self_signed();
+        let pin = spki_sha256(&root).unwrap();
+        assert!(is_pinned(&root, &[pin]));
+        assert!(!is_pinned(&other, &[pin]));
+
+        assert!(TlsConfig::new().add_root_pem(b"not a certificate").is_err());
+        let config = TlsConfig::new()
+            .add_root_pem(&root.to_pem().unwrap())
+            .unwrap()
+            .without_system_roots()
+            .pin_spki_sha256(pin);
+        assert_eq!(config.roots.len(), 1);
+        assert!(config.connector().is_ok());
+    }
+}
@@ -0,0 +1,30 @@
+//! Logging terstruktur
+//!
+//! Dengan feature `tracing`, event koneksi (siklus koneksi, tahap handshake,
+//! id stanza) dikirim sebagai event `tracing` dengan field terstruktur di
+//! dalam span `connection`, sehingga aplikasi bisa memasang subscriber sendiri.
+//! Tanpa feature tersebut event yang sama diteruskan ke crate `log`, dengan
+//! field ditambahkan di akhir pesan sebagai `nama=nilai`.
+
+/// `event!(level, field = nilai, ..., "pesan {}", arg)`; nilai field ditampilkan dengan `Debug`
+macro_rules! event {
+    ($level:ident, $($field:ident = $value:expr,)* $fmt:literal $(, $arg:expr)* $(,)?) => {{
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($field = ?$value,)* $fmt $(, $arg)*);
+        #[cfg(not(feature = "tracing"))]
+        log::$level!(concat!($fmt $(, " ", stringify!($field), "={:?}")*) $(, $arg)* $(, $value)*);
+    }};
+}
+
+pub(crate) use event;
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_event_accepts_fields_and_arguments() {
+        let id = "3EB0C431";
+        event!(debug, tag = "message", id = id, "inbound stanza");
+        event!(warn, code = 1006, "socket closed: {}", "reset");
+        event!(info, "no fields");
+    }
+}
@@ -0,0 +1,243 @@
+//! Preset traffic shaping untuk mengurangi risiko deteksi spam
+//!
+//! Satu preset menggabungkan jeda antar pengiriman (global dan per chat),
+//! simulasi mengetik, mode presence, dan perilaku read receipt. Pengguna baru
+//! cukup memilih `TrafficProfile` di builder; pengguna lanjutan bisa mengubah
+//! `TrafficSettings` secara langsung.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::errors::*;
+use crate::messages::WebMessageInfo;
+use crate::node_protocol::Node;
+use crate::receipts::{self, ReceiptType};
+use crate::routing::NodeContext;
+
+/// Preset traffic shaping
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrafficProfile {
+    /// Paling aman untuk nomor baru dan pengiriman massal
+    Conservative,
+    /// Default yang wajar untuk bot interaktif
+    Balanced,
+    /// Minim jeda, hanya untuk nomor yang sudah terpercaya
+    Aggressive,
+}
+
+/// Kapan status online dikirim
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PresenceMode {
+    /// Tidak pernah tampil online (tidak mengirim presence apa pun)
+    Unavailable,
+    /// Tampil online hanya saat mengirim pesan
+    OnlineWhileSending,
+    /// Tampil online sejak terhubung
+    AlwaysOnline,
+}
+
+/// Simulasi mengetik sebelum mengirim pesan teks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypingSimulation {
+    pub chars_per_second: u32,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl TypingSimulation {
+    /// Lama mengetik untuk teks sepanjang `chars`
+    pub fn duration_for(&self, chars: usize) -> Duration {
+        let cps = self.chars_per_second.max(1) as u64;
+        let typing = Duration::from_millis(chars as u64 * 1000 / cps);
+        typing.max(self.min).min(self.max)
+    }
+}
+
+/// Pengaturan traffic shaping
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrafficSettings {
+    /// Jeda minimal antar pengiriman ke chat mana pun
+    pub min_send_interval: Duration,
+    /// Jeda minimal antar pengiriman ke chat yang sama
+    pub per_chat_interval: Duration,
+    pub typing: Option<TypingSimulation>,
+    pub presence: PresenceMode,
+    /// Mengirim read receipt otomatis untuk pesan masuk
+    pub read_receipts: bool,
+}
+
+impl Default for TrafficSettings {
+    /// Tanpa shaping: perilaku client sebelum preset diperkenalkan
+    fn default() -> Self {
+        TrafficSettings {
+            min_send_interval: Duration::from_millis(0),
+            per_chat_interval: Duration::from_millis(0),
+            typing: None,
+            presence: PresenceMode::Unavailable,
+            read_receipts: false,
+        }
+    }
+}
+
+impl TrafficProfile {
+    pub fn settings(&self) -> TrafficSettings {
+        match self {
+            TrafficProfile::Conservative => TrafficSettings {
+                min_send_interval: Duration::from_secs(3),
+                per_chat_interval: Duration::from_secs(10),
+                typing: Some(TypingSimulation {
+                    chars_per_second: 8,
+                    min: Duration::from_secs(1),
+                    max: Duration::from_secs(8),
+                }),
+                presence: PresenceMode::OnlineWhileSending,
+                read_receipts: true,
+            },
+            TrafficProfile::Balanced => TrafficSettings {
+                min_send_interval: Duration::from_millis(1000),
+                per_chat_interval: Duration::from_secs(3),
+                typing: Some(TypingSimulation {
+                    chars_per_second: 15,
+                    min: Duration::from_millis(500),
+                    max: Duration::from_secs(4),
+                }),
+                presence: PresenceMode::OnlineWhileSending,
+                read_receipts: true,
+            },
+            TrafficProfile::Aggressive => TrafficSettings {
+                min_send_interval: Duration::from_millis(200),
+                per_chat_interval: Duration::from_millis(500),
+                typing: None,
+                presence: PresenceMode::AlwaysOnline,
+                read_receipts: false,
+            },
+        }
+    }
+}
+
+/// Menjadwalkan pengiriman sesuai `TrafficSettings`
+#[derive(Default)]
+pub struct TrafficShaper {
+    pub settings: TrafficSettings,
+    next_global: Option<Instant>,
+    next_per_chat: HashMap<String, Instant>,
+    /// Jeda per chat yang menggantikan `settings.per_chat_interval`
+    chat_intervals: HashMap<String, Duration>,
+}
+
+impl TrafficShaper {
+    pub fn new(settings: TrafficSettings) -> Self {
+        TrafficShaper {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    /// Mengatur jeda khusus untuk satu chat; `None` kembali ke pengaturan umum
+    pub fn set_chat_interval(&mut self, chat: &str, interval: Option<Duration>) {
+        match interval {
+            Some(interval) => self.chat_intervals.insert(chat.to_string(), interval),
+            None => self.chat_intervals.remove(chat),
+        };
+    }
+
+    /// Memesan slot pengiriman ke `chat` dan mengembalikan lama menunggu.
+    /// Slot langsung dipesan sehingga pemanggil paralel tidak berebut slot yang sama.
+    pub fn reserve(&mut self, chat: &str, now: Instant) -> Duration {
+        let mut at = now;
+        if let Some(next) = self.next_global {
+            at = at.max(next);
+        }
+        if let Some(next) = self.next_per_chat.get(chat) {
+            at = at.max(*next);
+        }
+
+        self.next_global = Some(at + self.settings.min_send_interval);
+        let per_chat = self.chat_intervals.get(chat).copied().unwrap_or(self.settings.per_chat_interval);
+        self.next_per_chat.insert(chat.to_string(), at + per_chat);
+        // Slot per chat yang sudah lewat tidak lagi berpengaruh
+        self.next_per_chat.retain(|_, next| *next > now);
+
+        at.saturating_duration_since(now)
+    }
+
+    /// Lama menunggu sampai slot berikutnya ke `chat` tanpa memesannya
+    pub fn wait_for(&self, chat: &str, now: Instant) -> Duration {
+        let at = [self.next_global, self.next_per_chat.get(chat).copied()].into_iter().flatten().fold(now, Instant::max);
+        at - now
+    }
+
+    /// Seperti `reserve`, tetapi slot hanya dipesan jika bisa dipakai sekarang;
+    /// jika tidak, mengembalikan lama menunggu tanpa memesan apa pun
+    pub fn try_reserve(&mut self, chat: &str, now: Instant) -> std::result::Result<(), Duration> {
+        let wait = self.wait_for(chat, now);
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+        self.reserve(chat, now);
+        Ok(())
+    }
+
+    /// Lama simulasi mengetik untuk teks, jika diaktifkan
+    pub fn typing_for(&self, text: &str) -> Option<Duration> {
+        self.settings.typing.map(|typing| typing.duration_for(text.chars().count()))
+    }
+}
+
+/// Handler `message` yang mengirim read receipt jika diaktifkan
+pub fn read_receipt_handler(traffic: Arc<Mutex<TrafficShaper>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        if !traffic.lock().unwrap().settings.read_receipts {
+            return Ok(());
+        }
+        // Pesan sendiri (dari perangkat lain akun ini) tidak perlu read receipt
+        let from_me = node.get_bytes().and_then(|bytes| WebMessageInfo::from_bytes(bytes).ok()).map(|message| message.key.from_me);
+        if from_me == Some(true) {
+            return Ok(());
+        }
+        let (id, from) = match (node.get_attr("id"), node.get_attr("from")) {
+            (Some(id), Some(from)) => (id, from),
+            _ => return Ok(()),
+        };
+
+        let receipt = receipts::receipt_node(from, node.get_attr("participant"), &[id.to_string()], ReceiptType::Read)?;
+        ctx.send_node(&receipt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_spaces_sends() {
+        let mut shaper = TrafficShaper::new(TrafficSettings {
+            min_send_interval: Duration::from_secs(1),
+            per_chat_interval: Duration::from_secs(5),
+            ..Default::default()
+        });
+        let now = Instant::now();
+
+        assert_eq!(shaper.reserve("a", now), Duration::from_secs(0));
+        assert_eq!(shaper.reserve("b", now), Duration::from_secs(1));
+        // Chat yang sama menunggu jeda per chat
+        assert_eq!(shaper.reserve("a", now), Duration::from_secs(5));
+
+        shaper.set_chat_interval("c", Some(Duration::from_secs(20)));
+        assert_eq!(shaper.reserve("c", now), Duration::from_secs(6));
+        assert_eq!(shaper.reserve("c", now), Duration::from_secs(26));
+
+        // `wait_for` dan `try_reserve` tidak memesan slot yang harus ditunggu
+        assert_eq!(shaper.wait_for("d", now), Duration::from_secs(27));
+        assert_eq!(shaper.try_reserve("d", now), Err(Duration::from_secs(27)));
+        assert_eq!(shaper.try_reserve("d", now + Duration::from_secs(27)), Ok(()));
+    }
+
+    #[test]
+    fn test_typing_is_clamped() {
+        let typing = TrafficProfile::Balanced.settings().typing.unwrap();
+        assert_eq!(typing.duration_for(1), typing.min);
+        assert_eq!(typing.duration_for(10_000), typing.max);
+    }
+}
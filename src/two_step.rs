@@ -0,0 +1,124 @@
+//! Status verifikasi dua langkah (PIN)
+//!
+//! Akun dengan verifikasi dua langkah sesekali diminta memasukkan PIN lagi di
+//! ponsel. Selama PIN belum dimasukkan, pairing dan login bisa tertahan tanpa
+//! error apa pun. Status PIN dibaca dari `Conn` (field `2fa`), balasan
+//! `Account::has_two_step_pin` dan notifikasi `account_sync`; permintaan
+//! verifikasi ulang dari server dikirim sebagai
+//! `Event::TwoStepVerificationRequired`.
+
+use std::sync::{Arc, Mutex};
+
+use crate::errors::*;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{Event, WhatsAppClient};
+
+/// Status PIN yang terakhir diketahui
+#[derive(Default)]
+pub struct TwoStepMonitor {
+    enabled: Option<bool>,
+    verification_required: bool,
+}
+
+impl TwoStepMonitor {
+    pub fn new() -> Self {
+        TwoStepMonitor::default()
+    }
+
+    /// `None` jika server belum memberi tahu
+    pub fn enabled(&self) -> Option<bool> {
+        self.enabled
+    }
+
+    pub fn verification_required(&self) -> bool {
+        self.verification_required
+    }
+
+    /// Memperbarui status; mengembalikan event jika status berubah
+    pub fn set_enabled(&mut self, enabled: bool) -> Option<Event> {
+        if self.enabled == Some(enabled) {
+            return None;
+        }
+        self.enabled = Some(enabled);
+        Some(Event::TwoStepStatusChanged { enabled })
+    }
+
+    /// Server meminta PIN; event hanya dikirim sekali sampai login berhasil
+    pub fn require_verification(&mut self) -> Option<Event> {
+        self.enabled = Some(true);
+        if self.verification_required {
+            return None;
+        }
+        self.verification_required = true;
+        Some(Event::TwoStepVerificationRequired)
+    }
+
+    /// Login berhasil berarti PIN sudah diverifikasi
+    pub fn on_authenticated(&mut self) {
+        self.verification_required = false;
+    }
+}
+
+/// Membaca `<2fa>`: PIN aktif jika ada `<code/>` atau `enabled="true"`
+pub fn parse_two_fa(node: &Node) -> Option<bool> {
+    let two_fa = node.get_child("2fa")?;
+    Some(two_fa.get_child("code").is_some() || two_fa.get_attr("enabled") == Some("true"))
+}
+
+/// `<2fa><verify/></2fa>` berarti server meminta PIN dimasukkan ulang
+pub fn requires_verification(node: &Node) -> bool {
+    node.get_child("2fa").map_or(false, |two_fa| two_fa.get_child("verify").is_some())
+}
+
+/// Handler `notification type="account_sync"`
+pub fn notification_handler(two_step: Arc<Mutex<TwoStepMonitor>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, ctx: &NodeContext| {
+        let mut two_step = two_step.lock().unwrap();
+        let event = if requires_verification(node) {
+            two_step.require_verification()
+        } else {
+            parse_two_fa(node).and_then(|enabled| two_step.set_enabled(enabled))
+        };
+        if let Some(event) = event {
+            ctx.emit(event);
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Apakah verifikasi dua langkah aktif; `None` jika belum diketahui
+    /// (lihat `Account::has_two_step_pin` untuk menanyakan server)
+    pub fn two_step_enabled(&self) -> Option<bool> {
+        self.two_step.lock().unwrap().enabled()
+    }
+
+    /// Apakah server sedang menunggu PIN dimasukkan ulang di ponsel
+    pub fn two_step_verification_required(&self) -> bool {
+        self.two_step.lock().unwrap().verification_required()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_request_reported_once() {
+        let mut two_step = TwoStepMonitor::new();
+        let notification = Node::new("notification")
+            .attr("type", "account_sync")
+            .children(vec![Node::new("2fa").children(vec![Node::new("verify")])]);
+        assert!(requires_verification(&notification));
+
+        assert!(matches!(two_step.require_verification(), Some(Event::TwoStepVerificationRequired)));
+        assert!(two_step.require_verification().is_none());
+        assert_eq!(two_step.enabled(), Some(true));
+
+        two_step.on_authenticated();
+        assert!(!two_step.verification_required());
+        assert!(two_step.set_enabled(true).is_none());
+        assert!(matches!(two_step.set_enabled(false), Some(Event::TwoStepStatusChanged { enabled: false })));
+    }
+}
@@ -0,0 +1,145 @@
+//! Query USync: cek nomor terdaftar di WhatsApp
+//!
+//! Satu IQ `usync` memeriksa banyak nomor sekaligus. Untuk nomor yang
+//! terdaftar server mengembalikan JID kanonik (bisa berbeda dari nomor yang
+//! ditanyakan, mis. tanpa angka 0 awal), LID-nya dan apakah akun bisnis.
+//! Kirim pesan hanya ke JID hasil query agar tidak memantul.
+
+use crate::errors::*;
+use crate::iq;
+use crate::node_protocol::Node;
+use crate::{utils, Jid, WhatsAppClient};
+
+/// Jumlah nomor maksimal per IQ
+pub const MAX_USYNC_USERS: usize = 500;
+
+/// Hasil cek satu nomor
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnWhatsAppResult {
+    /// Nomor seperti ditanyakan (hanya digit)
+    pub phone: String,
+    pub exists: bool,
+    /// JID kanonik; `None` jika tidak terdaftar
+    pub jid: Option<Jid>,
+    pub lid: Option<Jid>,
+    pub is_business: bool,
+}
+
+/// Menyisakan digit nomor (`+62 812-3456` → `628123456`)
+pub fn normalize_phone(phone: &str) -> Option<String> {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 5 || digits.len() > 20 {
+        return None;
+    }
+    Some(digits)
+}
+
+/// IQ usync untuk nomor (sudah dinormalisasi)
+pub fn contact_query_node(phones: &[String]) -> Node {
+    let users = phones
+        .iter()
+        .map(|phone| Node::new("user").children(vec![Node::new("contact").bytes(format!("+{}", phone).into_bytes())]))
+        .collect();
+    Node::new("iq")
+        .attr("id", &utils::generate_message_id())
+        .attr("xmlns", "usync")
+        .attr("type", "get")
+        .attr("to", "s.whatsapp.net")
+        .children(vec![Node::new("usync")
+            .attr("sid", &utils::generate_message_id())
+            .attr("mode", "query")
+            .attr("last", "true")
+            .attr("index", "0")
+            .attr("context", "interactive")
+            .children(vec![
+                Node::new("query").children(vec![
+                    Node::new("contact"),
+                    Node::new("lid"),
+                    Node::new("business").children(vec![Node::new("verified_name")]),
+                ]),
+                Node::new("list").children(users),
+            ])])
+}
+
+/// Membaca `<usync><list><user jid><contact type>+nomor</contact><lid val/><business/></user>`.
+/// Nomor yang tidak ada di balasan dianggap tidak terdaftar.
+pub fn parse_contact_response(response: &Node, phones: &[String]) -> Vec<OnWhatsAppResult> {
+    let users: Vec<&Node> = response
+        .get_child("usync")
+        .and_then(|usync| usync.get_child("list"))
+        .map(|list| list.get_children().iter().filter(|child| child.tag == "user").collect())
+        .unwrap_or_default();
+
+    phones
+        .iter()
+        .map(|phone| {
+            let user = users.iter().find(|user| {
+                user.get_child("contact")
+                    .and_then(|contact| contact.get_bytes())
+                    .and_then(|bytes| normalize_phone(&String::from_utf8_lossy(bytes)))
+                    .as_deref()
+                    == Some(phone.as_str())
+            });
+            let exists = user
+                .and_then(|user| user.get_child("contact"))
+                .map_or(false, |contact| contact.get_attr("type") == Some("in"));
+            let user = user.filter(|_| exists);
+            OnWhatsAppResult {
+                phone: phone.clone(),
+                exists,
+                jid: user.and_then(|user| Jid::from_string(user.get_attr("jid")?).ok()),
+                lid: user
+                    .and_then(|user| user.get_child("lid"))
+                    .and_then(|lid| Jid::from_string(lid.get_attr("val")?).ok()),
+                is_business: user.map_or(false, |user| user.get_child("business").is_some_and(|business| business.get_child("error").is_none())),
+            }
+        })
+        .collect()
+}
+
+impl WhatsAppClient {
+    /// Mengecek apakah nomor terdaftar di WhatsApp. Hasil mengikuti urutan
+    /// `phones`; nomor dengan format tidak valid menghasilkan error.
+    pub fn is_on_whatsapp(&self, phones: &[&str]) -> Result<Vec<OnWhatsAppResult>> {
+        let normalized = phones
+            .iter()
+            .map(|phone| normalize_phone(phone).ok_or_else(|| Error::from(format!("Invalid phone number: {}", phone))))
+            .collect::<Result<Vec<String>>>()?;
+
+        let mut results = Vec::with_capacity(normalized.len());
+        for batch in normalized.chunks(MAX_USYNC_USERS) {
+            let response = self.query(&contact_query_node(batch), iq::DEFAULT_QUERY_TIMEOUT)?;
+            results.extend(parse_contact_response(&response, batch));
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_contact_response() {
+        let phones = vec![normalize_phone("+62 812-3456-789").unwrap(), "628999".to_string()];
+        assert_eq!(phones[0], "628123456789");
+
+        let response = Node::new("iq").attr("type", "result").children(vec![Node::new("usync").children(vec![Node::new("list").children(vec![
+            Node::new("user").attr("jid", "628123456789@s.whatsapp.net").children(vec![
+                Node::new("contact").attr("type", "in").bytes(b"+628123456789".to_vec()),
+                Node::new("lid").attr("val", "123456@lid"),
+                Node::new("business").children(vec![Node::new("verified_name")]),
+            ]),
+            Node::new("user").children(vec![Node::new("contact").attr("type", "out").bytes(b"+628999".to_vec())]),
+        ])])]);
+
+        let results = parse_contact_response(&response, &phones);
+        assert!(results[0].exists);
+        assert_eq!(results[0].jid.as_ref().unwrap().to_string(), "628123456789@s.whatsapp.net");
+        assert!(results[0].lid.as_ref().unwrap().is_lid());
+        assert!(results[0].is_business);
+        assert!(!results[1].exists);
+        assert_eq!(results[1].jid, None);
+        assert_eq!(normalize_phone("12"), None);
+    }
+}
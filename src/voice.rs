@@ -0,0 +1,180 @@
+//! Voice note (PTT) dari file Ogg Opus
+//!
+//! Durasi dihitung dari granule position halaman Ogg terakhir dikurangi
+//! pre-skip di `OpusHead`. Waveform yang ditampilkan client baru berisi
+//! `WAVEFORM_SAMPLES` nilai 0..=100; karena crate ini tidak mendekode Opus,
+//! ukuran tiap paket dipakai sebagai perkiraan kerasnya suara (bitrate VBR
+//! Opus naik mengikuti energi sinyal).
+
+use crate::errors::*;
+use crate::{media_validation, messages, Jid, MediaType, WhatsAppClient};
+
+/// Sample rate granule position Opus
+const OPUS_GRANULE_RATE: u64 = 48_000;
+
+/// Jumlah nilai waveform
+pub const WAVEFORM_SAMPLES: usize = 64;
+
+/// Ringkasan isi file Ogg Opus
+#[derive(Debug, Clone, PartialEq)]
+pub struct OggOpusInfo {
+    pub seconds: u32,
+    /// Ukuran tiap paket audio (tanpa paket header)
+    pub packet_sizes: Vec<usize>,
+}
+
+fn invalid(msg: &str) -> Error {
+    Error { kind: ErrorKind::InvalidFormat(msg.to_string()) }
+}
+
+/// Membaca halaman-halaman Ogg dan mengumpulkan paket serta granule terakhir
+pub fn parse_ogg_opus(bytes: &[u8]) -> Result<OggOpusInfo> {
+    let mut offset = 0;
+    let mut packets: Vec<Vec<u8>> = Vec::new();
+    let mut current: Vec<u8> = Vec::new();
+    let mut last_granule = 0u64;
+
+    while offset < bytes.len() {
+        if bytes.len() < offset + 27 || &bytes[offset..offset + 4] != b"OggS" {
+            return Err(invalid("Invalid Ogg page"));
+        }
+        let mut granule = [0u8; 8];
+        granule.copy_from_slice(&bytes[offset + 6..offset + 14]);
+        let granule = u64::from_le_bytes(granule);
+        if granule != u64::MAX {
+            last_granule = granule;
+        }
+
+        let segments = bytes[offset + 26] as usize;
+        let lacing_start = offset + 27;
+        let mut data = lacing_start + segments;
+        if bytes.len() < data {
+            return Err(invalid("Truncated Ogg page"));
+        }
+        for &lace in &bytes[lacing_start..lacing_start + segments] {
+            let end = data + lace as usize;
+            if bytes.len() < end {
+                return Err(invalid("Truncated Ogg page"));
+            }
+            current.extend_from_slice(&bytes[data..end]);
+            data = end;
+            if lace < 255 {
+                packets.push(std::mem::take(&mut current));
+            }
+        }
+        offset = data;
+    }
+
+    let head = packets.first().ok_or_else(|| invalid("Empty Ogg stream"))?;
+    if head.len() < 19 || &head[..8] != b"OpusHead" {
+        return Err(invalid("Not an Opus stream"));
+    }
+    let pre_skip = u16::from_le_bytes([head[10], head[11]]) as u64;
+    let samples = last_granule.saturating_sub(pre_skip);
+
+    Ok(OggOpusInfo {
+        seconds: samples.div_ceil(OPUS_GRANULE_RATE) as u32,
+        // Paket pertama OpusHead, kedua OpusTags
+        packet_sizes: packets.iter().skip(2).map(|packet| packet.len()).collect(),
+    })
+}
+
+/// Waveform `WAVEFORM_SAMPLES` nilai 0..=100 dari ukuran paket
+pub fn waveform(packet_sizes: &[usize]) -> Vec<u8> {
+    if packet_sizes.is_empty() {
+        return vec![0; WAVEFORM_SAMPLES];
+    }
+    let buckets: Vec<f64> = (0..WAVEFORM_SAMPLES)
+        .map(|i| {
+            let start = i * packet_sizes.len() / WAVEFORM_SAMPLES;
+            let end = ((i + 1) * packet_sizes.len() / WAVEFORM_SAMPLES).max(start + 1);
+            let chunk = &packet_sizes[start..end.min(packet_sizes.len())];
+            chunk.iter().sum::<usize>() as f64 / chunk.len() as f64
+        })
+        .collect();
+
+    let max = buckets.iter().cloned().fold(0.0, f64::max);
+    if max == 0.0 {
+        return vec![0; WAVEFORM_SAMPLES];
+    }
+    buckets.iter().map(|value| (value / max * 100.0).round() as u8).collect()
+}
+
+impl WhatsAppClient {
+    /// Mengirim voice note (PTT) dari file Ogg Opus.
+    /// Membutuhkan `MediaUploader` (lihat `set_media_uploader`).
+    pub fn send_voice_note(&self, to: &Jid, ogg_opus: &[u8]) -> Result<String> {
+        media_validation::validate_voice_note(ogg_opus)?;
+        let info = parse_ogg_opus(ogg_opus)?;
+        let (media, uploaded) = self.upload_media(ogg_opus, MediaType::Ptt)?;
+
+        let message = messages::Message {
+            audio_message: Some(messages::AudioMessage {
+                url: uploaded.url,
+                direct_path: uploaded.direct_path,
+                mimetype: media_validation::VOICE_NOTE_MIMETYPE.to_string(),
+                file_sha256: media.file_sha256,
+                file_enc_sha256: media.file_enc_sha256,
+                file_length: media.file_length,
+                media_key: media.media_key,
+                media_key_timestamp: chrono::Utc::now().timestamp(),
+                seconds: info.seconds,
+                ptt: true,
+                waveform: Some(waveform(&info.packet_sizes)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        self.send_message(to, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(granule: u64, packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut lacing = Vec::new();
+        let mut body = Vec::new();
+        for packet in packets {
+            let mut len = packet.len();
+            while len >= 255 {
+                lacing.push(255);
+                len -= 255;
+            }
+            lacing.push(len as u8);
+            body.extend_from_slice(packet);
+        }
+        let mut page = b"OggS".to_vec();
+        page.extend_from_slice(&[0, 0]);
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&[0; 12]);
+        page.push(lacing.len() as u8);
+        page.extend_from_slice(&lacing);
+        page.extend_from_slice(&body);
+        page
+    }
+
+    #[test]
+    fn test_duration_and_packets() {
+        let mut head = b"OpusHead".to_vec();
+        head.extend_from_slice(&[1, 1, 0x38, 0x01, 0x80, 0xbb, 0, 0, 0, 0, 0]);
+        let mut stream = page(0, &[head]);
+        stream.extend(page(0, &[b"OpusTags".to_vec()]));
+        // 2.5 detik audio + pre-skip 312
+        stream.extend(page(120_000 + 312, &[vec![1; 300], vec![2; 20]]));
+
+        let info = parse_ogg_opus(&stream).unwrap();
+        assert_eq!(info.seconds, 3);
+        assert_eq!(info.packet_sizes, vec![300, 20]);
+    }
+
+    #[test]
+    fn test_waveform_is_normalized() {
+        let wave = waveform(&[10, 20, 40]);
+        assert_eq!(wave.len(), WAVEFORM_SAMPLES);
+        assert_eq!(*wave.iter().max().unwrap(), 100);
+        assert_eq!(wave[0], 25);
+        assert!(parse_ogg_opus(b"not ogg").is_err());
+    }
+}
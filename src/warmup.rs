@@ -0,0 +1,223 @@
+//! Pemanasan (warm-up) untuk akun yang baru dipasangkan
+//!
+//! Nomor baru yang langsung mengirim banyak pesan ke banyak orang asing
+//! mudah diblokir. Selama `WarmupSettings::days` hari pertama sejak pairing,
+//! pengiriman dibatasi: jumlah pesan per hari naik bertahap dan jumlah
+//! penerima baru (yang belum pernah berkirim pesan dengan akun ini) per hari
+//! dibatasi. Fitur ini opt-in lewat `WhatsAppClientBuilder::with_warmup`.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use crate::errors::*;
+use crate::messages::WebMessageInfo;
+use crate::node_protocol::Node;
+use crate::routing::NodeContext;
+use crate::{Event, WhatsAppClient};
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Batas pemanasan
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmupSettings {
+    /// Lama pemanasan sejak pairing
+    pub days: u32,
+    /// Batas pesan pada hari pertama
+    pub initial_daily_messages: u32,
+    /// Tambahan batas pesan per hari berikutnya
+    pub daily_increase: u32,
+    /// Batas penerima baru per hari
+    pub max_new_recipients_per_day: u32,
+}
+
+impl Default for WarmupSettings {
+    fn default() -> Self {
+        WarmupSettings {
+            days: 14,
+            initial_daily_messages: 20,
+            daily_increase: 15,
+            max_new_recipients_per_day: 10,
+        }
+    }
+}
+
+impl WarmupSettings {
+    /// Batas pesan pada hari ke-`day` (mulai 0)
+    pub fn daily_limit(&self, day: u32) -> u32 {
+        self.initial_daily_messages.saturating_add(self.daily_increase.saturating_mul(day))
+    }
+}
+
+/// Batas yang menolak pengiriman
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WarmupLimit {
+    DailyMessages { limit: u32 },
+    NewRecipients { limit: u32 },
+}
+
+/// Posisi pemanasan saat ini
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmupStatus {
+    /// Hari ke- sejak pairing (mulai 0)
+    pub day: u32,
+    pub daily_limit: u32,
+    pub sent_today: u32,
+    pub new_recipients_today: u32,
+}
+
+/// Penghitung pemanasan per hari
+#[derive(Default)]
+pub struct WarmupScheduler {
+    settings: Option<WarmupSettings>,
+    day: u32,
+    sent_today: u32,
+    new_today: HashSet<String>,
+    /// Penerima yang sudah pernah berkirim pesan dengan akun ini
+    known: HashSet<String>,
+    completed: bool,
+}
+
+impl WarmupScheduler {
+    pub fn new() -> Self {
+        WarmupScheduler::default()
+    }
+
+    pub fn set_settings(&mut self, settings: Option<WarmupSettings>) {
+        self.settings = settings;
+    }
+
+    /// Mencatat kontak yang mengirim pesan; membalasnya tidak dihitung sebagai penerima baru
+    pub fn mark_known(&mut self, jid: &str) {
+        self.known.insert(jid.to_string());
+    }
+
+    /// Pindah ke hari baru jika perlu; `true` jika pemanasan baru saja selesai
+    fn advance(&mut self, paired_at: i64, now: i64) -> bool {
+        let day = ((now - paired_at).max(0) / SECS_PER_DAY) as u32;
+        if day != self.day {
+            self.day = day;
+            self.sent_today = 0;
+            self.new_today.clear();
+        }
+        let finished = self.settings.as_ref().is_some_and(|settings| day >= settings.days);
+        let just_completed = finished && !self.completed;
+        self.completed = finished;
+        just_completed
+    }
+
+    /// Memeriksa dan mencatat pengiriman ke `recipients`. Semua penerima
+    /// ditolak sekaligus jika salah satu batas terlampaui.
+    pub fn admit(&mut self, recipients: &[String], paired_at: i64, now: i64) -> (std::result::Result<(), WarmupLimit>, Option<Event>) {
+        let completed_event = if self.advance(paired_at, now) { Some(Event::WarmupCompleted) } else { None };
+        let settings = match self.settings {
+            Some(ref settings) if !self.completed => settings.clone(),
+            _ => return (Ok(()), completed_event),
+        };
+
+        let limit = settings.daily_limit(self.day);
+        if self.sent_today + recipients.len() as u32 > limit {
+            return (Err(WarmupLimit::DailyMessages { limit }), completed_event);
+        }
+        let new: HashSet<&String> = recipients.iter().filter(|jid| !self.known.contains(*jid) && !self.new_today.contains(*jid)).collect();
+        if self.new_today.len() + new.len() > settings.max_new_recipients_per_day as usize {
+            return (Err(WarmupLimit::NewRecipients { limit: settings.max_new_recipients_per_day }), completed_event);
+        }
+
+        self.sent_today += recipients.len() as u32;
+        for jid in new {
+            self.new_today.insert(jid.clone());
+        }
+        (Ok(()), completed_event)
+    }
+
+    pub fn status(&self) -> Option<WarmupStatus> {
+        let settings = self.settings.as_ref().filter(|_| !self.completed)?;
+        Some(WarmupStatus {
+            day: self.day,
+            daily_limit: settings.daily_limit(self.day),
+            sent_today: self.sent_today,
+            new_recipients_today: self.new_today.len() as u32,
+        })
+    }
+}
+
+/// Handler `message` yang mencatat pengirim pesan masuk sebagai kontak dikenal
+pub fn inbound_handler(warmup: Arc<Mutex<WarmupScheduler>>) -> impl Fn(&Node, &NodeContext) -> Result<()> + Send + 'static {
+    move |node: &Node, _ctx: &NodeContext| {
+        if let Some(web_message) = node.get_bytes().and_then(|bytes| WebMessageInfo::from_bytes(bytes).ok()) {
+            if !web_message.key.from_me {
+                warmup.lock().unwrap().mark_known(&web_message.key.remote_jid);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WhatsAppClient {
+    /// Posisi pemanasan; `None` jika tidak aktif atau sudah selesai
+    pub fn warmup_status(&self) -> Option<WarmupStatus> {
+        self.warmup.lock().unwrap().status()
+    }
+
+    /// Memeriksa batas pemanasan sebelum mengirim ke `recipients`
+    pub(crate) fn admit_warmup(&self, recipients: &[String]) -> Result<()> {
+        let paired_at = match self.session.lock().unwrap().as_ref().and_then(|session| session.paired_at) {
+            Some(paired_at) => paired_at,
+            None => return Ok(()),
+        };
+        let now = chrono::Utc::now().timestamp();
+        let (admitted, event) = self.warmup.lock().unwrap().admit(recipients, paired_at, now);
+        if let Some(event) = event {
+            self.event_tx.send(event).ok();
+        }
+        admitted.map_err(|limit| {
+            self.event_tx.send(Event::WarmupLimitReached(limit)).ok();
+            Error::from(format!("Warm-up limit reached: {:?}", limit))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler() -> WarmupScheduler {
+        let mut warmup = WarmupScheduler::new();
+        warmup.set_settings(Some(WarmupSettings {
+            days: 3,
+            initial_daily_messages: 3,
+            daily_increase: 2,
+            max_new_recipients_per_day: 2,
+        }));
+        warmup
+    }
+
+    #[test]
+    fn test_new_recipients_capped_but_known_contacts_allowed() {
+        let mut warmup = scheduler();
+        warmup.mark_known("3@s.whatsapp.net");
+        let admit = |warmup: &mut WarmupScheduler, jid: &str| warmup.admit(&[jid.to_string()], 0, 10).0;
+
+        assert_eq!(admit(&mut warmup, "1@s.whatsapp.net"), Ok(()));
+        assert_eq!(admit(&mut warmup, "2@s.whatsapp.net"), Ok(()));
+        assert_eq!(admit(&mut warmup, "4@s.whatsapp.net"), Err(WarmupLimit::NewRecipients { limit: 2 }));
+        assert_eq!(admit(&mut warmup, "3@s.whatsapp.net"), Ok(()));
+        assert_eq!(admit(&mut warmup, "1@s.whatsapp.net"), Err(WarmupLimit::DailyMessages { limit: 3 }));
+    }
+
+    #[test]
+    fn test_limit_ramps_up_then_completes() {
+        let mut warmup = scheduler();
+        let day = |n: i64| n * SECS_PER_DAY;
+        let batch: Vec<String> = (0..5).map(|_| "1@s.whatsapp.net".to_string()).collect();
+
+        assert!(warmup.admit(&batch, 0, day(0)).0.is_err());
+        assert_eq!(warmup.admit(&batch, 0, day(1)).0, Ok(()));
+        assert_eq!(warmup.status().unwrap().daily_limit, 5);
+
+        let (admitted, event) = warmup.admit(&batch, 0, day(3));
+        assert_eq!(admitted, Ok(()));
+        assert!(matches!(event, Some(Event::WarmupCompleted)));
+        assert!(warmup.status().is_none());
+    }
+}
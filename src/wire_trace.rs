@@ -0,0 +1,147 @@
+//! Mode debug protokol: dump setiap node masuk dan keluar
+//!
+//! Jika diaktifkan (`WhatsAppClientBuilder::with_wire_trace`), setiap node yang
+//! diterima (setelah didekode) dan dikirim ke server dicatat lewat `log`
+//! dengan target `rustdi::wire` pada level debug, dalam bentuk mirip XML.
+//! Untuk kebutuhan lanjutan, `with_raw_node_hook` memanggil callback aplikasi
+//! untuk setiap node.
+//!
+//! Berbeda dengan capture di bundle diagnostik, dump ini tidak menyamarkan
+//! nomor telepon dan menampilkan isi bytes; jangan aktifkan di produksi.
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+use crate::node_protocol::{Node, NodeContent};
+
+/// Target `log` untuk dump node
+pub const WIRE_LOG_TARGET: &str = "rustdi::wire";
+
+/// Jumlah byte yang ditampilkan untuk konten biner
+const MAX_RENDERED_BYTES: usize = 64;
+
+/// Arah node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+/// Callback untuk setiap node masuk dan keluar
+pub type RawNodeHook = Arc<dyn Fn(Direction, &Node) + Send + Sync>;
+
+/// Pengaturan dump node
+#[derive(Default, Clone)]
+pub struct WireTrace {
+    enabled: bool,
+    hook: Option<RawNodeHook>,
+}
+
+impl WireTrace {
+    pub fn new() -> Self {
+        WireTrace::default()
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn set_hook(&mut self, hook: Option<RawNodeHook>) {
+        self.hook = hook;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.enabled || self.hook.is_some()
+    }
+
+    pub fn observe(&self, direction: Direction, node: &Node) {
+        if self.enabled {
+            let arrow = match direction {
+                Direction::Inbound => "<<",
+                Direction::Outbound => ">>",
+            };
+            log::debug!(target: WIRE_LOG_TARGET, "{}\n{}", arrow, render(node));
+        }
+        if let Some(ref hook) = self.hook {
+            hook(direction, node);
+        }
+    }
+}
+
+/// Menampilkan node dalam bentuk mirip XML dengan indentasi dua spasi.
+/// Atribut diurutkan; konten biner ditampilkan sebagai teks jika UTF-8 yang
+/// bisa dicetak, selain itu sebagai hex (dipotong).
+pub fn render(node: &Node) -> String {
+    let mut out = String::new();
+    render_into(&mut out, node, 0);
+    out
+}
+
+fn render_into(out: &mut String, node: &Node, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let mut attrs: Vec<_> = node.attrs.iter().collect();
+    attrs.sort();
+    write!(out, "{}<{}", indent, node.tag).ok();
+    for (key, value) in attrs {
+        write!(out, " {}=\"{}\"", key, value).ok();
+    }
+    match node.content {
+        None => out.push_str("/>\n"),
+        Some(NodeContent::List(ref children)) if children.is_empty() => out.push_str("/>\n"),
+        Some(NodeContent::List(ref children)) => {
+            out.push_str(">\n");
+            for child in children {
+                render_into(out, child, depth + 1);
+            }
+            writeln!(out, "{}</{}>", indent, node.tag).ok();
+        }
+        Some(NodeContent::Text(ref text)) => {
+            writeln!(out, ">{}</{}>", text, node.tag).ok();
+        }
+        Some(NodeContent::Binary(ref data)) => {
+            writeln!(out, ">{}</{}>", render_bytes(data), node.tag).ok();
+        }
+    }
+}
+
+fn render_bytes(data: &[u8]) -> String {
+    if let Ok(text) = std::str::from_utf8(data) {
+        if !text.chars().any(|c| c.is_control() && c != '\n') {
+            return text.to_string();
+        }
+    }
+    let mut hex: String = data.iter().take(MAX_RENDERED_BYTES).map(|b| format!("{:02x}", b)).collect();
+    if data.len() > MAX_RENDERED_BYTES {
+        write!(hex, "... ({} bytes)", data.len()).ok();
+    }
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_render_and_hook() {
+        let node = Node::new("iq")
+            .attr("type", "get")
+            .attr("id", "1")
+            .children(vec![Node::new("ping"), Node::new("enc").bytes(vec![0, 1, 255]), Node::new("body").bytes(b"halo".to_vec())]);
+        assert_eq!(
+            render(&node),
+            "<iq id=\"1\" type=\"get\">\n  <ping/>\n  <enc>0001ff</enc>\n  <body>halo</body>\n</iq>\n"
+        );
+        assert!(render_bytes(&[0u8; 100]).ends_with("... (100 bytes)"));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut trace = WireTrace::new();
+        assert!(!trace.is_active());
+        let seen_clone = Arc::clone(&seen);
+        trace.set_hook(Some(Arc::new(move |direction, node: &Node| {
+            seen_clone.lock().unwrap().push((direction, node.tag.clone()));
+        })));
+        trace.observe(Direction::Outbound, &node);
+        assert_eq!(*seen.lock().unwrap(), vec![(Direction::Outbound, "iq".to_string())]);
+    }
+}
@@ -0,0 +1,144 @@
+//! Test end-to-end dengan dua (atau lebih) client di dalam satu proses
+//!
+//! Pesan antar client dienkripsi Signal dengan prekey bundle yang diunggah
+//! setiap client ke mock server; mock server hanya meneruskan ciphertext
+//! (lihat dokumentasi `rustdi::testing`).
+//!
+//! Jalankan dengan: `cargo test --features testing --test e2e`
+
+#![cfg(feature = "testing")]
+
+use rustdi::testing::{connect_client, wait_for_event, wait_for_message, MockServer, DEFAULT_EVENT_TIMEOUT};
+use rustdi::{AuthMethod, Event, Jid, WhatsAppClientBuilder};
+use std::time::Duration;
+
+fn user(number: &str) -> Jid {
+    Jid::user(number)
+}
+
+#[test]
+fn test_pairing_emits_qr_and_authenticates() {
+    let server = MockServer::start().unwrap();
+    let alice = user("6281100000001");
+    server.expect_client(&alice);
+
+    let client = WhatsAppClientBuilder::new()
+        .with_event_handler(Box::new(rustdi::testing::NoopEventHandler))
+        .with_websocket_url(&server.url())
+        .build()
+        .unwrap();
+    client.connect(AuthMethod::QRCode { callback: Box::new(|_| {}) }).unwrap();
+
+    assert!(wait_for_event(&client, DEFAULT_EVENT_TIMEOUT, |e| matches!(e, Event::QrCodeGenerated(_))).is_some());
+    assert!(wait_for_event(&client, DEFAULT_EVENT_TIMEOUT, |e| matches!(e, Event::Authenticated)).is_some());
+    assert_eq!(client.get_own_jid(), Some(alice.clone()));
+    assert!(server.is_connected(&alice));
+}
+
+#[test]
+fn test_direct_message_roundtrip() {
+    let server = MockServer::start().unwrap();
+    let alice_jid = user("6281100000001");
+    let bob_jid = user("6281100000002");
+
+    let alice = connect_client(&server, &alice_jid).unwrap();
+    let bob = connect_client(&server, &bob_jid).unwrap();
+
+    alice.send_text_message(&bob_jid, "halo bob").unwrap();
+
+    let received = wait_for_message(&bob, DEFAULT_EVENT_TIMEOUT).expect("bob should receive the message");
+    assert_eq!(received.key.remote_jid, alice_jid.to_string());
+    assert!(!received.key.from_me);
+    assert_eq!(received.message.and_then(|m| m.conversation), Some("halo bob".to_string()));
+
+    // Pesan berikutnya memakai sesi yang sama dengan chain key yang sudah maju
+    alice.send_text_message(&bob_jid, "masih di sana?").unwrap();
+    let next = wait_for_message(&bob, DEFAULT_EVENT_TIMEOUT).expect("bob should receive the second message");
+    assert_eq!(next.message.and_then(|m| m.conversation), Some("masih di sana?".to_string()));
+
+    bob.send_text_message(&alice_jid, "halo juga").unwrap();
+    let reply = wait_for_message(&alice, DEFAULT_EVENT_TIMEOUT).expect("alice should receive the reply");
+    assert_eq!(reply.key.remote_jid, bob_jid.to_string());
+}
+
+#[test]
+fn test_group_message_fanout() {
+    let server = MockServer::start().unwrap();
+    let alice_jid = user("6281100000001");
+    let bob_jid = user("6281100000002");
+    let carol_jid = user("6281100000003");
+    let group_jid = Jid::group("120363000000000001");
+    server.add_group(&group_jid, &[alice_jid.clone(), bob_jid.clone(), carol_jid.clone()]);
+
+    let alice = connect_client(&server, &alice_jid).unwrap();
+    let bob = connect_client(&server, &bob_jid).unwrap();
+    let carol = connect_client(&server, &carol_jid).unwrap();
+
+    alice.send_text_message(&group_jid, "halo grup").unwrap();
+
+    for member in [&bob, &carol] {
+        let received = wait_for_message(member, DEFAULT_EVENT_TIMEOUT).expect("member should receive group message");
+        assert_eq!(received.key.remote_jid, group_jid.to_string());
+        assert_eq!(received.key.participant, Some(alice_jid.to_string()));
+    }
+
+    // Pengirim tidak menerima salinan pesannya sendiri
+    assert!(wait_for_message(&alice, Duration::from_millis(300)).is_none());
+}
+
+#[test]
+fn test_create_group_returns_metadata() {
+    let server = MockServer::start().unwrap();
+    let alice_jid = user("6281100000001");
+    let bob_jid = user("6281100000002");
+
+    let alice = connect_client(&server, &alice_jid).unwrap();
+    let bob = connect_client(&server, &bob_jid).unwrap();
+
+    let meta = alice.create_group("Tim", vec![bob_jid.clone()]).unwrap();
+    assert!(meta.jid.is_group());
+    assert_eq!(meta.subject, "Tim");
+    assert_eq!(meta.participants.len(), 2);
+    assert!(meta.participants.iter().any(|p| p.jid == alice_jid && p.is_super_admin));
+    assert!(wait_for_event(&alice, DEFAULT_EVENT_TIMEOUT, |e| matches!(e, Event::GroupIntroduce { newly_created: true, .. })).is_some());
+
+    // Grup baru langsung bisa dipakai
+    alice.send_text_message(&meta.jid, "selamat datang").unwrap();
+    let received = wait_for_message(&bob, DEFAULT_EVENT_TIMEOUT).expect("bob should receive group message");
+    assert_eq!(received.key.remote_jid, meta.jid.to_string());
+}
+
+#[test]
+fn test_participant_changes_report_status() {
+    let server = MockServer::start().unwrap();
+    let alice_jid = user("6281100000001");
+    let bob_jid = user("6281100000002");
+    let carol_jid = user("6281100000003");
+
+    let alice = connect_client(&server, &alice_jid).unwrap();
+    let meta = alice.create_group("Tim", vec![bob_jid.clone()]).unwrap();
+
+    let added = alice.add_participants(&meta.jid, &[bob_jid.clone(), carol_jid.clone()]).unwrap();
+    assert_eq!(added.iter().map(|s| s.code).collect::<Vec<_>>(), vec![409, 200]);
+    let changed = wait_for_event(&alice, DEFAULT_EVENT_TIMEOUT, |e| matches!(e, Event::GroupParticipantsChanged { .. }));
+    assert!(matches!(changed, Some(Event::GroupParticipantsChanged { participants, .. }) if participants == vec![carol_jid.clone()]));
+
+    let removed = alice.remove_participants(&meta.jid, &[carol_jid.clone()]).unwrap();
+    assert!(removed[0].is_success());
+    let removed_again = alice.remove_participants(&meta.jid, &[carol_jid]).unwrap();
+    assert_eq!(removed_again[0].code, 404);
+}
+
+#[test]
+fn test_loopback_peer_echoes_messages() {
+    let peer = user("6281100000009");
+    let transport = rustdi::loopback::LoopbackTransport::start(&peer).unwrap();
+    let alice = transport.connect(&user("6281100000001")).unwrap();
+
+    alice.send_text_message(&peer, "tes loopback").unwrap();
+
+    let echo = wait_for_message(&alice, DEFAULT_EVENT_TIMEOUT).expect("loopback peer should reply");
+    assert_eq!(echo.key.remote_jid, peer.to_string());
+    assert!(!echo.key.from_me);
+    assert_eq!(echo.message.and_then(|m| m.conversation), Some("tes loopback".to_string()));
+}